@@ -0,0 +1,212 @@
+import { createHash } from 'crypto';
+import nacl from 'tweetnacl';
+import bs58 from 'bs58';
+import { logger } from './utils/logger';
+
+/**
+ * Whale-alert evaluation and daily compliance digest generation
+ *
+ * Transfers are checked against two thresholds:
+ *  - a single transfer larger than `singleTransferThreshold`
+ *  - a wallet receiving more than `dailyAccumulationThreshold` within one UTC day
+ *
+ * At the end of each day the alerts are rolled into a digest, hashed, and signed
+ * with the compliance officer (MLRO) key. The hash is what gets anchored on-chain
+ * via the program's `post_digest` instruction.
+ */
+
+export interface WhaleAlertThresholds {
+    singleTransferThreshold: number;
+    dailyAccumulationThreshold: number;
+}
+
+export type WhaleAlertKind = 'large_transfer' | 'daily_accumulation';
+
+export interface WhaleAlert {
+    kind: WhaleAlertKind;
+    mint: string;
+    wallet: string;
+    amount: number;
+    threshold: number;
+    signature: string;
+    timestamp: number;
+}
+
+export interface TransferObservation {
+    mint: string;
+    from: string;
+    to: string;
+    amount: number;
+    signature: string;
+    timestamp: number;
+}
+
+export interface ComplianceDigest {
+    mint: string;
+    periodStart: number;
+    periodEnd: number;
+    thresholds: WhaleAlertThresholds;
+    alerts: WhaleAlert[];
+}
+
+export interface SignedComplianceDigest {
+    digest: ComplianceDigest;
+    /** Hex-encoded sha256 of the canonical digest JSON (the value passed to `post_digest`) */
+    hash: string;
+    /** Base58 ed25519 signature over the hash bytes, or null when no MLRO key is configured */
+    signature: string | null;
+    signer: string | null;
+}
+
+const SECONDS_PER_DAY = 86_400;
+
+export function dayStart(timestamp: number): number {
+    return Math.floor(timestamp / SECONDS_PER_DAY) * SECONDS_PER_DAY;
+}
+
+export function thresholdsFromEnv(): WhaleAlertThresholds {
+    return {
+        singleTransferThreshold: Number(process.env.WHALE_SINGLE_TRANSFER_THRESHOLD || 0),
+        dailyAccumulationThreshold: Number(process.env.WHALE_DAILY_ACCUMULATION_THRESHOLD || 0),
+    };
+}
+
+export class WhaleAlertMonitor {
+    private thresholds: WhaleAlertThresholds;
+    // key: `${mint}:${day}:${wallet}` -> amount received that day
+    private accumulation: Map<string, number>;
+    // key: `${mint}:${day}` -> alerts raised that day
+    private alertsByDay: Map<string, WhaleAlert[]>;
+
+    constructor(thresholds: WhaleAlertThresholds = thresholdsFromEnv()) {
+        this.thresholds = thresholds;
+        this.accumulation = new Map();
+        this.alertsByDay = new Map();
+    }
+
+    /**
+     * Evaluate a transfer and return any alerts it raises. A threshold of 0 disables that check.
+     * The accumulation alert fires once per wallet per day, when the threshold is first crossed.
+     */
+    evaluateTransfer(transfer: TransferObservation): WhaleAlert[] {
+        const alerts: WhaleAlert[] = [];
+        const day = dayStart(transfer.timestamp);
+        const { singleTransferThreshold, dailyAccumulationThreshold } = this.thresholds;
+
+        if (singleTransferThreshold > 0 && transfer.amount > singleTransferThreshold) {
+            alerts.push({
+                kind: 'large_transfer',
+                mint: transfer.mint,
+                wallet: transfer.from,
+                amount: transfer.amount,
+                threshold: singleTransferThreshold,
+                signature: transfer.signature,
+                timestamp: transfer.timestamp,
+            });
+        }
+
+        const accumulationKey = `${transfer.mint}:${day}:${transfer.to}`;
+        const previous = this.accumulation.get(accumulationKey) || 0;
+        const received = previous + transfer.amount;
+        this.accumulation.set(accumulationKey, received);
+
+        if (
+            dailyAccumulationThreshold > 0 &&
+            previous <= dailyAccumulationThreshold &&
+            received > dailyAccumulationThreshold
+        ) {
+            alerts.push({
+                kind: 'daily_accumulation',
+                mint: transfer.mint,
+                wallet: transfer.to,
+                amount: received,
+                threshold: dailyAccumulationThreshold,
+                signature: transfer.signature,
+                timestamp: transfer.timestamp,
+            });
+        }
+
+        if (alerts.length > 0) {
+            const dayKey = `${transfer.mint}:${day}`;
+            this.alertsByDay.set(dayKey, [...(this.alertsByDay.get(dayKey) || []), ...alerts]);
+            for (const alert of alerts) {
+                logger.warn('Whale alert raised', alert);
+            }
+        }
+
+        return alerts;
+    }
+
+    /**
+     * Build the signed digest for one mint and UTC day, then drop that day's working state
+     */
+    buildDailyDigest(mint: string, day: number, mlroSecretKey?: Uint8Array): SignedComplianceDigest {
+        const periodStart = dayStart(day);
+        const dayKey = `${mint}:${periodStart}`;
+        const digest: ComplianceDigest = {
+            mint,
+            periodStart,
+            periodEnd: periodStart + SECONDS_PER_DAY,
+            thresholds: this.thresholds,
+            alerts: this.alertsByDay.get(dayKey) || [],
+        };
+
+        const signed = signDigest(digest, mlroSecretKey);
+
+        this.alertsByDay.delete(dayKey);
+        for (const key of this.accumulation.keys()) {
+            if (key.startsWith(`${dayKey}:`)) {
+                this.accumulation.delete(key);
+            }
+        }
+
+        logger.info('Compliance digest generated', {
+            mint,
+            periodStart,
+            alerts: digest.alerts.length,
+            hash: signed.hash,
+        });
+
+        return signed;
+    }
+}
+
+/**
+ * Canonical JSON (sorted keys) so the same digest always hashes identically
+ */
+export function canonicalJson(value: unknown): string {
+    if (Array.isArray(value)) {
+        return `[${value.map(canonicalJson).join(',')}]`;
+    }
+    if (value && typeof value === 'object') {
+        const entries = Object.keys(value as Record<string, unknown>)
+            .sort()
+            .map((key) => `${JSON.stringify(key)}:${canonicalJson((value as Record<string, unknown>)[key])}`);
+        return `{${entries.join(',')}}`;
+    }
+    return JSON.stringify(value);
+}
+
+export function signDigest(digest: ComplianceDigest, mlroSecretKey?: Uint8Array): SignedComplianceDigest {
+    const hashBytes = createHash('sha256').update(canonicalJson(digest)).digest();
+    const secretKey = mlroSecretKey ?? mlroSecretKeyFromEnv();
+
+    if (!secretKey) {
+        return { digest, hash: hashBytes.toString('hex'), signature: null, signer: null };
+    }
+
+    const keypair = nacl.sign.keyPair.fromSecretKey(secretKey);
+    const signature = nacl.sign.detached(hashBytes, keypair.secretKey);
+
+    return {
+        digest,
+        hash: hashBytes.toString('hex'),
+        signature: bs58.encode(signature),
+        signer: bs58.encode(keypair.publicKey),
+    };
+}
+
+function mlroSecretKeyFromEnv(): Uint8Array | null {
+    const encoded = process.env.MLRO_SECRET_KEY;
+    return encoded ? bs58.decode(encoded) : null;
+}
@@ -0,0 +1,158 @@
+import { createHash } from 'crypto';
+import { PublicKey } from '@solana/web3.js';
+
+/**
+ * Event schema compatibility table for the Gated Token program
+ *
+ * Every event emitted by the program since schema version 1 starts with a
+ * `schema_version: u8` field. Legacy (version 0) events have no version byte.
+ * Version 2 follows it with a `sequence: u64` that counts up per mint across
+ * all event types, so a gap in the stored sequence means a missed event.
+ * During a rolling upgrade both layouts can appear on-chain at once, so the
+ * decoder tries each registered layout (newest first) and accepts the first
+ * one that consumes the event payload exactly.
+ */
+
+export type FieldType = 'pubkey' | 'string' | 'u8' | 'u16' | 'u64' | 'i64' | 'bool';
+
+export interface EventLayout {
+    version: number;
+    fields: Array<[name: string, type: FieldType]>;
+}
+
+export interface DecodedEvent {
+    name: string;
+    schemaVersion: number;
+    fields: Record<string, string | number | boolean>;
+}
+
+/** Latest schema version emitted by the program (mirrors `EVENT_SCHEMA_VERSION`) */
+export const CURRENT_SCHEMA_VERSION = 2;
+
+const V0_LAYOUTS: Record<string, EventLayout['fields']> = {
+    TokenInitializedEvent: [
+        ['authority', 'pubkey'],
+        ['mint', 'pubkey'],
+        ['symbol', 'string'],
+        ['name', 'string'],
+        ['decimals', 'u8'],
+    ],
+    WalletApprovedEvent: [
+        ['token_mint', 'pubkey'],
+        ['wallet', 'pubkey'],
+        ['approved_by', 'pubkey'],
+        ['timestamp', 'i64'],
+    ],
+    WalletRevokedEvent: [
+        ['token_mint', 'pubkey'],
+        ['wallet', 'pubkey'],
+        ['revoked_by', 'pubkey'],
+        ['timestamp', 'i64'],
+    ],
+    TokensMintedEvent: [
+        ['token_mint', 'pubkey'],
+        ['recipient', 'pubkey'],
+        ['amount', 'u64'],
+        ['new_supply', 'u64'],
+    ],
+    TokensTransferredEvent: [
+        ['token_mint', 'pubkey'],
+        ['from', 'pubkey'],
+        ['to', 'pubkey'],
+        ['amount', 'u64'],
+    ],
+};
+
+/**
+ * Compatibility table: event name -> layouts by schema version (newest first)
+ */
+export const EVENT_SCHEMAS: Record<string, EventLayout[]> = Object.fromEntries(
+    Object.entries(V0_LAYOUTS).map(([name, fields]) => [
+        name,
+        [
+            {
+                version: 2,
+                fields: [['schema_version', 'u8'] as [string, FieldType], ['sequence', 'u64'] as [string, FieldType], ...fields],
+            },
+            { version: 1, fields: [['schema_version', 'u8'] as [string, FieldType], ...fields] },
+            { version: 0, fields },
+        ],
+    ])
+);
+
+/** Anchor event discriminator: first 8 bytes of sha256("event:<Name>") */
+export function eventDiscriminator(name: string): string {
+    return createHash('sha256').update(`event:${name}`).digest().subarray(0, 8).toString('hex');
+}
+
+const NAMES_BY_DISCRIMINATOR: Record<string, string> = Object.fromEntries(
+    Object.keys(EVENT_SCHEMAS).map((name) => [eventDiscriminator(name), name])
+);
+
+function decodeLayout(layout: EventLayout, data: Buffer): DecodedEvent['fields'] | null {
+    const fields: DecodedEvent['fields'] = {};
+    let offset = 0;
+
+    for (const [name, type] of layout.fields) {
+        switch (type) {
+            case 'pubkey':
+                if (offset + 32 > data.length) return null;
+                fields[name] = new PublicKey(data.subarray(offset, offset + 32)).toString();
+                offset += 32;
+                break;
+            case 'string': {
+                if (offset + 4 > data.length) return null;
+                const len = data.readUInt32LE(offset);
+                if (offset + 4 + len > data.length) return null;
+                fields[name] = data.subarray(offset + 4, offset + 4 + len).toString('utf8');
+                offset += 4 + len;
+                break;
+            }
+            case 'u8':
+            case 'bool':
+                if (offset + 1 > data.length) return null;
+                fields[name] = type === 'bool' ? data.readUInt8(offset) === 1 : data.readUInt8(offset);
+                offset += 1;
+                break;
+            case 'u16':
+                if (offset + 2 > data.length) return null;
+                fields[name] = data.readUInt16LE(offset);
+                offset += 2;
+                break;
+            case 'u64':
+                if (offset + 8 > data.length) return null;
+                fields[name] = Number(data.readBigUInt64LE(offset));
+                offset += 8;
+                break;
+            case 'i64':
+                if (offset + 8 > data.length) return null;
+                fields[name] = Number(data.readBigInt64LE(offset));
+                offset += 8;
+                break;
+        }
+    }
+
+    // A layout only matches if it consumes the whole payload
+    if (offset !== data.length) return null;
+    if (layout.version > 0 && fields.schema_version !== layout.version) return null;
+    return fields;
+}
+
+/**
+ * Decode an event payload (discriminator already stripped) using the compatibility table.
+ * Returns null for unknown events or payloads that match no registered layout.
+ */
+export function decodeEvent(discriminator: Buffer, data: Buffer): DecodedEvent | null {
+    const name = NAMES_BY_DISCRIMINATOR[discriminator.toString('hex')];
+    if (!name) return null;
+
+    for (const layout of EVENT_SCHEMAS[name]) {
+        const fields = decodeLayout(layout, data);
+        if (fields) {
+            delete fields.schema_version;
+            return { name, schemaVersion: layout.version, fields };
+        }
+    }
+
+    return null;
+}
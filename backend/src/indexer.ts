@@ -15,6 +15,8 @@ import {
     TokensMintedEventData,
     TokensTransferredEventData,
 } from './types/indexer.types';
+import { decodeEvent } from './event-schemas';
+import { WhaleAlertMonitor } from './compliance-alerts';
 
 /**
  * Event Indexer for Solana Gated Token Program
@@ -30,6 +32,7 @@ export class EventIndexer extends EventEmitter {
     private maxReconnectAttempts: number;
     private reconnectDelay: number;
     private healthCheckInterval: NodeJS.Timeout | null;
+    public whaleAlerts: WhaleAlertMonitor;
 
     constructor(connection: Connection, programId: string) {
         super();
@@ -42,6 +45,7 @@ export class EventIndexer extends EventEmitter {
         this.maxReconnectAttempts = 10;
         this.reconnectDelay = 5000; // 5 seconds
         this.healthCheckInterval = null;
+        this.whaleAlerts = new WhaleAlertMonitor();
     }
 
     /**
@@ -296,16 +300,25 @@ export class EventIndexer extends EventEmitter {
 
     /**
      * Parse event data based on discriminator
-     * Note: This is a simplified parser. In production, use the IDL for proper parsing.
+     * Known events are decoded through the schema compatibility table so that
+     * payloads from old and new program versions are both understood.
      */
     private parseEvent(discriminator: Buffer, data: Buffer): ParsedEvent | null {
-        // Event discriminators (computed from event name)
-        // These are simplified - in production, use anchor.BorshEventCoder
         const discriminatorStr = discriminator.toString('hex');
 
         try {
-            // For now, we'll extract events by pattern matching in logs
-            // In production, implement proper Borsh deserialization using the IDL
+            const decoded = decodeEvent(discriminator, data);
+            if (decoded) {
+                return {
+                    discriminator: discriminatorStr,
+                    data: data,
+                    raw: false,
+                    name: decoded.name,
+                    schemaVersion: decoded.schemaVersion,
+                    fields: decoded.fields,
+                };
+            }
+
             return {
                 discriminator: discriminatorStr,
                 data: data,
@@ -326,8 +339,31 @@ export class EventIndexer extends EventEmitter {
         slot: number,
         blockTime: number | null
     ): Promise<void> {
-        // This is a placeholder - proper event parsing requires the program IDL
-        logger.debug('Event detected', { signature, slot, event });
+        logger.debug('Event detected', {
+            signature,
+            slot,
+            name: event.name,
+            schemaVersion: event.schemaVersion,
+        });
+
+        const fields = event.fields as any;
+        switch (event.name) {
+            case 'TokenInitializedEvent':
+                await this.processTokenInitializedEvent(fields, signature, slot, blockTime);
+                break;
+            case 'WalletApprovedEvent':
+                await this.processWalletApprovedEvent(fields, signature, slot, blockTime);
+                break;
+            case 'WalletRevokedEvent':
+                await this.processWalletRevokedEvent(fields, signature, slot, blockTime);
+                break;
+            case 'TokensMintedEvent':
+                await this.processTokensMintedEvent(fields, signature, slot, blockTime);
+                break;
+            case 'TokensTransferredEvent':
+                await this.processTokensTransferredEvent(fields, signature, slot, blockTime);
+                break;
+        }
 
         // Emit event for real-time subscribers
         this.emit('event', { event, signature, slot, blockTime });
@@ -638,6 +674,18 @@ export class EventIndexer extends EventEmitter {
             logger.error('Failed to update recipient balance', recipientResult.error as any);
         }
 
+        const alerts = this.whaleAlerts.evaluateTransfer({
+            mint: token_mint,
+            from,
+            to,
+            amount,
+            signature,
+            timestamp: blockTime ?? Math.floor(Date.now() / 1000),
+        });
+        for (const alert of alerts) {
+            this.emit('whale_alert', { security_id: security.id, ...alert });
+        }
+
         logger.info('Transfer recorded', { from, to, amount });
         this.emit('tokens_transferred', { security_id: security.id, from, to, amount });
 
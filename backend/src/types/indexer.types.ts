@@ -6,6 +6,9 @@ export interface ParsedEvent {
     discriminator: string;
     data: Buffer;
     raw: boolean;
+    name?: string;
+    schemaVersion?: number;
+    fields?: Record<string, string | number | boolean>;
 }
 
 export interface TokenInitializedEventData {
@@ -0,0 +1,205 @@
+//! `bootstrap`: seed a localnet or devnet that already has gated-token deployed with a
+//! demo issuer. Initializes the program config and token registry if they are missing
+//! (the keypair must then be the program's upgrade authority), creates a token,
+//! approves freshly generated wallets, mints to each and prints every address as JSON.
+//! Wallet keypairs are written to `--out-dir` so frontends can sign as them.
+
+use std::path::PathBuf;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{bail, Context, Result};
+use chainequity_client::gated_token::{accounts, ProtocolParameters, TokenRegistry};
+use chainequity_client::{accounts as fetch, instructions, pda, resolve, PROGRAM_ID};
+use clap::Parser;
+use serde_json::json;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, write_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_sdk_ids::{bpf_loader_upgradeable, system_program, sysvar};
+
+/// SOL each demo wallet gets for fees
+const WALLET_FUNDING: u64 = LAMPORTS_PER_SOL / 10;
+
+#[derive(Parser)]
+#[command(version, about = "Seed a cluster with a demo ChainEquity issuer")]
+struct Args {
+    #[arg(short, long, env = "CHAINEQUITY_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Keypair that pays for everything and becomes the token authority
+    #[arg(short, long, env = "CHAINEQUITY_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    keypair: String,
+    #[arg(long, default_value = "DEMO")]
+    symbol: String,
+    #[arg(long, default_value = "Demo Holdings Common Stock")]
+    name: String,
+    /// Number of approved wallets to create
+    #[arg(long, default_value_t = 3)]
+    wallets: usize,
+    /// Base units minted to each wallet
+    #[arg(long, default_value_t = 10_000)]
+    amount: u64,
+    /// Directory the wallet keypairs are written to
+    #[arg(long, default_value = "bootstrap-wallets")]
+    out_dir: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let keypair_path = match args.keypair.strip_prefix("~/") {
+        Some(rest) => format!("{}/{rest}", std::env::var("HOME").context("HOME is not set")?),
+        None => args.keypair.clone(),
+    };
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|error| anyhow::anyhow!("reading keypair {keypair_path}: {error}"))?;
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    let authority = payer.pubkey();
+
+    if !exists(&rpc, &PROGRAM_ID).await? {
+        bail!("gated-token ({PROGRAM_ID}) is not deployed to {}; run `anchor deploy` first", args.url);
+    }
+    if rpc.get_balance(&authority).await? < LAMPORTS_PER_SOL {
+        airdrop(&rpc, &authority).await?;
+    }
+    if !exists(&rpc, &pda::program_config()).await? {
+        let program_data = Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0;
+        let parameters = ProtocolParameters {
+            fee_recipient: authority,
+            token_creation_fee: 0,
+            default_min_transfer_amount: 0,
+            default_timelock_seconds: 0,
+        };
+        let accounts = accounts::InitializeProgramConfig {
+            admin: authority,
+            program_config: pda::program_config(),
+            program: PROGRAM_ID,
+            program_data,
+            system_program: system_program::ID,
+        };
+        send(&rpc, &payer, &[instructions::initialize_program_config(accounts, parameters)], &[]).await
+            .context("initializing the program config; is the keypair the upgrade authority?")?;
+    }
+    if !exists(&rpc, &pda::token_registry()).await? {
+        let accounts = accounts::InitializeTokenRegistry {
+            payer: authority,
+            token_registry: pda::token_registry(),
+            system_program: system_program::ID,
+        };
+        send(&rpc, &payer, &[instructions::initialize_token_registry(accounts)], &[]).await?;
+    }
+
+    let program_config = fetch::fetch_program_config(&rpc).await?;
+    let registry: TokenRegistry = fetch::fetch(&rpc, &pda::token_registry()).await?;
+    let mint = Keypair::new();
+    let mint_key = mint.pubkey();
+    let accounts = accounts::InitializeToken {
+        authority,
+        mint_authority: pda::mint_authority(&mint_key),
+        freeze_authority: pda::freeze_authority(&mint_key),
+        mint: mint_key,
+        token_config: pda::token_config(&mint_key),
+        token_registry: pda::token_registry(),
+        program_config: pda::program_config(),
+        fee_recipient: (program_config.parameters.token_creation_fee > 0)
+            .then_some(program_config.parameters.fee_recipient),
+        registry_page: pda::registry_page(registry.current_page_index()),
+        metadata: None,
+        token_metadata_program: None,
+        transfer_hook_program: None,
+        extra_account_meta_list: None,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    };
+    let initialize = instructions::initialize_token(accounts, args.symbol.clone(), args.name.clone(), 0, None, String::new());
+    send(&rpc, &payer, &[initialize], &[&mint]).await?;
+
+    std::fs::create_dir_all(&args.out_dir)
+        .with_context(|| format!("creating {}", args.out_dir.display()))?;
+    let mut wallets = Vec::new();
+    for number in 0..args.wallets {
+        let wallet = Keypair::new();
+        let wallet_key = wallet.pubkey();
+        let keypair_file = args.out_dir.join(format!("wallet-{number}.json"));
+        write_keypair_file(&wallet, &keypair_file)
+            .map_err(|error| anyhow::anyhow!("writing {}: {error}", keypair_file.display()))?;
+
+        let approve = instructions::approve_wallet(accounts::ApproveWallet {
+            authority,
+            wallet: wallet_key,
+            token_config: pda::token_config(&mint_key),
+            allowlist_entry: pda::allowlist_entry(&mint_key, &wallet_key),
+            system_program: system_program::ID,
+        });
+        let setup = [
+            solana_sdk::system_instruction::transfer(&authority, &wallet_key, WALLET_FUNDING),
+            approve,
+            create_associated_token_account_idempotent(&authority, &wallet_key, &mint_key, &anchor_spl::token::ID),
+        ];
+        send(&rpc, &payer, &setup, &[]).await?;
+        let accounts = resolve::mint_tokens(&rpc, &authority, &mint_key, &wallet_key, args.amount).await?;
+        send(&rpc, &payer, &[instructions::mint_tokens(accounts, args.amount)], &[]).await?;
+
+        wallets.push(json!({
+            "wallet": wallet_key.to_string(),
+            "keypair": keypair_file.display().to_string(),
+            "allowlist_entry": pda::allowlist_entry(&mint_key, &wallet_key).to_string(),
+            "token_account": get_associated_token_address(&wallet_key, &mint_key).to_string(),
+            "balance": args.amount,
+        }));
+    }
+
+    let output = json!({
+        "rpc_url": args.url,
+        "program_id": PROGRAM_ID.to_string(),
+        "program_config": pda::program_config().to_string(),
+        "token_registry": pda::token_registry().to_string(),
+        "authority": authority.to_string(),
+        "mint": mint_key.to_string(),
+        "token_config": pda::token_config(&mint_key).to_string(),
+        "symbol": args.symbol,
+        "total_supply": args.amount * args.wallets as u64,
+        "wallets": wallets,
+    });
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+async fn send(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction], extra: &[&Keypair]) -> Result<()> {
+    let mut signers = vec![payer];
+    signers.extend_from_slice(extra);
+    let blockhash = rpc.get_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &signers, blockhash);
+    rpc.send_and_confirm_transaction(&transaction).await?;
+    Ok(())
+}
+
+async fn exists(rpc: &RpcClient, address: &Pubkey) -> Result<bool> {
+    Ok(rpc.get_multiple_accounts(&[*address]).await?[0].is_some())
+}
+
+/// Top up the payer where the cluster has a faucet, as localnet and devnet do
+async fn airdrop(rpc: &RpcClient, address: &Pubkey) -> Result<()> {
+    let signature = rpc.request_airdrop(address, 2 * LAMPORTS_PER_SOL).await
+        .context("payer has under 1 SOL and the airdrop request failed")?;
+    rpc.poll_for_signature(&signature).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn arguments_are_well_formed() {
+        Args::command().debug_assert();
+        let args = Args::try_parse_from(["bootstrap", "--wallets", "5", "--out-dir", "/tmp/demo"]).unwrap();
+        assert_eq!((args.wallets, args.amount, args.symbol.as_str()), (5, 10_000, "DEMO"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Import of a compliance policy exported from an ERC-3643 (T-REX) deployment. The
+//! policy is JSON naming the identity registry's claim topics and trusted issuers and
+//! the compliance contract's modules:
+//!
+//! ```json
+//! {
+//!   "decimals": 18,
+//!   "claimTopics": [1, 7],
+//!   "trustedIssuers": [{ "issuer": "<base58 key>", "claimTopics": [1, 7] }],
+//!   "modules": [
+//!     { "name": "CountryAllowModule", "countries": [840, 826] },
+//!     { "name": "CountryRestrictModule", "countries": [408] },
+//!     { "name": "SupplyLimitModule", "limit": "1000000000000000000000000" },
+//!     { "name": "TimeTransfersLimitsModule", "limits": [{ "limitTime": 86400, "limitValue": "5000" }] }
+//!   ]
+//! }
+//! ```
+//!
+//! Claim topics and trusted issuers become the token's `ClaimTopicsRegistry`; issuers
+//! must be given as the Solana keys they sign claims with. Countries are ISO 3166-1
+//! numeric codes, as ERC-3643 stores them, and become country rules. The supply limit
+//! becomes the supply cap and a 24-hour time transfer limit the daily per-wallet limit,
+//! both rescaled from the policy's `decimals` to the token's. Any other module has no
+//! equivalent here and fails the import, so a policy is never half enforced.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Result};
+use chainequity_client::gated_token::{accounts, TrustedIssuer};
+use chainequity_client::{instructions, pda};
+use serde::Deserialize;
+use serde_json::Value;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+/// Window of a `TimeTransfersLimitsModule` limit the daily transfer limit can express
+const DAY_SECONDS: u64 = 86_400;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Policy {
+    /// Decimals of the ERC-3643 token the amounts are denominated in
+    #[serde(default)]
+    pub decimals: u8,
+    #[serde(default)]
+    pub claim_topics: Vec<u64>,
+    #[serde(default)]
+    pub trusted_issuers: Vec<PolicyIssuer>,
+    #[serde(default)]
+    pub modules: Vec<Module>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyIssuer {
+    pub issuer: String,
+    pub claim_topics: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Module {
+    pub name: String,
+    #[serde(default)]
+    pub countries: Vec<u16>,
+    pub limit: Option<Value>,
+    #[serde(default)]
+    pub limits: Vec<TimeLimit>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLimit {
+    pub limit_time: u64,
+    pub limit_value: Value,
+}
+
+/// One instruction of an import, with what it enforces
+pub struct Step {
+    pub description: String,
+    pub instruction: Instruction,
+}
+
+/// Instructions enforcing `policy` on `mint`, whose token has `decimals` decimals. The
+/// signer must be both the token authority and its compliance officer.
+pub fn plan(policy: &Policy, mint: &Pubkey, signer: &Pubkey, decimals: u8) -> Result<Vec<Step>> {
+    let mut steps = Vec::new();
+
+    if !policy.claim_topics.is_empty() || !policy.trusted_issuers.is_empty() {
+        let trusted_issuers = policy.trusted_issuers.iter().map(|trusted| {
+            Ok(TrustedIssuer {
+                issuer: trusted.issuer.parse()
+                    .with_context(|| format!("trusted issuer {} is not a base58 key", trusted.issuer))?,
+                topics: trusted.claim_topics.clone(),
+            })
+        }).collect::<Result<Vec<_>>>()?;
+        let accounts = accounts::SetClaimTopics {
+            compliance_officer: *signer,
+            token_config: pda::token_config(mint),
+            claim_topics: pda::claim_topics(mint),
+            system_program: system_program::ID,
+        };
+        steps.push(Step {
+            description: format!(
+                "require claim topics {:?} from {} trusted issuer(s)",
+                policy.claim_topics,
+                trusted_issuers.len()
+            ),
+            instruction: instructions::set_claim_topics(accounts, policy.claim_topics.clone(), trusted_issuers),
+        });
+    }
+
+    let mut countries = BTreeMap::new();
+    for module in &policy.modules {
+        match module.name.as_str() {
+            "CountryAllowModule" | "CountryRestrictModule" => {
+                let allowed = module.name == "CountryAllowModule";
+                for numeric in &module.countries {
+                    let code = alpha2(*numeric)
+                        .with_context(|| format!("{numeric} is not an ISO 3166-1 numeric country code"))?;
+                    if countries.insert(code, allowed).is_some_and(|earlier| earlier != allowed) {
+                        bail!("country {numeric} is both allowed and restricted");
+                    }
+                }
+            }
+            "SupplyLimitModule" => {
+                let limit = module.limit.as_ref().context("SupplyLimitModule has no limit")?;
+                let max_supply = rescale(limit, policy.decimals, decimals)?;
+                steps.push(Step {
+                    description: format!("cap supply at {max_supply} base units"),
+                    instruction: instructions::set_max_supply(settings(mint, signer), max_supply),
+                });
+            }
+            "TimeTransfersLimitsModule" => {
+                let [limit] = &module.limits[..] else {
+                    bail!("TimeTransfersLimitsModule must have exactly one limit");
+                };
+                if limit.limit_time != DAY_SECONDS {
+                    bail!("only a {DAY_SECONDS}-second transfer limit has an equivalent, not {}", limit.limit_time);
+                }
+                let daily = rescale(&limit.limit_value, policy.decimals, decimals)?;
+                steps.push(Step {
+                    description: format!("limit each wallet to {daily} base units sent per day"),
+                    instruction: instructions::set_daily_transfer_limit(settings(mint, signer), daily),
+                });
+            }
+            other => bail!("compliance module {other} has no gated-token equivalent"),
+        }
+    }
+    for (code, allowed) in countries {
+        let accounts = accounts::SetCountryRule {
+            compliance_officer: *signer,
+            token_config: pda::token_config(mint),
+            country_rule: pda::country_rule(mint, code),
+            system_program: system_program::ID,
+        };
+        steps.push(Step {
+            description: format!(
+                "{} investors resident in {}",
+                if allowed { "allow" } else { "restrict" },
+                String::from_utf8_lossy(&code)
+            ),
+            instruction: instructions::set_country_rule(accounts, code, allowed, 0),
+        });
+    }
+    Ok(steps)
+}
+
+fn settings(mint: &Pubkey, signer: &Pubkey) -> accounts::UpdateTokenSettings {
+    accounts::UpdateTokenSettings {
+        authority: *signer,
+        token_config: pda::token_config(mint),
+    }
+}
+
+/// A uint256 amount, exported as a decimal string or a JSON number, converted from
+/// `from` decimals to `to` decimals. Precision that would be lost is an error.
+fn rescale(amount: &Value, from: u8, to: u8) -> Result<u64> {
+    let amount: u128 = match amount {
+        Value::String(text) => text.parse().with_context(|| format!("{text} is not an amount"))?,
+        Value::Number(number) => number.as_u64().map(u128::from).with_context(|| format!("{number} is not an amount"))?,
+        other => bail!("{other} is not an amount"),
+    };
+    let scaled = if from >= to {
+        let divisor = 10u128.checked_pow(u32::from(from - to)).context("decimals out of range")?;
+        if !amount.is_multiple_of(divisor) {
+            bail!("{amount} cannot be expressed in {to} decimals");
+        }
+        amount / divisor
+    } else {
+        10u128.checked_pow(u32::from(to - from))
+            .and_then(|factor| amount.checked_mul(factor))
+            .context("amount overflows")?
+    };
+    u64::try_from(scaled).with_context(|| format!("{scaled} does not fit a u64 amount"))
+}
+
+/// ISO 3166-1 alpha-2 code of a numeric country code
+fn alpha2(numeric: u16) -> Option<[u8; 2]> {
+    COUNTRIES.iter().find(|(code, _)| *code == numeric).map(|(_, alpha2)| *alpha2)
+}
+
+const COUNTRIES: &[(u16, [u8; 2])] = &[
+    (4, *b"AF"), (8, *b"AL"), (10, *b"AQ"), (12, *b"DZ"), (16, *b"AS"), (20, *b"AD"),
+    (24, *b"AO"), (28, *b"AG"), (31, *b"AZ"), (32, *b"AR"), (36, *b"AU"), (40, *b"AT"),
+    (44, *b"BS"), (48, *b"BH"), (50, *b"BD"), (51, *b"AM"), (52, *b"BB"), (56, *b"BE"),
+    (60, *b"BM"), (64, *b"BT"), (68, *b"BO"), (70, *b"BA"), (72, *b"BW"), (74, *b"BV"),
+    (76, *b"BR"), (84, *b"BZ"), (86, *b"IO"), (90, *b"SB"), (92, *b"VG"), (96, *b"BN"),
+    (100, *b"BG"), (104, *b"MM"), (108, *b"BI"), (112, *b"BY"), (116, *b"KH"), (120, *b"CM"),
+    (124, *b"CA"), (132, *b"CV"), (136, *b"KY"), (140, *b"CF"), (144, *b"LK"), (148, *b"TD"),
+    (152, *b"CL"), (156, *b"CN"), (158, *b"TW"), (162, *b"CX"), (166, *b"CC"), (170, *b"CO"),
+    (174, *b"KM"), (175, *b"YT"), (178, *b"CG"), (180, *b"CD"), (184, *b"CK"), (188, *b"CR"),
+    (191, *b"HR"), (192, *b"CU"), (196, *b"CY"), (203, *b"CZ"), (204, *b"BJ"), (208, *b"DK"),
+    (212, *b"DM"), (214, *b"DO"), (218, *b"EC"), (222, *b"SV"), (226, *b"GQ"), (231, *b"ET"),
+    (232, *b"ER"), (233, *b"EE"), (234, *b"FO"), (238, *b"FK"), (239, *b"GS"), (242, *b"FJ"),
+    (246, *b"FI"), (248, *b"AX"), (250, *b"FR"), (254, *b"GF"), (258, *b"PF"), (260, *b"TF"),
+    (262, *b"DJ"), (266, *b"GA"), (268, *b"GE"), (270, *b"GM"), (275, *b"PS"), (276, *b"DE"),
+    (288, *b"GH"), (292, *b"GI"), (296, *b"KI"), (300, *b"GR"), (304, *b"GL"), (308, *b"GD"),
+    (312, *b"GP"), (316, *b"GU"), (320, *b"GT"), (324, *b"GN"), (328, *b"GY"), (332, *b"HT"),
+    (334, *b"HM"), (336, *b"VA"), (340, *b"HN"), (344, *b"HK"), (348, *b"HU"), (352, *b"IS"),
+    (356, *b"IN"), (360, *b"ID"), (364, *b"IR"), (368, *b"IQ"), (372, *b"IE"), (376, *b"IL"),
+    (380, *b"IT"), (384, *b"CI"), (388, *b"JM"), (392, *b"JP"), (398, *b"KZ"), (400, *b"JO"),
+    (404, *b"KE"), (408, *b"KP"), (410, *b"KR"), (414, *b"KW"), (417, *b"KG"), (418, *b"LA"),
+    (422, *b"LB"), (426, *b"LS"), (428, *b"LV"), (430, *b"LR"), (434, *b"LY"), (438, *b"LI"),
+    (440, *b"LT"), (442, *b"LU"), (446, *b"MO"), (450, *b"MG"), (454, *b"MW"), (458, *b"MY"),
+    (462, *b"MV"), (466, *b"ML"), (470, *b"MT"), (474, *b"MQ"), (478, *b"MR"), (480, *b"MU"),
+    (484, *b"MX"), (492, *b"MC"), (496, *b"MN"), (498, *b"MD"), (499, *b"ME"), (500, *b"MS"),
+    (504, *b"MA"), (508, *b"MZ"), (512, *b"OM"), (516, *b"NA"), (520, *b"NR"), (524, *b"NP"),
+    (528, *b"NL"), (531, *b"CW"), (533, *b"AW"), (534, *b"SX"), (535, *b"BQ"), (540, *b"NC"),
+    (548, *b"VU"), (554, *b"NZ"), (558, *b"NI"), (562, *b"NE"), (566, *b"NG"), (570, *b"NU"),
+    (574, *b"NF"), (578, *b"NO"), (580, *b"MP"), (581, *b"UM"), (583, *b"FM"), (584, *b"MH"),
+    (585, *b"PW"), (586, *b"PK"), (591, *b"PA"), (598, *b"PG"), (600, *b"PY"), (604, *b"PE"),
+    (608, *b"PH"), (612, *b"PN"), (616, *b"PL"), (620, *b"PT"), (624, *b"GW"), (626, *b"TL"),
+    (630, *b"PR"), (634, *b"QA"), (638, *b"RE"), (642, *b"RO"), (643, *b"RU"), (646, *b"RW"),
+    (652, *b"BL"), (654, *b"SH"), (659, *b"KN"), (660, *b"AI"), (662, *b"LC"), (663, *b"MF"),
+    (666, *b"PM"), (670, *b"VC"), (674, *b"SM"), (678, *b"ST"), (682, *b"SA"), (686, *b"SN"),
+    (688, *b"RS"), (690, *b"SC"), (694, *b"SL"), (702, *b"SG"), (703, *b"SK"), (704, *b"VN"),
+    (705, *b"SI"), (706, *b"SO"), (710, *b"ZA"), (716, *b"ZW"), (724, *b"ES"), (728, *b"SS"),
+    (729, *b"SD"), (732, *b"EH"), (740, *b"SR"), (744, *b"SJ"), (748, *b"SZ"), (752, *b"SE"),
+    (756, *b"CH"), (760, *b"SY"), (762, *b"TJ"), (764, *b"TH"), (768, *b"TG"), (772, *b"TK"),
+    (776, *b"TO"), (780, *b"TT"), (784, *b"AE"), (788, *b"TN"), (792, *b"TR"), (795, *b"TM"),
+    (796, *b"TC"), (798, *b"TV"), (800, *b"UG"), (804, *b"UA"), (807, *b"MK"), (818, *b"EG"),
+    (826, *b"GB"), (831, *b"GG"), (832, *b"JE"), (833, *b"IM"), (834, *b"TZ"), (840, *b"US"),
+    (850, *b"VI"), (854, *b"BF"), (858, *b"UY"), (860, *b"UZ"), (862, *b"VE"), (876, *b"WF"),
+    (882, *b"WS"), (887, *b"YE"), (894, *b"ZM"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_supported_modules_and_refuses_the_rest() {
+        let (mint, signer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let issuer = Pubkey::new_unique();
+        let policy: Policy = serde_json::from_value(serde_json::json!({
+            "decimals": 18,
+            "claimTopics": [1, 7],
+            "trustedIssuers": [{ "issuer": issuer.to_string(), "claimTopics": [1, 7] }],
+            "modules": [
+                { "name": "CountryAllowModule", "countries": [840, 276] },
+                { "name": "CountryRestrictModule", "countries": [408] },
+                { "name": "SupplyLimitModule", "limit": "1000000000000000000000000" },
+                { "name": "TimeTransfersLimitsModule", "limits": [{ "limitTime": 86400, "limitValue": "5000000000000000000000" }] },
+            ],
+        })).unwrap();
+
+        let steps = plan(&policy, &mint, &signer, 0).unwrap();
+        let descriptions: Vec<&str> = steps.iter().map(|step| step.description.as_str()).collect();
+        assert_eq!(descriptions, [
+            "require claim topics [1, 7] from 1 trusted issuer(s)",
+            "cap supply at 1000000 base units",
+            "limit each wallet to 5000 base units sent per day",
+            "allow investors resident in DE",
+            "restrict investors resident in KP",
+            "allow investors resident in US",
+        ]);
+        assert_eq!(steps[0].instruction.accounts[2].pubkey, pda::claim_topics(&mint));
+        assert_eq!(steps[3].instruction.accounts[2].pubkey, pda::country_rule(&mint, *b"DE"));
+
+        let policy: Policy = serde_json::from_value(serde_json::json!({
+            "modules": [{ "name": "MaxBalanceModule" }],
+        })).unwrap();
+        assert!(plan(&policy, &mint, &signer, 0).is_err());
+        assert!(rescale(&Value::from("1500"), 3, 0).is_err());
+        assert_eq!(rescale(&Value::from(15), 0, 2).unwrap(), 1_500);
+        assert_eq!(alpha2(250), Some(*b"FR"));
+        assert_eq!(alpha2(1), None);
+    }
+}
@@ -0,0 +1,240 @@
+//! Point-in-time cap table built from every token account of a mint rather than the
+//! on-chain cap table pages, so it works for any token. Each holder is joined with its
+//! allowlist entry (approval, jurisdiction, insider flag, lockup) and the token's share
+//! class. Balances and percentages are in raw token units, the unit on-chain pro-rata
+//! payouts use; `shares` gives the split-adjusted count alongside.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use chainequity_client::gated_token::{AllowlistEntry, ShareClass};
+use chainequity_client::{accounts as fetch, pda, resolve};
+use serde_json::{json, Value};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+
+/// Accounts per `getMultipleAccounts` request
+const BATCH: usize = 100;
+
+pub struct CapTableExport {
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub slot: u64,
+    pub timestamp: i64,
+    /// Raw mint supply
+    pub supply: u64,
+    /// Split-adjusted supply, `TokenConfig::total_supply`
+    pub total_shares: u64,
+    pub class: String,
+    pub holders: Vec<HolderRow>,
+}
+
+pub struct HolderRow {
+    pub wallet: Pubkey,
+    /// Raw token units
+    pub balance: u64,
+    /// `balance` after splits
+    pub shares: u64,
+    pub approved: bool,
+    pub country_code: String,
+    pub insider: bool,
+    pub lockup_until: Option<i64>,
+    pub transfer_locked: bool,
+}
+
+impl HolderRow {
+    fn lockup_status(&self, now: i64) -> &'static str {
+        if self.transfer_locked {
+            "transfer_locked"
+        } else if self.lockup_until.is_some_and(|until| until > now) {
+            "locked"
+        } else {
+            "unlocked"
+        }
+    }
+}
+
+impl CapTableExport {
+    /// Percentage of the raw supply held by `row`
+    fn share(&self, row: &HolderRow) -> f64 {
+        match self.supply {
+            0 => 0.0,
+            supply => row.balance as f64 * 100.0 / supply as f64,
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("holder,balance,shares,percent,class,approved,country,insider,lockup_status,lockup_until\n");
+        for row in &self.holders {
+            csv.push_str(&format!(
+                "{},{},{},{:.6},{},{},{},{},{},{}\n",
+                row.wallet,
+                row.balance,
+                row.shares,
+                self.share(row),
+                csv_field(&self.class),
+                row.approved,
+                csv_field(&row.country_code),
+                row.insider,
+                row.lockup_status(self.timestamp),
+                row.lockup_until.map(|until| until.to_string()).unwrap_or_default(),
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Value {
+        let holders: Vec<Value> = self.holders.iter().map(|row| json!({
+            "holder": row.wallet.to_string(),
+            "balance": row.balance,
+            "shares": row.shares,
+            "percent": self.share(row),
+            "class": self.class,
+            "approved": row.approved,
+            "country": row.country_code,
+            "insider": row.insider,
+            "lockup_status": row.lockup_status(self.timestamp),
+            "lockup_until": row.lockup_until,
+        })).collect();
+        json!({
+            "mint": self.mint.to_string(),
+            "symbol": self.symbol,
+            "slot": self.slot,
+            "timestamp": self.timestamp,
+            "supply": self.supply,
+            "total_shares": self.total_shares,
+            "holder_count": self.holders.len(),
+            "holders": holders,
+        })
+    }
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Every wallet holding `mint`, largest first, as of the current slot
+pub async fn cap_table(rpc: &RpcClient, mint: &Pubkey) -> Result<CapTableExport> {
+    let slot = rpc.get_slot().await?;
+    let token_config = fetch::fetch_token_config(rpc, mint).await?;
+    let token_program = resolve::token_program(rpc, mint).await?;
+    let supply = fetch::fetch_mint_supply(rpc, mint).await?;
+
+    // Token account layout: mint, owner, amount; Token-2022 accounts may be longer
+    let config = RpcProgramAccountsConfig {
+        filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, mint.to_bytes().to_vec()))]),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let mut balances = BTreeMap::<Pubkey, u64>::new();
+    for (_, account) in rpc.get_program_accounts_with_config(&token_program, config).await? {
+        let Some(data) = account.data.get(..72) else { continue };
+        let owner = Pubkey::try_from(&data[32..64]).unwrap();
+        let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+        if amount > 0 {
+            *balances.entry(owner).or_default() += amount;
+        }
+    }
+
+    let wallets: Vec<Pubkey> = balances.keys().copied().collect();
+    let mut entries = Vec::with_capacity(wallets.len());
+    for chunk in wallets.chunks(BATCH) {
+        let addresses: Vec<Pubkey> = chunk.iter().map(|wallet| pda::allowlist_entry(mint, wallet)).collect();
+        for account in rpc.get_multiple_accounts(&addresses).await? {
+            entries.push(account.and_then(|account| fetch::decode::<AllowlistEntry>(&account.data).ok()));
+        }
+    }
+    let class = match fetch::fetch::<ShareClass>(rpc, &pda::share_class(mint)).await {
+        Ok(class) => class.name,
+        Err(chainequity_client::ClientError::AccountNotFound(_)) => String::new(),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut holders = Vec::with_capacity(wallets.len());
+    for (wallet, entry) in wallets.into_iter().zip(entries) {
+        let balance = balances[&wallet];
+        holders.push(HolderRow {
+            wallet,
+            balance,
+            shares: token_config.split_adjusted(balance)?,
+            approved: entry.as_ref().is_some_and(|entry| entry.is_approved),
+            country_code: entry.as_ref()
+                .map(|entry| String::from_utf8_lossy(&entry.country_code).trim_end_matches('\0').to_string())
+                .unwrap_or_default(),
+            insider: entry.as_ref().is_some_and(|entry| entry.is_insider),
+            lockup_until: entry.as_ref().and_then(|entry| entry.lockup_until),
+            transfer_locked: entry.as_ref().is_some_and(|entry| entry.transfer_locked),
+        });
+    }
+    holders.sort_by(|a, b| b.balance.cmp(&a.balance).then(a.wallet.cmp(&b.wallet)));
+
+    Ok(CapTableExport {
+        mint: *mint,
+        symbol: token_config.symbol,
+        slot,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64,
+        supply,
+        total_shares: token_config.total_supply,
+        class,
+        holders,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_csv_with_shares_and_lockups() {
+        let export = CapTableExport {
+            mint: Pubkey::new_unique(),
+            symbol: "ACME".into(),
+            slot: 7,
+            timestamp: 1_000,
+            supply: 400,
+            total_shares: 800,
+            class: "Series A, Preferred".into(),
+            holders: vec![
+                HolderRow {
+                    wallet: Pubkey::new_unique(),
+                    balance: 300,
+                    shares: 600,
+                    approved: true,
+                    country_code: "US".into(),
+                    insider: true,
+                    lockup_until: Some(2_000),
+                    transfer_locked: false,
+                },
+                HolderRow {
+                    wallet: Pubkey::new_unique(),
+                    balance: 100,
+                    shares: 200,
+                    approved: false,
+                    country_code: String::new(),
+                    insider: false,
+                    lockup_until: Some(500),
+                    transfer_locked: false,
+                },
+            ],
+        };
+        let csv = export.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].ends_with(",300,600,75.000000,\"Series A, Preferred\",true,US,true,locked,2000"));
+        assert!(lines[2].ends_with(",100,200,25.000000,\"Series A, Preferred\",false,,false,unlocked,500"));
+        let json = export.to_json();
+        assert_eq!(json["holders"][0]["lockup_status"], "locked");
+        assert_eq!((json["supply"].as_u64(), json["total_shares"].as_u64()), (Some(400), Some(800)));
+    }
+}
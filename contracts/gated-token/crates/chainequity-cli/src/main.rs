@@ -0,0 +1,400 @@
+//! `chainequity-cli`: run day-to-day issuer operations against a deployed gated-token
+//! program from the shell. Every command signs with `--keypair` and sends to `--url`;
+//! the keypair is the token authority except for `transfer`, where it is the sender.
+
+mod erc3643;
+mod export;
+
+use std::path::PathBuf;
+
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{bail, Context, Result};
+use chainequity_client::gated_token::{accounts, CapTable, HolderPage, Snapshot, TokenRegistry};
+use chainequity_client::{accounts as fetch, instructions, pda, resolve, ClientError};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_sdk_ids::{system_program, sysvar};
+
+#[derive(Parser)]
+#[command(version, about = "Issuer operations for ChainEquity gated tokens")]
+struct Cli {
+    /// JSON RPC endpoint of the cluster the program is deployed to
+    #[arg(short, long, global = true, env = "CHAINEQUITY_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Keypair file that signs and pays for each transaction
+    #[arg(short, long, global = true, env = "CHAINEQUITY_KEYPAIR", default_value = "~/.config/solana/id.json")]
+    keypair: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new gated token with the keypair as its authority
+    InitToken {
+        #[arg(long)]
+        symbol: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long, default_value_t = 0)]
+        decimals: u8,
+        /// Hard cap on total supply, in base units
+        #[arg(long)]
+        max_supply: Option<u64>,
+        #[arg(long, default_value = "")]
+        uri: String,
+    },
+    /// Add a wallet to the token's allowlist
+    Approve {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        wallet: Pubkey,
+    },
+    /// Remove a wallet from the token's allowlist
+    Revoke {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        wallet: Pubkey,
+    },
+    /// Mint base units to an approved wallet, creating its token account if needed
+    Mint {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        to: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Transfer base units from the keypair's wallet to another approved wallet
+    Transfer {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        to: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Halt all transfers of the token
+    Pause {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Resume transfers of a paused token
+    Unpause {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Record a balance snapshot of the token
+    Snapshot {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Print every holder on the token's on-chain cap table
+    CapTable {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Write a point-in-time cap table of every token account holder, joined with
+    /// allowlist data, for counsel and auditors
+    ExportCapTable {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+        /// File to write instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Enforce a compliance policy exported from an ERC-3643 (T-REX) deployment: claim
+    /// topics, trusted issuers and the compliance modules that have an equivalent here.
+    /// The keypair must be both token authority and compliance officer
+    ImportErc3643 {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Policy JSON file
+        #[arg(long)]
+        policy: PathBuf,
+        /// Print the steps without sending them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Json,
+}
+
+struct Session {
+    rpc: RpcClient,
+    signer: Keypair,
+}
+
+impl Session {
+    fn authority(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    /// Sign `instructions` with the keypair plus `extra` signers and wait for confirmation
+    async fn send(&self, instructions: &[Instruction], extra: &[&Keypair]) -> Result<Signature> {
+        let mut signers = vec![&self.signer];
+        signers.extend_from_slice(extra);
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&self.authority()), &signers, blockhash);
+        Ok(self.rpc.send_and_confirm_transaction(&transaction).await?)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let keypair_path = match cli.keypair.strip_prefix("~/") {
+        Some(rest) => format!("{}/{rest}", std::env::var("HOME").context("HOME is not set")?),
+        None => cli.keypair,
+    };
+    let signer = read_keypair_file(&keypair_path)
+        .map_err(|error| anyhow::anyhow!("reading keypair {keypair_path}: {error}"))?;
+    let session = Session {
+        rpc: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        signer,
+    };
+
+    match cli.command {
+        Command::InitToken { symbol, name, decimals, max_supply, uri } => {
+            init_token(&session, symbol, name, decimals, max_supply, uri).await
+        }
+        Command::Approve { mint, wallet } => {
+            let accounts = accounts::ApproveWallet {
+                authority: session.authority(),
+                wallet,
+                token_config: pda::token_config(&mint),
+                allowlist_entry: pda::allowlist_entry(&mint, &wallet),
+                system_program: system_program::ID,
+            };
+            report(session.send(&[instructions::approve_wallet(accounts)], &[]).await?);
+            Ok(())
+        }
+        Command::Revoke { mint, wallet } => revoke(&session, mint, wallet).await,
+        Command::Mint { mint, to, amount } => mint_tokens(&session, mint, to, amount).await,
+        Command::Transfer { mint, to, amount } => transfer(&session, mint, to, amount).await,
+        Command::Pause { mint } => {
+            let accounts = accounts::PauseToken {
+                authority: session.authority(),
+                token_config: pda::token_config(&mint),
+            };
+            report(session.send(&[instructions::pause(accounts)], &[]).await?);
+            Ok(())
+        }
+        Command::Unpause { mint } => {
+            let accounts = accounts::SetPaused {
+                authority: session.authority(),
+                token_config: pda::token_config(&mint),
+            };
+            report(session.send(&[instructions::unpause(accounts)], &[]).await?);
+            Ok(())
+        }
+        Command::Snapshot { mint } => snapshot(&session, mint).await,
+        Command::CapTable { mint } => cap_table(&session, mint).await,
+        Command::ExportCapTable { mint, format, output } => {
+            let export = export::cap_table(&session.rpc, &mint).await?;
+            let rendered = match format {
+                Format::Csv => export.to_csv(),
+                Format::Json => serde_json::to_string_pretty(&export.to_json())? + "\n",
+            };
+            match output {
+                Some(path) => std::fs::write(&path, rendered).with_context(|| format!("writing {}", path.display()))?,
+                None => print!("{rendered}"),
+            }
+            Ok(())
+        }
+        Command::ImportErc3643 { mint, policy, dry_run } => import_erc3643(&session, mint, policy, dry_run).await,
+    }
+}
+
+fn report(signature: Signature) {
+    println!("signature: {signature}");
+}
+
+async fn init_token(
+    session: &Session,
+    symbol: String,
+    name: String,
+    decimals: u8,
+    max_supply: Option<u64>,
+    uri: String,
+) -> Result<()> {
+    let program_config = fetch::fetch_program_config(&session.rpc).await
+        .context("program config is not initialized on this cluster")?;
+    let registry: TokenRegistry = fetch::fetch(&session.rpc, &pda::token_registry()).await
+        .context("token registry is not initialized on this cluster")?;
+    let mint = Keypair::new();
+    let mint_key = mint.pubkey();
+    let accounts = accounts::InitializeToken {
+        authority: session.authority(),
+        mint_authority: pda::mint_authority(&mint_key),
+        freeze_authority: pda::freeze_authority(&mint_key),
+        mint: mint_key,
+        token_config: pda::token_config(&mint_key),
+        token_registry: pda::token_registry(),
+        program_config: pda::program_config(),
+        fee_recipient: (program_config.parameters.token_creation_fee > 0)
+            .then_some(program_config.parameters.fee_recipient),
+        registry_page: pda::registry_page(registry.current_page_index()),
+        metadata: None,
+        token_metadata_program: None,
+        transfer_hook_program: None,
+        extra_account_meta_list: None,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    };
+    let instruction = instructions::initialize_token(accounts, symbol, name, decimals, max_supply, uri);
+    let signature = session.send(&[instruction], &[&mint]).await?;
+    println!("mint: {mint_key}");
+    println!("token_config: {}", pda::token_config(&mint_key));
+    report(signature);
+    Ok(())
+}
+
+async fn revoke(session: &Session, mint: Pubkey, wallet: Pubkey) -> Result<()> {
+    let entry = fetch::fetch_allowlist_entry(&session.rpc, &mint, &wallet).await?;
+    let country_rule = pda::country_rule(&mint, entry.country_code);
+    let has_country_rule = account_exists(session, &country_rule).await?;
+    let accounts = accounts::RevokeWallet {
+        authority: session.authority(),
+        wallet,
+        token_config: pda::token_config(&mint),
+        allowlist_entry: pda::allowlist_entry(&mint, &wallet),
+        country_rule: has_country_rule.then_some(country_rule),
+    };
+    report(session.send(&[instructions::revoke_wallet(accounts)], &[]).await?);
+    Ok(())
+}
+
+async fn mint_tokens(session: &Session, mint: Pubkey, to: Pubkey, amount: u64) -> Result<()> {
+    let accounts = resolve::mint_tokens(&session.rpc, &session.authority(), &mint, &to, amount).await?;
+    let create_account = create_associated_token_account_idempotent(
+        &session.authority(),
+        &to,
+        &mint,
+        &accounts.token_program,
+    );
+    let recipient_token_account = accounts.recipient_token_account;
+    let mut instructions = vec![instructions::mint_tokens(accounts, amount)];
+    if !account_exists(session, &recipient_token_account).await? {
+        instructions.insert(0, create_account);
+    }
+    report(session.send(&instructions, &[]).await?);
+    Ok(())
+}
+
+async fn transfer(session: &Session, mint: Pubkey, to: Pubkey, amount: u64) -> Result<()> {
+    let accounts = resolve::gated_transfer(&session.rpc, &mint, &session.authority(), &to, amount).await?;
+    let create_account = create_associated_token_account_idempotent(
+        &session.authority(),
+        &to,
+        &mint,
+        &accounts.token_program,
+    );
+    let to_token_account = accounts.to_token_account;
+    let mut instructions = vec![instructions::gated_transfer(accounts, amount, None)];
+    if !account_exists(session, &to_token_account).await? {
+        instructions.insert(0, create_account);
+    }
+    report(session.send(&instructions, &[]).await?);
+    Ok(())
+}
+
+async fn snapshot(session: &Session, mint: Pubkey) -> Result<()> {
+    let token_config = fetch::fetch_token_config(&session.rpc, &mint).await?;
+    let snapshot_id = token_config.current_snapshot_id + 1;
+    let address = pda::snapshot(&mint, snapshot_id);
+    let accounts = accounts::CreateSnapshot {
+        authority: session.authority(),
+        token_config: pda::token_config(&mint),
+        mint,
+        snapshot: address,
+        system_program: system_program::ID,
+    };
+    let signature = session.send(&[instructions::create_snapshot(accounts)], &[]).await?;
+    let snapshot: Snapshot = fetch::fetch(&session.rpc, &address).await?;
+    println!("snapshot: {snapshot_id} ({address})");
+    println!("total_supply: {}", snapshot.total_supply);
+    println!("holder_count: {}", snapshot.holder_count);
+    report(signature);
+    Ok(())
+}
+
+async fn cap_table(session: &Session, mint: Pubkey) -> Result<()> {
+    let token_config = fetch::fetch_token_config(&session.rpc, &mint).await?;
+    if token_config.cap_table == Pubkey::default() {
+        bail!("{} has no on-chain cap table; enable one with initialize_cap_table", token_config.symbol);
+    }
+    let cap_table: CapTable = fetch::fetch(&session.rpc, &token_config.cap_table).await?;
+    let mut holders = Vec::new();
+    for page_index in 0..cap_table.next_page_index {
+        match fetch::fetch_zero_copy::<HolderPage>(&session.rpc, &pda::holder_page(&mint, page_index)).await {
+            Ok(page) => holders.extend_from_slice(page.holders()),
+            // Closed pages leave gaps in the index range
+            Err(ClientError::AccountNotFound(_)) => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    holders.sort_by(|a, b| b.balance.cmp(&a.balance).then(a.wallet.cmp(&b.wallet)));
+
+    // Page balances are raw token units, so shares of the raw mint supply; the shares
+    // column is the split-adjusted count recorded in `total_supply`
+    let supply = fetch::fetch_mint_supply(&session.rpc, &mint).await?;
+    println!("{:<44} {:>20} {:>20} {:>9}", "holder", "balance", "shares", "share");
+    for holder in &holders {
+        let share = match supply {
+            0 => 0.0,
+            supply => holder.balance as f64 * 100.0 / supply as f64,
+        };
+        let shares = token_config.split_adjusted(holder.balance)?;
+        println!("{:<44} {:>20} {:>20} {:>8.4}%", holder.wallet.to_string(), holder.balance, shares, share);
+    }
+    println!("{} holders, supply {supply}, total shares {}", holders.len(), token_config.total_supply);
+    Ok(())
+}
+
+async fn import_erc3643(session: &Session, mint: Pubkey, policy: PathBuf, dry_run: bool) -> Result<()> {
+    let text = std::fs::read_to_string(&policy).with_context(|| format!("reading {}", policy.display()))?;
+    let policy: erc3643::Policy = serde_json::from_str(&text).context("parsing the ERC-3643 policy")?;
+    let token_config = fetch::fetch_token_config(&session.rpc, &mint).await?;
+    let steps = erc3643::plan(&policy, &mint, &session.authority(), token_config.decimals)?;
+    for step in steps {
+        println!("{}", step.description);
+        if !dry_run {
+            report(session.send(&[step.instruction], &[]).await?);
+        }
+    }
+    Ok(())
+}
+
+async fn account_exists(session: &Session, address: &Pubkey) -> Result<bool> {
+    Ok(session.rpc.get_multiple_accounts(&[*address]).await?[0].is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn arguments_are_well_formed() {
+        Cli::command().debug_assert();
+        let cli = Cli::try_parse_from(["chainequity-cli", "mint", "--mint", "11111111111111111111111111111111",
+            "--to", "11111111111111111111111111111111", "--amount", "5", "--url", "http://localhost:8899"]).unwrap();
+        assert_eq!(cli.url, "http://localhost:8899");
+        assert!(matches!(cli.command, Command::Mint { amount: 5, .. }));
+    }
+}
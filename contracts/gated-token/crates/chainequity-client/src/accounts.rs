@@ -0,0 +1,90 @@
+//! Fetch and decode gated-token accounts. Decoding checks the Anchor discriminator, so
+//! reading an address as the wrong account type fails instead of misparsing.
+
+use anchor_lang::{AccountDeserialize, ZeroCopy};
+
+use crate::{ClientError, Result};
+
+/// Decode a Borsh account from its raw data, discriminator included
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..]).map_err(|_| ClientError::InvalidAccountData)
+}
+
+/// Decode a zero-copy account (`HolderPage`, `TransferJournal`, ...) from its raw data
+pub fn decode_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<T> {
+    let body = data
+        .strip_prefix(T::DISCRIMINATOR)
+        .ok_or(ClientError::InvalidAccountData)?;
+    let body = body
+        .get(..std::mem::size_of::<T>())
+        .ok_or(ClientError::InvalidAccountData)?;
+    bytemuck::try_pod_read_unaligned(body).map_err(|_| ClientError::InvalidAccountData)
+}
+
+/// Raw supply of an SPL Token or Token-2022 mint from its account data. Holder balances
+/// and snapshot supplies are in these units; `TokenConfig::split_adjusted` converts them
+/// to post-split shares.
+pub fn mint_supply(data: &[u8]) -> Result<u64> {
+    // Mint layout: a 36-byte optional mint authority, then the supply
+    let supply = data.get(36..44).ok_or(ClientError::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(supply.try_into().unwrap()))
+}
+
+#[cfg(feature = "rpc")]
+pub use fetching::*;
+
+#[cfg(feature = "rpc")]
+mod fetching {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::{AccountDeserialize, ZeroCopy};
+    use gated_token::{AllowlistEntry, ProgramConfig, TokenConfig};
+    use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+
+    use super::{decode, decode_zero_copy, mint_supply};
+    use crate::{pda, ClientError, Result};
+
+    /// Raw data of a gated-token account
+    pub async fn fetch_data(rpc: &RpcClient, address: &Pubkey) -> Result<Vec<u8>> {
+        let account = rpc
+            .get_account_with_commitment(address, rpc.commitment())
+            .await
+            .map_err(Box::new)?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?;
+        if account.owner != gated_token::ID {
+            return Err(ClientError::WrongOwner(*address));
+        }
+        Ok(account.data)
+    }
+
+    pub async fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+        decode(&fetch_data(rpc, address).await?)
+    }
+
+    pub async fn fetch_zero_copy<T: ZeroCopy>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+        decode_zero_copy(&fetch_data(rpc, address).await?)
+    }
+
+    pub async fn fetch_program_config(rpc: &RpcClient) -> Result<ProgramConfig> {
+        fetch(rpc, &pda::program_config()).await
+    }
+
+    pub async fn fetch_token_config(rpc: &RpcClient, mint: &Pubkey) -> Result<TokenConfig> {
+        fetch(rpc, &pda::token_config(mint)).await
+    }
+
+    /// Raw supply of `mint`, see [`mint_supply`]
+    pub async fn fetch_mint_supply(rpc: &RpcClient, mint: &Pubkey) -> Result<u64> {
+        let account = rpc
+            .get_account_with_commitment(mint, rpc.commitment())
+            .await
+            .map_err(Box::new)?
+            .value
+            .ok_or(ClientError::AccountNotFound(*mint))?;
+        mint_supply(&account.data)
+    }
+
+    pub async fn fetch_allowlist_entry(rpc: &RpcClient, mint: &Pubkey, wallet: &Pubkey) -> Result<AllowlistEntry> {
+        fetch(rpc, &pda::allowlist_entry(mint, wallet)).await
+    }
+}
@@ -0,0 +1,99 @@
+//! The allowlist, lockup, pause and lot rules `gated_transfer` applies, evaluated
+//! against cached state. Rules that depend on accounts outside the two allowlist
+//! entries (country rules, sanctions, blackout windows, velocity limits) are only
+//! covered by simulation.
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use gated_token::{AllowlistEntry, ErrorCode, FeatureFlags, TokenConfig};
+
+pub struct Transfer<'a> {
+    pub sender: Option<&'a AllowlistEntry>,
+    pub recipient: Option<&'a AllowlistEntry>,
+    pub from_token_account: Pubkey,
+    pub to_token_account: Pubkey,
+    pub sender_balance: u64,
+    pub amount: u64,
+}
+
+/// Ok if the program would let `transfer` through these rules at `now`, in the order
+/// the program checks them. A wallet with no allowlist entry counts as unapproved.
+pub fn precheck(token_config: &TokenConfig, transfer: &Transfer, now: i64) -> Result<()> {
+    require!(transfer.amount > 0, ErrorCode::InvalidAmount);
+    require!(!token_config.paused, ErrorCode::TokenPaused);
+    require!(token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+    token_config.validate_lot(transfer.amount)?;
+
+    let sender = transfer.sender.ok_or(ErrorCode::SenderNotApproved)?;
+    require!(sender.is_approved, ErrorCode::SenderNotApproved);
+    require!(!sender.transfer_locked, ErrorCode::SenderTransferLocked);
+    require!(sender.permits_token_account(&transfer.from_token_account), ErrorCode::TokenAccountNotApproved);
+    if let Some(lockup_until) = sender.lockup_until {
+        require!(now >= lockup_until, ErrorCode::SenderLockedUp);
+    }
+
+    let recipient = transfer.recipient.ok_or(ErrorCode::RecipientNotApproved)?;
+    require!(recipient.is_approved, ErrorCode::RecipientNotApproved);
+    require!(recipient.permits_token_account(&transfer.to_token_account), ErrorCode::TokenAccountNotApproved);
+
+    require!(transfer.sender_balance >= transfer.amount, ErrorCode::InsufficientBalance);
+    Ok(())
+}
+
+/// The token account `wallet` must transact through: the one its entry binds, or its
+/// associated token account
+pub fn token_account(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey, entry: Option<&AllowlistEntry>) -> Pubkey {
+    entry.and_then(|entry| entry.approved_token_account)
+        .unwrap_or_else(|| get_associated_token_address_with_program_id(wallet, mint, token_program))
+}
+
+/// Error name and code of a rejection
+pub fn reason(error: &Error) -> (String, u32) {
+    match error {
+        Error::AnchorError(error) => (error.error_name.clone(), error.error_code_number),
+        Error::ProgramError(error) => (error.program_error.to_string(), 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Account decoded from zeroes: empty strings, `None`s and cleared flags
+    fn zeroed<T: AnchorDeserialize>() -> T {
+        T::deserialize(&mut &[0u8; 2_048][..]).unwrap()
+    }
+
+    #[test]
+    fn precheck_follows_the_program_rules() {
+        let mut config: TokenConfig = zeroed();
+        config.features = FeatureFlags::DEFAULT;
+        let mut sender: AllowlistEntry = zeroed();
+        sender.is_approved = true;
+        let mut recipient: AllowlistEntry = zeroed();
+        recipient.is_approved = true;
+        fn transfer<'a>(sender: Option<&'a AllowlistEntry>, recipient: Option<&'a AllowlistEntry>, amount: u64) -> Transfer<'a> {
+            Transfer {
+                sender,
+                recipient,
+                from_token_account: Pubkey::new_unique(),
+                to_token_account: Pubkey::new_unique(),
+                sender_balance: 100,
+                amount,
+            }
+        }
+        let rejection = |result: Result<()>| result.map_err(|error| reason(&error).0).err();
+
+        assert_eq!(rejection(precheck(&config, &transfer(Some(&sender), Some(&recipient), 100), 0)), None);
+        assert_eq!(rejection(precheck(&config, &transfer(Some(&sender), Some(&recipient), 101), 0)).as_deref(), Some("InsufficientBalance"));
+        assert_eq!(rejection(precheck(&config, &transfer(Some(&sender), None, 1), 0)).as_deref(), Some("RecipientNotApproved"));
+
+        sender.lockup_until = Some(50);
+        assert_eq!(rejection(precheck(&config, &transfer(Some(&sender), Some(&recipient), 1), 49)).as_deref(), Some("SenderLockedUp"));
+        assert_eq!(rejection(precheck(&config, &transfer(Some(&sender), Some(&recipient), 1), 50)), None);
+
+        config.paused = true;
+        let (name, code) = reason(&precheck(&config, &transfer(Some(&sender), Some(&recipient), 1), 50).unwrap_err());
+        assert_eq!((name.as_str(), code), ("TokenPaused", u32::from(ErrorCode::TokenPaused)));
+    }
+}
@@ -0,0 +1,157 @@
+//! Decode the events gated-token emits with `emit!`, which appear in transaction logs as
+//! `Program data: <base64>` lines holding the event discriminator and Borsh body. Only
+//! lines logged while gated-token itself is executing are considered, so events of other
+//! programs in the same transaction are never misread.
+
+use anchor_lang::{Discriminator, Event};
+use base64::Engine;
+
+use crate::{ClientError, Result};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// Payloads (discriminator followed by the event body) emitted by gated-token, in order
+pub fn payloads<S: AsRef<str>>(logs: &[S]) -> Vec<Vec<u8>> {
+    let program = gated_token::ID.to_string();
+    let mut invocations: Vec<bool> = Vec::new();
+    let mut payloads = Vec::new();
+    for line in logs.iter().map(AsRef::as_ref) {
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA) {
+            if invocations.last() == Some(&true) {
+                if let Ok(payload) = base64::engine::general_purpose::STANDARD.decode(data) {
+                    payloads.push(payload);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (id, status) = (words.next().unwrap_or_default(), words.next());
+            // `Program log:` and similar carry free text, not an invocation status
+            if id.ends_with(':') {
+                continue;
+            }
+            match status {
+                Some("invoke") => invocations.push(id == program),
+                Some("success") | Some("failed:") => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    payloads
+}
+
+/// Decode `payload` as an `E`, or `None` when it is a different event
+pub fn decode<E: Event>(payload: &[u8]) -> Result<Option<E>> {
+    let Some(body) = payload.strip_prefix(E::DISCRIMINATOR) else {
+        return Ok(None);
+    };
+    E::deserialize(&mut &body[..])
+        .map(Some)
+        .map_err(|_| ClientError::InvalidEventData)
+}
+
+/// Every `E` emitted by gated-token in `logs`
+pub fn decode_all<E: Event, S: AsRef<str>>(logs: &[S]) -> Result<Vec<E>> {
+    payloads(logs)
+        .iter()
+        .filter_map(|payload| decode(payload).transpose())
+        .collect()
+}
+
+macro_rules! known_events {
+    (sequenced: [$($sequenced:ident),* $(,)?], unsequenced: [$($unsequenced:ident),* $(,)?] $(,)?) => {
+        /// Type name of the gated-token event `payload` holds, e.g. `"TokensMintedEvent"`
+        pub fn name(payload: &[u8]) -> Option<&'static str> {
+            $(
+                if payload.starts_with(gated_token::$sequenced::DISCRIMINATOR) {
+                    return Some(stringify!($sequenced));
+                }
+            )*
+            $(
+                if payload.starts_with(gated_token::$unsequenced::DISCRIMINATOR) {
+                    return Some(stringify!($unsequenced));
+                }
+            )*
+            None
+        }
+
+        /// Per-mint sequence number of the event `payload` holds. `None` for the
+        /// deployment-wide and issuer events, which carry no sequence.
+        pub fn sequence(payload: &[u8]) -> Option<u64> {
+            let sequenced = [$(gated_token::$sequenced::DISCRIMINATOR),*]
+                .iter()
+                .any(|discriminator| payload.starts_with(discriminator));
+            // Sequenced events open with `schema_version: u8, sequence: u64`
+            let bytes = payload.get(9..17).filter(|_| sequenced)?;
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        }
+    };
+}
+
+known_events! {
+    sequenced: [
+        TokenInitializedEvent, WalletApprovedEvent, WalletRevokedEvent,
+        TokensMintedEvent, MintScheduleCreatedEvent, MintScheduleCancelledEvent,
+        EmissionMintedEvent, IssuerTokenRegisteredEvent, AccountMigratedEvent,
+        StaleAccountsReclaimedEvent, TokenClosedEvent, FreezeAuthorityChangedEvent,
+        TokenAccountFreezeChangedEvent, MaxSupplyUpdatedEvent, SupplyCapReachedEvent,
+        TokensBurnedEvent, AllowlistBitmapPageCreatedEvent, AllowlistSlotsAssignedEvent,
+        AllowlistSlotsApprovalUpdatedEvent, AllowlistTreeInitializedEvent,
+        CompressedEntryUpdatedEvent, TokensTransferredEvent, PermitTransferEvent,
+        ForcedTransferEvent,
+        DelegateApprovedEvent, DelegatedTransferEvent, EscrowCreatedEvent,
+        EscrowReleasedEvent, EscrowCancelledEvent, SwapOfferCreatedEvent,
+        SwapExecutedEvent, SwapOfferCancelledEvent, WrapperInitializedEvent,
+        SharesWrappedEvent, SharesUnwrappedEvent, WrapperReconciledEvent,
+        StreamCreatedEvent, StreamWithdrawnEvent, StreamPausedEvent,
+        StreamCancelledEvent, TransferJournalUpdatedEvent, CpiCallersRestrictedEvent,
+        TrustedCallerUpdatedEvent, FeaturesUpdatedEvent, RequireMemoUpdatedEvent,
+        AirdropCampaignCreatedEvent, AirdropClaimedEvent, AirdropSweptEvent,
+        BatchMintedEvent, BatchTransferEvent, StockSplitExecutedEvent,
+        HolderMigratedEvent, SymbolChangedEvent, TokenPausedEvent, TokenUnpausedEvent,
+        CircuitBreakerTrippedEvent, CircuitBreakerResetEvent, DestinationApprovedEvent,
+        DestinationRemovedEvent, TransferFeeCollectedEvent, TransferFeeUpdatedEvent,
+        TransferFeesWithdrawnEvent, InvestorOnboardedEvent, TransferLockUpdatedEvent,
+        ActionReceiptIssuedEvent, BlackoutWindowCreatedEvent,
+        BlackoutWindowCancelledEvent, TransferBlockedEvent, InsiderStatusUpdatedEvent,
+        DailyTransferLimitUpdatedEvent, ComplianceOfficerUpdatedEvent,
+        GuardianUpdatedEvent, ComplianceDigestPostedEvent, LotRulesUpdatedEvent,
+        TokenRegisteredEvent, TokenAccountBoundEvent, CountryRuleUpdatedEvent,
+        WalletCountryAssignedEvent, SanctionsOracleUpdatedEvent,
+        DistributionScheduleUpdatedEvent, DistributionCreatedEvent,
+        DividendClaimedEvent, DistributionSweptEvent, SnapshotCreatedEvent,
+        SplitExecutedEvent, ShareClassUpdatedEvent, VestingScheduleCreatedEvent,
+        VestedTokensReleasedEvent, VestingRevokedEvent, OptionGrantCreatedEvent,
+        OptionsExercisedEvent, OptionGrantClosedEvent, RedemptionVaultFundedEvent,
+        RedemptionWindowUpdatedEvent, SharesRedeemedEvent, TenderOfferOpenedEvent,
+        SharesTenderedEvent, TenderOfferClosedEvent, TenderSettledEvent,
+        ConvertibleNoteIssuedEvent, NoteConvertedEvent, WarrantIssuedEvent,
+        WarrantExercisedEvent, WarrantClosedEvent, CapTableInitializedEvent,
+        HolderIndexInitializedEvent, HolderNumberAssignedEvent,
+        HolderNumberReleasedEvent, HolderPageCreatedEvent, HolderPagesCompactedEvent,
+        HolderPageClosedEvent, RofrPeriodUpdatedEvent, SaleProposedEvent,
+        SaleProposalWithdrawnEvent, RofrExercisedEvent, RofrSettledEvent,
+        RightsOfferingOpenedEvent, RightsSubscribedEvent, RightsSettledEvent,
+        AllocationOpenedEvent, AllocationMintedEvent, AllocationFinalizedEvent,
+        DripElectionUpdatedEvent, DripPriceUpdatedEvent, DividendReinvestedEvent,
+        ShareClassAccrualUpdatedEvent, InterestAccruedEvent, InterestClaimedEvent,
+        LiquidationPreferenceUpdatedEvent, WaterfallTranchePaidEvent,
+        AuthorityProposedEvent, AuthorityProposalCancelledEvent,
+        AuthorityTransferredEvent, AuthorityCouncilUpdatedEvent, OperationProposedEvent,
+        OperationApprovedEvent, OperationExecutedEvent, OperationClosedEvent,
+        TimelockUpdatedEvent, OperationQueuedEvent, QueuedOperationExecutedEvent,
+        QueuedOperationCancelledEvent, ProposalCreatedEvent, ProposalRulesUpdatedEvent,
+        ResolutionRecordedEvent, VoteCastEvent, VotesDelegatedEvent,
+        ProposalFinalizedEvent, VoterWeightRegistrarUpdatedEvent,
+        VoterWeightUpdatedEvent, ClaimTopicsUpdatedEvent, ClaimAddedEvent,
+        ClaimRemovedEvent, BridgeConfiguredEvent, SharesBridgedOutEvent,
+        SharesBridgedInEvent, PriceOracleUpdatedEvent, SharesRedeemedAtNavEvent,
+        OptionsExercisedAtMarketEvent, CertificateTreeInitializedEvent,
+        ShareCertificateIssuedEvent, ShareCertificateRetiredEvent,
+    ],
+    unsequenced: [
+        ProgramConfigUpdatedEvent, ProgramPausedEvent, ProgramAdminTransferredEvent,
+        IssuerCreatedEvent, IssuerComplianceOfficerUpdatedEvent, WaterfallExecutedEvent,
+    ],
+}
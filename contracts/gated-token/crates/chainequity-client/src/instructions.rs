@@ -0,0 +1,250 @@
+//! One builder per gated-token instruction. Each takes the instruction's generated
+//! accounts struct (`gated_token::accounts::*`) followed by its arguments, and returns a
+//! ready-to-sign [`Instruction`]. Instructions that read remaining accounts (batches,
+//! waterfalls, `*_with_ata` variants) expect the caller to append them to `accounts`.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use gated_token::accounts;
+use gated_token::instruction as data;
+use gated_token::{
+    BlackoutScope, ConversionTrigger, FeatureFlags, OperationAccount, ProposalRules, ProposalType,
+    ProtocolParameters, ReleaseCondition, ResolutionKind, ShareClassKind, TrustedIssuer,
+    VestingTerms, VoteChoice, WaterfallClassInput,
+};
+
+use crate::pda;
+
+/// Pair any accounts struct with any instruction data, for instructions built by hand
+pub fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: gated_token::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Accounts the allowlist transfer hook needs to move shares of `mint` from a token
+/// account owned by `source_owner` to one owned by `destination_owner`. Instructions
+/// moving shares of a mint created with the hook take them as remaining accounts,
+/// after any blackout windows; one set per mint covers every leg between the same
+/// owners.
+pub fn transfer_hook_accounts(mint: &Pubkey, source_owner: &Pubkey, destination_owner: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(gated_token::ALLOWLIST_TRANSFER_HOOK_ID, false),
+        AccountMeta::new_readonly(pda::extra_account_meta_list(mint), false),
+        AccountMeta::new_readonly(gated_token::ID, false),
+        AccountMeta::new_readonly(pda::token_config(mint), false),
+        AccountMeta::new_readonly(pda::allowlist_entry(mint, source_owner), false),
+        AccountMeta::new_readonly(pda::allowlist_entry(mint, destination_owner), false),
+    ]
+}
+
+macro_rules! builders {
+    ($($name:ident => $data:ident($accounts:ident) { $($arg:ident: $ty:ty),* })*) => {
+        $(
+            pub fn $name(accounts: accounts::$accounts, $($arg: $ty),*) -> Instruction {
+                build(accounts, data::$data { $($arg),* })
+            }
+        )*
+    };
+}
+
+builders! {
+    initialize_token => InitializeToken(InitializeToken) { symbol: String, name: String, decimals: u8, max_supply: Option<u64>, uri: String }
+    approve_wallet => ApproveWallet(ApproveWallet) {}
+    revoke_wallet => RevokeWallet(RevokeWallet) {}
+    create_allowlist_bitmap_page => CreateAllowlistBitmapPage(CreateAllowlistBitmapPage) { page_index: u32 }
+    assign_allowlist_slots => AssignAllowlistSlots(UpdateAllowlistBitmapPage) { wallets: Vec<Pubkey>, approve: bool }
+    set_allowlist_slots_approved => SetAllowlistSlotsApproved(UpdateAllowlistBitmapPage) { slots: Vec<u32>, approved: bool }
+    compact_mint => CompactMint(CompactMint) { amount: u64, slot: u32, proof: Vec<[u8; 32]> }
+    compact_transfer => CompactTransfer(CompactTransfer) { amount: u64, sender_slot: u32, sender_proof: Vec<[u8; 32]>, recipient_slot: u32, recipient_proof: Vec<[u8; 32]> }
+    init_allowlist_tree => InitAllowlistTree(InitAllowlistTree) { max_depth: u32, max_buffer_size: u32 }
+    append_compressed_entry => AppendCompressedEntry(UpdateAllowlistTree) { wallet: Pubkey }
+    set_compressed_entry_approved => SetCompressedEntryApproved(UpdateAllowlistTree) { wallet: Pubkey, root: [u8; 32], leaf_index: u32, approved: bool }
+    compressed_mint => CompressedMint(CompressedMint) { amount: u64, root: [u8; 32], leaf_index: u32 }
+    compressed_transfer => CompressedTransfer(CompressedTransfer) { amount: u64, sender_root: [u8; 32], sender_leaf_index: u32, recipient_root: [u8; 32], recipient_leaf_index: u32, sender_proof_len: u8 }
+    mint_tokens => MintTokens(MintTokens) { amount: u64 }
+    mint_tokens_with_ata => MintTokensWithAta(CreateRecipientAta) { amount: u64 }
+    batch_mint => BatchMint(BatchMint) { amounts: Vec<u64> }
+    gated_transfer_with_ata => GatedTransferWithAta(CreateRecipientAta) { amount: u64, travel_rule_hash: Option<[u8; 32]> }
+    gated_transfer => GatedTransfer(GatedTransfer) { amount: u64, travel_rule_hash: Option<[u8; 32]> }
+    batch_transfer => BatchTransfer(BatchTransfer) { amounts: Vec<u64> }
+    permit_transfer => PermitTransfer(PermitTransfer) { amount: u64, nonce: u64, expiry: i64 }
+    approve_delegate => ApproveDelegate(ApproveDelegate) { amount: u64 }
+    revoke_delegate => RevokeDelegate(RevokeDelegate) {}
+    delegated_transfer => DelegatedTransfer(DelegatedTransfer) { amount: u64 }
+    create_escrow => CreateEscrow(CreateEscrow) { amount: u64, condition: ReleaseCondition }
+    release_escrow => ReleaseEscrow(ReleaseEscrow) {}
+    cancel_escrow => CancelEscrow(CancelEscrow) {}
+    create_swap_offer => CreateSwapOffer(CreateSwapOffer) { offered_amount: u64, requested_amount: u64, taker: Option<Pubkey>, expires_at: Option<i64> }
+    accept_swap_offer => AcceptSwapOffer(AcceptSwapOffer) {}
+    cancel_swap_offer => CancelSwapOffer(CancelSwapOffer) {}
+    initialize_wrapper => InitializeWrapper(InitializeWrapper) {}
+    wrap_shares => WrapShares(WrapShares) { amount: u64 }
+    unwrap_shares => UnwrapShares(UnwrapShares) { amount: u64 }
+    reconcile_wrapper => ReconcileWrapper(ReconcileWrapper) {}
+    create_stream => CreateStream(CreateStream) { amount: u64, rate_per_second: u64, start_time: i64 }
+    withdraw_streamed => WithdrawStreamed(WithdrawStreamed) {}
+    set_stream_paused => SetStreamPaused(SetStreamPaused) { paused: bool }
+    cancel_stream => CancelStream(CancelStream) {}
+    execute_stock_split => ExecuteStockSplit(ExecuteStockSplit) { split_ratio: u64, new_symbol: String, new_name: String }
+    migrate_holder_split => MigrateHolderSplit(MigrateHolderSplit) { old_balance: u64 }
+    update_token_metadata => UpdateTokenMetadata(UpdateTokenMetadata) { new_symbol: String, new_name: String, new_uri: String }
+    pause => Pause(PauseToken) {}
+    unpause => Unpause(SetPaused) {}
+    initialize_circuit_breaker => InitializeCircuitBreaker(InitializeCircuitBreaker) { supply_tolerance: u64, reset_signers: Vec<Pubkey>, reset_threshold: u8 }
+    check_invariants => CheckInvariants(CheckInvariants) { expected_checksum: Option<[u8; 32]> }
+    reset_circuit_breaker => ResetCircuitBreaker(ResetCircuitBreaker) { reconcile: bool }
+    initialize_destination_registry => InitializeDestinationRegistry(InitializeDestinationRegistry) {}
+    add_approved_destination => AddApprovedDestination(UpdateDestinationRegistry) { destination: Pubkey }
+    remove_approved_destination => RemoveApprovedDestination(UpdateDestinationRegistry) { destination: Pubkey }
+    initialize_fee_treasury => InitializeFeeTreasury(InitializeFeeTreasury) {}
+    set_transfer_fee => SetTransferFee(UpdateTokenSettings) { fee_bps: u16 }
+    withdraw_fees => WithdrawFees(WithdrawFees) { amount: u64 }
+    onboard_investor => OnboardInvestor(OnboardInvestor) { lockup_until: Option<i64>, initial_amount: Option<u64> }
+    set_transfer_locked => SetTransferLocked(UpdateAllowlistEntry) { locked: bool }
+    freeze_token_account => FreezeTokenAccount(SetTokenAccountFrozen) {}
+    thaw_token_account => ThawTokenAccount(SetTokenAccountFrozen) {}
+    force_transfer => ForceTransfer(ForceTransfer) { amount: u64, reason_hash: [u8; 32] }
+    disable_freezing => DisableFreezing(DisableFreezing) {}
+    close_token_config => CloseTokenConfig(CloseTokenConfig) {}
+    create_blackout_window => CreateBlackoutWindow(CreateBlackoutWindow) { start_ts: i64, end_ts: i64, scope: BlackoutScope }
+    cancel_blackout_window => CancelBlackoutWindow(CancelBlackoutWindow) {}
+    reclaim_stale_accounts => ReclaimStaleAccounts(ReclaimStaleAccounts) {}
+    migrate_token_config => MigrateTokenConfig(MigrateTokenConfig) {}
+    resize_token_config => ResizeTokenConfig(ResizeTokenConfig) {}
+    migrate_allowlist_entry => MigrateAllowlistEntry(MigrateAllowlistEntry) {}
+    set_insider => SetInsider(UpdateAllowlistEntry) { is_insider: bool }
+    set_daily_transfer_limit => SetDailyTransferLimit(UpdateTokenSettings) { limit: u64 }
+    set_max_supply => SetMaxSupply(UpdateTokenSettings) { max_supply: u64 }
+    propose_authority => ProposeAuthority(ProposeAuthority) { new_authority: Pubkey }
+    cancel_authority_proposal => CancelAuthorityProposal(UpdateTokenSettings) {}
+    accept_authority => AcceptAuthority(AcceptAuthority) {}
+    set_guardian => SetGuardian(UpdateTokenSettings) { guardian: Pubkey }
+    set_compliance_officer => SetComplianceOfficer(UpdateTokenSettings) { compliance_officer: Pubkey }
+    create_issuer => CreateIssuer(CreateIssuer) { name: String }
+    register_issuer_token => RegisterIssuerToken(RegisterIssuerToken) {}
+    set_issuer_compliance_officer => SetIssuerComplianceOfficer(UpdateIssuer) { compliance_officer: Pubkey }
+    post_digest => PostDigest(PostDigest) { period_start: i64, period_end: i64, digest_hash: [u8; 32], alert_count: u32 }
+    set_lot_rules => SetLotRules(UpdateTokenSettings) { min_transfer_amount: u64, lot_size: u64 }
+    set_whole_shares_only => SetWholeSharesOnly(UpdateTokenSettings) { enabled: bool }
+    set_require_memo => SetRequireMemo(UpdateTokenSettings) { enabled: bool }
+    initialize_program_config => InitializeProgramConfig(InitializeProgramConfig) { parameters: ProtocolParameters }
+    update_program_config => UpdateProgramConfig(UpdateProgramConfig) { parameters: ProtocolParameters }
+    set_program_paused => SetProgramPaused(UpdateProgramConfig) { paused: bool }
+    transfer_program_admin => TransferProgramAdmin(TransferProgramAdmin) {}
+    initialize_token_registry => InitializeTokenRegistry(InitializeTokenRegistry) {}
+    bind_token_account => BindTokenAccount(UpdateAllowlistEntry) { token_account: Option<Pubkey> }
+    initialize_transfer_journal => InitializeTransferJournal(InitializeTransferJournal) {}
+    set_transfer_journal_enabled => SetTransferJournalEnabled(UpdateTokenSettings) { enabled: bool }
+    set_restrict_cpi_callers => SetRestrictCpiCallers(UpdateTokenSettings) { restricted: bool }
+    set_features => SetFeatures(UpdateTokenSettings) { features: FeatureFlags }
+    add_trusted_caller => AddTrustedCaller(AddTrustedCaller) { program_id: Pubkey }
+    remove_trusted_caller => RemoveTrustedCaller(RemoveTrustedCaller) {}
+    set_country_rule => SetCountryRule(SetCountryRule) { country_code: [u8; 2], is_allowed: bool, max_investors: u32 }
+    assign_wallet_country => AssignWalletCountry(AssignWalletCountry) { country_code: [u8; 2] }
+    set_sanctions_oracle => SetSanctionsOracle(UpdateTokenSettings) { sanctions_oracle: Pubkey }
+    set_price_oracle => SetPriceOracle(SetPriceOracle) { oracle_program: Pubkey, price_feed: Pubkey, max_price_age_seconds: i64 }
+    create_distribution => CreateDistribution(CreateDistribution) { record_date: i64, snapshot_root: [u8; 32], snapshot_supply: u64, amount: u64 }
+    claim_dividend => ClaimDividend(ClaimDividend) { snapshot_balance: u64, proof: Vec<[u8; 32]> }
+    create_token_distribution => CreateTokenDistribution(CreateTokenDistribution) { record_date: i64, snapshot_root: [u8; 32], snapshot_supply: u64, amount: u64, claim_deadline: i64 }
+    claim_token_dividend => ClaimTokenDividend(ClaimTokenDividend) { snapshot_balance: u64, proof: Vec<[u8; 32]> }
+    create_distribution_schedule => CreateDistributionSchedule(CreateDistributionSchedule) { amount_per_period: u64, interval_seconds: i64, claim_window_seconds: i64, first_run_at: i64 }
+    set_distribution_schedule_active => SetDistributionScheduleActive(SetDistributionScheduleActive) { active: bool }
+    crank_distribution => CrankDistribution(CrankDistribution) {}
+    claim_scheduled_dividend => ClaimScheduledDividend(ClaimScheduledDividend) {}
+    sweep_distribution => SweepDistribution(SweepDistribution) {}
+    create_airdrop_campaign => CreateAirdropCampaign(CreateAirdropCampaign) { merkle_root: [u8; 32], total_amount: u64, expires_at: i64 }
+    claim_airdrop => ClaimAirdrop(ClaimAirdrop) { amount: u64, proof: Vec<[u8; 32]> }
+    sweep_airdrop => SweepAirdrop(SweepAirdrop) {}
+    create_snapshot => CreateSnapshot(CreateSnapshot) {}
+    execute_split => ExecuteSplit(ExecuteSplit) { numerator: u64, denominator: u64 }
+    set_share_class => SetShareClass(SetShareClass) { name: String, kind: ShareClassKind, votes_per_share: u16, dividend_priority: u8, transferable: bool, max_holders: u32 }
+    create_vesting_schedule => CreateVestingSchedule(CreateVestingSchedule) { total_amount: u64, terms: VestingTerms, revocable: bool }
+    release_vested => ReleaseVested(ReleaseVested) {}
+    revoke_vesting => RevokeVesting(RevokeVesting) {}
+    create_option_grant => CreateOptionGrant(CreateOptionGrant) { quantity: u64, strike_price: u64, vesting: VestingTerms, expires_at: i64 }
+    exercise_options => ExerciseOptions(ExerciseOptions) { amount: u64 }
+    exercise_options_at_market => ExerciseOptionsAtMarket(ExerciseOptionsAtMarket) { amount: u64, min_shares: u64 }
+    close_option_grant => CloseOptionGrant(CloseOptionGrant) {}
+    initialize_redemption => InitializeRedemption(InitializeRedemption) {}
+    fund_redemption_vault => FundRedemptionVault(FundRedemptionVault) { amount: u64 }
+    open_redemption_window => OpenRedemptionWindow(UpdateRedemptionWindow) { price: u64, closes_at: i64 }
+    open_nav_redemption_window => OpenNavRedemptionWindow(UpdateRedemptionWindow) { closes_at: i64 }
+    close_redemption_window => CloseRedemptionWindow(UpdateRedemptionWindow) {}
+    burn_tokens => BurnTokens(BurnTokens) { amount: u64 }
+    redeem => Redeem(Redeem) { amount: u64 }
+    redeem_at_nav => RedeemAtNav(RedeemAtNav) { amount: u64, min_payout: u64 }
+    open_tender_offer => OpenTenderOffer(OpenTenderOffer) { price: u64, cap: u64, min_acceptance: u64, deadline: i64 }
+    tender_shares => TenderShares(TenderShares) { amount: u64 }
+    withdraw_tender_offer => WithdrawTenderOffer(CloseTenderOffer) {}
+    close_tender_offer => CloseTenderOffer(CloseTenderOffer) {}
+    settle_tender => SettleTender(SettleTender) {}
+    issue_convertible_note => IssueConvertibleNote(IssueConvertibleNote) { principal: u64, discount_bps: u16, valuation_cap: u64, maturity: i64 }
+    convert_note => ConvertNote(ConvertNote) { trigger: ConversionTrigger }
+    issue_warrant => IssueWarrant(IssueWarrant) { quantity: u64, strike_price: u64, expires_at: i64 }
+    exercise_warrant => ExerciseWarrant(ExerciseWarrant) { amount: u64 }
+    close_warrant => CloseWarrant(CloseWarrant) {}
+    initialize_cap_table => InitializeCapTable(InitializeCapTable) {}
+    initialize_holder_index => InitializeHolderIndex(InitializeHolderIndex) {}
+    index_holder => IndexHolder(IndexHolder) {}
+    init_certificate_tree => InitCertificateTree(InitCertificateTree) { max_depth: u32, max_buffer_size: u32, legend: String, uri: String }
+    issue_share_certificate => IssueShareCertificate(IssueShareCertificate) {}
+    retire_share_certificate => RetireShareCertificate(RetireShareCertificate) { root: [u8; 32] }
+    create_holder_page => CreateHolderPage(CreateHolderPage) {}
+    sync_holder => SyncHolder(SyncHolder) {}
+    compact_holder_pages => CompactHolderPages(CompactHolderPages) {}
+    close_holder_page => CloseHolderPage(CloseHolderPage) {}
+    set_rofr_period => SetRofrPeriod(UpdateTokenSettings) { period_seconds: i64 }
+    propose_sale => ProposeSale(ProposeSale) { buyer: Pubkey, amount: u64, price: u64 }
+    withdraw_sale_proposal => WithdrawSaleProposal(WithdrawSaleProposal) {}
+    exercise_rofr => ExerciseRofr(ExerciseRofr) {}
+    settle_rofr => SettleRofr(SettleRofr) {}
+    open_rights_offering => OpenRightsOffering(OpenRightsOffering) { new_shares: u64, price: u64, deadline: i64, allow_oversubscription: bool }
+    subscribe_rights => SubscribeRights(SubscribeRights) { amount: u64, oversubscription: u64 }
+    settle_rights_subscription => SettleRightsSubscription(SettleRightsSubscription) {}
+    open_allocation => OpenAllocation(OpenAllocation) { issuance_amount: u64 }
+    crank_allocation => CrankAllocation(CrankAllocation) {}
+    finalize_allocation => FinalizeAllocation(FinalizeAllocation) {}
+    set_drip_election => SetDripElection(SetDripElection) { enabled: bool }
+    set_drip_price => SetDripPrice(SetDripPrice) { drip_price: u64 }
+    set_share_class_accrual => SetShareClassAccrual(SetShareClassAccrual) { rate_bps: u16 }
+    create_mint_schedule => CreateMintSchedule(CreateMintSchedule) { amount_per_period: u64, period_seconds: i64, start_time: i64, end_time: i64 }
+    cancel_mint_schedule => CancelMintSchedule(CancelMintSchedule) {}
+    crank_emission => CrankEmission(CrankEmission) {}
+    accrue_interest => AccrueInterest(AccrueInterest) {}
+    claim_interest => ClaimInterest(ClaimInterest) {}
+    set_liquidation_preference => SetLiquidationPreference(SetLiquidationPreference) { multiple_bps: u32, issue_price: u64, participating: bool }
+    execute_waterfall => ExecuteWaterfall(ExecuteWaterfall) { total_proceeds: u64, claim_deadline: i64, classes: Vec<WaterfallClassInput> }
+    initialize_authority_council => InitializeAuthorityCouncil(InitializeAuthorityCouncil) { members: Vec<Pubkey>, threshold: u8 }
+    update_authority_council => UpdateAuthorityCouncil(UpdateAuthorityCouncil) { members: Vec<Pubkey>, threshold: u8 }
+    propose_operation => ProposeOperation(ProposeOperation) { data: Vec<u8>, accounts: Vec<OperationAccount> }
+    approve_operation => ApproveOperation(ApproveOperation) {}
+    execute_operation => ExecuteOperation(ExecuteOperation) {}
+    close_operation => CloseOperation(CloseOperation) {}
+    set_timelock => SetTimelock(SetTimelock) { delay_seconds: i64 }
+    queue_operation => QueueOperation(QueueOperation) { data: Vec<u8>, accounts: Vec<OperationAccount> }
+    execute_queued_operation => ExecuteQueuedOperation(ExecuteQueuedOperation) {}
+    cancel_queued_operation => CancelQueuedOperation(CancelQueuedOperation) {}
+    create_proposal => CreateProposal(CreateProposal) { title: String, description_hash: [u8; 32], proposal_type: ProposalType, voting_period_seconds: i64 }
+    initialize_governance_config => InitializeGovernanceConfig(InitializeGovernanceConfig) {}
+    set_proposal_rules => SetProposalRules(SetProposalRules) { proposal_type: ProposalType, rules: ProposalRules }
+    cast_vote => CastVote(CastVote) { choice: VoteChoice }
+    delegate_votes => DelegateVotes(DelegateVotes) { delegate: Pubkey }
+    revoke_delegation => RevokeDelegation(RevokeDelegation) {}
+    finalize_proposal => FinalizeProposal(FinalizeProposal) {}
+    configure_voter_weight_registrar => ConfigureVoterWeightRegistrar(ConfigureVoterWeightRegistrar) {}
+    update_voter_weight_record => UpdateVoterWeightRecord(UpdateVoterWeightRecord) {}
+    update_max_voter_weight_record => UpdateMaxVoterWeightRecord(UpdateMaxVoterWeightRecord) {}
+    record_resolution => RecordResolution(RecordResolution) { kind: ResolutionKind, document_hash: [u8; 32], uri: String, effective_date: i64 }
+    set_claim_topics => SetClaimTopics(SetClaimTopics) { required_topics: Vec<u64>, trusted_issuers: Vec<TrustedIssuer> }
+    add_claim => AddClaim(AddClaim) { topic: u64, data_hash: [u8; 32], expires_at: Option<i64> }
+    remove_claim => RemoveClaim(RemoveClaim) { topic: u64, issuer: Pubkey }
+    verify_identity => VerifyIdentity(VerifyIdentity) {}
+    revoke_unverified => RevokeUnverified(RevokeUnverified) {}
+    configure_bridge => ConfigureBridge(ConfigureBridge) { token_bridge_program: Pubkey, wormhole_program: Pubkey, enabled: bool }
+    bridge_out => BridgeOut(BridgeOut) { amount: u64, target_chain: u16, target_address: [u8; 32], nonce: u32 }
+    redeem_bridged => RedeemBridged(RedeemBridged) {}
+}
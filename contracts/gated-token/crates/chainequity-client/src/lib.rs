@@ -0,0 +1,50 @@
+//! Off-chain client for the gated-token program, so services build, read and decode
+//! ChainEquity transactions without hand-rolling Anchor discriminators or seeds.
+//!
+//! - [`pda`] derives every program address from its seeds
+//! - [`check`] applies `gated_transfer`'s allowlist rules to accounts already fetched
+//! - [`instructions`] builds each instruction from its typed accounts and arguments
+//! - [`accounts`] fetches and decodes program accounts, checking owner and discriminator
+//! - [`events`] pulls the program's events out of transaction logs
+//! - [`resolve`] fills in the optional accounts a mint or transfer needs
+//! - [`squads`] runs instructions as a Squads multisig vault acting as authority
+//! - [`solana_pay`] links wallets to a pre-validated transfer through Solana Pay
+//!
+//! Everything that talks to an RPC node sits behind the default `rpc` feature.
+//!
+//! The program's own account, argument and event types are re-exported as [`gated_token`].
+
+pub mod accounts;
+pub mod check;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+#[cfg(feature = "rpc")]
+pub mod resolve;
+pub mod solana_pay;
+pub mod squads;
+
+pub use gated_token;
+pub use gated_token::ID as PROGRAM_ID;
+
+use anchor_lang::prelude::Pubkey;
+
+/// Errors from fetching or decoding program state
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account {0} does not exist")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not owned by the gated-token program")]
+    WrongOwner(Pubkey),
+    #[error("account data does not match the expected type")]
+    InvalidAccountData,
+    #[error("wallet {0} holds no holder number")]
+    HolderNumberNotFound(Pubkey),
+    #[error("event data does not match the expected type")]
+    InvalidEventData,
+    #[cfg(feature = "rpc")]
+    #[error(transparent)]
+    Rpc(#[from] Box<solana_rpc_client_api::client_error::Error>),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
@@ -0,0 +1,381 @@
+//! Addresses of the gated-token program's PDAs, mirroring the seeds in its account
+//! constraints. Ids and indices are encoded little-endian, as the program does.
+
+use anchor_lang::prelude::Pubkey;
+
+/// Address and bump for `seeds` under the gated-token program
+pub fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &gated_token::ID)
+}
+
+fn address(seeds: &[&[u8]]) -> Pubkey {
+    find(seeds).0
+}
+
+// Deployment-wide
+
+pub fn program_config() -> Pubkey {
+    address(&[b"program_config"])
+}
+
+pub fn token_registry() -> Pubkey {
+    address(&[b"token_registry"])
+}
+
+pub fn registry_page(page_index: u32) -> Pubkey {
+    address(&[b"registry_page", &page_index.to_le_bytes()])
+}
+
+pub fn issuer(authority: &Pubkey) -> Pubkey {
+    address(&[b"issuer", authority.as_ref()])
+}
+
+/// Metaplex metadata account of `mint`, owned by the Token Metadata program
+pub fn metadata(mint: &Pubkey) -> Pubkey {
+    let program = anchor_spl::metadata::ID;
+    Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref()], &program).0
+}
+
+/// Transfer hook validation account of `mint`, owned by allowlist-transfer-hook
+pub fn extra_account_meta_list(mint: &Pubkey) -> Pubkey {
+    let program = gated_token::ALLOWLIST_TRANSFER_HOOK_ID;
+    Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &program).0
+}
+
+// Per token
+
+pub fn token_config(mint: &Pubkey) -> Pubkey {
+    address(&[b"token_config", mint.as_ref()])
+}
+
+pub fn mint_authority(mint: &Pubkey) -> Pubkey {
+    address(&[b"mint_authority", mint.as_ref()])
+}
+
+pub fn freeze_authority(mint: &Pubkey) -> Pubkey {
+    address(&[b"freeze_authority", mint.as_ref()])
+}
+
+pub fn allowlist_entry(mint: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    address(&[b"allowlist", mint.as_ref(), wallet.as_ref()])
+}
+
+pub fn allowlist_bitmap(mint: &Pubkey, page_index: u32) -> Pubkey {
+    address(&[b"allowlist_bitmap", mint.as_ref(), &page_index.to_le_bytes()])
+}
+
+pub fn allowlist_tree(mint: &Pubkey) -> Pubkey {
+    address(&[b"allowlist_tree", mint.as_ref()])
+}
+
+pub fn trusted_caller(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    address(&[b"trusted_caller", mint.as_ref(), program_id.as_ref()])
+}
+
+pub fn treasury(mint: &Pubkey) -> Pubkey {
+    address(&[b"treasury", mint.as_ref()])
+}
+
+pub fn fee_treasury(mint: &Pubkey) -> Pubkey {
+    address(&[b"fee_treasury", mint.as_ref()])
+}
+
+pub fn country_rule(mint: &Pubkey, country_code: [u8; 2]) -> Pubkey {
+    address(&[b"country_rule", mint.as_ref(), &country_code])
+}
+
+pub fn destination_registry(mint: &Pubkey) -> Pubkey {
+    address(&[b"destination_registry", mint.as_ref()])
+}
+
+pub fn velocity_counter(mint: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    address(&[b"velocity", mint.as_ref(), wallet.as_ref()])
+}
+
+pub fn circuit_breaker(mint: &Pubkey) -> Pubkey {
+    address(&[b"circuit_breaker", mint.as_ref()])
+}
+
+pub fn blackout_window(mint: &Pubkey, blackout_id: u64) -> Pubkey {
+    address(&[b"blackout", mint.as_ref(), &blackout_id.to_le_bytes()])
+}
+
+pub fn permit_nonce(mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    address(&[b"permit_nonce", mint.as_ref(), owner.as_ref()])
+}
+
+pub fn sale_proposal(mint: &Pubkey, seller: &Pubkey) -> Pubkey {
+    address(&[b"sale_proposal", mint.as_ref(), seller.as_ref()])
+}
+
+pub fn share_class(mint: &Pubkey) -> Pubkey {
+    address(&[b"share_class", mint.as_ref()])
+}
+
+pub fn transfer_journal(mint: &Pubkey) -> Pubkey {
+    address(&[b"transfer_journal", mint.as_ref()])
+}
+
+pub fn compliance_digest(mint: &Pubkey, period_start: i64) -> Pubkey {
+    address(&[b"digest", mint.as_ref(), &period_start.to_le_bytes()])
+}
+
+pub fn timelock(mint: &Pubkey) -> Pubkey {
+    address(&[b"timelock", mint.as_ref()])
+}
+
+pub fn pending_operation(mint: &Pubkey, operation_id: u64) -> Pubkey {
+    address(&[b"pending_operation", mint.as_ref(), &operation_id.to_le_bytes()])
+}
+
+pub fn registrar(mint: &Pubkey) -> Pubkey {
+    address(&[b"registrar", mint.as_ref()])
+}
+
+pub fn voter_weight_record(realm: &Pubkey, mint: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"voter-weight-record", realm.as_ref(), mint.as_ref(), holder.as_ref()])
+}
+
+pub fn max_voter_weight_record(realm: &Pubkey, mint: &Pubkey) -> Pubkey {
+    address(&[b"max-voter-weight-record", realm.as_ref(), mint.as_ref()])
+}
+
+// Cap table and holder index
+
+pub fn cap_table(mint: &Pubkey) -> Pubkey {
+    address(&[b"cap_table", mint.as_ref()])
+}
+
+pub fn holder_page(mint: &Pubkey, page_index: u32) -> Pubkey {
+    address(&[b"holder_page", mint.as_ref(), &page_index.to_le_bytes()])
+}
+
+pub fn holder_index(mint: &Pubkey) -> Pubkey {
+    address(&[b"holder_index", mint.as_ref()])
+}
+
+pub fn holder_number(mint: &Pubkey, number: u64) -> Pubkey {
+    address(&[b"holder_number", mint.as_ref(), &number.to_le_bytes()])
+}
+
+pub fn snapshot(mint: &Pubkey, snapshot_id: u64) -> Pubkey {
+    address(&[b"snapshot", mint.as_ref(), &snapshot_id.to_le_bytes()])
+}
+
+pub fn snapshot_balance(mint: &Pubkey, snapshot_id: u64, holder: &Pubkey) -> Pubkey {
+    address(&[b"snapshot_balance", mint.as_ref(), &snapshot_id.to_le_bytes(), holder.as_ref()])
+}
+
+pub fn certificate_tree(mint: &Pubkey) -> Pubkey {
+    address(&[b"certificate_tree", mint.as_ref()])
+}
+
+pub fn share_certificate(mint: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"share_certificate", mint.as_ref(), holder.as_ref()])
+}
+
+/// Bubblegum's config of a certificate tree
+pub fn bubblegum_tree_config(merkle_tree: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &gated_token::BUBBLEGUM_ID).0
+}
+
+/// Bubblegum asset ID of the certificate minted at `nonce` of `merkle_tree`
+pub fn certificate_asset(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()], &gated_token::BUBBLEGUM_ID).0
+}
+
+// Corporate actions
+
+pub fn split_config(original_mint: &Pubkey, new_mint: &Pubkey) -> Pubkey {
+    address(&[b"split_config", original_mint.as_ref(), new_mint.as_ref()])
+}
+
+pub fn split_receipt(split_config: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"receipt", split_config.as_ref(), holder.as_ref()])
+}
+
+pub fn distribution(mint: &Pubkey, distribution_id: u64) -> Pubkey {
+    address(&[b"distribution", mint.as_ref(), &distribution_id.to_le_bytes()])
+}
+
+pub fn distribution_vault(distribution: &Pubkey) -> Pubkey {
+    address(&[b"distribution_vault", distribution.as_ref()])
+}
+
+pub fn dividend_claim(distribution: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"dividend_claim", distribution.as_ref(), holder.as_ref()])
+}
+
+pub fn distribution_schedule(mint: &Pubkey) -> Pubkey {
+    address(&[b"distribution_schedule", mint.as_ref()])
+}
+
+pub fn schedule_vault(schedule: &Pubkey) -> Pubkey {
+    address(&[b"schedule_vault", schedule.as_ref()])
+}
+
+pub fn mint_schedule(mint: &Pubkey) -> Pubkey {
+    address(&[b"mint_schedule", mint.as_ref()])
+}
+
+pub fn allocation(mint: &Pubkey, allocation_id: u64) -> Pubkey {
+    address(&[b"allocation", mint.as_ref(), &allocation_id.to_le_bytes()])
+}
+
+pub fn redemption(mint: &Pubkey) -> Pubkey {
+    address(&[b"redemption", mint.as_ref()])
+}
+
+pub fn redemption_vault(mint: &Pubkey) -> Pubkey {
+    address(&[b"redemption_vault", mint.as_ref()])
+}
+
+pub fn tender_offer(mint: &Pubkey, offer_id: u64) -> Pubkey {
+    address(&[b"tender_offer", mint.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn tender_escrow(tender_offer: &Pubkey) -> Pubkey {
+    address(&[b"tender_escrow", tender_offer.as_ref()])
+}
+
+pub fn tender_payment_vault(tender_offer: &Pubkey) -> Pubkey {
+    address(&[b"tender_payment_vault", tender_offer.as_ref()])
+}
+
+pub fn tender_position(tender_offer: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"tender_position", tender_offer.as_ref(), holder.as_ref()])
+}
+
+pub fn rights_offering(mint: &Pubkey, offering_id: u64) -> Pubkey {
+    address(&[b"rights_offering", mint.as_ref(), &offering_id.to_le_bytes()])
+}
+
+pub fn rights_payment_vault(rights_offering: &Pubkey) -> Pubkey {
+    address(&[b"rights_payment_vault", rights_offering.as_ref()])
+}
+
+pub fn rights_subscription(rights_offering: &Pubkey, holder: &Pubkey) -> Pubkey {
+    address(&[b"rights_subscription", rights_offering.as_ref(), holder.as_ref()])
+}
+
+pub fn airdrop_campaign(mint: &Pubkey, campaign_id: u64) -> Pubkey {
+    address(&[b"airdrop", mint.as_ref(), &campaign_id.to_le_bytes()])
+}
+
+pub fn airdrop_vault(campaign: &Pubkey) -> Pubkey {
+    address(&[b"airdrop_vault", campaign.as_ref()])
+}
+
+pub fn airdrop_claim(campaign: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    address(&[b"airdrop_claim", campaign.as_ref(), wallet.as_ref()])
+}
+
+// Equity instruments
+
+pub fn vesting_schedule(mint: &Pubkey, vesting_id: u64) -> Pubkey {
+    address(&[b"vesting", mint.as_ref(), &vesting_id.to_le_bytes()])
+}
+
+pub fn vesting_vault(vesting_schedule: &Pubkey) -> Pubkey {
+    address(&[b"vesting_vault", vesting_schedule.as_ref()])
+}
+
+pub fn option_grant(mint: &Pubkey, grant_id: u64) -> Pubkey {
+    address(&[b"option_grant", mint.as_ref(), &grant_id.to_le_bytes()])
+}
+
+pub fn convertible_note(mint: &Pubkey, note_id: u64) -> Pubkey {
+    address(&[b"convertible_note", mint.as_ref(), &note_id.to_le_bytes()])
+}
+
+pub fn warrant(mint: &Pubkey, warrant_id: u64) -> Pubkey {
+    address(&[b"warrant", mint.as_ref(), &warrant_id.to_le_bytes()])
+}
+
+// Escrows, swaps, streams and wrapping
+
+pub fn escrow(mint: &Pubkey, escrow_id: u64) -> Pubkey {
+    address(&[b"escrow", mint.as_ref(), &escrow_id.to_le_bytes()])
+}
+
+pub fn escrow_vault(escrow: &Pubkey) -> Pubkey {
+    address(&[b"escrow_vault", escrow.as_ref()])
+}
+
+pub fn swap_offer(offered_mint: &Pubkey, offer_id: u64) -> Pubkey {
+    address(&[b"swap_offer", offered_mint.as_ref(), &offer_id.to_le_bytes()])
+}
+
+pub fn swap_vault(offer: &Pubkey) -> Pubkey {
+    address(&[b"swap_vault", offer.as_ref()])
+}
+
+pub fn stream(mint: &Pubkey, stream_id: u64) -> Pubkey {
+    address(&[b"stream", mint.as_ref(), &stream_id.to_le_bytes()])
+}
+
+pub fn stream_vault(stream: &Pubkey) -> Pubkey {
+    address(&[b"stream_vault", stream.as_ref()])
+}
+
+pub fn wrapper(mint: &Pubkey) -> Pubkey {
+    address(&[b"wrapper", mint.as_ref()])
+}
+
+pub fn wrapped_mint(mint: &Pubkey) -> Pubkey {
+    address(&[b"wrapped_mint", mint.as_ref()])
+}
+
+pub fn wrapper_vault(mint: &Pubkey) -> Pubkey {
+    address(&[b"wrapper_vault", mint.as_ref()])
+}
+
+// Governance
+
+pub fn authority_council(mint: &Pubkey) -> Pubkey {
+    address(&[b"authority_council", mint.as_ref()])
+}
+
+pub fn council_operation(council: &Pubkey, operation_id: u64) -> Pubkey {
+    address(&[b"council_operation", council.as_ref(), &operation_id.to_le_bytes()])
+}
+
+pub fn council_signer(council: &Pubkey) -> Pubkey {
+    address(&[b"council_signer", council.as_ref()])
+}
+
+pub fn governance_config(mint: &Pubkey) -> Pubkey {
+    address(&[b"governance_config", mint.as_ref()])
+}
+
+pub fn proposal(mint: &Pubkey, proposal_id: u64) -> Pubkey {
+    address(&[b"proposal", mint.as_ref(), &proposal_id.to_le_bytes()])
+}
+
+pub fn vote(proposal: &Pubkey, voter: &Pubkey) -> Pubkey {
+    address(&[b"vote", proposal.as_ref(), voter.as_ref()])
+}
+
+pub fn vote_delegation(mint: &Pubkey, delegator: &Pubkey) -> Pubkey {
+    address(&[b"vote_delegation", mint.as_ref(), delegator.as_ref()])
+}
+
+pub fn resolution(mint: &Pubkey, document_hash: &[u8; 32]) -> Pubkey {
+    address(&[b"resolution", mint.as_ref(), document_hash])
+}
+
+pub fn claim_topics(mint: &Pubkey) -> Pubkey {
+    address(&[b"claim_topics", mint.as_ref()])
+}
+
+pub fn identity(mint: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    address(&[b"identity", mint.as_ref(), wallet.as_ref()])
+}
+
+pub fn bridge_config(mint: &Pubkey) -> Pubkey {
+    address(&[b"bridge_config", mint.as_ref()])
+}
+
+pub fn bridge_vault(mint: &Pubkey) -> Pubkey {
+    address(&[b"bridge_vault", mint.as_ref()])
+}
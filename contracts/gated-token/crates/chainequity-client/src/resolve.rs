@@ -0,0 +1,243 @@
+//! Fill in the optional accounts a mint or transfer needs under the token's current
+//! configuration. The program rejects a balance change that leaves out an account a
+//! rule depends on (cap table pages, snapshot checkpoints, holder numbers, ...), so
+//! callers that only know the mint and the wallets resolve the rest here.
+//!
+//! Sanctions flag accounts live in the registered oracle's own program and are not
+//! resolved; tokens with a sanctions oracle must set them on the returned struct.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use gated_token::accounts::{GatedTransfer, MintTokens};
+use gated_token::{AllowlistEntry, CapTable, HolderIndex, HolderNumber, TokenConfig};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+
+use crate::accounts::{fetch, fetch_allowlist_entry, fetch_token_config};
+use crate::{pda, ClientError, Result};
+
+/// Accounts for `mint_tokens` of `amount` to `recipient`'s bound token account, or its
+/// associated token account if none is bound yet
+pub async fn mint_tokens(
+    rpc: &RpcClient,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<MintTokens> {
+    let token_config = fetch_token_config(rpc, mint).await?;
+    let entry = fetch_allowlist_entry(rpc, mint, recipient).await?;
+    let token_program = token_program(rpc, mint).await?;
+    let recipient_token_account = entry.approved_token_account.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(recipient, mint, &token_program)
+    });
+    let present = existing(rpc, &[
+        pda::country_rule(mint, entry.country_code),
+        pda::share_class(mint),
+    ]).await?;
+    let balance = token_balance(rpc, &recipient_token_account).await?;
+    let joining = balance == 0 && amount > 0;
+
+    let mut holder_number = None;
+    if token_config.holder_index != Pubkey::default() && joining && !entry.holder_indexed {
+        let index: HolderIndex = fetch(rpc, &token_config.holder_index).await?;
+        holder_number = Some(pda::holder_number(mint, index.len));
+    }
+
+    Ok(MintTokens {
+        authority: *authority,
+        recipient: *recipient,
+        token_config: pda::token_config(mint),
+        program_config: pda::program_config(),
+        mint: *mint,
+        mint_authority: pda::mint_authority(mint),
+        recipient_token_account,
+        recipient_allowlist_entry: pda::allowlist_entry(mint, recipient),
+        recipient_country_rule: present[0].then(|| pda::country_rule(mint, entry.country_code)),
+        recipient_sanctions_flag: None,
+        share_class: present[1].then(|| pda::share_class(mint)),
+        recipient_snapshot_balance: snapshot_checkpoint(&token_config, &entry),
+        recipient_holder_page: holder_page(rpc, &token_config, &entry).await?,
+        holder_index: (token_config.holder_index != Pubkey::default()).then_some(token_config.holder_index),
+        recipient_holder_number: holder_number,
+        instructions: None,
+        trusted_caller: None,
+        token_program,
+        system_program: system_program::ID,
+    })
+}
+
+/// Accounts for a `gated_transfer` of `amount` between the two wallets' bound token
+/// accounts, or their associated token accounts where none is bound yet
+pub async fn gated_transfer(
+    rpc: &RpcClient,
+    mint: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+) -> Result<GatedTransfer> {
+    let token_config = fetch_token_config(rpc, mint).await?;
+    let sender_entry = fetch_allowlist_entry(rpc, mint, sender).await?;
+    let recipient_entry = fetch_allowlist_entry(rpc, mint, recipient).await?;
+    let token_program = token_program(rpc, mint).await?;
+    let token_account = |wallet: &Pubkey, entry: &AllowlistEntry| {
+        entry.approved_token_account.unwrap_or_else(|| {
+            get_associated_token_address_with_program_id(wallet, mint, &token_program)
+        })
+    };
+    let from_token_account = token_account(sender, &sender_entry);
+    let to_token_account = token_account(recipient, &recipient_entry);
+
+    let optional = [
+        pda::destination_registry(mint),
+        pda::fee_treasury(mint),
+        pda::country_rule(mint, sender_entry.country_code),
+        pda::country_rule(mint, recipient_entry.country_code),
+        pda::share_class(mint),
+        pda::sale_proposal(mint, sender),
+        pda::transfer_journal(mint),
+    ];
+    let present = existing(rpc, &optional).await?;
+    let only_present = |index: usize| present[index].then_some(optional[index]);
+
+    let sender_page = holder_page(rpc, &token_config, &sender_entry).await?;
+    let recipient_page = holder_page(rpc, &token_config, &recipient_entry).await?
+        .filter(|page| Some(*page) != sender_page);
+
+    let mut holder_index = None;
+    let (mut sender_number, mut last_number, mut recipient_number) = (None, None, None);
+    if token_config.holder_index != Pubkey::default() && from_token_account != to_token_account {
+        let index: HolderIndex = fetch(rpc, &token_config.holder_index).await?;
+        let leaving = sender_entry.holder_indexed
+            && token_balance(rpc, &from_token_account).await? == amount;
+        let joining = !recipient_entry.holder_indexed && amount > 0;
+        if leaving {
+            sender_number = Some(holder_number_of(rpc, mint, sender).await?);
+            if !joining {
+                last_number = Some(pda::holder_number(mint, index.len.saturating_sub(1)));
+            }
+        } else if joining {
+            recipient_number = Some(pda::holder_number(mint, index.len));
+        }
+        holder_index = Some(token_config.holder_index);
+    }
+
+    Ok(GatedTransfer {
+        authority: *sender,
+        recipient: *recipient,
+        token_config: pda::token_config(mint),
+        program_config: pda::program_config(),
+        mint: *mint,
+        from_token_account,
+        to_token_account,
+        sender_allowlist_entry: pda::allowlist_entry(mint, sender),
+        recipient_allowlist_entry: pda::allowlist_entry(mint, recipient),
+        destination_registry: only_present(0),
+        fee_treasury: only_present(1),
+        velocity_counter: (token_config.daily_transfer_limit > 0)
+            .then(|| pda::velocity_counter(mint, sender)),
+        sender_country_rule: only_present(2),
+        recipient_country_rule: only_present(3),
+        sender_sanctions_flag: None,
+        recipient_sanctions_flag: None,
+        sender_snapshot_balance: snapshot_checkpoint(&token_config, &sender_entry),
+        share_class: only_present(4),
+        recipient_snapshot_balance: snapshot_checkpoint(&token_config, &recipient_entry),
+        sender_holder_page: sender_page,
+        recipient_holder_page: recipient_page,
+        sale_proposal: only_present(5),
+        holder_index,
+        sender_holder_number: sender_number,
+        last_holder_number: last_number,
+        recipient_holder_number: recipient_number,
+        transfer_journal: only_present(6),
+        token_program,
+        system_program: system_program::ID,
+    })
+}
+
+/// Token program that owns `mint`, SPL Token or Token-2022
+pub async fn token_program(rpc: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = rpc
+        .get_account_with_commitment(mint, rpc.commitment())
+        .await
+        .map_err(Box::new)?
+        .value
+        .ok_or(ClientError::AccountNotFound(*mint))?;
+    Ok(account.owner)
+}
+
+/// Whether each of `addresses` exists
+async fn existing(rpc: &RpcClient, addresses: &[Pubkey]) -> Result<Vec<bool>> {
+    let accounts = rpc.get_multiple_accounts(addresses).await.map_err(Box::new)?;
+    Ok(accounts.iter().map(Option::is_some).collect())
+}
+
+/// Balance of a token account, zero if it does not exist yet
+async fn token_balance(rpc: &RpcClient, token_account: &Pubkey) -> Result<u64> {
+    let account = rpc
+        .get_account_with_commitment(token_account, rpc.commitment())
+        .await
+        .map_err(Box::new)?
+        .value;
+    // SPL token account layout: mint, owner, then the u64 amount
+    Ok(account
+        .and_then(|account| account.data.get(64..72).map(|amount| u64::from_le_bytes(amount.try_into().unwrap())))
+        .unwrap_or_default())
+}
+
+/// Checkpoint account for the wallet's first balance change since the latest snapshot,
+/// see `checkpoint_snapshot_balance`
+fn snapshot_checkpoint(token_config: &TokenConfig, entry: &AllowlistEntry) -> Option<Pubkey> {
+    (entry.last_snapshot_id < token_config.current_snapshot_id).then(|| {
+        pda::snapshot_balance(&token_config.mint, token_config.current_snapshot_id, &entry.wallet)
+    })
+}
+
+/// Cap table page the wallet is on, or the newest page for a wallet about to join
+async fn holder_page(rpc: &RpcClient, token_config: &TokenConfig, entry: &AllowlistEntry) -> Result<Option<Pubkey>> {
+    if token_config.cap_table == Pubkey::default() {
+        return Ok(None);
+    }
+    let page_index = match entry.holder_page {
+        Some(page_index) => page_index,
+        None => {
+            let cap_table: CapTable = fetch(rpc, &token_config.cap_table).await?;
+            match cap_table.next_page_index.checked_sub(1) {
+                Some(page_index) => page_index,
+                None => return Ok(None),
+            }
+        }
+    };
+    Ok(Some(pda::holder_page(&token_config.mint, page_index)))
+}
+
+/// Holder number account assigned to `wallet`, found by scanning the token's numbers
+async fn holder_number_of(rpc: &RpcClient, mint: &Pubkey, wallet: &Pubkey) -> Result<Pubkey> {
+    use anchor_lang::Discriminator;
+
+    // HolderNumber layout: discriminator, token_mint, number, wallet
+    let filters = vec![
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, HolderNumber::DISCRIMINATOR.to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(8, mint.to_bytes().to_vec())),
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(48, wallet.to_bytes().to_vec())),
+    ];
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            commitment: Some(rpc.commitment()),
+            ..RpcAccountInfoConfig::default()
+        },
+        ..RpcProgramAccountsConfig::default()
+    };
+    let accounts = rpc
+        .get_program_accounts_with_config(&gated_token::ID, config)
+        .await
+        .map_err(Box::new)?;
+    accounts
+        .first()
+        .map(|(address, _)| *address)
+        .ok_or(ClientError::HolderNumberNotFound(*wallet))
+}
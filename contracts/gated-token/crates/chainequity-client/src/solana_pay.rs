@@ -0,0 +1,19 @@
+//! Solana Pay transaction requests for gated transfers. A wallet that opens a
+//! [`transfer_request`] link asks the endpoint it names for the transaction to sign,
+//! and the endpoint in the `chainequity-pay` crate only returns one once the payer and
+//! recipient both pass the allowlist checks, so a link cannot lead to a transfer the
+//! program would reject on those grounds.
+
+use anchor_lang::prelude::Pubkey;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Everything but RFC 3986's unreserved characters
+const LINK: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// `solana:` link for the wallet that opens it to transfer `amount` base units of
+/// `mint` to `recipient`, fetching the transaction from `endpoint` (an `https` URL
+/// without a query string)
+pub fn transfer_request(endpoint: &str, mint: &Pubkey, recipient: &Pubkey, amount: u64) -> String {
+    let link = format!("{endpoint}?mint={mint}&recipient={recipient}&amount={amount}");
+    format!("solana:{}", utf8_percent_encode(&link, LINK))
+}
@@ -0,0 +1,193 @@
+//! Squads v4 multisig support, for issuers whose token `authority` (or compliance
+//! officer, guardian, ...) is a Squads vault PDA. The vault only signs through Squads'
+//! `vault_transaction_execute`, so gated-token instructions built with the vault as
+//! their signer are wrapped into a vault transaction and its proposal with [`propose`],
+//! voted on with [`approve`] and run with [`execute`].
+//!
+//! - Instructions that need a fresh keypair signer, such as a new mint, take one of the
+//!   transaction's [`ephemeral_signer`]s instead
+//! - Tokens that restrict CPI callers must `trust_caller` [`PROGRAM_ID`] first, since
+//!   Squads is the program that starts the call chain
+//! - Off-chain signatures, like compliance-officer transfer permits, cannot come from a
+//!   vault
+
+use anchor_lang::prelude::{pubkey, Pubkey};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use solana_message::Message;
+use solana_sha256_hasher::hash;
+
+/// Squads v4 multisig program
+pub const PROGRAM_ID: Pubkey = pubkey!("SQDS4ep65T869zMMBKyuUq6aD6EgTu8psMjkvj52pCf");
+
+fn address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &PROGRAM_ID).0
+}
+
+pub fn multisig(create_key: &Pubkey) -> Pubkey {
+    address(&[b"multisig", b"multisig", create_key.as_ref()])
+}
+
+/// Vault PDA that holds assets and signs for the multisig; index 0 is the default vault
+pub fn vault(multisig: &Pubkey, vault_index: u8) -> Pubkey {
+    address(&[b"multisig", multisig.as_ref(), b"vault", &[vault_index]])
+}
+
+pub fn transaction(multisig: &Pubkey, transaction_index: u64) -> Pubkey {
+    address(&[b"multisig", multisig.as_ref(), b"transaction", &transaction_index.to_le_bytes()])
+}
+
+pub fn proposal(multisig: &Pubkey, transaction_index: u64) -> Pubkey {
+    address(&[
+        b"multisig",
+        multisig.as_ref(),
+        b"transaction",
+        &transaction_index.to_le_bytes(),
+        b"proposal",
+    ])
+}
+
+/// PDA Squads signs with in place of the transaction's `index`th fresh keypair
+pub fn ephemeral_signer(transaction: &Pubkey, index: u8) -> Pubkey {
+    address(&[b"multisig", transaction.as_ref(), b"ephemeral_signer", &[index]])
+}
+
+/// Index the next vault transaction must take, read from a `Multisig` account's data:
+/// discriminator, create key, config authority, threshold (u16), time lock (u32), then
+/// the index of the last transaction
+pub fn next_transaction_index(multisig_data: &[u8]) -> Option<u64> {
+    let last = multisig_data.get(78..86)?;
+    u64::from_le_bytes(last.try_into().unwrap()).checked_add(1)
+}
+
+fn instruction_data(name: &str, args: &[u8]) -> Vec<u8> {
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    data.extend_from_slice(args);
+    data
+}
+
+/// `instructions` compiled with the vault as payer, and how many of its accounts are
+/// signers, writable signers and writable non-signers
+fn compile(vault: &Pubkey, instructions: &[Instruction]) -> (Message, u8, u8, u8) {
+    let message = Message::new(instructions, Some(vault));
+    let header = message.header;
+    let signers = header.num_required_signatures;
+    let writable_signers = signers - header.num_readonly_signed_accounts;
+    let writable_non_signers = message.account_keys.len() as u8 - signers - header.num_readonly_unsigned_accounts;
+    (message, signers, writable_signers, writable_non_signers)
+}
+
+/// Squads' compact `TransactionMessage` encoding of `instructions` run by `vault`.
+/// Lengths of its arrays are a single byte, except instruction data, which takes two.
+pub fn transaction_message(vault: &Pubkey, instructions: &[Instruction]) -> Vec<u8> {
+    let (message, signers, writable_signers, writable_non_signers) = compile(vault, instructions);
+    let mut encoded = vec![signers, writable_signers, writable_non_signers];
+    encoded.push(message.account_keys.len() as u8);
+    for key in &message.account_keys {
+        encoded.extend_from_slice(key.as_ref());
+    }
+    encoded.push(message.instructions.len() as u8);
+    for instruction in &message.instructions {
+        encoded.push(instruction.program_id_index);
+        encoded.push(instruction.accounts.len() as u8);
+        encoded.extend_from_slice(&instruction.accounts);
+        encoded.extend_from_slice(&(instruction.data.len() as u16).to_le_bytes());
+        encoded.extend_from_slice(&instruction.data);
+    }
+    // No address lookup tables
+    encoded.push(0);
+    encoded
+}
+
+/// Vault transaction running `instructions` as vault `vault_index`, and a proposal to
+/// approve it at `transaction_index` (see [`next_transaction_index`]). `creator` must be a
+/// member allowed to initiate and pays the rent of both accounts.
+pub fn propose(
+    multisig: &Pubkey,
+    vault_index: u8,
+    transaction_index: u64,
+    creator: &Pubkey,
+    instructions: &[Instruction],
+    ephemeral_signers: u8,
+) -> [Instruction; 2] {
+    let message = transaction_message(&vault(multisig, vault_index), instructions);
+    let mut args = vec![vault_index, ephemeral_signers];
+    args.extend_from_slice(&(message.len() as u32).to_le_bytes());
+    args.extend_from_slice(&message);
+    // No memo
+    args.push(0);
+    let create_transaction = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(transaction(multisig, transaction_index), false),
+            AccountMeta::new_readonly(*creator, true),
+            AccountMeta::new(*creator, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: instruction_data("vault_transaction_create", &args),
+    };
+
+    let mut args = transaction_index.to_le_bytes().to_vec();
+    // Not a draft: open for voting straight away
+    args.push(0);
+    let create_proposal = Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(proposal(multisig, transaction_index), false),
+            AccountMeta::new_readonly(*creator, true),
+            AccountMeta::new(*creator, true),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: instruction_data("proposal_create", &args),
+    };
+    [create_transaction, create_proposal]
+}
+
+/// `member`'s vote for the proposal at `transaction_index`
+pub fn approve(multisig: &Pubkey, transaction_index: u64, member: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(*member, true),
+            AccountMeta::new(proposal(multisig, transaction_index), false),
+        ],
+        // No memo
+        data: instruction_data("proposal_approve", &[0]),
+    }
+}
+
+/// Run an approved vault transaction. `instructions` must be the ones it was proposed
+/// with: their accounts follow Squads' own, in the order the transaction message lists
+/// them, with the vault and ephemeral signers left for Squads to sign.
+pub fn execute(
+    multisig: &Pubkey,
+    vault_index: u8,
+    transaction_index: u64,
+    member: &Pubkey,
+    instructions: &[Instruction],
+) -> Instruction {
+    let (message, signers, writable_signers, writable_non_signers) =
+        compile(&vault(multisig, vault_index), instructions);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*multisig, false),
+        AccountMeta::new(proposal(multisig, transaction_index), false),
+        AccountMeta::new_readonly(transaction(multisig, transaction_index), false),
+        AccountMeta::new_readonly(*member, true),
+    ];
+    accounts.extend(message.account_keys.iter().enumerate().map(|(index, key)| {
+        let is_writable = if index < usize::from(signers) {
+            index < usize::from(writable_signers)
+        } else {
+            index < usize::from(signers + writable_non_signers)
+        };
+        AccountMeta { pubkey: *key, is_signer: false, is_writable }
+    }));
+    Instruction {
+        program_id: PROGRAM_ID,
+        accounts,
+        data: instruction_data("vault_transaction_execute", &[]),
+    }
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{Discriminator, Event};
+use base64::Engine;
+use chainequity_client::gated_token::{self, IssuerCreatedEvent, RequireMemoUpdatedEvent, TokensMintedEvent};
+use chainequity_client::{events, instructions, pda, solana_pay, squads};
+
+fn minted(amount: u64) -> TokensMintedEvent {
+    TokensMintedEvent {
+        schema_version: gated_token::EVENT_SCHEMA_VERSION,
+        sequence: 7,
+        token_mint: Pubkey::new_unique(),
+        recipient: Pubkey::new_unique(),
+        amount,
+        new_supply: amount,
+    }
+}
+
+fn data_line(event: &impl Event) -> String {
+    format!("Program data: {}", base64::engine::general_purpose::STANDARD.encode(event.data()))
+}
+
+#[test]
+fn decodes_only_events_logged_by_gated_token() {
+    let program = gated_token::ID;
+    let other = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {program} invoke [1]"),
+        "Program log: Instruction: MintTokens".to_string(),
+        data_line(&minted(10)),
+        format!("Program {other} invoke [2]"),
+        data_line(&minted(99)),
+        format!("Program {other} success"),
+        data_line(&minted(20)),
+        format!("Program {program} consumed 30000 of 200000 compute units"),
+        format!("Program {program} success"),
+        data_line(&minted(30)),
+    ];
+
+    let minted: Vec<TokensMintedEvent> = events::decode_all(&logs).unwrap();
+    let amounts: Vec<u64> = minted.iter().map(|event| event.amount).collect();
+    assert_eq!(amounts, [10, 20]);
+
+    let memo: Vec<RequireMemoUpdatedEvent> = events::decode_all(&logs).unwrap();
+    assert!(memo.is_empty());
+}
+
+#[test]
+fn names_events_and_reads_their_sequence() {
+    let payload = minted(5).data();
+    assert_eq!(events::name(&payload), Some("TokensMintedEvent"));
+    assert_eq!(events::sequence(&payload), Some(7));
+
+    let issuer = IssuerCreatedEvent {
+        schema_version: gated_token::EVENT_SCHEMA_VERSION,
+        issuer: Pubkey::new_unique(),
+        authority: Pubkey::new_unique(),
+        name: "Acme Inc.".to_string(),
+        timestamp: 0,
+    }
+    .data();
+    assert_eq!(events::name(&issuer), Some("IssuerCreatedEvent"));
+    assert_eq!(events::sequence(&issuer), None);
+    assert_eq!(events::name(&[0; 16]), None);
+}
+
+#[test]
+fn builders_encode_discriminator_and_accounts() {
+    let mint = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let instruction = instructions::set_require_memo(
+        gated_token::accounts::UpdateTokenSettings {
+            authority,
+            token_config: pda::token_config(&mint),
+        },
+        true,
+    );
+
+    assert_eq!(instruction.program_id, gated_token::ID);
+    assert_eq!(instruction.accounts[0].pubkey, authority);
+    assert!(instruction.accounts[0].is_signer);
+    assert_eq!(instruction.accounts[1].pubkey, pda::token_config(&mint));
+    assert!(instruction.accounts[1].is_writable);
+    let discriminator = gated_token::instruction::SetRequireMemo::DISCRIMINATOR;
+    assert_eq!(&instruction.data[..discriminator.len()], discriminator);
+    assert_eq!(instruction.data[discriminator.len()..], [1]);
+}
+
+#[test]
+fn squads_proposals_run_instructions_as_the_vault() {
+    let multisig = squads::multisig(&Pubkey::new_unique());
+    let vault = squads::vault(&multisig, 0);
+    assert!(!vault.is_on_curve());
+    let member = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let set_memo = instructions::set_require_memo(
+        gated_token::accounts::UpdateTokenSettings {
+            authority: vault,
+            token_config: pda::token_config(&mint),
+        },
+        true,
+    );
+
+    let mut multisig_data = vec![0; 86];
+    multisig_data[78] = 4;
+    let index = squads::next_transaction_index(&multisig_data).unwrap();
+    assert_eq!(index, 5);
+
+    // The vault is the only signer; the config is writable and the program read-only
+    let message = squads::transaction_message(&vault, std::slice::from_ref(&set_memo));
+    assert_eq!(message[..4], [1, 1, 1, 3]);
+    assert_eq!(message[4..36], vault.to_bytes());
+
+    let [create_transaction, create_proposal] =
+        squads::propose(&multisig, 0, index, &member, std::slice::from_ref(&set_memo), 0);
+    assert_eq!(create_transaction.accounts[1].pubkey, squads::transaction(&multisig, index));
+    assert!(create_transaction.data.ends_with(&[0]));
+    assert_eq!(create_proposal.accounts[1].pubkey, squads::proposal(&multisig, index));
+    assert_eq!(squads::approve(&multisig, index, &member).accounts[2].pubkey, squads::proposal(&multisig, index));
+
+    let execute = squads::execute(&multisig, 0, index, &member, &[set_memo]);
+    let inner = &execute.accounts[4..];
+    assert_eq!(inner.len(), 3);
+    assert_eq!((inner[0].pubkey, inner[0].is_signer, inner[0].is_writable), (vault, false, true));
+    assert_eq!((inner[1].pubkey, inner[1].is_writable), (pda::token_config(&mint), true));
+    assert_eq!((inner[2].pubkey, inner[2].is_writable), (gated_token::ID, false));
+}
+
+#[test]
+fn solana_pay_links_carry_the_encoded_request_endpoint() {
+    let (mint, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let link = solana_pay::transfer_request("https://pay.example.com/transfer", &mint, &recipient, 2_500);
+
+    assert_eq!(
+        link,
+        format!("solana:https%3A%2F%2Fpay.example.com%2Ftransfer%3Fmint%3D{mint}%26recipient%3D{recipient}%26amount%3D2500"),
+    );
+}
@@ -0,0 +1,117 @@
+//! Geyser plugin that streams gated-token compliance state as it changes: every
+//! `AllowlistEntry` write and every token-account write for a watched mint becomes one
+//! JSON line (see [`record::ChangeRecord`]) sent to each client connected over TCP.
+//!
+//! Load it with a validator config file such as
+//!
+//! ```json
+//! {
+//!     "libpath": "/path/to/libchainequity_geyser.so",
+//!     "bind_address": "127.0.0.1:9810",
+//!     "mints": [],
+//!     "queue_capacity": 65536
+//! }
+//! ```
+//!
+//! An empty `mints` list watches every gated-token mint. Accounts loaded from the
+//! snapshot at start-up are used to learn mints but are not streamed.
+
+pub mod record;
+pub mod stream;
+
+use std::str::FromStr;
+
+use agave_geyser_plugin_interface::geyser_plugin_interface::{
+    GeyserPlugin, GeyserPluginError, ReplicaAccountInfoVersions, Result,
+};
+use anchor_lang::prelude::Pubkey;
+use serde::Deserialize;
+
+use crate::record::{AccountUpdate, Watcher};
+use crate::stream::Broadcaster;
+
+#[derive(Deserialize)]
+struct Config {
+    bind_address: String,
+    #[serde(default)]
+    mints: Vec<String>,
+    #[serde(default = "default_queue_capacity")]
+    queue_capacity: usize,
+}
+
+fn default_queue_capacity() -> usize {
+    65_536
+}
+
+fn config_error(msg: impl ToString) -> GeyserPluginError {
+    GeyserPluginError::ConfigFileReadError { msg: msg.to_string() }
+}
+
+#[derive(Default)]
+pub struct ComplianceStreamPlugin {
+    watcher: Option<Watcher>,
+    broadcaster: Option<Broadcaster>,
+}
+
+impl std::fmt::Debug for ComplianceStreamPlugin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComplianceStreamPlugin").finish_non_exhaustive()
+    }
+}
+
+impl GeyserPlugin for ComplianceStreamPlugin {
+    fn name(&self) -> &'static str {
+        "chainequity-geyser"
+    }
+
+    fn on_load(&mut self, config_file: &str, _is_reload: bool) -> Result<()> {
+        let config: Config = serde_json::from_str(&std::fs::read_to_string(config_file)?)
+            .map_err(config_error)?;
+        let mints = config.mints.iter()
+            .map(|mint| Pubkey::from_str(mint).map_err(|_| config_error(format!("invalid mint {mint}"))))
+            .collect::<Result<Vec<_>>>()?;
+        let broadcaster = Broadcaster::bind(&config.bind_address, config.queue_capacity)?;
+        log::info!("chainequity-geyser streaming on {}", config.bind_address);
+        self.watcher = Some(Watcher::new(mints));
+        self.broadcaster = Some(broadcaster);
+        Ok(())
+    }
+
+    fn update_account(&self, account: ReplicaAccountInfoVersions, slot: u64, is_startup: bool) -> Result<()> {
+        let (Some(watcher), Some(broadcaster)) = (&self.watcher, &self.broadcaster) else {
+            return Ok(());
+        };
+        let (address, owner, data, write_version) = match account {
+            ReplicaAccountInfoVersions::V0_0_1(info) => (info.pubkey, info.owner, info.data, info.write_version),
+            ReplicaAccountInfoVersions::V0_0_2(info) => (info.pubkey, info.owner, info.data, info.write_version),
+            ReplicaAccountInfoVersions::V0_0_3(info) => (info.pubkey, info.owner, info.data, info.write_version),
+        };
+        let (Ok(address), Ok(owner)) = (Pubkey::try_from(address), Pubkey::try_from(owner)) else {
+            return Ok(());
+        };
+        let record = watcher.observe(&AccountUpdate { address, owner, data, slot, write_version });
+        if let Some(record) = record.filter(|_| !is_startup) {
+            broadcaster.send(&record);
+        }
+        Ok(())
+    }
+
+    fn account_data_notifications_enabled(&self) -> bool {
+        true
+    }
+
+    fn transaction_notifications_enabled(&self) -> bool {
+        false
+    }
+}
+
+/// Entry point the validator looks up when loading the plugin
+///
+/// # Safety
+///
+/// Called once by the validator's plugin loader, which takes ownership of the box.
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub unsafe extern "C" fn _create_plugin() -> *mut dyn GeyserPlugin {
+    Box::into_raw(Box::<ComplianceStreamPlugin>::default())
+}
@@ -0,0 +1,150 @@
+//! Turn raw account updates into normalized change records. Only gated-token
+//! `AllowlistEntry` accounts and SPL token accounts of watched mints produce records.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+use chainequity_client::gated_token::{AllowlistEntry, TokenConfig};
+use chainequity_client::{accounts, pda};
+use serde::Serialize;
+
+/// SPL token account layout: mint, owner, amount, ..., state at byte 108
+const TOKEN_ACCOUNT_LEN: usize = 165;
+const TOKEN_ACCOUNT_STATE: usize = 108;
+const FROZEN: u8 = 2;
+
+/// One line of the stream, serialized as JSON with a `kind` tag
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeRecord {
+    AllowlistEntry {
+        slot: u64,
+        write_version: u64,
+        address: String,
+        mint: String,
+        wallet: String,
+        approved: bool,
+        revoked_at: Option<i64>,
+        lockup_until: Option<i64>,
+        transfer_locked: bool,
+        country_code: String,
+        approved_token_account: Option<String>,
+    },
+    TokenAccount {
+        slot: u64,
+        write_version: u64,
+        address: String,
+        mint: String,
+        owner: String,
+        amount: u64,
+        frozen: bool,
+    },
+}
+
+/// An account as the validator reports it
+pub struct AccountUpdate<'a> {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+    pub data: &'a [u8],
+    pub slot: u64,
+    pub write_version: u64,
+}
+
+/// Tracks which mints are watched. A fixed list comes from the plugin config; with an
+/// empty list every gated-token mint is watched, learned from its `TokenConfig`.
+pub struct Watcher {
+    fixed: bool,
+    mints: RwLock<HashSet<Pubkey>>,
+    /// Mint of each allowlist entry seen, as the entry does not store it
+    entry_mints: Mutex<HashMap<Pubkey, Pubkey>>,
+}
+
+impl Watcher {
+    pub fn new(mints: Vec<Pubkey>) -> Self {
+        Self {
+            fixed: !mints.is_empty(),
+            mints: RwLock::new(mints.into_iter().collect()),
+            entry_mints: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn observe(&self, update: &AccountUpdate) -> Option<ChangeRecord> {
+        if update.owner == chainequity_client::PROGRAM_ID {
+            if update.data.starts_with(TokenConfig::DISCRIMINATOR) {
+                self.learn_mint(update.data);
+                return None;
+            }
+            if update.data.starts_with(AllowlistEntry::DISCRIMINATOR) {
+                return self.allowlist_entry(update);
+            }
+            return None;
+        }
+        if update.owner == anchor_spl::token::ID || update.owner == anchor_spl::token_2022::ID {
+            return self.token_account(update);
+        }
+        None
+    }
+
+    fn learn_mint(&self, data: &[u8]) {
+        if self.fixed {
+            return;
+        }
+        // TokenConfig opens with the authority, then the mint
+        if let Some(mint) = data.get(40..72) {
+            self.mints.write().unwrap().insert(Pubkey::try_from(mint).unwrap());
+        }
+    }
+
+    fn allowlist_entry(&self, update: &AccountUpdate) -> Option<ChangeRecord> {
+        let entry: AllowlistEntry = accounts::decode(update.data).ok()?;
+        let mint = self.entry_mint(&update.address, &entry.wallet)?;
+        Some(ChangeRecord::AllowlistEntry {
+            slot: update.slot,
+            write_version: update.write_version,
+            address: update.address.to_string(),
+            mint: mint.to_string(),
+            wallet: entry.wallet.to_string(),
+            approved: entry.is_approved,
+            revoked_at: entry.revoked_at,
+            lockup_until: entry.lockup_until,
+            transfer_locked: entry.transfer_locked,
+            country_code: String::from_utf8_lossy(&entry.country_code).into_owned(),
+            approved_token_account: entry.approved_token_account.map(|account| account.to_string()),
+        })
+    }
+
+    /// Watched mint whose allowlist PDA for `wallet` is `address`
+    fn entry_mint(&self, address: &Pubkey, wallet: &Pubkey) -> Option<Pubkey> {
+        let mut entry_mints = self.entry_mints.lock().unwrap();
+        if let Some(mint) = entry_mints.get(address) {
+            return Some(*mint);
+        }
+        let mint = *self.mints.read().unwrap()
+            .iter()
+            .find(|mint| pda::allowlist_entry(mint, wallet) == *address)?;
+        entry_mints.insert(*address, mint);
+        Some(mint)
+    }
+
+    fn token_account(&self, update: &AccountUpdate) -> Option<ChangeRecord> {
+        // Token-2022 accounts may carry extensions after the base layout
+        if update.data.len() < TOKEN_ACCOUNT_LEN {
+            return None;
+        }
+        let mint = Pubkey::try_from(&update.data[..32]).unwrap();
+        if !self.mints.read().unwrap().contains(&mint) {
+            return None;
+        }
+        Some(ChangeRecord::TokenAccount {
+            slot: update.slot,
+            write_version: update.write_version,
+            address: update.address.to_string(),
+            mint: mint.to_string(),
+            owner: Pubkey::try_from(&update.data[32..64]).unwrap().to_string(),
+            amount: u64::from_le_bytes(update.data[64..72].try_into().unwrap()),
+            frozen: update.data[TOKEN_ACCOUNT_STATE] == FROZEN,
+        })
+    }
+}
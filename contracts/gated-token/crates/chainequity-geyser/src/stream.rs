@@ -0,0 +1,58 @@
+//! Newline-delimited JSON over TCP. Records are queued to a writer thread so a slow
+//! client never stalls the validator; when the queue is full new records are dropped
+//! and counted.
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::record::ChangeRecord;
+
+pub struct Broadcaster {
+    queue: SyncSender<String>,
+    dropped: AtomicU64,
+}
+
+impl Broadcaster {
+    /// Listen on `bind_address` and start the accept and writer threads
+    pub fn bind(bind_address: &str, capacity: usize) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(bind_address)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let (queue, records) = sync_channel(capacity);
+
+        let accepting = Arc::clone(&clients);
+        thread::Builder::new().name("chainequity-accept".into()).spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = stream.set_nodelay(true);
+                accepting.lock().unwrap().push(stream);
+            }
+        })?;
+        thread::Builder::new().name("chainequity-write".into()).spawn(move || write_all(records, clients))?;
+
+        Ok(Self {
+            queue,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub fn send(&self, record: &ChangeRecord) {
+        let line = serde_json::to_string(record).expect("records always serialize");
+        if let Err(TrySendError::Full(_)) = self.queue.try_send(line) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                log::warn!("chainequity-geyser queue full, {dropped} records dropped so far");
+            }
+        }
+    }
+}
+
+/// Write each record to every client, forgetting clients whose connection fails
+fn write_all(records: Receiver<String>, clients: Arc<Mutex<Vec<TcpStream>>>) {
+    for mut line in records {
+        line.push('\n');
+        clients.lock().unwrap().retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+}
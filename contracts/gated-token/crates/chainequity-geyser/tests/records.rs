@@ -0,0 +1,83 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountSerialize;
+use chainequity_client::gated_token::{AllowlistEntry, Versioned};
+use chainequity_client::{pda, PROGRAM_ID};
+use chainequity_geyser::record::{AccountUpdate, ChangeRecord, Watcher};
+
+fn entry(wallet: Pubkey) -> Vec<u8> {
+    let entry = AllowlistEntry {
+        wallet,
+        is_approved: true,
+        approved_at: 1,
+        revoked_at: None,
+        bump: 255,
+        lockup_until: Some(100),
+        transfer_locked: false,
+        is_insider: false,
+        approved_token_account: None,
+        country_code: *b"US",
+        last_snapshot_id: 0,
+        holder_page: None,
+        allocated_through: 0,
+        drip_enabled: false,
+        interest_index: 0,
+        accrued_interest: 0,
+        version: AllowlistEntry::CURRENT_VERSION,
+        holder_indexed: false,
+    };
+    let mut data = Vec::new();
+    entry.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn token_account(mint: Pubkey, owner: Pubkey, amount: u64, state: u8) -> Vec<u8> {
+    let mut data = vec![0; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    data[108] = state;
+    data
+}
+
+#[test]
+fn normalizes_allowlist_entries_of_watched_mints() {
+    let (mint, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let watcher = Watcher::new(vec![mint]);
+    let data = entry(wallet);
+    let update = |address| AccountUpdate { address, owner: PROGRAM_ID, data: &data, slot: 9, write_version: 1 };
+
+    let record = watcher.observe(&update(pda::allowlist_entry(&mint, &wallet))).unwrap();
+    let ChangeRecord::AllowlistEntry { mint: record_mint, approved, country_code, lockup_until, .. } = record else {
+        panic!("expected an allowlist record");
+    };
+    assert_eq!(record_mint, mint.to_string());
+    assert!(approved);
+    assert_eq!(country_code, "US");
+    assert_eq!(lockup_until, Some(100));
+
+    // The same wallet's entry for another mint is not watched
+    let other = pda::allowlist_entry(&Pubkey::new_unique(), &wallet);
+    assert!(watcher.observe(&update(other)).is_none());
+}
+
+#[test]
+fn normalizes_token_accounts_of_watched_mints() {
+    let (mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let watcher = Watcher::new(vec![mint]);
+    let data = token_account(mint, owner, 42, 2);
+    let update = AccountUpdate {
+        address: Pubkey::new_unique(),
+        owner: anchor_spl::token::ID,
+        data: &data,
+        slot: 3,
+        write_version: 7,
+    };
+    let json = serde_json::to_value(watcher.observe(&update).unwrap()).unwrap();
+    assert_eq!(json["kind"], "token_account");
+    assert_eq!(json["owner"], owner.to_string());
+    assert_eq!(json["amount"], 42);
+    assert_eq!(json["frozen"], true);
+
+    let unwatched = token_account(Pubkey::new_unique(), owner, 1, 1);
+    assert!(watcher.observe(&AccountUpdate { data: &unwatched, ..update }).is_none());
+}
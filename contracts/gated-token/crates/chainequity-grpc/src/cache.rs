@@ -0,0 +1,105 @@
+//! Account reads shared by every request. Each account, present or missing, is kept
+//! for the TTL, so a burst of checks on the same mint costs one RPC round trip.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+
+/// Accounts per `getMultipleAccounts` request, the RPC maximum
+const BATCH: usize = 100;
+
+type Entry<T> = (Instant, T);
+/// Owner and balance of each holder, largest first
+pub type Holders = Arc<Vec<(Pubkey, u64)>>;
+
+pub struct Cache {
+    pub rpc: RpcClient,
+    ttl: Duration,
+    accounts: Mutex<HashMap<Pubkey, Entry<Option<Arc<Account>>>>>,
+    holders: Mutex<HashMap<Pubkey, Entry<Holders>>>,
+}
+
+impl Cache {
+    pub fn new(rpc: RpcClient, ttl: Duration) -> Self {
+        Self {
+            rpc,
+            ttl,
+            accounts: Mutex::new(HashMap::new()),
+            holders: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Each of `addresses`, fetching all those not cached together
+    pub async fn accounts(&self, addresses: &[Pubkey]) -> Result<Vec<Option<Arc<Account>>>> {
+        let mut found = vec![None; addresses.len()];
+        let mut missing = Vec::new();
+        {
+            let accounts = self.accounts.lock().unwrap();
+            for (slot, address) in found.iter_mut().zip(addresses) {
+                match accounts.get(address) {
+                    Some((at, account)) if at.elapsed() < self.ttl => *slot = Some(account.clone()),
+                    _ => missing.push(*address),
+                }
+            }
+        }
+        if !missing.is_empty() {
+            let mut fetched = Vec::with_capacity(missing.len());
+            for chunk in missing.chunks(BATCH) {
+                fetched.extend(self.rpc.get_multiple_accounts(chunk).await?);
+            }
+            let now = Instant::now();
+            let mut accounts = self.accounts.lock().unwrap();
+            for (address, account) in missing.iter().zip(fetched) {
+                accounts.insert(*address, (now, account.map(Arc::new)));
+            }
+            for (slot, address) in found.iter_mut().zip(addresses) {
+                if slot.is_none() {
+                    *slot = Some(accounts[address].1.clone());
+                }
+            }
+        }
+        Ok(found.into_iter().map(Option::flatten).collect())
+    }
+
+    pub async fn account(&self, address: &Pubkey) -> Result<Option<Arc<Account>>> {
+        Ok(self.accounts(&[*address]).await?.remove(0))
+    }
+
+    /// Owner and balance of every non-empty token account of `mint`, summed per owner
+    pub async fn holders(&self, mint: &Pubkey, token_program: &Pubkey) -> Result<Holders> {
+        if let Some((at, holders)) = self.holders.lock().unwrap().get(mint) {
+            if at.elapsed() < self.ttl {
+                return Ok(holders.clone());
+            }
+        }
+        // Token account layout: mint, owner, amount; Token-2022 accounts may be longer
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, mint.to_bytes().to_vec()))]),
+            account_config: RpcAccountInfoConfig {
+                commitment: Some(self.rpc.commitment()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let mut balances = HashMap::<Pubkey, u64>::new();
+        for (_, account) in self.rpc.get_program_accounts_with_config(token_program, config).await? {
+            let Some(data) = account.data.get(..72) else { continue };
+            let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+            if amount > 0 {
+                *balances.entry(Pubkey::try_from(&data[32..64]).unwrap()).or_default() += amount;
+            }
+        }
+        let mut holders: Vec<(Pubkey, u64)> = balances.into_iter().collect();
+        holders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let holders = Arc::new(holders);
+        self.holders.lock().unwrap().insert(*mint, (Instant::now(), holders.clone()));
+        Ok(holders)
+    }
+}
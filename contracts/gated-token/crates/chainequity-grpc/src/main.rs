@@ -0,0 +1,52 @@
+//! `chainequity-grpc`: low-latency pre-trade checks for venues trading gated-token
+//! securities. Serves the `chainequity.v1.ComplianceQuery` service in
+//! `proto/chainequity.proto` from RPC reads cached for `--cache-ttl-ms`, so the answers
+//! may be that much behind the cluster; `CanTransfer` with `simulate` skips the cache.
+
+mod cache;
+mod service;
+
+mod proto {
+    tonic::include_proto!("chainequity.v1");
+}
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::Parser;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tonic::transport::Server;
+
+use crate::cache::Cache;
+use crate::proto::compliance_query_server::ComplianceQueryServer;
+use crate::service::Query;
+
+#[derive(Parser)]
+#[command(version, about = "gRPC allowlist and balance queries for ChainEquity tokens")]
+struct Args {
+    #[arg(short, long, env = "CHAINEQUITY_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Address the gRPC server listens on
+    #[arg(long, env = "CHAINEQUITY_GRPC_LISTEN", default_value = "127.0.0.1:50051")]
+    listen: SocketAddr,
+    /// How long account reads are reused
+    #[arg(long, default_value_t = 1_000)]
+    cache_ttl_ms: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let rpc = RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed());
+    let query = Query {
+        cache: Cache::new(rpc, Duration::from_millis(args.cache_ttl_ms)),
+    };
+    println!("serving chainequity.v1.ComplianceQuery on {}", args.listen);
+    Server::builder()
+        .add_service(ComplianceQueryServer::new(query))
+        .serve(args.listen)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! `ComplianceQuery` implementation over the account cache.
+
+// Handlers return tonic's `Status`, so the helpers they call do too
+#![allow(clippy::result_large_err)]
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chainequity_client::check::{self, token_account, Transfer};
+use chainequity_client::gated_token::{AllowlistEntry, TokenConfig};
+use chainequity_client::{accounts, instructions, pda, resolve};
+use solana_rpc_client_api::config::RpcSimulateTransactionConfig;
+use solana_sdk::account::Account;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+use tonic::{Request, Response, Status};
+
+use crate::cache::Cache;
+use crate::proto::compliance_query_server::ComplianceQuery;
+use crate::proto::{
+    CanTransferRequest, CanTransferResponse, GetWalletStatusRequest, Holder, ListHoldersRequest,
+    ListHoldersResponse, WalletStatus,
+};
+
+pub struct Query {
+    pub cache: Cache,
+}
+
+fn address(value: &str, field: &str) -> Result<Pubkey, Status> {
+    Pubkey::from_str(value).map_err(|_| Status::invalid_argument(format!("{field} is not a base58 address")))
+}
+
+fn unavailable(error: impl std::fmt::Display) -> Status {
+    Status::unavailable(error.to_string())
+}
+
+fn token_config(account: Option<&Arc<Account>>, mint: &Pubkey) -> Result<TokenConfig, Status> {
+    let account = account.ok_or_else(|| Status::not_found(format!("{mint} is not a gated-token mint")))?;
+    accounts::decode(&account.data).map_err(unavailable)
+}
+
+fn entry(account: Option<&Arc<Account>>) -> Option<AllowlistEntry> {
+    account.and_then(|account| accounts::decode(&account.data).ok())
+}
+
+/// Token program of a mint account
+fn token_program(mint: Option<&Arc<Account>>, address: &Pubkey) -> Result<Pubkey, Status> {
+    mint.map(|account| account.owner)
+        .ok_or_else(|| Status::not_found(format!("mint {address} does not exist")))
+}
+
+fn balance(account: Option<&Arc<Account>>) -> u64 {
+    account
+        .and_then(|account| account.data.get(64..72))
+        .map_or(0, |amount| u64::from_le_bytes(amount.try_into().unwrap()))
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+impl Query {
+    /// Run the transfer against the cluster, unsigned, and report the first error
+    async fn simulate(&self, mint: &Pubkey, from: &Pubkey, to: &Pubkey, amount: u64) -> Result<CanTransferResponse, Status> {
+        let rpc = &self.cache.rpc;
+        let accounts = resolve::gated_transfer(rpc, mint, from, to, amount).await.map_err(unavailable)?;
+        let message = Message::new(&[instructions::gated_transfer(accounts, amount, None)], Some(from));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(rpc.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = rpc.simulate_transaction_with_config(&Transaction::new_unsigned(message), config).await
+            .map_err(unavailable)?
+            .value;
+        let Some(error) = result.err else {
+            return Ok(CanTransferResponse { allowed: true, simulated: true, ..CanTransferResponse::default() });
+        };
+        // Anchor logs "... Error Code: <name>. Error Number: <code>. ..."
+        let logged = result.logs.unwrap_or_default().iter().find_map(|log| {
+            let (_, rest) = log.split_once("Error Code: ")?;
+            let (name, rest) = rest.split_once(". Error Number: ")?;
+            let code = rest.split('.').next()?.parse().ok()?;
+            Some((name.to_string(), code))
+        });
+        let (reason, error_code) = logged.unwrap_or_else(|| (error.to_string(), 0));
+        Ok(CanTransferResponse { allowed: false, reason, error_code, simulated: true })
+    }
+}
+
+#[tonic::async_trait]
+impl ComplianceQuery for Query {
+    async fn get_wallet_status(&self, request: Request<GetWalletStatusRequest>) -> Result<Response<WalletStatus>, Status> {
+        let request = request.into_inner();
+        let mint = address(&request.mint, "mint")?;
+        let wallet = address(&request.wallet, "wallet")?;
+        let fetched = self.cache.accounts(&[mint, pda::allowlist_entry(&mint, &wallet)]).await.map_err(unavailable)?;
+        let token_program = token_program(fetched[0].as_ref(), &mint)?;
+        let entry = entry(fetched[1].as_ref());
+        let token_account = token_account(&wallet, &mint, &token_program, entry.as_ref());
+        let balance = balance(self.cache.account(&token_account).await.map_err(unavailable)?.as_ref());
+
+        Ok(Response::new(WalletStatus {
+            mint: request.mint,
+            wallet: request.wallet,
+            listed: entry.is_some(),
+            approved: entry.as_ref().is_some_and(|entry| entry.is_approved),
+            approved_at: entry.as_ref().map(|entry| entry.approved_at),
+            revoked_at: entry.as_ref().and_then(|entry| entry.revoked_at),
+            lockup_until: entry.as_ref().and_then(|entry| entry.lockup_until),
+            transfer_locked: entry.as_ref().is_some_and(|entry| entry.transfer_locked),
+            insider: entry.as_ref().is_some_and(|entry| entry.is_insider),
+            country_code: entry.as_ref()
+                .filter(|entry| entry.country_code != [0; 2])
+                .map(|entry| String::from_utf8_lossy(&entry.country_code).into_owned())
+                .unwrap_or_default(),
+            token_account: token_account.to_string(),
+            balance,
+        }))
+    }
+
+    async fn can_transfer(&self, request: Request<CanTransferRequest>) -> Result<Response<CanTransferResponse>, Status> {
+        let request = request.into_inner();
+        let mint = address(&request.mint, "mint")?;
+        let from = address(&request.from, "from")?;
+        let to = address(&request.to, "to")?;
+        if request.simulate {
+            return self.simulate(&mint, &from, &to, request.amount).await.map(Response::new);
+        }
+
+        let fetched = self.cache.accounts(&[
+            pda::token_config(&mint),
+            mint,
+            pda::allowlist_entry(&mint, &from),
+            pda::allowlist_entry(&mint, &to),
+        ]).await.map_err(unavailable)?;
+        let config = token_config(fetched[0].as_ref(), &mint)?;
+        let token_program = token_program(fetched[1].as_ref(), &mint)?;
+        let (sender, recipient) = (entry(fetched[2].as_ref()), entry(fetched[3].as_ref()));
+        let from_token_account = token_account(&from, &mint, &token_program, sender.as_ref());
+        let to_token_account = token_account(&to, &mint, &token_program, recipient.as_ref());
+        let sender_balance = balance(self.cache.account(&from_token_account).await.map_err(unavailable)?.as_ref());
+
+        let transfer = Transfer {
+            sender: sender.as_ref(),
+            recipient: recipient.as_ref(),
+            from_token_account,
+            to_token_account,
+            sender_balance,
+            amount: request.amount,
+        };
+        let response = match check::precheck(&config, &transfer, now()) {
+            Ok(()) => CanTransferResponse { allowed: true, ..CanTransferResponse::default() },
+            Err(error) => {
+                let (reason, error_code) = check::reason(&error);
+                CanTransferResponse { allowed: false, reason, error_code, simulated: false }
+            }
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn list_holders(&self, request: Request<ListHoldersRequest>) -> Result<Response<ListHoldersResponse>, Status> {
+        let mint = address(&request.into_inner().mint, "mint")?;
+        let fetched = self.cache.accounts(&[pda::token_config(&mint), mint]).await.map_err(unavailable)?;
+        let config = token_config(fetched[0].as_ref(), &mint)?;
+        let token_program = token_program(fetched[1].as_ref(), &mint)?;
+        let holders = self.cache.holders(&mint, &token_program).await.map_err(unavailable)?;
+
+        let entries: Vec<Pubkey> = holders.iter().map(|(wallet, _)| pda::allowlist_entry(&mint, wallet)).collect();
+        let entries = self.cache.accounts(&entries).await.map_err(unavailable)?;
+        let holders = holders.iter().zip(&entries).map(|((wallet, balance), entry_account)| Ok(Holder {
+            wallet: wallet.to_string(),
+            balance: *balance,
+            approved: entry(entry_account.as_ref()).is_some_and(|entry| entry.is_approved),
+            shares: config.split_adjusted(*balance).map_err(unavailable)?,
+        })).collect::<Result<_, Status>>()?;
+        let supply = fetched[1].as_ref().map_or(Ok(0), |mint| accounts::mint_supply(&mint.data)).map_err(unavailable)?;
+        Ok(Response::new(ListHoldersResponse { holders, total_shares: config.total_supply, supply }))
+    }
+}
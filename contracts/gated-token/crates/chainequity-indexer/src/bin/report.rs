@@ -0,0 +1,261 @@
+//! `report`: transfer-agent style compliance report for one mint over a date range,
+//! built from the indexer's database and printed as JSON. Covers new holders,
+//! approvals and revocations, forced transfers, blocked trade attempts and holder
+//! counts by jurisdiction at the end of the period.
+//!
+//! Balances are rebuilt from the normalized mints, transfers and burns, so balance
+//! changes the indexer does not normalize (escrows, splits, batch transfers, ...) are
+//! not reflected in `new_holders` or `holders_by_jurisdiction`. `forced_transfers` lists
+//! the authority's `force_transfer`s. Events without a block time are outside every
+//! period.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{bail, Context, Result};
+use chainequity_client::events;
+use chainequity_client::gated_token::{ForcedTransferEvent, TransferBlockedEvent, WalletCountryAssignedEvent};
+use clap::Parser;
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use tokio_postgres::{Client, NoTls};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+#[derive(Parser)]
+#[command(version, about = "Compliance report of a ChainEquity token from the indexer database")]
+struct Args {
+    /// Postgres connection string of the indexer
+    #[arg(long, env = "DATABASE_URL")]
+    database_url: String,
+    #[arg(long)]
+    mint: Pubkey,
+    /// First day of the period, `YYYY-MM-DD` (UTC)
+    #[arg(long, value_parser = parse_date)]
+    from: i64,
+    /// Day after the period ends, `YYYY-MM-DD` (UTC)
+    #[arg(long, value_parser = parse_date)]
+    to: i64,
+}
+
+/// An event with where and when it was logged
+struct Logged<E> {
+    signature: String,
+    block_time: i64,
+    event: E,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    if args.from >= args.to {
+        bail!("--from must be before --to");
+    }
+    let (db, connection) = tokio_postgres::connect(&args.database_url, NoTls).await?;
+    tokio::spawn(async move {
+        if let Err(error) = connection.await {
+            eprintln!("postgres connection closed: {error}");
+        }
+    });
+    let report = report(&db, &args.mint, args.from, args.to).await?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+async fn report(db: &Client, mint: &Pubkey, from: i64, to: i64) -> Result<Value> {
+    let mint_text = mint.to_string();
+
+    let new_holders: Vec<Value> = db.query(
+        "SELECT wallet, MIN(block_time) FROM (
+             SELECT m.recipient AS wallet, e.block_time
+               FROM mints m JOIN program_events e USING (signature, log_index) WHERE m.mint = $1
+             UNION ALL
+             SELECT t.to_wallet, e.block_time
+               FROM transfers t JOIN program_events e USING (signature, log_index) WHERE t.mint = $1
+         ) receipts
+         GROUP BY wallet
+         HAVING MIN(block_time) >= $2 AND MIN(block_time) < $3
+         ORDER BY 2, 1",
+        &[&mint_text, &from, &to],
+    ).await?.iter().map(|row| json!({
+        "wallet": row.get::<_, String>(0),
+        "first_received": row.get::<_, i64>(1),
+    })).collect();
+
+    let mut approvals = 0;
+    let mut revocations = Vec::new();
+    for row in db.query(
+        "SELECT wallet, approved, authority, timestamp, signature FROM wallet_approvals
+         WHERE mint = $1 AND timestamp >= $2 AND timestamp < $3
+         ORDER BY timestamp",
+        &[&mint_text, &from, &to],
+    ).await? {
+        if row.get::<_, bool>(1) {
+            approvals += 1;
+            continue;
+        }
+        revocations.push(json!({
+            "wallet": row.get::<_, String>(0),
+            "revoked_by": row.get::<_, String>(2),
+            "timestamp": row.get::<_, i64>(3),
+            "signature": row.get::<_, String>(4),
+        }));
+    }
+
+    let forced_transfers: Vec<Value> = logged::<ForcedTransferEvent>(db, "ForcedTransferEvent", from, to).await?
+        .into_iter()
+        .filter(|logged| logged.event.token_mint == *mint)
+        .map(|logged| json!({
+            "from": logged.event.from.to_string(),
+            "to": logged.event.to.to_string(),
+            "amount": logged.event.amount,
+            "reason_hash": logged.event.reason_hash.iter().map(|byte| format!("{byte:02x}")).collect::<String>(),
+            "authority": logged.event.authority.to_string(),
+            "timestamp": logged.block_time,
+            "signature": logged.signature,
+        }))
+        .collect();
+
+    let blocked: Vec<Value> = logged::<TransferBlockedEvent>(db, "TransferBlockedEvent", from, to).await?
+        .into_iter()
+        .filter(|logged| logged.event.token_mint == *mint)
+        .map(|logged| json!({
+            "from": logged.event.from.to_string(),
+            "to": logged.event.to.to_string(),
+            "blackout_window": logged.event.window_id,
+            "timestamp": logged.event.timestamp,
+            "signature": logged.signature,
+        }))
+        .collect();
+
+    let holders: Vec<String> = db.query(
+        "SELECT wallet FROM (
+             SELECT m.recipient AS wallet, m.amount AS delta, e.block_time
+               FROM mints m JOIN program_events e USING (signature, log_index) WHERE m.mint = $1
+             UNION ALL
+             SELECT t.to_wallet, t.amount, e.block_time
+               FROM transfers t JOIN program_events e USING (signature, log_index) WHERE t.mint = $1
+             UNION ALL
+             SELECT t.from_wallet, -t.amount, e.block_time
+               FROM transfers t JOIN program_events e USING (signature, log_index) WHERE t.mint = $1
+             UNION ALL
+             SELECT b.holder, -b.amount, e.block_time
+               FROM burns b JOIN program_events e USING (signature, log_index) WHERE b.mint = $1
+         ) flows
+         WHERE block_time < $2
+         GROUP BY wallet
+         HAVING SUM(delta) > 0",
+        &[&mint_text, &to],
+    ).await?.iter().map(|row| row.get(0)).collect();
+
+    let mut countries = HashMap::new();
+    for logged in logged::<WalletCountryAssignedEvent>(db, "WalletCountryAssignedEvent", i64::MIN, to).await? {
+        if logged.event.token_mint == *mint {
+            countries.insert(logged.event.wallet.to_string(), logged.event.country_code);
+        }
+    }
+    let mut by_jurisdiction = BTreeMap::<String, u64>::new();
+    for holder in &holders {
+        let country = countries.get(holder).map(jurisdiction).unwrap_or_else(|| "unassigned".to_string());
+        *by_jurisdiction.entry(country).or_default() += 1;
+    }
+
+    Ok(json!({
+        "mint": mint_text,
+        "period": { "from": format_date(from), "to": format_date(to) },
+        "new_holders": { "count": new_holders.len(), "holders": new_holders },
+        "approvals": approvals,
+        "revocations": { "count": revocations.len(), "revocations": revocations },
+        "forced_transfers": { "count": forced_transfers.len(), "transfers": forced_transfers },
+        "blocked_trade_attempts": { "count": blocked.len(), "attempts": blocked },
+        "holder_count": holders.len(),
+        "holders_by_jurisdiction": by_jurisdiction,
+    }))
+}
+
+/// Every `name` event logged from `from` up to `to`, in log order
+async fn logged<E: anchor_lang::Event>(db: &Client, name: &str, from: i64, to: i64) -> Result<Vec<Logged<E>>> {
+    let rows = db.query(
+        "SELECT signature, block_time, payload FROM program_events
+         WHERE event_name = $1 AND block_time >= $2 AND block_time < $3
+         ORDER BY slot, log_index",
+        &[&name, &from, &to],
+    ).await?;
+    let mut logged = Vec::with_capacity(rows.len());
+    for row in rows {
+        let payload: Vec<u8> = row.get(2);
+        let event = events::decode::<E>(&payload)?
+            .with_context(|| format!("stored {name} does not decode as one"))?;
+        logged.push(Logged {
+            signature: row.get(0),
+            block_time: row.get(1),
+            event,
+        });
+    }
+    Ok(logged)
+}
+
+fn jurisdiction(country_code: &[u8; 2]) -> String {
+    match country_code {
+        [0, 0] => "unassigned".to_string(),
+        code => String::from_utf8_lossy(code).into_owned(),
+    }
+}
+
+/// Unix time of midnight UTC on a `YYYY-MM-DD` date
+fn parse_date(date: &str) -> Result<i64, String> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts[..] else {
+        return Err(format!("{date} is not YYYY-MM-DD"));
+    };
+    let parse = |part: &str| part.parse::<i64>().map_err(|_| format!("{date} is not YYYY-MM-DD"));
+    let (year, month, day) = (parse(year)?, parse(month)?, parse(day)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(format!("{date} is not a valid date"));
+    }
+    Ok(days_from_civil(year, month, day) * SECONDS_PER_DAY)
+}
+
+fn format_date(timestamp: i64) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(SECONDS_PER_DAY));
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_round_trip_through_unix_time() {
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert_eq!(parse_date("2024-03-01"), Ok(1_709_251_200));
+        assert_eq!(format_date(1_709_251_200), "2024-03-01");
+        assert_eq!(format_date(parse_date("2000-02-29").unwrap()), "2000-02-29");
+        assert!(parse_date("2024-13-01").is_err());
+        assert!(parse_date("March 1").is_err());
+        assert_eq!(jurisdiction(&[0, 0]), "unassigned");
+        assert_eq!(jurisdiction(b"DE"), "DE");
+    }
+}
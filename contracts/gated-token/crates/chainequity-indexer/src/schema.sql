@@ -0,0 +1,73 @@
+-- Every gated-token event, in log order within its transaction. Normalized tables below
+-- share this primary key, so an event is stored exactly once however often it is seen.
+CREATE TABLE IF NOT EXISTS program_events (
+    signature   TEXT    NOT NULL,
+    log_index   INTEGER NOT NULL,
+    slot        BIGINT  NOT NULL,
+    block_time  BIGINT,
+    event_name  TEXT    NOT NULL,
+    -- Per-mint sequence; NULL for deployment-wide and issuer events
+    sequence    NUMERIC(20, 0),
+    payload     BYTEA   NOT NULL,
+    -- Events of failed transactions (only rejections such as TransferBlockedEvent) are
+    -- kept as a record of the attempt but never normalized
+    failed      BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (signature, log_index)
+);
+CREATE INDEX IF NOT EXISTS program_events_name_slot ON program_events (event_name, slot);
+
+CREATE TABLE IF NOT EXISTS wallet_approvals (
+    signature   TEXT    NOT NULL,
+    log_index   INTEGER NOT NULL,
+    mint        TEXT    NOT NULL,
+    wallet      TEXT    NOT NULL,
+    approved    BOOLEAN NOT NULL,
+    authority   TEXT    NOT NULL,
+    timestamp   BIGINT  NOT NULL,
+    PRIMARY KEY (signature, log_index),
+    FOREIGN KEY (signature, log_index) REFERENCES program_events
+);
+CREATE INDEX IF NOT EXISTS wallet_approvals_mint_wallet ON wallet_approvals (mint, wallet);
+
+CREATE TABLE IF NOT EXISTS mints (
+    signature   TEXT           NOT NULL,
+    log_index   INTEGER        NOT NULL,
+    mint        TEXT           NOT NULL,
+    recipient   TEXT           NOT NULL,
+    amount      NUMERIC(20, 0) NOT NULL,
+    new_supply  NUMERIC(20, 0) NOT NULL,
+    PRIMARY KEY (signature, log_index),
+    FOREIGN KEY (signature, log_index) REFERENCES program_events
+);
+CREATE INDEX IF NOT EXISTS mints_mint ON mints (mint);
+
+CREATE TABLE IF NOT EXISTS transfers (
+    signature        TEXT           NOT NULL,
+    log_index        INTEGER        NOT NULL,
+    mint             TEXT           NOT NULL,
+    from_wallet      TEXT           NOT NULL,
+    to_wallet        TEXT           NOT NULL,
+    amount           NUMERIC(20, 0) NOT NULL,
+    travel_rule_hash BYTEA,
+    PRIMARY KEY (signature, log_index),
+    FOREIGN KEY (signature, log_index) REFERENCES program_events
+);
+CREATE INDEX IF NOT EXISTS transfers_mint ON transfers (mint);
+
+CREATE TABLE IF NOT EXISTS burns (
+    signature   TEXT           NOT NULL,
+    log_index   INTEGER        NOT NULL,
+    mint        TEXT           NOT NULL,
+    holder      TEXT           NOT NULL,
+    amount      NUMERIC(20, 0) NOT NULL,
+    new_supply  NUMERIC(20, 0) NOT NULL,
+    PRIMARY KEY (signature, log_index),
+    FOREIGN KEY (signature, log_index) REFERENCES program_events
+);
+CREATE INDEX IF NOT EXISTS burns_mint ON burns (mint);
+
+-- Highest slot indexed, where a restart without --from-slot resumes its backfill
+CREATE TABLE IF NOT EXISTS indexer_cursor (
+    id   BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    slot BIGINT  NOT NULL
+);
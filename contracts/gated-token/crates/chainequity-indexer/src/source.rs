@@ -0,0 +1,107 @@
+//! Where program logs come from: a backfill over the program's transaction history and
+//! a live `logsSubscribe` stream.
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chainequity_client::PROGRAM_ID;
+use futures_util::StreamExt;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::UiTransactionEncoding;
+
+use crate::store::{IndexedTransaction, Store};
+
+/// Signatures fetched per `getSignaturesForAddress` page, the RPC maximum
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Index every program transaction from `from_slot` up to now, oldest first
+pub async fn backfill(rpc: &RpcClient, store: &mut Store, from_slot: u64) -> Result<u64> {
+    let mut statuses = Vec::new();
+    let mut before = None;
+    loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(SIGNATURE_PAGE),
+            commitment: Some(rpc.commitment()),
+        };
+        let page = rpc.get_signatures_for_address_with_config(&PROGRAM_ID, config).await?;
+        let exhausted = page.len() < SIGNATURE_PAGE;
+        let Some(last) = page.last() else { break };
+        before = Some(Signature::from_str(&last.signature)?);
+        let reached_start = last.slot < from_slot;
+        statuses.extend(page.into_iter().filter(|status| status.slot >= from_slot));
+        if exhausted || reached_start {
+            break;
+        }
+    }
+
+    let mut stored = 0;
+    for status in statuses.iter().rev() {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let signature = Signature::from_str(&status.signature)?;
+        let transaction = rpc.get_transaction_with_config(&signature, config).await
+            .with_context(|| format!("fetching transaction {signature}"))?;
+        let logs = match transaction.transaction.meta.map(|meta| meta.log_messages) {
+            Some(OptionSerializer::Some(logs)) => logs,
+            _ => continue,
+        };
+        stored += store.record(&IndexedTransaction {
+            signature: &status.signature,
+            slot: status.slot,
+            block_time: status.block_time,
+            logs: &logs,
+            failed: status.err.is_some(),
+        }).await?;
+    }
+    Ok(stored)
+}
+
+/// Index program transactions as they are confirmed, until the subscription ends
+pub async fn follow(pubsub: &PubsubClient, rpc: &RpcClient, store: &mut Store, from_slot: Option<u64>) -> Result<()> {
+    let (mut notifications, unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![PROGRAM_ID.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(rpc.commitment()),
+            },
+        )
+        .await?;
+
+    // Subscribe before backfilling so nothing confirmed in between is missed; the
+    // overlap is deduplicated by the store
+    if let Some(from_slot) = from_slot {
+        let stored = backfill(rpc, store, from_slot).await?;
+        println!("backfilled {stored} events from slot {from_slot}");
+    }
+
+    let mut block_time = (0, None);
+    while let Some(notification) = notifications.next().await {
+        let slot = notification.context.slot;
+        // Log notifications carry no block time; look it up once per slot
+        if block_time.0 != slot {
+            block_time = (slot, rpc.get_block_time(slot).await.ok());
+        }
+        let stored = store.record(&IndexedTransaction {
+            signature: &notification.value.signature,
+            slot,
+            block_time: block_time.1,
+            logs: &notification.value.logs,
+            failed: notification.value.err.is_some(),
+        }).await?;
+        if stored > 0 {
+            println!("slot {}: {stored} events from {}", notification.context.slot, notification.value.signature);
+        }
+    }
+    unsubscribe().await;
+    Ok(())
+}
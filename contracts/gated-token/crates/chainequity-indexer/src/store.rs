@@ -0,0 +1,185 @@
+//! Postgres side of the indexer: the schema in `schema.sql` and idempotent writes of
+//! each transaction's events.
+
+use anyhow::Result;
+use chainequity_client::events;
+use chainequity_client::gated_token::{
+    ForcedTransferEvent, TokensBurnedEvent, TokensMintedEvent, TokensTransferredEvent,
+    WalletApprovedEvent, WalletRevokedEvent,
+};
+use tokio_postgres::{Client, GenericClient, NoTls};
+
+const SCHEMA: &str = include_str!("schema.sql");
+
+/// Events a rejected instruction logs before failing; the only ones kept from failed
+/// transactions, as evidence of the attempt
+const REJECTION_EVENTS: &[&str] = &["TransferBlockedEvent"];
+
+/// A program transaction whose logs are to be indexed
+pub struct IndexedTransaction<'a> {
+    pub signature: &'a str,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: &'a [String],
+    /// Whether the transaction failed, so its state changes never happened
+    pub failed: bool,
+}
+
+pub struct Store {
+    client: Client,
+}
+
+impl Store {
+    /// Connect to `database_url` and create any missing tables
+    pub async fn connect(database_url: &str) -> Result<Self> {
+        let (client, connection) = tokio_postgres::connect(database_url, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(error) = connection.await {
+                eprintln!("postgres connection closed: {error}");
+            }
+        });
+        client.batch_execute(SCHEMA).await?;
+        Ok(Self { client })
+    }
+
+    /// Highest slot indexed so far
+    pub async fn cursor(&self) -> Result<Option<u64>> {
+        let row = self.client.query_opt("SELECT slot FROM indexer_cursor", &[]).await?;
+        Ok(row.map(|row| row.get::<_, i64>(0) as u64))
+    }
+
+    /// Store every gated-token event in `transaction` (only rejection events if it
+    /// failed) and advance the cursor, in one database transaction. Events already stored are skipped, so backfill and live
+    /// subscription may overlap. Returns how many events were new.
+    pub async fn record(&mut self, transaction: &IndexedTransaction<'_>) -> Result<u64> {
+        let db = self.client.transaction().await?;
+        let mut stored = 0;
+        for (log_index, payload) in events::payloads(transaction.logs).iter().enumerate() {
+            let Some(name) = events::name(payload) else {
+                continue;
+            };
+            if transaction.failed && !REJECTION_EVENTS.contains(&name) {
+                continue;
+            }
+            let log_index = log_index as i32;
+            let inserted = db.execute(
+                "INSERT INTO program_events
+                     (signature, log_index, slot, block_time, event_name, sequence, payload, failed)
+                 VALUES ($1, $2, $3, $4, $5, $6::text::numeric, $7, $8)
+                 ON CONFLICT DO NOTHING",
+                &[
+                    &transaction.signature,
+                    &log_index,
+                    &(transaction.slot as i64),
+                    &transaction.block_time,
+                    &name,
+                    &events::sequence(payload).map(|sequence| sequence.to_string()),
+                    payload,
+                    &transaction.failed,
+                ],
+            ).await?;
+            if inserted == 1 && !transaction.failed {
+                normalize(&db, transaction.signature, log_index, payload).await?;
+                stored += 1;
+            }
+        }
+        db.execute(
+            "INSERT INTO indexer_cursor (slot) VALUES ($1)
+             ON CONFLICT (id) DO UPDATE SET slot = GREATEST(indexer_cursor.slot, EXCLUDED.slot)",
+            &[&(transaction.slot as i64)],
+        ).await?;
+        db.commit().await?;
+        Ok(stored)
+    }
+}
+
+/// Copy the fields of approval, mint, transfer (forced ones included) and burn events into
+/// their own tables
+async fn normalize(db: &impl GenericClient, signature: &str, log_index: i32, payload: &[u8]) -> Result<()> {
+    if let Some(event) = events::decode::<WalletApprovedEvent>(payload)? {
+        insert_approval(db, signature, log_index, &event.token_mint, &event.wallet, true, &event.approved_by, event.timestamp).await?;
+    } else if let Some(event) = events::decode::<WalletRevokedEvent>(payload)? {
+        insert_approval(db, signature, log_index, &event.token_mint, &event.wallet, false, &event.revoked_by, event.timestamp).await?;
+    } else if let Some(event) = events::decode::<TokensMintedEvent>(payload)? {
+        db.execute(
+            "INSERT INTO mints (signature, log_index, mint, recipient, amount, new_supply)
+             VALUES ($1, $2, $3, $4, $5::text::numeric, $6::text::numeric)",
+            &[
+                &signature,
+                &log_index,
+                &event.token_mint.to_string(),
+                &event.recipient.to_string(),
+                &event.amount.to_string(),
+                &event.new_supply.to_string(),
+            ],
+        ).await?;
+    } else if let Some(event) = events::decode::<TokensTransferredEvent>(payload)? {
+        db.execute(
+            "INSERT INTO transfers (signature, log_index, mint, from_wallet, to_wallet, amount, travel_rule_hash)
+             VALUES ($1, $2, $3, $4, $5, $6::text::numeric, $7)",
+            &[
+                &signature,
+                &log_index,
+                &event.token_mint.to_string(),
+                &event.from.to_string(),
+                &event.to.to_string(),
+                &event.amount.to_string(),
+                &event.travel_rule_hash.map(|hash| hash.to_vec()),
+            ],
+        ).await?;
+    } else if let Some(event) = events::decode::<ForcedTransferEvent>(payload)? {
+        db.execute(
+            "INSERT INTO transfers (signature, log_index, mint, from_wallet, to_wallet, amount)
+             VALUES ($1, $2, $3, $4, $5, $6::text::numeric)",
+            &[
+                &signature,
+                &log_index,
+                &event.token_mint.to_string(),
+                &event.from.to_string(),
+                &event.to.to_string(),
+                &event.amount.to_string(),
+            ],
+        ).await?;
+    } else if let Some(event) = events::decode::<TokensBurnedEvent>(payload)? {
+        db.execute(
+            "INSERT INTO burns (signature, log_index, mint, holder, amount, new_supply)
+             VALUES ($1, $2, $3, $4, $5::text::numeric, $6::text::numeric)",
+            &[
+                &signature,
+                &log_index,
+                &event.token_mint.to_string(),
+                &event.holder.to_string(),
+                &event.amount.to_string(),
+                &event.new_supply.to_string(),
+            ],
+        ).await?;
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn insert_approval(
+    db: &impl GenericClient,
+    signature: &str,
+    log_index: i32,
+    mint: &impl ToString,
+    wallet: &impl ToString,
+    approved: bool,
+    authority: &impl ToString,
+    timestamp: i64,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO wallet_approvals (signature, log_index, mint, wallet, approved, authority, timestamp)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        &[
+            &signature,
+            &log_index,
+            &mint.to_string(),
+            &wallet.to_string(),
+            &approved,
+            &authority.to_string(),
+            &timestamp,
+        ],
+    ).await?;
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! The transaction request endpoint. `GET` answers with the label and icon wallets show
+//! before connecting; `POST` with the payer's `account` runs the allowlist, lockup,
+//! pause, lot and balance rules of `chainequity_client::check` for the payer and the
+//! link's recipient, and only if they pass answers with the `gated_transfer`
+//! transaction for the payer to sign. Refusals are `400`s whose `message` wallets show
+//! as the reason. Rules the check leaves to simulation can still fail the transfer once
+//! it is sent.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::error::Error;
+use axum::body::Bytes;
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::Engine;
+use chainequity_client::check::{self, token_account, Transfer};
+use chainequity_client::gated_token::{AllowlistEntry, TokenConfig};
+use chainequity_client::{accounts, instructions, pda, resolve};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::account::Account;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::Transaction;
+
+pub struct Endpoint {
+    pub rpc: RpcClient,
+    /// Name wallets show for the request
+    pub label: String,
+    /// Absolute URL of the icon wallets show next to the label
+    pub icon: String,
+}
+
+/// Query string of a `transfer_request` link
+#[derive(Deserialize)]
+struct TransferRequest {
+    mint: String,
+    recipient: String,
+    amount: u64,
+}
+
+/// Body wallets `POST`
+#[derive(Deserialize)]
+struct Payer {
+    account: String,
+}
+
+/// Serve the endpoint at the router's root
+pub fn router(endpoint: Endpoint) -> Router {
+    Router::new()
+        .route("/", get(describe).post(transaction).options(preflight))
+        .with_state(Arc::new(endpoint))
+}
+
+/// Why no transaction is returned
+struct Refusal {
+    status: StatusCode,
+    message: String,
+}
+
+impl IntoResponse for Refusal {
+    fn into_response(self) -> Response {
+        reply(self.status, json!({ "message": self.message }))
+    }
+}
+
+fn refuse(message: impl Into<String>) -> Refusal {
+    Refusal { status: StatusCode::BAD_REQUEST, message: message.into() }
+}
+
+fn unavailable(error: impl std::fmt::Display) -> Refusal {
+    Refusal { status: StatusCode::SERVICE_UNAVAILABLE, message: error.to_string() }
+}
+
+/// JSON reply browser wallets may read cross-origin
+fn reply(status: StatusCode, body: Value) -> Response {
+    let headers = [(header::CONTENT_TYPE, "application/json"), (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")];
+    (status, headers, body.to_string()).into_response()
+}
+
+fn address(value: &str, field: &str) -> Result<Pubkey, Refusal> {
+    Pubkey::from_str(value).map_err(|_| refuse(format!("{field} is not a base58 address")))
+}
+
+fn entry(account: Option<&Account>) -> Option<AllowlistEntry> {
+    account.and_then(|account| accounts::decode(&account.data).ok())
+}
+
+fn balance(account: Option<&Account>) -> u64 {
+    account
+        .and_then(|account| account.data.get(64..72))
+        .map_or(0, |amount| u64::from_le_bytes(amount.try_into().unwrap()))
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
+
+/// What the program says when it rejects the transfer
+fn rejection(error: &Error) -> String {
+    match error {
+        Error::AnchorError(error) => error.error_msg.clone(),
+        Error::ProgramError(error) => error.program_error.to_string(),
+    }
+}
+
+async fn describe(State(endpoint): State<Arc<Endpoint>>) -> Response {
+    reply(StatusCode::OK, json!({ "label": endpoint.label, "icon": endpoint.icon }))
+}
+
+async fn preflight() -> Response {
+    let headers = [
+        (header::ACCESS_CONTROL_ALLOW_ORIGIN, "*"),
+        (header::ACCESS_CONTROL_ALLOW_METHODS, "GET, POST"),
+        (header::ACCESS_CONTROL_ALLOW_HEADERS, "Content-Type"),
+    ];
+    (StatusCode::NO_CONTENT, headers).into_response()
+}
+
+async fn transaction(
+    State(endpoint): State<Arc<Endpoint>>,
+    Query(request): Query<TransferRequest>,
+    body: Bytes,
+) -> Response {
+    match endpoint.transaction(&request, &body).await {
+        Ok(reply) => reply,
+        Err(refusal) => refusal.into_response(),
+    }
+}
+
+impl Endpoint {
+    async fn transaction(&self, request: &TransferRequest, body: &[u8]) -> Result<Response, Refusal> {
+        let payer: Payer = serde_json::from_slice(body).map_err(|_| refuse("body must be {\"account\": <address>}"))?;
+        let payer = address(&payer.account, "account")?;
+        let mint = address(&request.mint, "mint")?;
+        let recipient = address(&request.recipient, "recipient")?;
+
+        let fetched = self.rpc.get_multiple_accounts(&[
+            pda::token_config(&mint),
+            mint,
+            pda::allowlist_entry(&mint, &payer),
+            pda::allowlist_entry(&mint, &recipient),
+        ]).await.map_err(unavailable)?;
+        let config: TokenConfig = fetched[0].as_ref()
+            .and_then(|account| accounts::decode(&account.data).ok())
+            .ok_or_else(|| refuse(format!("{mint} is not a gated-token mint")))?;
+        let token_program = fetched[1].as_ref()
+            .map(|account| account.owner)
+            .ok_or_else(|| refuse(format!("mint {mint} does not exist")))?;
+        let (sender, receiver) = (entry(fetched[2].as_ref()), entry(fetched[3].as_ref()));
+        let from_token_account = token_account(&payer, &mint, &token_program, sender.as_ref());
+        let to_token_account = token_account(&recipient, &mint, &token_program, receiver.as_ref());
+        let sender_balance = balance(self.rpc.get_multiple_accounts(&[from_token_account]).await.map_err(unavailable)?[0].as_ref());
+
+        let transfer = Transfer {
+            sender: sender.as_ref(),
+            recipient: receiver.as_ref(),
+            from_token_account,
+            to_token_account,
+            sender_balance,
+            amount: request.amount,
+        };
+        check::precheck(&config, &transfer, now()).map_err(|error| refuse(rejection(&error)))?;
+
+        let accounts = resolve::gated_transfer(&self.rpc, &mint, &payer, &recipient, request.amount).await.map_err(unavailable)?;
+        let blockhash = self.rpc.get_latest_blockhash().await.map_err(unavailable)?;
+        let message = Message::new_with_blockhash(
+            &[instructions::gated_transfer(accounts, request.amount, None)],
+            Some(&payer),
+            &blockhash,
+        );
+        let transaction = bincode::serialize(&Transaction::new_unsigned(message)).map_err(unavailable)?;
+        Ok(reply(StatusCode::OK, json!({
+            "transaction": base64::engine::general_purpose::STANDARD.encode(transaction),
+            "message": format!("Transfer {} {} to {recipient}", request.amount, config.symbol),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn router() -> Router {
+        super::router(Endpoint {
+            // Never reached: both requests are answered before any RPC call
+            rpc: RpcClient::new("http://127.0.0.1:1".to_string()),
+            label: "Acme Series A".to_string(),
+            icon: "https://acme.example/icon.svg".to_string(),
+        })
+    }
+
+    async fn send(request: Request<Body>) -> (StatusCode, Value) {
+        let response = router().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn describes_the_request_and_refuses_malformed_payers() {
+        let (status, body) = send(Request::get("/").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, json!({ "label": "Acme Series A", "icon": "https://acme.example/icon.svg" }));
+
+        let uri = format!("/?mint={}&recipient={}&amount=10", Pubkey::new_unique(), Pubkey::new_unique());
+        let (status, body) = send(Request::post(&uri).body(Body::from(r#"{"account":"not-a-key"}"#)).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["message"], "account is not a base58 address");
+    }
+}
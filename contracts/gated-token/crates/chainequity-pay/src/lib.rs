@@ -0,0 +1,5 @@
+//! Solana Pay transaction requests for gated-token transfers. [`endpoint`] serves the
+//! links `chainequity_client::solana_pay::transfer_request` builds, and refuses to hand
+//! out a transfer the payer or recipient is not allowlisted for.
+
+pub mod endpoint;
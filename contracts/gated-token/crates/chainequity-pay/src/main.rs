@@ -0,0 +1,41 @@
+//! `chainequity-pay`: serves the Solana Pay transaction request endpoint for
+//! gated-token transfers at `/`. Put it behind TLS: wallets only follow `https` links.
+
+use std::net::SocketAddr;
+
+use anyhow::Result;
+use chainequity_pay::endpoint::{self, Endpoint};
+use clap::Parser;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use tokio::net::TcpListener;
+
+#[derive(Parser)]
+#[command(version, about = "Solana Pay transaction requests for ChainEquity token transfers")]
+struct Args {
+    #[arg(short, long, env = "CHAINEQUITY_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Address the HTTP server listens on
+    #[arg(long, env = "CHAINEQUITY_PAY_LISTEN", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// Name wallets show for the request, such as the issuer's
+    #[arg(long)]
+    label: String,
+    /// Absolute URL of an SVG, PNG or WebP icon wallets show next to the label
+    #[arg(long)]
+    icon: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let endpoint = Endpoint {
+        rpc: RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed()),
+        label: args.label,
+        icon: args.icon,
+    };
+    let listener = TcpListener::bind(args.listen).await?;
+    println!("serving Solana Pay transfer requests on {}", args.listen);
+    axum::serve(listener, endpoint::router(endpoint)).await?;
+    Ok(())
+}
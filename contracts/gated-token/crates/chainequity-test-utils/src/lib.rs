@@ -0,0 +1,485 @@
+//! `solana-program-test` fixtures for programs that integrate with gated-token. A
+//! [`ChainEquityTest`] starts a bank with the gated-token program deployed, its
+//! program config and token registry initialized, and the payer as upgrade authority;
+//! [`ChainEquityTest::create_token`] then gives a token with approved, funded wallets.
+//!
+//! The program is loaded from `gated_token.so` in `SBF_OUT_DIR` (falling back to this
+//! workspace's `target/deploy`), so run `anchor build` first:
+//!
+//! ```ignore
+//! let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+//! let token = test.create_token("ACME", 3).await;
+//! test.mint(&token, 0, 1_000).await.unwrap();
+//! test.transfer(&token, 0, 1, 400).await.unwrap();
+//! test.assert_balance(&token, 1, 400).await;
+//! ```
+//!
+//! Helpers pass no optional accounts, so they cover tokens in their default
+//! configuration; tokens with cap tables, snapshots and the like can be driven with
+//! [`ChainEquityTest::process`] and the client's instruction builders.
+//! [`ChainEquityTest::create_hooked_token`] gives a Token-2022 token running the
+//! allowlist transfer hook, which needs [`ChainEquityTest::program_test_with_hook`].
+
+use anchor_lang::AccountDeserialize;
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use chainequity_client::gated_token::{
+    accounts, AllowlistEntry, ErrorCode, ProtocolParameters, TokenConfig, TokenRegistry, ALLOWLIST_TRANSFER_HOOK_ID,
+};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk_ids::{bpf_loader_upgradeable, system_program, sysvar};
+
+/// A token created by [`ChainEquityTest::create_token`]. Its instruction builders pass
+/// no optional accounts, matching the token's default configuration.
+pub struct TestToken {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    /// Token program the mint lives under
+    pub token_program: Pubkey,
+    /// Mint runs the allowlist transfer hook, so transfers forward its accounts
+    pub transfer_hook: bool,
+    /// Funded wallets with associated token accounts, approved unless added with
+    /// [`ChainEquityTest::add_wallet`]
+    pub wallets: Vec<Keypair>,
+}
+
+impl TestToken {
+    pub fn wallet(&self, index: usize) -> Pubkey {
+        self.wallets[index].pubkey()
+    }
+
+    pub fn token_account(&self, index: usize) -> Pubkey {
+        get_associated_token_address_with_program_id(&self.wallet(index), &self.mint, &self.token_program)
+    }
+
+    pub fn approve_instruction(&self, wallet: &Pubkey) -> Instruction {
+        instructions::approve_wallet(accounts::ApproveWallet {
+            authority: self.authority,
+            wallet: *wallet,
+            token_config: pda::token_config(&self.mint),
+            allowlist_entry: pda::allowlist_entry(&self.mint, wallet),
+            system_program: system_program::ID,
+        })
+    }
+
+    pub fn revoke_instruction(&self, wallet: &Pubkey) -> Instruction {
+        instructions::revoke_wallet(accounts::RevokeWallet {
+            authority: self.authority,
+            wallet: *wallet,
+            token_config: pda::token_config(&self.mint),
+            allowlist_entry: pda::allowlist_entry(&self.mint, wallet),
+            country_rule: None,
+        })
+    }
+
+    /// Mint `amount` to wallet number `index`
+    pub fn mint_instruction(&self, index: usize, amount: u64) -> Instruction {
+        let wallet = self.wallet(index);
+        instructions::mint_tokens(
+            accounts::MintTokens {
+                authority: self.authority,
+                recipient: wallet,
+                token_config: pda::token_config(&self.mint),
+                program_config: pda::program_config(),
+                mint: self.mint,
+                mint_authority: pda::mint_authority(&self.mint),
+                recipient_token_account: self.token_account(index),
+                recipient_allowlist_entry: pda::allowlist_entry(&self.mint, &wallet),
+                recipient_country_rule: None,
+                recipient_sanctions_flag: None,
+                share_class: None,
+                recipient_snapshot_balance: None,
+                recipient_holder_page: None,
+                holder_index: None,
+                recipient_holder_number: None,
+                instructions: None,
+                trusted_caller: None,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            amount,
+        )
+    }
+
+    /// `gated_transfer` of `amount` from wallet number `from` to wallet number `to`,
+    /// signed by `from`
+    pub fn transfer_instruction(&self, from: usize, to: usize, amount: u64) -> Instruction {
+        let mut transfer = instructions::gated_transfer(self.transfer_accounts(from, to), amount, None);
+        if self.transfer_hook {
+            transfer.accounts.extend(instructions::transfer_hook_accounts(&self.mint, &self.wallet(from), &self.wallet(to)));
+        }
+        transfer
+    }
+
+    /// Accounts of a `gated_transfer` from wallet number `from` to wallet number `to`,
+    /// for tests that fill in optional accounts
+    pub fn transfer_accounts(&self, from: usize, to: usize) -> accounts::GatedTransfer {
+        let (sender, recipient) = (self.wallet(from), self.wallet(to));
+        accounts::GatedTransfer {
+            authority: sender,
+            recipient,
+            token_config: pda::token_config(&self.mint),
+            program_config: pda::program_config(),
+            mint: self.mint,
+            from_token_account: self.token_account(from),
+            to_token_account: self.token_account(to),
+            sender_allowlist_entry: pda::allowlist_entry(&self.mint, &sender),
+            recipient_allowlist_entry: pda::allowlist_entry(&self.mint, &recipient),
+            destination_registry: None,
+            fee_treasury: None,
+            velocity_counter: None,
+            sender_country_rule: None,
+            recipient_country_rule: None,
+            sender_sanctions_flag: None,
+            recipient_sanctions_flag: None,
+            sender_snapshot_balance: None,
+            share_class: None,
+            recipient_snapshot_balance: None,
+            sender_holder_page: None,
+            recipient_holder_page: None,
+            sale_proposal: None,
+            holder_index: None,
+            sender_holder_number: None,
+            last_holder_number: None,
+            recipient_holder_number: None,
+            transfer_journal: None,
+            token_program: self.token_program,
+            system_program: system_program::ID,
+        }
+    }
+
+    /// Burn `amount` from wallet number `index`, signed by that wallet
+    pub fn burn_instruction(&self, index: usize, amount: u64) -> Instruction {
+        let holder = self.wallet(index);
+        instructions::burn_tokens(
+            accounts::BurnTokens {
+                holder,
+                token_config: pda::token_config(&self.mint),
+                program_config: pda::program_config(),
+                mint: self.mint,
+                holder_token_account: self.token_account(index),
+                holder_allowlist_entry: pda::allowlist_entry(&self.mint, &holder),
+                holder_snapshot_balance: None,
+                holder_page: None,
+                holder_index: None,
+                holder_number: None,
+                last_holder_number: None,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            amount,
+        )
+    }
+}
+
+pub struct ChainEquityTest {
+    pub context: ProgramTestContext,
+}
+
+impl ChainEquityTest {
+    /// A `ProgramTest` with gated-token deployed under the upgradeable loader. Add the
+    /// integrating program to it before passing it to [`ChainEquityTest::start`].
+    pub fn program_test() -> ProgramTest {
+        if std::env::var_os("SBF_OUT_DIR").is_none() {
+            std::env::set_var("SBF_OUT_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy"));
+        }
+        let mut program_test = ProgramTest::default();
+        program_test.add_upgradeable_program_to_genesis("gated_token", &PROGRAM_ID);
+        program_test
+    }
+
+    /// Start the bank with default protocol parameters: no creation fee, no default
+    /// minimum transfer or timelock
+    pub async fn start(program_test: ProgramTest) -> Self {
+        let mut context = program_test.start_with_context().await;
+        let payer = context.payer.pubkey();
+        // ProgramData metadata: 4-byte tag, 8-byte slot, then an optional authority
+        let program_data = program_data_address();
+        let mut account = context.banks_client.get_account(program_data).await.unwrap().unwrap();
+        account.data[12] = 1;
+        account.data[13..45].copy_from_slice(payer.as_ref());
+        context.set_account(&program_data, &account.into());
+
+        let mut test = Self { context };
+        let parameters = ProtocolParameters {
+            fee_recipient: payer,
+            token_creation_fee: 0,
+            default_min_transfer_amount: 0,
+            default_timelock_seconds: 0,
+        };
+        let initialize_config = instructions::initialize_program_config(
+            accounts::InitializeProgramConfig {
+                admin: payer,
+                program_config: pda::program_config(),
+                program: PROGRAM_ID,
+                program_data,
+                system_program: system_program::ID,
+            },
+            parameters,
+        );
+        let initialize_registry = instructions::initialize_token_registry(accounts::InitializeTokenRegistry {
+            payer,
+            token_registry: pda::token_registry(),
+            system_program: system_program::ID,
+        });
+        test.process(&[initialize_config, initialize_registry], &[]).await
+            .expect("initializing gated-token");
+        test
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Send `instructions` in one transaction paid by the payer, also signed by `signers`
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let mut keys: Vec<&Keypair> = vec![&self.context.payer];
+        keys.extend_from_slice(signers);
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&self.payer()), &keys, blockhash);
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    /// [`ChainEquityTest::program_test`] with allowlist-transfer-hook deployed too
+    pub fn program_test_with_hook() -> ProgramTest {
+        let mut program_test = Self::program_test();
+        program_test.add_upgradeable_program_to_genesis("allowlist_transfer_hook", &ALLOWLIST_TRANSFER_HOOK_ID);
+        program_test
+    }
+
+    /// Create a zero-decimal token and `wallets` approved wallets, each with an
+    /// associated token account and some SOL for fees
+    pub async fn create_token(&mut self, symbol: &str, wallets: usize) -> TestToken {
+        self.create_token_under(symbol, wallets, false).await
+    }
+
+    /// [`ChainEquityTest::create_token`] under Token-2022 with the allowlist transfer hook
+    pub async fn create_hooked_token(&mut self, symbol: &str, wallets: usize) -> TestToken {
+        self.create_token_under(symbol, wallets, true).await
+    }
+
+    async fn create_token_under(&mut self, symbol: &str, wallets: usize, transfer_hook: bool) -> TestToken {
+        let payer = self.payer();
+        let mint = Keypair::new();
+        let token_program = if transfer_hook { spl_token_2022::ID } else { spl_token::ID };
+        let registry: TokenRegistry = self.account(&pda::token_registry()).await
+            .expect("token registry");
+        let initialize = instructions::initialize_token(
+            accounts::InitializeToken {
+                authority: payer,
+                mint_authority: pda::mint_authority(&mint.pubkey()),
+                freeze_authority: pda::freeze_authority(&mint.pubkey()),
+                mint: mint.pubkey(),
+                token_config: pda::token_config(&mint.pubkey()),
+                token_registry: pda::token_registry(),
+                program_config: pda::program_config(),
+                fee_recipient: None,
+                registry_page: pda::registry_page(registry.current_page_index()),
+                metadata: None,
+                token_metadata_program: None,
+                transfer_hook_program: transfer_hook.then_some(ALLOWLIST_TRANSFER_HOOK_ID),
+                extra_account_meta_list: transfer_hook.then(|| pda::extra_account_meta_list(&mint.pubkey())),
+                token_program,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            symbol.to_string(),
+            format!("{symbol} Test Shares"),
+            0,
+            None,
+            String::new(),
+        );
+        self.process(&[initialize], &[&mint]).await.expect("initialize_token");
+
+        let mut token = TestToken {
+            mint: mint.pubkey(),
+            authority: payer,
+            token_program,
+            transfer_hook,
+            wallets: Vec::new(),
+        };
+        for _ in 0..wallets {
+            self.add_wallet(&mut token, true).await;
+        }
+        token
+    }
+
+    /// Add a funded wallet with an associated token account to `token`, approving it
+    /// if `approved`, and return its index
+    pub async fn add_wallet(&mut self, token: &mut TestToken, approved: bool) -> usize {
+        let payer = self.payer();
+        let wallet = Keypair::new();
+        if approved {
+            self.approve(token, &wallet.pubkey()).await.expect("approve_wallet");
+        }
+        let setup = [
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &payer,
+                &wallet.pubkey(),
+                &token.mint,
+                &token.token_program,
+            ),
+            solana_sdk::system_instruction::transfer(&payer, &wallet.pubkey(), 100_000_000),
+        ];
+        self.process(&setup, &[]).await.expect("funding test wallet");
+        token.wallets.push(wallet);
+        token.wallets.len() - 1
+    }
+
+    pub async fn approve(&mut self, token: &TestToken, wallet: &Pubkey) -> Result<(), BanksClientError> {
+        self.process(&[token.approve_instruction(wallet)], &[]).await
+    }
+
+    pub async fn revoke(&mut self, token: &TestToken, wallet: &Pubkey) -> Result<(), BanksClientError> {
+        self.process(&[token.revoke_instruction(wallet)], &[]).await
+    }
+
+    /// Mint `amount` to the token's wallet number `index`
+    pub async fn mint(&mut self, token: &TestToken, index: usize, amount: u64) -> Result<(), BanksClientError> {
+        self.process(&[token.mint_instruction(index, amount)], &[]).await
+    }
+
+    /// `gated_transfer` of `amount` from wallet number `from` to wallet number `to`
+    pub async fn transfer(&mut self, token: &TestToken, from: usize, to: usize, amount: u64) -> Result<(), BanksClientError> {
+        let signer = token.wallets[from].insecure_clone();
+        self.process(&[token.transfer_instruction(from, to, amount)], &[&signer]).await
+    }
+
+    /// Burn `amount` from the token's wallet number `index`
+    pub async fn burn(&mut self, token: &TestToken, index: usize, amount: u64) -> Result<(), BanksClientError> {
+        let signer = token.wallets[index].insecure_clone();
+        self.process(&[token.burn_instruction(index, amount)], &[&signer]).await
+    }
+
+    /// Decode the Anchor account at `address`, or `None` if it does not exist
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> Option<T> {
+        let account = self.context.banks_client.get_account(*address).await.unwrap()?;
+        Some(T::try_deserialize(&mut &account.data[..]).expect("account data"))
+    }
+
+    pub async fn token_config(&mut self, token: &TestToken) -> TokenConfig {
+        self.account(&pda::token_config(&token.mint)).await.expect("token config")
+    }
+
+    pub async fn allowlist_entry(&mut self, token: &TestToken, wallet: &Pubkey) -> Option<AllowlistEntry> {
+        self.account(&pda::allowlist_entry(&token.mint, wallet)).await
+    }
+
+    /// Token balance of wallet number `index`
+    pub async fn balance(&mut self, token: &TestToken, index: usize) -> u64 {
+        let account = self.context.banks_client.get_account(token.token_account(index)).await.unwrap()
+            .expect("token account");
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+            .expect("token account data")
+            .base
+            .amount
+    }
+
+    pub async fn assert_balance(&mut self, token: &TestToken, index: usize, expected: u64) {
+        let balance = self.balance(token, index).await;
+        assert_eq!(balance, expected, "balance of wallet {index} ({})", token.wallet(index));
+    }
+
+    /// Assert the token's recorded supply and that it equals the sum of wallet balances
+    pub async fn assert_supply(&mut self, token: &TestToken, expected: u64) {
+        let config = self.token_config(token).await;
+        assert_eq!(config.total_supply, expected, "total_supply of {}", token.mint);
+        let mut held = 0;
+        for index in 0..token.wallets.len() {
+            held += self.balance(token, index).await;
+        }
+        assert_eq!(held, expected, "sum of wallet balances of {}", token.mint);
+    }
+
+    pub async fn assert_approved(&mut self, token: &TestToken, wallet: &Pubkey, approved: bool) {
+        let entry = self.allowlist_entry(token, wallet).await;
+        assert_eq!(entry.map(|entry| entry.is_approved), Some(approved), "approval of {wallet}");
+    }
+
+    /// Create a classic SPL mint the payer mints, such as a payout currency
+    pub async fn create_spl_mint(&mut self, decimals: u8) -> Pubkey {
+        let payer = self.payer();
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let setup = [
+            solana_sdk::system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, None, decimals).unwrap(),
+        ];
+        self.process(&setup, &[&mint]).await.expect("creating SPL mint");
+        mint.pubkey()
+    }
+
+    /// Associated token account of `owner` for a classic SPL `mint`, created if missing
+    pub async fn spl_account(&mut self, mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        let create = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &self.payer(),
+            owner,
+            mint,
+            &spl_token::ID,
+        );
+        self.process(&[create], &[]).await.expect("creating token account");
+        get_associated_token_address_with_program_id(owner, mint, &spl_token::ID)
+    }
+
+    /// Mint `amount` of a mint made by [`ChainEquityTest::create_spl_mint`] to `account`
+    pub async fn mint_spl(&mut self, mint: &Pubkey, account: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let mint_to = spl_token::instruction::mint_to(&spl_token::ID, mint, account, &payer, &[], amount).unwrap();
+        self.process(&[mint_to], &[]).await.expect("minting SPL tokens");
+    }
+
+    /// Balance of any classic SPL token account
+    pub async fn spl_balance(&mut self, account: &Pubkey) -> u64 {
+        self.context.banks_client
+            .get_packed_account_data::<spl_token::state::Account>(*account)
+            .await
+            .expect("token account")
+            .amount
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp
+    }
+
+    /// Move the bank's clock `seconds` ahead, to a new slot so later transactions get a
+    /// fresh blockhash
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        let mut clock = self.context.banks_client.get_sysvar::<Clock>().await.unwrap();
+        self.context.warp_to_slot(clock.slot + 1).unwrap();
+        clock.slot += 1;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+}
+
+/// Assert that `result` failed with the gated-token error `expected`
+pub fn assert_program_error(result: Result<(), BanksClientError>, expected: ErrorCode) {
+    let code = u32::from(expected);
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(actual))))
+        | Err(BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::Custom(actual)),
+            ..
+        }) => assert_eq!(actual, code, "expected gated-token error {code}, got {actual}"),
+        other => panic!("expected gated-token error {code}, got {other:?}"),
+    }
+}
+
+fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
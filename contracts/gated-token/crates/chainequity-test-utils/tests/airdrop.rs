@@ -0,0 +1,171 @@
+//! Merkle airdrop claims under the token and program pauses and the holder index.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test airdrop -- --ignored
+
+use chainequity_client::gated_token::{accounts, AllowlistEntry, ErrorCode, HolderIndex};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::hash::hashv;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+/// Grants per wallet number
+const GRANTS: [u64; 2] = [300, 200];
+
+fn leaf(wallet: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[b"airdrop", wallet.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+/// Root of the two-leaf tree over `GRANTS`, siblings hashed in sorted order
+fn root(token: &TestToken) -> [u8; 32] {
+    let mut leaves = [leaf(&token.wallet(0), GRANTS[0]), leaf(&token.wallet(1), GRANTS[1])];
+    leaves.sort();
+    hashv(&[&leaves[0], &leaves[1]]).to_bytes()
+}
+
+/// Bind every wallet to its token account and enable the holder index
+async fn enable_holder_index(test: &mut ChainEquityTest, token: &TestToken) {
+    let mut setup = Vec::new();
+    for index in 0..token.wallets.len() {
+        setup.push(instructions::bind_token_account(
+            accounts::UpdateAllowlistEntry {
+                authority: token.authority,
+                token_config: pda::token_config(&token.mint),
+                allowlist_entry: pda::allowlist_entry(&token.mint, &token.wallet(index)),
+            },
+            Some(token.token_account(index)),
+        ));
+    }
+    setup.push(instructions::initialize_holder_index(accounts::InitializeHolderIndex {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        holder_index: pda::holder_index(&token.mint),
+        system_program: system_program::ID,
+    }));
+    test.process(&setup, &[]).await.expect("enabling the holder index");
+}
+
+/// Open a campaign over `GRANTS`, returning its address
+async fn create_campaign(test: &mut ChainEquityTest, token: &TestToken) -> Pubkey {
+    let campaign = pda::airdrop_campaign(&token.mint, test.token_config(token).await.next_airdrop_id);
+    let create = instructions::create_airdrop_campaign(
+        accounts::CreateAirdropCampaign {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            campaign,
+            campaign_vault: pda::airdrop_vault(&campaign),
+            vault_allowlist_entry: None,
+            instructions: None,
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        root(token),
+        GRANTS.iter().sum(),
+        test.now().await + 3_600,
+    );
+    test.process(&[create], &[]).await.expect("create_airdrop_campaign");
+    campaign
+}
+
+/// Wallet number `index` claims its grant, passing `holder_number` for the number it
+/// takes on joining the holder index
+async fn claim(
+    test: &mut ChainEquityTest,
+    token: &TestToken,
+    campaign: &Pubkey,
+    index: usize,
+    holder_number: Option<u64>,
+) -> Result<(), BanksClientError> {
+    let wallet = token.wallet(index);
+    let sibling = 1 - index;
+    let claim = instructions::claim_airdrop(
+        accounts::ClaimAirdrop {
+            wallet,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            campaign: *campaign,
+            campaign_vault: pda::airdrop_vault(campaign),
+            airdrop_claim: pda::airdrop_claim(campaign, &wallet),
+            wallet_token_account: token.token_account(index),
+            wallet_allowlist_entry: pda::allowlist_entry(&token.mint, &wallet),
+            wallet_sanctions_flag: None,
+            share_class: None,
+            wallet_snapshot_balance: None,
+            wallet_holder_page: None,
+            holder_index: holder_number.map(|_| pda::holder_index(&token.mint)),
+            wallet_holder_number: holder_number.map(|number| pda::holder_number(&token.mint, number)),
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        GRANTS[index],
+        vec![leaf(&token.wallet(sibling), GRANTS[sibling])],
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[claim], &[&signer]).await
+}
+
+async fn set_program_paused(test: &mut ChainEquityTest, paused: bool) {
+    let set = instructions::set_program_paused(
+        accounts::UpdateProgramConfig { admin: test.payer(), program_config: pda::program_config() },
+        paused,
+    );
+    test.process(&[set], &[]).await.expect("set_program_paused");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn claims_wait_out_either_pause() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("DROP", 2).await;
+    let campaign = create_campaign(&mut test, &token).await;
+
+    let pause = instructions::pause(accounts::PauseToken {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[pause], &[]).await.expect("pause");
+    assert_program_error(claim(&mut test, &token, &campaign, 0, None).await, ErrorCode::TokenPaused);
+    let unpause = instructions::unpause(accounts::SetPaused {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[unpause], &[]).await.expect("unpause");
+
+    set_program_paused(&mut test, true).await;
+    test.warp_forward(1).await;
+    assert_program_error(claim(&mut test, &token, &campaign, 0, None).await, ErrorCode::ProgramPaused);
+    set_program_paused(&mut test, false).await;
+
+    test.warp_forward(1).await;
+    claim(&mut test, &token, &campaign, 0, None).await.expect("claim_airdrop");
+    test.assert_balance(&token, 0, GRANTS[0]).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn claims_number_new_holders_in_the_holder_index() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("DROP", 2).await;
+    enable_holder_index(&mut test, &token).await;
+    let campaign = create_campaign(&mut test, &token).await;
+
+    assert_program_error(claim(&mut test, &token, &campaign, 0, None).await, ErrorCode::HolderIndexRequired);
+    claim(&mut test, &token, &campaign, 0, Some(0)).await.expect("claim_airdrop");
+    claim(&mut test, &token, &campaign, 1, Some(1)).await.expect("claim_airdrop");
+
+    let index: HolderIndex = test.account(&pda::holder_index(&token.mint)).await.unwrap();
+    assert_eq!(index.len, 2);
+    for (wallet, grant) in GRANTS.into_iter().enumerate() {
+        test.assert_balance(&token, wallet, grant).await;
+        let entry: AllowlistEntry = test.account(&pda::allowlist_entry(&token.mint, &token.wallet(wallet))).await.unwrap();
+        assert!(entry.holder_indexed);
+    }
+}
@@ -0,0 +1,142 @@
+//! Pro-rata issuance cranked over batches of holders, with the rounding dust minted to
+//! the treasury. Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by
+//! plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test allocation -- --ignored
+
+use chainequity_client::gated_token::{accounts, Allocation, ErrorCode};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const ISSUANCE: u64 = 100;
+
+fn snapshot_instruction(token: &TestToken, snapshot_id: u64) -> Instruction {
+    instructions::create_snapshot(accounts::CreateSnapshot {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        snapshot: pda::snapshot(&token.mint, snapshot_id),
+        system_program: system_program::ID,
+    })
+}
+
+/// Snapshot the holders and open an allocation of `ISSUANCE` against it
+async fn open_allocation(test: &mut ChainEquityTest, token: &TestToken) -> Allocation {
+    let config = test.token_config(token).await;
+    let snapshot_id = config.current_snapshot_id + 1;
+    let allocation = pda::allocation(&token.mint, config.last_allocation_id + 1);
+    let open = instructions::open_allocation(
+        accounts::OpenAllocation {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            snapshot: pda::snapshot(&token.mint, snapshot_id),
+            allocation,
+            system_program: system_program::ID,
+        },
+        ISSUANCE,
+    );
+    test.process(&[snapshot_instruction(token, snapshot_id), open], &[]).await.expect("open_allocation");
+    test.account(&allocation).await.unwrap()
+}
+
+/// `crank_allocation` over the wallets numbered in `holders`
+async fn crank(test: &mut ChainEquityTest, token: &TestToken, allocation: &Allocation, holders: &[usize]) -> Result<(), BanksClientError> {
+    let mut crank = instructions::crank_allocation(accounts::CrankAllocation {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        mint_authority: pda::mint_authority(&token.mint),
+        allocation: pda::allocation(&token.mint, allocation.allocation_id),
+        share_class: None,
+        holder_index: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+    });
+    for &index in holders {
+        let wallet = token.wallet(index);
+        crank.accounts.extend([
+            AccountMeta::new(pda::allowlist_entry(&token.mint, &wallet), false),
+            AccountMeta::new(token.token_account(index), false),
+            AccountMeta::new(pda::snapshot_balance(&token.mint, allocation.snapshot_id, &wallet), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+        ]);
+    }
+    test.process(&[crank], &[]).await
+}
+
+/// `finalize_allocation`, minting the unallocated remainder to `treasury_account`
+async fn finalize(test: &mut ChainEquityTest, token: &TestToken, allocation: &Allocation, treasury_account: &Pubkey) -> Result<(), BanksClientError> {
+    let finalize = instructions::finalize_allocation(accounts::FinalizeAllocation {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        mint_authority: pda::mint_authority(&token.mint),
+        allocation: pda::allocation(&token.mint, allocation.allocation_id),
+        treasury: pda::treasury(&token.mint),
+        treasury_token_account: *treasury_account,
+        token_program: token.token_program,
+    });
+    test.process(&[finalize], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn overlapping_batches_allocate_once_and_dust_goes_to_the_treasury() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("ALOC", 3).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    for index in 0..3 {
+        test.mint(&token, index, 1).await.unwrap();
+    }
+    let allocation = open_allocation(&mut test, &token).await;
+
+    // Wallet 1 is in both batches but is only allocated once
+    crank(&mut test, &token, &allocation, &[0, 1]).await.expect("crank_allocation");
+    crank(&mut test, &token, &allocation, &[1, 2]).await.expect("crank_allocation");
+    for index in 0..3 {
+        test.assert_balance(&token, index, 1 + ISSUANCE / 3).await;
+    }
+    let cranked: Allocation = test.account(&pda::allocation(&token.mint, allocation.allocation_id)).await.unwrap();
+    assert_eq!(cranked.holders_processed, 3);
+    assert_eq!(cranked.allocated_amount, 99);
+
+    finalize(&mut test, &token, &allocation, &treasury_account).await.expect("finalize_allocation");
+    assert_eq!(test.spl_balance(&treasury_account).await, 1, "rounding dust");
+    assert_eq!(test.token_config(&token).await.total_supply, 3 + ISSUANCE, "exactly the issuance is minted");
+    assert!(!test.token_config(&token).await.allocation_active);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn revoked_holders_share_falls_to_the_treasury_and_finalized_allocations_stop() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("ALOC", 3).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    for index in 0..3 {
+        test.mint(&token, index, 1).await.unwrap();
+    }
+    let allocation = open_allocation(&mut test, &token).await;
+    test.revoke(&token, &token.wallet(2)).await.unwrap();
+
+    // Record balances are frozen while the allocation is running
+    let snapshot_id = test.token_config(&token).await.current_snapshot_id + 1;
+    let snapshot = test.process(&[snapshot_instruction(&token, snapshot_id)], &[]).await;
+    assert_program_error(snapshot, ErrorCode::AllocationActive);
+
+    crank(&mut test, &token, &allocation, &[0, 1, 2]).await.expect("crank_allocation");
+    test.assert_balance(&token, 2, 1).await;
+    finalize(&mut test, &token, &allocation, &treasury_account).await.expect("finalize_allocation");
+    assert_eq!(test.spl_balance(&treasury_account).await, ISSUANCE / 3 + 1);
+    assert_eq!(test.token_config(&token).await.total_supply, 3 + ISSUANCE);
+
+    assert_program_error(crank(&mut test, &token, &allocation, &[2]).await, ErrorCode::AllocationFinalized);
+    test.warp_forward(1).await;
+    assert_program_error(finalize(&mut test, &token, &allocation, &treasury_account).await, ErrorCode::AllocationFinalized);
+}
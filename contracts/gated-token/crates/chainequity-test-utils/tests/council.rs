@@ -0,0 +1,143 @@
+//! m-of-n authority councils: proposals, approvals and threshold-gated execution.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test council -- --ignored
+
+use chainequity_client::gated_token::{accounts, AuthorityCouncil, CouncilOperation, ErrorCode, OperationAccount};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const THRESHOLD: u8 = 2;
+
+/// Hand the token's authority to a `threshold`-of-n council of `members`
+async fn initialize_council(test: &mut ChainEquityTest, token: &TestToken, members: Vec<Pubkey>, threshold: u8) -> Result<(), BanksClientError> {
+    let council = pda::authority_council(&token.mint);
+    let initialize = instructions::initialize_authority_council(
+        accounts::InitializeAuthorityCouncil {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            mint: token.mint,
+            council,
+            council_signer: pda::council_signer(&council),
+            timelock: None,
+            system_program: system_program::ID,
+        },
+        members,
+        threshold,
+    );
+    test.process(&[initialize], &[]).await
+}
+
+fn pause_instruction(token: &TestToken, authority: Pubkey) -> Instruction {
+    instructions::pause(accounts::PauseToken {
+        authority,
+        token_config: pda::token_config(&token.mint),
+    })
+}
+
+/// Wallet `index` proposes pausing the token as the council, returning the operation
+async fn propose_pause(test: &mut ChainEquityTest, token: &TestToken, index: usize) -> Result<Pubkey, BanksClientError> {
+    let council = pda::authority_council(&token.mint);
+    let next_operation_id = test.account::<AuthorityCouncil>(&council).await
+        .expect("council")
+        .next_operation_id;
+    let operation = pda::council_operation(&council, next_operation_id);
+    let pause = pause_instruction(token, pda::council_signer(&council));
+    let propose = instructions::propose_operation(
+        accounts::ProposeOperation {
+            proposer: token.wallet(index),
+            council,
+            operation,
+            system_program: system_program::ID,
+            token_config: pda::token_config(&token.mint),
+        },
+        pause.data,
+        pause
+            .accounts
+            .iter()
+            .map(|account| OperationAccount {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[propose], &[&signer]).await.map(|()| operation)
+}
+
+async fn approve(test: &mut ChainEquityTest, token: &TestToken, operation: &Pubkey, index: usize) -> Result<(), BanksClientError> {
+    let approve = instructions::approve_operation(accounts::ApproveOperation {
+        member: token.wallet(index),
+        council: pda::authority_council(&token.mint),
+        operation: *operation,
+        token_config: pda::token_config(&token.mint),
+    });
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[approve], &[&signer]).await
+}
+
+/// Wallet `index` executes the pause `operation`; the council signer PDA signs inside
+/// the program
+async fn execute(test: &mut ChainEquityTest, token: &TestToken, operation: &Pubkey, index: usize) -> Result<(), BanksClientError> {
+    let council = pda::authority_council(&token.mint);
+    let mut execute = instructions::execute_operation(accounts::ExecuteOperation {
+        executor: token.wallet(index),
+        council,
+        operation: *operation,
+        token_config: pda::token_config(&token.mint),
+    });
+    let pause = pause_instruction(token, pda::council_signer(&council));
+    execute.accounts.extend(pause.accounts.into_iter().map(|account| AccountMeta { is_signer: false, ..account }));
+    execute.accounts.push(AccountMeta::new_readonly(PROGRAM_ID, false));
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[execute], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn operations_execute_once_the_threshold_approves() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("CNCL", 3).await;
+    let members = (0..3).map(|index| token.wallet(index)).collect();
+    initialize_council(&mut test, &token, members, THRESHOLD).await.expect("initialize_authority_council");
+
+    let direct = test.process(&[pause_instruction(&token, token.authority)], &[]).await;
+    assert_program_error(direct, ErrorCode::UnauthorizedAuthority);
+
+    // The proposer's approval counts, one short of the threshold
+    let operation = propose_pause(&mut test, &token, 0).await.expect("propose_operation");
+    assert_program_error(execute(&mut test, &token, &operation, 0).await, ErrorCode::OperationThresholdNotMet);
+    assert!(!test.token_config(&token).await.paused);
+
+    approve(&mut test, &token, &operation, 1).await.expect("approve_operation");
+    execute(&mut test, &token, &operation, 2).await.expect("execute_operation");
+    assert!(test.token_config(&token).await.paused);
+    let executed: CouncilOperation = test.account(&operation).await.unwrap();
+    assert!(executed.executed);
+
+    assert_program_error(execute(&mut test, &token, &operation, 0).await, ErrorCode::OperationExecuted);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn only_members_propose_and_each_approves_once() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("CNCL", 4).await;
+    let too_high = initialize_council(&mut test, &token, vec![token.wallet(0), token.wallet(1)], 3).await;
+    assert_program_error(too_high, ErrorCode::InvalidCouncil);
+    let members = (0..3).map(|index| token.wallet(index)).collect();
+    initialize_council(&mut test, &token, members, THRESHOLD).await.expect("initialize_authority_council");
+
+    assert_program_error(propose_pause(&mut test, &token, 3).await.map(drop), ErrorCode::NotCouncilMember);
+    let operation = propose_pause(&mut test, &token, 0).await.expect("propose_operation");
+    assert_program_error(approve(&mut test, &token, &operation, 0).await, ErrorCode::OperationAlreadyApproved);
+    assert_program_error(approve(&mut test, &token, &operation, 3).await, ErrorCode::NotCouncilMember);
+    assert_program_error(execute(&mut test, &token, &operation, 3).await, ErrorCode::NotCouncilMember);
+    assert!(!test.token_config(&token).await.paused);
+}
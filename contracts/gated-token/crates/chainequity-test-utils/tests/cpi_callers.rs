@@ -0,0 +1,62 @@
+//! Restricting the programs that may reach issuance through CPI. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test cpi_callers -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, VestingTerms};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::{system_program, sysvar};
+
+/// `create_vesting_schedule` of `amount` for wallet 0, passing the instructions sysvar
+/// when `instructions` is set
+fn create_vesting_schedule(token: &TestToken, schedule: &Pubkey, amount: u64, start_ts: i64, instructions: bool) -> Instruction {
+    instructions::create_vesting_schedule(
+        accounts::CreateVestingSchedule {
+            authority: token.authority,
+            beneficiary: token.wallet(0),
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            vesting_schedule: *schedule,
+            vesting_vault: pda::vesting_vault(schedule),
+            vault_allowlist_entry: None,
+            instructions: instructions.then_some(sysvar::instructions::ID),
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        amount,
+        VestingTerms { start_ts, cliff_seconds: 0, duration_seconds: 3_600 },
+        true,
+    )
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn restricted_tokens_check_the_caller_of_vesting_issuance() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("CPIC", 1).await;
+    let restrict = instructions::set_restrict_cpi_callers(
+        accounts::UpdateTokenSettings {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        true,
+    );
+    test.process(&[restrict], &[]).await.expect("set_restrict_cpi_callers");
+
+    let schedule = pda::vesting_schedule(&token.mint, test.token_config(&token).await.next_vesting_id);
+    let start_ts = test.now().await;
+    let unchecked = test.process(&[create_vesting_schedule(&token, &schedule, 100, start_ts, false)], &[]).await;
+    assert_program_error(unchecked, ErrorCode::InstructionsSysvarRequired);
+
+    // A direct call passes once the sysvar shows this program at the top level
+    test.process(&[create_vesting_schedule(&token, &schedule, 100, start_ts, true)], &[]).await
+        .expect("create_vesting_schedule");
+    assert_eq!(test.spl_balance(&pda::vesting_vault(&schedule)).await, 100);
+}
@@ -0,0 +1,151 @@
+//! Vote delegation: delegates casting delegators' record-date weight, and delegations
+//! made too late to count. Needs `target/deploy/gated_token.so` from `anchor build`, so
+//! ignored by plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test delegation -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, Proposal, ProposalType, VoteChoice, VoteRecord};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const BALANCES: [u64; 3] = [100, 200, 300];
+
+async fn governed_token(test: &mut ChainEquityTest) -> TestToken {
+    let token = test.create_token("DLGT", BALANCES.len()).await;
+    for (index, amount) in BALANCES.into_iter().enumerate() {
+        test.mint(&token, index, amount).await.unwrap();
+    }
+    let initialize = instructions::initialize_governance_config(accounts::InitializeGovernanceConfig {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        governance_config: pda::governance_config(&token.mint),
+        system_program: system_program::ID,
+    });
+    test.process(&[initialize], &[]).await.expect("initialize_governance_config");
+    token
+}
+
+/// Wallet `index` delegates its votes to wallet `delegate`
+async fn delegate(test: &mut ChainEquityTest, token: &TestToken, index: usize, delegate: usize) -> Result<(), BanksClientError> {
+    let delegator = token.wallet(index);
+    let delegate = instructions::delegate_votes(
+        accounts::DelegateVotes {
+            delegator,
+            token_config: pda::token_config(&token.mint),
+            delegator_allowlist_entry: pda::allowlist_entry(&token.mint, &delegator),
+            delegation: pda::vote_delegation(&token.mint, &delegator),
+            system_program: system_program::ID,
+        },
+        token.wallet(delegate),
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[delegate], &[&signer]).await
+}
+
+/// Take the record-date snapshot a second from now and open an ordinary proposal on it
+async fn open_proposal(test: &mut ChainEquityTest, token: &TestToken) -> Pubkey {
+    test.warp_forward(1).await;
+    let config = test.token_config(token).await;
+    let snapshot = pda::snapshot(&token.mint, config.current_snapshot_id + 1);
+    let proposal = pda::proposal(&token.mint, config.next_proposal_id);
+    let take_snapshot = instructions::create_snapshot(accounts::CreateSnapshot {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        snapshot,
+        system_program: system_program::ID,
+    });
+    let create = instructions::create_proposal(
+        accounts::CreateProposal {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            snapshot,
+            governance_config: pda::governance_config(&token.mint),
+            share_class: None,
+            proposal,
+            system_program: system_program::ID,
+        },
+        "Approve the merger".to_string(),
+        [2; 32],
+        ProposalType::Ordinary,
+        3 * 86_400,
+    );
+    test.process(&[take_snapshot, create], &[]).await.expect("create_proposal");
+    proposal
+}
+
+/// Wallet `index` votes For on `proposal`, also casting for the wallets in `delegators`
+async fn vote(test: &mut ChainEquityTest, token: &TestToken, proposal: &Pubkey, index: usize, delegators: &[usize]) -> Result<(), BanksClientError> {
+    let voter = token.wallet(index);
+    let snapshot_id = test.account::<Proposal>(proposal).await.expect("proposal").snapshot_id;
+    let mut vote = instructions::cast_vote(
+        accounts::CastVote {
+            voter,
+            token_config: pda::token_config(&token.mint),
+            proposal: *proposal,
+            snapshot: pda::snapshot(&token.mint, snapshot_id),
+            share_class: None,
+            voter_allowlist_entry: pda::allowlist_entry(&token.mint, &voter),
+            voter_token_account: Some(token.token_account(index)),
+            voter_snapshot_balance: None,
+            vote_record: pda::vote(proposal, &voter),
+            system_program: system_program::ID,
+        },
+        VoteChoice::For,
+    );
+    for &delegator_index in delegators {
+        let delegator = token.wallet(delegator_index);
+        vote.accounts.extend([
+            AccountMeta::new_readonly(pda::vote_delegation(&token.mint, &delegator), false),
+            AccountMeta::new_readonly(pda::allowlist_entry(&token.mint, &delegator), false),
+            AccountMeta::new_readonly(token.token_account(delegator_index), false),
+            AccountMeta::new_readonly(PROGRAM_ID, false),
+            AccountMeta::new(pda::vote(proposal, &delegator), false),
+        ]);
+    }
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[vote], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_delegate_casts_the_delegators_record_date_weight() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test).await;
+    assert_program_error(delegate(&mut test, &token, 0, 0).await, ErrorCode::InvalidDelegate);
+    delegate(&mut test, &token, 0, 2).await.expect("delegate_votes");
+    let proposal = open_proposal(&mut test, &token).await;
+
+    // Lamports sent to the delegator's vote address do not block the delegated vote
+    let delegator_vote = pda::vote(&proposal, &token.wallet(0));
+    let grief = solana_sdk::system_instruction::transfer(&test.payer(), &delegator_vote, 1_000_000);
+    test.process(&[grief], &[]).await.unwrap();
+
+    assert_program_error(vote(&mut test, &token, &proposal, 1, &[0]).await, ErrorCode::NotDelegate);
+    vote(&mut test, &token, &proposal, 2, &[0]).await.expect("cast_vote");
+    let tallied: Proposal = test.account(&proposal).await.unwrap();
+    assert_eq!(tallied.votes_for, BALANCES[2] + BALANCES[0]);
+    let record: VoteRecord = test.account(&delegator_vote).await.unwrap();
+    assert_eq!((record.weight, record.cast_by), (BALANCES[0], token.wallet(2)));
+
+    assert!(vote(&mut test, &token, &proposal, 0, &[]).await.is_err(), "the delegator's vote is already cast");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_delegation_made_after_the_snapshot_does_not_count() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test).await;
+    let proposal = open_proposal(&mut test, &token).await;
+    delegate(&mut test, &token, 1, 2).await.expect("delegate_votes");
+
+    assert_program_error(vote(&mut test, &token, &proposal, 2, &[1]).await, ErrorCode::DelegationAfterSnapshot);
+    // The late delegator still votes for themselves
+    vote(&mut test, &token, &proposal, 1, &[]).await.expect("cast_vote");
+    let tallied: Proposal = test.account(&proposal).await.unwrap();
+    assert_eq!(tallied.votes_for, BALANCES[1]);
+}
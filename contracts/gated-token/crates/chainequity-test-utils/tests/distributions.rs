@@ -0,0 +1,164 @@
+//! Pro-rata payouts against on-chain snapshots. Needs `target/deploy/gated_token.so`
+//! from `anchor build`, so ignored by plain `cargo test` and run by the `gated-token` CI
+//! workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test distributions -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, Distribution, Snapshot, SnapshotBalance, TokenConfig};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{ChainEquityTest, TestToken};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const PAYOUT: u64 = 1_000_000;
+
+/// Open a scheduled distribution of `PAYOUT` against a fresh snapshot, returning its
+/// address and payout mint
+async fn crank_payout(test: &mut ChainEquityTest, token: &TestToken) -> (Pubkey, Pubkey) {
+    let payout_mint = test.create_spl_mint(6).await;
+    let schedule = pda::distribution_schedule(&token.mint);
+    let schedule_vault = pda::schedule_vault(&schedule);
+    let first_run_at = test.now().await - 60;
+    let create = instructions::create_distribution_schedule(
+        accounts::CreateDistributionSchedule {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            schedule,
+            payout_mint,
+            schedule_vault,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        PAYOUT,
+        90 * 86_400,
+        30 * 86_400,
+        first_run_at,
+    );
+    test.process(&[create], &[]).await.expect("create_distribution_schedule");
+    test.mint_spl(&payout_mint, &schedule_vault, PAYOUT).await;
+
+    let config: TokenConfig = test.token_config(token).await;
+    let snapshot = pda::snapshot(&token.mint, config.current_snapshot_id + 1);
+    let distribution = pda::distribution(&token.mint, config.next_distribution_id);
+    let crank = instructions::crank_distribution(accounts::CrankDistribution {
+        cranker: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        schedule,
+        schedule_vault,
+        payout_mint,
+        mint: token.mint,
+        snapshot,
+        distribution,
+        distribution_vault: pda::distribution_vault(&distribution),
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+    });
+    test.process(&[crank], &[]).await.expect("crank_distribution");
+    (distribution, payout_mint)
+}
+
+/// Claim wallet number `index`'s share of `distribution`, passing its snapshot
+/// checkpoint if it has moved shares since, and return the payout
+async fn claim(test: &mut ChainEquityTest, token: &TestToken, distribution: &Pubkey, payout_mint: &Pubkey, index: usize) -> u64 {
+    let holder = token.wallet(index);
+    let payout_account = test.spl_account(payout_mint, &holder).await;
+    let epoch: Distribution = test.account(distribution).await.expect("distribution");
+    let checkpoint = pda::snapshot_balance(&token.mint, epoch.snapshot_id, &holder);
+    let checkpointed = test.account::<SnapshotBalance>(&checkpoint).await.is_some();
+    let claim = instructions::claim_scheduled_dividend(accounts::ClaimScheduledDividend {
+        holder,
+        distribution: *distribution,
+        snapshot: pda::snapshot(&token.mint, epoch.snapshot_id),
+        payout_mint: *payout_mint,
+        distribution_vault: pda::distribution_vault(distribution),
+        holder_payout_account: payout_account,
+        share_token_account: token.token_account(index),
+        holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+        holder_snapshot_balance: checkpointed.then_some(checkpoint),
+        dividend_claim: pda::dividend_claim(distribution, &holder),
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+    });
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[claim], &[&signer]).await.expect("claim_scheduled_dividend");
+    test.spl_balance(&payout_account).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn split_then_snapshot_pays_out_the_whole_distribution() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("SPLT", 3).await;
+    let balances = [100, 300, 600];
+    for (index, amount) in balances.into_iter().enumerate() {
+        test.mint(&token, index, amount).await.unwrap();
+    }
+
+    // 3-for-1: the recorded supply triples while raw balances stay put
+    let split = instructions::execute_split(
+        accounts::ExecuteSplit {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            mint: token.mint,
+        },
+        3,
+        1,
+    );
+    test.process(&[split], &[]).await.unwrap();
+    assert_eq!(test.token_config(&token).await.total_supply, 3_000);
+
+    let (distribution, payout_mint) = crank_payout(&mut test, &token).await;
+    let epoch: Distribution = test.account(&distribution).await.unwrap();
+    let snapshot: Snapshot = test.account(&pda::snapshot(&token.mint, epoch.snapshot_id)).await.unwrap();
+    assert_eq!(snapshot.total_supply, 1_000, "snapshot records the raw supply balances are read in");
+
+    let mut claimed = 0;
+    for (index, balance) in balances.into_iter().enumerate() {
+        let payout = claim(&mut test, &token, &distribution, &payout_mint, index).await;
+        assert_eq!(payout, PAYOUT * balance / 1_000);
+        claimed += payout;
+    }
+    assert_eq!(claimed, PAYOUT);
+    let epoch: Distribution = test.account(&distribution).await.unwrap();
+    assert_eq!(epoch.claimed_amount, epoch.total_amount);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn claims_never_exceed_the_funding_when_holders_trade_after_the_record_date() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("CSRV", 7).await;
+    // 53 shares in all, so no balance divides the payout evenly
+    let balances = [1, 2, 3, 5, 8, 13, 21];
+    for (index, amount) in balances.into_iter().enumerate() {
+        test.mint(&token, index, amount).await.unwrap();
+    }
+    let (distribution, payout_mint) = crank_payout(&mut test, &token).await;
+
+    // Trades after the record date checkpoint both sides at their snapshot balances
+    let snapshot_id = test.token_config(&token).await.current_snapshot_id;
+    for (from, to, amount) in [(6, 0, 10), (3, 4, 5), (4, 1, 13)] {
+        let mut accounts = token.transfer_accounts(from, to);
+        accounts.sender_snapshot_balance = Some(pda::snapshot_balance(&token.mint, snapshot_id, &token.wallet(from)));
+        accounts.recipient_snapshot_balance = Some(pda::snapshot_balance(&token.mint, snapshot_id, &token.wallet(to)));
+        let transfer = instructions::gated_transfer(accounts, amount, None);
+        let signer = token.wallets[from].insecure_clone();
+        test.process(&[transfer], &[&signer]).await.expect("gated_transfer");
+    }
+
+    let mut claimed = 0;
+    for (index, balance) in balances.into_iter().enumerate() {
+        let payout = claim(&mut test, &token, &distribution, &payout_mint, index).await;
+        assert_eq!(payout, PAYOUT * balance / 53, "paid on the record-date balance");
+        claimed += payout;
+    }
+    assert!(claimed <= PAYOUT);
+    assert!(PAYOUT - claimed < balances.len() as u64, "at most a unit of dust per holder");
+    let epoch: Distribution = test.account(&distribution).await.unwrap();
+    assert_eq!(epoch.claimed_amount, claimed);
+    assert_eq!(test.spl_balance(&pda::distribution_vault(&distribution)).await, PAYOUT - claimed);
+}
@@ -0,0 +1,108 @@
+//! Scheduled emissions: the permissionless crank minting due periods to the treasury.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test emissions -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, MintSchedule};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const AMOUNT_PER_PERIOD: u64 = 10;
+const PERIOD: i64 = 100;
+/// Three and a half periods, so only three ever come due
+const LIFETIME: i64 = 350;
+
+/// Schedule emissions from now until `lifetime` from now into a new treasury account,
+/// returning that account
+async fn create_schedule(test: &mut ChainEquityTest, token: &TestToken, lifetime: i64) -> Result<Pubkey, BanksClientError> {
+    let treasury = pda::treasury(&token.mint);
+    let treasury_account = test.spl_account(&token.mint, &treasury).await;
+    let now = test.now().await;
+    let create = instructions::create_mint_schedule(
+        accounts::CreateMintSchedule {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            treasury,
+            treasury_account,
+            mint_schedule: pda::mint_schedule(&token.mint),
+            system_program: system_program::ID,
+        },
+        AMOUNT_PER_PERIOD,
+        PERIOD,
+        now,
+        now + lifetime,
+    );
+    test.process(&[create], &[]).await.map(|()| treasury_account)
+}
+
+/// Crank `crank_emission`, signed only by the fee payer
+async fn crank(test: &mut ChainEquityTest, token: &TestToken, treasury_account: &Pubkey) -> Result<(), BanksClientError> {
+    let crank = instructions::crank_emission(accounts::CrankEmission {
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint_schedule: pda::mint_schedule(&token.mint),
+        mint: token.mint,
+        mint_authority: pda::mint_authority(&token.mint),
+        treasury_account: *treasury_account,
+        token_program: token.token_program,
+    });
+    test.process(&[crank], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn crank_mints_each_due_period_once_until_the_end_date() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("EMIT", 1).await;
+    let treasury_account = create_schedule(&mut test, &token, LIFETIME).await.expect("create_mint_schedule");
+    assert_program_error(crank(&mut test, &token, &treasury_account).await, ErrorCode::NoEmissionDue);
+
+    test.warp_forward(2 * PERIOD + PERIOD / 2).await;
+    crank(&mut test, &token, &treasury_account).await.expect("crank_emission");
+    assert_eq!(test.spl_balance(&treasury_account).await, 2 * AMOUNT_PER_PERIOD);
+    test.warp_forward(1).await;
+    assert_program_error(crank(&mut test, &token, &treasury_account).await, ErrorCode::NoEmissionDue);
+
+    // Long past the end date only the third period is still owed
+    test.warp_forward(10 * PERIOD).await;
+    crank(&mut test, &token, &treasury_account).await.expect("crank_emission");
+    assert_eq!(test.spl_balance(&treasury_account).await, 3 * AMOUNT_PER_PERIOD);
+    let schedule: MintSchedule = test.account(&pda::mint_schedule(&token.mint)).await.unwrap();
+    assert_eq!((schedule.periods_emitted, schedule.total_emitted), (3, 3 * AMOUNT_PER_PERIOD));
+    assert_eq!(test.token_config(&token).await.total_supply, 3 * AMOUNT_PER_PERIOD);
+    test.warp_forward(PERIOD).await;
+    assert_program_error(crank(&mut test, &token, &treasury_account).await, ErrorCode::NoEmissionDue);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn crank_is_refused_while_paused_and_schedules_need_a_future_end() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("EMIT", 1).await;
+    assert_program_error(create_schedule(&mut test, &token, 0).await.map(drop), ErrorCode::InvalidMintSchedule);
+    test.warp_forward(1).await;
+    let treasury_account = create_schedule(&mut test, &token, LIFETIME).await.expect("create_mint_schedule");
+
+    let pause = instructions::pause(accounts::PauseToken {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[pause], &[]).await.expect("pause");
+    test.warp_forward(PERIOD).await;
+    assert_program_error(crank(&mut test, &token, &treasury_account).await, ErrorCode::TokenPaused);
+    assert_eq!(test.spl_balance(&treasury_account).await, 0);
+
+    // Unpausing releases what came due in the meantime
+    let unpause = instructions::unpause(accounts::SetPaused {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[unpause], &[]).await.expect("unpause");
+    test.warp_forward(1).await;
+    crank(&mut test, &token, &treasury_account).await.expect("crank_emission");
+    assert_eq!(test.spl_balance(&treasury_account).await, AMOUNT_PER_PERIOD);
+}
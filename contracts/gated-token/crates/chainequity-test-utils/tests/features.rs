@@ -0,0 +1,124 @@
+//! Per-token feature flags. Needs `target/deploy/gated_token.so` from `anchor build`, so
+//! ignored by plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test features -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, FeatureFlags, ReleaseCondition};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+async fn set_features(test: &mut ChainEquityTest, token: &TestToken, bits: u32) {
+    let set_features = instructions::set_features(
+        accounts::UpdateTokenSettings {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        FeatureFlags { bits },
+    );
+    test.process(&[set_features], &[]).await.expect("set_features");
+}
+
+/// Escrow `amount` of wallet 0's shares for wallet 1
+async fn create_escrow(test: &mut ChainEquityTest, token: &TestToken, escrow: &Pubkey, amount: u64) -> Result<(), BanksClientError> {
+    let sender = token.wallet(0);
+    let create = instructions::create_escrow(
+        accounts::CreateEscrow {
+            sender,
+            recipient: token.wallet(1),
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            sender_token_account: token.token_account(0),
+            sender_allowlist_entry: pda::allowlist_entry(&token.mint, &sender),
+            sender_country_rule: None,
+            sender_sanctions_flag: None,
+            destination_registry: None,
+            escrow: *escrow,
+            escrow_vault: pda::escrow_vault(escrow),
+            vault_allowlist_entry: None,
+            sender_snapshot_balance: None,
+            sender_holder_page: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+            holder_index: None,
+            last_holder_number: None,
+            sender_holder_number: None,
+        },
+        amount,
+        ReleaseCondition::CounterSignature { signer: token.authority },
+    );
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[create], &[&signer]).await
+}
+
+/// Cancel `escrow`, refunding wallet 0
+async fn cancel_escrow(test: &mut ChainEquityTest, token: &TestToken, escrow: &Pubkey) -> Result<(), BanksClientError> {
+    let sender = token.wallet(0);
+    let cancel = instructions::cancel_escrow(accounts::CancelEscrow {
+        sender,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        escrow: *escrow,
+        escrow_vault: pda::escrow_vault(escrow),
+        vault_allowlist_entry: None,
+        sender_token_account: token.token_account(0),
+        sender_allowlist_entry: pda::allowlist_entry(&token.mint, &sender),
+        price_feed: None,
+        sender_snapshot_balance: None,
+        sender_holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        holder_index: None,
+        sender_holder_number: None,
+    });
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[cancel], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn clearing_transfers_enabled_stops_escrows_and_their_refunds() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("FLAG", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    let first_id = test.token_config(&token).await.next_escrow_id;
+    let escrow = pda::escrow(&token.mint, first_id);
+    create_escrow(&mut test, &token, &escrow, 60).await.expect("create_escrow");
+
+    set_features(&mut test, &token, 0).await;
+    assert_program_error(cancel_escrow(&mut test, &token, &escrow).await, ErrorCode::TransfersDisabled);
+    let second = pda::escrow(&token.mint, first_id + 1);
+    assert_program_error(create_escrow(&mut test, &token, &second, 10).await, ErrorCode::TransfersDisabled);
+    assert_program_error(test.transfer(&token, 0, 1, 10).await, ErrorCode::TransfersDisabled);
+    test.assert_balance(&token, 0, 40).await;
+
+    set_features(&mut test, &token, FeatureFlags::TRANSFERS_ENABLED).await;
+    cancel_escrow(&mut test, &token, &escrow).await.expect("cancel_escrow");
+    test.assert_balance(&token, 0, 100).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_program_pause_stops_escrows_but_not_their_cancellation() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("FLAG", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    let first_id = test.token_config(&token).await.next_escrow_id;
+    let escrow = pda::escrow(&token.mint, first_id);
+    create_escrow(&mut test, &token, &escrow, 60).await.expect("create_escrow");
+
+    let pause = instructions::set_program_paused(
+        accounts::UpdateProgramConfig { admin: test.payer(), program_config: pda::program_config() },
+        true,
+    );
+    test.process(&[pause], &[]).await.expect("set_program_paused");
+    let second = pda::escrow(&token.mint, first_id + 1);
+    assert_program_error(create_escrow(&mut test, &token, &second, 10).await, ErrorCode::ProgramPaused);
+
+    // The escrowed shares can still go back to the sender
+    cancel_escrow(&mut test, &token, &escrow).await.expect("cancel_escrow");
+    test.assert_balance(&token, 0, 100).await;
+}
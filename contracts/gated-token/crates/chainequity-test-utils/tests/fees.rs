@@ -0,0 +1,115 @@
+//! Transfer fees: collection into the fee treasury and withdrawal to a wallet. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test fees -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_sdk_ids::{system_program, sysvar};
+
+/// Open the fee treasury and charge `fee_bps` on transfers
+async fn charge_fees(test: &mut ChainEquityTest, token: &TestToken, fee_bps: u16) {
+    let initialize = instructions::initialize_fee_treasury(accounts::InitializeFeeTreasury {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        treasury: pda::treasury(&token.mint),
+        fee_treasury: pda::fee_treasury(&token.mint),
+        vault_allowlist_entry: None,
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+    });
+    test.process(&[initialize], &[]).await.expect("initialize_fee_treasury");
+    set_fee(test, token, fee_bps).await;
+}
+
+async fn set_fee(test: &mut ChainEquityTest, token: &TestToken, fee_bps: u16) {
+    let set_fee = instructions::set_transfer_fee(
+        accounts::UpdateTokenSettings {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        fee_bps,
+    );
+    test.process(&[set_fee], &[]).await.expect("set_transfer_fee");
+}
+
+/// `gated_transfer` that pays its fee into the fee treasury
+async fn transfer_with_fee(test: &mut ChainEquityTest, token: &TestToken, from: usize, to: usize, amount: u64) {
+    let transfer = instructions::gated_transfer(
+        accounts::GatedTransfer {
+            fee_treasury: Some(pda::fee_treasury(&token.mint)),
+            ..token.transfer_accounts(from, to)
+        },
+        amount,
+        None,
+    );
+    let signer = token.wallets[from].insecure_clone();
+    test.process(&[transfer], &[&signer]).await.expect("gated_transfer with fee");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn withdrawn_fees_make_a_fresh_wallet_a_holder_it_can_transfer_from() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("FEES", 3).await;
+    test.mint(&token, 0, 1_000).await.unwrap();
+    charge_fees(&mut test, &token, 1_000).await;
+
+    transfer_with_fee(&mut test, &token, 0, 1, 500).await;
+    test.assert_balance(&token, 1, 450).await;
+    assert_eq!(test.spl_balance(&pda::fee_treasury(&token.mint)).await, 50);
+    assert_eq!(test.token_config(&token).await.holder_count, 2);
+
+    // Wallet 2 has never held the token
+    let destination = token.wallet(2);
+    let withdraw = instructions::withdraw_fees(
+        accounts::WithdrawFees {
+            authority: token.authority,
+            destination,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            treasury: pda::treasury(&token.mint),
+            fee_treasury: pda::fee_treasury(&token.mint),
+            destination_token_account: token.token_account(2),
+            destination_allowlist_entry: pda::allowlist_entry(&token.mint, &destination),
+            destination_country_rule: None,
+            destination_sanctions_flag: None,
+            share_class: None,
+            destination_snapshot_balance: None,
+            destination_holder_page: None,
+            holder_index: None,
+            destination_holder_number: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        50,
+    );
+    let pause = instructions::pause(accounts::PauseToken {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[pause], &[]).await.expect("pause");
+    assert_program_error(test.process(std::slice::from_ref(&withdraw), &[]).await, ErrorCode::TokenPaused);
+    let unpause = instructions::unpause(accounts::SetPaused {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[unpause], &[]).await.expect("unpause");
+    test.warp_forward(1).await;
+    test.process(&[withdraw], &[]).await.expect("withdraw_fees");
+    test.assert_balance(&token, 2, 50).await;
+    assert_eq!(test.token_config(&token).await.holder_count, 3, "the fee recipient joins the holders");
+
+    // It leaves again by transferring its whole balance on
+    set_fee(&mut test, &token, 0).await;
+    test.transfer(&token, 2, 1, 50).await.expect("transfer from the fee recipient");
+    test.assert_balance(&token, 1, 500).await;
+    test.assert_balance(&token, 2, 0).await;
+    assert_eq!(test.token_config(&token).await.holder_count, 2);
+}
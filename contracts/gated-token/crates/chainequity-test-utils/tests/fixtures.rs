@@ -0,0 +1,31 @@
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test fixtures -- --ignored
+
+use chainequity_client::gated_token::ErrorCode;
+use chainequity_test_utils::{assert_program_error, ChainEquityTest};
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn token_with_approved_wallets_mints_and_transfers() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("FIXT", 2).await;
+    for index in 0..2 {
+        test.assert_approved(&token, &token.wallet(index), true).await;
+    }
+
+    test.mint(&token, 0, 1_000).await.unwrap();
+    test.transfer(&token, 0, 1, 400).await.unwrap();
+    test.assert_balance(&token, 0, 600).await;
+    test.assert_balance(&token, 1, 400).await;
+    test.assert_supply(&token, 1_000).await;
+
+    test.revoke(&token, &token.wallet(1)).await.unwrap();
+    test.assert_approved(&token, &token.wallet(1), false).await;
+    assert_program_error(test.transfer(&token, 0, 1, 1).await, ErrorCode::RecipientNotApproved);
+
+    let stranger = Keypair::new();
+    assert!(test.allowlist_entry(&token, &stranger.pubkey()).await.is_none());
+}
@@ -0,0 +1,164 @@
+//! Forced transfers by the token authority, directly and through the timelock queue.
+//! Needs `gated_token.so` and `allowlist_transfer_hook.so` from `anchor build`, so ignored
+//! by plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test force_transfer -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, FeatureFlags, OperationAccount};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk_ids::system_program;
+
+const REASON: [u8; 32] = [7; 32];
+
+async fn set_features(test: &mut ChainEquityTest, token: &TestToken, bits: u32) {
+    let set_features = instructions::set_features(
+        accounts::UpdateTokenSettings {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        FeatureFlags { bits },
+    );
+    test.process(&[set_features], &[]).await.expect("set_features");
+}
+
+/// `force_transfer` of `amount` from wallet `from` to wallet `to`, with the hook's
+/// accounts for hooked mints and the timelock PDA when `timelocked`
+fn force_transfer(token: &TestToken, from: usize, to: usize, amount: u64, timelocked: bool) -> Instruction {
+    let (holder, recipient) = (token.wallet(from), token.wallet(to));
+    let mut force = instructions::force_transfer(
+        accounts::ForceTransfer {
+            authority: token.authority,
+            holder,
+            recipient,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            from_token_account: token.token_account(from),
+            to_token_account: token.token_account(to),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            recipient_allowlist_entry: pda::allowlist_entry(&token.mint, &recipient),
+            recipient_country_rule: None,
+            recipient_sanctions_flag: None,
+            share_class: None,
+            holder_snapshot_balance: None,
+            recipient_snapshot_balance: None,
+            holder_page: None,
+            recipient_holder_page: None,
+            holder_index: None,
+            holder_number: None,
+            last_holder_number: None,
+            recipient_holder_number: None,
+            timelock: timelocked.then(|| pda::timelock(&token.mint)),
+            instructions: None,
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        amount,
+        REASON,
+    );
+    if token.transfer_hook {
+        force.accounts.extend(instructions::transfer_hook_accounts(&token.mint, &holder, &recipient));
+    }
+    force
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so and allowlist_transfer_hook.so from `anchor build`"]
+async fn forced_transfer_moves_a_revoked_holders_shares_once_enabled() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test_with_hook()).await;
+    let token = test.create_hooked_token("FORC", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    test.revoke(&token, &token.wallet(0)).await.unwrap();
+
+    let disabled = test.process(&[force_transfer(&token, 0, 1, 100, false)], &[]).await;
+    assert_program_error(disabled, ErrorCode::ForcedTransfersDisabled);
+
+    // Transfers stay off: the forced transfer is governed by its own flag
+    set_features(&mut test, &token, FeatureFlags::FORCED_TRANSFERS).await;
+    test.process(&[force_transfer(&token, 0, 1, 100, false)], &[]).await.expect("force_transfer");
+    test.assert_balance(&token, 0, 0).await;
+    test.assert_balance(&token, 1, 100).await;
+    assert_eq!(test.token_config(&token).await.holder_count, 1, "the holder left and the recipient joined");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn forced_transfer_of_a_classic_spl_mint_is_refused() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("FORC", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    set_features(&mut test, &token, FeatureFlags::TRANSFERS_ENABLED | FeatureFlags::FORCED_TRANSFERS).await;
+
+    let classic = test.process(&[force_transfer(&token, 0, 1, 100, false)], &[]).await;
+    assert_program_error(classic, ErrorCode::ForcedTransferUnsupported);
+    test.assert_balance(&token, 0, 100).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so and allowlist_transfer_hook.so from `anchor build`"]
+async fn forced_transfer_waits_for_the_timelock() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test_with_hook()).await;
+    let token = test.create_hooked_token("FORC", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    set_features(&mut test, &token, FeatureFlags::TRANSFERS_ENABLED | FeatureFlags::FORCED_TRANSFERS).await;
+    let set_timelock = instructions::set_timelock(
+        accounts::SetTimelock {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            timelock: None,
+        },
+        3_600,
+    );
+    test.process(&[set_timelock], &[]).await.expect("set_timelock");
+
+    let direct = test.process(&[force_transfer(&token, 0, 1, 40, false)], &[]).await;
+    assert_program_error(direct, ErrorCode::TimelockRequired);
+
+    let queued = force_transfer(&token, 0, 1, 40, true);
+    let operation = pda::pending_operation(&token.mint, test.token_config(&token).await.next_pending_operation_id);
+    let queue = instructions::queue_operation(
+        accounts::QueueOperation {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            operation,
+            system_program: system_program::ID,
+        },
+        queued.data.clone(),
+        queued
+            .accounts
+            .iter()
+            .map(|account| OperationAccount {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+    );
+    test.process(&[queue], &[]).await.expect("queue_operation");
+
+    // The timelock PDA signs inside the program; the authority signs the outer transaction
+    let timelock = pda::timelock(&token.mint);
+    let mut execute = instructions::execute_queued_operation(accounts::ExecuteQueuedOperation {
+        executor: token.authority,
+        operation,
+        queued_by: token.authority,
+        timelock,
+        token_config: pda::token_config(&token.mint),
+    });
+    execute.accounts.extend(queued.accounts.iter().map(|account| AccountMeta {
+        is_signer: account.is_signer && account.pubkey != timelock,
+        ..account.clone()
+    }));
+    execute.accounts.push(AccountMeta::new_readonly(PROGRAM_ID, false));
+
+    let early = test.process(&[execute.clone()], &[]).await;
+    assert_program_error(early, ErrorCode::TimelockNotElapsed);
+    test.warp_forward(3_600).await;
+    test.process(&[execute], &[]).await.expect("execute_queued_operation");
+    test.assert_balance(&token, 0, 60).await;
+    test.assert_balance(&token, 1, 40).await;
+}
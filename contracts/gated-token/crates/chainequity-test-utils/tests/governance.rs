@@ -0,0 +1,241 @@
+//! Shareholder proposals: record-date voting, the voting period, quorum and approval
+//! thresholds, and the resolutions recorded against them. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test governance -- --ignored
+
+use chainequity_client::gated_token::{
+    accounts, ErrorCode, Proposal, ProposalRules, ProposalStatus, ProposalType, Resolution, ResolutionKind, VoteChoice,
+};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const ORDINARY_PERIOD: i64 = 3 * 86_400;
+const SPECIAL_PERIOD: i64 = 7 * 86_400;
+
+/// Mint `balances` to the token's wallets, set up governance and take the record-date
+/// snapshot
+async fn governed_token(test: &mut ChainEquityTest, symbol: &str, balances: &[u64]) -> TestToken {
+    let token = test.create_token(symbol, balances.len()).await;
+    for (index, &amount) in balances.iter().enumerate() {
+        if amount > 0 {
+            test.mint(&token, index, amount).await.unwrap();
+        }
+    }
+    let initialize = instructions::initialize_governance_config(accounts::InitializeGovernanceConfig {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        governance_config: pda::governance_config(&token.mint),
+        system_program: system_program::ID,
+    });
+    let snapshot = instructions::create_snapshot(accounts::CreateSnapshot {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        snapshot: pda::snapshot(&token.mint, 1),
+        system_program: system_program::ID,
+    });
+    test.process(&[initialize, snapshot], &[]).await.expect("governance setup");
+    token
+}
+
+async fn create_proposal(test: &mut ChainEquityTest, token: &TestToken, proposal_type: ProposalType, voting_period_seconds: i64) -> Result<Pubkey, BanksClientError> {
+    let config = test.token_config(token).await;
+    let proposal = pda::proposal(&token.mint, config.next_proposal_id);
+    let create = instructions::create_proposal(
+        accounts::CreateProposal {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            snapshot: pda::snapshot(&token.mint, config.current_snapshot_id),
+            governance_config: pda::governance_config(&token.mint),
+            share_class: None,
+            proposal,
+            system_program: system_program::ID,
+        },
+        "Elect the board".to_string(),
+        [1; 32],
+        proposal_type,
+        voting_period_seconds,
+    );
+    test.process(&[create], &[]).await.map(|()| proposal)
+}
+
+/// Wallet `index` votes `choice` on `proposal` with its record-date balance
+async fn vote(test: &mut ChainEquityTest, token: &TestToken, proposal: &Pubkey, index: usize, choice: VoteChoice) -> Result<(), BanksClientError> {
+    let voter = token.wallet(index);
+    let snapshot_id = test.account::<Proposal>(proposal).await.expect("proposal").snapshot_id;
+    let vote = instructions::cast_vote(
+        accounts::CastVote {
+            voter,
+            token_config: pda::token_config(&token.mint),
+            proposal: *proposal,
+            snapshot: pda::snapshot(&token.mint, snapshot_id),
+            share_class: None,
+            voter_allowlist_entry: pda::allowlist_entry(&token.mint, &voter),
+            voter_token_account: Some(token.token_account(index)),
+            voter_snapshot_balance: None,
+            vote_record: pda::vote(proposal, &voter),
+            system_program: system_program::ID,
+        },
+        choice,
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[vote], &[&signer]).await
+}
+
+async fn finalize(test: &mut ChainEquityTest, token: &TestToken, proposal: &Pubkey) -> Result<Proposal, BanksClientError> {
+    let finalize = instructions::finalize_proposal(accounts::FinalizeProposal {
+        proposal: *proposal,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[finalize], &[]).await?;
+    Ok(test.account(proposal).await.expect("proposal"))
+}
+
+/// Record a resolution for `document_hash`, implementing `proposal` if given
+async fn record_resolution(test: &mut ChainEquityTest, token: &TestToken, document_hash: [u8; 32], uri: &str, proposal: Option<Pubkey>) -> Result<Resolution, BanksClientError> {
+    let resolution = pda::resolution(&token.mint, &document_hash);
+    let record = instructions::record_resolution(
+        accounts::RecordResolution {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            proposal,
+            resolution,
+            system_program: system_program::ID,
+        },
+        ResolutionKind::ShareholderResolution,
+        document_hash,
+        uri.to_string(),
+        test.now().await,
+    );
+    test.process(&[record], &[]).await?;
+    Ok(test.account(&resolution).await.expect("resolution"))
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn record_date_votes_decide_a_proposal_after_the_period() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test, "GOVN", &[100, 200, 300]).await;
+    let proposal = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD).await.expect("create_proposal");
+
+    vote(&mut test, &token, &proposal, 0, VoteChoice::Against).await.expect("cast_vote");
+    vote(&mut test, &token, &proposal, 1, VoteChoice::For).await.expect("cast_vote");
+    test.warp_forward(1).await;
+    assert!(vote(&mut test, &token, &proposal, 1, VoteChoice::Against).await.is_err(), "one vote per holder");
+    assert_program_error(finalize(&mut test, &token, &proposal).await.map(drop), ErrorCode::VotingActive);
+
+    test.warp_forward(ORDINARY_PERIOD).await;
+    assert_program_error(vote(&mut test, &token, &proposal, 2, VoteChoice::For).await, ErrorCode::VotingClosed);
+    let tallied = finalize(&mut test, &token, &proposal).await.expect("finalize_proposal");
+    assert_eq!(tallied.eligible_votes, 600);
+    assert_eq!((tallied.votes_for, tallied.votes_against), (200, 100));
+    assert_eq!(tallied.status, ProposalStatus::Passed);
+
+    test.warp_forward(1).await;
+    assert_program_error(finalize(&mut test, &token, &proposal).await.map(drop), ErrorCode::ProposalFinalized);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn proposals_need_a_full_period_and_voters_need_shares() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test, "GOVN", &[100, 0]).await;
+    let short = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD - 1).await;
+    assert_program_error(short.map(drop), ErrorCode::InvalidProposal);
+
+    let proposal = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD).await.expect("create_proposal");
+    assert_program_error(vote(&mut test, &token, &proposal, 1, VoteChoice::For).await, ErrorCode::NoVotingPower);
+
+    test.warp_forward(ORDINARY_PERIOD).await;
+    let tallied = finalize(&mut test, &token, &proposal).await.expect("finalize_proposal");
+    assert_eq!(tallied.status, ProposalStatus::Rejected, "no votes means no quorum");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn special_resolutions_need_two_thirds_of_decisive_votes() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test, "SPCL", &[300, 300, 400]).await;
+    let short = create_proposal(&mut test, &token, ProposalType::Special, ORDINARY_PERIOD).await;
+    assert_program_error(short.map(drop), ErrorCode::InvalidProposal);
+
+    let seventy = create_proposal(&mut test, &token, ProposalType::Special, SPECIAL_PERIOD).await.expect("create_proposal");
+    let sixty = create_proposal(&mut test, &token, ProposalType::Special, SPECIAL_PERIOD).await.expect("create_proposal");
+    for (index, choice) in [(0, VoteChoice::For), (1, VoteChoice::Against), (2, VoteChoice::For)] {
+        vote(&mut test, &token, &seventy, index, choice).await.expect("cast_vote");
+    }
+    for (index, choice) in [(0, VoteChoice::For), (1, VoteChoice::For), (2, VoteChoice::Against)] {
+        vote(&mut test, &token, &sixty, index, choice).await.expect("cast_vote");
+    }
+
+    test.warp_forward(SPECIAL_PERIOD).await;
+    assert_eq!(finalize(&mut test, &token, &seventy).await.unwrap().status, ProposalStatus::Passed);
+    let tallied = finalize(&mut test, &token, &sixty).await.unwrap();
+    assert_eq!(tallied.status, ProposalStatus::Rejected, "a majority short of two thirds");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn abstentions_count_toward_quorum_but_not_approval() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test, "QRUM", &[300, 300, 400]).await;
+    let thin = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD).await.expect("create_proposal");
+    let quorate = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD).await.expect("create_proposal");
+
+    // Proposals already open keep the rules they were created under
+    let set_rules = |rules: ProposalRules| instructions::set_proposal_rules(
+        accounts::SetProposalRules {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            governance_config: pda::governance_config(&token.mint),
+            timelock: None,
+        },
+        ProposalType::Ordinary,
+        rules,
+    );
+    let invalid = set_rules(ProposalRules { quorum_bps: 5_000, approval_threshold_bps: 4_999, min_voting_period_seconds: ORDINARY_PERIOD });
+    assert_program_error(test.process(&[invalid], &[]).await, ErrorCode::InvalidProposalRules);
+    let unanimous_turnout = set_rules(ProposalRules { quorum_bps: 10_000, approval_threshold_bps: 5_000, min_voting_period_seconds: ORDINARY_PERIOD });
+    test.process(&[unanimous_turnout], &[]).await.expect("set_proposal_rules");
+
+    vote(&mut test, &token, &thin, 0, VoteChoice::For).await.expect("cast_vote");
+    vote(&mut test, &token, &quorate, 0, VoteChoice::For).await.expect("cast_vote");
+    vote(&mut test, &token, &quorate, 2, VoteChoice::Abstain).await.expect("cast_vote");
+
+    test.warp_forward(ORDINARY_PERIOD).await;
+    assert_eq!(finalize(&mut test, &token, &thin).await.unwrap().status, ProposalStatus::Rejected, "300 of 1,000 misses quorum");
+    let tallied = finalize(&mut test, &token, &quorate).await.unwrap();
+    assert_eq!((tallied.votes_for, tallied.votes_against, tallied.votes_abstain), (300, 0, 400));
+    assert_eq!(tallied.status, ProposalStatus::Passed);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn resolutions_record_a_document_once_and_only_for_passed_proposals() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = governed_token(&mut test, "RSLN", &[100, 200]).await;
+    let uri = "https://example.com/board-consent.pdf";
+    assert_program_error(record_resolution(&mut test, &token, [0; 32], uri, None).await.map(drop), ErrorCode::InvalidResolution);
+    assert_program_error(record_resolution(&mut test, &token, [3; 32], "", None).await.map(drop), ErrorCode::InvalidResolution);
+
+    let consent = record_resolution(&mut test, &token, [3; 32], uri, None).await.expect("record_resolution");
+    assert_eq!((consent.uri.as_str(), consent.proposal), (uri, None));
+    test.warp_forward(1).await;
+    assert!(record_resolution(&mut test, &token, [3; 32], uri, None).await.is_err(), "one record per document");
+
+    let proposal = create_proposal(&mut test, &token, ProposalType::Ordinary, ORDINARY_PERIOD).await.expect("create_proposal");
+    let early = record_resolution(&mut test, &token, [4; 32], uri, Some(proposal)).await;
+    assert_program_error(early.map(drop), ErrorCode::ProposalNotPassed);
+    vote(&mut test, &token, &proposal, 1, VoteChoice::For).await.expect("cast_vote");
+    test.warp_forward(ORDINARY_PERIOD).await;
+    finalize(&mut test, &token, &proposal).await.expect("finalize_proposal");
+
+    let implemented = record_resolution(&mut test, &token, [4; 32], uri, Some(proposal)).await.expect("record_resolution");
+    assert_eq!(implemented.proposal, Some(proposal));
+    assert_eq!(implemented.recorded_by, token.authority);
+}
@@ -0,0 +1,87 @@
+//! The emergency guardian: a key that can pause the token and nothing else. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test guardian -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+fn set_guardian_instruction(token: &TestToken, authority: Pubkey, guardian: Pubkey) -> Instruction {
+    instructions::set_guardian(
+        accounts::UpdateTokenSettings {
+            authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        guardian,
+    )
+}
+
+fn pause_instruction(token: &TestToken, authority: Pubkey) -> Instruction {
+    instructions::pause(accounts::PauseToken {
+        authority,
+        token_config: pda::token_config(&token.mint),
+    })
+}
+
+fn unpause_instruction(token: &TestToken, authority: Pubkey) -> Instruction {
+    instructions::unpause(accounts::SetPaused {
+        authority,
+        token_config: pda::token_config(&token.mint),
+    })
+}
+
+/// Submit `instruction` signed by the guardian rather than the authority
+async fn as_guardian(test: &mut ChainEquityTest, guardian: &Keypair, instruction: Instruction) -> Result<(), BanksClientError> {
+    test.process(&[instruction], &[guardian]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn guardian_pauses_but_only_the_authority_unpauses() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("GRDN", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    let authority = test.payer();
+    let guardian = token.wallets[1].insecure_clone();
+
+    let early = as_guardian(&mut test, &guardian, pause_instruction(&token, guardian.pubkey())).await;
+    assert_program_error(early, ErrorCode::UnauthorizedAuthority);
+    test.process(&[set_guardian_instruction(&token, authority, guardian.pubkey())], &[]).await.expect("set_guardian");
+    test.warp_forward(1).await;
+    as_guardian(&mut test, &guardian, pause_instruction(&token, guardian.pubkey())).await.expect("pause");
+    assert!(test.token_config(&token).await.paused);
+    assert_program_error(test.transfer(&token, 0, 1, 10).await, ErrorCode::TokenPaused);
+
+    let unpause = unpause_instruction(&token, guardian.pubkey());
+    assert_program_error(as_guardian(&mut test, &guardian, unpause).await, ErrorCode::UnauthorizedAuthority);
+    test.process(&[unpause_instruction(&token, authority)], &[]).await.expect("unpause");
+    test.warp_forward(1).await;
+    test.transfer(&token, 0, 1, 10).await.expect("transfer");
+    test.assert_balance(&token, 1, 10).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn guardian_cannot_reassign_itself_and_loses_pause_once_removed() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("GRDN", 2).await;
+    let authority = test.payer();
+    let guardian = token.wallets[1].insecure_clone();
+    test.process(&[set_guardian_instruction(&token, authority, guardian.pubkey())], &[]).await.expect("set_guardian");
+
+    let reassign = set_guardian_instruction(&token, guardian.pubkey(), token.wallet(0));
+    let reassigned = as_guardian(&mut test, &guardian, reassign).await;
+    assert_program_error(reassigned, ErrorCode::UnauthorizedAuthority);
+    assert_eq!(test.token_config(&token).await.guardian, guardian.pubkey());
+
+    test.process(&[set_guardian_instruction(&token, authority, Pubkey::default())], &[]).await.expect("set_guardian");
+    let removed = as_guardian(&mut test, &guardian, pause_instruction(&token, guardian.pubkey())).await;
+    assert_program_error(removed, ErrorCode::UnauthorizedAuthority);
+    assert!(!test.token_config(&token).await.paused);
+}
@@ -0,0 +1,219 @@
+//! Holder numbering across paths other than mints and transfers: shares vesting out to
+//! a new holder and a holder redeeming down to zero. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test holder_index -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, AllowlistEntry, ErrorCode, HolderIndex, HolderNumber, VestingTerms};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const DURATION: i64 = 1_000;
+const PRICE: u64 = 2;
+
+/// Bind every wallet to its token account and enable the holder index
+async fn enable_holder_index(test: &mut ChainEquityTest, token: &TestToken) {
+    let mut setup = Vec::new();
+    for index in 0..token.wallets.len() {
+        setup.push(instructions::bind_token_account(
+            accounts::UpdateAllowlistEntry {
+                authority: token.authority,
+                token_config: pda::token_config(&token.mint),
+                allowlist_entry: pda::allowlist_entry(&token.mint, &token.wallet(index)),
+            },
+            Some(token.token_account(index)),
+        ));
+    }
+    setup.push(instructions::initialize_holder_index(accounts::InitializeHolderIndex {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        holder_index: pda::holder_index(&token.mint),
+        system_program: system_program::ID,
+    }));
+    test.process(&setup, &[]).await.expect("enabling the holder index");
+}
+
+/// Vest `amount` to wallet number `index` linearly over `DURATION` from now
+async fn create_schedule(test: &mut ChainEquityTest, token: &TestToken, index: usize, amount: u64) -> Pubkey {
+    let schedule = pda::vesting_schedule(&token.mint, test.token_config(token).await.next_vesting_id);
+    let create = instructions::create_vesting_schedule(
+        accounts::CreateVestingSchedule {
+            authority: token.authority,
+            beneficiary: token.wallet(index),
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            vesting_schedule: schedule,
+            vesting_vault: pda::vesting_vault(&schedule),
+            vault_allowlist_entry: None,
+            instructions: None,
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        amount,
+        VestingTerms { start_ts: test.now().await, cliff_seconds: 0, duration_seconds: DURATION },
+        false,
+    );
+    test.process(&[create], &[]).await.expect("create_vesting_schedule");
+    schedule
+}
+
+/// Crank `release_vested` on wallet number `index`'s `schedule`, passing
+/// `holder_number` for the number it takes on joining
+async fn release(
+    test: &mut ChainEquityTest,
+    token: &TestToken,
+    index: usize,
+    schedule: &Pubkey,
+    holder_number: Option<u64>,
+) -> Result<(), BanksClientError> {
+    let beneficiary = token.wallet(index);
+    let release = instructions::release_vested(accounts::ReleaseVested {
+        cranker: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        vesting_schedule: *schedule,
+        vesting_vault: pda::vesting_vault(schedule),
+        beneficiary_token_account: token.token_account(index),
+        beneficiary_allowlist_entry: pda::allowlist_entry(&token.mint, &beneficiary),
+        beneficiary_country_rule: None,
+        beneficiary_sanctions_flag: None,
+        share_class: None,
+        beneficiary_snapshot_balance: None,
+        beneficiary_holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        beneficiary_holder_number: holder_number.map(|number| pda::holder_number(&token.mint, number)),
+        holder_index: holder_number.map(|_| pda::holder_index(&token.mint)),
+    });
+    test.process(&[release], &[]).await
+}
+
+/// Open a redemption window paying `PRICE` in a new mint, with `funding` in the vault,
+/// returning the payment mint
+async fn open_redemptions(test: &mut ChainEquityTest, token: &TestToken, funding: u64) -> Pubkey {
+    let payment_mint = test.create_spl_mint(6).await;
+    let funding_account = test.spl_account(&payment_mint, &token.authority).await;
+    test.mint_spl(&payment_mint, &funding_account, funding).await;
+    let initialize = instructions::initialize_redemption(accounts::InitializeRedemption {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        payment_mint,
+        redemption_config: pda::redemption(&token.mint),
+        redemption_vault: pda::redemption_vault(&token.mint),
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+    });
+    let fund = instructions::fund_redemption_vault(
+        accounts::FundRedemptionVault {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            redemption_config: pda::redemption(&token.mint),
+            payment_mint,
+            redemption_vault: pda::redemption_vault(&token.mint),
+            funding_token_account: funding_account,
+            token_program: spl_token::ID,
+        },
+        funding,
+    );
+    let open = instructions::open_redemption_window(
+        accounts::UpdateRedemptionWindow {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            redemption_config: pda::redemption(&token.mint),
+        },
+        PRICE,
+        test.now().await + 3_600,
+    );
+    test.process(&[initialize, fund, open], &[]).await.expect("opening redemptions");
+    payment_mint
+}
+
+/// Wallet 0 redeems `amount` shares, paid into `payment_account`, releasing
+/// `holder_number` and moving `last_number` into its place
+async fn redeem(
+    test: &mut ChainEquityTest,
+    token: &TestToken,
+    payment_mint: &Pubkey,
+    payment_account: &Pubkey,
+    amount: u64,
+    holder_number: u64,
+    last_number: Option<u64>,
+) -> Result<(), BanksClientError> {
+    let holder = token.wallet(0);
+    let redeem = instructions::redeem(
+        accounts::Redeem {
+            holder,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            holder_token_account: token.token_account(0),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            redemption_config: pda::redemption(&token.mint),
+            payment_mint: *payment_mint,
+            redemption_vault: pda::redemption_vault(&token.mint),
+            holder_payment_account: *payment_account,
+            holder_snapshot_balance: None,
+            holder_page: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            holder_index: Some(pda::holder_index(&token.mint)),
+            holder_number: Some(pda::holder_number(&token.mint, holder_number)),
+            last_holder_number: last_number.map(|number| pda::holder_number(&token.mint, number)),
+        },
+        amount,
+    );
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[redeem], &[&signer]).await
+}
+
+async fn holder_indexed(test: &mut ChainEquityTest, token: &TestToken, index: usize) -> bool {
+    let entry: AllowlistEntry = test.account(&pda::allowlist_entry(&token.mint, &token.wallet(index))).await.unwrap();
+    entry.holder_indexed
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn vesting_and_redemption_keep_the_holder_index_dense() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("HIDX", 2).await;
+    enable_holder_index(&mut test, &token).await;
+    let first = create_schedule(&mut test, &token, 0, 1_000).await;
+    let second = create_schedule(&mut test, &token, 1, 500).await;
+    test.warp_forward(DURATION).await;
+
+    // A release that starts a holding cannot skip the index
+    assert_program_error(release(&mut test, &token, 0, &first, None).await, ErrorCode::HolderIndexRequired);
+    release(&mut test, &token, 0, &first, Some(0)).await.expect("release_vested");
+    release(&mut test, &token, 1, &second, Some(1)).await.expect("release_vested");
+    let index: HolderIndex = test.account(&pda::holder_index(&token.mint)).await.unwrap();
+    assert_eq!(index.len, 2);
+    let number: HolderNumber = test.account(&pda::holder_number(&token.mint, 0)).await.unwrap();
+    assert_eq!(number.wallet, token.wallet(0));
+    assert!(holder_indexed(&mut test, &token, 1).await);
+
+    // Redeeming part of a holding keeps the number; redeeming the rest hands it to the
+    // last holder and closes the last number
+    let payment_mint = open_redemptions(&mut test, &token, 1_000 * PRICE).await;
+    let payment_account = test.spl_account(&payment_mint, &token.wallet(0)).await;
+    redeem(&mut test, &token, &payment_mint, &payment_account, 400, 0, None).await.expect("redeem");
+    assert!(holder_indexed(&mut test, &token, 0).await);
+    redeem(&mut test, &token, &payment_mint, &payment_account, 600, 0, Some(1)).await.expect("redeem");
+    test.assert_balance(&token, 0, 0).await;
+    assert!(!holder_indexed(&mut test, &token, 0).await);
+    let index: HolderIndex = test.account(&pda::holder_index(&token.mint)).await.unwrap();
+    assert_eq!(index.len, 1);
+    let number: HolderNumber = test.account(&pda::holder_number(&token.mint, 0)).await.unwrap();
+    assert_eq!(number.wallet, token.wallet(1));
+    assert!(test.account::<HolderNumber>(&pda::holder_number(&token.mint, 1)).await.is_none());
+}
@@ -0,0 +1,111 @@
+//! Share-class interest: index accrual at the class rate and holder claims. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test interest -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, ShareClassKind, INTEREST_INDEX_SCALE, SECONDS_PER_YEAR};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk_ids::system_program;
+
+const HOLDING: u64 = 1_000_000;
+const YEAR: i64 = SECONDS_PER_YEAR as i64;
+
+/// Give the token a share class, once wallet 0 holds `HOLDING`
+async fn create_share_class(test: &mut ChainEquityTest, token: &TestToken) {
+    test.mint(token, 0, HOLDING).await.unwrap();
+    let set = instructions::set_share_class(
+        accounts::SetShareClass {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            share_class: pda::share_class(&token.mint),
+            system_program: system_program::ID,
+        },
+        "Series A".to_string(),
+        ShareClassKind::Preferred,
+        1,
+        0,
+        true,
+        0,
+    );
+    test.process(&[set], &[]).await.expect("set_share_class");
+}
+
+async fn set_rate(test: &mut ChainEquityTest, token: &TestToken, rate_bps: u16) -> Result<(), BanksClientError> {
+    let set = instructions::set_share_class_accrual(
+        accounts::SetShareClassAccrual {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            share_class: pda::share_class(&token.mint),
+        },
+        rate_bps,
+    );
+    test.process(&[set], &[]).await
+}
+
+/// Wallet 0 claims its accrued interest, co-signed by the authority
+async fn claim(test: &mut ChainEquityTest, token: &TestToken) -> Result<(), BanksClientError> {
+    let holder = token.wallet(0);
+    let claim = instructions::claim_interest(accounts::ClaimInterest {
+        holder,
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        mint_authority: pda::mint_authority(&token.mint),
+        share_class: pda::share_class(&token.mint),
+        holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+        holder_token_account: token.token_account(0),
+        holder_snapshot_balance: None,
+        holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        holder_index: None,
+        holder_number: None,
+    });
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[claim], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_year_at_the_class_rate_accrues_and_claims_once() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("INTR", 1).await;
+    create_share_class(&mut test, &token).await;
+    set_rate(&mut test, &token, 1_000).await.expect("set_share_class_accrual");
+
+    assert_program_error(claim(&mut test, &token).await, ErrorCode::NoInterestAccrued);
+
+    test.warp_forward(YEAR).await;
+    claim(&mut test, &token).await.expect("claim_interest");
+    assert_eq!(test.token_config(&token).await.interest_index, INTEREST_INDEX_SCALE / 10);
+    test.assert_balance(&token, 0, HOLDING + HOLDING / 10).await;
+    test.assert_supply(&token, HOLDING + HOLDING / 10).await;
+
+    test.warp_forward(0).await;
+    assert_program_error(claim(&mut test, &token).await, ErrorCode::NoInterestAccrued);
+    let entry = test.allowlist_entry(&token, &token.wallet(0)).await.unwrap();
+    assert_eq!(entry.accrued_interest, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_rate_change_closes_the_period_at_the_old_rate() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("INTR", 1).await;
+    create_share_class(&mut test, &token).await;
+    assert_program_error(set_rate(&mut test, &token, 10_001).await, ErrorCode::InvalidAccrualRate);
+    set_rate(&mut test, &token, 1_000).await.expect("set_share_class_accrual");
+
+    test.warp_forward(YEAR / 2).await;
+    set_rate(&mut test, &token, 0).await.expect("set_share_class_accrual");
+    test.warp_forward(YEAR / 2).await;
+    claim(&mut test, &token).await.expect("claim_interest");
+    test.assert_balance(&token, 0, HOLDING + HOLDING / 20).await;
+
+    test.warp_forward(YEAR).await;
+    assert_program_error(claim(&mut test, &token).await, ErrorCode::NoInterestAccrued);
+}
@@ -0,0 +1,93 @@
+//! Random approve/revoke/mint/transfer/burn sequences, checking after every operation
+//! that `total_supply` equals the sum of balances, that `holder_count` equals the number
+//! of wallets holding tokens and that a wallet never approved holds nothing. Operations
+//! are free to fail. Needs `target/deploy/gated_token.so`, so it is ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow. `PROPTEST_CASES` overrides
+//! the number of sequences tried:
+//!
+//!     PROPTEST_CASES=16 cargo test -p chainequity-test-utils --test invariants -- --ignored
+
+use std::cell::RefCell;
+
+use chainequity_test_utils::{ChainEquityTest, TestToken};
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+
+const APPROVED_WALLETS: usize = 3;
+const STRANGERS: usize = 2;
+const DEFAULT_CASES: u32 = 48;
+
+#[derive(Debug, Clone)]
+enum Op {
+    Approve(usize),
+    Revoke(usize),
+    Mint { to: usize, amount: u64 },
+    Transfer { from: usize, to: usize, amount: u64 },
+    Burn { holder: usize, amount: u64 },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let wallet = 0..APPROVED_WALLETS + STRANGERS;
+    let amount = 0..2_000u64;
+    prop_oneof![
+        wallet.clone().prop_map(Op::Approve),
+        wallet.clone().prop_map(Op::Revoke),
+        (wallet.clone(), amount.clone()).prop_map(|(to, amount)| Op::Mint { to, amount }),
+        (wallet.clone(), wallet.clone(), amount.clone())
+            .prop_map(|(from, to, amount)| Op::Transfer { from, to, amount }),
+        (wallet, amount).prop_map(|(holder, amount)| Op::Burn { holder, amount }),
+    ]
+}
+
+#[test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+fn random_operations_keep_invariants() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    let test = RefCell::new(runtime.block_on(ChainEquityTest::start(ChainEquityTest::program_test())));
+    let cases = std::env::var("PROPTEST_CASES").ok().and_then(|cases| cases.parse().ok()).unwrap_or(DEFAULT_CASES);
+    let mut runner = TestRunner::new(Config { cases, ..Config::default() });
+
+    runner
+        .run(&prop::collection::vec(op(), 1..40), |ops| {
+            runtime.block_on(run(&mut test.borrow_mut(), &ops))
+        })
+        .unwrap();
+}
+
+async fn run(test: &mut ChainEquityTest, ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut token = test.create_token("PROP", APPROVED_WALLETS).await;
+    for _ in 0..STRANGERS {
+        test.add_wallet(&mut token, false).await;
+    }
+    let mut ever_approved: Vec<bool> = (0..token.wallets.len()).map(|index| index < APPROVED_WALLETS).collect();
+
+    for op in ops {
+        let applied = match *op {
+            Op::Approve(wallet) => test.approve(&token, &token.wallet(wallet)).await,
+            Op::Revoke(wallet) => test.revoke(&token, &token.wallet(wallet)).await,
+            Op::Mint { to, amount } => test.mint(&token, to, amount).await,
+            Op::Transfer { from, to, amount } => test.transfer(&token, from, to, amount).await,
+            Op::Burn { holder, amount } => test.burn(&token, holder, amount).await,
+        };
+        if let (Op::Approve(wallet), Ok(())) = (op, &applied) {
+            ever_approved[*wallet] = true;
+        }
+        check(test, &token, &ever_approved, op).await?;
+    }
+    Ok(())
+}
+
+async fn check(test: &mut ChainEquityTest, token: &TestToken, ever_approved: &[bool], op: &Op) -> Result<(), TestCaseError> {
+    let mut held = 0;
+    let mut holders = 0;
+    for (index, approved) in ever_approved.iter().enumerate() {
+        let balance = test.balance(token, index).await;
+        prop_assert!(*approved || balance == 0, "never-approved wallet {} holds {} after {:?}", index, balance, op);
+        held += balance;
+        holders += i64::from(balance > 0);
+    }
+    let config = test.token_config(token).await;
+    prop_assert_eq!(config.total_supply, held, "total_supply vs balances after {:?}", op);
+    prop_assert_eq!(config.holder_count, holders, "holder_count vs holders after {:?}", op);
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Convertible notes: conversion at the discounted round price or the valuation cap.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test notes -- --ignored
+
+use chainequity_client::gated_token::{accounts, ConversionTrigger, ConvertibleNote, ErrorCode};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const PRINCIPAL: u64 = 10_000;
+/// 20% off the round price
+const DISCOUNT_BPS: u16 = 2_000;
+/// Caps the price at 5 per share over the 1,000 shares held by wallet 1
+const VALUATION_CAP: u64 = 5_000;
+const MATURITY: i64 = 86_400;
+
+/// Issue a note to wallet 0 maturing `MATURITY` from now
+async fn issue_note(test: &mut ChainEquityTest, token: &TestToken) -> Pubkey {
+    let note = pda::convertible_note(&token.mint, test.token_config(token).await.next_note_id);
+    let issue = instructions::issue_convertible_note(
+        accounts::IssueConvertibleNote {
+            authority: token.authority,
+            noteholder: token.wallet(0),
+            token_config: pda::token_config(&token.mint),
+            convertible_note: note,
+            system_program: system_program::ID,
+        },
+        PRINCIPAL,
+        DISCOUNT_BPS,
+        VALUATION_CAP,
+        test.now().await + MATURITY,
+    );
+    test.process(&[issue], &[]).await.expect("issue_convertible_note");
+    note
+}
+
+async fn convert(test: &mut ChainEquityTest, token: &TestToken, note: &Pubkey, trigger: ConversionTrigger) -> Result<(), BanksClientError> {
+    let noteholder = token.wallet(0);
+    let convert = instructions::convert_note(
+        accounts::ConvertNote {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            convertible_note: *note,
+            noteholder_token_account: token.token_account(0),
+            noteholder_allowlist_entry: pda::allowlist_entry(&token.mint, &noteholder),
+            share_class: None,
+            noteholder_snapshot_balance: None,
+            noteholder_page: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+            holder_index: None,
+            noteholder_number: None,
+        },
+        trigger,
+    );
+    test.process(&[convert], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn financing_round_converts_at_the_discount_when_below_the_cap() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("NOTE", 2).await;
+    test.mint(&token, 1, 1_000).await.unwrap();
+    let note = issue_note(&mut test, &token).await;
+
+    let early = convert(&mut test, &token, &note, ConversionTrigger::Maturity).await;
+    assert_program_error(early, ErrorCode::NoteNotConvertible);
+
+    // A round at 5 discounts to 4, under the cap price of 5
+    convert(&mut test, &token, &note, ConversionTrigger::QualifiedFinancing { price_per_share: 5 }).await
+        .expect("convert_note");
+    test.assert_balance(&token, 0, PRINCIPAL / 4).await;
+    test.assert_supply(&token, 1_000 + PRINCIPAL / 4).await;
+    assert!(test.account::<ConvertibleNote>(&note).await.is_none(), "conversion settles the note");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn valuation_cap_binds_on_a_richer_round_and_at_maturity() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("NOTE", 2).await;
+    test.mint(&token, 1, 1_000).await.unwrap();
+    let note = issue_note(&mut test, &token).await;
+
+    // A round at 10 discounts to 8, above the cap price of 5
+    convert(&mut test, &token, &note, ConversionTrigger::QualifiedFinancing { price_per_share: 10 }).await
+        .expect("convert_note");
+    test.assert_balance(&token, 0, PRINCIPAL / 5).await;
+
+    // The cap price now spreads over 3,000 shares: 5,000 / 3,000 rounds down to 1
+    let note = issue_note(&mut test, &token).await;
+    test.warp_forward(MATURITY).await;
+    convert(&mut test, &token, &note, ConversionTrigger::Maturity).await.expect("convert_note");
+    test.assert_balance(&token, 0, PRINCIPAL / 5 + PRINCIPAL).await;
+}
@@ -0,0 +1,120 @@
+//! Employee option grants: vesting, partial exercise against the strike and expiry.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test options -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode, OptionGrant, VestingTerms};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const STRIKE: u64 = 2;
+
+/// Accounts of an option grant to wallet 0 paid in `payment_mint`
+struct Grant {
+    option_grant: Pubkey,
+    payment_mint: Pubkey,
+    employee_payment_account: Pubkey,
+    treasury_payment_account: Pubkey,
+}
+
+/// Grant wallet 0 `quantity` options vesting linearly over `duration` from now and
+/// expiring after `lifetime`, funding the employee with `cash` to pay the strike
+async fn create_grant(test: &mut ChainEquityTest, token: &TestToken, quantity: u64, duration: i64, lifetime: i64, cash: u64) -> Grant {
+    let payment_mint = test.create_spl_mint(6).await;
+    let employee_payment_account = test.spl_account(&payment_mint, &token.wallet(0)).await;
+    let treasury_payment_account = test.spl_account(&payment_mint, &pda::treasury(&token.mint)).await;
+    test.mint_spl(&payment_mint, &employee_payment_account, cash).await;
+
+    let now = test.now().await;
+    let option_grant = pda::option_grant(&token.mint, test.token_config(token).await.next_option_grant_id);
+    let create = instructions::create_option_grant(
+        accounts::CreateOptionGrant {
+            authority: token.authority,
+            employee: token.wallet(0),
+            token_config: pda::token_config(&token.mint),
+            payment_mint,
+            option_grant,
+            system_program: system_program::ID,
+        },
+        quantity,
+        STRIKE,
+        VestingTerms { start_ts: now, cliff_seconds: 0, duration_seconds: duration },
+        now + lifetime,
+    );
+    test.process(&[create], &[]).await.expect("create_option_grant");
+    Grant { option_grant, payment_mint, employee_payment_account, treasury_payment_account }
+}
+
+/// Wallet 0 exercises `amount` options of `grant`, co-signed by the authority
+async fn exercise(test: &mut ChainEquityTest, token: &TestToken, grant: &Grant, amount: u64) -> Result<(), BanksClientError> {
+    let employee = token.wallet(0);
+    let exercise = instructions::exercise_options(
+        accounts::ExerciseOptions {
+            employee,
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            option_grant: grant.option_grant,
+            payment_mint: grant.payment_mint,
+            employee_token_account: token.token_account(0),
+            employee_allowlist_entry: pda::allowlist_entry(&token.mint, &employee),
+            employee_payment_account: grant.employee_payment_account,
+            treasury: pda::treasury(&token.mint),
+            treasury_payment_account: grant.treasury_payment_account,
+            share_class: None,
+            employee_snapshot_balance: None,
+            employee_holder_page: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            employee_holder_number: None,
+            holder_index: None,
+        },
+        amount,
+    );
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[exercise], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn vested_options_exercise_in_parts_against_the_strike() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("OPTN", 1).await;
+    let grant = create_grant(&mut test, &token, 1_000, 1_000, 10_000, 10_000).await;
+
+    test.warp_forward(500).await;
+    assert_program_error(exercise(&mut test, &token, &grant, 501).await, ErrorCode::OptionsNotExercisable);
+
+    exercise(&mut test, &token, &grant, 200).await.expect("exercise_options");
+    exercise(&mut test, &token, &grant, 300).await.expect("exercise_options");
+    test.assert_balance(&token, 0, 500).await;
+    test.assert_supply(&token, 500).await;
+    assert_eq!(test.spl_balance(&grant.treasury_payment_account).await, 500 * STRIKE);
+    assert_eq!(test.spl_balance(&grant.employee_payment_account).await, 10_000 - 500 * STRIKE);
+
+    // Everything vested so far is spent
+    test.warp_forward(1).await;
+    assert_program_error(exercise(&mut test, &token, &grant, 2).await, ErrorCode::OptionsNotExercisable);
+    let exercised: OptionGrant = test.account(&grant.option_grant).await.unwrap();
+    assert_eq!(exercised.exercised, 500);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn expired_options_cannot_be_exercised() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("OPTN", 1).await;
+    let grant = create_grant(&mut test, &token, 1_000, 1_000, 2_000, 10_000).await;
+
+    test.warp_forward(2_000).await;
+    assert_program_error(exercise(&mut test, &token, &grant, 1_000).await, ErrorCode::OptionGrantExpired);
+    test.assert_balance(&token, 0, 0).await;
+    assert_eq!(test.spl_balance(&grant.employee_payment_account).await, 10_000);
+}
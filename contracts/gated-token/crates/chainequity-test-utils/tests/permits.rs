@@ -0,0 +1,172 @@
+//! Compliance-officer transfer permits. Needs `target/deploy/gated_token.so` from
+//! `anchor build`, so ignored by plain `cargo test` and run by the `gated-token` CI
+//! workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test permits -- --ignored
+
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, BlackoutScope, ErrorCode, PERMIT_DOMAIN};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk_ids::{system_program, sysvar};
+
+/// The message the compliance officer signs, as `permit_transfer` rebuilds it
+fn permit_message(token: &TestToken, amount: u64, nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut message = PERMIT_DOMAIN.to_vec();
+    message.extend_from_slice(PROGRAM_ID.as_ref());
+    message.extend_from_slice(token.mint.as_ref());
+    message.extend_from_slice(token.wallet(0).as_ref());
+    message.extend_from_slice(token.wallet(1).as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// The ed25519 check of `signer`'s permit for wallet 0 to send wallet 1 `amount`,
+/// followed by `permit_transfer` itself with `blackout_windows` as remaining accounts
+fn permit_transfer(
+    token: &TestToken,
+    signer: &Keypair,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    blackout_windows: &[solana_sdk::pubkey::Pubkey],
+) -> [Instruction; 2] {
+    let message = permit_message(token, amount, nonce, expiry);
+    let signature: [u8; 64] = signer.sign_message(&message).into();
+    let check = solana_ed25519_program::new_ed25519_instruction_with_signature(
+        &message,
+        &signature,
+        &signer.pubkey().to_bytes(),
+    );
+
+    let (sender, recipient) = (token.wallet(0), token.wallet(1));
+    let mut transfer = instructions::permit_transfer(
+        accounts::PermitTransfer {
+            authority: sender,
+            recipient,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            from_token_account: token.token_account(0),
+            to_token_account: token.token_account(1),
+            permit_nonce: pda::permit_nonce(&token.mint, &sender),
+            sender_allowlist_entry: None,
+            recipient_allowlist_entry: None,
+            sender_sanctions_flag: None,
+            recipient_sanctions_flag: None,
+            sender_country_rule: None,
+            recipient_country_rule: None,
+            destination_registry: None,
+            velocity_counter: None,
+            sale_proposal: None,
+            share_class: None,
+            sender_snapshot_balance: None,
+            recipient_snapshot_balance: None,
+            sender_holder_page: None,
+            recipient_holder_page: None,
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            holder_index: None,
+            last_holder_number: None,
+            recipient_holder_number: None,
+            sender_holder_number: None,
+        },
+        amount,
+        nonce,
+        expiry,
+    );
+    transfer.accounts.extend(blackout_windows.iter().map(|window| AccountMeta::new_readonly(*window, false)));
+    [check, transfer]
+}
+
+/// A token whose compliance officer is `officer`, with wallets 0 and 1 left off the
+/// allowlist and wallet 0 holding 100 shares
+async fn permit_token(test: &mut ChainEquityTest, officer: &Keypair) -> TestToken {
+    let token = test.create_token("PRMT", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    let set_officer = instructions::set_compliance_officer(
+        accounts::UpdateTokenSettings {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+        },
+        officer.pubkey(),
+    );
+    test.process(&[set_officer], &[]).await.expect("set_compliance_officer");
+    for index in 0..2 {
+        test.revoke(&token, &token.wallet(index)).await.expect("revoke_wallet");
+    }
+    token
+}
+
+/// Send a permitted transfer, signed by its sender, wallet 0
+async fn send(test: &mut ChainEquityTest, token: &TestToken, instructions: &[Instruction]) -> Result<(), BanksClientError> {
+    let sender = token.wallets[0].insecure_clone();
+    test.process(instructions, &[&sender]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn permit_moves_shares_once_and_its_nonce_cannot_be_replayed() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let officer = Keypair::new();
+    let token = permit_token(&mut test, &officer).await;
+    let expiry = test.now().await + 3_600;
+
+    let permit = permit_transfer(&token, &officer, 40, 0, expiry, &[]);
+    send(&mut test, &token, &permit).await.expect("permit_transfer");
+    test.assert_balance(&token, 1, 40).await;
+
+    // Same signed permit in a fresh transaction
+    test.warp_forward(1).await;
+    let replay = send(&mut test, &token, &permit).await;
+    assert_program_error(replay, ErrorCode::InvalidPermitNonce);
+    test.assert_balance(&token, 1, 40).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn permit_signed_by_anyone_but_the_compliance_officer_is_rejected() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let officer = Keypair::new();
+    let token = permit_token(&mut test, &officer).await;
+    let expiry = test.now().await + 3_600;
+
+    let forged = permit_transfer(&token, &Keypair::new(), 40, 0, expiry, &[]);
+    assert_program_error(send(&mut test, &token, &forged).await, ErrorCode::InvalidPermitSignature);
+    test.assert_balance(&token, 0, 100).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn permit_does_not_lift_a_blackout_window() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let officer = Keypair::new();
+    let token = permit_token(&mut test, &officer).await;
+    let now = test.now().await;
+
+    let window = pda::blackout_window(&token.mint, test.token_config(&token).await.next_blackout_id);
+    let create = instructions::create_blackout_window(
+        accounts::CreateBlackoutWindow {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            blackout_window: window,
+            system_program: system_program::ID,
+        },
+        now - 60,
+        now + 3_600,
+        BlackoutScope::AllHolders,
+    );
+    test.process(&[create], &[]).await.expect("create_blackout_window");
+
+    let permit = permit_transfer(&token, &officer, 40, 0, now + 3_600, &[window]);
+    assert_program_error(send(&mut test, &token, &permit).await, ErrorCode::TradingBlackout);
+    let skipped = permit_transfer(&token, &officer, 40, 0, now + 3_600, &[]);
+    assert_program_error(send(&mut test, &token, &skipped).await, ErrorCode::BlackoutWindowsMissing);
+}
@@ -0,0 +1,107 @@
+//! Rent-reclaim sweeps of stale compliance accounts. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test reclaim -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, VestingSchedule, VestingTerms, STALE_ENTRY_RETENTION_SECONDS};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+/// `reclaim_stale_accounts` over `stale`, passing the token program when `token_program`
+async fn sweep(test: &mut ChainEquityTest, token: &TestToken, stale: &[Pubkey], token_program: bool) -> Result<(), BanksClientError> {
+    let mut sweep = instructions::reclaim_stale_accounts(accounts::ReclaimStaleAccounts {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        token_program: token_program.then_some(token.token_program),
+    });
+    sweep.accounts.extend(stale.iter().map(|account| AccountMeta::new(*account, false)));
+    test.process(&[sweep], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn allowlist_entries_are_kept_until_long_revoked() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RCLM", 2).await;
+    let entry = pda::allowlist_entry(&token.mint, &token.wallet(1));
+    let stale = [entry, token.token_account(1)];
+
+    assert_program_error(sweep(&mut test, &token, &stale, false).await, ErrorCode::AccountNotReclaimable);
+    test.revoke(&token, &token.wallet(1)).await.unwrap();
+    test.warp_forward(1).await;
+    assert_program_error(sweep(&mut test, &token, &stale, false).await, ErrorCode::AccountNotReclaimable);
+
+    test.warp_forward(STALE_ENTRY_RETENTION_SECONDS).await;
+    sweep(&mut test, &token, &stale, false).await.expect("reclaim_stale_accounts");
+    assert!(test.allowlist_entry(&token, &token.wallet(1)).await.is_none());
+    test.assert_approved(&token, &token.wallet(0), true).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn fully_released_vesting_schedule_is_reclaimed_with_its_vault() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RCLM", 1).await;
+    let beneficiary = token.wallet(0);
+    let schedule = pda::vesting_schedule(&token.mint, test.token_config(&token).await.next_vesting_id);
+    let vault = pda::vesting_vault(&schedule);
+    let create = instructions::create_vesting_schedule(
+        accounts::CreateVestingSchedule {
+            authority: token.authority,
+            beneficiary,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            vesting_schedule: schedule,
+            vesting_vault: vault,
+            vault_allowlist_entry: None,
+            instructions: None,
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        100,
+        VestingTerms { start_ts: test.now().await, cliff_seconds: 0, duration_seconds: 3_600 },
+        false,
+    );
+    test.process(&[create], &[]).await.expect("create_vesting_schedule");
+    let stale = [schedule, vault, pda::allowlist_entry(&token.mint, &schedule)];
+
+    // Still vesting: the schedule and the shares in its vault are live
+    assert_program_error(sweep(&mut test, &token, &stale, true).await, ErrorCode::AccountNotReclaimable);
+
+    test.warp_forward(3_600).await;
+    let release = instructions::release_vested(accounts::ReleaseVested {
+        cranker: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        vesting_schedule: schedule,
+        vesting_vault: vault,
+        beneficiary_token_account: token.token_account(0),
+        beneficiary_allowlist_entry: pda::allowlist_entry(&token.mint, &beneficiary),
+        beneficiary_country_rule: None,
+        beneficiary_sanctions_flag: None,
+        share_class: None,
+        beneficiary_snapshot_balance: None,
+        beneficiary_holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        beneficiary_holder_number: None,
+        holder_index: None,
+    });
+    test.process(&[release], &[]).await.expect("release_vested");
+    test.assert_balance(&token, 0, 100).await;
+
+    // The empty vault is still open, so closing it needs the token program
+    assert_program_error(sweep(&mut test, &token, &stale, false).await, ErrorCode::AccountNotReclaimable);
+    sweep(&mut test, &token, &stale, true).await.expect("reclaim_stale_accounts");
+    assert!(test.account::<VestingSchedule>(&schedule).await.is_none());
+    assert!(test.context.banks_client.get_account(vault).await.unwrap().is_none());
+}
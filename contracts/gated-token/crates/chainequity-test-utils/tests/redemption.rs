@@ -0,0 +1,142 @@
+//! Issuer buybacks at a fixed price through redemption windows. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test redemption -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const PRICE: u64 = 3;
+
+fn window_accounts(token: &TestToken) -> accounts::UpdateRedemptionWindow {
+    accounts::UpdateRedemptionWindow {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        redemption_config: pda::redemption(&token.mint),
+    }
+}
+
+/// Set up redemptions paid in a new mint with `funding` in the vault, returning the
+/// payment mint
+async fn fund_redemptions(test: &mut ChainEquityTest, token: &TestToken, funding: u64) -> Pubkey {
+    let payment_mint = test.create_spl_mint(6).await;
+    let funding_account = test.spl_account(&payment_mint, &token.authority).await;
+    test.mint_spl(&payment_mint, &funding_account, funding).await;
+    let initialize = instructions::initialize_redemption(accounts::InitializeRedemption {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        payment_mint,
+        redemption_config: pda::redemption(&token.mint),
+        redemption_vault: pda::redemption_vault(&token.mint),
+        token_program: spl_token::ID,
+        system_program: system_program::ID,
+    });
+    let fund = instructions::fund_redemption_vault(
+        accounts::FundRedemptionVault {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            redemption_config: pda::redemption(&token.mint),
+            payment_mint,
+            redemption_vault: pda::redemption_vault(&token.mint),
+            funding_token_account: funding_account,
+            token_program: spl_token::ID,
+        },
+        funding,
+    );
+    test.process(&[initialize, fund], &[]).await.expect("funding redemptions");
+    payment_mint
+}
+
+/// Open a window at `PRICE` closing `seconds` from now
+async fn open_window(test: &mut ChainEquityTest, token: &TestToken, seconds: i64) {
+    let closes_at = test.now().await + seconds;
+    let open = instructions::open_redemption_window(window_accounts(token), PRICE, closes_at);
+    test.process(&[open], &[]).await.expect("open_redemption_window");
+}
+
+fn redeem_instruction(token: &TestToken, payment_mint: &Pubkey, payment_account: &Pubkey, amount: u64) -> Instruction {
+    let holder = token.wallet(0);
+    instructions::redeem(
+        accounts::Redeem {
+            holder,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            holder_token_account: token.token_account(0),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            redemption_config: pda::redemption(&token.mint),
+            payment_mint: *payment_mint,
+            redemption_vault: pda::redemption_vault(&token.mint),
+            holder_payment_account: *payment_account,
+            holder_snapshot_balance: None,
+            holder_page: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            holder_index: None,
+            holder_number: None,
+            last_holder_number: None,
+        },
+        amount,
+    )
+}
+
+/// Wallet 0 redeems `amount` shares, paid into `payment_account`
+async fn redeem(test: &mut ChainEquityTest, token: &TestToken, payment_mint: &Pubkey, payment_account: &Pubkey, amount: u64) -> Result<(), BanksClientError> {
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[redeem_instruction(token, payment_mint, payment_account, amount)], &[&signer]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn redemptions_pay_the_window_price_only_while_it_is_open() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RDMP", 1).await;
+    test.mint(&token, 0, 200).await.unwrap();
+    let payment_mint = fund_redemptions(&mut test, &token, 1_000).await;
+    let payment_account = test.spl_account(&payment_mint, &token.wallet(0)).await;
+
+    let closed = redeem(&mut test, &token, &payment_mint, &payment_account, 100).await;
+    assert_program_error(closed, ErrorCode::RedemptionWindowClosed);
+
+    open_window(&mut test, &token, 3_600).await;
+    redeem(&mut test, &token, &payment_mint, &payment_account, 100).await.expect("redeem");
+    test.assert_balance(&token, 0, 100).await;
+    test.assert_supply(&token, 100).await;
+    assert_eq!(test.spl_balance(&payment_account).await, 100 * PRICE);
+
+    let close = instructions::close_redemption_window(window_accounts(&token));
+    test.process(&[close], &[]).await.expect("close_redemption_window");
+    let closed = redeem(&mut test, &token, &payment_mint, &payment_account, 50).await;
+    assert_program_error(closed, ErrorCode::RedemptionWindowClosed);
+    test.assert_balance(&token, 0, 100).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn redemptions_stop_when_the_vault_runs_dry_or_the_window_lapses() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RDMP", 1).await;
+    test.mint(&token, 0, 200).await.unwrap();
+    let payment_mint = fund_redemptions(&mut test, &token, 100).await;
+    let payment_account = test.spl_account(&payment_mint, &token.wallet(0)).await;
+    open_window(&mut test, &token, 3_600).await;
+
+    let short = redeem(&mut test, &token, &payment_mint, &payment_account, 50).await;
+    assert_program_error(short, ErrorCode::InsufficientRedemptionFunds);
+    redeem(&mut test, &token, &payment_mint, &payment_account, 30).await.expect("redeem");
+    assert_eq!(test.spl_balance(&pda::redemption_vault(&token.mint)).await, 10);
+
+    test.warp_forward(3_600).await;
+    let lapsed = redeem(&mut test, &token, &payment_mint, &payment_account, 1).await;
+    assert_program_error(lapsed, ErrorCode::RedemptionWindowClosed);
+    test.assert_balance(&token, 0, 170).await;
+}
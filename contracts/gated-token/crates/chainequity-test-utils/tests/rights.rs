@@ -0,0 +1,183 @@
+//! Rights offerings: pro-rata entitlements at the record date, oversubscription and
+//! refunds on settlement. Needs `target/deploy/gated_token.so` from `anchor build`, so
+//! ignored by plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test rights -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode, RightsOffering};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const PRICE: u64 = 2;
+const DEADLINE: i64 = 3_600;
+const CASH: u64 = 1_000;
+
+/// An open offering and the accounts it is paid through
+struct Offering {
+    rights_offering: Pubkey,
+    snapshot_id: u64,
+    payment_mint: Pubkey,
+    treasury_payment_account: Pubkey,
+}
+
+/// Snapshot the holders and offer them `new_shares` at `PRICE`, funding wallets
+/// `0..holders` with `CASH` each to subscribe
+async fn open_offering(test: &mut ChainEquityTest, token: &TestToken, holders: usize, new_shares: u64, allow_oversubscription: bool) -> Offering {
+    let payment_mint = test.create_spl_mint(6).await;
+    for index in 0..holders {
+        let payment_account = test.spl_account(&payment_mint, &token.wallet(index)).await;
+        test.mint_spl(&payment_mint, &payment_account, CASH).await;
+    }
+    let treasury_payment_account = test.spl_account(&payment_mint, &pda::treasury(&token.mint)).await;
+
+    let config = test.token_config(token).await;
+    let snapshot_id = config.current_snapshot_id + 1;
+    let snapshot = instructions::create_snapshot(accounts::CreateSnapshot {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        snapshot: pda::snapshot(&token.mint, snapshot_id),
+        system_program: system_program::ID,
+    });
+    let rights_offering = pda::rights_offering(&token.mint, config.next_rights_offering_id);
+    let open = instructions::open_rights_offering(
+        accounts::OpenRightsOffering {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            mint: token.mint,
+            payment_mint,
+            snapshot: pda::snapshot(&token.mint, snapshot_id),
+            rights_offering,
+            payment_vault: pda::rights_payment_vault(&rights_offering),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        new_shares,
+        PRICE,
+        test.now().await + DEADLINE,
+        allow_oversubscription,
+    );
+    test.process(&[snapshot, open], &[]).await.expect("open_rights_offering");
+    Offering { rights_offering, snapshot_id, payment_mint, treasury_payment_account }
+}
+
+/// Wallet `index` subscribes `amount` of its rights and asks for `oversubscription` more
+async fn subscribe(test: &mut ChainEquityTest, token: &TestToken, offering: &Offering, index: usize, amount: u64, oversubscription: u64) -> Result<(), BanksClientError> {
+    let holder = token.wallet(index);
+    let subscribe = instructions::subscribe_rights(
+        accounts::SubscribeRights {
+            holder,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            rights_offering: offering.rights_offering,
+            payment_mint: offering.payment_mint,
+            snapshot: pda::snapshot(&token.mint, offering.snapshot_id),
+            subscription: pda::rights_subscription(&offering.rights_offering, &holder),
+            holder_token_account: token.token_account(index),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            holder_snapshot_balance: None,
+            holder_payment_account: test.spl_account(&offering.payment_mint, &holder).await,
+            payment_vault: pda::rights_payment_vault(&offering.rights_offering),
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        amount,
+        oversubscription,
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[subscribe], &[&signer]).await
+}
+
+/// Settle wallet `index`'s subscription, returning its payment account
+async fn settle(test: &mut ChainEquityTest, token: &TestToken, offering: &Offering, index: usize) -> Result<Pubkey, BanksClientError> {
+    let holder = token.wallet(index);
+    let payment_account = test.spl_account(&offering.payment_mint, &holder).await;
+    let settle = instructions::settle_rights_subscription(accounts::SettleRightsSubscription {
+        authority: token.authority,
+        holder,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        mint_authority: pda::mint_authority(&token.mint),
+        rights_offering: offering.rights_offering,
+        payment_mint: offering.payment_mint,
+        subscription: pda::rights_subscription(&offering.rights_offering, &holder),
+        holder_token_account: token.token_account(index),
+        holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+        payment_vault: pda::rights_payment_vault(&offering.rights_offering),
+        holder_payment_account: payment_account,
+        treasury: pda::treasury(&token.mint),
+        treasury_payment_account: offering.treasury_payment_account,
+        share_class: None,
+        holder_snapshot_balance: Some(pda::snapshot_balance(&token.mint, offering.snapshot_id, &holder)),
+        holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        holder_index: None,
+        holder_number: None,
+    });
+    test.process(&[settle], &[]).await.map(|()| payment_account)
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn lapsed_rights_go_pro_rata_to_oversubscribers() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RGHT", 3).await;
+    for (index, amount) in [100, 100, 200].into_iter().enumerate() {
+        test.mint(&token, index, amount).await.unwrap();
+    }
+    // Entitlements of 50, 50 and 100
+    let offering = open_offering(&mut test, &token, 3, 200, true).await;
+
+    assert_program_error(subscribe(&mut test, &token, &offering, 0, 51, 0).await, ErrorCode::RightsEntitlementExceeded);
+    subscribe(&mut test, &token, &offering, 0, 50, 60).await.expect("subscribe_rights");
+    subscribe(&mut test, &token, &offering, 1, 20, 60).await.expect("subscribe_rights");
+    subscribe(&mut test, &token, &offering, 2, 100, 0).await.expect("subscribe_rights");
+    assert_program_error(settle(&mut test, &token, &offering, 0).await.map(drop), ErrorCode::RightsOfferingActive);
+
+    test.warp_forward(DEADLINE).await;
+    assert_program_error(subscribe(&mut test, &token, &offering, 1, 10, 0).await, ErrorCode::RightsOfferingClosed);
+    let closed: RightsOffering = test.account(&offering.rights_offering).await.unwrap();
+    assert_eq!(closed.total_subscribed, 170);
+    assert_eq!(closed.total_oversubscription, 120);
+
+    // 30 lapsed rights against 120 requested: each oversubscriber gets 60 * 30 / 120
+    let expected = [(100 + 65, 65), (100 + 35, 35), (200 + 100, 100)];
+    for (index, (balance, allotted)) in expected.into_iter().enumerate() {
+        let payment_account = settle(&mut test, &token, &offering, index).await.expect("settle_rights_subscription");
+        test.assert_balance(&token, index, balance).await;
+        assert_eq!(test.spl_balance(&payment_account).await, CASH - allotted * PRICE, "unallotted payment is refunded");
+    }
+    test.assert_supply(&token, 400 + 200).await;
+    assert_eq!(test.spl_balance(&offering.treasury_payment_account).await, 200 * PRICE);
+    assert_eq!(test.spl_balance(&pda::rights_payment_vault(&offering.rights_offering)).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn unsubscribed_rights_lapse_without_oversubscription() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("RGHT", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    test.mint(&token, 1, 100).await.unwrap();
+    let offering = open_offering(&mut test, &token, 2, 100, false).await;
+
+    let over = subscribe(&mut test, &token, &offering, 0, 50, 10).await;
+    assert_program_error(over, ErrorCode::OversubscriptionNotAllowed);
+    subscribe(&mut test, &token, &offering, 0, 50, 0).await.expect("subscribe_rights");
+    subscribe(&mut test, &token, &offering, 1, 10, 0).await.expect("subscribe_rights");
+
+    test.warp_forward(DEADLINE).await;
+    settle(&mut test, &token, &offering, 0).await.expect("settle_rights_subscription");
+    settle(&mut test, &token, &offering, 1).await.expect("settle_rights_subscription");
+    test.assert_balance(&token, 0, 150).await;
+    test.assert_balance(&token, 1, 110).await;
+    test.assert_supply(&token, 260).await;
+    assert_eq!(test.spl_balance(&offering.treasury_payment_account).await, 60 * PRICE);
+}
@@ -0,0 +1,198 @@
+//! Tender offers: escrowed tenders, pro-rata acceptance and returns. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test tender -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode, TenderOffer, TenderOfferStatus};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const PRICE: u64 = 2;
+const DEADLINE: i64 = 3_600;
+
+/// An open offer and the accounts it pays from and refunds to
+struct Offer {
+    tender_offer: Pubkey,
+    payment_mint: Pubkey,
+    funding_account: Pubkey,
+}
+
+/// Open an offer for up to `cap` shares at `PRICE`, funded in full by the authority
+async fn open_offer(test: &mut ChainEquityTest, token: &TestToken, cap: u64, min_acceptance: u64) -> Offer {
+    let payment_mint = test.create_spl_mint(6).await;
+    let funding_account = test.spl_account(&payment_mint, &token.authority).await;
+    test.mint_spl(&payment_mint, &funding_account, cap * PRICE).await;
+    let tender_offer = pda::tender_offer(&token.mint, test.token_config(token).await.next_tender_offer_id);
+    let open = instructions::open_tender_offer(
+        accounts::OpenTenderOffer {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            payment_mint,
+            tender_offer,
+            share_escrow: pda::tender_escrow(&tender_offer),
+            vault_allowlist_entry: None,
+            payment_vault: pda::tender_payment_vault(&tender_offer),
+            funding_token_account: funding_account,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        PRICE,
+        cap,
+        min_acceptance,
+        test.now().await + DEADLINE,
+    );
+    test.process(&[open], &[]).await.expect("open_tender_offer");
+    Offer { tender_offer, payment_mint, funding_account }
+}
+
+/// Wallet `index` tenders `amount` shares into `offer`
+async fn tender(test: &mut ChainEquityTest, token: &TestToken, offer: &Offer, index: usize, amount: u64) -> Result<(), BanksClientError> {
+    let holder = token.wallet(index);
+    let tender = instructions::tender_shares(
+        accounts::TenderShares {
+            holder,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            tender_offer: offer.tender_offer,
+            share_escrow: pda::tender_escrow(&offer.tender_offer),
+            tender_position: pda::tender_position(&offer.tender_offer, &holder),
+            holder_token_account: token.token_account(index),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            holder_snapshot_balance: None,
+            holder_page: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+            holder_index: None,
+            holder_number: None,
+            last_holder_number: None,
+        },
+        amount,
+    );
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[tender], &[&signer]).await
+}
+
+/// Close `offer` after its deadline, refunding unneeded funding to the authority
+async fn close(test: &mut ChainEquityTest, token: &TestToken, offer: &Offer) -> Result<(), BanksClientError> {
+    let close = instructions::close_tender_offer(accounts::CloseTenderOffer {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        tender_offer: offer.tender_offer,
+        payment_mint: offer.payment_mint,
+        payment_vault: pda::tender_payment_vault(&offer.tender_offer),
+        refund_token_account: offer.funding_account,
+        token_program: spl_token::ID,
+    });
+    test.process(&[close], &[]).await
+}
+
+/// Settle wallet `index`'s position in `offer`, returning its payment account
+async fn settle(test: &mut ChainEquityTest, token: &TestToken, offer: &Offer, treasury_account: &Pubkey, index: usize) -> Result<Pubkey, BanksClientError> {
+    let holder = token.wallet(index);
+    let payment_account = test.spl_account(&offer.payment_mint, &holder).await;
+    let settle = instructions::settle_tender(accounts::SettleTender {
+        cranker: token.authority,
+        holder,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        tender_offer: offer.tender_offer,
+        payment_mint: offer.payment_mint,
+        tender_position: pda::tender_position(&offer.tender_offer, &holder),
+        share_escrow: pda::tender_escrow(&offer.tender_offer),
+        payment_vault: pda::tender_payment_vault(&offer.tender_offer),
+        holder_token_account: token.token_account(index),
+        holder_payment_account: payment_account,
+        holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+        treasury: pda::treasury(&token.mint),
+        treasury_token_account: *treasury_account,
+        holder_snapshot_balance: None,
+        holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        holder_index: None,
+        holder_number: None,
+    });
+    test.process(&[settle], &[]).await.map(|()| payment_account)
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn oversubscribed_tender_accepts_pro_rata_and_rounds_down() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("TNDR", 3).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    for index in 0..3 {
+        test.mint(&token, index, 50).await.unwrap();
+    }
+    let offer = open_offer(&mut test, &token, 100, 0).await;
+    for index in 0..3 {
+        tender(&mut test, &token, &offer, index, 50).await.expect("tender_shares");
+    }
+
+    assert_program_error(close(&mut test, &token, &offer).await, ErrorCode::TenderOfferActive);
+    assert_program_error(settle(&mut test, &token, &offer, &treasury_account, 0).await.map(drop), ErrorCode::TenderOfferActive);
+    test.warp_forward(DEADLINE).await;
+    close(&mut test, &token, &offer).await.expect("close_tender_offer");
+    let closed: TenderOffer = test.account(&offer.tender_offer).await.unwrap();
+    assert_eq!(closed.status, TenderOfferStatus::Settled);
+    assert_eq!(closed.accepted_amount, 100);
+
+    // 150 tendered against a cap of 100: each holder gets floor(50 * 100 / 150) accepted
+    let mut paid = 0;
+    for index in 0..3 {
+        let payment_account = settle(&mut test, &token, &offer, &treasury_account, index).await.expect("settle_tender");
+        test.assert_balance(&token, index, 17).await;
+        paid += test.spl_balance(&payment_account).await;
+    }
+    assert_eq!(test.spl_balance(&treasury_account).await, 99, "rounding never accepts beyond the cap");
+    assert_eq!(paid, 99 * PRICE);
+    assert_eq!(test.spl_balance(&pda::tender_payment_vault(&offer.tender_offer)).await, PRICE, "unpaid dust stays in the vault");
+    assert_eq!(test.spl_balance(&pda::tender_escrow(&offer.tender_offer)).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn undersubscribed_tender_returns_every_share_and_the_funding() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("TNDR", 1).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    test.mint(&token, 0, 50).await.unwrap();
+    let offer = open_offer(&mut test, &token, 100, 60).await;
+    tender(&mut test, &token, &offer, 0, 40).await.expect("tender_shares");
+
+    test.warp_forward(DEADLINE).await;
+    assert_program_error(tender(&mut test, &token, &offer, 0, 10).await, ErrorCode::TenderOfferClosed);
+    close(&mut test, &token, &offer).await.expect("close_tender_offer");
+    let closed: TenderOffer = test.account(&offer.tender_offer).await.unwrap();
+    assert_eq!(closed.status, TenderOfferStatus::Withdrawn);
+    assert_eq!(test.spl_balance(&offer.funding_account).await, 100 * PRICE);
+
+    // Returned shares wait out a pause like any other transfer
+    let pause = instructions::pause(accounts::PauseToken {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[pause], &[]).await.expect("pause");
+    assert_program_error(settle(&mut test, &token, &offer, &treasury_account, 0).await.map(drop), ErrorCode::TokenPaused);
+    let unpause = instructions::unpause(accounts::SetPaused {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[unpause], &[]).await.expect("unpause");
+    test.warp_forward(1).await;
+
+    let payment_account = settle(&mut test, &token, &offer, &treasury_account, 0).await.expect("settle_tender");
+    test.assert_balance(&token, 0, 50).await;
+    assert_eq!(test.spl_balance(&payment_account).await, 0);
+    assert_eq!(test.spl_balance(&treasury_account).await, 0);
+}
@@ -0,0 +1,119 @@
+//! Timelocked operations: queueing, the delay, execution and cancellation. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test timelock -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, OperationAccount, PendingOperation, MAX_TIMELOCK_SECONDS};
+use chainequity_client::{instructions, pda, PROGRAM_ID};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const DELAY: i64 = 3_600;
+
+/// `set_timelock` to `delay_seconds`, signed for by the timelock PDA when `timelocked`
+fn set_timelock(token: &TestToken, delay_seconds: i64, timelocked: bool) -> Instruction {
+    instructions::set_timelock(
+        accounts::SetTimelock {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            timelock: timelocked.then(|| pda::timelock(&token.mint)),
+        },
+        delay_seconds,
+    )
+}
+
+/// Queue `queued` behind the timelock, returning the pending operation
+async fn queue(test: &mut ChainEquityTest, token: &TestToken, queued: &Instruction) -> Result<Pubkey, BanksClientError> {
+    let operation = pda::pending_operation(&token.mint, test.token_config(token).await.next_pending_operation_id);
+    let queue = instructions::queue_operation(
+        accounts::QueueOperation {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            operation,
+            system_program: system_program::ID,
+        },
+        queued.data.clone(),
+        queued
+            .accounts
+            .iter()
+            .map(|account| OperationAccount {
+                pubkey: account.pubkey,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+    );
+    test.process(&[queue], &[]).await.map(|()| operation)
+}
+
+/// Execute the pending `operation` holding `queued`; the authority signs the outer
+/// transaction and the timelock PDA signs inside the program
+async fn execute(test: &mut ChainEquityTest, token: &TestToken, operation: &Pubkey, queued: &Instruction) -> Result<(), BanksClientError> {
+    let timelock = pda::timelock(&token.mint);
+    let mut execute = instructions::execute_queued_operation(accounts::ExecuteQueuedOperation {
+        executor: token.authority,
+        operation: *operation,
+        queued_by: token.authority,
+        timelock,
+        token_config: pda::token_config(&token.mint),
+    });
+    execute.accounts.extend(queued.accounts.iter().map(|account| AccountMeta {
+        is_signer: account.is_signer && account.pubkey != timelock,
+        ..account.clone()
+    }));
+    execute.accounts.push(AccountMeta::new_readonly(PROGRAM_ID, false));
+    test.process(&[execute], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn queued_operations_execute_only_after_the_delay() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("TMLK", 0).await;
+    let too_long = test.process(&[set_timelock(&token, MAX_TIMELOCK_SECONDS + 1, false)], &[]).await;
+    assert_program_error(too_long, ErrorCode::InvalidTimelock);
+    test.process(&[set_timelock(&token, DELAY, false)], &[]).await.expect("set_timelock");
+
+    // Changing the delay is itself timelocked
+    let direct = test.process(&[set_timelock(&token, 0, false)], &[]).await;
+    assert_program_error(direct, ErrorCode::TimelockRequired);
+
+    let queued = set_timelock(&token, 0, true);
+    let operation = queue(&mut test, &token, &queued).await.expect("queue_operation");
+    assert_program_error(execute(&mut test, &token, &operation, &queued).await, ErrorCode::TimelockNotElapsed);
+    test.warp_forward(DELAY - 1).await;
+    assert_program_error(execute(&mut test, &token, &operation, &queued).await, ErrorCode::TimelockNotElapsed);
+
+    test.warp_forward(1).await;
+    execute(&mut test, &token, &operation, &queued).await.expect("execute_queued_operation");
+    assert_eq!(test.token_config(&token).await.timelock_seconds, 0);
+    assert!(test.account::<PendingOperation>(&operation).await.is_none(), "execution closes the operation");
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn cancelled_operations_never_execute() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("TMLK", 0).await;
+    let queued = set_timelock(&token, 0, true);
+    assert_program_error(queue(&mut test, &token, &queued).await.map(drop), ErrorCode::TimelockNotEnabled);
+
+    test.process(&[set_timelock(&token, DELAY, false)], &[]).await.expect("set_timelock");
+    let operation = queue(&mut test, &token, &queued).await.expect("queue_operation");
+    let cancel = instructions::cancel_queued_operation(accounts::CancelQueuedOperation {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        operation,
+        queued_by: token.authority,
+    });
+    test.process(&[cancel], &[]).await.expect("cancel_queued_operation");
+    assert!(test.account::<PendingOperation>(&operation).await.is_none());
+
+    test.warp_forward(DELAY).await;
+    assert!(execute(&mut test, &token, &operation, &queued).await.is_err());
+    assert_eq!(test.token_config(&token).await.timelock_seconds, DELAY);
+}
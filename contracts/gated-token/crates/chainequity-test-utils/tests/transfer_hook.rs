@@ -0,0 +1,150 @@
+//! Token-2022 shares running the allowlist transfer hook. Needs `gated_token.so` and
+//! `allowlist_transfer_hook.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test transfer_hook -- --ignored
+
+use anchor_spl::token_2022::spl_token_2022;
+use chainequity_client::gated_token::{accounts, ErrorCode, ReleaseCondition};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use solana_sdk_ids::system_program;
+
+/// Plain Token-2022 `transfer_checked` of `amount` from wallet `from` to wallet `to`,
+/// forwarding the hook's accounts as any wallet would
+fn raw_transfer(token: &TestToken, from: usize, to: usize, amount: u64) -> Instruction {
+    let mut transfer = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::ID,
+        &token.token_account(from),
+        &token.mint,
+        &token.token_account(to),
+        &token.wallet(from),
+        &[],
+        amount,
+        0,
+    )
+    .unwrap();
+    transfer.accounts.extend(instructions::transfer_hook_accounts(&token.mint, &token.wallet(from), &token.wallet(to)));
+    transfer
+}
+
+/// Assert that `result` failed with the transfer hook's error `expected`
+fn assert_hook_error(result: Result<(), BanksClientError>, expected: allowlist_transfer_hook::ErrorCode) {
+    let code = u32::from(expected);
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(actual))))
+        | Err(BanksClientError::SimulationError {
+            err: TransactionError::InstructionError(_, InstructionError::Custom(actual)),
+            ..
+        }) => assert_eq!(actual, code, "expected transfer hook error {code}, got {actual}"),
+        other => panic!("expected transfer hook error {code}, got {other:?}"),
+    }
+}
+
+/// `create_escrow` of `amount` from wallet 0 to wallet 1, with or without the vault's
+/// allowlist entry
+fn create_escrow(token: &TestToken, escrow: &Pubkey, amount: u64, vault_entry: bool) -> Instruction {
+    let sender = token.wallet(0);
+    let mut create = instructions::create_escrow(
+        accounts::CreateEscrow {
+            sender,
+            recipient: token.wallet(1),
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            sender_token_account: token.token_account(0),
+            sender_allowlist_entry: pda::allowlist_entry(&token.mint, &sender),
+            sender_country_rule: None,
+            sender_sanctions_flag: None,
+            destination_registry: None,
+            escrow: *escrow,
+            escrow_vault: pda::escrow_vault(escrow),
+            vault_allowlist_entry: vault_entry.then(|| pda::allowlist_entry(&token.mint, escrow)),
+            sender_snapshot_balance: None,
+            sender_holder_page: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+            holder_index: None,
+            last_holder_number: None,
+            sender_holder_number: None,
+        },
+        amount,
+        ReleaseCondition::CounterSignature { signer: token.authority },
+    );
+    create.accounts.extend(instructions::transfer_hook_accounts(&token.mint, &sender, escrow));
+    create
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so and allowlist_transfer_hook.so from `anchor build`"]
+async fn hooked_shares_move_between_approved_wallets_by_either_path() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test_with_hook()).await;
+    let token = test.create_hooked_token("HOOK", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+
+    test.transfer(&token, 0, 1, 40).await.expect("gated_transfer");
+    let sender = token.wallets[0].insecure_clone();
+    test.process(&[raw_transfer(&token, 0, 1, 10)], &[&sender]).await.expect("raw transfer_checked");
+    test.assert_balance(&token, 0, 50).await;
+    test.assert_balance(&token, 1, 50).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so and allowlist_transfer_hook.so from `anchor build`"]
+async fn raw_transfer_to_a_wallet_off_the_allowlist_is_rejected() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test_with_hook()).await;
+    let token = test.create_hooked_token("HOOK", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    test.revoke(&token, &token.wallet(1)).await.unwrap();
+
+    let sender = token.wallets[0].insecure_clone();
+    let transfer = test.process(&[raw_transfer(&token, 0, 1, 10)], &[&sender]).await;
+    assert_hook_error(transfer, allowlist_transfer_hook::ErrorCode::RecipientNotApproved);
+    test.assert_balance(&token, 0, 100).await;
+    test.assert_balance(&token, 1, 0).await;
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so and allowlist_transfer_hook.so from `anchor build`"]
+async fn escrow_vault_of_a_hooked_mint_needs_an_allowlist_entry() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test_with_hook()).await;
+    let token = test.create_hooked_token("HOOK", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    let escrow = pda::escrow(&token.mint, test.token_config(&token).await.next_escrow_id);
+    let sender = token.wallets[0].insecure_clone();
+
+    let unlisted = test.process(&[create_escrow(&token, &escrow, 60, false)], &[&sender]).await;
+    assert_program_error(unlisted, ErrorCode::VaultAllowlistEntryRequired);
+
+    test.process(&[create_escrow(&token, &escrow, 60, true)], &[&sender]).await.expect("create_escrow");
+    test.assert_approved(&token, &escrow, true).await;
+    test.assert_balance(&token, 0, 40).await;
+
+    // Cancelling drains the vault back through the hook and retires its entry
+    let sender_key = token.wallet(0);
+    let mut cancel = instructions::cancel_escrow(accounts::CancelEscrow {
+        sender: sender_key,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        escrow,
+        escrow_vault: pda::escrow_vault(&escrow),
+        vault_allowlist_entry: Some(pda::allowlist_entry(&token.mint, &escrow)),
+        sender_token_account: token.token_account(0),
+        sender_allowlist_entry: pda::allowlist_entry(&token.mint, &sender_key),
+        price_feed: None,
+        sender_snapshot_balance: None,
+        sender_holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        holder_index: None,
+        sender_holder_number: None,
+    });
+    cancel.accounts.extend(instructions::transfer_hook_accounts(&token.mint, &escrow, &sender_key));
+    test.process(&[cancel], &[&sender]).await.expect("cancel_escrow");
+    test.assert_balance(&token, 0, 100).await;
+    assert!(test.allowlist_entry(&token, &escrow).await.is_none());
+}
@@ -0,0 +1,159 @@
+//! Vesting schedules: cliff, linear release and revocation. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test vesting -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, VestingSchedule, VestingTerms};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const CLIFF: i64 = 1_000;
+const DURATION: i64 = 4_000;
+
+/// Vest `amount` to wallet 0 from now, with a `CLIFF` cliff over `DURATION`
+async fn create_schedule(test: &mut ChainEquityTest, token: &TestToken, amount: u64, revocable: bool) -> Pubkey {
+    let schedule = pda::vesting_schedule(&token.mint, test.token_config(token).await.next_vesting_id);
+    let create = instructions::create_vesting_schedule(
+        accounts::CreateVestingSchedule {
+            authority: token.authority,
+            beneficiary: token.wallet(0),
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            vesting_schedule: schedule,
+            vesting_vault: pda::vesting_vault(&schedule),
+            vault_allowlist_entry: None,
+            instructions: None,
+            trusted_caller: None,
+            token_program: token.token_program,
+            system_program: system_program::ID,
+        },
+        amount,
+        VestingTerms { start_ts: test.now().await, cliff_seconds: CLIFF, duration_seconds: DURATION },
+        revocable,
+    );
+    test.process(&[create], &[]).await.expect("create_vesting_schedule");
+    schedule
+}
+
+/// Crank `release_vested` on `schedule`, paying wallet 0
+async fn release(test: &mut ChainEquityTest, token: &TestToken, schedule: &Pubkey) -> Result<(), BanksClientError> {
+    let beneficiary = token.wallet(0);
+    let release = instructions::release_vested(accounts::ReleaseVested {
+        cranker: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        vesting_schedule: *schedule,
+        vesting_vault: pda::vesting_vault(schedule),
+        beneficiary_token_account: token.token_account(0),
+        beneficiary_allowlist_entry: pda::allowlist_entry(&token.mint, &beneficiary),
+        beneficiary_country_rule: None,
+        beneficiary_sanctions_flag: None,
+        share_class: None,
+        beneficiary_snapshot_balance: None,
+        beneficiary_holder_page: None,
+        token_program: token.token_program,
+        system_program: system_program::ID,
+        beneficiary_holder_number: None,
+        holder_index: None,
+    });
+    test.process(&[release], &[]).await
+}
+
+/// `revoke_vesting` on `schedule`, returning the unvested shares to `treasury_account`
+async fn revoke(test: &mut ChainEquityTest, token: &TestToken, schedule: &Pubkey, treasury_account: &Pubkey) -> Result<(), BanksClientError> {
+    let revoke = instructions::revoke_vesting(accounts::RevokeVesting {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        program_config: pda::program_config(),
+        mint: token.mint,
+        vesting_schedule: *schedule,
+        vesting_vault: pda::vesting_vault(schedule),
+        treasury: pda::treasury(&token.mint),
+        treasury_token_account: *treasury_account,
+        token_program: token.token_program,
+    });
+    test.process(&[revoke], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn shares_release_after_the_cliff_then_linearly() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("VEST", 1).await;
+    let schedule = create_schedule(&mut test, &token, 1_000, false).await;
+
+    test.warp_forward(CLIFF - 1).await;
+    assert_program_error(release(&mut test, &token, &schedule).await, ErrorCode::NothingVested);
+
+    // Halfway through: the cliff releases everything accrued since the start
+    test.warp_forward(DURATION / 2 - CLIFF + 1).await;
+    release(&mut test, &token, &schedule).await.expect("release_vested");
+    test.assert_balance(&token, 0, 500).await;
+    test.warp_forward(1).await;
+    assert_program_error(release(&mut test, &token, &schedule).await, ErrorCode::NothingVested);
+
+    test.warp_forward(DURATION).await;
+    release(&mut test, &token, &schedule).await.expect("release_vested");
+    test.assert_balance(&token, 0, 1_000).await;
+    let vested: VestingSchedule = test.account(&schedule).await.unwrap();
+    assert_eq!(vested.released_amount, vested.total_amount);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn revocation_returns_the_unvested_remainder_to_the_treasury() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("VEST", 1).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    let fixed = create_schedule(&mut test, &token, 1_000, false).await;
+    let schedule = create_schedule(&mut test, &token, 1_000, true).await;
+
+    assert_program_error(revoke(&mut test, &token, &fixed, &treasury_account).await, ErrorCode::VestingNotRevocable);
+
+    test.warp_forward(DURATION * 3 / 4).await;
+    revoke(&mut test, &token, &schedule, &treasury_account).await.expect("revoke_vesting");
+    assert_eq!(test.spl_balance(&treasury_account).await, 250);
+    test.warp_forward(1).await;
+    assert_program_error(revoke(&mut test, &token, &schedule, &treasury_account).await, ErrorCode::VestingNotRevocable);
+
+    // What had vested stays releasable, and nothing more accrues
+    test.warp_forward(DURATION).await;
+    release(&mut test, &token, &schedule).await.expect("release_vested");
+    test.assert_balance(&token, 0, 750).await;
+    test.warp_forward(1).await;
+    assert_program_error(release(&mut test, &token, &schedule).await, ErrorCode::NothingVested);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn releases_and_revocations_wait_out_a_pause() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("VEST", 1).await;
+    let treasury_account = test.spl_account(&token.mint, &pda::treasury(&token.mint)).await;
+    let schedule = create_schedule(&mut test, &token, 1_000, true).await;
+    test.warp_forward(DURATION / 2).await;
+
+    let pause = instructions::pause(accounts::PauseToken {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[pause], &[]).await.expect("pause");
+    assert_program_error(release(&mut test, &token, &schedule).await, ErrorCode::TokenPaused);
+    assert_program_error(revoke(&mut test, &token, &schedule, &treasury_account).await, ErrorCode::TokenPaused);
+
+    let unpause = instructions::unpause(accounts::SetPaused {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+    });
+    test.process(&[unpause], &[]).await.expect("unpause");
+    test.warp_forward(1).await;
+    release(&mut test, &token, &schedule).await.expect("release_vested");
+    assert!(test.balance(&token, 0).await > 0);
+}
@@ -0,0 +1,151 @@
+//! SPL Governance voter-weight plugin: registrar, holder and max voter-weight records.
+//! Needs `target/deploy/gated_token.so` from `anchor build`, so ignored by plain
+//! `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test voter_weight -- --ignored
+
+use chainequity_client::gated_token::{accounts, ErrorCode, MaxVoterWeightRecord, ShareClassKind, VoterWeightRecord};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sysvar::clock::Clock;
+use solana_sdk_ids::system_program;
+
+/// Start the bank with a stand-in realm owned by a mock governance program
+async fn start_with_realm() -> (ChainEquityTest, Pubkey) {
+    let realm = Pubkey::new_unique();
+    let mut program_test = ChainEquityTest::program_test();
+    program_test.add_account(realm, Account {
+        lamports: 1_000_000_000,
+        data: vec![0; 64],
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    });
+    (ChainEquityTest::start(program_test).await, realm)
+}
+
+/// A token with 100 and 300 minted to wallets 0 and 1
+async fn holders_token(test: &mut ChainEquityTest) -> TestToken {
+    let token = test.create_token("VOTE", 2).await;
+    test.mint(&token, 0, 100).await.unwrap();
+    test.mint(&token, 1, 300).await.unwrap();
+    token
+}
+
+async fn take_snapshot(test: &mut ChainEquityTest, token: &TestToken) {
+    let snapshot_id = test.token_config(token).await.current_snapshot_id + 1;
+    let snapshot = instructions::create_snapshot(accounts::CreateSnapshot {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        mint: token.mint,
+        snapshot: pda::snapshot(&token.mint, snapshot_id),
+        system_program: system_program::ID,
+    });
+    test.process(&[snapshot], &[]).await.expect("create_snapshot");
+}
+
+async fn configure_registrar(test: &mut ChainEquityTest, token: &TestToken, realm: &Pubkey) -> Result<(), BanksClientError> {
+    let configure = instructions::configure_voter_weight_registrar(accounts::ConfigureVoterWeightRegistrar {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        realm: *realm,
+        registrar: pda::registrar(&token.mint),
+        system_program: system_program::ID,
+    });
+    test.process(&[configure], &[]).await
+}
+
+/// Refresh wallet `index`'s voter-weight record, returning it
+async fn update_voter_weight(test: &mut ChainEquityTest, token: &TestToken, realm: &Pubkey, index: usize, share_class: Option<Pubkey>) -> Result<VoterWeightRecord, BanksClientError> {
+    let holder = token.wallet(index);
+    let record = pda::voter_weight_record(realm, &token.mint, &holder);
+    let snapshot_id = test.token_config(token).await.current_snapshot_id;
+    let update = instructions::update_voter_weight_record(accounts::UpdateVoterWeightRecord {
+        holder,
+        token_config: pda::token_config(&token.mint),
+        registrar: pda::registrar(&token.mint),
+        snapshot: pda::snapshot(&token.mint, snapshot_id),
+        share_class,
+        holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+        holder_token_account: token.token_account(index),
+        holder_snapshot_balance: None,
+        voter_weight_record: record,
+        system_program: system_program::ID,
+    });
+    let signer = token.wallets[index].insecure_clone();
+    test.process(&[update], &[&signer]).await?;
+    Ok(test.account(&record).await.expect("voter weight record"))
+}
+
+async fn current_slot(test: &mut ChainEquityTest) -> u64 {
+    test.context.banks_client.get_sysvar::<Clock>().await.unwrap().slot
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn voter_weight_is_the_record_date_balance_times_class_votes() {
+    let (mut test, realm) = start_with_realm().await;
+    let token = holders_token(&mut test).await;
+    let set_class = instructions::set_share_class(
+        accounts::SetShareClass {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            share_class: pda::share_class(&token.mint),
+            system_program: system_program::ID,
+        },
+        "Class B".to_string(),
+        ShareClassKind::Common,
+        2,
+        0,
+        true,
+        0,
+    );
+    test.process(&[set_class], &[]).await.expect("set_share_class");
+    take_snapshot(&mut test, &token).await;
+    configure_registrar(&mut test, &token, &realm).await.expect("configure_voter_weight_registrar");
+
+    let unweighted = update_voter_weight(&mut test, &token, &realm, 0, None).await;
+    assert_program_error(unweighted.map(drop), ErrorCode::ShareClassRequired);
+
+    let record = update_voter_weight(&mut test, &token, &realm, 0, Some(pda::share_class(&token.mint))).await
+        .expect("update_voter_weight_record");
+    assert_eq!(record.voter_weight, 200);
+    assert_eq!((record.realm, record.governing_token_owner), (realm, token.wallet(0)));
+    assert_eq!(record.voter_weight_expiry, Some(current_slot(&mut test).await), "valid for this slot only");
+
+    let max_record = pda::max_voter_weight_record(&realm, &token.mint);
+    let update_max = instructions::update_max_voter_weight_record(accounts::UpdateMaxVoterWeightRecord {
+        payer: token.authority,
+        token_config: pda::token_config(&token.mint),
+        registrar: pda::registrar(&token.mint),
+        snapshot: pda::snapshot(&token.mint, 1),
+        share_class: Some(pda::share_class(&token.mint)),
+        max_voter_weight_record: max_record,
+        system_program: system_program::ID,
+    });
+    test.process(&[update_max], &[]).await.expect("update_max_voter_weight_record");
+    let max: MaxVoterWeightRecord = test.account(&max_record).await.unwrap();
+    assert_eq!(max.max_voter_weight, 800);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn only_a_program_owned_realm_and_approved_holders_count() {
+    let (mut test, realm) = start_with_realm().await;
+    let token = holders_token(&mut test).await;
+    take_snapshot(&mut test, &token).await;
+
+    let wallet_realm = configure_registrar(&mut test, &token, &token.wallet(0)).await;
+    assert_program_error(wallet_realm, ErrorCode::InvalidRealm);
+    configure_registrar(&mut test, &token, &realm).await.expect("configure_voter_weight_registrar");
+
+    let record = update_voter_weight(&mut test, &token, &realm, 1, None).await.expect("update_voter_weight_record");
+    assert_eq!(record.voter_weight, 300);
+    test.revoke(&token, &token.wallet(1)).await.unwrap();
+    test.warp_forward(1).await;
+    let revoked = update_voter_weight(&mut test, &token, &realm, 1, None).await;
+    assert_program_error(revoked.map(drop), ErrorCode::WalletNotApproved);
+}
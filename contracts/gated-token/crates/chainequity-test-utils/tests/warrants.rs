@@ -0,0 +1,134 @@
+//! Warrants: partial exercise against the strike, expiry and closing. Needs
+//! `target/deploy/gated_token.so` from `anchor build`, so ignored by plain `cargo test`
+//! and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test warrants -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, ErrorCode, Warrant};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const STRIKE: u64 = 3;
+const LIFETIME: i64 = 3_600;
+
+/// Accounts of a warrant held by wallet 0 and paid in `payment_mint`
+struct Issued {
+    warrant: Pubkey,
+    payment_mint: Pubkey,
+    holder_payment_account: Pubkey,
+    treasury_payment_account: Pubkey,
+}
+
+/// Issue wallet 0 a warrant over `quantity` shares expiring `LIFETIME` from now,
+/// funding the holder with `cash` to pay the strike
+async fn issue_warrant(test: &mut ChainEquityTest, token: &TestToken, quantity: u64, cash: u64) -> Issued {
+    let payment_mint = test.create_spl_mint(6).await;
+    let holder_payment_account = test.spl_account(&payment_mint, &token.wallet(0)).await;
+    let treasury_payment_account = test.spl_account(&payment_mint, &pda::treasury(&token.mint)).await;
+    test.mint_spl(&payment_mint, &holder_payment_account, cash).await;
+
+    let warrant = pda::warrant(&token.mint, test.token_config(token).await.next_warrant_id);
+    let issue = instructions::issue_warrant(
+        accounts::IssueWarrant {
+            authority: token.authority,
+            holder: token.wallet(0),
+            token_config: pda::token_config(&token.mint),
+            payment_mint,
+            warrant,
+            system_program: system_program::ID,
+        },
+        quantity,
+        STRIKE,
+        test.now().await + LIFETIME,
+    );
+    test.process(&[issue], &[]).await.expect("issue_warrant");
+    Issued { warrant, payment_mint, holder_payment_account, treasury_payment_account }
+}
+
+/// Wallet 0 exercises `amount` of `issued`, co-signed by the authority
+async fn exercise(test: &mut ChainEquityTest, token: &TestToken, issued: &Issued, amount: u64) -> Result<(), BanksClientError> {
+    let holder = token.wallet(0);
+    let exercise = instructions::exercise_warrant(
+        accounts::ExerciseWarrant {
+            holder,
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            program_config: pda::program_config(),
+            mint: token.mint,
+            mint_authority: pda::mint_authority(&token.mint),
+            warrant: issued.warrant,
+            payment_mint: issued.payment_mint,
+            holder_token_account: token.token_account(0),
+            holder_allowlist_entry: pda::allowlist_entry(&token.mint, &holder),
+            holder_payment_account: issued.holder_payment_account,
+            treasury: pda::treasury(&token.mint),
+            treasury_payment_account: issued.treasury_payment_account,
+            share_class: None,
+            holder_snapshot_balance: None,
+            holder_page: None,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            holder_index: None,
+            holder_number: None,
+        },
+        amount,
+    );
+    let signer = token.wallets[0].insecure_clone();
+    test.process(&[exercise], &[&signer]).await
+}
+
+async fn close(test: &mut ChainEquityTest, token: &TestToken, issued: &Issued) -> Result<(), BanksClientError> {
+    let close = instructions::close_warrant(accounts::CloseWarrant {
+        authority: token.authority,
+        token_config: pda::token_config(&token.mint),
+        warrant: issued.warrant,
+    });
+    test.process(&[close], &[]).await
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn warrants_exercise_in_parts_until_spent() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("WRNT", 1).await;
+    let issued = issue_warrant(&mut test, &token, 100, 1_000).await;
+
+    assert_program_error(exercise(&mut test, &token, &issued, 101).await, ErrorCode::WarrantQuantityExceeded);
+    exercise(&mut test, &token, &issued, 40).await.expect("exercise_warrant");
+    test.assert_balance(&token, 0, 40).await;
+    assert_eq!(test.spl_balance(&issued.treasury_payment_account).await, 40 * STRIKE);
+    assert_program_error(close(&mut test, &token, &issued).await, ErrorCode::WarrantActive);
+
+    exercise(&mut test, &token, &issued, 60).await.expect("exercise_warrant");
+    test.assert_balance(&token, 0, 100).await;
+    test.assert_supply(&token, 100).await;
+    assert_eq!(test.spl_balance(&issued.holder_payment_account).await, 1_000 - 100 * STRIKE);
+    let spent: Warrant = test.account(&issued.warrant).await.unwrap();
+    assert_eq!(spent.remaining(), 0);
+
+    // Fully exercised warrants close before expiry
+    test.warp_forward(1).await;
+    close(&mut test, &token, &issued).await.expect("close_warrant");
+    assert!(test.account::<Warrant>(&issued.warrant).await.is_none());
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn expired_warrants_cannot_be_exercised_and_close() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    let token = test.create_token("WRNT", 1).await;
+    let issued = issue_warrant(&mut test, &token, 100, 1_000).await;
+    exercise(&mut test, &token, &issued, 10).await.expect("exercise_warrant");
+
+    test.warp_forward(LIFETIME).await;
+    assert_program_error(exercise(&mut test, &token, &issued, 10).await, ErrorCode::WarrantExpired);
+    test.assert_balance(&token, 0, 10).await;
+    assert_eq!(test.spl_balance(&issued.holder_payment_account).await, 1_000 - 10 * STRIKE);
+
+    close(&mut test, &token, &issued).await.expect("close_warrant");
+    assert!(test.account::<Warrant>(&issued.warrant).await.is_none());
+}
@@ -0,0 +1,146 @@
+//! Liquidation waterfalls: preferences by seniority, pro-rata shortfalls within a tier
+//! and the residual to common. Needs `target/deploy/gated_token.so` from `anchor build`,
+//! so ignored by plain `cargo test` and run by the `gated-token` CI workflow:
+//!
+//!     cargo test -p chainequity-test-utils --test waterfall -- --ignored
+
+use anchor_spl::token::spl_token;
+use chainequity_client::gated_token::{accounts, Distribution, ErrorCode, ShareClassKind, WaterfallClassInput};
+use chainequity_client::{instructions, pda};
+use chainequity_test_utils::{assert_program_error, ChainEquityTest, TestToken};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk_ids::system_program;
+
+const ISSUE_PRICE: u64 = 10;
+const CLAIM_WINDOW: i64 = 30 * 86_400;
+
+/// A one-holder token of class `kind` at `seniority` with `shares` outstanding and
+/// (when `multiple_bps` is non-zero) a non-participating preference at `ISSUE_PRICE`
+async fn class_token(test: &mut ChainEquityTest, symbol: &str, kind: ShareClassKind, seniority: u8, shares: u64, multiple_bps: u32) -> TestToken {
+    let token = test.create_token(symbol, 1).await;
+    test.mint(&token, 0, shares).await.unwrap();
+    let set_class = instructions::set_share_class(
+        accounts::SetShareClass {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            share_class: pda::share_class(&token.mint),
+            system_program: system_program::ID,
+        },
+        symbol.to_string(),
+        kind,
+        1,
+        seniority,
+        true,
+        0,
+    );
+    test.process(&[set_class], &[]).await.expect("set_share_class");
+    if multiple_bps > 0 {
+        set_preference(test, &token, multiple_bps, ISSUE_PRICE).await.expect("set_liquidation_preference");
+    }
+    token
+}
+
+async fn set_preference(test: &mut ChainEquityTest, token: &TestToken, multiple_bps: u32, issue_price: u64) -> Result<(), BanksClientError> {
+    let set = instructions::set_liquidation_preference(
+        accounts::SetLiquidationPreference {
+            authority: token.authority,
+            token_config: pda::token_config(&token.mint),
+            share_class: pda::share_class(&token.mint),
+        },
+        multiple_bps,
+        issue_price,
+        false,
+    );
+    test.process(&[set], &[]).await
+}
+
+/// Payout mint plus the authority's account holding `proceeds` of it
+async fn fund_proceeds(test: &mut ChainEquityTest, proceeds: u64) -> (Pubkey, Pubkey) {
+    let payout_mint = test.create_spl_mint(6).await;
+    let funding_account = test.spl_account(&payout_mint, &test.payer()).await;
+    test.mint_spl(&payout_mint, &funding_account, proceeds).await;
+    (payout_mint, funding_account)
+}
+
+/// `execute_waterfall` of `proceeds` across `classes`, each at its full supply
+async fn execute(test: &mut ChainEquityTest, classes: &[&TestToken], payout_mint: &Pubkey, funding_account: &Pubkey, proceeds: u64, claim_deadline: i64) -> Result<(), BanksClientError> {
+    let mut inputs = Vec::new();
+    let mut class_accounts = Vec::new();
+    for token in classes {
+        let config = test.token_config(token).await;
+        let distribution = pda::distribution(&token.mint, config.next_distribution_id);
+        inputs.push(WaterfallClassInput { snapshot_root: [0; 32], snapshot_supply: config.total_supply });
+        class_accounts.extend([
+            AccountMeta::new(pda::token_config(&token.mint), false),
+            AccountMeta::new_readonly(pda::share_class(&token.mint), false),
+            AccountMeta::new_readonly(token.mint, false),
+            AccountMeta::new(distribution, false),
+            AccountMeta::new(pda::distribution_vault(&distribution), false),
+        ]);
+    }
+    let mut execute = instructions::execute_waterfall(
+        accounts::ExecuteWaterfall {
+            authority: test.payer(),
+            payout_mint: *payout_mint,
+            funding_token_account: *funding_account,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+            program_config: pda::program_config(),
+        },
+        proceeds,
+        claim_deadline,
+        inputs,
+    );
+    execute.accounts.extend(class_accounts);
+    test.process(&[execute], &[]).await
+}
+
+/// What the class's first distribution holds, or `None` if it was paid nothing
+async fn payout(test: &mut ChainEquityTest, token: &TestToken) -> Option<u64> {
+    let distribution = pda::distribution(&token.mint, 0);
+    let paid: Distribution = test.account(&distribution).await?;
+    assert_eq!(test.spl_balance(&pda::distribution_vault(&distribution)).await, paid.total_amount);
+    Some(paid.total_amount)
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn preferences_are_paid_first_and_common_takes_the_residual() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    // A 2x preference on 100 shares at 10 is owed 2,000
+    let preferred = class_token(&mut test, "PREF", ShareClassKind::Preferred, 0, 100, 20_000).await;
+    let common = class_token(&mut test, "COMM", ShareClassKind::Common, 1, 300, 0).await;
+    let (payout_mint, funding_account) = fund_proceeds(&mut test, 5_000).await;
+    let deadline = test.now().await + CLAIM_WINDOW;
+
+    execute(&mut test, &[&common, &preferred], &payout_mint, &funding_account, 5_000, deadline).await.expect("execute_waterfall");
+    assert_eq!(payout(&mut test, &preferred).await, Some(2_000));
+    assert_eq!(payout(&mut test, &common).await, Some(3_000), "non-participating preferred sits out the residual");
+    assert_eq!(test.spl_balance(&funding_account).await, 0);
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn a_shortfall_splits_the_senior_tier_pro_rata_and_leaves_juniors_unpaid() {
+    let mut test = ChainEquityTest::start(ChainEquityTest::program_test()).await;
+    // Owed 1,000 and 3,000 at the same seniority
+    let series_a = class_token(&mut test, "SERA", ShareClassKind::Preferred, 0, 100, 10_000).await;
+    let series_b = class_token(&mut test, "SERB", ShareClassKind::Preferred, 0, 300, 10_000).await;
+    let common = class_token(&mut test, "COMM", ShareClassKind::Common, 1, 500, 0).await;
+    let unpriced = set_preference(&mut test, &common, 10_000, 0).await;
+    assert_program_error(unpriced, ErrorCode::InvalidLiquidationPreference);
+    let (payout_mint, funding_account) = fund_proceeds(&mut test, 2_000).await;
+
+    let now = test.now().await;
+    let lapsed = execute(&mut test, &[&series_a, &series_b, &common], &payout_mint, &funding_account, 2_000, now).await;
+    assert_program_error(lapsed, ErrorCode::InvalidDistribution);
+
+    execute(&mut test, &[&series_a, &series_b, &common], &payout_mint, &funding_account, 2_000, now + CLAIM_WINDOW).await
+        .expect("execute_waterfall");
+    assert_eq!(payout(&mut test, &series_a).await, Some(500));
+    assert_eq!(payout(&mut test, &series_b).await, Some(1_500));
+    assert_eq!(payout(&mut test, &common).await, None);
+    assert_eq!(test.token_config(&common).await.next_distribution_id, 0);
+}
@@ -0,0 +1,379 @@
+//! Browser bindings for the gated-token client, built with
+//! `wasm-pack build crates/chainequity-wasm --target web`. Addresses are base58 strings
+//! and `u64` amounts are `BigInt`s. PDAs come from the same [`pda`] functions the Rust
+//! client uses, so dApps never restate seeds in TypeScript.
+//!
+//! [`TransactionBuilder`] collects instructions and serializes them as an unsigned
+//! legacy transaction (zeroed signatures) for a wallet adapter to sign and send, e.g.
+//! `Transaction.from(bytes)` in `@solana/web3.js`.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use chainequity_client::gated_token::accounts;
+use chainequity_client::{instructions, pda};
+use serde::Deserialize;
+use solana_hash::Hash;
+use solana_message::Message;
+use wasm_bindgen::prelude::*;
+
+fn key(address: &str) -> Result<Pubkey, JsError> {
+    Pubkey::from_str(address).map_err(|_| JsError::new(&format!("invalid address: {address}")))
+}
+
+fn optional_key(address: Option<&String>) -> Result<Option<Pubkey>, JsError> {
+    address.map(|address| key(address)).transpose()
+}
+
+fn country_code(code: &str) -> Result<[u8; 2], JsError> {
+    code.as_bytes()
+        .try_into()
+        .map_err(|_| JsError::new(&format!("country code must be two ASCII letters: {code}")))
+}
+
+#[wasm_bindgen(js_name = programId)]
+pub fn program_id() -> String {
+    chainequity_client::PROGRAM_ID.to_string()
+}
+
+#[wasm_bindgen(js_name = programConfigAddress)]
+pub fn program_config_address() -> String {
+    pda::program_config().to_string()
+}
+
+#[wasm_bindgen(js_name = tokenRegistryAddress)]
+pub fn token_registry_address() -> String {
+    pda::token_registry().to_string()
+}
+
+#[wasm_bindgen(js_name = registryPageAddress)]
+pub fn registry_page_address(page_index: u32) -> String {
+    pda::registry_page(page_index).to_string()
+}
+
+#[wasm_bindgen(js_name = tokenConfigAddress)]
+pub fn token_config_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::token_config(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = mintAuthorityAddress)]
+pub fn mint_authority_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::mint_authority(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = freezeAuthorityAddress)]
+pub fn freeze_authority_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::freeze_authority(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = allowlistEntryAddress)]
+pub fn allowlist_entry_address(mint: &str, wallet: &str) -> Result<String, JsError> {
+    Ok(pda::allowlist_entry(&key(mint)?, &key(wallet)?).to_string())
+}
+
+#[wasm_bindgen(js_name = countryRuleAddress)]
+pub fn country_rule_address(mint: &str, code: &str) -> Result<String, JsError> {
+    Ok(pda::country_rule(&key(mint)?, country_code(code)?).to_string())
+}
+
+#[wasm_bindgen(js_name = shareClassAddress)]
+pub fn share_class_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::share_class(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = feeTreasuryAddress)]
+pub fn fee_treasury_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::fee_treasury(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = velocityCounterAddress)]
+pub fn velocity_counter_address(mint: &str, wallet: &str) -> Result<String, JsError> {
+    Ok(pda::velocity_counter(&key(mint)?, &key(wallet)?).to_string())
+}
+
+#[wasm_bindgen(js_name = snapshotAddress)]
+pub fn snapshot_address(mint: &str, snapshot_id: u64) -> Result<String, JsError> {
+    Ok(pda::snapshot(&key(mint)?, snapshot_id).to_string())
+}
+
+#[wasm_bindgen(js_name = snapshotBalanceAddress)]
+pub fn snapshot_balance_address(mint: &str, snapshot_id: u64, holder: &str) -> Result<String, JsError> {
+    Ok(pda::snapshot_balance(&key(mint)?, snapshot_id, &key(holder)?).to_string())
+}
+
+#[wasm_bindgen(js_name = capTableAddress)]
+pub fn cap_table_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::cap_table(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = holderPageAddress)]
+pub fn holder_page_address(mint: &str, page_index: u32) -> Result<String, JsError> {
+    Ok(pda::holder_page(&key(mint)?, page_index).to_string())
+}
+
+#[wasm_bindgen(js_name = holderIndexAddress)]
+pub fn holder_index_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::holder_index(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = holderNumberAddress)]
+pub fn holder_number_address(mint: &str, number: u64) -> Result<String, JsError> {
+    Ok(pda::holder_number(&key(mint)?, number).to_string())
+}
+
+#[wasm_bindgen(js_name = transferJournalAddress)]
+pub fn transfer_journal_address(mint: &str) -> Result<String, JsError> {
+    Ok(pda::transfer_journal(&key(mint)?).to_string())
+}
+
+#[wasm_bindgen(js_name = saleProposalAddress)]
+pub fn sale_proposal_address(mint: &str, seller: &str) -> Result<String, JsError> {
+    Ok(pda::sale_proposal(&key(mint)?, &key(seller)?).to_string())
+}
+
+/// Associated token account of `wallet` for `mint`; `token_program` defaults to SPL Token
+#[wasm_bindgen(js_name = associatedTokenAddress)]
+pub fn associated_token_address(wallet: &str, mint: &str, token_program: Option<String>) -> Result<String, JsError> {
+    let token_program = optional_key(token_program.as_ref())?.unwrap_or(anchor_spl::token::ID);
+    Ok(get_associated_token_address_with_program_id(&key(wallet)?, &key(mint)?, &token_program).to_string())
+}
+
+/// Optional `mint_tokens` accounts, as a JS object with camelCase keys. Which ones a
+/// token needs depends on its configuration; see the Rust client's `resolve` module.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct MintOptions {
+    token_program: Option<String>,
+    recipient_country_rule: Option<String>,
+    recipient_sanctions_flag: Option<String>,
+    share_class: Option<String>,
+    recipient_snapshot_balance: Option<String>,
+    recipient_holder_page: Option<String>,
+    holder_index: Option<String>,
+    recipient_holder_number: Option<String>,
+}
+
+/// Optional `gated_transfer` accounts, as a JS object with camelCase keys
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TransferOptions {
+    token_program: Option<String>,
+    destination_registry: Option<String>,
+    fee_treasury: Option<String>,
+    velocity_counter: Option<String>,
+    sender_country_rule: Option<String>,
+    recipient_country_rule: Option<String>,
+    sender_sanctions_flag: Option<String>,
+    recipient_sanctions_flag: Option<String>,
+    sender_snapshot_balance: Option<String>,
+    share_class: Option<String>,
+    recipient_snapshot_balance: Option<String>,
+    sender_holder_page: Option<String>,
+    recipient_holder_page: Option<String>,
+    sale_proposal: Option<String>,
+    holder_index: Option<String>,
+    sender_holder_number: Option<String>,
+    last_holder_number: Option<String>,
+    recipient_holder_number: Option<String>,
+    transfer_journal: Option<String>,
+}
+
+fn options<T: Default + for<'de> Deserialize<'de>>(value: Option<JsValue>) -> Result<T, JsError> {
+    match value {
+        Some(value) => serde_wasm_bindgen::from_value(value).map_err(|error| JsError::new(&error.to_string())),
+        None => Ok(T::default()),
+    }
+}
+
+/// Instructions for one unsigned transaction, in the order they are added
+#[wasm_bindgen]
+pub struct TransactionBuilder {
+    fee_payer: Pubkey,
+    instructions: Vec<Instruction>,
+}
+
+#[wasm_bindgen]
+impl TransactionBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new(fee_payer: &str) -> Result<TransactionBuilder, JsError> {
+        Ok(Self {
+            fee_payer: key(fee_payer)?,
+            instructions: Vec::new(),
+        })
+    }
+
+    #[wasm_bindgen(js_name = approveWallet)]
+    pub fn approve_wallet(&mut self, authority: &str, mint: &str, wallet: &str) -> Result<(), JsError> {
+        let (mint, wallet) = (key(mint)?, key(wallet)?);
+        self.instructions.push(instructions::approve_wallet(accounts::ApproveWallet {
+            authority: key(authority)?,
+            wallet,
+            token_config: pda::token_config(&mint),
+            allowlist_entry: pda::allowlist_entry(&mint, &wallet),
+            system_program: system_program::ID,
+        }));
+        Ok(())
+    }
+
+    /// `country_rule` is the wallet's country rule account, when the token has one
+    #[wasm_bindgen(js_name = revokeWallet)]
+    pub fn revoke_wallet(&mut self, authority: &str, mint: &str, wallet: &str, country_rule: Option<String>) -> Result<(), JsError> {
+        let (mint, wallet) = (key(mint)?, key(wallet)?);
+        self.instructions.push(instructions::revoke_wallet(accounts::RevokeWallet {
+            authority: key(authority)?,
+            wallet,
+            token_config: pda::token_config(&mint),
+            allowlist_entry: pda::allowlist_entry(&mint, &wallet),
+            country_rule: optional_key(country_rule.as_ref())?,
+        }));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = mintTokens)]
+    pub fn mint_tokens(
+        &mut self,
+        authority: &str,
+        mint: &str,
+        recipient: &str,
+        recipient_token_account: &str,
+        amount: u64,
+        optional: Option<JsValue>,
+    ) -> Result<(), JsError> {
+        let optional: MintOptions = options(optional)?;
+        let (mint, recipient) = (key(mint)?, key(recipient)?);
+        let accounts = accounts::MintTokens {
+            authority: key(authority)?,
+            recipient,
+            token_config: pda::token_config(&mint),
+            program_config: pda::program_config(),
+            mint,
+            mint_authority: pda::mint_authority(&mint),
+            recipient_token_account: key(recipient_token_account)?,
+            recipient_allowlist_entry: pda::allowlist_entry(&mint, &recipient),
+            recipient_country_rule: optional_key(optional.recipient_country_rule.as_ref())?,
+            recipient_sanctions_flag: optional_key(optional.recipient_sanctions_flag.as_ref())?,
+            share_class: optional_key(optional.share_class.as_ref())?,
+            recipient_snapshot_balance: optional_key(optional.recipient_snapshot_balance.as_ref())?,
+            recipient_holder_page: optional_key(optional.recipient_holder_page.as_ref())?,
+            holder_index: optional_key(optional.holder_index.as_ref())?,
+            recipient_holder_number: optional_key(optional.recipient_holder_number.as_ref())?,
+            instructions: None,
+            trusted_caller: None,
+            token_program: optional_key(optional.token_program.as_ref())?.unwrap_or(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        };
+        self.instructions.push(instructions::mint_tokens(accounts, amount));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = gatedTransfer)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn gated_transfer(
+        &mut self,
+        sender: &str,
+        mint: &str,
+        recipient: &str,
+        from_token_account: &str,
+        to_token_account: &str,
+        amount: u64,
+        optional: Option<JsValue>,
+    ) -> Result<(), JsError> {
+        let optional: TransferOptions = options(optional)?;
+        let (mint, sender, recipient) = (key(mint)?, key(sender)?, key(recipient)?);
+        let accounts = accounts::GatedTransfer {
+            authority: sender,
+            recipient,
+            token_config: pda::token_config(&mint),
+            program_config: pda::program_config(),
+            mint,
+            from_token_account: key(from_token_account)?,
+            to_token_account: key(to_token_account)?,
+            sender_allowlist_entry: pda::allowlist_entry(&mint, &sender),
+            recipient_allowlist_entry: pda::allowlist_entry(&mint, &recipient),
+            destination_registry: optional_key(optional.destination_registry.as_ref())?,
+            fee_treasury: optional_key(optional.fee_treasury.as_ref())?,
+            velocity_counter: optional_key(optional.velocity_counter.as_ref())?,
+            sender_country_rule: optional_key(optional.sender_country_rule.as_ref())?,
+            recipient_country_rule: optional_key(optional.recipient_country_rule.as_ref())?,
+            sender_sanctions_flag: optional_key(optional.sender_sanctions_flag.as_ref())?,
+            recipient_sanctions_flag: optional_key(optional.recipient_sanctions_flag.as_ref())?,
+            sender_snapshot_balance: optional_key(optional.sender_snapshot_balance.as_ref())?,
+            share_class: optional_key(optional.share_class.as_ref())?,
+            recipient_snapshot_balance: optional_key(optional.recipient_snapshot_balance.as_ref())?,
+            sender_holder_page: optional_key(optional.sender_holder_page.as_ref())?,
+            recipient_holder_page: optional_key(optional.recipient_holder_page.as_ref())?,
+            sale_proposal: optional_key(optional.sale_proposal.as_ref())?,
+            holder_index: optional_key(optional.holder_index.as_ref())?,
+            sender_holder_number: optional_key(optional.sender_holder_number.as_ref())?,
+            last_holder_number: optional_key(optional.last_holder_number.as_ref())?,
+            recipient_holder_number: optional_key(optional.recipient_holder_number.as_ref())?,
+            transfer_journal: optional_key(optional.transfer_journal.as_ref())?,
+            token_program: optional_key(optional.token_program.as_ref())?.unwrap_or(anchor_spl::token::ID),
+            system_program: system_program::ID,
+        };
+        self.instructions.push(instructions::gated_transfer(accounts, amount, None));
+        Ok(())
+    }
+
+    pub fn pause(&mut self, authority: &str, mint: &str) -> Result<(), JsError> {
+        self.instructions.push(instructions::pause(accounts::PauseToken {
+            authority: key(authority)?,
+            token_config: pda::token_config(&key(mint)?),
+        }));
+        Ok(())
+    }
+
+    pub fn unpause(&mut self, authority: &str, mint: &str) -> Result<(), JsError> {
+        self.instructions.push(instructions::unpause(accounts::SetPaused {
+            authority: key(authority)?,
+            token_config: pda::token_config(&key(mint)?),
+        }));
+        Ok(())
+    }
+
+    /// `snapshot_id` is the token's `current_snapshot_id + 1`
+    #[wasm_bindgen(js_name = createSnapshot)]
+    pub fn create_snapshot(&mut self, authority: &str, mint: &str, snapshot_id: u64) -> Result<(), JsError> {
+        let mint = key(mint)?;
+        self.instructions.push(instructions::create_snapshot(accounts::CreateSnapshot {
+            authority: key(authority)?,
+            token_config: pda::token_config(&mint),
+            mint,
+            snapshot: pda::snapshot(&mint, snapshot_id),
+            system_program: system_program::ID,
+        }));
+        Ok(())
+    }
+
+    /// Serialize the instructions added so far as an unsigned transaction
+    pub fn build(&self, recent_blockhash: &str) -> Result<Vec<u8>, JsError> {
+        let blockhash = Hash::from_str(recent_blockhash)
+            .map_err(|_| JsError::new(&format!("invalid blockhash: {recent_blockhash}")))?;
+        Ok(unsigned_transaction(&self.instructions, &self.fee_payer, blockhash))
+    }
+}
+
+/// Wire format of a legacy transaction whose signatures are all zero: a compact-u16
+/// signature count, the signatures, then the message
+pub fn unsigned_transaction(instructions: &[Instruction], fee_payer: &Pubkey, blockhash: Hash) -> Vec<u8> {
+    let message = Message::new_with_blockhash(instructions, Some(fee_payer), &blockhash);
+    let signatures = message.header.num_required_signatures as usize;
+    let mut bytes = Vec::new();
+    // Compact-u16: 7 bits per byte, high bit set on all but the last
+    let mut count = signatures;
+    loop {
+        let byte = (count & 0x7f) as u8;
+        count >>= 7;
+        if count == 0 {
+            bytes.push(byte);
+            break;
+        }
+        bytes.push(byte | 0x80);
+    }
+    bytes.resize(bytes.len() + 64 * signatures, 0);
+    bytes.extend_from_slice(&message.serialize());
+    bytes
+}
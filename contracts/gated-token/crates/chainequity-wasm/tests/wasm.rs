@@ -0,0 +1,30 @@
+use anchor_lang::prelude::Pubkey;
+use chainequity_client::pda;
+use chainequity_wasm::{allowlist_entry_address, TransactionBuilder};
+use solana_hash::Hash;
+use solana_message::Message;
+
+#[test]
+fn derives_the_client_addresses() {
+    let (mint, wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let address = allowlist_entry_address(&mint.to_string(), &wallet.to_string()).unwrap();
+    assert_eq!(address, pda::allowlist_entry(&mint, &wallet).to_string());
+}
+
+#[test]
+fn builds_unsigned_transactions() {
+    let (authority, mint, wallet) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let blockhash = Hash::new_unique();
+    let mut builder = TransactionBuilder::new(&authority.to_string()).unwrap();
+    builder.approve_wallet(&authority.to_string(), &mint.to_string(), &wallet.to_string()).unwrap();
+    builder.pause(&authority.to_string(), &mint.to_string()).unwrap();
+    let bytes = builder.build(&blockhash.to_string()).unwrap();
+
+    // One signer, the authority paying the fee, with a zeroed signature slot
+    assert_eq!(bytes[0], 1);
+    assert!(bytes[1..65].iter().all(|byte| *byte == 0));
+    let message: Message = bincode::deserialize(&bytes[65..]).unwrap();
+    assert_eq!(message.account_keys[0], authority);
+    assert_eq!(message.recent_blockhash, blockhash);
+    assert_eq!(message.instructions.len(), 2);
+}
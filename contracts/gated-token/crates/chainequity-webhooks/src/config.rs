@@ -0,0 +1,44 @@
+//! Endpoint configuration, read from a JSON file such as
+//!
+//! ```json
+//! {
+//!     "max_attempts": 8,
+//!     "endpoints": [
+//!         { "url": "https://backoffice.example/hooks/approvals", "secret": "...",
+//!           "events": ["WalletApprovedEvent", "WalletRevokedEvent"] },
+//!         { "url": "https://ledger.example/hooks", "secret": "..." }
+//!     ]
+//! }
+//! ```
+//!
+//! An endpoint without `events` receives every event.
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Config {
+    pub endpoints: Vec<Endpoint>,
+    /// Attempts per delivery before it is given up on
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Endpoint {
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature sent with each delivery
+    pub secret: String,
+    /// Event type names, e.g. `TokensTransferredEvent`; empty for all
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+impl Endpoint {
+    pub fn wants(&self, event: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|name| name == event)
+    }
+}
+
+fn default_max_attempts() -> u32 {
+    8
+}
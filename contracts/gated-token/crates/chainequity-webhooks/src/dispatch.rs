@@ -0,0 +1,89 @@
+//! Fan deliveries out to endpoints. Each endpoint has its own queue and worker, so a
+//! slow or failing endpoint delays only its own deliveries, which stay in order.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::config::{Config, Endpoint};
+use crate::payload::{signature, Delivery};
+
+/// Deliveries buffered per endpoint before the event source waits
+const QUEUE: usize = 1_024;
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+const MAX_RETRY: Duration = Duration::from_secs(300);
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct Dispatcher {
+    routes: Vec<(Endpoint, mpsc::Sender<Arc<Delivery>>)>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl Dispatcher {
+    pub fn start(config: &Config) -> Result<Self> {
+        let http = reqwest::Client::builder().timeout(TIMEOUT).build()?;
+        let mut routes = Vec::new();
+        let mut workers = Vec::new();
+        for endpoint in &config.endpoints {
+            let (queue, deliveries) = mpsc::channel(QUEUE);
+            workers.push(tokio::spawn(deliver_all(http.clone(), endpoint.clone(), config.max_attempts, deliveries)));
+            routes.push((endpoint.clone(), queue));
+        }
+        Ok(Self { routes, workers })
+    }
+
+    /// Queue `delivery` for every endpoint subscribed to its event type
+    pub async fn dispatch(&self, delivery: Delivery) {
+        let delivery = Arc::new(delivery);
+        for (endpoint, queue) in &self.routes {
+            if endpoint.wants(delivery.event) {
+                // A closed queue means the worker panicked; nothing more can be sent
+                let _ = queue.send(Arc::clone(&delivery)).await;
+            }
+        }
+    }
+
+    /// Stop accepting deliveries and wait for the queued ones to be attempted
+    pub async fn drain(self) {
+        drop(self.routes);
+        for worker in self.workers {
+            let _ = worker.await;
+        }
+    }
+}
+
+async fn deliver_all(http: reqwest::Client, endpoint: Endpoint, max_attempts: u32, mut deliveries: mpsc::Receiver<Arc<Delivery>>) {
+    while let Some(delivery) = deliveries.recv().await {
+        let mut backoff = FIRST_RETRY;
+        for attempt in 1..=max_attempts {
+            match post(&http, &endpoint, &delivery).await {
+                Ok(()) => break,
+                Err(error) if attempt == max_attempts => {
+                    eprintln!("giving up on {} to {} after {attempt} attempts: {error}", delivery.id, endpoint.url);
+                }
+                Err(error) => {
+                    eprintln!("delivery {} to {} failed ({error}), retrying in {backoff:?}", delivery.id, endpoint.url);
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RETRY);
+                }
+            }
+        }
+    }
+}
+
+async fn post(http: &reqwest::Client, endpoint: &Endpoint, delivery: &Delivery) -> Result<()> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+    http.post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .header("X-ChainEquity-Event", delivery.event)
+        .header("X-ChainEquity-Delivery", &delivery.id)
+        .header("X-ChainEquity-Signature", signature(&endpoint.secret, timestamp, &delivery.body))
+        .body(delivery.body.clone())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,97 @@
+//! `chainequity-webhooks`: tail the gated-token program's events and POST each one as
+//! signed JSON to the webhook endpoints subscribed to its type (see `config.rs` for the
+//! endpoint file and `payload.rs` for the body and signature). Failed deliveries are
+//! retried with exponential backoff.
+//!
+//! `--replay-from-slot` redelivers history from a slot before following new
+//! transactions; with `--replay-only` the relay exits once the replay is delivered.
+
+mod config;
+mod dispatch;
+mod payload;
+mod source;
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::config::Config;
+use crate::dispatch::Dispatcher;
+
+#[derive(Parser)]
+#[command(version, about = "Relay ChainEquity gated-token events to webhooks")]
+struct Args {
+    /// JSON RPC endpoint used for replay
+    #[arg(short, long, env = "CHAINEQUITY_RPC_URL", default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// PubSub endpoint; defaults to `--url` with a websocket scheme and the port after it
+    #[arg(long, env = "CHAINEQUITY_WS_URL")]
+    ws_url: Option<String>,
+    /// JSON file listing the webhook endpoints
+    #[arg(short, long, env = "CHAINEQUITY_WEBHOOKS")]
+    config: PathBuf,
+    /// Redeliver events from this slot before following new transactions
+    #[arg(long)]
+    replay_from_slot: Option<u64>,
+    /// Exit once the replay is delivered instead of following
+    #[arg(long, requires = "replay_from_slot")]
+    replay_only: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config: Config = serde_json::from_str(
+        &std::fs::read_to_string(&args.config).with_context(|| format!("reading {}", args.config.display()))?,
+    )?;
+    if config.endpoints.is_empty() {
+        bail!("{} lists no endpoints", args.config.display());
+    }
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    let dispatcher = Dispatcher::start(&config)?;
+
+    if let (true, Some(from_slot)) = (args.replay_only, args.replay_from_slot) {
+        let relayed = source::replay(&rpc, &dispatcher, from_slot).await?;
+        dispatcher.drain().await;
+        println!("replayed {relayed} events from slot {from_slot}");
+        return Ok(());
+    }
+    let ws_url = args.ws_url.unwrap_or_else(|| websocket_url(&args.url));
+    let pubsub = PubsubClient::new(&ws_url).await?;
+    source::follow(&pubsub, &rpc, &dispatcher, args.replay_from_slot).await
+}
+
+/// Solana's convention: PubSub listens on the RPC port plus one
+fn websocket_url(rpc_url: &str) -> String {
+    let url = rpc_url.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1);
+    let Some((base, port)) = url.rsplit_once(':') else {
+        return url;
+    };
+    match port.trim_end_matches('/').parse::<u16>() {
+        Ok(port) => format!("{base}:{}", port + 1),
+        Err(_) => url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_filter_by_event_type() {
+        let config: Config = serde_json::from_str(r#"{
+            "endpoints": [
+                { "url": "https://a.example", "secret": "s", "events": ["WalletApprovedEvent"] },
+                { "url": "https://b.example", "secret": "s" }
+            ]
+        }"#).unwrap();
+        assert_eq!(config.max_attempts, 8);
+        assert!(config.endpoints[0].wants("WalletApprovedEvent"));
+        assert!(!config.endpoints[0].wants("TokensTransferredEvent"));
+        assert!(config.endpoints[1].wants("TokensTransferredEvent"));
+    }
+}
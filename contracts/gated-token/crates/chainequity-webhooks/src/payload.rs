@@ -0,0 +1,135 @@
+//! What a delivery carries. Every event is sent with its raw Borsh data; approvals,
+//! revocations, mints, transfers and burns also get their fields spelled out.
+//!
+//! Each request is signed like `X-ChainEquity-Signature: t=<unix time>,v1=<hex>`, where
+//! the hex is HMAC-SHA256 of `<unix time>.<body>` under the endpoint's secret. Receivers
+//! should recompute it and reject stale timestamps. The `id` is stable across retries
+//! and replays, so receivers can deduplicate on it.
+
+use base64::Engine;
+use chainequity_client::events;
+use chainequity_client::gated_token::{
+    TokensBurnedEvent, TokensMintedEvent, TokensTransferredEvent, WalletApprovedEvent,
+    WalletRevokedEvent,
+};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+pub struct Delivery {
+    pub id: String,
+    pub event: &'static str,
+    pub body: Vec<u8>,
+}
+
+impl Delivery {
+    /// Delivery of the `log_index`th event of a transaction, or `None` if `payload` is
+    /// not a gated-token event
+    pub fn new(signature: &str, slot: u64, log_index: usize, payload: &[u8]) -> Option<Self> {
+        let event = events::name(payload)?;
+        let id = format!("{signature}:{log_index}");
+        let body = json!({
+            "id": id,
+            "event": event,
+            "signature": signature,
+            "slot": slot,
+            "log_index": log_index,
+            "sequence": events::sequence(payload),
+            "data": base64::engine::general_purpose::STANDARD.encode(payload),
+            "fields": fields(payload),
+        });
+        Some(Self {
+            id,
+            event,
+            body: serde_json::to_vec(&body).expect("payloads always serialize"),
+        })
+    }
+}
+
+/// Fields of the events back offices most often act on
+fn fields(payload: &[u8]) -> Option<Value> {
+    if let Ok(Some(event)) = events::decode::<WalletApprovedEvent>(payload) {
+        return Some(json!({
+            "mint": event.token_mint.to_string(),
+            "wallet": event.wallet.to_string(),
+            "approved_by": event.approved_by.to_string(),
+            "timestamp": event.timestamp,
+        }));
+    }
+    if let Ok(Some(event)) = events::decode::<WalletRevokedEvent>(payload) {
+        return Some(json!({
+            "mint": event.token_mint.to_string(),
+            "wallet": event.wallet.to_string(),
+            "revoked_by": event.revoked_by.to_string(),
+            "timestamp": event.timestamp,
+        }));
+    }
+    if let Ok(Some(event)) = events::decode::<TokensMintedEvent>(payload) {
+        return Some(json!({
+            "mint": event.token_mint.to_string(),
+            "recipient": event.recipient.to_string(),
+            "amount": event.amount.to_string(),
+            "new_supply": event.new_supply.to_string(),
+        }));
+    }
+    if let Ok(Some(event)) = events::decode::<TokensTransferredEvent>(payload) {
+        return Some(json!({
+            "mint": event.token_mint.to_string(),
+            "from": event.from.to_string(),
+            "to": event.to.to_string(),
+            "amount": event.amount.to_string(),
+        }));
+    }
+    if let Ok(Some(event)) = events::decode::<TokensBurnedEvent>(payload) {
+        return Some(json!({
+            "mint": event.token_mint.to_string(),
+            "holder": event.holder.to_string(),
+            "amount": event.amount.to_string(),
+            "new_supply": event.new_supply.to_string(),
+        }));
+    }
+    None
+}
+
+/// `X-ChainEquity-Signature` header value for `body` sent at `timestamp`
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    let digest: String = mac.finalize().into_bytes().iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("t={timestamp},v1={digest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{Discriminator, Event};
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn deliveries_spell_out_fields_and_sign_the_body() {
+        let event = WalletRevokedEvent {
+            schema_version: 1,
+            sequence: 42,
+            token_mint: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            revoked_by: Pubkey::new_unique(),
+            timestamp: 1_700_000_000,
+        };
+        let payload = event.data();
+        assert!(payload.starts_with(WalletRevokedEvent::DISCRIMINATOR));
+
+        let delivery = Delivery::new("5ig", 9, 2, &payload).unwrap();
+        assert_eq!((delivery.id.as_str(), delivery.event), ("5ig:2", "WalletRevokedEvent"));
+        let body: Value = serde_json::from_slice(&delivery.body).unwrap();
+        assert_eq!(body["sequence"], 42);
+        assert_eq!(body["fields"]["wallet"], event.wallet.to_string());
+        assert!(Delivery::new("5ig", 9, 0, b"not an event").is_none());
+
+        assert_eq!(
+            signature("key", 1_700_000_000, b"{}"),
+            "t=1700000000,v1=9d713ed406bb7076d4123f0dc2c39d2df5c654ed4b0cd56b52c8b4c940bd63ae",
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Program transactions to relay: a replay of history from a slot and a live
+//! `logsSubscribe` stream. Failed transactions changed nothing and are skipped.
+
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chainequity_client::{events, PROGRAM_ID};
+use futures_util::StreamExt;
+use solana_pubsub_client::nonblocking::pubsub_client::PubsubClient;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_rpc_client_api::config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter};
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::UiTransactionEncoding;
+
+use crate::dispatch::Dispatcher;
+use crate::payload::Delivery;
+
+/// Signatures fetched per `getSignaturesForAddress` page, the RPC maximum
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Queue every event of a transaction's logs
+async fn relay(dispatcher: &Dispatcher, signature: &str, slot: u64, logs: &[String]) -> usize {
+    let mut relayed = 0;
+    for (log_index, payload) in events::payloads(logs).iter().enumerate() {
+        if let Some(delivery) = Delivery::new(signature, slot, log_index, payload) {
+            dispatcher.dispatch(delivery).await;
+            relayed += 1;
+        }
+    }
+    relayed
+}
+
+/// Relay every successful program transaction from `from_slot` up to now, oldest first
+pub async fn replay(rpc: &RpcClient, dispatcher: &Dispatcher, from_slot: u64) -> Result<usize> {
+    let mut statuses = Vec::new();
+    let mut before = None;
+    loop {
+        let config = GetConfirmedSignaturesForAddress2Config {
+            before,
+            until: None,
+            limit: Some(SIGNATURE_PAGE),
+            commitment: Some(rpc.commitment()),
+        };
+        let page = rpc.get_signatures_for_address_with_config(&PROGRAM_ID, config).await?;
+        let exhausted = page.len() < SIGNATURE_PAGE;
+        let Some(last) = page.last() else { break };
+        before = Some(Signature::from_str(&last.signature)?);
+        let reached_start = last.slot < from_slot;
+        statuses.extend(page.into_iter().filter(|status| status.slot >= from_slot));
+        if exhausted || reached_start {
+            break;
+        }
+    }
+
+    let mut relayed = 0;
+    for status in statuses.iter().rev().filter(|status| status.err.is_none()) {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Json),
+            commitment: Some(rpc.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let signature = Signature::from_str(&status.signature)?;
+        let transaction = rpc.get_transaction_with_config(&signature, config).await
+            .with_context(|| format!("fetching transaction {signature}"))?;
+        if let Some(OptionSerializer::Some(logs)) = transaction.transaction.meta.map(|meta| meta.log_messages) {
+            relayed += relay(dispatcher, &status.signature, status.slot, &logs).await;
+        }
+    }
+    Ok(relayed)
+}
+
+/// Relay program transactions as they are confirmed, until the subscription ends. With
+/// `replay_from` history from that slot is relayed first.
+pub async fn follow(pubsub: &PubsubClient, rpc: &RpcClient, dispatcher: &Dispatcher, replay_from: Option<u64>) -> Result<()> {
+    let (mut notifications, unsubscribe) = pubsub
+        .logs_subscribe(
+            RpcTransactionLogsFilter::Mentions(vec![PROGRAM_ID.to_string()]),
+            RpcTransactionLogsConfig {
+                commitment: Some(rpc.commitment()),
+            },
+        )
+        .await?;
+
+    // Subscribe before replaying so nothing confirmed in between is missed; events in
+    // the overlap are delivered twice with the same id
+    if let Some(from_slot) = replay_from {
+        let relayed = replay(rpc, dispatcher, from_slot).await?;
+        println!("replayed {relayed} events from slot {from_slot}");
+    }
+
+    while let Some(notification) = notifications.next().await {
+        if notification.value.err.is_some() {
+            continue;
+        }
+        relay(dispatcher, &notification.value.signature, notification.context.slot, &notification.value.logs).await;
+    }
+    unsubscribe().await;
+    Ok(())
+}
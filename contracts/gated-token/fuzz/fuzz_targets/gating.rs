@@ -0,0 +1,147 @@
+//! Drives a token through arbitrary approve/revoke/mint/transfer/burn sequences, each
+//! instruction optionally corrupted (data bytes flipped or cut, account metas swapped
+//! or replaced with another wallet's accounts), and checks after every transaction that
+//!
+//! - the mint supply, `TokenConfig::total_supply` and the sum of wallet balances agree
+//! - no wallet's balance grew while its allowlist entry is missing or unapproved
+//!
+//! Transactions are free to fail; only a successful one can break an invariant.
+
+#![no_main]
+
+use std::cell::RefCell;
+
+use anchor_spl::token::spl_token;
+use arbitrary::Arbitrary;
+use chainequity_test_utils::{ChainEquityTest, TestToken};
+use libfuzzer_sys::fuzz_target;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::Keypair;
+
+const APPROVED_WALLETS: usize = 3;
+const STRANGERS: usize = 2;
+const MAX_STEPS: usize = 24;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Approve { wallet: u8 },
+    Revoke { wallet: u8 },
+    Mint { to: u8, amount: u32 },
+    Transfer { from: u8, to: u8, amount: u32 },
+    Burn { holder: u8, amount: u32 },
+}
+
+#[derive(Arbitrary, Debug)]
+enum Mutation {
+    None,
+    FlipByte { offset: u8, mask: u8 },
+    Truncate { len: u8 },
+    SwapAccounts { a: u8, b: u8 },
+    /// Replace a non-signer account with wallet `wallet`'s token account or, if `entry`,
+    /// its allowlist entry
+    Substitute { position: u8, wallet: u8, entry: bool },
+}
+
+#[derive(Arbitrary, Debug)]
+struct Step {
+    op: Op,
+    mutation: Mutation,
+}
+
+thread_local! {
+    static HARNESS: RefCell<Option<(tokio::runtime::Runtime, ChainEquityTest)>> = const { RefCell::new(None) };
+}
+
+fuzz_target!(|steps: Vec<Step>| {
+    HARNESS.with(|harness| {
+        let mut harness = harness.borrow_mut();
+        let (runtime, test) = harness.get_or_insert_with(|| {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            let test = runtime.block_on(ChainEquityTest::start(ChainEquityTest::program_test()));
+            (runtime, test)
+        });
+        runtime.block_on(run(test, &steps));
+    });
+});
+
+async fn run(test: &mut ChainEquityTest, steps: &[Step]) {
+    let mut token = test.create_token("FUZZ", APPROVED_WALLETS).await;
+    for _ in 0..STRANGERS {
+        test.add_wallet(&mut token, false).await;
+    }
+    let mut balances = vec![0; token.wallets.len()];
+
+    for step in steps.iter().take(MAX_STEPS) {
+        let (mut instruction, signer) = build(&token, &step.op);
+        mutate(&token, &mut instruction, &step.mutation);
+        let signers: Vec<&Keypair> = signer.iter().collect();
+        if test.process(&[instruction], &signers).await.is_err() {
+            continue;
+        }
+        check(test, &token, &mut balances, step).await;
+    }
+}
+
+fn build(token: &TestToken, op: &Op) -> (Instruction, Option<Keypair>) {
+    let pick = |index: u8| index as usize % token.wallets.len();
+    let signer = |index: usize| Some(token.wallets[index].insecure_clone());
+    match *op {
+        Op::Approve { wallet } => (token.approve_instruction(&token.wallet(pick(wallet))), None),
+        Op::Revoke { wallet } => (token.revoke_instruction(&token.wallet(pick(wallet))), None),
+        Op::Mint { to, amount } => (token.mint_instruction(pick(to), amount.into()), None),
+        Op::Transfer { from, to, amount } => {
+            let from = pick(from);
+            (token.transfer_instruction(from, pick(to), amount.into()), signer(from))
+        }
+        Op::Burn { holder, amount } => {
+            let holder = pick(holder);
+            (token.burn_instruction(holder, amount.into()), signer(holder))
+        }
+    }
+}
+
+fn mutate(token: &TestToken, instruction: &mut Instruction, mutation: &Mutation) {
+    let accounts = instruction.accounts.len();
+    match *mutation {
+        Mutation::None => {}
+        Mutation::FlipByte { offset, mask } => {
+            let len = instruction.data.len();
+            instruction.data[offset as usize % len] ^= mask;
+        }
+        Mutation::Truncate { len } => instruction.data.truncate(len as usize),
+        Mutation::SwapAccounts { a, b } => instruction.accounts.swap(a as usize % accounts, b as usize % accounts),
+        Mutation::Substitute { position, wallet, entry } => {
+            let meta = &mut instruction.accounts[position as usize % accounts];
+            // Signer slots keep their key so the transaction can still be signed
+            if meta.is_signer {
+                return;
+            }
+            let wallet = wallet as usize % token.wallets.len();
+            meta.pubkey = if entry {
+                chainequity_client::pda::allowlist_entry(&token.mint, &token.wallet(wallet))
+            } else {
+                token.token_account(wallet)
+            };
+        }
+    }
+}
+
+async fn check(test: &mut ChainEquityTest, token: &TestToken, balances: &mut [u64], step: &Step) {
+    let mint = test.context.banks_client.get_account(token.mint).await.unwrap().expect("mint");
+    let supply = spl_token::state::Mint::unpack(&mint.data).expect("mint data").supply;
+    let mut held = 0;
+    for (index, previous) in balances.iter_mut().enumerate() {
+        let balance = test.balance(token, index).await;
+        if balance > *previous {
+            let approved = test.allowlist_entry(token, &token.wallet(index)).await
+                .is_some_and(|entry| entry.is_approved);
+            assert!(approved, "unapproved wallet {index} received {} after {step:?}", balance - *previous);
+        }
+        *previous = balance;
+        held += balance;
+    }
+    let recorded = test.token_config(token).await.total_supply;
+    assert_eq!(supply, held, "mint supply vs wallet balances after {step:?}");
+    assert_eq!(recorded, held, "total_supply vs wallet balances after {step:?}");
+}
@@ -0,0 +1,243 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_hook::TransferHookAccount;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Account as SplTokenAccount;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use gated_token::{AllowlistEntry, FeatureFlags, TokenConfig};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::account::ExtraAccountMeta;
+use spl_tlv_account_resolution::seeds::Seed;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::{ExecuteInstruction, InitializeExtraAccountMetaListInstruction};
+
+declare_id!("97WbTLYAUdT1TrgJRn2hbtbBUtUvkXz8cVTA6NUu7wvG");
+
+/// Token-2022 transfer hook that enforces the gated-token allowlist on every transfer of
+/// a mint registered with it, so raw SPL transfers cannot bypass `gated_transfer`.
+/// Both token account owners need an approved `AllowlistEntry`, including program-owned
+/// vaults and escrows, and transfers stop while the token is paused or has transfers
+/// disabled. Callers of `transfer_checked` on such a mint, gated-token included, must
+/// forward the extra accounts listed in the mint's validation account.
+#[program]
+pub mod allowlist_transfer_hook {
+    use super::*;
+
+    /// Write the extra accounts Token-2022 must resolve for each transfer: the gated-token
+    /// program, the mint's `TokenConfig`, and both owners' allowlist entries. The list is
+    /// fixed by the mint, so anyone may pay to create it.
+    #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn initialize_extra_account_meta_list(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+        let extra_account_metas = extra_account_metas()?;
+        let space = ExtraAccountMetaList::size_of(extra_account_metas.len())?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[
+            b"extra-account-metas",
+            mint_key.as_ref(),
+            &[ctx.bumps.extra_account_meta_list],
+        ];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = system_program::CreateAccount {
+            from: ctx.accounts.payer.to_account_info(),
+            to: ctx.accounts.extra_account_meta_list.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        system_program::create_account(
+            cpi_ctx,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &crate::ID,
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
+        )?;
+
+        Ok(())
+    }
+
+    /// Invoked by Token-2022 during `transfer_checked`: reject the transfer unless the
+    /// token is live with transfers enabled, the sender's entry is approved, unlocked and
+    /// past any lockup, and the recipient's entry is approved. A gated-token
+    /// `force_transfer`, signed by the mint's permanent delegate, skips the transfers flag
+    /// and the sender checks, which that instruction governs itself.
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn transfer_hook(ctx: Context<TransferHook>, _amount: u64) -> Result<()> {
+        assert_is_transferring(&ctx.accounts.source_token.to_account_info())?;
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+
+        if !is_forced_transfer(&ctx.accounts.owner.key(), &ctx.accounts.mint.key()) {
+            require!(
+                ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED),
+                ErrorCode::TransfersDisabled
+            );
+            let clock = Clock::get()?;
+
+            let sender_entry = &ctx.accounts.sender_allowlist_entry;
+            require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
+            require!(!sender_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+            require!(
+                sender_entry.permits_token_account(&ctx.accounts.source_token.key()),
+                ErrorCode::TokenAccountNotApproved
+            );
+            if let Some(lockup_until) = sender_entry.lockup_until {
+                require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+            }
+        }
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.destination_token.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        Ok(())
+    }
+}
+
+// Helpers
+/// Extra accounts resolved after the standard source, mint, destination, owner and
+/// validation accounts (indices 0-4)
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    // Token account owner sits at bytes 32..64 of the account data
+    let owner_of = |account_index: u8| Seed::AccountData {
+        account_index,
+        data_index: 32,
+        length: 32,
+    };
+    let allowlist_seeds = |account_index: u8| {
+        [
+            Seed::Literal { bytes: b"allowlist".to_vec() },
+            Seed::AccountKey { index: 1 },
+            owner_of(account_index),
+        ]
+    };
+
+    Ok(vec![
+        // 5: gated-token program, which owns the PDAs below
+        ExtraAccountMeta::new_with_pubkey(&gated_token::ID, false, false)?,
+        // 6: token config
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            5,
+            &[
+                Seed::Literal { bytes: b"token_config".to_vec() },
+                Seed::AccountKey { index: 1 },
+            ],
+            false,
+            false,
+        )?,
+        // 7: sender allowlist entry, keyed by the source account's owner
+        ExtraAccountMeta::new_external_pda_with_seeds(5, &allowlist_seeds(0), false, false)?,
+        // 8: recipient allowlist entry, keyed by the destination account's owner
+        ExtraAccountMeta::new_external_pda_with_seeds(5, &allowlist_seeds(2), false, false)?,
+    ])
+}
+
+/// Whether the transfer is signed by gated-token's mint-authority PDA, which every
+/// Token-2022 share mint names as its permanent delegate and which only signs for
+/// `force_transfer`
+fn is_forced_transfer(authority: &Pubkey, mint: &Pubkey) -> bool {
+    let (permanent_delegate, _) =
+        Pubkey::find_program_address(&[b"mint_authority", mint.as_ref()], &gated_token::ID);
+    *authority == permanent_delegate
+}
+
+/// The hook must only pass while Token-2022 is mid-transfer, not when invoked directly
+fn assert_is_transferring(source_token: &AccountInfo) -> Result<()> {
+    let data = source_token.try_borrow_data()?;
+    let account = StateWithExtensions::<SplTokenAccount>::unpack(&data)?;
+    let extension = account.get_extension::<TransferHookAccount>()?;
+    require!(bool::from(extension.transferring), ErrorCode::NotTransferring);
+    Ok(())
+}
+
+// Context structures
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Validation account created and written in the handler
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Account order is fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct TransferHook<'info> {
+    #[account(token::mint = mint)]
+    pub source_token: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(token::mint = mint)]
+    pub destination_token: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Source owner or delegate; authority is already checked by Token-2022
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Validation account listing the extra accounts below
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// CHECK: gated-token program
+    #[account(address = gated_token::ID)]
+    pub gated_token_program: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        seeds::program = gated_token::ID
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), source_token.owner.as_ref()],
+        bump = sender_allowlist_entry.bump,
+        seeds::program = gated_token::ID
+    )]
+    pub sender_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), destination_token.owner.as_ref()],
+        bump = recipient_allowlist_entry.bump,
+        seeds::program = gated_token::ID
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+}
+
+// Error codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Transfer hook may only run during a Token-2022 transfer")]
+    NotTransferring,
+    #[msg("Token is paused")]
+    TokenPaused,
+    #[msg("Transfers are disabled for this token")]
+    TransfersDisabled,
+    #[msg("Sender is not approved on the allowlist")]
+    SenderNotApproved,
+    #[msg("Recipient is not approved on the allowlist")]
+    RecipientNotApproved,
+    #[msg("Sender is transfer-locked")]
+    SenderTransferLocked,
+    #[msg("Sender is still within their lockup period")]
+    SenderLockedUp,
+    #[msg("Token account is not the one bound to this approval")]
+    TokenAccountNotApproved,
+}
@@ -1,5 +1,14 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, AssociatedToken};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata, UpdateMetadataAccountsV2};
+use anchor_spl::token_interface::{self as token, Mint, TokenAccount, TokenInterface, TransferChecked};
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::ed25519_program;
+use std::collections::BTreeSet;
 
 declare_id!("7zmjGpWX7frSmnFfyZuhhrfoLgV3yH44RJZbKob1FSJF");
 
@@ -13,10 +22,100 @@ pub mod gated_token {
         symbol: String,
         name: String,
         decimals: u8,
+        max_supply: Option<u64>,
+        uri: String,
     ) -> Result<()> {
         require!(symbol.len() >= 3 && symbol.len() <= 10, ErrorCode::InvalidSymbol);
         require!(name.len() >= 2 && name.len() <= 50, ErrorCode::InvalidName);
         require!(decimals <= 9, ErrorCode::InvalidDecimals);
+        require!(uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::InvalidMetadataUri);
+
+        let transfer_hook = match (&ctx.accounts.transfer_hook_program, &ctx.accounts.extra_account_meta_list) {
+            (Some(program), Some(extra_account_meta_list)) => Some((program, extra_account_meta_list)),
+            (None, None) => None,
+            _ => return err!(ErrorCode::TransferHookAccountsRequired),
+        };
+        create_share_mint(
+            &ctx.accounts.authority,
+            &ctx.accounts.mint,
+            &ctx.accounts.mint_authority.key(),
+            &ctx.accounts.freeze_authority.key(),
+            decimals,
+            transfer_hook.is_some(),
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+        )?;
+        if let Some((program, extra_account_meta_list)) = transfer_hook {
+            let instruction = Instruction {
+                program_id: ALLOWLIST_TRANSFER_HOOK_ID,
+                accounts: vec![
+                    AccountMeta::new(ctx.accounts.authority.key(), true),
+                    AccountMeta::new(extra_account_meta_list.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+                    AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+                ],
+                data: TRANSFER_HOOK_INITIALIZE_EXTRA_ACCOUNT_METAS.to_vec(),
+            };
+            invoke(
+                &instruction,
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    extra_account_meta_list.to_account_info(),
+                    ctx.accounts.mint.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    program.to_account_info(),
+                ],
+            )?;
+        }
+
+        // Publish name, symbol and URI through Metaplex so wallets and explorers can show them
+        match (&ctx.accounts.metadata, &ctx.accounts.token_metadata_program) {
+            (Some(metadata_account), Some(token_metadata_program)) => {
+                let cpi_accounts = CreateMetadataAccountsV3 {
+                    metadata: metadata_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    mint_authority: ctx.accounts.mint_authority.to_account_info(),
+                    payer: ctx.accounts.authority.to_account_info(),
+                    update_authority: ctx.accounts.authority.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                };
+                let cpi_program = token_metadata_program.to_account_info();
+                let mint_key = ctx.accounts.mint.key();
+                let seeds: &[&[u8]] = &[b"mint_authority", mint_key.as_ref(), &[ctx.bumps.mint_authority]];
+                let signer_seeds = &[seeds];
+                metadata::create_metadata_accounts_v3(
+                    CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
+                    DataV2 {
+                        name: name.clone(),
+                        symbol: symbol.clone(),
+                        uri: uri.clone(),
+                        seller_fee_basis_points: 0,
+                        creators: None,
+                        collection: None,
+                        uses: None,
+                    },
+                    true,
+                    true,
+                    None,
+                )?;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::MetadataAccountsRequired),
+        }
+
+        let parameters = ctx.accounts.program_config.parameters;
+        if parameters.token_creation_fee > 0 {
+            let fee_recipient = ctx.accounts.fee_recipient.as_ref().ok_or(ErrorCode::FeeRecipientRequired)?;
+            let cpi_accounts = system_program::Transfer {
+                from: ctx.accounts.authority.to_account_info(),
+                to: fee_recipient.to_account_info(),
+            };
+            system_program::transfer(
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts),
+                parameters.token_creation_fee,
+            )?;
+        }
 
         let token_config = &mut ctx.accounts.token_config;
         token_config.authority = ctx.accounts.authority.key();
@@ -26,8 +125,75 @@ pub mod gated_token {
         token_config.decimals = decimals;
         token_config.total_supply = 0;
         token_config.bump = ctx.bumps.token_config;
+        token_config.paused = false;
+        token_config.breaker_tripped = false;
+        token_config.holder_count = 0;
+        token_config.allowlist_checksum = [0u8; 32];
+        token_config.transfer_fee_bps = 0;
+        token_config.next_blackout_id = 0;
+        token_config.open_blackout_windows = 0;
+        token_config.daily_transfer_limit = 0;
+        token_config.compliance_officer = ctx.accounts.authority.key();
+        token_config.min_transfer_amount = parameters.default_min_transfer_amount;
+        token_config.lot_size = 1;
+        token_config.sanctions_oracle = Pubkey::default();
+        token_config.next_distribution_id = 0;
+        token_config.current_snapshot_id = 0;
+        token_config.split_numerator = 1;
+        token_config.split_denominator = 1;
+        token_config.share_class = Pubkey::default();
+        token_config.next_vesting_id = 0;
+        token_config.next_option_grant_id = 0;
+        token_config.next_tender_offer_id = 0;
+        token_config.next_note_id = 0;
+        token_config.next_warrant_id = 0;
+        token_config.cap_table = Pubkey::default();
+        token_config.rofr_period_seconds = 0;
+        token_config.next_rights_offering_id = 0;
+        token_config.last_allocation_id = 0;
+        token_config.allocation_active = false;
+        token_config.interest_index = 0;
+        token_config.interest_accrued_at = 0;
+        token_config.pending_authority = Pubkey::default();
+        token_config.timelock_seconds = parameters.default_timelock_seconds;
+        token_config.next_pending_operation_id = 0;
+        token_config.next_proposal_id = 0;
+        token_config.guardian = Pubkey::default();
+        token_config.max_supply = max_supply;
+        token_config.uri = uri;
+        token_config.issuer = Pubkey::default();
+        token_config.next_escrow_id = 0;
+        token_config.next_swap_offer_id = 0;
+        token_config.next_stream_id = 0;
+        token_config.require_memo = false;
+        token_config.next_airdrop_id = 0;
+        token_config.version = TokenConfig::CURRENT_VERSION;
+        token_config.holder_index = Pubkey::default();
+        token_config.journal_enabled = false;
+        token_config.event_sequence = 0;
+        token_config.restrict_cpi_callers = false;
+        token_config.features = FeatureFlags::DEFAULT;
+        token_config.price_oracle = Pubkey::default();
+        token_config.price_feed = Pubkey::default();
+        token_config.max_price_age_seconds = 0;
+
+        let clock = Clock::get()?;
+        let registry_entry = RegistryEntry {
+            mint: token_config.mint,
+            authority: token_config.authority,
+            created_at: clock.unix_timestamp,
+        };
+        append_to_token_registry(
+            token_config,
+            &mut ctx.accounts.token_registry,
+            &mut ctx.accounts.registry_page,
+            ctx.bumps.registry_page,
+            registry_entry,
+        )?;
 
         emit!(TokenInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
             authority: ctx.accounts.authority.key(),
             mint: ctx.accounts.mint.key(),
             symbol: token_config.symbol.clone(),
@@ -35,6 +201,16 @@ pub mod gated_token {
             decimals,
         });
 
+        emit!(FreezeAuthorityChangedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_freeze_authority: None,
+            new_freeze_authority: Some(ctx.accounts.freeze_authority.key()),
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 
@@ -46,9 +222,27 @@ pub mod gated_token {
         allowlist_entry.wallet = ctx.accounts.wallet.key();
         allowlist_entry.is_approved = true;
         allowlist_entry.approved_at = clock.unix_timestamp;
+        allowlist_entry.lockup_until = None;
+        allowlist_entry.transfer_locked = false;
+        allowlist_entry.is_insider = false;
+        allowlist_entry.approved_token_account = None;
+        allowlist_entry.country_code = UNASSIGNED_COUNTRY;
+        allowlist_entry.last_snapshot_id = 0;
+        allowlist_entry.holder_page = None;
+        allowlist_entry.allocated_through = 0;
+        allowlist_entry.drip_enabled = false;
+        allowlist_entry.interest_index = ctx.accounts.token_config.interest_index;
+        allowlist_entry.accrued_interest = 0;
+        allowlist_entry.version = AllowlistEntry::CURRENT_VERSION;
+        allowlist_entry.holder_indexed = false;
         allowlist_entry.bump = ctx.bumps.allowlist_entry;
 
+        let token_config = &mut ctx.accounts.token_config;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &allowlist_entry.wallet);
+
         emit!(WalletApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
             token_mint: ctx.accounts.token_config.mint,
             wallet: ctx.accounts.wallet.key(),
             approved_by: ctx.accounts.authority.key(),
@@ -62,11 +256,25 @@ pub mod gated_token {
     pub fn revoke_wallet(ctx: Context<RevokeWallet>) -> Result<()> {
         let allowlist_entry = &mut ctx.accounts.allowlist_entry;
         let clock = Clock::get()?;
+        require!(allowlist_entry.is_approved, ErrorCode::WalletNotApproved);
+
+        // Free the wallet's slot under its country's investor cap
+        if allowlist_entry.country_code != UNASSIGNED_COUNTRY {
+            let country_rule = ctx.accounts.country_rule
+                .as_mut()
+                .ok_or(ErrorCode::CountryRuleRequired)?;
+            country_rule.investor_count = country_rule.investor_count.saturating_sub(1);
+        }
 
         allowlist_entry.is_approved = false;
         allowlist_entry.revoked_at = Some(clock.unix_timestamp);
 
+        let token_config = &mut ctx.accounts.token_config;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &allowlist_entry.wallet);
+
         emit!(WalletRevokedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
             token_mint: ctx.accounts.token_config.mint,
             wallet: ctx.accounts.wallet.key(),
             revoked_by: ctx.accounts.authority.key(),
@@ -76,30 +284,132 @@ pub mod gated_token {
         Ok(())
     }
 
-    /// Mint tokens to an approved wallet
-    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+    /// Open bitmap page `page_index` of a token's compact allowlist
+    pub fn create_allowlist_bitmap_page(
+        ctx: Context<CreateAllowlistBitmapPage>,
+        page_index: u32,
+    ) -> Result<()> {
+        let mut page = ctx.accounts.page.load_init()?;
+        page.token_mint = ctx.accounts.token_config.mint;
+        page.page_index = page_index;
+        page.bump = ctx.bumps.page;
+        // An empty page commits to a tree of zero leaves
+        page.assignment_root = (0..BITMAP_PAGE_DEPTH).fold([0u8; 32], |zero, _| hash_pair(&zero, &zero));
+
+        emit!(AllowlistBitmapPageCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: page.token_mint,
+            page_index,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Assign the next free slots of a bitmap page to `wallets`, in order, optionally
+    /// approving them at once. Indexers rebuild each page's assignment tree from these
+    /// events to serve the proofs compact transfers need.
+    pub fn assign_allowlist_slots(
+        ctx: Context<UpdateAllowlistBitmapPage>,
+        wallets: Vec<Pubkey>,
+        approve: bool,
+    ) -> Result<()> {
+        require!(!wallets.is_empty(), ErrorCode::InvalidAllowlistSlot);
+        let mut page = ctx.accounts.page.load_mut()?;
+        let first_slot = page.first_slot() + page.assigned;
+        for wallet in wallets.iter() {
+            let slot = page.assign(wallet)?;
+            if approve {
+                page.set_approved(slot, true)?;
+            }
+        }
+
+        emit!(AllowlistSlotsAssignedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: page.token_mint,
+            first_slot,
+            wallets,
+            approved: approve,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Approve or revoke already-assigned slots of a bitmap page
+    pub fn set_allowlist_slots_approved(
+        ctx: Context<UpdateAllowlistBitmapPage>,
+        slots: Vec<u32>,
+        approved: bool,
+    ) -> Result<()> {
+        require!(!slots.is_empty(), ErrorCode::InvalidAllowlistSlot);
+        let mut page = ctx.accounts.page.load_mut()?;
+        for &slot in slots.iter() {
+            page.set_approved(slot, approved)?;
+        }
+
+        emit!(AllowlistSlotsApprovalUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: page.token_mint,
+            slots,
+            approved,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Mint to a wallet approved on the compact allowlist. Compact holders carry no
+    /// per-wallet state, so tokens with snapshots, a cap table or sanctions screening
+    /// must use `mint_tokens`.
+    pub fn compact_mint(
+        ctx: Context<CompactMint>,
+        amount: u64,
+        slot: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        require!(
+            ctx.accounts.token_config.supports_compact_allowlist() && !ctx.accounts.token_config.restrict_cpi_callers,
+            ErrorCode::CompactAllowlistUnsupported
+        );
+        require!(
+            ctx.accounts.recipient_page.load()?.is_approved(&ctx.accounts.recipient.key(), slot, &proof)?,
+            ErrorCode::WalletNotApproved
+        );
 
-        // Verify recipient is approved
-        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
-        require!(recipient_entry.is_approved, ErrorCode::WalletNotApproved);
+        let is_new_holder = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
 
-        // Mint tokens
-        let cpi_accounts = token::MintTo {
-            mint: ctx.accounts.mint.to_account_info(),
-            to: ctx.accounts.recipient_token_account.to_account_info(),
-            authority: ctx.accounts.authority.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::mint_to(cpi_ctx, amount)?;
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
 
-        // Update total supply
         let token_config = &mut ctx.accounts.token_config;
-        token_config.total_supply = token_config.total_supply.checked_add(amount)
-            .ok_or(ErrorCode::Overflow)?;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
 
         emit!(TokensMintedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
             token_mint: ctx.accounts.mint.key(),
             recipient: ctx.accounts.recipient.key(),
             amount,
@@ -109,498 +419,24718 @@ pub mod gated_token {
         Ok(())
     }
 
-    /// Transfer tokens with allowlist validation
-    pub fn gated_transfer(ctx: Context<GatedTransfer>, amount: u64) -> Result<()> {
+    /// Transfer between wallets approved on the compact allowlist, each proving its slot.
+    /// Lockups, transfer locks and jurisdictions live on `AllowlistEntry`, so they do not
+    /// apply here; tokens with controls this path cannot enforce must use `gated_transfer`,
+    /// as must mints with a transfer hook, which reads the per-wallet entries.
+    pub fn compact_transfer(
+        ctx: Context<CompactTransfer>,
+        amount: u64,
+        sender_slot: u32,
+        sender_proof: Vec<[u8; 32]>,
+        recipient_slot: u32,
+        recipient_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
+        let token_config = &ctx.accounts.token_config;
+        require!(!token_config.paused, ErrorCode::TokenPaused);
+        require!(token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        token_config.validate_lot(amount)?;
+        require!(!has_transfer_hook(&ctx.accounts.mint)?, ErrorCode::TransferHookUnsupported);
+        require!(
+            token_config.supports_compact_allowlist()
+                && token_config.transfer_fee_bps == 0
+                && token_config.daily_transfer_limit == 0
+                && token_config.open_blackout_windows == 0
+                && token_config.rofr_period_seconds == 0
+                && !token_config.features.contains(FeatureFlags::MEMO_REQUIRED)
+                && !token_config.journal_enabled,
+            ErrorCode::CompactAllowlistUnsupported
+        );
+        require!(
+            ctx.accounts.sender_page.load()?.is_approved(&ctx.accounts.authority.key(), sender_slot, &sender_proof)?,
+            ErrorCode::SenderNotApproved
+        );
+        require!(
+            ctx.accounts.recipient_page.load()?.is_approved(&ctx.accounts.recipient.key(), recipient_slot, &recipient_proof)?,
+            ErrorCode::RecipientNotApproved
+        );
 
-        // Verify sender is approved
-        let sender_entry = &ctx.accounts.sender_allowlist_entry;
-        require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
-
-        // Verify recipient is approved
-        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
-        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        let from = &ctx.accounts.from_token_account;
+        let to = &ctx.accounts.to_token_account;
+        let holder_delta = if from.key() == to.key() {
+            0
+        } else {
+            i64::from(to.amount == 0) - i64::from(from.amount == amount)
+        };
+        enforce_share_class(token_config, ctx.accounts.share_class.as_ref(), holder_delta, true)?;
 
-        // Execute transfer
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.from_token_account.to_account_info(),
-            to: ctx.accounts.to_token_account.to_account_info(),
+        let cpi_accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: to.to_account_info(),
             authority: ctx.accounts.authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        token::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
 
         emit!(TokensTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
             token_mint: ctx.accounts.mint.key(),
             from: ctx.accounts.authority.key(),
             to: ctx.accounts.recipient.key(),
             amount,
+            travel_rule_hash: None,
         });
 
         Ok(())
     }
 
-    /// Execute a stock split by creating a new token with multiplied supply
-    pub fn execute_stock_split(
-        ctx: Context<ExecuteStockSplit>,
-        split_ratio: u64,
-        new_symbol: String,
-        new_name: String,
+    /// Back a token's compact allowlist with a concurrent Merkle tree. `merkle_tree` must be
+    /// allocated to the compression program beforehand, sized for `max_depth`,
+    /// `max_buffer_size` and the desired canopy.
+    pub fn init_allowlist_tree(
+        ctx: Context<InitAllowlistTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
     ) -> Result<()> {
-        require!(split_ratio > 0, ErrorCode::InvalidSplitRatio);
-        require!(new_symbol.len() >= 3 && new_symbol.len() <= 10, ErrorCode::InvalidSymbol);
-        require!(new_name.len() >= 2 && new_name.len() <= 50, ErrorCode::InvalidName);
+        let allowlist_tree = &mut ctx.accounts.allowlist_tree;
+        allowlist_tree.token_mint = ctx.accounts.token_config.mint;
+        allowlist_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        allowlist_tree.max_depth = max_depth;
+        allowlist_tree.next_leaf_index = 0;
+        allowlist_tree.bump = ctx.bumps.allowlist_tree;
 
-        let split_config = &mut ctx.accounts.split_config;
-        let clock = Clock::get()?;
-        
-        split_config.original_mint = ctx.accounts.old_token_config.mint;
-        split_config.new_mint = ctx.accounts.new_mint.key();
-        split_config.split_ratio = split_ratio;
-        split_config.executed_at = clock.unix_timestamp;
-        split_config.executed_by = ctx.accounts.authority.key();
-        split_config.bump = ctx.bumps.split_config;
+        let args = [max_depth.to_le_bytes(), max_buffer_size.to_le_bytes()].concat();
+        modify_allowlist_tree(
+            "init_empty_merkle_tree",
+            &args,
+            &ctx.accounts.merkle_tree.to_account_info(),
+            allowlist_tree,
+            &ctx.accounts.noop_program.to_account_info(),
+            &[],
+        )?;
 
-        // Initialize new token config with split ratio applied
-        let new_token_config = &mut ctx.accounts.new_token_config;
-        new_token_config.authority = ctx.accounts.authority.key();
-        new_token_config.mint = ctx.accounts.new_mint.key();
-        new_token_config.symbol = new_symbol.clone();
-        new_token_config.name = new_name.clone();
-        new_token_config.decimals = ctx.accounts.old_token_config.decimals;
-        new_token_config.total_supply = ctx.accounts.old_token_config.total_supply
-            .checked_mul(split_ratio)
-            .ok_or(ErrorCode::Overflow)?;
-        new_token_config.bump = ctx.bumps.new_token_config;
+        emit!(AllowlistTreeInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: allowlist_tree.token_mint,
+            merkle_tree: allowlist_tree.merkle_tree,
+            max_depth,
+            max_buffer_size,
+            authority: ctx.accounts.authority.key(),
+        });
 
-        emit!(StockSplitExecutedEvent {
-            old_mint: split_config.original_mint,
-            new_mint: split_config.new_mint,
-            split_ratio,
+        Ok(())
+    }
+
+    /// Approve a wallet on the compressed allowlist by appending its leaf
+    pub fn append_compressed_entry(ctx: Context<UpdateAllowlistTree>, wallet: Pubkey) -> Result<()> {
+        modify_allowlist_tree(
+            "append",
+            &compressed_entry_leaf(&wallet, true),
+            &ctx.accounts.merkle_tree.to_account_info(),
+            &ctx.accounts.allowlist_tree,
+            &ctx.accounts.noop_program.to_account_info(),
+            &[],
+        )?;
+
+        let allowlist_tree = &mut ctx.accounts.allowlist_tree;
+        let leaf_index = allowlist_tree.next_leaf_index;
+        allowlist_tree.next_leaf_index = leaf_index.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        emit!(CompressedEntryUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: allowlist_tree.token_mint,
+            wallet,
+            leaf_index,
+            approved: true,
             authority: ctx.accounts.authority.key(),
-            timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
 
-    /// Migrate a holder's balance to the new token after a split
-    pub fn migrate_holder_split(
-        ctx: Context<MigrateHolderSplit>,
-        old_balance: u64,
+    /// Approve or revoke a wallet already in the compressed allowlist by replacing its leaf.
+    /// The proof for the leaf's current state goes in the remaining accounts.
+    pub fn set_compressed_entry_approved<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateAllowlistTree<'info>>,
+        wallet: Pubkey,
+        root: [u8; 32],
+        leaf_index: u32,
+        approved: bool,
     ) -> Result<()> {
-        let split_config = &ctx.accounts.split_config;
-        let new_balance = old_balance
-            .checked_mul(split_config.split_ratio)
-            .ok_or(ErrorCode::Overflow)?;
+        let args = [
+            &root[..],
+            &compressed_entry_leaf(&wallet, !approved)[..],
+            &compressed_entry_leaf(&wallet, approved)[..],
+            &leaf_index.to_le_bytes()[..],
+        ]
+        .concat();
+        modify_allowlist_tree(
+            "replace_leaf",
+            &args,
+            &ctx.accounts.merkle_tree.to_account_info(),
+            &ctx.accounts.allowlist_tree,
+            &ctx.accounts.noop_program.to_account_info(),
+            ctx.remaining_accounts,
+        )?;
 
-        // Mint new tokens equal to old balance * split ratio
-        let cpi_accounts = token::MintTo {
-            mint: ctx.accounts.new_mint.to_account_info(),
-            to: ctx.accounts.holder_new_token_account.to_account_info(),
+        emit!(CompressedEntryUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.allowlist_tree.token_mint,
+            wallet,
+            leaf_index,
+            approved,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// `compact_mint` for a wallet approved on the compressed allowlist; the proof of its
+    /// leaf goes in the remaining accounts
+    pub fn compressed_mint<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompressedMint<'info>>,
+        amount: u64,
+        root: [u8; 32],
+        leaf_index: u32,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        require!(
+            ctx.accounts.token_config.supports_compact_allowlist() && !ctx.accounts.token_config.restrict_cpi_callers,
+            ErrorCode::CompactAllowlistUnsupported
+        );
+        verify_compressed_leaf(
+            &ctx.accounts.merkle_tree.to_account_info(),
+            root,
+            compressed_entry_leaf(&ctx.accounts.recipient.key(), true),
+            leaf_index,
+            ctx.remaining_accounts,
+        )?;
+
+        let is_new_holder = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TokensMintedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            new_supply: token_config.total_supply,
+        });
+
+        Ok(())
+    }
+
+    /// `compact_transfer` between wallets approved on the compressed allowlist. The
+    /// remaining accounts hold the sender's proof (`sender_proof_len` nodes), then the
+    /// recipient's. Mints with a transfer hook are refused, as for `compact_transfer`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn compressed_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CompressedTransfer<'info>>,
+        amount: u64,
+        sender_root: [u8; 32],
+        sender_leaf_index: u32,
+        recipient_root: [u8; 32],
+        recipient_leaf_index: u32,
+        sender_proof_len: u8,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        let token_config = &ctx.accounts.token_config;
+        require!(!token_config.paused, ErrorCode::TokenPaused);
+        require!(token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        token_config.validate_lot(amount)?;
+        require!(!has_transfer_hook(&ctx.accounts.mint)?, ErrorCode::TransferHookUnsupported);
+        require!(
+            token_config.supports_compact_allowlist()
+                && token_config.transfer_fee_bps == 0
+                && token_config.daily_transfer_limit == 0
+                && token_config.open_blackout_windows == 0
+                && token_config.rofr_period_seconds == 0
+                && !token_config.features.contains(FeatureFlags::MEMO_REQUIRED)
+                && !token_config.journal_enabled,
+            ErrorCode::CompactAllowlistUnsupported
+        );
+        let sender_proof_len = usize::from(sender_proof_len);
+        require!(sender_proof_len <= ctx.remaining_accounts.len(), ErrorCode::InvalidAllowlistSlot);
+        let (sender_proof, recipient_proof) = ctx.remaining_accounts.split_at(sender_proof_len);
+        let merkle_tree = ctx.accounts.merkle_tree.to_account_info();
+        verify_compressed_leaf(
+            &merkle_tree,
+            sender_root,
+            compressed_entry_leaf(&ctx.accounts.authority.key(), true),
+            sender_leaf_index,
+            sender_proof,
+        )?;
+        verify_compressed_leaf(
+            &merkle_tree,
+            recipient_root,
+            compressed_entry_leaf(&ctx.accounts.recipient.key(), true),
+            recipient_leaf_index,
+            recipient_proof,
+        )?;
+
+        let from = &ctx.accounts.from_token_account;
+        let to = &ctx.accounts.to_token_account;
+        let holder_delta = if from.key() == to.key() {
+            0
+        } else {
+            i64::from(to.amount == 0) - i64::from(from.amount == amount)
+        };
+        enforce_share_class(token_config, ctx.accounts.share_class.as_ref(), holder_delta, true)?;
+
+        let cpi_accounts = TransferChecked {
+            from: from.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: to.to_account_info(),
             authority: ctx.accounts.authority.to_account_info(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
         let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::mint_to(cpi_ctx, new_balance)?;
+        token::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
 
-        // Update new token config total supply
-        let new_token_config = &mut ctx.accounts.new_token_config;
-        new_token_config.total_supply = new_token_config.total_supply
-            .checked_add(new_balance)
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TokensTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            from: ctx.accounts.authority.key(),
+            to: ctx.accounts.recipient.key(),
+            amount,
+            travel_rule_hash: None,
+        });
+
+        Ok(())
+    }
+
+    /// Mint tokens to an approved wallet
+    pub fn mint_tokens(ctx: Context<MintTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        ctx.accounts.token_config.validate_lot(amount)?;
+
+        // Verify recipient is approved
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.recipient_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        enforce_country_rule(recipient_entry, ctx.accounts.recipient_country_rule.as_deref())?;
+
+        // Hard-fail on a sanctions hit regardless of allowlist status
+        let sanctions_oracle = ctx.accounts.token_config.sanctions_oracle;
+        require!(
+            !is_sanctioned(
+                &sanctions_oracle,
+                ctx.accounts.recipient_sanctions_flag.as_ref(),
+                &recipient_entry.wallet,
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        let is_new_holder = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+
+        // Record the recipient's record-date balance before it moves
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.recipient_token_account.amount,
+        )?;
+        let recipient_balance = ctx.accounts.recipient_token_account.amount.checked_add(amount)
             .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((
+                &mut ctx.accounts.recipient_allowlist_entry,
+                &ctx.accounts.recipient_token_account.key(),
+                recipient_balance,
+            )),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &ctx.accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_holder_page.as_ref(),
+            recipient_balance,
+        )?;
 
-        emit!(HolderMigratedEvent {
-            wallet: ctx.accounts.holder.key(),
-            old_balance,
-            new_balance,
-            split_ratio: split_config.split_ratio,
+        // Mint tokens
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        // Update total supply
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TokensMintedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            new_supply: token_config.total_supply,
         });
 
         Ok(())
     }
 
-    /// Update token metadata (symbol and name)
-    pub fn update_token_metadata(
-        ctx: Context<UpdateTokenMetadata>,
-        new_symbol: String,
-        new_name: String,
+    /// `mint_tokens` for a recipient that may not hold a token account yet: their
+    /// associated token account is created first, paid for by the authority. The
+    /// `MintTokens` accounts follow as remaining accounts, in their usual order.
+    pub fn mint_tokens_with_ata<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateRecipientAta<'info>>,
+        amount: u64,
     ) -> Result<()> {
-        require!(new_symbol.len() >= 3 && new_symbol.len() <= 10, ErrorCode::InvalidSymbol);
-        require!(new_name.len() >= 2 && new_name.len() <= 50, ErrorCode::InvalidName);
+        create_recipient_ata(ctx.accounts)?;
 
-        let token_config = &mut ctx.accounts.token_config;
-        let old_symbol = token_config.symbol.clone();
-        let old_name = token_config.name.clone();
+        let mut remaining = ctx.remaining_accounts;
+        let mut bumps = MintTokensBumps::default();
+        let mut accounts = MintTokens::try_accounts(&crate::ID, &mut remaining, &[], &mut bumps, &mut BTreeSet::new())?;
+        require_keys_eq!(accounts.authority.key(), ctx.accounts.authority.key(), ErrorCode::UnauthorizedAuthority);
+        require_keys_eq!(
+            accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_token_account.key(),
+            ErrorCode::TokenAccountNotApproved
+        );
+        mint_tokens(Context::new(&crate::ID, &mut accounts, remaining, bumps), amount)?;
+        accounts.exit(&crate::ID)
+    }
 
-        token_config.symbol = new_symbol.clone();
-        token_config.name = new_name.clone();
+    /// Mint to many investors at once, e.g. when closing a financing round. Each
+    /// recipient is a group of `BATCH_MINT_ACCOUNTS_PER_RECIPIENT` remaining accounts
+    /// (token account, allowlist entry) receiving `amounts[i]`; supply is recorded once for
+    /// the whole batch. Sanctions-screened tokens, and recipients that owe a snapshot
+    /// checkpoint, sit in the cap table, would join the holder index or carry a
+    /// jurisdiction, must use `mint_tokens`.
+    pub fn batch_mint<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchMint<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(!amounts.is_empty(), ErrorCode::InvalidAmount);
+        require!(
+            ctx.remaining_accounts.len() == amounts.len() * BATCH_MINT_ACCOUNTS_PER_RECIPIENT,
+            ErrorCode::InvalidBatchAccounts
+        );
+        require!(
+            ctx.accounts.token_config.sanctions_oracle == Pubkey::default(),
+            ErrorCode::BatchMintUnsupported
+        );
+        let mint_key = ctx.accounts.mint.key();
+        let mut recipients = Vec::with_capacity(amounts.len());
+        let mut total_amount: u64 = 0;
+        let mut new_holders: i64 = 0;
 
-        let clock = Clock::get()?;
+        for (accounts, &amount) in ctx.remaining_accounts.chunks(BATCH_MINT_ACCOUNTS_PER_RECIPIENT).zip(&amounts) {
+            require!(amount > 0, ErrorCode::InvalidAmount);
+            ctx.accounts.token_config.validate_lot(amount)?;
+            require!(
+                accounts.iter().all(|account| account.is_writable),
+                ErrorCode::InvalidBatchAccounts
+            );
+            let token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accounts[0])?;
+            require_keys_eq!(token_account.mint, mint_key, ErrorCode::InvalidBatchAccounts);
+            let mut entry: Account<AllowlistEntry> = Account::try_from(&accounts[1])?;
+            require_keys_eq!(entry.wallet, token_account.owner, ErrorCode::InvalidBatchAccounts);
+            let expected_entry = Pubkey::create_program_address(
+                &[b"allowlist", mint_key.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidBatchAccounts)?;
+            require_keys_eq!(expected_entry, entry.key(), ErrorCode::InvalidBatchAccounts);
 
-        emit!(SymbolChangedEvent {
-            mint: token_config.mint,
-            old_symbol,
-            new_symbol,
-            old_name,
-            new_name,
+            require!(entry.is_approved, ErrorCode::WalletNotApproved);
+            require!(entry.permits_token_account(&token_account.key()), ErrorCode::TokenAccountNotApproved);
+            enforce_country_rule(&entry, None)?;
+
+            checkpoint_snapshot_balance(&ctx.accounts.token_config, &mut entry, None, None, token_account.amount)?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut entry,
+                &token_account.key(),
+                None,
+                token_account.amount.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+            )?;
+            entry.exit(&crate::ID)?;
+
+            // A wallet listed twice only joins the cap table once
+            if token_account.amount == 0 && !recipients.contains(&entry.wallet) {
+                new_holders += 1;
+            }
+            mint_with_program_authority(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                accounts[0].clone(),
+                &ctx.accounts.mint_authority,
+                ctx.bumps.mint_authority,
+                amount,
+            )?;
+            total_amount = total_amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+            recipients.push(entry.wallet);
+        }
+
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            new_holders,
+            false,
+        )?;
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, total_amount)?;
+        token_config.holder_count = token_config.holder_count.checked_add(new_holders)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(BatchMintedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            recipients,
+            amounts,
+            total_amount,
+            new_supply: token_config.total_supply,
             authority: ctx.accounts.authority.key(),
-            timestamp: clock.unix_timestamp,
         });
 
         Ok(())
     }
-}
-
-// Account structures
-#[account]
-pub struct TokenConfig {
-    pub authority: Pubkey,
-    pub mint: Pubkey,
-    pub symbol: String,
-    pub name: String,
-    pub decimals: u8,
-    pub total_supply: u64,
-    pub bump: u8,
-}
 
-#[account]
-pub struct AllowlistEntry {
-    pub wallet: Pubkey,
-    pub is_approved: bool,
-    pub approved_at: i64,
-    pub revoked_at: Option<i64>,
-    pub bump: u8,
-}
+    /// `gated_transfer` to a recipient that may not hold a token account yet: their
+    /// associated token account is created first, paid for by the sender. The
+    /// `GatedTransfer` accounts follow as remaining accounts, then any blackout windows.
+    pub fn gated_transfer_with_ata<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateRecipientAta<'info>>,
+        amount: u64,
+        travel_rule_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        create_recipient_ata(ctx.accounts)?;
 
-#[account]
-pub struct SplitConfig {
-    pub original_mint: Pubkey,
-    pub new_mint: Pubkey,
-    pub split_ratio: u64,
-    pub executed_at: i64,
-    pub executed_by: Pubkey,
-    pub bump: u8,
-}
+        let mut remaining = ctx.remaining_accounts;
+        let mut bumps = GatedTransferBumps::default();
+        let mut accounts = GatedTransfer::try_accounts(&crate::ID, &mut remaining, &[], &mut bumps, &mut BTreeSet::new())?;
+        require_keys_eq!(accounts.authority.key(), ctx.accounts.authority.key(), ErrorCode::UnauthorizedAuthority);
+        require_keys_eq!(
+            accounts.to_token_account.key(),
+            ctx.accounts.recipient_token_account.key(),
+            ErrorCode::TokenAccountNotApproved
+        );
+        gated_transfer(Context::new(&crate::ID, &mut accounts, remaining, bumps), amount, travel_rule_hash)?;
+        accounts.exit(&crate::ID)
+    }
+
+    /// Transfer tokens with allowlist validation
+    /// Any open `BlackoutWindow` accounts for the mint must be passed as remaining accounts.
+    pub fn gated_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, GatedTransfer<'info>>,
+        amount: u64,
+        travel_rule_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        require!(
+            !ctx.accounts.token_config.features.contains(FeatureFlags::MEMO_REQUIRED) || travel_rule_hash.is_some(),
+            ErrorCode::TravelRuleHashRequired
+        );
+        let clock = Clock::get()?;
+        let from_token_account = ctx.accounts.from_token_account.key();
+        let to_token_account = ctx.accounts.to_token_account.key();
+        let same_account = from_token_account == to_token_account;
+
+        // Verify sender is approved
+        let sender_entry = &ctx.accounts.sender_allowlist_entry;
+        require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!sender_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        require!(
+            sender_entry.permits_token_account(&from_token_account),
+            ErrorCode::TokenAccountNotApproved
+        );
+        if let Some(lockup_until) = sender_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+
+        // Verify recipient is approved
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&to_token_account),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let (blackout_windows, hook_accounts) =
+            split_blackout_windows(&ctx.accounts.token_config, ctx.remaining_accounts)?;
+        if let (Some(velocity_counter), Some(bump)) =
+            (ctx.accounts.velocity_counter.as_mut(), ctx.bumps.velocity_counter)
+        {
+            velocity_counter.bump = bump;
+        }
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: sender_entry.wallet,
+                entry: Some(sender_entry),
+                country_rule: ctx.accounts.sender_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.sender_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: recipient_entry.wallet,
+                entry: Some(recipient_entry),
+                country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+            }),
+            &ctx.accounts.recipient.key(),
+            Some(&ctx.accounts.recipient),
+            TransferPolicyAccounts {
+                sale_proposal: ctx.accounts.sale_proposal.as_deref(),
+                destination_registry: ctx.accounts.destination_registry.as_ref(),
+                velocity_counter: ctx.accounts.velocity_counter.as_mut(),
+            },
+            blackout_windows,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        // Split off the issuer's transfer fee
+        // (rounded down to whole lots so both legs stay lot-aligned)
+        let lot_size = ctx.accounts.token_config.lot_size.max(1);
+        let fee = calculate_transfer_fee(amount, ctx.accounts.token_config.transfer_fee_bps)?;
+        let fee = fee - fee % lot_size;
+        let net_amount = amount.checked_sub(fee).ok_or(ErrorCode::Overflow)?;
+
+        // Track holders entering and leaving the cap table
+        let holder_delta: i64 = if same_account {
+            0
+        } else {
+            let joins = i64::from(ctx.accounts.to_token_account.amount == 0 && net_amount > 0);
+            let leaves = i64::from(ctx.accounts.from_token_account.amount == amount);
+            joins - leaves
+        };
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            holder_delta,
+            true,
+        )?;
+
+        // Record both parties' record-date balances before they move
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+            ctx.bumps.sender_snapshot_balance,
+            ctx.accounts.from_token_account.amount,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.to_token_account.amount,
+        )?;
+
+        // Keep the on-chain cap table in step with both balances
+        let sender_balance = ctx.accounts.from_token_account.amount
+            .checked_sub(if same_account { fee } else { amount })
+            .ok_or(ErrorCode::Overflow)?;
+        if let (Some(sender_page), Some(recipient_page)) =
+            (&ctx.accounts.sender_holder_page, &ctx.accounts.recipient_holder_page)
+        {
+            require_keys_neq!(sender_page.key(), recipient_page.key(), ErrorCode::InvalidHolderPage);
+        }
+        let recipient_balance = ctx.accounts.to_token_account.amount.checked_add(net_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.sender_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((
+                &mut ctx.accounts.sender_allowlist_entry,
+                &from_token_account,
+                sender_balance,
+            )),
+            (!same_account).then_some((
+                &mut ctx.accounts.recipient_allowlist_entry,
+                &to_token_account,
+                recipient_balance,
+            )),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            &from_token_account,
+            ctx.accounts.sender_holder_page.as_ref(),
+            sender_balance,
+        )?;
+        if !same_account {
+            // Both wallets on one page: the page is passed once, as the sender's
+            let recipient_page = match ctx.accounts.recipient_holder_page.as_ref() {
+                Some(page) => Some(page),
+                None => ctx.accounts.sender_holder_page.as_ref(),
+            };
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.recipient_allowlist_entry,
+                &to_token_account,
+                recipient_page,
+                recipient_balance,
+            )?;
+        }
+
+        // Execute transfer
+        if net_amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.from_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.to_token_account.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            transfer_checked_with_hook(cpi_ctx, net_amount, ctx.accounts.mint.decimals, hook_accounts)?;
+        }
+
+        if fee > 0 {
+            let fee_treasury = ctx.accounts.fee_treasury
+                .as_ref()
+                .ok_or(ErrorCode::FeeTreasuryRequired)?;
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.from_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: fee_treasury.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            transfer_checked_with_hook(cpi_ctx, fee, ctx.accounts.mint.decimals, hook_accounts)?;
+
+            emit!(TransferFeeCollectedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: ctx.accounts.token_config.next_event_sequence()?,
+                token_mint: ctx.accounts.mint.key(),
+                from: ctx.accounts.authority.key(),
+                fee,
+                fee_bps: ctx.accounts.token_config.transfer_fee_bps,
+            });
+        }
+
+        if ctx.accounts.token_config.journal_enabled {
+            let mut journal = ctx.accounts.transfer_journal
+                .as_ref()
+                .ok_or(ErrorCode::TransferJournalRequired)?
+                .load_mut()?;
+            journal.append(JournalEntry {
+                from: ctx.accounts.authority.key(),
+                to: ctx.accounts.recipient.key(),
+                amount: net_amount,
+                slot: clock.slot,
+            })?;
+        }
+
+        // The cleared proposal is spent by this sale
+        if ctx.accounts.token_config.rofr_period_seconds > 0 {
+            if let Some(proposal) = ctx.accounts.sale_proposal.as_ref() {
+                proposal.close(ctx.accounts.authority.to_account_info())?;
+            }
+        }
+
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TokensTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            from: ctx.accounts.authority.key(),
+            to: ctx.accounts.recipient.key(),
+            amount: net_amount,
+            travel_rule_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a block trade in one atomic instruction. Each leg is a group of
+    /// `BATCH_TRANSFER_ACCOUNTS_PER_LEG` remaining accounts (from, to, sender entry,
+    /// recipient entry) moving `amounts[i]`, signed by `authority` as owner or delegate of
+    /// every source account. Any leg failing the allowlist checks aborts the whole batch.
+    /// Mints with a transfer hook take the hook's accounts after the last leg.
+    /// Controls that need per-wallet accounts (fees, velocity limits, sanctions, blackout
+    /// windows, ROFR, travel-rule memos) are not supported here; wallets that owe a snapshot
+    /// checkpoint, sit in the cap table, would join or leave the holder index or carry a
+    /// jurisdiction must use `gated_transfer`.
+    pub fn batch_transfer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchTransfer<'info>>,
+        amounts: Vec<u64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        require!(!amounts.is_empty(), ErrorCode::InvalidAmount);
+        require!(
+            ctx.remaining_accounts.len() >= amounts.len() * BATCH_TRANSFER_ACCOUNTS_PER_LEG,
+            ErrorCode::InvalidBatchAccounts
+        );
+        let legs = &ctx.remaining_accounts[..amounts.len() * BATCH_TRANSFER_ACCOUNTS_PER_LEG];
+        let token_config = &ctx.accounts.token_config;
+        require!(
+            token_config.transfer_fee_bps == 0
+                && token_config.daily_transfer_limit == 0
+                && token_config.sanctions_oracle == Pubkey::default()
+                && token_config.open_blackout_windows == 0
+                && token_config.rofr_period_seconds == 0
+                && !token_config.features.contains(FeatureFlags::MEMO_REQUIRED)
+                && !token_config.journal_enabled,
+            ErrorCode::BatchTransferUnsupported
+        );
+        let clock = Clock::get()?;
+        let mint_key = ctx.accounts.mint.key();
+        let mut total_amount: u64 = 0;
+
+        for (accounts, &amount) in legs.chunks(BATCH_TRANSFER_ACCOUNTS_PER_LEG).zip(&amounts) {
+            require!(amount > 0, ErrorCode::InvalidAmount);
+            ctx.accounts.token_config.validate_lot(amount)?;
+            require!(
+                accounts.iter().all(|account| account.is_writable),
+                ErrorCode::InvalidBatchAccounts
+            );
+            let from: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accounts[0])?;
+            let to: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accounts[1])?;
+            require_keys_eq!(from.mint, mint_key, ErrorCode::InvalidBatchAccounts);
+            require_keys_eq!(to.mint, mint_key, ErrorCode::InvalidBatchAccounts);
+
+            let mut sender_entry: Account<AllowlistEntry> = Account::try_from(&accounts[2])?;
+            let mut recipient_entry: Account<AllowlistEntry> = Account::try_from(&accounts[3])?;
+            for (entry, owner) in [(&sender_entry, from.owner), (&recipient_entry, to.owner)] {
+                require_keys_eq!(entry.wallet, owner, ErrorCode::InvalidBatchAccounts);
+                let expected_entry = Pubkey::create_program_address(
+                    &[b"allowlist", mint_key.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                    &crate::ID,
+                ).map_err(|_| ErrorCode::InvalidBatchAccounts)?;
+                require_keys_eq!(expected_entry, entry.key(), ErrorCode::InvalidBatchAccounts);
+            }
+
+            require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
+            require!(!sender_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+            require!(sender_entry.permits_token_account(&from.key()), ErrorCode::TokenAccountNotApproved);
+            if let Some(lockup_until) = sender_entry.lockup_until {
+                require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+            }
+            require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+            require!(recipient_entry.permits_token_account(&to.key()), ErrorCode::TokenAccountNotApproved);
+            enforce_country_rule(&sender_entry, None)?;
+            enforce_country_rule(&recipient_entry, None)?;
+            // Program-controlled owners need the destination registry, which only `gated_transfer`
+            // consults; the token's own authority may be a multisig vault PDA
+            require!(
+                to.owner.is_on_curve() || to.owner == ctx.accounts.token_config.authority,
+                ErrorCode::DestinationProgramNotApproved
+            );
+
+            let same_account = from.key() == to.key();
+            let holder_delta = if same_account {
+                0
+            } else {
+                i64::from(to.amount == 0) - i64::from(from.amount == amount)
+            };
+            enforce_share_class(
+                &ctx.accounts.token_config,
+                ctx.accounts.share_class.as_ref(),
+                holder_delta,
+                true,
+            )?;
+
+            if !same_account {
+                checkpoint_snapshot_balance(&ctx.accounts.token_config, &mut sender_entry, None, None, from.amount)?;
+                checkpoint_snapshot_balance(&ctx.accounts.token_config, &mut recipient_entry, None, None, to.amount)?;
+                sync_cap_table(
+                    &ctx.accounts.token_config,
+                    &mut sender_entry,
+                    &from.key(),
+                    None,
+                    from.amount.checked_sub(amount).ok_or(ErrorCode::Overflow)?,
+                )?;
+                sync_cap_table(
+                    &ctx.accounts.token_config,
+                    &mut recipient_entry,
+                    &to.key(),
+                    None,
+                    to.amount.checked_add(amount).ok_or(ErrorCode::Overflow)?,
+                )?;
+            }
+            sender_entry.exit(&crate::ID)?;
+            recipient_entry.exit(&crate::ID)?;
+
+            let cpi_accounts = TransferChecked {
+                from: accounts[0].clone(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: accounts[1].clone(),
+                authority: ctx.accounts.authority.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+            if holder_delta != 0 {
+                let token_config = &mut ctx.accounts.token_config;
+                token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+            total_amount = total_amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+            emit!(TokensTransferredEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: ctx.accounts.token_config.next_event_sequence()?,
+                token_mint: mint_key,
+                from: from.owner,
+                to: to.owner,
+                amount,
+                travel_rule_hash: None,
+            });
+        }
+
+        emit!(BatchTransferEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            authority: ctx.accounts.authority.key(),
+            legs: amounts.len() as u32,
+            total_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Transfer under a one-time permit signed off-chain by the compliance officer over
+    /// (sender, recipient, amount, nonce, expiry). The signature is checked through an
+    /// ed25519 program instruction placed immediately before this one; the permit stands in
+    /// for the allowlist, so either party may lack an entry. Every other control of
+    /// `gated_transfer` still applies, with open `BlackoutWindow` accounts passed as
+    /// remaining accounts; tokens charging fees, requiring travel-rule memos or keeping a
+    /// transfer journal are refused. The sender's permit nonce is consumed.
+    pub fn permit_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, PermitTransfer<'info>>,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        require_keys_neq!(ctx.accounts.authority.key(), ctx.accounts.recipient.key(), ErrorCode::InvalidPermit);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp <= expiry, ErrorCode::PermitExpired);
+
+        let permit_nonce = &mut ctx.accounts.permit_nonce;
+        if permit_nonce.wallet == Pubkey::default() {
+            permit_nonce.token_mint = ctx.accounts.mint.key();
+            permit_nonce.wallet = ctx.accounts.authority.key();
+            permit_nonce.bump = ctx.bumps.permit_nonce;
+        }
+        require!(permit_nonce.nonce == nonce, ErrorCode::InvalidPermitNonce);
+
+        let signed_by = ctx.accounts.token_config.compliance_officer;
+        let message = permit_message(
+            &ctx.accounts.mint.key(),
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.recipient.key(),
+            amount,
+            nonce,
+            expiry,
+        );
+        verify_ed25519_signature(&ctx.accounts.instructions, &signed_by, &message)?;
+
+        // A permit stands in for the allowlist only: every other transfer control applies
+        require_no_fee_memo_or_journal(&ctx.accounts.token_config)?;
+        let (blackout_windows, hook_accounts) =
+            split_blackout_windows(&ctx.accounts.token_config, ctx.remaining_accounts)?;
+        if let (Some(velocity_counter), Some(bump)) =
+            (ctx.accounts.velocity_counter.as_mut(), ctx.bumps.velocity_counter)
+        {
+            velocity_counter.bump = bump;
+        }
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: ctx.accounts.authority.key(),
+                entry: ctx.accounts.sender_allowlist_entry.as_deref().map(|entry| &**entry),
+                country_rule: ctx.accounts.sender_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.sender_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: ctx.accounts.recipient.key(),
+                entry: ctx.accounts.recipient_allowlist_entry.as_deref().map(|entry| &**entry),
+                country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+            }),
+            &ctx.accounts.recipient.key(),
+            Some(&ctx.accounts.recipient),
+            TransferPolicyAccounts {
+                sale_proposal: ctx.accounts.sale_proposal.as_deref(),
+                destination_registry: ctx.accounts.destination_registry.as_deref(),
+                velocity_counter: ctx.accounts.velocity_counter.as_deref_mut(),
+            },
+            blackout_windows,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        // Track holders entering and leaving the cap table
+        let joins = i64::from(ctx.accounts.to_token_account.amount == 0);
+        let leaves = i64::from(ctx.accounts.from_token_account.amount == amount);
+        let holder_delta = joins - leaves;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            holder_delta,
+            true,
+        )?;
+
+        // Parties holding an allowlist entry keep their snapshot and cap table records current
+        if let Some(entry) = ctx.accounts.sender_allowlist_entry.as_deref_mut() {
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                entry,
+                ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+                ctx.bumps.sender_snapshot_balance,
+                ctx.accounts.from_token_account.amount,
+            )?;
+            let sender_balance = ctx.accounts.from_token_account.amount.checked_sub(amount)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: ctx.accounts.sender_holder_number.as_deref_mut(),
+                    last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                    joining_number: None,
+                    payer: &ctx.accounts.authority.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                Some((entry, &ctx.accounts.from_token_account.key(), sender_balance)),
+                None,
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                entry,
+                &ctx.accounts.from_token_account.key(),
+                ctx.accounts.sender_holder_page.as_ref(),
+                sender_balance,
+            )?;
+        }
+        if let Some(entry) = ctx.accounts.recipient_allowlist_entry.as_deref_mut() {
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                entry,
+                ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+                ctx.bumps.recipient_snapshot_balance,
+                ctx.accounts.to_token_account.amount,
+            )?;
+            let recipient_balance = ctx.accounts.to_token_account.amount.checked_add(amount)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: None,
+                    last_number: None,
+                    joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                    payer: &ctx.accounts.authority.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                None,
+                Some((entry, &ctx.accounts.to_token_account.key(), recipient_balance)),
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                entry,
+                &ctx.accounts.to_token_account.key(),
+                ctx.accounts.recipient_holder_page.as_ref(),
+                recipient_balance,
+            )?;
+        }
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, hook_accounts)?;
+
+        ctx.accounts.permit_nonce.nonce = nonce.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        // The cleared proposal is spent by this sale
+        if ctx.accounts.token_config.rofr_period_seconds > 0 {
+            if let Some(proposal) = ctx.accounts.sale_proposal.as_ref() {
+                proposal.close(ctx.accounts.authority.to_account_info())?;
+            }
+        }
+
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(PermitTransferEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            from: ctx.accounts.authority.key(),
+            to: ctx.accounts.recipient.key(),
+            amount,
+            nonce,
+            signed_by,
+        });
+
+        Ok(())
+    }
+
+    /// Let an approved delegate (e.g. a broker) move up to `amount` from the owner's token
+    /// account through `delegated_transfer`. Mirrors SPL `approve`: a new approval replaces
+    /// the previous one.
+    pub fn approve_delegate(ctx: Context<ApproveDelegate>, amount: u64) -> Result<()> {
+        let owner_entry = &ctx.accounts.owner_allowlist_entry;
+        require!(owner_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(
+            owner_entry.permits_token_account(&ctx.accounts.owner_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(ctx.accounts.delegate_allowlist_entry.is_approved, ErrorCode::DelegateNotApproved);
+
+        let cpi_accounts = token::ApproveChecked {
+            to: ctx.accounts.owner_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            delegate: ctx.accounts.delegate.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::approve_checked(
+            CpiContext::new(cpi_program, cpi_accounts),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        let clock = Clock::get()?;
+
+        emit!(DelegateApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            owner: ctx.accounts.owner.key(),
+            delegate: ctx.accounts.delegate.key(),
+            token_account: ctx.accounts.owner_token_account.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw any delegate approval on the owner's token account
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        let cpi_accounts = token::Revoke {
+            source: ctx.accounts.owner_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::revoke(CpiContext::new(cpi_program, cpi_accounts))?;
+
+        let clock = Clock::get()?;
+
+        emit!(DelegateApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.owner_token_account.mint,
+            owner: ctx.accounts.owner.key(),
+            delegate: Pubkey::default(),
+            token_account: ctx.accounts.owner_token_account.key(),
+            amount: 0,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Transfer from an owner's account as its SPL delegate. The true owner must be able to
+    /// send, the recipient to receive, and the delegate itself must be allowlisted. The
+    /// transfer controls of `gated_transfer` apply as between owner and recipient, with open
+    /// `BlackoutWindow` accounts passed as remaining accounts; tokens charging fees,
+    /// requiring travel-rule memos or keeping a transfer journal are refused.
+    pub fn delegated_transfer<'info>(ctx: Context<'_, '_, '_, 'info, DelegatedTransfer<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        require!(
+            ctx.accounts.from_token_account.delegated_amount >= amount,
+            ErrorCode::InsufficientAllowance
+        );
+        let clock = Clock::get()?;
+
+        let owner_entry = &ctx.accounts.owner_allowlist_entry;
+        require!(owner_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!owner_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        require!(
+            owner_entry.permits_token_account(&ctx.accounts.from_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        if let Some(lockup_until) = owner_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.to_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(ctx.accounts.delegate_allowlist_entry.is_approved, ErrorCode::DelegateNotApproved);
+
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.delegate_sanctions_flag.as_ref(),
+                &ctx.accounts.delegate.key(),
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        // The owner and recipient face every control a direct transfer between them would
+        require_no_fee_memo_or_journal(&ctx.accounts.token_config)?;
+        let (blackout_windows, hook_accounts) =
+            split_blackout_windows(&ctx.accounts.token_config, ctx.remaining_accounts)?;
+        if let (Some(velocity_counter), Some(bump)) =
+            (ctx.accounts.velocity_counter.as_mut(), ctx.bumps.velocity_counter)
+        {
+            velocity_counter.bump = bump;
+        }
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: ctx.accounts.owner.key(),
+                entry: Some(owner_entry),
+                country_rule: ctx.accounts.owner_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.owner_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: ctx.accounts.recipient.key(),
+                entry: Some(recipient_entry),
+                country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+            }),
+            &ctx.accounts.recipient.key(),
+            Some(&ctx.accounts.recipient),
+            TransferPolicyAccounts {
+                sale_proposal: ctx.accounts.sale_proposal.as_deref(),
+                destination_registry: ctx.accounts.destination_registry.as_deref(),
+                velocity_counter: ctx.accounts.velocity_counter.as_deref_mut(),
+            },
+            blackout_windows,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        // Track holders entering and leaving the cap table
+        let joins = i64::from(ctx.accounts.to_token_account.amount == 0);
+        let leaves = i64::from(ctx.accounts.from_token_account.amount == amount);
+        let holder_delta = joins - leaves;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            holder_delta,
+            true,
+        )?;
+
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.owner_allowlist_entry,
+            ctx.accounts.owner_snapshot_balance.as_deref_mut(),
+            ctx.bumps.owner_snapshot_balance,
+            ctx.accounts.from_token_account.amount,
+        )?;
+        let sender_balance = ctx.accounts.from_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let recipient_balance = ctx.accounts.to_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.owner_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.delegate.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.owner_allowlist_entry, &ctx.accounts.from_token_account.key(), sender_balance)),
+            Some((&mut ctx.accounts.recipient_allowlist_entry, &ctx.accounts.to_token_account.key(), recipient_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.owner_allowlist_entry,
+            &ctx.accounts.from_token_account.key(),
+            ctx.accounts.owner_holder_page.as_ref(),
+            sender_balance,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.to_token_account.amount,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &ctx.accounts.to_token_account.key(),
+            ctx.accounts.recipient_holder_page.as_ref(),
+            recipient_balance,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: ctx.accounts.delegate.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, hook_accounts)?;
+
+        // The cleared proposal is spent by this sale
+        if ctx.accounts.token_config.rofr_period_seconds > 0 {
+            if let Some(proposal) = ctx.accounts.sale_proposal.as_ref() {
+                proposal.close(ctx.accounts.delegate.to_account_info())?;
+            }
+        }
+
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(DelegatedTransferEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            owner: ctx.accounts.owner.key(),
+            delegate: ctx.accounts.delegate.key(),
+            to: ctx.accounts.recipient.key(),
+            amount,
+            remaining_allowance: ctx.accounts.from_token_account.delegated_amount - amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lock `amount` of the sender's shares in a program vault until `condition` is met,
+    /// then `release_escrow` delivers them to `recipient`. Refused while the token charges
+    /// fees, requires memos, keeps a journal, or runs velocity limits, blackout windows or
+    /// ROFR, which judge the moment of sale rather than a later release.
+    pub fn create_escrow<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEscrow<'info>>,
+        amount: u64,
+        condition: ReleaseCondition,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        let clock = Clock::get()?;
+        if let ReleaseCondition::Timestamp { release_at } = condition {
+            require!(release_at > clock.unix_timestamp, ErrorCode::InvalidEscrowCondition);
+        }
+
+        let sender_entry = &ctx.accounts.sender_allowlist_entry;
+        require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!sender_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = sender_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            sender_entry.permits_token_account(&ctx.accounts.sender_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        // Controls that judge the moment of sale can't be applied to a delayed release
+        require_no_transfer_controls(&ctx.accounts.token_config)?;
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: ctx.accounts.sender.key(),
+                entry: Some(sender_entry),
+                country_rule: ctx.accounts.sender_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.sender_sanctions_flag.as_ref(),
+            },
+            None,
+            &ctx.accounts.recipient.key(),
+            Some(&ctx.accounts.recipient),
+            TransferPolicyAccounts {
+                sale_proposal: None,
+                destination_registry: ctx.accounts.destination_registry.as_deref(),
+                velocity_counter: None,
+            },
+            &[],
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        let leaves = ctx.accounts.sender_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+            ctx.bumps.sender_snapshot_balance,
+            ctx.accounts.sender_token_account.amount,
+        )?;
+        let sender_balance = ctx.accounts.sender_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.sender_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.sender.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.sender_allowlist_entry, &ctx.accounts.sender_token_account.key(), sender_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            &ctx.accounts.sender_token_account.key(),
+            ctx.accounts.sender_holder_page.as_ref(),
+            sender_balance,
+        )?;
+
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.escrow.key(),
+            ctx.accounts.sender.key(),
+        )?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.sender_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.escrow_vault.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.token_mint = token_config.mint;
+        escrow.escrow_id = token_config.next_escrow_id;
+        escrow.sender = ctx.accounts.sender.key();
+        escrow.recipient = ctx.accounts.recipient.key();
+        escrow.amount = amount;
+        escrow.condition = condition;
+        escrow.created_at = clock.unix_timestamp;
+        escrow.bump = ctx.bumps.escrow;
+        token_config.next_escrow_id = token_config.next_escrow_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(EscrowCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: escrow.token_mint,
+            escrow_id: escrow.escrow_id,
+            sender: escrow.sender,
+            recipient: escrow.recipient,
+            amount,
+            condition,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Deliver escrowed shares once the release condition holds. Anyone may crank a
+    /// timestamp or oracle release; a counter-signature release needs that signer. The
+    /// recipient must still be approved and clear of sanctions and country rules, and rent
+    /// returns to the sender.
+    pub fn release_escrow<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseEscrow<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.escrow.condition.is_met(
+                clock.unix_timestamp,
+                ctx.accounts.counter_signer.as_ref(),
+                ctx.accounts.price_feed.as_ref(),
+            )?,
+            ErrorCode::EscrowConditionNotMet
+        );
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.recipient_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        // Controls switched on since creation leave the escrow to be cancelled
+        require_no_transfer_controls(&ctx.accounts.token_config)?;
+        screen_transfer_party(&ctx.accounts.token_config, &TransferParty {
+            wallet: ctx.accounts.escrow.recipient,
+            entry: Some(recipient_entry),
+            country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+            sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+        })?;
+
+        let amount = ctx.accounts.escrow.amount;
+        let joins = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(joins),
+            true,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.recipient_token_account.amount,
+        )?;
+        let recipient_balance = ctx.accounts.recipient_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.payer.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.recipient_allowlist_entry, &ctx.accounts.recipient_token_account.key(), recipient_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &ctx.accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_holder_page.as_ref(),
+            recipient_balance,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        let id_bytes = escrow.escrow_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"escrow", escrow.token_mint.as_ref(), &id_bytes, &[escrow.bump]];
+        drain_program_vault(
+            &ctx.accounts.escrow_vault,
+            &ctx.accounts.mint,
+            ctx.accounts.recipient_token_account.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            escrow.to_account_info(),
+            &[seeds],
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+        retire_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref(),
+        )?;
+
+        if joins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(EscrowReleasedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.escrow.token_mint,
+            escrow_id: ctx.accounts.escrow.escrow_id,
+            recipient: ctx.accounts.escrow.recipient,
+            amount,
+            released_by: ctx.accounts.payer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Return escrowed shares to the sender while the release condition is still unmet
+    pub fn cancel_escrow<'info>(ctx: Context<'_, '_, '_, 'info, CancelEscrow<'info>>) -> Result<()> {
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        require!(
+            !ctx.accounts.escrow.condition.is_met(clock.unix_timestamp, None, ctx.accounts.price_feed.as_ref())?,
+            ErrorCode::EscrowConditionMet
+        );
+
+        let sender_entry = &ctx.accounts.sender_allowlist_entry;
+        require!(
+            sender_entry.permits_token_account(&ctx.accounts.sender_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let amount = ctx.accounts.escrow.amount;
+        let rejoins = ctx.accounts.sender_token_account.amount == 0;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+            ctx.bumps.sender_snapshot_balance,
+            ctx.accounts.sender_token_account.amount,
+        )?;
+        let sender_balance = ctx.accounts.sender_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.sender_holder_number.as_deref(),
+                payer: &ctx.accounts.sender.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.sender_allowlist_entry, &ctx.accounts.sender_token_account.key(), sender_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            &ctx.accounts.sender_token_account.key(),
+            ctx.accounts.sender_holder_page.as_ref(),
+            sender_balance,
+        )?;
+
+        let escrow = &ctx.accounts.escrow;
+        let id_bytes = escrow.escrow_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"escrow", escrow.token_mint.as_ref(), &id_bytes, &[escrow.bump]];
+        drain_program_vault(
+            &ctx.accounts.escrow_vault,
+            &ctx.accounts.mint,
+            ctx.accounts.sender_token_account.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            escrow.to_account_info(),
+            &[seeds],
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+        retire_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref(),
+        )?;
+
+        if rejoins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(EscrowCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.escrow.token_mint,
+            escrow_id: ctx.accounts.escrow.escrow_id,
+            sender: ctx.accounts.escrow.sender,
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Offer `offered_amount` of token A for `requested_amount` of token B. The offered
+    /// shares are locked in a program vault so a later `accept_swap_offer` settles both
+    /// legs atomically without the maker signing again. `taker` restricts who may accept.
+    pub fn create_swap_offer<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateSwapOffer<'info>>,
+        offered_amount: u64,
+        requested_amount: u64,
+        taker: Option<Pubkey>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        require!(offered_amount > 0 && requested_amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.offered_token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.offered_token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        require!(!ctx.accounts.requested_token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.requested_token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.offered_token_config.validate_lot(offered_amount)?;
+        ctx.accounts.requested_token_config.validate_lot(requested_amount)?;
+        let clock = Clock::get()?;
+        if let Some(expires_at) = expires_at {
+            require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidSwapOffer);
+        }
+
+        let maker_entry = &ctx.accounts.maker_offered_allowlist_entry;
+        require!(maker_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!maker_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = maker_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            maker_entry.permits_token_account(&ctx.accounts.maker_offered_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(
+            ctx.accounts.maker_requested_allowlist_entry.is_approved,
+            ErrorCode::RecipientNotApproved
+        );
+        // Refused here too so a maker doesn't lock shares in an offer no one could accept
+        require_no_transfer_controls(&ctx.accounts.offered_token_config)?;
+        require_no_transfer_controls(&ctx.accounts.requested_token_config)?;
+
+        let leaves = ctx.accounts.maker_offered_token_account.amount == offered_amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.offered_token_config,
+            &mut ctx.accounts.maker_offered_allowlist_entry,
+            ctx.accounts.maker_snapshot_balance.as_deref_mut(),
+            ctx.bumps.maker_snapshot_balance,
+            ctx.accounts.maker_offered_token_account.amount,
+        )?;
+        let maker_offered_balance = ctx.accounts.maker_offered_token_account.amount.checked_sub(offered_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.offered_token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.maker_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.maker.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.maker_offered_allowlist_entry, &ctx.accounts.maker_offered_token_account.key(), maker_offered_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.offered_token_config,
+            &mut ctx.accounts.maker_offered_allowlist_entry,
+            &ctx.accounts.maker_offered_token_account.key(),
+            ctx.accounts.maker_holder_page.as_ref(),
+            maker_offered_balance,
+        )?;
+
+        approve_vault_owner(
+            &mut ctx.accounts.offered_token_config,
+            &ctx.accounts.offered_mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.offer.key(),
+            ctx.accounts.maker.key(),
+        )?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.maker_offered_token_account.to_account_info(),
+            mint: ctx.accounts.offered_mint.to_account_info(),
+            to: ctx.accounts.offer_vault.to_account_info(),
+            authority: ctx.accounts.maker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(
+            CpiContext::new(cpi_program, cpi_accounts),
+            offered_amount,
+            ctx.accounts.offered_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+
+        let token_config = &mut ctx.accounts.offered_token_config;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let offer = &mut ctx.accounts.offer;
+        offer.offer_id = token_config.next_swap_offer_id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.taker = taker;
+        offer.offered_mint = token_config.mint;
+        offer.offered_amount = offered_amount;
+        offer.requested_mint = ctx.accounts.requested_token_config.mint;
+        offer.requested_amount = requested_amount;
+        offer.expires_at = expires_at;
+        offer.created_at = clock.unix_timestamp;
+        offer.bump = ctx.bumps.offer;
+        token_config.next_swap_offer_id = token_config.next_swap_offer_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SwapOfferCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            offer_id: offer.offer_id,
+            maker: offer.maker,
+            taker,
+            offered_mint: offer.offered_mint,
+            offered_amount,
+            requested_mint: offer.requested_mint,
+            requested_amount,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a swap offer in one transaction: the taker's token B goes to the maker and
+    /// the vaulted token A goes to the taker. Both parties must be approved on both mints
+    /// at settlement, and neither token may be paused. Each leg is screened like a direct
+    /// transfer for sanctions, country rules and program-controlled recipients; tokens
+    /// running fees, memos, the journal, velocity limits, blackout windows or ROFR are refused.
+    pub fn accept_swap_offer<'info>(ctx: Context<'_, '_, '_, 'info, AcceptSwapOffer<'info>>) -> Result<()> {
+        require!(!ctx.accounts.offered_token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.offered_token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        require!(!ctx.accounts.requested_token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.requested_token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let offer = &ctx.accounts.offer;
+        if let Some(taker) = offer.taker {
+            require_keys_eq!(taker, ctx.accounts.taker.key(), ErrorCode::SwapOfferRestricted);
+        }
+        if let Some(expires_at) = offer.expires_at {
+            require!(clock.unix_timestamp < expires_at, ErrorCode::SwapOfferExpired);
+        }
+        let offered_amount = offer.offered_amount;
+        let requested_amount = offer.requested_amount;
+
+        // Leg A: maker -> taker, out of the vault
+        let maker_entry = &ctx.accounts.maker_offered_allowlist_entry;
+        require!(maker_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!maker_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        let taker_entry = &ctx.accounts.taker_offered_allowlist_entry;
+        require!(taker_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            taker_entry.permits_token_account(&ctx.accounts.taker_offered_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        // Leg B: taker -> maker
+        let taker_entry = &ctx.accounts.taker_requested_allowlist_entry;
+        require!(taker_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!taker_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = taker_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            taker_entry.permits_token_account(&ctx.accounts.taker_requested_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        let maker_entry = &ctx.accounts.maker_requested_allowlist_entry;
+        require!(maker_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            maker_entry.permits_token_account(&ctx.accounts.maker_requested_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        // Each leg is screened as a direct transfer on its own token; controls that need
+        // per-sale accounts are refused on either token
+        require_no_transfer_controls(&ctx.accounts.offered_token_config)?;
+        require_no_transfer_controls(&ctx.accounts.requested_token_config)?;
+        let maker = ctx.accounts.maker.key();
+        let taker = ctx.accounts.taker.key();
+        let taker_info = ctx.accounts.taker.to_account_info();
+        enforce_transfer_policy(
+            &ctx.accounts.offered_token_config,
+            &TransferParty {
+                wallet: maker,
+                entry: Some(&ctx.accounts.maker_offered_allowlist_entry),
+                country_rule: ctx.accounts.maker_offered_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.maker_offered_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: taker,
+                entry: Some(&ctx.accounts.taker_offered_allowlist_entry),
+                country_rule: ctx.accounts.taker_offered_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.taker_offered_sanctions_flag.as_ref(),
+            }),
+            &taker,
+            Some(&taker_info),
+            TransferPolicyAccounts {
+                sale_proposal: None,
+                destination_registry: ctx.accounts.offered_destination_registry.as_deref(),
+                velocity_counter: None,
+            },
+            &[],
+            offered_amount,
+            clock.unix_timestamp,
+        )?;
+        enforce_transfer_policy(
+            &ctx.accounts.requested_token_config,
+            &TransferParty {
+                wallet: taker,
+                entry: Some(&ctx.accounts.taker_requested_allowlist_entry),
+                country_rule: ctx.accounts.taker_requested_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.taker_requested_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: maker,
+                entry: Some(&ctx.accounts.maker_requested_allowlist_entry),
+                country_rule: ctx.accounts.maker_requested_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.maker_requested_sanctions_flag.as_ref(),
+            }),
+            &maker,
+            Some(&ctx.accounts.maker),
+            TransferPolicyAccounts {
+                sale_proposal: None,
+                destination_registry: ctx.accounts.requested_destination_registry.as_deref(),
+                velocity_counter: None,
+            },
+            &[],
+            requested_amount,
+            clock.unix_timestamp,
+        )?;
+
+        let offered_delta = i64::from(ctx.accounts.taker_offered_token_account.amount == 0);
+        let requested_delta = i64::from(ctx.accounts.maker_requested_token_account.amount == 0)
+            - i64::from(ctx.accounts.taker_requested_token_account.amount == requested_amount);
+        enforce_share_class(
+            &ctx.accounts.offered_token_config,
+            ctx.accounts.offered_share_class.as_ref(),
+            offered_delta,
+            true,
+        )?;
+        enforce_share_class(
+            &ctx.accounts.requested_token_config,
+            ctx.accounts.requested_share_class.as_ref(),
+            requested_delta,
+            true,
+        )?;
+
+        checkpoint_snapshot_balance(
+            &ctx.accounts.offered_token_config,
+            &mut ctx.accounts.taker_offered_allowlist_entry,
+            ctx.accounts.taker_offered_snapshot_balance.as_deref_mut(),
+            ctx.bumps.taker_offered_snapshot_balance,
+            ctx.accounts.taker_offered_token_account.amount,
+        )?;
+        let taker_offered_balance = ctx.accounts.taker_offered_token_account.amount.checked_add(offered_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.offered_token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.offered_holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.taker_offered_holder_number.as_deref(),
+                payer: &ctx.accounts.taker.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.taker_offered_allowlist_entry, &ctx.accounts.taker_offered_token_account.key(), taker_offered_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.offered_token_config,
+            &mut ctx.accounts.taker_offered_allowlist_entry,
+            &ctx.accounts.taker_offered_token_account.key(),
+            ctx.accounts.taker_offered_holder_page.as_ref(),
+            taker_offered_balance,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.requested_token_config,
+            &mut ctx.accounts.taker_requested_allowlist_entry,
+            ctx.accounts.taker_requested_snapshot_balance.as_deref_mut(),
+            ctx.bumps.taker_requested_snapshot_balance,
+            ctx.accounts.taker_requested_token_account.amount,
+        )?;
+        let taker_requested_balance = ctx.accounts.taker_requested_token_account.amount.checked_sub(requested_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let maker_requested_balance = ctx.accounts.maker_requested_token_account.amount.checked_add(requested_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.requested_token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.requested_holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.taker_requested_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.requested_last_holder_number.as_deref_mut(),
+                joining_number: ctx.accounts.maker_requested_holder_number.as_deref(),
+                payer: &ctx.accounts.taker.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.taker_requested_allowlist_entry, &ctx.accounts.taker_requested_token_account.key(), taker_requested_balance)),
+            Some((&mut ctx.accounts.maker_requested_allowlist_entry, &ctx.accounts.maker_requested_token_account.key(), maker_requested_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.requested_token_config,
+            &mut ctx.accounts.taker_requested_allowlist_entry,
+            &ctx.accounts.taker_requested_token_account.key(),
+            ctx.accounts.taker_requested_holder_page.as_ref(),
+            taker_requested_balance,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.requested_token_config,
+            &mut ctx.accounts.maker_requested_allowlist_entry,
+            ctx.accounts.maker_requested_snapshot_balance.as_deref_mut(),
+            ctx.bumps.maker_requested_snapshot_balance,
+            ctx.accounts.maker_requested_token_account.amount,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.requested_token_config,
+            &mut ctx.accounts.maker_requested_allowlist_entry,
+            &ctx.accounts.maker_requested_token_account.key(),
+            ctx.accounts.maker_requested_holder_page.as_ref(),
+            maker_requested_balance,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.taker_requested_token_account.to_account_info(),
+            mint: ctx.accounts.requested_mint.to_account_info(),
+            to: ctx.accounts.maker_requested_token_account.to_account_info(),
+            authority: ctx.accounts.taker.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(
+            CpiContext::new(cpi_program, cpi_accounts),
+            requested_amount,
+            ctx.accounts.requested_mint.decimals,
+            ctx.remaining_accounts,
+        )?;
+
+        let offer = &ctx.accounts.offer;
+        let id_bytes = offer.offer_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"swap_offer", offer.offered_mint.as_ref(), &id_bytes, &[offer.bump]];
+        drain_program_vault(
+            &ctx.accounts.offer_vault,
+            &ctx.accounts.offered_mint,
+            ctx.accounts.taker_offered_token_account.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            offer.to_account_info(),
+            &[seeds],
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+        retire_vault_owner(
+            &mut ctx.accounts.offered_token_config,
+            &ctx.accounts.offered_mint,
+            ctx.accounts.vault_allowlist_entry.as_deref(),
+        )?;
+
+        let offered_config = &mut ctx.accounts.offered_token_config;
+        offered_config.holder_count = offered_config.holder_count.checked_add(offered_delta)
+            .ok_or(ErrorCode::Overflow)?;
+        let requested_config = &mut ctx.accounts.requested_token_config;
+        requested_config.holder_count = requested_config.holder_count.checked_add(requested_delta)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SwapExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.offered_token_config.next_event_sequence()?,
+            offer_id: ctx.accounts.offer.offer_id,
+            maker,
+            taker,
+            offered_mint: ctx.accounts.offer.offered_mint,
+            offered_amount,
+            requested_mint: ctx.accounts.offer.requested_mint,
+            requested_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an unaccepted swap offer, returning the vaulted shares to the maker
+    pub fn cancel_swap_offer<'info>(ctx: Context<'_, '_, '_, 'info, CancelSwapOffer<'info>>) -> Result<()> {
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.maker_allowlist_entry
+                .permits_token_account(&ctx.accounts.maker_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let amount = ctx.accounts.offer.offered_amount;
+        let rejoins = ctx.accounts.maker_token_account.amount == 0;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.maker_allowlist_entry,
+            ctx.accounts.maker_snapshot_balance.as_deref_mut(),
+            ctx.bumps.maker_snapshot_balance,
+            ctx.accounts.maker_token_account.amount,
+        )?;
+        let maker_balance = ctx.accounts.maker_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.maker_holder_number.as_deref(),
+                payer: &ctx.accounts.maker.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.maker_allowlist_entry, &ctx.accounts.maker_token_account.key(), maker_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.maker_allowlist_entry,
+            &ctx.accounts.maker_token_account.key(),
+            ctx.accounts.maker_holder_page.as_ref(),
+            maker_balance,
+        )?;
+
+        let offer = &ctx.accounts.offer;
+        let id_bytes = offer.offer_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"swap_offer", offer.offered_mint.as_ref(), &id_bytes, &[offer.bump]];
+        drain_program_vault(
+            &ctx.accounts.offer_vault,
+            &ctx.accounts.mint,
+            ctx.accounts.maker_token_account.to_account_info(),
+            ctx.accounts.maker.to_account_info(),
+            offer.to_account_info(),
+            &[seeds],
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+        retire_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref(),
+        )?;
+
+        if rejoins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(SwapOfferCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            offer_id: ctx.accounts.offer.offer_id,
+            maker: ctx.accounts.offer.maker,
+            offered_mint: ctx.accounts.offer.offered_mint,
+            offered_amount: amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the freely tradable wrapper for a gated token: an ordinary SPL mint at
+    /// `[b"wrapped_mint", mint]` backed one-for-one by shares held in the wrapper vault
+    pub fn initialize_wrapper(ctx: Context<InitializeWrapper>) -> Result<()> {
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.wrapper.key(),
+            ctx.accounts.authority.key(),
+        )?;
+        let wrapper = &mut ctx.accounts.wrapper;
+        wrapper.token_mint = ctx.accounts.mint.key();
+        wrapper.wrapped_mint = ctx.accounts.wrapped_mint.key();
+        wrapper.total_wrapped = 0;
+        wrapper.bump = ctx.bumps.wrapper;
+
+        emit!(WrapperInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: wrapper.token_mint,
+            wrapped_mint: wrapper.wrapped_mint,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Lock an approved holder's shares in the wrapper vault and mint the same amount of
+    /// the wrapper token to them. Wrapped units leave the cap table until unwrapped.
+    pub fn wrap_shares<'info>(ctx: Context<'_, '_, '_, 'info, WrapShares<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        let clock = Clock::get()?;
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!holder_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = holder_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.wrapper_vault.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(CpiContext::new(cpi_program.clone(), cpi_accounts), amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        let token_mint = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"wrapper", token_mint.as_ref(), &[ctx.accounts.wrapper.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = token::MintTo {
+            mint: ctx.accounts.wrapped_mint.to_account_info(),
+            to: ctx.accounts.holder_wrapped_account.to_account_info(),
+            authority: ctx.accounts.wrapper.to_account_info(),
+        };
+        token::mint_to(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount)?;
+
+        if leaves {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        let wrapper = &mut ctx.accounts.wrapper;
+        wrapper.total_wrapped = wrapper.total_wrapped.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesWrappedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            total_wrapped: wrapper.total_wrapped,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Burn wrapper tokens and release the backing shares. Anyone may hold the wrapper,
+    /// but only a wallet that passes the allowlist and sanctions checks can unwrap.
+    pub fn unwrap_shares<'info>(ctx: Context<'_, '_, '_, 'info, UnwrapShares<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        let clock = Clock::get()?;
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.holder_sanctions_flag.as_ref(),
+                &ctx.accounts.holder.key(),
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        let joins = ctx.accounts.holder_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(joins),
+            true,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.holder_number.as_deref(),
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = token::Burn {
+            mint: ctx.accounts.wrapped_mint.to_account_info(),
+            from: ctx.accounts.holder_wrapped_account.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program.clone(), cpi_accounts), amount)?;
+
+        let token_mint = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"wrapper", token_mint.as_ref(), &[ctx.accounts.wrapper.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.wrapper_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.holder_token_account.to_account_info(),
+            authority: ctx.accounts.wrapper.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if joins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        let wrapper = &mut ctx.accounts.wrapper;
+        wrapper.total_wrapped = wrapper.total_wrapped.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesUnwrappedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            total_wrapped: wrapper.total_wrapped,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless audit that the wrapper is fully backed: recorded wrapped supply,
+    /// the wrapper mint's supply and the shares in the vault must all agree
+    pub fn reconcile_wrapper(ctx: Context<ReconcileWrapper>) -> Result<()> {
+        let wrapper = &ctx.accounts.wrapper;
+        let escrowed = ctx.accounts.wrapper_vault.amount;
+        let wrapped_supply = ctx.accounts.wrapped_mint.supply;
+
+        require!(
+            wrapper.total_wrapped == wrapped_supply && escrowed == wrapped_supply,
+            ErrorCode::WrapperOutOfBalance
+        );
+
+        emit!(WrapperReconciledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: wrapper.token_mint,
+            total_wrapped: wrapper.total_wrapped,
+            wrapped_supply,
+            escrowed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lock `amount` of the sender's shares and stream them to `recipient` at
+    /// `rate_per_second` from `start_time`, e.g. advisor compensation paid in shares.
+    /// Refused while the token charges fees, requires memos, keeps a journal, or runs
+    /// velocity limits, blackout windows or ROFR.
+    pub fn create_stream<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateStream<'info>>,
+        amount: u64,
+        rate_per_second: u64,
+        start_time: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(rate_per_second > 0, ErrorCode::InvalidStream);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        ctx.accounts.token_config.validate_lot(amount)?;
+        let clock = Clock::get()?;
+
+        let sender_entry = &ctx.accounts.sender_allowlist_entry;
+        require!(sender_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!sender_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = sender_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            sender_entry.permits_token_account(&ctx.accounts.sender_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(
+            ctx.accounts.recipient_allowlist_entry.is_approved,
+            ErrorCode::RecipientNotApproved
+        );
+
+        // Controls that judge the moment of sale can't be applied to shares paid out over time
+        require_no_transfer_controls(&ctx.accounts.token_config)?;
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: ctx.accounts.sender.key(),
+                entry: Some(sender_entry),
+                country_rule: ctx.accounts.sender_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.sender_sanctions_flag.as_ref(),
+            },
+            Some(&TransferParty {
+                wallet: ctx.accounts.recipient.key(),
+                entry: Some(&ctx.accounts.recipient_allowlist_entry),
+                country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+            }),
+            &ctx.accounts.recipient.key(),
+            Some(&ctx.accounts.recipient),
+            TransferPolicyAccounts {
+                sale_proposal: None,
+                destination_registry: ctx.accounts.destination_registry.as_deref(),
+                velocity_counter: None,
+            },
+            &[],
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        let leaves = ctx.accounts.sender_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+            ctx.bumps.sender_snapshot_balance,
+            ctx.accounts.sender_token_account.amount,
+        )?;
+        let sender_balance = ctx.accounts.sender_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.sender_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.sender.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.sender_allowlist_entry, &ctx.accounts.sender_token_account.key(), sender_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            &ctx.accounts.sender_token_account.key(),
+            ctx.accounts.sender_holder_page.as_ref(),
+            sender_balance,
+        )?;
+
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.stream.key(),
+            ctx.accounts.sender.key(),
+        )?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.sender_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.stream_vault.to_account_info(),
+            authority: ctx.accounts.sender.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let stream = &mut ctx.accounts.stream;
+        stream.token_mint = token_config.mint;
+        stream.stream_id = token_config.next_stream_id;
+        stream.sender = ctx.accounts.sender.key();
+        stream.recipient = ctx.accounts.recipient.key();
+        stream.deposited = amount;
+        stream.withdrawn = 0;
+        stream.rate_per_second = rate_per_second;
+        stream.start_time = start_time;
+        stream.paused_at = None;
+        stream.paused_seconds = 0;
+        stream.bump = ctx.bumps.stream;
+        token_config.next_stream_id = token_config.next_stream_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(StreamCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: stream.token_mint,
+            stream_id: stream.stream_id,
+            sender: stream.sender,
+            recipient: stream.recipient,
+            deposited: amount,
+            rate_per_second,
+            start_time,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw everything streamed to the recipient so far, re-checking their approval,
+    /// sanctions status and country rule
+    pub fn withdraw_streamed<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawStreamed<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let stream = &ctx.accounts.stream;
+        let amount = stream.streamed_amount(clock.unix_timestamp)?
+            .checked_sub(stream.withdrawn)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(amount > 0, ErrorCode::NothingStreamed);
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.recipient_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        // Controls switched on since creation leave the stream to be cancelled
+        require_no_transfer_controls(&ctx.accounts.token_config)?;
+        screen_transfer_party(&ctx.accounts.token_config, &TransferParty {
+            wallet: ctx.accounts.recipient.key(),
+            entry: Some(recipient_entry),
+            country_rule: ctx.accounts.recipient_country_rule.as_deref(),
+            sanctions_flag: ctx.accounts.recipient_sanctions_flag.as_ref(),
+        })?;
+
+        let joins = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(joins),
+            true,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.recipient_token_account.amount,
+        )?;
+        let recipient_balance = ctx.accounts.recipient_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.recipient.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.recipient_allowlist_entry, &ctx.accounts.recipient_token_account.key(), recipient_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &ctx.accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_holder_page.as_ref(),
+            recipient_balance,
+        )?;
+
+        let stream = &ctx.accounts.stream;
+        let id_bytes = stream.stream_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"stream", stream.token_mint.as_ref(), &id_bytes, &[stream.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.stream_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: stream.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if joins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        let stream = &mut ctx.accounts.stream;
+        stream.withdrawn = stream.withdrawn.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(StreamWithdrawnEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: stream.token_mint,
+            stream_id: stream.stream_id,
+            recipient: stream.recipient,
+            amount,
+            withdrawn: stream.withdrawn,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume a stream. Nothing accrues while paused; what streamed before the
+    /// pause stays withdrawable.
+    pub fn set_stream_paused(ctx: Context<SetStreamPaused>, paused: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let stream = &mut ctx.accounts.stream;
+        match (stream.paused_at, paused) {
+            (None, true) => stream.paused_at = Some(clock.unix_timestamp),
+            (Some(paused_at), false) => {
+                // Only time after the start counts towards the paused total
+                let paused_from = paused_at.max(stream.start_time);
+                let paused_for = clock.unix_timestamp.saturating_sub(paused_from).max(0);
+                stream.paused_seconds = stream.paused_seconds.checked_add(paused_for)
+                    .ok_or(ErrorCode::Overflow)?;
+                stream.paused_at = None;
+            }
+            _ => return err!(ErrorCode::InvalidStreamState),
+        }
+
+        emit!(StreamPausedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: stream.token_mint,
+            stream_id: stream.stream_id,
+            paused,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// End a stream: whatever has streamed but not been withdrawn goes to the recipient,
+    /// the unstreamed remainder and all rent return to the sender
+    pub fn cancel_stream<'info>(ctx: Context<'_, '_, '_, 'info, CancelStream<'info>>) -> Result<()> {
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let stream = &ctx.accounts.stream;
+        let owed = stream.streamed_amount(clock.unix_timestamp)?
+            .checked_sub(stream.withdrawn)
+            .ok_or(ErrorCode::Overflow)?;
+        let refunded = ctx.accounts.stream_vault.amount.checked_sub(owed)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let recipient_joins = owed > 0 && ctx.accounts.recipient_token_account.amount == 0;
+        let sender_rejoins = refunded > 0 && ctx.accounts.sender_token_account.amount == 0;
+        if owed > 0 {
+            let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+            require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+            require!(
+                recipient_entry.permits_token_account(&ctx.accounts.recipient_token_account.key()),
+                ErrorCode::TokenAccountNotApproved
+            );
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.recipient_allowlist_entry,
+                ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+                ctx.bumps.recipient_snapshot_balance,
+                ctx.accounts.recipient_token_account.amount,
+            )?;
+            let recipient_balance = ctx.accounts.recipient_token_account.amount.checked_add(owed)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: None,
+                    last_number: None,
+                    joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                    payer: &ctx.accounts.sender.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                None,
+                Some((&mut ctx.accounts.recipient_allowlist_entry, &ctx.accounts.recipient_token_account.key(), recipient_balance)),
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.recipient_allowlist_entry,
+                &ctx.accounts.recipient_token_account.key(),
+                ctx.accounts.recipient_holder_page.as_ref(),
+                recipient_balance,
+            )?;
+        }
+        require!(
+            ctx.accounts.sender_allowlist_entry
+                .permits_token_account(&ctx.accounts.sender_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            ctx.accounts.sender_snapshot_balance.as_deref_mut(),
+            ctx.bumps.sender_snapshot_balance,
+            ctx.accounts.sender_token_account.amount,
+        )?;
+        let sender_balance = ctx.accounts.sender_token_account.amount.checked_add(refunded)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.sender_holder_number.as_deref(),
+                payer: &ctx.accounts.sender.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.sender_allowlist_entry, &ctx.accounts.sender_token_account.key(), sender_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.sender_allowlist_entry,
+            &ctx.accounts.sender_token_account.key(),
+            ctx.accounts.sender_holder_page.as_ref(),
+            sender_balance,
+        )?;
+
+        let stream = &ctx.accounts.stream;
+        let id_bytes = stream.stream_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"stream", stream.token_mint.as_ref(), &id_bytes, &[stream.bump]];
+        let signer_seeds = &[seeds];
+        if owed > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.stream_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.recipient_token_account.to_account_info(),
+                authority: stream.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            transfer_checked_with_hook(cpi_ctx, owed, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+            ctx.accounts.stream_vault.reload()?;
+        }
+        drain_program_vault(
+            &ctx.accounts.stream_vault,
+            &ctx.accounts.mint,
+            ctx.accounts.sender_token_account.to_account_info(),
+            ctx.accounts.sender.to_account_info(),
+            stream.to_account_info(),
+            signer_seeds,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+        retire_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref(),
+        )?;
+
+        let holder_delta = i64::from(recipient_joins) + i64::from(sender_rejoins);
+        if holder_delta != 0 {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(StreamCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.stream.token_mint,
+            stream_id: ctx.accounts.stream.stream_id,
+            paid_to_recipient: owed,
+            refunded_to_sender: refunded,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a stock split by creating a new token with multiplied supply
+    pub fn execute_stock_split(
+        ctx: Context<ExecuteStockSplit>,
+        split_ratio: u64,
+        new_symbol: String,
+        new_name: String,
+    ) -> Result<()> {
+        require!(split_ratio > 0, ErrorCode::InvalidSplitRatio);
+        require!(new_symbol.len() >= 3 && new_symbol.len() <= 10, ErrorCode::InvalidSymbol);
+        require!(new_name.len() >= 2 && new_name.len() <= 50, ErrorCode::InvalidName);
+
+        let split_config = &mut ctx.accounts.split_config;
+        let clock = Clock::get()?;
+        
+        split_config.original_mint = ctx.accounts.old_token_config.mint;
+        split_config.new_mint = ctx.accounts.new_mint.key();
+        split_config.split_ratio = split_ratio;
+        split_config.executed_at = clock.unix_timestamp;
+        split_config.executed_by = ctx.accounts.authority.key();
+        split_config.bump = ctx.bumps.split_config;
+
+        // Initialize new token config with split ratio applied
+        let new_token_config = &mut ctx.accounts.new_token_config;
+        new_token_config.authority = ctx.accounts.authority.key();
+        new_token_config.mint = ctx.accounts.new_mint.key();
+        new_token_config.symbol = new_symbol.clone();
+        new_token_config.name = new_name.clone();
+        new_token_config.decimals = ctx.accounts.old_token_config.decimals;
+        new_token_config.total_supply = ctx.accounts.old_token_config.total_supply
+            .checked_mul(split_ratio)
+            .ok_or(ErrorCode::Overflow)?;
+        new_token_config.bump = ctx.bumps.new_token_config;
+        new_token_config.compliance_officer = ctx.accounts.old_token_config.compliance_officer;
+        new_token_config.lot_size = 1;
+        new_token_config.sanctions_oracle = ctx.accounts.old_token_config.sanctions_oracle;
+        new_token_config.split_numerator = 1;
+        new_token_config.split_denominator = 1;
+        new_token_config.max_supply = match ctx.accounts.old_token_config.max_supply {
+            Some(cap) => Some(cap.checked_mul(split_ratio).ok_or(ErrorCode::Overflow)?),
+            None => None,
+        };
+        new_token_config.uri = ctx.accounts.old_token_config.uri.clone();
+        new_token_config.version = TokenConfig::CURRENT_VERSION;
+        new_token_config.features = ctx.accounts.old_token_config.features;
+        new_token_config.price_oracle = ctx.accounts.old_token_config.price_oracle;
+        new_token_config.price_feed = ctx.accounts.old_token_config.price_feed;
+        new_token_config.max_price_age_seconds = ctx.accounts.old_token_config.max_price_age_seconds;
+
+        let registry_entry = RegistryEntry {
+            mint: new_token_config.mint,
+            authority: new_token_config.authority,
+            created_at: clock.unix_timestamp,
+        };
+        append_to_token_registry(
+            new_token_config,
+            &mut ctx.accounts.token_registry,
+            &mut ctx.accounts.registry_page,
+            ctx.bumps.registry_page,
+            registry_entry,
+        )?;
+
+        emit!(StockSplitExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.old_token_config.next_event_sequence()?,
+            old_mint: split_config.original_mint,
+            new_mint: split_config.new_mint,
+            split_ratio,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Migrate a holder's balance to the new token after a split
+    pub fn migrate_holder_split(
+        ctx: Context<MigrateHolderSplit>,
+        old_balance: u64,
+    ) -> Result<()> {
+        let split_config = &ctx.accounts.split_config;
+        let new_balance = old_balance
+            .checked_mul(split_config.split_ratio)
+            .ok_or(ErrorCode::Overflow)?;
+        let is_new_holder = ctx.accounts.holder_new_token_account.amount == 0 && new_balance > 0;
+
+        // Mint new tokens equal to old balance * split ratio
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.new_mint,
+            ctx.accounts.holder_new_token_account.to_account_info(),
+            &ctx.accounts.new_mint_authority,
+            ctx.bumps.new_mint_authority,
+            new_balance,
+        )?;
+
+        // Update new token config total supply
+        let new_token_config = &mut ctx.accounts.new_token_config;
+        new_token_config.total_supply = new_token_config.total_supply
+            .checked_add(new_balance)
+            .ok_or(ErrorCode::Overflow)?;
+        if is_new_holder {
+            new_token_config.holder_count = new_token_config.holder_count
+                .checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(HolderMigratedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.new_token_config.next_event_sequence()?,
+            wallet: ctx.accounts.holder.key(),
+            old_balance,
+            new_balance,
+            split_ratio: split_config.split_ratio,
+        });
+
+        // Durable per-holder proof of the migration; also prevents double migration
+        let clock = Clock::get()?;
+        write_action_receipt(
+            &mut ctx.accounts.new_token_config,
+            &mut ctx.accounts.receipt,
+            ActionReceipt {
+                action: split_config.key(),
+                kind: ActionKind::SplitMigration,
+                holder: ctx.accounts.holder.key(),
+                mint_in: split_config.original_mint,
+                mint_out: split_config.new_mint,
+                amount_in: old_balance,
+                amount_out: new_balance,
+                rate_numerator: split_config.split_ratio,
+                rate_denominator: 1,
+                settled_at: clock.unix_timestamp,
+                bump: ctx.bumps.receipt,
+            },
+        )?;
+
+        Ok(())
+    }
+
+    /// Update token metadata (symbol, name and URI). The config account is resized to fit
+    /// the new strings exactly, and the Metaplex metadata is rewritten when its accounts
+    /// are passed.
+    pub fn update_token_metadata(
+        ctx: Context<UpdateTokenMetadata>,
+        new_symbol: String,
+        new_name: String,
+        new_uri: String,
+    ) -> Result<()> {
+        require!(new_symbol.len() >= 3 && new_symbol.len() <= 10, ErrorCode::InvalidSymbol);
+        require!(new_name.len() >= 2 && new_name.len() <= 50, ErrorCode::InvalidName);
+        require!(new_uri.len() <= MAX_METADATA_URI_LEN, ErrorCode::InvalidMetadataUri);
+
+        match (&ctx.accounts.metadata, &ctx.accounts.token_metadata_program) {
+            (Some(metadata_account), Some(token_metadata_program)) => {
+                let cpi_accounts = UpdateMetadataAccountsV2 {
+                    metadata: metadata_account.to_account_info(),
+                    update_authority: ctx.accounts.authority.to_account_info(),
+                };
+                let cpi_program = token_metadata_program.to_account_info();
+                metadata::update_metadata_accounts_v2(
+                    CpiContext::new(cpi_program, cpi_accounts),
+                    None,
+                    Some(DataV2 {
+                        name: new_name.clone(),
+                        symbol: new_symbol.clone(),
+                        uri: new_uri.clone(),
+                        seller_fee_basis_points: 0,
+                        creators: None,
+                        collection: None,
+                        uses: None,
+                    }),
+                    None,
+                    None,
+                )?;
+            }
+            (None, None) => {}
+            _ => return err!(ErrorCode::MetadataAccountsRequired),
+        }
+
+        let token_config = &mut ctx.accounts.token_config;
+        let old_symbol = token_config.symbol.clone();
+        let old_name = token_config.name.clone();
+        let old_uri = token_config.uri.clone();
+
+        token_config.symbol = new_symbol.clone();
+        token_config.name = new_name.clone();
+        token_config.uri = new_uri.clone();
+
+        let clock = Clock::get()?;
+
+        emit!(SymbolChangedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            mint: token_config.mint,
+            old_symbol,
+            new_symbol,
+            old_name,
+            new_name,
+            old_uri,
+            new_uri,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pause all minting and transfers. The guardian may pause as well as the authority.
+    pub fn pause(ctx: Context<PauseToken>) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.paused = true;
+
+        let clock = Clock::get()?;
+
+        emit!(TokenPausedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            paused_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Resume minting and transfers (not allowed while the circuit breaker is tripped)
+    pub fn unpause(ctx: Context<SetPaused>) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        require!(!token_config.breaker_tripped, ErrorCode::CircuitBreakerTripped);
+        token_config.paused = false;
+
+        let clock = Clock::get()?;
+
+        emit!(TokenUnpausedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            unpaused_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the invariant circuit breaker and the signer set required to reset it
+    pub fn initialize_circuit_breaker(
+        ctx: Context<InitializeCircuitBreaker>,
+        supply_tolerance: u64,
+        reset_signers: Vec<Pubkey>,
+        reset_threshold: u8,
+    ) -> Result<()> {
+        require!(
+            !reset_signers.is_empty() && reset_signers.len() <= MAX_BREAKER_SIGNERS,
+            ErrorCode::InvalidBreakerConfig
+        );
+        require!(
+            reset_threshold > 0 && usize::from(reset_threshold) <= reset_signers.len(),
+            ErrorCode::InvalidBreakerConfig
+        );
+        for (i, signer) in reset_signers.iter().enumerate() {
+            require!(!reset_signers[..i].contains(signer), ErrorCode::InvalidBreakerConfig);
+        }
+
+        let circuit_breaker = &mut ctx.accounts.circuit_breaker;
+        circuit_breaker.token_mint = ctx.accounts.token_config.mint;
+        circuit_breaker.supply_tolerance = supply_tolerance;
+        circuit_breaker.reset_signers = reset_signers;
+        circuit_breaker.reset_threshold = reset_threshold;
+        circuit_breaker.last_trip_reason = None;
+        circuit_breaker.tripped_at = None;
+        circuit_breaker.trip_count = 0;
+        circuit_breaker.bump = ctx.bumps.circuit_breaker;
+
+        Ok(())
+    }
+
+    /// Evaluate supply, holder-count, and allowlist registry invariants.
+    /// Anyone may call this; a breach pauses the token until a threshold-signed reset.
+    /// Only a reset signer may supply an expected registry checksum.
+    pub fn check_invariants(
+        ctx: Context<CheckInvariants>,
+        expected_checksum: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let circuit_breaker = &mut ctx.accounts.circuit_breaker;
+        let token_config = &mut ctx.accounts.token_config;
+
+        if expected_checksum.is_some() {
+            require!(
+                circuit_breaker.reset_signers.contains(&ctx.accounts.caller.key()),
+                ErrorCode::UnauthorizedMonitor
+            );
+        }
+
+        if token_config.breaker_tripped {
+            return Ok(());
+        }
+
+        let observed_supply = token_config.split_adjusted(ctx.accounts.mint.supply)?;
+        let supply_drift = observed_supply.abs_diff(token_config.total_supply);
+
+        let reason = if supply_drift > circuit_breaker.supply_tolerance {
+            Some(BreakerTripReason::SupplyDrift)
+        } else if token_config.holder_count < 0 {
+            Some(BreakerTripReason::NegativeHolderCount)
+        } else if expected_checksum.is_some_and(|checksum| checksum != token_config.allowlist_checksum) {
+            Some(BreakerTripReason::RegistryChecksumMismatch)
+        } else {
+            None
+        };
+
+        if let Some(reason) = reason {
+            let clock = Clock::get()?;
+
+            token_config.paused = true;
+            token_config.breaker_tripped = true;
+            circuit_breaker.last_trip_reason = Some(reason);
+            circuit_breaker.tripped_at = Some(clock.unix_timestamp);
+            circuit_breaker.trip_count = circuit_breaker.trip_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+
+            emit!(CircuitBreakerTrippedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: token_config.next_event_sequence()?,
+                token_mint: token_config.mint,
+                reason,
+                recorded_supply: token_config.total_supply,
+                observed_supply,
+                holder_count: token_config.holder_count,
+                detected_by: ctx.accounts.caller.key(),
+                timestamp: clock.unix_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Clear a tripped circuit breaker and unpause the token.
+    /// Requires `reset_threshold` distinct reset signers passed as signing remaining accounts.
+    /// When `reconcile` is set, the recorded supply is resynced to the mint and a negative
+    /// holder count is clamped to zero.
+    pub fn reset_circuit_breaker(ctx: Context<ResetCircuitBreaker>, reconcile: bool) -> Result<()> {
+        let circuit_breaker = &ctx.accounts.circuit_breaker;
+        let token_config = &mut ctx.accounts.token_config;
+        require!(token_config.breaker_tripped, ErrorCode::CircuitBreakerNotTripped);
+
+        let mut approvals: Vec<Pubkey> = Vec::with_capacity(circuit_breaker.reset_signers.len());
+        for account in ctx.remaining_accounts.iter() {
+            if account.is_signer
+                && circuit_breaker.reset_signers.contains(account.key)
+                && !approvals.contains(account.key)
+            {
+                approvals.push(*account.key);
+            }
+        }
+        require!(
+            approvals.len() >= usize::from(circuit_breaker.reset_threshold),
+            ErrorCode::InsufficientResetSignatures
+        );
+
+        if reconcile {
+            token_config.total_supply = token_config.split_adjusted(ctx.accounts.mint.supply)?;
+            token_config.holder_count = token_config.holder_count.max(0);
+        }
+
+        token_config.breaker_tripped = false;
+        token_config.paused = false;
+
+        let clock = Clock::get()?;
+
+        emit!(CircuitBreakerResetEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            signers: approvals,
+            reconciled: reconcile,
+            total_supply: token_config.total_supply,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the registry of program-controlled destinations allowed to hold the token
+    pub fn initialize_destination_registry(ctx: Context<InitializeDestinationRegistry>) -> Result<()> {
+        let destination_registry = &mut ctx.accounts.destination_registry;
+        destination_registry.token_mint = ctx.accounts.token_config.mint;
+        destination_registry.approved_destinations = Vec::new();
+        destination_registry.bump = ctx.bumps.destination_registry;
+
+        Ok(())
+    }
+
+    /// Approve a program (or a specific program-derived owner) as a transfer destination
+    pub fn add_approved_destination(
+        ctx: Context<UpdateDestinationRegistry>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        require!(destination != system_program::ID, ErrorCode::InvalidDestination);
+
+        let destination_registry = &mut ctx.accounts.destination_registry;
+        require!(
+            !destination_registry.approved_destinations.contains(&destination),
+            ErrorCode::DestinationAlreadyApproved
+        );
+        require!(
+            destination_registry.approved_destinations.len() < MAX_APPROVED_DESTINATIONS,
+            ErrorCode::DestinationRegistryFull
+        );
+        destination_registry.approved_destinations.push(destination);
+
+        let clock = Clock::get()?;
+
+        emit!(DestinationApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: destination_registry.token_mint,
+            destination,
+            approved_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a previously approved destination
+    pub fn remove_approved_destination(
+        ctx: Context<UpdateDestinationRegistry>,
+        destination: Pubkey,
+    ) -> Result<()> {
+        let destination_registry = &mut ctx.accounts.destination_registry;
+        let position = destination_registry.approved_destinations
+            .iter()
+            .position(|approved| *approved == destination)
+            .ok_or(ErrorCode::DestinationNotFound)?;
+        destination_registry.approved_destinations.swap_remove(position);
+
+        let clock = Clock::get()?;
+
+        emit!(DestinationRemovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: destination_registry.token_mint,
+            destination,
+            removed_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the program-owned token account that collects transfer fees
+    pub fn initialize_fee_treasury(ctx: Context<InitializeFeeTreasury>) -> Result<()> {
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.treasury.key(),
+            ctx.accounts.authority.key(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Set the transfer fee in basis points (0 disables the fee)
+    pub fn set_transfer_fee(ctx: Context<UpdateTokenSettings>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= MAX_TRANSFER_FEE_BPS, ErrorCode::InvalidTransferFee);
+
+        let token_config = &mut ctx.accounts.token_config;
+        let old_fee_bps = token_config.transfer_fee_bps;
+        token_config.transfer_fee_bps = fee_bps;
+
+        let clock = Clock::get()?;
+
+        emit!(TransferFeeUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_fee_bps,
+            new_fee_bps: fee_bps,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw collected fees from the treasury to an approved wallet
+    pub fn withdraw_fees<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let destination_token_account = ctx.accounts.destination_token_account.key();
+
+        // The destination is screened like any other recipient
+        let destination_entry = &ctx.accounts.destination_allowlist_entry;
+        require!(destination_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            destination_entry.permits_token_account(&destination_token_account),
+            ErrorCode::TokenAccountNotApproved
+        );
+        enforce_country_rule(destination_entry, ctx.accounts.destination_country_rule.as_deref())?;
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.destination_sanctions_flag.as_ref(),
+                &destination_entry.wallet,
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        // Fees are issuer-held, so paying them out is treated like an issuance
+        let is_new_holder = ctx.accounts.destination_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+
+        // Record the destination's record-date balance before it moves
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.destination_allowlist_entry,
+            ctx.accounts.destination_snapshot_balance.as_deref_mut(),
+            ctx.bumps.destination_snapshot_balance,
+            ctx.accounts.destination_token_account.amount,
+        )?;
+        let destination_balance = ctx.accounts.destination_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.destination_holder_number.as_deref(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((
+                &mut ctx.accounts.destination_allowlist_entry,
+                &destination_token_account,
+                destination_balance,
+            )),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.destination_allowlist_entry,
+            &destination_token_account,
+            ctx.accounts.destination_holder_page.as_ref(),
+            destination_balance,
+        )?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"treasury", mint_key.as_ref(), &[ctx.bumps.treasury]];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.fee_treasury.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.destination_token_account.to_account_info(),
+            authority: ctx.accounts.treasury.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if is_new_holder {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TransferFeesWithdrawnEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            destination: ctx.accounts.destination.key(),
+            amount,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Onboard an investor atomically: approve the wallet, create its associated token
+    /// account, apply an optional lockup, and optionally mint an initial allocation.
+    /// Rent is paid by `sponsor`, so the investor needs no SOL.
+    pub fn onboard_investor(
+        ctx: Context<OnboardInvestor>,
+        lockup_until: Option<i64>,
+        initial_amount: Option<u64>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        let clock = Clock::get()?;
+        if let Some(lockup_until) = lockup_until {
+            require!(lockup_until > clock.unix_timestamp, ErrorCode::InvalidLockup);
+        }
+
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        allowlist_entry.wallet = ctx.accounts.investor.key();
+        allowlist_entry.is_approved = true;
+        allowlist_entry.approved_at = clock.unix_timestamp;
+        allowlist_entry.lockup_until = lockup_until;
+        allowlist_entry.transfer_locked = false;
+        allowlist_entry.is_insider = false;
+        allowlist_entry.approved_token_account = None;
+        allowlist_entry.country_code = UNASSIGNED_COUNTRY;
+        allowlist_entry.last_snapshot_id = 0;
+        allowlist_entry.holder_page = None;
+        allowlist_entry.allocated_through = 0;
+        allowlist_entry.drip_enabled = false;
+        allowlist_entry.interest_index = ctx.accounts.token_config.interest_index;
+        allowlist_entry.accrued_interest = 0;
+        allowlist_entry.version = AllowlistEntry::CURRENT_VERSION;
+        allowlist_entry.holder_indexed = false;
+        allowlist_entry.bump = ctx.bumps.allowlist_entry;
+
+        let token_config = &mut ctx.accounts.token_config;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &allowlist_entry.wallet);
+
+        emit!(WalletApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            wallet: ctx.accounts.investor.key(),
+            approved_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        let initial_amount = initial_amount.unwrap_or(0);
+        if initial_amount > 0 {
+            ctx.accounts.token_config.validate_lot(initial_amount)?;
+            let is_new_holder = ctx.accounts.investor_token_account.amount == 0;
+            enforce_share_class(
+                &ctx.accounts.token_config,
+                ctx.accounts.share_class.as_ref(),
+                i64::from(is_new_holder),
+                false,
+            )?;
+
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.allowlist_entry,
+                ctx.accounts.snapshot_balance.as_deref_mut(),
+                ctx.bumps.snapshot_balance,
+                ctx.accounts.investor_token_account.amount,
+            )?;
+            let investor_balance = ctx.accounts.investor_token_account.amount.checked_add(initial_amount)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: None,
+                    last_number: None,
+                    joining_number: ctx.accounts.holder_number.as_deref(),
+                    payer: &ctx.accounts.sponsor.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                None,
+                Some((&mut ctx.accounts.allowlist_entry, &ctx.accounts.investor_token_account.key(), investor_balance)),
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.allowlist_entry,
+                &ctx.accounts.investor_token_account.key(),
+                ctx.accounts.holder_page.as_ref(),
+                investor_balance,
+            )?;
+
+            mint_with_program_authority(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                ctx.accounts.investor_token_account.to_account_info(),
+                &ctx.accounts.mint_authority,
+                ctx.bumps.mint_authority,
+                initial_amount,
+            )?;
+
+            let token_config = &mut ctx.accounts.token_config;
+            record_minted_supply(token_config, initial_amount)?;
+            if is_new_holder {
+                token_config.holder_count = token_config.holder_count.checked_add(1)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+
+            emit!(TokensMintedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: token_config.next_event_sequence()?,
+                token_mint: ctx.accounts.mint.key(),
+                recipient: ctx.accounts.investor.key(),
+                amount: initial_amount,
+                new_supply: token_config.total_supply,
+            });
+        }
+
+        emit!(InvestorOnboardedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            investor: ctx.accounts.investor.key(),
+            token_account: ctx.accounts.investor_token_account.key(),
+            sponsor: ctx.accounts.sponsor.key(),
+            lockup_until,
+            initial_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Mark a wallet as soulbound (cannot send) or release it. Locked wallets can still
+    /// receive mints and distributions.
+    pub fn set_transfer_locked(ctx: Context<UpdateAllowlistEntry>, locked: bool) -> Result<()> {
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        allowlist_entry.transfer_locked = locked;
+
+        let clock = Clock::get()?;
+
+        emit!(TransferLockUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            wallet: allowlist_entry.wallet,
+            transfer_locked: locked,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze a token account through the program's freeze authority PDA, for compliance
+    /// holds such as court orders. Frozen accounts can neither send nor receive.
+    pub fn freeze_token_account(ctx: Context<SetTokenAccountFrozen>) -> Result<()> {
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"freeze_authority", mint_key.as_ref(), &[ctx.bumps.freeze_authority]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = token::FreezeAccount {
+            account: ctx.accounts.token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: ctx.accounts.freeze_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::freeze_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+
+        let clock = Clock::get()?;
+
+        emit!(TokenAccountFreezeChangedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            token_account: ctx.accounts.token_account.key(),
+            owner: ctx.accounts.token_account.owner,
+            frozen: true,
+            updated_by: ctx.accounts.compliance_officer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a compliance freeze placed by `freeze_token_account`
+    pub fn thaw_token_account(ctx: Context<SetTokenAccountFrozen>) -> Result<()> {
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"freeze_authority", mint_key.as_ref(), &[ctx.bumps.freeze_authority]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = token::ThawAccount {
+            account: ctx.accounts.token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: ctx.accounts.freeze_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::thaw_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+
+        let clock = Clock::get()?;
+
+        emit!(TokenAccountFreezeChangedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            token_account: ctx.accounts.token_account.key(),
+            owner: ctx.accounts.token_account.owner,
+            frozen: false,
+            updated_by: ctx.accounts.compliance_officer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Move `amount` shares out of a holder's account without their signature, e.g. to
+    /// carry out a court order or recover a lost wallet. Signed for by the mint-authority
+    /// PDA as the mint's permanent delegate, so only Token-2022 mints created by
+    /// `initialize_token` support it, and only while `FORCED_TRANSFERS` is set. The holder
+    /// is not screened; the recipient is screened like any other. Timelocked when a delay
+    /// is set. Hooked mints take the hook's accounts as remaining accounts.
+    pub fn force_transfer<'info>(
+        ctx: Context<'_, '_, '_, 'info, ForceTransfer<'info>>,
+        amount: u64,
+        reason_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(
+            ctx.accounts.token_config.features.contains(FeatureFlags::FORCED_TRANSFERS),
+            ErrorCode::ForcedTransfersDisabled
+        );
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+        require!(
+            is_permanent_delegate(&ctx.accounts.mint, &ctx.accounts.mint_authority.key())?,
+            ErrorCode::ForcedTransferUnsupported
+        );
+        ctx.accounts.token_config.validate_lot(amount)?;
+        let from_token_account = ctx.accounts.from_token_account.key();
+        let to_token_account = ctx.accounts.to_token_account.key();
+        require_keys_neq!(from_token_account, to_token_account, ErrorCode::InvalidForcedTransfer);
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&to_token_account),
+            ErrorCode::TokenAccountNotApproved
+        );
+        enforce_country_rule(recipient_entry, ctx.accounts.recipient_country_rule.as_deref())?;
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.recipient_sanctions_flag.as_ref(),
+                &recipient_entry.wallet,
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        // A forced transfer ignores the class's transferability but not its holder cap
+        let joins = i64::from(ctx.accounts.to_token_account.amount == 0);
+        let leaves = i64::from(ctx.accounts.from_token_account.amount == amount);
+        let holder_delta = joins - leaves;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            holder_delta,
+            false,
+        )?;
+
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.from_token_account.amount,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.to_token_account.amount,
+        )?;
+
+        let holder_balance = ctx.accounts.from_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::InsufficientBalance)?;
+        let recipient_balance = ctx.accounts.to_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        if let (Some(holder_page), Some(recipient_page)) =
+            (&ctx.accounts.holder_page, &ctx.accounts.recipient_holder_page)
+        {
+            require_keys_neq!(holder_page.key(), recipient_page.key(), ErrorCode::InvalidHolderPage);
+        }
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((
+                &mut ctx.accounts.holder_allowlist_entry,
+                &from_token_account,
+                holder_balance,
+            )),
+            Some((
+                &mut ctx.accounts.recipient_allowlist_entry,
+                &to_token_account,
+                recipient_balance,
+            )),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &from_token_account,
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+        // Both wallets on one page: the page is passed once, as the holder's
+        let recipient_page = match ctx.accounts.recipient_holder_page.as_ref() {
+            Some(page) => Some(page),
+            None => ctx.accounts.holder_page.as_ref(),
+        };
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &to_token_account,
+            recipient_page,
+            recipient_balance,
+        )?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"mint_authority", mint_key.as_ref(), &[ctx.bumps.mint_authority]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: ctx.accounts.mint_authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        if holder_delta != 0 {
+            token_config.holder_count = token_config.holder_count.checked_add(holder_delta)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let clock = Clock::get()?;
+
+        emit!(ForcedTransferEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            from: ctx.accounts.holder.key(),
+            to: ctx.accounts.recipient.key(),
+            amount,
+            reason_hash,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permanently renounce the mint's freeze authority. Accounts frozen at that moment
+    /// stay frozen forever, so thaw any holds first.
+    pub fn disable_freezing(ctx: Context<DisableFreezing>) -> Result<()> {
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+
+        let mint_key = ctx.accounts.mint.key();
+        let seeds: &[&[u8]] = &[b"freeze_authority", mint_key.as_ref(), &[ctx.bumps.freeze_authority]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = token::SetAuthority {
+            current_authority: ctx.accounts.freeze_authority.to_account_info(),
+            account_or_mint: ctx.accounts.mint.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::set_authority(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
+            token::spl_token_2022::instruction::AuthorityType::FreezeAccount,
+            None,
+        )?;
+
+        let clock = Clock::get()?;
+
+        emit!(FreezeAuthorityChangedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            old_freeze_authority: Some(ctx.accounts.freeze_authority.key()),
+            new_freeze_authority: None,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Retire a token once nothing is left in circulation. Shares still held in the issuer
+    /// treasury (e.g. after a buyout or full redemption) are burned and the treasury
+    /// account closed. Allowlist entries passed as writable remaining accounts are closed
+    /// too, and all rent returns to the authority.
+    pub fn close_token_config<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseTokenConfig<'info>>,
+    ) -> Result<()> {
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+        let mint_key = ctx.accounts.mint.key();
+        let authority_info = ctx.accounts.authority.to_account_info();
+        let mut rent_reclaimed = ctx.accounts.token_config.to_account_info().lamports();
+        let mut accounts_closed: u32 = 1;
+
+        match (&ctx.accounts.treasury, &ctx.accounts.treasury_token_account) {
+            (Some(treasury), Some(treasury_token_account)) => {
+                require_keys_eq!(treasury_token_account.owner, treasury.key(), ErrorCode::InvalidTreasuryAccount);
+                require!(
+                    ctx.accounts.mint.supply == treasury_token_account.amount,
+                    ErrorCode::SupplyOutstanding
+                );
+
+                let treasury_bump = ctx.bumps.treasury.ok_or(ErrorCode::InvalidTreasuryAccount)?;
+                let seeds: &[&[u8]] = &[b"treasury", mint_key.as_ref(), &[treasury_bump]];
+                let signer_seeds = &[seeds];
+                let cpi_program = ctx.accounts.token_program.to_account_info();
+                if treasury_token_account.amount > 0 {
+                    let cpi_accounts = token::Burn {
+                        mint: ctx.accounts.mint.to_account_info(),
+                        from: treasury_token_account.to_account_info(),
+                        authority: treasury.to_account_info(),
+                    };
+                    token::burn(
+                        CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds),
+                        treasury_token_account.amount,
+                    )?;
+                }
+
+                rent_reclaimed = rent_reclaimed.saturating_add(treasury_token_account.to_account_info().lamports());
+                let cpi_accounts = token::CloseAccount {
+                    account: treasury_token_account.to_account_info(),
+                    destination: authority_info.clone(),
+                    authority: treasury.to_account_info(),
+                };
+                token::close_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))?;
+                accounts_closed += 1;
+            }
+            (None, None) => require!(ctx.accounts.mint.supply == 0, ErrorCode::SupplyOutstanding),
+            _ => return err!(ErrorCode::InvalidTreasuryAccount),
+        }
+
+        for info in ctx.remaining_accounts.iter() {
+            require!(info.is_writable, ErrorCode::InvalidAllowlistAccount);
+            let entry: Account<AllowlistEntry> = Account::try_from(info)?;
+            let expected_entry = Pubkey::create_program_address(
+                &[b"allowlist", mint_key.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidAllowlistAccount)?;
+            require_keys_eq!(expected_entry, entry.key(), ErrorCode::InvalidAllowlistAccount);
+
+            rent_reclaimed = rent_reclaimed.saturating_add(info.lamports());
+            entry.close(authority_info.clone())?;
+            accounts_closed += 1;
+        }
+
+        match ctx.accounts.issuer.as_mut() {
+            Some(issuer) => issuer.mints.retain(|mint| *mint != mint_key),
+            None => require!(
+                ctx.accounts.token_config.issuer == Pubkey::default(),
+                ErrorCode::InvalidIssuerAccounts
+            ),
+        }
+
+        let clock = Clock::get()?;
+
+        emit!(TokenClosedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            accounts_closed,
+            rent_reclaimed,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Declare a trading blackout window
+    pub fn create_blackout_window(
+        ctx: Context<CreateBlackoutWindow>,
+        start_ts: i64,
+        end_ts: i64,
+        scope: BlackoutScope,
+    ) -> Result<()> {
+        require!(end_ts > start_ts, ErrorCode::InvalidBlackoutWindow);
+        let clock = Clock::get()?;
+        require!(end_ts > clock.unix_timestamp, ErrorCode::InvalidBlackoutWindow);
+
+        let token_config = &mut ctx.accounts.token_config;
+        require!(
+            usize::from(token_config.open_blackout_windows) < MAX_OPEN_BLACKOUT_WINDOWS,
+            ErrorCode::TooManyBlackoutWindows
+        );
+
+        let blackout_window = &mut ctx.accounts.blackout_window;
+        blackout_window.token_mint = token_config.mint;
+        blackout_window.window_id = token_config.next_blackout_id;
+        blackout_window.start_ts = start_ts;
+        blackout_window.end_ts = end_ts;
+        blackout_window.scope = scope;
+        blackout_window.created_by = ctx.accounts.authority.key();
+        blackout_window.bump = ctx.bumps.blackout_window;
+
+        token_config.next_blackout_id = token_config.next_blackout_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        token_config.open_blackout_windows += 1;
+
+        emit!(BlackoutWindowCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            window_id: blackout_window.window_id,
+            start_ts,
+            end_ts,
+            scope,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a blackout window (or clean up an elapsed one), reclaiming its rent
+    pub fn cancel_blackout_window(ctx: Context<CancelBlackoutWindow>) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.open_blackout_windows = token_config.open_blackout_windows
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let clock = Clock::get()?;
+
+        emit!(BlackoutWindowCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            window_id: ctx.accounts.blackout_window.window_id,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close compliance accounts that no longer do anything and return their rent to the
+    /// authority. Remaining accounts are writable and may be any mix of:
+    /// - an ended `BlackoutWindow`;
+    /// - an `AllowlistEntry` revoked at least `STALE_ENTRY_RETENTION_SECONDS` ago, out of
+    ///   the cap table and owed no interest, followed by the wallet's token account (its
+    ///   bound account, if it has one), which must be empty;
+    /// - a `VestingSchedule` that has released everything it will ever vest, or an
+    ///   expired `AirdropCampaign`, each followed by its vault and the vault owner's
+    ///   allowlist entry. A vault still open must be empty and needs `token_program`; an
+    ///   entry that was never created is skipped.
+    /// Anything else fails the whole sweep. Escrows, swap offers, streams, option grants,
+    /// warrants, mint schedules and queued operations close themselves when they settle
+    /// or are cancelled, so they are not listed.
+    pub fn reclaim_stale_accounts<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReclaimStaleAccounts<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.token_config.require_current_version()?;
+        require!(!ctx.remaining_accounts.is_empty(), ErrorCode::AccountNotReclaimable);
+        let mint_key = ctx.accounts.token_config.mint;
+        let authority_info = ctx.accounts.authority.to_account_info();
+        let now = Clock::get()?.unix_timestamp;
+        let mut rent_reclaimed: u64 = 0;
+        let mut accounts_closed: u32 = 0;
+
+        let mut accounts = ctx.remaining_accounts.iter();
+        while let Some(info) = accounts.next() {
+            require!(info.is_writable, ErrorCode::AccountNotReclaimable);
+            let (is_entry, is_vesting, is_campaign) = {
+                let data = info.try_borrow_data()?;
+                (
+                    data.starts_with(AllowlistEntry::DISCRIMINATOR),
+                    data.starts_with(VestingSchedule::DISCRIMINATOR),
+                    data.starts_with(AirdropCampaign::DISCRIMINATOR),
+                )
+            };
+            let mut lamports = info.lamports();
+
+            if is_entry {
+                let entry: Account<AllowlistEntry> = Account::try_from(info)?;
+                let expected_entry = Pubkey::create_program_address(
+                    &[b"allowlist", mint_key.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                    &crate::ID,
+                ).map_err(|_| ErrorCode::InvalidAllowlistAccount)?;
+                require_keys_eq!(expected_entry, entry.key(), ErrorCode::InvalidAllowlistAccount);
+
+                let revoked_long_ago = entry.revoked_at
+                    .and_then(|revoked_at| revoked_at.checked_add(STALE_ENTRY_RETENTION_SECONDS))
+                    .is_some_and(|stale_at| now >= stale_at);
+                require!(
+                    !entry.is_approved
+                        && revoked_long_ago
+                        && entry.holder_page.is_none()
+                        && !entry.holder_indexed
+                        && entry.accrued_interest == 0,
+                    ErrorCode::AccountNotReclaimable
+                );
+
+                let token_account_info = accounts.next().ok_or(ErrorCode::AccountNotReclaimable)?;
+                let token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(token_account_info)?;
+                require!(
+                    token_account.mint == mint_key
+                        && token_account.owner == entry.wallet
+                        && entry.permits_token_account(&token_account.key())
+                        && token_account.amount == 0,
+                    ErrorCode::AccountNotReclaimable
+                );
+
+                entry.close(authority_info.clone())?;
+            } else if is_vesting || is_campaign {
+                let (owner_seed, vault_seed, id, bump, finished) = if is_vesting {
+                    let schedule: Account<VestingSchedule> = Account::try_from(info)?;
+                    require_keys_eq!(schedule.token_mint, mint_key, ErrorCode::AccountNotReclaimable);
+                    let finished = schedule.released_amount == schedule.total_amount;
+                    (&b"vesting"[..], &b"vesting_vault"[..], schedule.vesting_id, schedule.bump, finished)
+                } else {
+                    let campaign: Account<AirdropCampaign> = Account::try_from(info)?;
+                    require_keys_eq!(campaign.token_mint, mint_key, ErrorCode::AccountNotReclaimable);
+                    let finished = now > campaign.expires_at;
+                    (&b"airdrop"[..], &b"airdrop_vault"[..], campaign.campaign_id, campaign.bump, finished)
+                };
+                require!(finished, ErrorCode::AccountNotReclaimable);
+                let id_bytes = id.to_le_bytes();
+                let seeds: &[&[u8]] = &[owner_seed, mint_key.as_ref(), &id_bytes, &[bump]];
+                let expected_owner = Pubkey::create_program_address(seeds, &crate::ID)
+                    .map_err(|_| ErrorCode::AccountNotReclaimable)?;
+                require_keys_eq!(expected_owner, info.key(), ErrorCode::AccountNotReclaimable);
+
+                let vault_info = accounts.next().ok_or(ErrorCode::AccountNotReclaimable)?;
+                let entry_info = accounts.next().ok_or(ErrorCode::AccountNotReclaimable)?;
+                lamports = lamports
+                    .saturating_add(vault_info.lamports())
+                    .saturating_add(entry_info.lamports());
+                accounts_closed += reclaim_vault_of(
+                    &mut ctx.accounts.token_config,
+                    info,
+                    &[seeds],
+                    vault_seed,
+                    vault_info,
+                    entry_info,
+                    ctx.accounts.token_program.as_ref(),
+                    &authority_info,
+                )?;
+                if is_vesting {
+                    Account::<VestingSchedule>::try_from(info)?.close(authority_info.clone())?;
+                } else {
+                    Account::<AirdropCampaign>::try_from(info)?.close(authority_info.clone())?;
+                }
+            } else {
+                let window: Account<BlackoutWindow> = Account::try_from(info)?;
+                let expected_window = Pubkey::create_program_address(
+                    &[b"blackout", mint_key.as_ref(), &window.window_id.to_le_bytes(), &[window.bump]],
+                    &crate::ID,
+                ).map_err(|_| ErrorCode::InvalidBlackoutWindow)?;
+                require_keys_eq!(expected_window, window.key(), ErrorCode::InvalidBlackoutWindow);
+                require!(now >= window.end_ts, ErrorCode::AccountNotReclaimable);
+
+                let token_config = &mut ctx.accounts.token_config;
+                token_config.open_blackout_windows = token_config.open_blackout_windows
+                    .checked_sub(1)
+                    .ok_or(ErrorCode::Overflow)?;
+                window.close(authority_info.clone())?;
+            }
+
+            rent_reclaimed = rent_reclaimed.saturating_add(lamports);
+            accounts_closed += 1;
+        }
+
+        emit!(StaleAccountsReclaimedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            accounts_closed,
+            rent_reclaimed,
+            authority: ctx.accounts.authority.key(),
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a token config written under an older layout to the current one.
+    /// Permissionless: the upgrade only fills in defaults for fields added since.
+    pub fn migrate_token_config(ctx: Context<MigrateTokenConfig>) -> Result<()> {
+        let token_config = ctx.accounts.token_config.to_account_info();
+        let from_version = migrate_account::<TokenConfig>(
+            &token_config,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        // Only readable as a typed account once it has the current layout
+        let sequence = {
+            let mut data = token_config.try_borrow_mut_data()?;
+            let mut migrated = TokenConfig::try_deserialize(&mut &data[..])?;
+            if from_version < 6 {
+                // Keep the rules the token ran under before they moved into `features`
+                migrated.features = FeatureFlags::DEFAULT;
+                migrated.features.set(FeatureFlags::MEMO_REQUIRED, migrated.require_memo);
+            }
+            let sequence = migrated.next_event_sequence()?;
+            migrated.try_serialize(&mut &mut data[..])?;
+            sequence
+        };
+
+        emit!(AccountMigratedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence,
+            token_mint: ctx.accounts.mint.key(),
+            account: token_config.key(),
+            from_version,
+            to_version: TokenConfig::CURRENT_VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Shrink (or grow) a token config to the exact size of its strings. Configs created
+    /// before space was computed from the strings carry fixed symbol and name budgets;
+    /// trimming them refunds the surplus rent to the authority.
+    pub fn resize_token_config(ctx: Context<ResizeTokenConfig>) -> Result<()> {
+        ctx.accounts.token_config.require_current_version()?;
+        Ok(())
+    }
+
+    /// Upgrade an allowlist entry written under an older layout to the current one.
+    /// Permissionless: the upgrade only fills in defaults for fields added since.
+    pub fn migrate_allowlist_entry(ctx: Context<MigrateAllowlistEntry>) -> Result<()> {
+        let allowlist_entry = ctx.accounts.allowlist_entry.to_account_info();
+        let from_version = migrate_account::<AllowlistEntry>(
+            &allowlist_entry,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        emit!(AccountMigratedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            account: allowlist_entry.key(),
+            from_version,
+            to_version: AllowlistEntry::CURRENT_VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Designate (or clear) a wallet as an insider subject to insider blackout windows
+    pub fn set_insider(ctx: Context<UpdateAllowlistEntry>, is_insider: bool) -> Result<()> {
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        allowlist_entry.is_insider = is_insider;
+
+        let clock = Clock::get()?;
+
+        emit!(InsiderStatusUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            wallet: allowlist_entry.wallet,
+            is_insider,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the maximum amount a wallet may send per 24h epoch (0 disables the limit)
+    pub fn set_daily_transfer_limit(ctx: Context<UpdateTokenSettings>, limit: u64) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_limit = token_config.daily_transfer_limit;
+        token_config.daily_transfer_limit = limit;
+
+        let clock = Clock::get()?;
+
+        emit!(DailyTransferLimitUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_limit,
+            new_limit: limit,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the hard supply cap, or set one on an uncapped token. The cap can never be
+    /// raised, nor set below the supply already issued.
+    pub fn set_max_supply(ctx: Context<UpdateTokenSettings>, max_supply: u64) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_max_supply = token_config.max_supply;
+        require!(
+            max_supply >= token_config.total_supply
+                && old_max_supply.is_none_or(|cap| max_supply < cap),
+            ErrorCode::InvalidMaxSupply
+        );
+        token_config.max_supply = Some(max_supply);
+
+        let clock = Clock::get()?;
+
+        emit!(MaxSupplyUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_max_supply,
+            new_max_supply: max_supply,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a new authority. Control only moves once the proposed key signs
+    /// `accept_authority`, so a mistyped key cannot take over (or brick) the token.
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Pubkey) -> Result<()> {
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+        let token_config = &mut ctx.accounts.token_config;
+        require!(
+            new_authority != Pubkey::default() && new_authority != token_config.authority,
+            ErrorCode::InvalidAuthorityProposal
+        );
+        token_config.pending_authority = new_authority;
+
+        let clock = Clock::get()?;
+
+        emit!(AuthorityProposedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            authority: token_config.authority,
+            pending_authority: new_authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a pending authority proposal
+    pub fn cancel_authority_proposal(ctx: Context<UpdateTokenSettings>) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        require!(
+            token_config.pending_authority != Pubkey::default(),
+            ErrorCode::NoPendingAuthority
+        );
+        let pending_authority = token_config.pending_authority;
+        token_config.pending_authority = Pubkey::default();
+
+        let clock = Clock::get()?;
+
+        emit!(AuthorityProposalCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            authority: token_config.authority,
+            pending_authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Accept a pending proposal, completing the handover. The outgoing authority co-signs.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_authority = token_config.authority;
+        token_config.authority = token_config.pending_authority;
+        token_config.pending_authority = Pubkey::default();
+
+        let clock = Clock::get()?;
+
+        emit!(AuthorityTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_authority,
+            new_authority: token_config.authority,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the guardian key, such as a monitoring service, that can pause the token in
+    /// an emergency without holding any other power. `Pubkey::default()` removes it.
+    pub fn set_guardian(ctx: Context<UpdateTokenSettings>, guardian: Pubkey) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_guardian = token_config.guardian;
+        token_config.guardian = guardian;
+
+        let clock = Clock::get()?;
+
+        emit!(GuardianUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_guardian,
+            new_guardian: guardian,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Assign the compliance officer (MLRO) key that signs off on compliance digests
+    pub fn set_compliance_officer(ctx: Context<UpdateTokenSettings>, compliance_officer: Pubkey) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_officer = token_config.compliance_officer;
+        token_config.compliance_officer = compliance_officer;
+
+        let clock = Clock::get()?;
+
+        emit!(ComplianceOfficerUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_officer,
+            new_officer: compliance_officer,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the on-chain record of a legal entity that issues several gated tokens.
+    /// Its compliance officer starts as the issuer authority.
+    pub fn create_issuer(ctx: Context<CreateIssuer>, name: String) -> Result<()> {
+        require!(name.len() >= 2 && name.len() <= MAX_ISSUER_NAME_LEN, ErrorCode::InvalidName);
+
+        let issuer = &mut ctx.accounts.issuer;
+        issuer.authority = ctx.accounts.authority.key();
+        issuer.compliance_officer = ctx.accounts.authority.key();
+        issuer.name = name;
+        issuer.mints = Vec::new();
+        issuer.bump = ctx.bumps.issuer;
+
+        let clock = Clock::get()?;
+
+        emit!(IssuerCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            issuer: issuer.key(),
+            authority: issuer.authority,
+            name: issuer.name.clone(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register a token under its issuer, listing the mint on the issuer account and
+    /// adopting the issuer's compliance officer. A token split onto a new mint must be
+    /// registered again.
+    pub fn register_issuer_token(ctx: Context<RegisterIssuerToken>) -> Result<()> {
+        let issuer = &mut ctx.accounts.issuer;
+        require!(issuer.mints.len() < MAX_ISSUER_TOKENS, ErrorCode::IssuerTokenLimitReached);
+        issuer.mints.push(ctx.accounts.token_config.mint);
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.issuer = issuer.key();
+        token_config.compliance_officer = issuer.compliance_officer;
+
+        let clock = Clock::get()?;
+
+        emit!(IssuerTokenRegisteredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            issuer: issuer.key(),
+            token_mint: token_config.mint,
+            token_count: issuer.mints.len() as u32,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the compliance officer shared by every token of an issuer. All of the
+    /// issuer's `TokenConfig`s must be passed, writable, as remaining accounts.
+    pub fn set_issuer_compliance_officer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateIssuer<'info>>,
+        compliance_officer: Pubkey,
+    ) -> Result<()> {
+        let issuer = &mut ctx.accounts.issuer;
+        require!(
+            ctx.remaining_accounts.len() == issuer.mints.len(),
+            ErrorCode::InvalidIssuerAccounts
+        );
+
+        let issuer_key = issuer.key();
+        for (info, mint) in ctx.remaining_accounts.iter().zip(issuer.mints.iter()) {
+            require!(info.is_writable, ErrorCode::InvalidIssuerAccounts);
+            let mut token_config: Account<TokenConfig> = Account::try_from(info)?;
+            require_keys_eq!(token_config.mint, *mint, ErrorCode::InvalidIssuerAccounts);
+            require_keys_eq!(token_config.issuer, issuer_key, ErrorCode::InvalidIssuerAccounts);
+            let expected_config = Pubkey::create_program_address(
+                &[b"token_config", mint.as_ref(), &[token_config.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidIssuerAccounts)?;
+            require_keys_eq!(expected_config, token_config.key(), ErrorCode::InvalidIssuerAccounts);
+
+            token_config.compliance_officer = compliance_officer;
+            token_config.exit(&crate::ID)?;
+        }
+
+        let old_officer = issuer.compliance_officer;
+        issuer.compliance_officer = compliance_officer;
+
+        let clock = Clock::get()?;
+
+        emit!(IssuerComplianceOfficerUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            issuer: issuer_key,
+            old_officer,
+            new_officer: compliance_officer,
+            tokens_updated: issuer.mints.len() as u32,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Anchor the hash of an off-chain compliance digest (whale alerts, accumulation
+    /// breaches) for a reporting period. Must be signed by the compliance officer.
+    pub fn post_digest(
+        ctx: Context<PostDigest>,
+        period_start: i64,
+        period_end: i64,
+        digest_hash: [u8; 32],
+        alert_count: u32,
+    ) -> Result<()> {
+        require!(period_end > period_start, ErrorCode::InvalidDigestPeriod);
+        let clock = Clock::get()?;
+        require!(period_end <= clock.unix_timestamp, ErrorCode::InvalidDigestPeriod);
+
+        let compliance_digest = &mut ctx.accounts.compliance_digest;
+        compliance_digest.token_mint = ctx.accounts.token_config.mint;
+        compliance_digest.period_start = period_start;
+        compliance_digest.period_end = period_end;
+        compliance_digest.digest_hash = digest_hash;
+        compliance_digest.alert_count = alert_count;
+        compliance_digest.signed_by = ctx.accounts.compliance_officer.key();
+        compliance_digest.posted_at = clock.unix_timestamp;
+        compliance_digest.bump = ctx.bumps.compliance_digest;
+
+        emit!(ComplianceDigestPostedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: compliance_digest.token_mint,
+            period_start,
+            period_end,
+            digest_hash,
+            alert_count,
+            signed_by: compliance_digest.signed_by,
+        });
+
+        Ok(())
+    }
+
+    /// Set the minimum transfer/mint amount and the lot size amounts must be multiples of,
+    /// both in raw base units of the token's own decimals
+    pub fn set_lot_rules(
+        ctx: Context<UpdateTokenSettings>,
+        min_transfer_amount: u64,
+        lot_size: u64,
+    ) -> Result<()> {
+        require!(lot_size > 0, ErrorCode::InvalidLotSize);
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.min_transfer_amount = min_transfer_amount;
+        token_config.lot_size = lot_size;
+
+        let clock = Clock::get()?;
+
+        emit!(LotRulesUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            min_transfer_amount,
+            lot_size,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Restrict mints and transfers to whole shares (a lot of `10^decimals` base units),
+    /// or lift the restriction back to single base units
+    pub fn set_whole_shares_only(ctx: Context<UpdateTokenSettings>, enabled: bool) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let lot_size = if enabled { token_config.whole_share_unit()? } else { 1 };
+        token_config.lot_size = lot_size;
+
+        let clock = Clock::get()?;
+
+        emit!(LotRulesUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            min_transfer_amount: token_config.min_transfer_amount,
+            lot_size,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require every `gated_transfer` to carry the 32-byte hash of its travel-rule
+    /// (originator/beneficiary) payload, recorded in the transfer event
+    pub fn set_require_memo(ctx: Context<UpdateTokenSettings>, enabled: bool) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        token_config.features.set(FeatureFlags::MEMO_REQUIRED, enabled);
+
+        let clock = Clock::get()?;
+
+        emit!(RequireMemoUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            enabled,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the deployment's `ProgramConfig`, once, right after deploying. Only the
+    /// program's upgrade authority may do so, and it becomes the protocol admin.
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        parameters: ProtocolParameters,
+    ) -> Result<()> {
+        parameters.validate()?;
+        let program_config = &mut ctx.accounts.program_config;
+        program_config.admin = ctx.accounts.admin.key();
+        program_config.parameters = parameters;
+        program_config.paused = false;
+        program_config.bump = ctx.bumps.program_config;
+
+        emit!(ProgramConfigUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            admin: program_config.admin,
+            parameters,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the protocol parameters. Defaults only reach tokens created afterwards.
+    pub fn update_program_config(
+        ctx: Context<UpdateProgramConfig>,
+        parameters: ProtocolParameters,
+    ) -> Result<()> {
+        parameters.validate()?;
+        let program_config = &mut ctx.accounts.program_config;
+        program_config.parameters = parameters;
+
+        emit!(ProgramConfigUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            admin: program_config.admin,
+            parameters,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Halt (or resume) token creation, issuance and transfers across every token in
+    /// the deployment, independent of each token's own pause. Every path that mints,
+    /// burns or moves shares or payment tokens stops, except cancellations and sweeps
+    /// that empty an escrow back out to its parties (`cancel_escrow`,
+    /// `cancel_swap_offer`, `cancel_stream`, `sweep_airdrop`, `sweep_distribution`), so
+    /// a pause never strands assets in program vaults.
+    pub fn set_program_paused(ctx: Context<UpdateProgramConfig>, paused: bool) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        program_config.paused = paused;
+
+        emit!(ProgramPausedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            paused,
+            admin: program_config.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Hand the protocol admin role to `new_admin`, who signs alongside the current admin
+    pub fn transfer_program_admin(ctx: Context<TransferProgramAdmin>) -> Result<()> {
+        let program_config = &mut ctx.accounts.program_config;
+        let old_admin = program_config.admin;
+        program_config.admin = ctx.accounts.new_admin.key();
+
+        emit!(ProgramAdminTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            old_admin,
+            new_admin: program_config.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the program-wide registry of issued tokens (once per deployment, anyone may pay)
+    pub fn initialize_token_registry(ctx: Context<InitializeTokenRegistry>) -> Result<()> {
+        let token_registry = &mut ctx.accounts.token_registry;
+        token_registry.total_tokens = 0;
+        token_registry.bump = ctx.bumps.token_registry;
+
+        Ok(())
+    }
+
+    /// Bind a wallet's approval to one specific token account (e.g. a segregated custody
+    /// account), or clear the binding with `None` so any account owned by the wallet qualifies
+    pub fn bind_token_account(
+        ctx: Context<UpdateAllowlistEntry>,
+        token_account: Option<Pubkey>,
+    ) -> Result<()> {
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        allowlist_entry.approved_token_account = token_account;
+
+        let clock = Clock::get()?;
+
+        emit!(TokenAccountBoundEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            wallet: allowlist_entry.wallet,
+            token_account,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the token's transfer journal. Recording starts once it is enabled with
+    /// `set_transfer_journal_enabled`.
+    pub fn initialize_transfer_journal(ctx: Context<InitializeTransferJournal>) -> Result<()> {
+        let mut journal = ctx.accounts.transfer_journal.load_init()?;
+        journal.token_mint = ctx.accounts.token_config.mint;
+        journal.bump = ctx.bumps.transfer_journal;
+        Ok(())
+    }
+
+    /// Start or stop appending every `gated_transfer` to the transfer journal. While
+    /// enabled, transfer paths that cannot reach the journal (batch and compact transfers)
+    /// are refused.
+    pub fn set_transfer_journal_enabled(ctx: Context<UpdateTokenSettings>, enabled: bool) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        token_config.journal_enabled = enabled;
+
+        let clock = Clock::get()?;
+
+        emit!(TransferJournalUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            enabled,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Require instructions that issue, force-move or freeze shares on the authority's
+    /// signature (`mint_tokens`, `batch_mint`, `onboard_investor`, airdrop and vesting
+    /// funding, `force_transfer` and freezes) to come from a program registered with
+    /// `add_trusted_caller` when reached through CPI. Direct calls are unaffected, and
+    /// compact and compressed mints, which do not check callers, are refused meanwhile.
+    /// Settings changes are left to the timelock and council.
+    pub fn set_restrict_cpi_callers(ctx: Context<UpdateTokenSettings>, restricted: bool) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        token_config.restrict_cpi_callers = restricted;
+
+        let clock = Clock::get()?;
+
+        emit!(CpiCallersRestrictedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            restricted,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the token's feature flags. Unknown bits are rejected, and once
+    /// `MINTING_FINALIZED` is set it cannot be cleared.
+    pub fn set_features(ctx: Context<UpdateTokenSettings>, features: FeatureFlags) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        require!(features.bits & !FeatureFlags::ALL == 0, ErrorCode::UnknownFeatureFlags);
+        require!(
+            !token_config.features.contains(FeatureFlags::MINTING_FINALIZED)
+                || features.contains(FeatureFlags::MINTING_FINALIZED),
+            ErrorCode::MintingFinalized
+        );
+        let previous = token_config.features;
+        token_config.features = features;
+
+        let clock = Clock::get()?;
+
+        emit!(FeaturesUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            previous_features: previous.bits,
+            features: features.bits,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Trust `program_id` to invoke the token's restricted instructions through CPI
+    pub fn add_trusted_caller(ctx: Context<AddTrustedCaller>, program_id: Pubkey) -> Result<()> {
+        let trusted_caller = &mut ctx.accounts.trusted_caller;
+        trusted_caller.token_mint = ctx.accounts.token_config.mint;
+        trusted_caller.program_id = program_id;
+        trusted_caller.bump = ctx.bumps.trusted_caller;
+
+        let clock = Clock::get()?;
+
+        emit!(TrustedCallerUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            program_id,
+            trusted: true,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Stop trusting a caller program, returning its account's rent to the authority
+    pub fn remove_trusted_caller(ctx: Context<RemoveTrustedCaller>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        emit!(TrustedCallerUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            program_id: ctx.accounts.trusted_caller.program_id,
+            trusted: false,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create or update the rule for one ISO 3166-1 alpha-2 country. A `max_investors`
+    /// of 0 leaves the country uncapped. Must be signed by the compliance officer.
+    pub fn set_country_rule(
+        ctx: Context<SetCountryRule>,
+        country_code: [u8; 2],
+        is_allowed: bool,
+        max_investors: u32,
+    ) -> Result<()> {
+        require!(is_valid_country_code(&country_code), ErrorCode::InvalidCountryCode);
+
+        let country_rule = &mut ctx.accounts.country_rule;
+        require!(
+            max_investors == 0 || max_investors >= country_rule.investor_count,
+            ErrorCode::CountryInvestorCapReached
+        );
+
+        country_rule.token_mint = ctx.accounts.token_config.mint;
+        country_rule.country_code = country_code;
+        country_rule.is_allowed = is_allowed;
+        country_rule.max_investors = max_investors;
+        country_rule.bump = ctx.bumps.country_rule;
+
+        let clock = Clock::get()?;
+
+        emit!(CountryRuleUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: country_rule.token_mint,
+            country_code,
+            is_allowed,
+            max_investors,
+            investor_count: country_rule.investor_count,
+            updated_by: ctx.accounts.compliance_officer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Record an approved wallet's country of residence. The country must be allowed and
+    /// below its investor cap; a wallet moving countries frees its slot in the old one.
+    pub fn assign_wallet_country(
+        ctx: Context<AssignWalletCountry>,
+        country_code: [u8; 2],
+    ) -> Result<()> {
+        require!(is_valid_country_code(&country_code), ErrorCode::InvalidCountryCode);
+
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        require!(allowlist_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(allowlist_entry.country_code != country_code, ErrorCode::InvalidCountryCode);
+
+        let country_rule = &mut ctx.accounts.country_rule;
+        require!(country_rule.is_allowed, ErrorCode::CountryNotAllowed);
+        require!(country_rule.has_capacity(), ErrorCode::CountryInvestorCapReached);
+        country_rule.investor_count = country_rule.investor_count.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let previous_country = allowlist_entry.country_code;
+        if previous_country != UNASSIGNED_COUNTRY {
+            let previous_rule = ctx.accounts.previous_country_rule
+                .as_mut()
+                .ok_or(ErrorCode::CountryRuleRequired)?;
+            previous_rule.investor_count = previous_rule.investor_count.saturating_sub(1);
+        }
+        allowlist_entry.country_code = country_code;
+
+        let clock = Clock::get()?;
+
+        emit!(WalletCountryAssignedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            wallet: allowlist_entry.wallet,
+            previous_country,
+            country_code,
+            assigned_by: ctx.accounts.compliance_officer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register the sanctions-oracle program whose per-wallet flag accounts are checked on
+    /// every mint and transfer. `Pubkey::default()` disables sanctions screening.
+    pub fn set_sanctions_oracle(ctx: Context<UpdateTokenSettings>, sanctions_oracle: Pubkey) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        let old_oracle = token_config.sanctions_oracle;
+        token_config.sanctions_oracle = sanctions_oracle;
+
+        let clock = Clock::get()?;
+
+        emit!(SanctionsOracleUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_oracle,
+            new_oracle: sanctions_oracle,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register the price feed NAV redemptions and market-priced option exercises read:
+    /// `price_feed`, owned by `oracle_program` (the Pyth receiver for a sponsored Pyth
+    /// `PriceUpdateV2` feed, or a program serving Pyth-style `PriceFeed` accounts), trusted
+    /// while it is at most `max_price_age_seconds` old. `Pubkey::default()` unsets it.
+    pub fn set_price_oracle(
+        ctx: Context<SetPriceOracle>,
+        oracle_program: Pubkey,
+        price_feed: Pubkey,
+        max_price_age_seconds: i64,
+    ) -> Result<()> {
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        if oracle_program == Pubkey::default() {
+            require!(price_feed == Pubkey::default(), ErrorCode::InvalidPriceOracle);
+        } else {
+            require!(
+                price_feed != Pubkey::default() && max_price_age_seconds > 0,
+                ErrorCode::InvalidPriceOracle
+            );
+        }
+        token_config.price_oracle = oracle_program;
+        token_config.price_feed = price_feed;
+        token_config.max_price_age_seconds = max_price_age_seconds;
+
+        let clock = Clock::get()?;
+
+        emit!(PriceOracleUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            oracle_program,
+            price_feed,
+            max_price_age_seconds,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a SOL dividend distribution funded with `amount` lamports from the authority.
+    /// Entitlements are fixed by `snapshot_root`, the Merkle root of every holder's balance
+    /// at `record_date`, and `snapshot_supply`, the sum of those balances.
+    pub fn create_distribution(
+        ctx: Context<CreateDistribution>,
+        record_date: i64,
+        snapshot_root: [u8; 32],
+        snapshot_supply: u64,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(snapshot_supply > 0, ErrorCode::InvalidDistribution);
+        let clock = Clock::get()?;
+        require!(record_date <= clock.unix_timestamp, ErrorCode::InvalidDistribution);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.distribution.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.token_mint = token_config.mint;
+        distribution.distribution_id = token_config.next_distribution_id;
+        distribution.record_date = record_date;
+        distribution.snapshot_root = snapshot_root;
+        distribution.snapshot_supply = snapshot_supply;
+        distribution.total_amount = amount;
+        distribution.claimed_amount = 0;
+        distribution.created_at = clock.unix_timestamp;
+        distribution.payout_mint = Pubkey::default();
+        distribution.claim_deadline = i64::MAX;
+        distribution.drip_price = 0;
+        distribution.snapshot_id = 0;
+        distribution.bump = ctx.bumps.distribution;
+
+        token_config.next_distribution_id = token_config.next_distribution_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(DistributionCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: distribution.token_mint,
+            distribution_id: distribution.distribution_id,
+            record_date,
+            snapshot_supply,
+            total_amount: amount,
+            payout_mint: Pubkey::default(),
+            claim_deadline: i64::MAX,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Claim a holder's pro-rata share of a distribution, proving their record-date balance
+    /// against the snapshot root. A claim receipt PDA prevents claiming twice.
+    pub fn claim_dividend(
+        ctx: Context<ClaimDividend>,
+        snapshot_balance: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.distribution.payout_mint == Pubkey::default(),
+            ErrorCode::InvalidDistribution
+        );
+
+        let payout = settle_dividend_claim(
+            &mut ctx.accounts.token_config,
+            &mut ctx.accounts.distribution,
+            &mut ctx.accounts.dividend_claim,
+            ctx.accounts.holder.key(),
+            snapshot_balance,
+            &proof,
+            ctx.bumps.dividend_claim,
+        )?;
+
+        ctx.accounts.distribution.sub_lamports(payout)?;
+        ctx.accounts.holder.add_lamports(payout)?;
+
+        Ok(())
+    }
+
+    /// Open a dividend distribution paid in an SPL token (e.g. USDC). `amount` is escrowed
+    /// from the authority into a vault owned by the distribution PDA; holders claim until
+    /// `claim_deadline`, after which the authority may sweep what is left.
+    pub fn create_token_distribution(
+        ctx: Context<CreateTokenDistribution>,
+        record_date: i64,
+        snapshot_root: [u8; 32],
+        snapshot_supply: u64,
+        amount: u64,
+        claim_deadline: i64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(snapshot_supply > 0, ErrorCode::InvalidDistribution);
+        let clock = Clock::get()?;
+        require!(record_date <= clock.unix_timestamp, ErrorCode::InvalidDistribution);
+        require!(claim_deadline > clock.unix_timestamp, ErrorCode::InvalidDistribution);
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.funding_token_account.to_account_info(),
+            mint: ctx.accounts.payout_mint.to_account_info(),
+            to: ctx.accounts.distribution_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.payout_mint.decimals)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.token_mint = token_config.mint;
+        distribution.distribution_id = token_config.next_distribution_id;
+        distribution.record_date = record_date;
+        distribution.snapshot_root = snapshot_root;
+        distribution.snapshot_supply = snapshot_supply;
+        distribution.total_amount = amount;
+        distribution.claimed_amount = 0;
+        distribution.created_at = clock.unix_timestamp;
+        distribution.payout_mint = ctx.accounts.payout_mint.key();
+        distribution.claim_deadline = claim_deadline;
+        distribution.drip_price = 0;
+        distribution.snapshot_id = 0;
+        distribution.bump = ctx.bumps.distribution;
+
+        token_config.next_distribution_id = token_config.next_distribution_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(DistributionCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: distribution.token_mint,
+            distribution_id: distribution.distribution_id,
+            record_date,
+            snapshot_supply,
+            total_amount: amount,
+            payout_mint: distribution.payout_mint,
+            claim_deadline,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Claim a holder's pro-rata share of an SPL-token distribution into a token account
+    /// owned by the (allowlisted) holder
+    pub fn claim_token_dividend(
+        ctx: Context<ClaimTokenDividend>,
+        snapshot_balance: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.holder_allowlist_entry.is_approved,
+            ErrorCode::RecipientNotApproved
+        );
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp <= ctx.accounts.distribution.claim_deadline,
+            ErrorCode::ClaimPeriodEnded
+        );
+
+        let payout = settle_dividend_claim(
+            &mut ctx.accounts.token_config,
+            &mut ctx.accounts.distribution,
+            &mut ctx.accounts.dividend_claim,
+            ctx.accounts.holder.key(),
+            snapshot_balance,
+            &proof,
+            ctx.bumps.dividend_claim,
+        )?;
+
+        // DRIP holders take new shares instead of cash, down to the last whole lot
+        let cash = if ctx.accounts.holder_allowlist_entry.drip_enabled
+            && ctx.accounts.distribution.drip_price > 0
+        {
+            let bump = ctx.bumps.holder_snapshot_balance;
+            let mint_authority_bump = ctx.bumps.mint_authority;
+            reinvest_dividend(ctx.accounts, bump, mint_authority_bump, payout)?
+        } else {
+            payout
+        };
+
+        if cash > 0 {
+            let distribution = &ctx.accounts.distribution;
+            let id_bytes = distribution.distribution_id.to_le_bytes();
+            let seeds: &[&[u8]] = &[
+                b"distribution",
+                distribution.token_mint.as_ref(),
+                &id_bytes,
+                &[distribution.bump],
+            ];
+            let signer_seeds = &[seeds];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.distribution_vault.to_account_info(),
+                mint: ctx.accounts.payout_mint.to_account_info(),
+                to: ctx.accounts.holder_token_account.to_account_info(),
+                authority: distribution.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token::transfer_checked(cpi_ctx, cash, ctx.accounts.payout_mint.decimals)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set up recurring distributions: every `interval_seconds` from `first_run_at`, anyone
+    /// may crank out a new epoch paying `amount_per_period` of `payout_mint` from the
+    /// schedule vault, which the issuer keeps funded with ordinary transfers
+    pub fn create_distribution_schedule(
+        ctx: Context<CreateDistributionSchedule>,
+        amount_per_period: u64,
+        interval_seconds: i64,
+        claim_window_seconds: i64,
+        first_run_at: i64,
+    ) -> Result<()> {
+        ctx.accounts.token_config.require_current_version()?;
+        require!(amount_per_period > 0, ErrorCode::InvalidAmount);
+        require!(
+            interval_seconds > 0 && claim_window_seconds > 0,
+            ErrorCode::InvalidDistributionSchedule
+        );
+
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.token_mint = ctx.accounts.token_config.mint;
+        schedule.payout_mint = ctx.accounts.payout_mint.key();
+        schedule.amount_per_period = amount_per_period;
+        schedule.interval_seconds = interval_seconds;
+        schedule.claim_window_seconds = claim_window_seconds;
+        schedule.next_run_at = first_run_at;
+        schedule.epochs_run = 0;
+        schedule.active = true;
+        schedule.bump = ctx.bumps.schedule;
+
+        emit!(DistributionScheduleUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: schedule.token_mint,
+            payout_mint: schedule.payout_mint,
+            amount_per_period,
+            interval_seconds,
+            next_run_at: first_run_at,
+            active: true,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Stop or restart a distribution schedule
+    pub fn set_distribution_schedule_active(
+        ctx: Context<SetDistributionScheduleActive>,
+        active: bool,
+    ) -> Result<()> {
+        let schedule = &mut ctx.accounts.schedule;
+        schedule.active = active;
+
+        emit!(DistributionScheduleUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: schedule.token_mint,
+            payout_mint: schedule.payout_mint,
+            amount_per_period: schedule.amount_per_period,
+            interval_seconds: schedule.interval_seconds,
+            next_run_at: schedule.next_run_at,
+            active,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank for the current period: take a snapshot and open a
+    /// distribution against it, funded from the schedule vault. Missed periods are skipped
+    /// rather than paid out in a burst.
+    pub fn crank_distribution(ctx: Context<CrankDistribution>) -> Result<()> {
+        let clock = Clock::get()?;
+        let schedule = &ctx.accounts.schedule;
+        require!(schedule.active, ErrorCode::DistributionScheduleInactive);
+        require!(clock.unix_timestamp >= schedule.next_run_at, ErrorCode::DistributionNotDue);
+        let amount = schedule.amount_per_period;
+        require!(ctx.accounts.schedule_vault.amount >= amount, ErrorCode::DistributionScheduleUnderfunded);
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.require_current_version()?;
+        require!(!token_config.allocation_active, ErrorCode::AllocationActive);
+        require!(ctx.accounts.mint.supply > 0, ErrorCode::InvalidDistribution);
+        token_config.current_snapshot_id = token_config.current_snapshot_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.token_mint = token_config.mint;
+        snapshot.snapshot_id = token_config.current_snapshot_id;
+        snapshot.total_supply = ctx.accounts.mint.supply;
+        snapshot.holder_count = token_config.holder_count;
+        snapshot.created_at = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.token_mint = token_config.mint;
+        distribution.distribution_id = token_config.next_distribution_id;
+        distribution.record_date = clock.unix_timestamp;
+        distribution.snapshot_root = [0; 32];
+        distribution.snapshot_supply = snapshot.total_supply;
+        distribution.total_amount = amount;
+        distribution.claimed_amount = 0;
+        distribution.created_at = clock.unix_timestamp;
+        distribution.payout_mint = schedule.payout_mint;
+        distribution.claim_deadline = clock.unix_timestamp.checked_add(schedule.claim_window_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        distribution.drip_price = 0;
+        distribution.snapshot_id = snapshot.snapshot_id;
+        distribution.bump = ctx.bumps.distribution;
+        token_config.next_distribution_id = token_config.next_distribution_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let mint_key = schedule.token_mint;
+        let seeds: &[&[u8]] = &[b"distribution_schedule", mint_key.as_ref(), &[schedule.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.schedule_vault.to_account_info(),
+            mint: ctx.accounts.payout_mint.to_account_info(),
+            to: ctx.accounts.distribution_vault.to_account_info(),
+            authority: schedule.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, amount, ctx.accounts.payout_mint.decimals)?;
+
+        let schedule = &mut ctx.accounts.schedule;
+        let periods_due = (clock.unix_timestamp - schedule.next_run_at) / schedule.interval_seconds + 1;
+        schedule.next_run_at = periods_due.checked_mul(schedule.interval_seconds)
+            .and_then(|advance| schedule.next_run_at.checked_add(advance))
+            .ok_or(ErrorCode::Overflow)?;
+        schedule.epochs_run = schedule.epochs_run.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SnapshotCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: snapshot.token_mint,
+            snapshot_id: snapshot.snapshot_id,
+            total_supply: snapshot.total_supply,
+            holder_count: snapshot.holder_count,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(DistributionCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: distribution.token_mint,
+            distribution_id: distribution.distribution_id,
+            record_date: distribution.record_date,
+            snapshot_supply: distribution.snapshot_supply,
+            total_amount: amount,
+            payout_mint: distribution.payout_mint,
+            claim_deadline: distribution.claim_deadline,
+            authority: ctx.accounts.cranker.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Claim a holder's share of a scheduled distribution. The record-date balance comes
+    /// from the on-chain snapshot: a checkpoint covering it, or the live balance if the
+    /// holder has not moved since.
+    pub fn claim_scheduled_dividend(ctx: Context<ClaimScheduledDividend>) -> Result<()> {
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        holder_entry.require_current_version()?;
+        require!(holder_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.share_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp <= ctx.accounts.distribution.claim_deadline,
+            ErrorCode::ClaimPeriodEnded
+        );
+
+        let snapshot_balance = ctx.accounts.snapshot.balance_of(
+            holder_entry,
+            ctx.accounts.share_token_account.amount,
+            ctx.accounts.holder_snapshot_balance.as_deref().map(|checkpoint| &**checkpoint),
+        )?;
+        let payout = record_dividend_claim(
+            &mut ctx.accounts.token_config,
+            &mut ctx.accounts.distribution,
+            &mut ctx.accounts.dividend_claim,
+            ctx.accounts.holder.key(),
+            snapshot_balance,
+            ctx.bumps.dividend_claim,
+        )?;
+
+        let distribution = &ctx.accounts.distribution;
+        let id_bytes = distribution.distribution_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"distribution",
+            distribution.token_mint.as_ref(),
+            &id_bytes,
+            &[distribution.bump],
+        ];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.distribution_vault.to_account_info(),
+            mint: ctx.accounts.payout_mint.to_account_info(),
+            to: ctx.accounts.holder_payout_account.to_account_info(),
+            authority: distribution.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, payout, ctx.accounts.payout_mint.decimals)?;
+
+        Ok(())
+    }
+
+    /// Return unclaimed funds of an SPL-token distribution to the authority once the
+    /// claim deadline has passed
+    pub fn sweep_distribution(ctx: Context<SweepDistribution>) -> Result<()> {
+        let clock = Clock::get()?;
+        let distribution = &ctx.accounts.distribution;
+        require!(
+            clock.unix_timestamp > distribution.claim_deadline,
+            ErrorCode::ClaimPeriodActive
+        );
+
+        let amount = ctx.accounts.distribution_vault.amount;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let id_bytes = distribution.distribution_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"distribution",
+            distribution.token_mint.as_ref(),
+            &id_bytes,
+            &[distribution.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.distribution_vault.to_account_info(),
+            mint: ctx.accounts.payout_mint.to_account_info(),
+            to: ctx.accounts.destination_token_account.to_account_info(),
+            authority: distribution.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, amount, ctx.accounts.payout_mint.decimals)?;
+
+        emit!(DistributionSweptEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: distribution.token_mint,
+            distribution_id: distribution.distribution_id,
+            amount,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a claim-based grant: mint `total_amount` into the campaign vault, claimable by
+    /// the wallets committed to in `merkle_root` (leaves are `sha256("airdrop" || wallet ||
+    /// amount_le)`) until `expires_at`
+    pub fn create_airdrop_campaign(
+        ctx: Context<CreateAirdropCampaign>,
+        merkle_root: [u8; 32],
+        total_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(total_amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        let clock = Clock::get()?;
+        require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidAirdropCampaign);
+
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.campaign.key(),
+            ctx.accounts.authority.key(),
+        )?;
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.campaign_vault.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            total_amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, total_amount)?;
+
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.token_mint = token_config.mint;
+        campaign.campaign_id = token_config.next_airdrop_id;
+        campaign.merkle_root = merkle_root;
+        campaign.total_amount = total_amount;
+        campaign.claimed_amount = 0;
+        campaign.expires_at = expires_at;
+        campaign.created_at = clock.unix_timestamp;
+        campaign.bump = ctx.bumps.campaign;
+        token_config.next_airdrop_id = token_config.next_airdrop_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(AirdropCampaignCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: campaign.token_mint,
+            campaign_id: campaign.campaign_id,
+            merkle_root,
+            total_amount,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Claim a wallet's airdrop grant with a Merkle proof. The claim receipt PDA makes a
+    /// second claim fail.
+    pub fn claim_airdrop<'info>(ctx: Context<'_, '_, '_, 'info, ClaimAirdrop<'info>>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        require!(clock.unix_timestamp <= campaign.expires_at, ErrorCode::AirdropExpired);
+        let wallet = ctx.accounts.wallet.key();
+        require!(
+            verify_merkle_proof(&campaign.merkle_root, airdrop_leaf(&wallet, amount), &proof),
+            ErrorCode::InvalidAirdropProof
+        );
+
+        let wallet_entry = &ctx.accounts.wallet_allowlist_entry;
+        require!(wallet_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            wallet_entry.permits_token_account(&ctx.accounts.wallet_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.wallet_sanctions_flag.as_ref(),
+                &wallet,
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        let joins = ctx.accounts.wallet_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(joins),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.wallet_allowlist_entry,
+            ctx.accounts.wallet_snapshot_balance.as_deref_mut(),
+            ctx.bumps.wallet_snapshot_balance,
+            ctx.accounts.wallet_token_account.amount,
+        )?;
+        let wallet_balance = ctx.accounts.wallet_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.wallet_holder_number.as_deref(),
+                payer: &ctx.accounts.wallet.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.wallet_allowlist_entry, &ctx.accounts.wallet_token_account.key(), wallet_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.wallet_allowlist_entry,
+            &ctx.accounts.wallet_token_account.key(),
+            ctx.accounts.wallet_holder_page.as_ref(),
+            wallet_balance,
+        )?;
+
+        let campaign = &ctx.accounts.campaign;
+        let id_bytes = campaign.campaign_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"airdrop", campaign.token_mint.as_ref(), &id_bytes, &[campaign.bump]];
+        let signer_seeds = &[seeds];
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.campaign_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.wallet_token_account.to_account_info(),
+            authority: campaign.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if joins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.claimed_amount = campaign.claimed_amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let airdrop_claim = &mut ctx.accounts.airdrop_claim;
+        airdrop_claim.campaign = campaign.key();
+        airdrop_claim.wallet = wallet;
+        airdrop_claim.amount = amount;
+        airdrop_claim.claimed_at = clock.unix_timestamp;
+        airdrop_claim.bump = ctx.bumps.airdrop_claim;
+
+        emit!(AirdropClaimedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: campaign.token_mint,
+            campaign_id: campaign.campaign_id,
+            wallet,
+            amount,
+            claimed_amount: campaign.claimed_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Return an expired campaign's unclaimed shares to the issuer treasury and close its vault
+    pub fn sweep_airdrop<'info>(ctx: Context<'_, '_, '_, 'info, SweepAirdrop<'info>>) -> Result<()> {
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        require!(clock.unix_timestamp > campaign.expires_at, ErrorCode::AirdropActive);
+
+        let amount = ctx.accounts.campaign_vault.amount;
+        let id_bytes = campaign.campaign_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"airdrop", campaign.token_mint.as_ref(), &id_bytes, &[campaign.bump]];
+        drain_program_vault(
+            &ctx.accounts.campaign_vault,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.accounts.authority.to_account_info(),
+            campaign.to_account_info(),
+            &[seeds],
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+        )?;
+
+        emit!(AirdropSweptEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: campaign.token_mint,
+            campaign_id: campaign.campaign_id,
+            amount,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Take a balance snapshot for a record date. The raw mint supply is recorded now;
+    /// holder balances are checkpointed lazily the first time each one moves afterwards.
+    pub fn create_snapshot(ctx: Context<CreateSnapshot>) -> Result<()> {
+        let clock = Clock::get()?;
+        let token_config = &mut ctx.accounts.token_config;
+        require!(!token_config.allocation_active, ErrorCode::AllocationActive);
+        token_config.current_snapshot_id = token_config.current_snapshot_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.token_mint = token_config.mint;
+        snapshot.snapshot_id = token_config.current_snapshot_id;
+        snapshot.total_supply = ctx.accounts.mint.supply;
+        snapshot.holder_count = token_config.holder_count;
+        snapshot.created_at = clock.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(SnapshotCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: snapshot.token_mint,
+            snapshot_id: snapshot.snapshot_id,
+            total_supply: snapshot.total_supply,
+            holder_count: snapshot.holder_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Split (or reverse split) the token in place by `numerator / denominator`. Raw
+    /// balances are untouched; the cumulative factor in `TokenConfig` is updated and
+    /// clients interpret balances through it (see `TokenConfig::split_adjusted`).
+    pub fn execute_split(ctx: Context<ExecuteSplit>, numerator: u64, denominator: u64) -> Result<()> {
+        require!(numerator > 0 && denominator > 0, ErrorCode::InvalidSplitRatio);
+        require!(numerator != denominator, ErrorCode::InvalidSplitRatio);
+
+        let token_config = &mut ctx.accounts.token_config;
+        require!(!token_config.breaker_tripped, ErrorCode::CircuitBreakerTripped);
+
+        let cumulative_numerator = u128::from(token_config.split_numerator)
+            .checked_mul(u128::from(numerator))
+            .ok_or(ErrorCode::Overflow)?;
+        let cumulative_denominator = u128::from(token_config.split_denominator)
+            .checked_mul(u128::from(denominator))
+            .ok_or(ErrorCode::Overflow)?;
+        let divisor = gcd(cumulative_numerator, cumulative_denominator);
+        token_config.split_numerator = u64::try_from(cumulative_numerator / divisor)
+            .map_err(|_| ErrorCode::Overflow)?;
+        token_config.split_denominator = u64::try_from(cumulative_denominator / divisor)
+            .map_err(|_| ErrorCode::Overflow)?;
+
+        let old_total_supply = token_config.total_supply;
+        token_config.total_supply = token_config.split_adjusted(ctx.accounts.mint.supply)?;
+
+        let clock = Clock::get()?;
+
+        emit!(SplitExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            numerator,
+            denominator,
+            cumulative_numerator: token_config.split_numerator,
+            cumulative_denominator: token_config.split_denominator,
+            old_total_supply,
+            new_total_supply: token_config.total_supply,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create or update the share class of this token (e.g. Common, Series A Preferred),
+    /// carrying its transfer rules, voting weight and dividend seniority
+    pub fn set_share_class(
+        ctx: Context<SetShareClass>,
+        name: String,
+        kind: ShareClassKind,
+        votes_per_share: u16,
+        dividend_priority: u8,
+        transferable: bool,
+        max_holders: u32,
+    ) -> Result<()> {
+        require!(!name.is_empty() && name.len() <= MAX_SHARE_CLASS_NAME_LEN, ErrorCode::InvalidName);
+
+        let share_class = &mut ctx.accounts.share_class;
+        share_class.token_mint = ctx.accounts.token_config.mint;
+        share_class.name = name;
+        share_class.kind = kind;
+        share_class.votes_per_share = votes_per_share;
+        share_class.dividend_priority = dividend_priority;
+        share_class.transferable = transferable;
+        share_class.max_holders = max_holders;
+        share_class.bump = ctx.bumps.share_class;
+
+        let token_config = &mut ctx.accounts.token_config;
+        token_config.share_class = share_class.key();
+
+        let clock = Clock::get()?;
+
+        emit!(ShareClassUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            share_class: token_config.share_class,
+            kind,
+            votes_per_share,
+            dividend_priority,
+            transferable,
+            max_holders,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grant `total_amount` newly minted tokens to `beneficiary`, held in a program vault
+    /// and released according to `terms`
+    pub fn create_vesting_schedule(
+        ctx: Context<CreateVestingSchedule>,
+        total_amount: u64,
+        terms: VestingTerms,
+        revocable: bool,
+    ) -> Result<()> {
+        require!(total_amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        enforce_cpi_caller(
+            &ctx.accounts.token_config,
+            ctx.accounts.instructions.as_ref(),
+            ctx.accounts.trusted_caller.as_deref(),
+        )?;
+        terms.validate()?;
+
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.vesting_schedule.key(),
+            ctx.accounts.authority.key(),
+        )?;
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.vesting_vault.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            total_amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, total_amount)?;
+
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        vesting_schedule.token_mint = token_config.mint;
+        vesting_schedule.vesting_id = token_config.next_vesting_id;
+        vesting_schedule.beneficiary = ctx.accounts.beneficiary.key();
+        vesting_schedule.total_amount = total_amount;
+        vesting_schedule.released_amount = 0;
+        vesting_schedule.terms = terms;
+        vesting_schedule.revocable = revocable;
+        vesting_schedule.revoked = false;
+        vesting_schedule.bump = ctx.bumps.vesting_schedule;
+
+        token_config.next_vesting_id = token_config.next_vesting_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(VestingScheduleCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: vesting_schedule.token_mint,
+            vesting_id: vesting_schedule.vesting_id,
+            beneficiary: vesting_schedule.beneficiary,
+            total_amount,
+            terms,
+            revocable,
+        });
+
+        Ok(())
+    }
+
+    /// Release everything vested so far to the beneficiary. Anyone may crank this.
+    pub fn release_vested<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseVested<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+
+        // The beneficiary is screened like any other recipient
+        let beneficiary_entry = &ctx.accounts.beneficiary_allowlist_entry;
+        require!(beneficiary_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            beneficiary_entry.permits_token_account(&ctx.accounts.beneficiary_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        enforce_country_rule(beneficiary_entry, ctx.accounts.beneficiary_country_rule.as_deref())?;
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.beneficiary_sanctions_flag.as_ref(),
+                &beneficiary_entry.wallet,
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        let clock = Clock::get()?;
+        let vesting_schedule = &ctx.accounts.vesting_schedule;
+        let amount = vesting_schedule.vested_amount(clock.unix_timestamp)?
+            .checked_sub(vesting_schedule.released_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(amount > 0, ErrorCode::NothingVested);
+
+        let is_new_holder = ctx.accounts.beneficiary_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.beneficiary_allowlist_entry,
+            ctx.accounts.beneficiary_snapshot_balance.as_deref_mut(),
+            ctx.bumps.beneficiary_snapshot_balance,
+            ctx.accounts.beneficiary_token_account.amount,
+        )?;
+        let beneficiary_balance = ctx.accounts.beneficiary_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.beneficiary_holder_number.as_deref(),
+                payer: &ctx.accounts.cranker.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.beneficiary_allowlist_entry, &ctx.accounts.beneficiary_token_account.key(), beneficiary_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.beneficiary_allowlist_entry,
+            &ctx.accounts.beneficiary_token_account.key(),
+            ctx.accounts.beneficiary_holder_page.as_ref(),
+            beneficiary_balance,
+        )?;
+
+        let vesting_schedule = &ctx.accounts.vesting_schedule;
+        let id_bytes = vesting_schedule.vesting_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"vesting",
+            vesting_schedule.token_mint.as_ref(),
+            &id_bytes,
+            &[vesting_schedule.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vesting_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.beneficiary_token_account.to_account_info(),
+            authority: vesting_schedule.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        transfer_checked_with_hook(cpi_ctx, amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        vesting_schedule.released_amount = vesting_schedule.released_amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        if is_new_holder {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(VestedTokensReleasedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: vesting_schedule.token_mint,
+            vesting_id: vesting_schedule.vesting_id,
+            beneficiary: vesting_schedule.beneficiary,
+            amount,
+            released_amount: vesting_schedule.released_amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a revocable vesting schedule: tokens vested so far stay releasable to the
+    /// beneficiary, the unvested remainder returns to the issuer treasury
+    pub fn revoke_vesting<'info>(ctx: Context<'_, '_, '_, 'info, RevokeVesting<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let vesting_schedule = &ctx.accounts.vesting_schedule;
+        require!(vesting_schedule.revocable, ErrorCode::VestingNotRevocable);
+        require!(!vesting_schedule.revoked, ErrorCode::VestingNotRevocable);
+
+        let vested = vesting_schedule.vested_amount(clock.unix_timestamp)?;
+        let unvested = vesting_schedule.total_amount.checked_sub(vested)
+            .ok_or(ErrorCode::Overflow)?;
+
+        if unvested > 0 {
+            let id_bytes = vesting_schedule.vesting_id.to_le_bytes();
+            let seeds: &[&[u8]] = &[
+                b"vesting",
+                vesting_schedule.token_mint.as_ref(),
+                &id_bytes,
+                &[vesting_schedule.bump],
+            ];
+            let signer_seeds = &[seeds];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.vesting_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: vesting_schedule.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            transfer_checked_with_hook(cpi_ctx, unvested, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+        }
+
+        let vesting_schedule = &mut ctx.accounts.vesting_schedule;
+        vesting_schedule.total_amount = vested;
+        vesting_schedule.revoked = true;
+
+        emit!(VestingRevokedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: vesting_schedule.token_mint,
+            vesting_id: vesting_schedule.vesting_id,
+            beneficiary: vesting_schedule.beneficiary,
+            vested_amount: vested,
+            returned_amount: unvested,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Grant an employee options on `quantity` raw token units at `strike_price` (payment
+    /// mint base units per whole share), exercisable as they vest until `expires_at`
+    pub fn create_option_grant(
+        ctx: Context<CreateOptionGrant>,
+        quantity: u64,
+        strike_price: u64,
+        vesting: VestingTerms,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(quantity > 0, ErrorCode::InvalidAmount);
+        vesting.validate()?;
+        let clock = Clock::get()?;
+        require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidOptionGrant);
+
+        let token_config = &mut ctx.accounts.token_config;
+        let option_grant = &mut ctx.accounts.option_grant;
+        option_grant.token_mint = token_config.mint;
+        option_grant.grant_id = token_config.next_option_grant_id;
+        option_grant.employee = ctx.accounts.employee.key();
+        option_grant.quantity = quantity;
+        option_grant.exercised = 0;
+        option_grant.strike_price = strike_price;
+        option_grant.payment_mint = ctx.accounts.payment_mint.key();
+        option_grant.vesting = vesting;
+        option_grant.expires_at = expires_at;
+        option_grant.bump = ctx.bumps.option_grant;
+
+        token_config.next_option_grant_id = token_config.next_option_grant_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OptionGrantCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: option_grant.token_mint,
+            grant_id: option_grant.grant_id,
+            employee: option_grant.employee,
+            quantity,
+            strike_price,
+            payment_mint: option_grant.payment_mint,
+            vesting,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Exercise `amount` vested options: the employee pays the strike into the issuer
+    /// treasury and the shares are minted to them. Partial exercises are allowed.
+    pub fn exercise_options(ctx: Context<ExerciseOptions>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        ctx.accounts.token_config.validate_lot(amount)?;
+
+        let clock = Clock::get()?;
+        let option_grant = &ctx.accounts.option_grant;
+        require!(clock.unix_timestamp < option_grant.expires_at, ErrorCode::OptionGrantExpired);
+        let exercisable = option_grant.exercisable(clock.unix_timestamp)?;
+        require!(amount <= exercisable, ErrorCode::OptionsNotExercisable);
+
+        let employee_entry = &ctx.accounts.employee_allowlist_entry;
+        require!(employee_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            employee_entry.permits_token_account(&ctx.accounts.employee_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        // Collect the strike, rounded up so fractional shares can't be exercised for free
+        let cost = option_grant.exercise_cost(amount, ctx.accounts.mint.decimals)?;
+        if cost > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.employee_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.treasury_payment_account.to_account_info(),
+                authority: ctx.accounts.employee.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), cost, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        let is_new_holder = ctx.accounts.employee_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.employee_allowlist_entry,
+            ctx.accounts.employee_snapshot_balance.as_deref_mut(),
+            ctx.bumps.employee_snapshot_balance,
+            ctx.accounts.employee_token_account.amount,
+        )?;
+        let employee_balance = ctx.accounts.employee_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.employee_holder_number.as_deref(),
+                payer: &ctx.accounts.employee.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.employee_allowlist_entry, &ctx.accounts.employee_token_account.key(), employee_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.employee_allowlist_entry,
+            &ctx.accounts.employee_token_account.key(),
+            ctx.accounts.employee_holder_page.as_ref(),
+            employee_balance,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.employee_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let option_grant = &mut ctx.accounts.option_grant;
+        option_grant.exercised = option_grant.exercised.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OptionsExercisedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: option_grant.token_mint,
+            grant_id: option_grant.grant_id,
+            employee: option_grant.employee,
+            amount,
+            cost,
+            exercised: option_grant.exercised,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cashless exercise of `amount` vested options at the oracle's market price: instead
+    /// of paying the strike, the employee receives only the shares worth the spread,
+    /// `amount * (market - strike) / market`, rounded down to a whole lot, and failing if
+    /// that is fewer than `min_shares`. All `amount` options count as exercised.
+    pub fn exercise_options_at_market(
+        ctx: Context<ExerciseOptionsAtMarket>,
+        amount: u64,
+        min_shares: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+
+        let clock = Clock::get()?;
+        let option_grant = &ctx.accounts.option_grant;
+        require!(clock.unix_timestamp < option_grant.expires_at, ErrorCode::OptionGrantExpired);
+        let exercisable = option_grant.exercisable(clock.unix_timestamp)?;
+        require!(amount <= exercisable, ErrorCode::OptionsNotExercisable);
+
+        let employee_entry = &ctx.accounts.employee_allowlist_entry;
+        require!(employee_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            employee_entry.permits_token_account(&ctx.accounts.employee_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let oracle_price = read_oracle_price(&ctx.accounts.token_config, &ctx.accounts.price_feed, clock.unix_timestamp)?;
+        let market_price = oracle_price.per_share(ctx.accounts.payment_mint.decimals)?;
+        require!(market_price > option_grant.strike_price, ErrorCode::OptionsOutOfTheMoney);
+        let spread = u128::from(amount)
+            .checked_mul(u128::from(market_price - option_grant.strike_price))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(market_price);
+        let spread = u64::try_from(spread).map_err(|_| ErrorCode::Overflow)?;
+        let lot_size = ctx.accounts.token_config.lot_size.max(1);
+        let shares = spread - spread % lot_size;
+        require!(shares > 0, ErrorCode::InvalidAmount);
+        require!(shares >= min_shares, ErrorCode::SlippageExceeded);
+        ctx.accounts.token_config.validate_lot(shares)?;
+
+        let is_new_holder = ctx.accounts.employee_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.employee_allowlist_entry,
+            ctx.accounts.employee_snapshot_balance.as_deref_mut(),
+            ctx.bumps.employee_snapshot_balance,
+            ctx.accounts.employee_token_account.amount,
+        )?;
+        let employee_balance = ctx.accounts.employee_token_account.amount.checked_add(shares)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.employee_holder_number.as_deref(),
+                payer: &ctx.accounts.employee.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.employee_allowlist_entry, &ctx.accounts.employee_token_account.key(), employee_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.employee_allowlist_entry,
+            &ctx.accounts.employee_token_account.key(),
+            ctx.accounts.employee_holder_page.as_ref(),
+            employee_balance,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.employee_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            shares,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, shares)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let option_grant = &mut ctx.accounts.option_grant;
+        option_grant.exercised = option_grant.exercised.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OptionsExercisedAtMarketEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: option_grant.token_mint,
+            grant_id: option_grant.grant_id,
+            employee: option_grant.employee,
+            amount,
+            shares_issued: shares,
+            strike_price: option_grant.strike_price,
+            market_price,
+            oracle_price,
+            exercised: option_grant.exercised,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an option grant that has expired or been fully exercised, returning its rent
+    /// to the authority. Unexercised options lapse.
+    pub fn close_option_grant(ctx: Context<CloseOptionGrant>) -> Result<()> {
+        let clock = Clock::get()?;
+        let option_grant = &ctx.accounts.option_grant;
+        require!(
+            clock.unix_timestamp >= option_grant.expires_at
+                || option_grant.exercised == option_grant.quantity,
+            ErrorCode::OptionGrantActive
+        );
+
+        emit!(OptionGrantClosedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: option_grant.token_mint,
+            grant_id: option_grant.grant_id,
+            employee: option_grant.employee,
+            lapsed: option_grant.quantity - option_grant.exercised,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set up issuer buybacks: a redemption config and a vault holding `payment_mint`
+    /// (e.g. USDC) that redeemed shares are paid from. Redemptions start closed.
+    pub fn initialize_redemption(ctx: Context<InitializeRedemption>) -> Result<()> {
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.token_mint = ctx.accounts.mint.key();
+        redemption_config.payment_mint = ctx.accounts.payment_mint.key();
+        redemption_config.price = 0;
+        redemption_config.is_open = false;
+        redemption_config.closes_at = 0;
+        redemption_config.total_redeemed = 0;
+        redemption_config.bump = ctx.bumps.redemption_config;
+
+        Ok(())
+    }
+
+    /// Deposit payment tokens from the authority into the redemption vault
+    pub fn fund_redemption_vault(ctx: Context<FundRedemptionVault>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.funding_token_account.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.redemption_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.payment_mint.decimals)?;
+
+        emit!(RedemptionVaultFundedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.redemption_config.token_mint,
+            amount,
+            vault_balance: ctx.accounts.redemption_vault.amount.checked_add(amount)
+                .ok_or(ErrorCode::Overflow)?,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Open a redemption window at `price` (payment mint base units per whole share)
+    /// until `closes_at`
+    pub fn open_redemption_window(
+        ctx: Context<UpdateRedemptionWindow>,
+        price: u64,
+        closes_at: i64,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidRedemptionPrice);
+        let clock = Clock::get()?;
+        require!(closes_at > clock.unix_timestamp, ErrorCode::InvalidRedemptionWindow);
+
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.price = price;
+        redemption_config.is_open = true;
+        redemption_config.closes_at = closes_at;
+
+        emit!(RedemptionWindowUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: redemption_config.token_mint,
+            is_open: true,
+            price,
+            closes_at,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a redemption window priced at the token's oracle NAV until `closes_at`.
+    /// Holders redeem through `redeem_at_nav`, at the price the feed reports when they do.
+    pub fn open_nav_redemption_window(ctx: Context<UpdateRedemptionWindow>, closes_at: i64) -> Result<()> {
+        require!(
+            ctx.accounts.token_config.price_oracle != Pubkey::default(),
+            ErrorCode::PriceOracleNotSet
+        );
+        let clock = Clock::get()?;
+        require!(closes_at > clock.unix_timestamp, ErrorCode::InvalidRedemptionWindow);
+
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.price = 0;
+        redemption_config.is_open = true;
+        redemption_config.closes_at = closes_at;
+
+        emit!(RedemptionWindowUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: redemption_config.token_mint,
+            is_open: true,
+            price: 0,
+            closes_at,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close the redemption window early
+    pub fn close_redemption_window(ctx: Context<UpdateRedemptionWindow>) -> Result<()> {
+        let clock = Clock::get()?;
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.is_open = false;
+
+        emit!(RedemptionWindowUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: redemption_config.token_mint,
+            is_open: false,
+            price: redemption_config.price,
+            closes_at: redemption_config.closes_at,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Retire `amount` of the holder's own shares, reducing the recorded total supply
+    pub fn burn_tokens(ctx: Context<BurnTokens>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        ctx.accounts.token_config.validate_lot(amount)?;
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((
+                &mut ctx.accounts.holder_allowlist_entry,
+                &ctx.accounts.holder_token_account.key(),
+                holder_balance,
+            )),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = token::Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let burned_shares = token_config.split_adjusted_exact(amount)?;
+        token_config.total_supply = token_config.total_supply.checked_sub(burned_shares)
+            .ok_or(ErrorCode::Overflow)?;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(TokensBurnedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.mint.key(),
+            holder: ctx.accounts.holder.key(),
+            amount,
+            new_supply: token_config.total_supply,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `amount` shares back to the issuer: the holder's shares are burned and the
+    /// redemption price is paid to them from the vault
+    pub fn redeem(ctx: Context<Redeem>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.redemption_config.is_window_open(clock.unix_timestamp),
+            ErrorCode::RedemptionWindowClosed
+        );
+        require!(ctx.accounts.redemption_config.price > 0, ErrorCode::RedemptionPricedAtNav);
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let payout = ctx.accounts.redemption_config.payout(amount, ctx.accounts.mint.decimals)?;
+        require!(payout > 0, ErrorCode::InvalidAmount);
+        require!(
+            ctx.accounts.redemption_vault.amount >= payout,
+            ErrorCode::InsufficientRedemptionFunds
+        );
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = token::Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let redemption_config = &ctx.accounts.redemption_config;
+        let seeds: &[&[u8]] = &[
+            b"redemption",
+            redemption_config.token_mint.as_ref(),
+            &[redemption_config.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.redemption_vault.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.holder_payment_account.to_account_info(),
+            authority: redemption_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, payout, ctx.accounts.payment_mint.decimals)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let burned_shares = token_config.split_adjusted_exact(amount)?;
+        token_config.total_supply = token_config.total_supply.checked_sub(burned_shares)
+            .ok_or(ErrorCode::Overflow)?;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.total_redeemed = redemption_config.total_redeemed.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesRedeemedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: redemption_config.token_mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            payout,
+            price: redemption_config.price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem `amount` shares in a NAV-priced window: the holder's shares are burned and
+    /// they are paid the oracle price per share from the vault, failing if that comes to
+    /// less than `min_payout`
+    pub fn redeem_at_nav(ctx: Context<RedeemAtNav>, amount: u64, min_payout: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let clock = Clock::get()?;
+        require!(
+            ctx.accounts.redemption_config.is_window_open(clock.unix_timestamp),
+            ErrorCode::RedemptionWindowClosed
+        );
+        require!(ctx.accounts.redemption_config.price == 0, ErrorCode::RedemptionNotPricedAtNav);
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let oracle_price = read_oracle_price(&ctx.accounts.token_config, &ctx.accounts.price_feed, clock.unix_timestamp)?;
+        let nav = oracle_price.per_share(ctx.accounts.payment_mint.decimals)?;
+        let payout = shares_value(amount, nav, ctx.accounts.mint.decimals)?;
+        require!(payout > 0, ErrorCode::InvalidAmount);
+        require!(payout >= min_payout, ErrorCode::SlippageExceeded);
+        require!(
+            ctx.accounts.redemption_vault.amount >= payout,
+            ErrorCode::InsufficientRedemptionFunds
+        );
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = token::Burn {
+            mint: ctx.accounts.mint.to_account_info(),
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::burn(CpiContext::new(cpi_program, cpi_accounts), amount)?;
+
+        let redemption_config = &ctx.accounts.redemption_config;
+        let seeds: &[&[u8]] = &[
+            b"redemption",
+            redemption_config.token_mint.as_ref(),
+            &[redemption_config.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.redemption_vault.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.holder_payment_account.to_account_info(),
+            authority: redemption_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, payout, ctx.accounts.payment_mint.decimals)?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let burned_shares = token_config.split_adjusted_exact(amount)?;
+        token_config.total_supply = token_config.total_supply.checked_sub(burned_shares)
+            .ok_or(ErrorCode::Overflow)?;
+        if leaves {
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let redemption_config = &mut ctx.accounts.redemption_config;
+        redemption_config.total_redeemed = redemption_config.total_redeemed.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesRedeemedAtNavEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: redemption_config.token_mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            payout,
+            nav,
+            oracle_price,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Open a tender offer to buy back up to `cap` raw token units at `price` (payment
+    /// mint base units per whole share) until `deadline`. The authority escrows the full
+    /// purchase price up front; the offer only completes if at least `min_acceptance`
+    /// units are tendered.
+    pub fn open_tender_offer(
+        ctx: Context<OpenTenderOffer>,
+        price: u64,
+        cap: u64,
+        min_acceptance: u64,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(price > 0 && cap > 0, ErrorCode::InvalidTenderOffer);
+        require!(min_acceptance <= cap, ErrorCode::InvalidTenderOffer);
+        let clock = Clock::get()?;
+        require!(deadline > clock.unix_timestamp, ErrorCode::InvalidTenderOffer);
+
+        approve_vault_owner(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.mint,
+            ctx.accounts.vault_allowlist_entry.as_deref_mut(),
+            ctx.bumps.vault_allowlist_entry,
+            ctx.accounts.tender_offer.key(),
+            ctx.accounts.authority.key(),
+        )?;
+        let token_config = &mut ctx.accounts.token_config;
+        let tender_offer = &mut ctx.accounts.tender_offer;
+        tender_offer.token_mint = token_config.mint;
+        tender_offer.offer_id = token_config.next_tender_offer_id;
+        tender_offer.payment_mint = ctx.accounts.payment_mint.key();
+        tender_offer.price = price;
+        tender_offer.cap = cap;
+        tender_offer.min_acceptance = min_acceptance;
+        tender_offer.deadline = deadline;
+        tender_offer.total_tendered = 0;
+        tender_offer.accepted_amount = 0;
+        tender_offer.status = TenderOfferStatus::Open;
+        tender_offer.bump = ctx.bumps.tender_offer;
+
+        token_config.next_tender_offer_id = token_config.next_tender_offer_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let funding = tender_offer.cost(cap, ctx.accounts.mint.decimals)?;
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.funding_token_account.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.payment_vault.to_account_info(),
+            authority: ctx.accounts.authority.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), funding, ctx.accounts.payment_mint.decimals)?;
+
+        emit!(TenderOfferOpenedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: tender_offer.token_mint,
+            offer_id: tender_offer.offer_id,
+            payment_mint: tender_offer.payment_mint,
+            price,
+            cap,
+            min_acceptance,
+            deadline,
+            funding,
+        });
+
+        Ok(())
+    }
+
+    /// Tender `amount` shares into an open offer's escrow
+    pub fn tender_shares<'info>(ctx: Context<'_, '_, '_, 'info, TenderShares<'info>>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let clock = Clock::get()?;
+        let tender_offer = &ctx.accounts.tender_offer;
+        require!(
+            tender_offer.status == TenderOfferStatus::Open && clock.unix_timestamp < tender_offer.deadline,
+            ErrorCode::TenderOfferClosed
+        );
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!holder_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = holder_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.holder_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.share_escrow.to_account_info(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if leaves {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let position = &mut ctx.accounts.tender_position;
+        if position.holder == Pubkey::default() {
+            position.tender_offer = ctx.accounts.tender_offer.key();
+            position.holder = ctx.accounts.holder.key();
+            position.bump = ctx.bumps.tender_position;
+        }
+        position.tendered = position.tendered.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let tender_offer = &mut ctx.accounts.tender_offer;
+        tender_offer.total_tendered = tender_offer.total_tendered.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesTenderedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: tender_offer.token_mint,
+            offer_id: tender_offer.offer_id,
+            holder: position.holder,
+            amount,
+            total_tendered: tender_offer.total_tendered,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw an offer before it closes; every tendered share will be returned
+    pub fn withdraw_tender_offer(ctx: Context<CloseTenderOffer>) -> Result<()> {
+        require!(
+            ctx.accounts.tender_offer.status == TenderOfferStatus::Open,
+            ErrorCode::TenderOfferClosed
+        );
+        ctx.accounts.tender_offer.status = TenderOfferStatus::Withdrawn;
+        finalize_tender_offer(ctx)
+    }
+
+    /// Close an offer after its deadline. Undersubscribed offers are treated as
+    /// withdrawn; otherwise tendered shares are accepted up to the cap.
+    pub fn close_tender_offer(ctx: Context<CloseTenderOffer>) -> Result<()> {
+        let clock = Clock::get()?;
+        let tender_offer = &mut ctx.accounts.tender_offer;
+        require!(tender_offer.status == TenderOfferStatus::Open, ErrorCode::TenderOfferClosed);
+        require!(clock.unix_timestamp >= tender_offer.deadline, ErrorCode::TenderOfferActive);
+
+        if tender_offer.total_tendered < tender_offer.min_acceptance.max(1) {
+            tender_offer.status = TenderOfferStatus::Withdrawn;
+        } else {
+            tender_offer.status = TenderOfferStatus::Settled;
+            tender_offer.accepted_amount = tender_offer.total_tendered.min(tender_offer.cap);
+        }
+        finalize_tender_offer(ctx)
+    }
+
+    /// Settle one holder's position in a closed offer: accepted shares move to the
+    /// treasury and are paid for, the rest are returned to the holder
+    pub fn settle_tender<'info>(ctx: Context<'_, '_, '_, 'info, SettleTender<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let tender_offer = &ctx.accounts.tender_offer;
+        require!(tender_offer.status != TenderOfferStatus::Open, ErrorCode::TenderOfferActive);
+
+        let tendered = ctx.accounts.tender_position.tendered;
+        let accepted = tender_offer.accepted_share(tendered)?;
+        let returned = tendered.checked_sub(accepted).ok_or(ErrorCode::Overflow)?;
+        let payout = tender_offer.cost(accepted, ctx.accounts.mint.decimals)?;
+
+        let id_bytes = tender_offer.offer_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"tender_offer",
+            tender_offer.token_mint.as_ref(),
+            &id_bytes,
+            &[tender_offer.bump],
+        ];
+        let signer_seeds = &[seeds];
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+
+        if accepted > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.share_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.treasury_token_account.to_account_info(),
+                authority: tender_offer.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+            transfer_checked_with_hook(cpi_ctx, accepted, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+        }
+
+        if payout > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.holder_payment_account.to_account_info(),
+                authority: tender_offer.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+            token::transfer_checked(cpi_ctx, payout, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        if returned > 0 {
+            let rejoins = ctx.accounts.holder_token_account.amount == 0;
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.holder_allowlist_entry,
+                ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+                ctx.bumps.holder_snapshot_balance,
+                ctx.accounts.holder_token_account.amount,
+            )?;
+            let holder_balance = ctx.accounts.holder_token_account.amount.checked_add(returned)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: None,
+                    last_number: None,
+                    joining_number: ctx.accounts.holder_number.as_deref(),
+                    payer: &ctx.accounts.cranker.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                None,
+                Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.holder_allowlist_entry,
+                &ctx.accounts.holder_token_account.key(),
+                ctx.accounts.holder_page.as_ref(),
+                holder_balance,
+            )?;
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.share_escrow.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.holder_token_account.to_account_info(),
+                authority: tender_offer.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            transfer_checked_with_hook(cpi_ctx, returned, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+            if rejoins {
+                let token_config = &mut ctx.accounts.token_config;
+                token_config.holder_count = token_config.holder_count.checked_add(1)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        emit!(TenderSettledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.tender_offer.token_mint,
+            offer_id: ctx.accounts.tender_offer.offer_id,
+            holder: ctx.accounts.holder.key(),
+            accepted,
+            returned,
+            payout,
+        });
+
+        Ok(())
+    }
+
+    /// Record a convertible note for `noteholder`. `principal` and `valuation_cap` are in
+    /// pricing currency base units (e.g. USDC); `discount_bps` applies to the price of
+    /// the financing round that triggers conversion.
+    pub fn issue_convertible_note(
+        ctx: Context<IssueConvertibleNote>,
+        principal: u64,
+        discount_bps: u16,
+        valuation_cap: u64,
+        maturity: i64,
+    ) -> Result<()> {
+        require!(principal > 0, ErrorCode::InvalidConvertibleNote);
+        require!(discount_bps < 10_000, ErrorCode::InvalidConvertibleNote);
+        let clock = Clock::get()?;
+        require!(maturity > clock.unix_timestamp, ErrorCode::InvalidConvertibleNote);
+
+        let token_config = &mut ctx.accounts.token_config;
+        let note = &mut ctx.accounts.convertible_note;
+        note.token_mint = token_config.mint;
+        note.note_id = token_config.next_note_id;
+        note.noteholder = ctx.accounts.noteholder.key();
+        note.principal = principal;
+        note.discount_bps = discount_bps;
+        note.valuation_cap = valuation_cap;
+        note.maturity = maturity;
+        note.issued_at = clock.unix_timestamp;
+        note.bump = ctx.bumps.convertible_note;
+
+        token_config.next_note_id = token_config.next_note_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ConvertibleNoteIssuedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: note.token_mint,
+            note_id: note.note_id,
+            noteholder: note.noteholder,
+            principal,
+            discount_bps,
+            valuation_cap,
+            maturity,
+        });
+
+        Ok(())
+    }
+
+    /// Convert a note into shares minted to the noteholder and close it. Shares are
+    /// priced at the lower of the discounted round price and the valuation cap price,
+    /// rounded down to whole lots.
+    pub fn convert_note(ctx: Context<ConvertNote>, trigger: ConversionTrigger) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let clock = Clock::get()?;
+
+        let noteholder_entry = &ctx.accounts.noteholder_allowlist_entry;
+        require!(noteholder_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            noteholder_entry.permits_token_account(&ctx.accounts.noteholder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let note = &ctx.accounts.convertible_note;
+        if trigger == ConversionTrigger::Maturity {
+            require!(clock.unix_timestamp >= note.maturity, ErrorCode::NoteNotConvertible);
+        }
+        let decimals = ctx.accounts.mint.decimals;
+        let conversion_price = note.conversion_price(trigger, ctx.accounts.mint.supply, decimals)?;
+        let lot_size = ctx.accounts.token_config.lot_size.max(1);
+        let shares = note.shares_at(conversion_price, decimals)?;
+        let amount = shares.checked_sub(shares % lot_size).ok_or(ErrorCode::Overflow)?;
+        require!(amount > 0, ErrorCode::NoteNotConvertible);
+        ctx.accounts.token_config.validate_lot(amount)?;
+
+        let is_new_holder = ctx.accounts.noteholder_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.noteholder_allowlist_entry,
+            ctx.accounts.noteholder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.noteholder_snapshot_balance,
+            ctx.accounts.noteholder_token_account.amount,
+        )?;
+        let noteholder_balance = ctx.accounts.noteholder_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.noteholder_number.as_deref(),
+                payer: &ctx.accounts.authority.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.noteholder_allowlist_entry, &ctx.accounts.noteholder_token_account.key(), noteholder_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.noteholder_allowlist_entry,
+            &ctx.accounts.noteholder_token_account.key(),
+            ctx.accounts.noteholder_page.as_ref(),
+            noteholder_balance,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.noteholder_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let note = &ctx.accounts.convertible_note;
+        emit!(NoteConvertedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: note.token_mint,
+            note_id: note.note_id,
+            noteholder: note.noteholder,
+            trigger,
+            principal: note.principal,
+            conversion_price,
+            shares_minted: amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Issue a warrant giving `holder` the right to buy `quantity` raw token units at
+    /// `strike_price` (payment mint base units per whole share) until `expires_at`
+    pub fn issue_warrant(
+        ctx: Context<IssueWarrant>,
+        quantity: u64,
+        strike_price: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(quantity > 0, ErrorCode::InvalidAmount);
+        ctx.accounts.token_config.validate_lot(quantity)?;
+        let clock = Clock::get()?;
+        require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidWarrant);
+
+        let token_config = &mut ctx.accounts.token_config;
+        let warrant = &mut ctx.accounts.warrant;
+        warrant.token_mint = token_config.mint;
+        warrant.warrant_id = token_config.next_warrant_id;
+        warrant.holder = ctx.accounts.holder.key();
+        warrant.quantity = quantity;
+        warrant.exercised = 0;
+        warrant.strike_price = strike_price;
+        warrant.payment_mint = ctx.accounts.payment_mint.key();
+        warrant.expires_at = expires_at;
+        warrant.bump = ctx.bumps.warrant;
+
+        token_config.next_warrant_id = token_config.next_warrant_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(WarrantIssuedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: warrant.token_mint,
+            warrant_id: warrant.warrant_id,
+            holder: warrant.holder,
+            quantity,
+            strike_price,
+            payment_mint: warrant.payment_mint,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Exercise `amount` of a warrant: the strike is paid into the issuer treasury and
+    /// the shares are minted to the holder
+    pub fn exercise_warrant(ctx: Context<ExerciseWarrant>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        ctx.accounts.token_config.validate_lot(amount)?;
+
+        let clock = Clock::get()?;
+        let warrant = &ctx.accounts.warrant;
+        require!(clock.unix_timestamp < warrant.expires_at, ErrorCode::WarrantExpired);
+        require!(amount <= warrant.remaining(), ErrorCode::WarrantQuantityExceeded);
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let cost = warrant.exercise_cost(amount, ctx.accounts.mint.decimals)?;
+        if cost > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.holder_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.treasury_payment_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), cost, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        let is_new_holder = ctx.accounts.holder_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.holder_number.as_deref(),
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.holder_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let warrant = &mut ctx.accounts.warrant;
+        warrant.exercised = warrant.exercised.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(WarrantExercisedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: warrant.token_mint,
+            warrant_id: warrant.warrant_id,
+            holder: warrant.holder,
+            amount,
+            cost,
+            exercised: warrant.exercised,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close a warrant that has expired or been fully exercised. Permissionless so
+    /// lapsed warrants can be swept by anyone; rent returns to the authority.
+    pub fn close_warrant(ctx: Context<CloseWarrant>) -> Result<()> {
+        let clock = Clock::get()?;
+        let warrant = &ctx.accounts.warrant;
+        require!(
+            clock.unix_timestamp >= warrant.expires_at || warrant.remaining() == 0,
+            ErrorCode::WarrantActive
+        );
+
+        emit!(WarrantClosedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: warrant.token_mint,
+            warrant_id: warrant.warrant_id,
+            holder: warrant.holder,
+            lapsed: warrant.remaining(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Enable the on-chain cap table. From then on every balance change of an allowlisted
+    /// wallet must pass the wallet's holder page, and wallets must have a bound token
+    /// account so one balance is recorded per holder.
+    pub fn initialize_cap_table(ctx: Context<InitializeCapTable>) -> Result<()> {
+        let cap_table = &mut ctx.accounts.cap_table;
+        cap_table.token_mint = ctx.accounts.token_config.mint;
+        cap_table.next_page_index = 0;
+        cap_table.bump = ctx.bumps.cap_table;
+
+        ctx.accounts.token_config.cap_table = cap_table.key();
+
+        emit!(CapTableInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: cap_table.token_mint,
+            cap_table: cap_table.key(),
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Enable the holder index. Balance changes that move an allowlisted wallet across zero
+    /// must then pass its holder number accounts, and existing holders are numbered with
+    /// `index_holder` before their balances next change.
+    pub fn initialize_holder_index(ctx: Context<InitializeHolderIndex>) -> Result<()> {
+        ctx.accounts.token_config.require_current_version()?;
+        let holder_index = &mut ctx.accounts.holder_index;
+        holder_index.token_mint = ctx.accounts.token_config.mint;
+        holder_index.len = 0;
+        holder_index.bump = ctx.bumps.holder_index;
+
+        ctx.accounts.token_config.holder_index = holder_index.key();
+
+        emit!(HolderIndexInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: holder_index.token_mint,
+            holder_index: holder_index.key(),
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Number a wallet that already held shares when the holder index was enabled.
+    /// Permissionless; the payer funds the number account.
+    pub fn index_holder(ctx: Context<IndexHolder>) -> Result<()> {
+        let entry = &mut ctx.accounts.allowlist_entry;
+        entry.require_current_version()?;
+        require!(!entry.holder_indexed, ErrorCode::HolderAlreadyIndexed);
+        require!(
+            entry.approved_token_account == Some(ctx.accounts.token_account.key()),
+            ErrorCode::TokenAccountBindingRequired
+        );
+        require!(ctx.accounts.token_account.amount > 0, ErrorCode::InvalidAmount);
+
+        assign_holder_number(
+            &mut ctx.accounts.token_config,
+            &mut ctx.accounts.holder_index,
+            entry,
+            Some(&ctx.accounts.holder_number.to_account_info()),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )
+    }
+
+    /// Back a token's share certificates with a Bubblegum tree. `merkle_tree` must be
+    /// allocated to the compression program beforehand, sized for `max_depth` and
+    /// `max_buffer_size`. The tree is private, with the `CertificateTree` PDA as its
+    /// creator, so only this program mints into it. Every certificate carries `legend`,
+    /// and its off-chain metadata is served at `uri` followed by `/<holder>`.
+    pub fn init_certificate_tree(
+        ctx: Context<InitCertificateTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+        legend: String,
+        uri: String,
+    ) -> Result<()> {
+        ctx.accounts.token_config.require_current_version()?;
+        require!(
+            !legend.is_empty() && legend.len() <= MAX_CERTIFICATE_LEGEND_LEN,
+            ErrorCode::InvalidCertificateLegend
+        );
+        require!(!uri.is_empty() && uri.len() <= MAX_CERTIFICATE_URI_LEN, ErrorCode::InvalidMetadataUri);
+
+        let certificate_tree = &mut ctx.accounts.certificate_tree;
+        certificate_tree.token_mint = ctx.accounts.token_config.mint;
+        certificate_tree.merkle_tree = ctx.accounts.merkle_tree.key();
+        certificate_tree.legend = legend;
+        certificate_tree.uri = uri;
+        certificate_tree.next_nonce = 0;
+        certificate_tree.bump = ctx.bumps.certificate_tree;
+
+        // Not public: minting needs the tree creator's signature
+        let args = [&max_depth.to_le_bytes()[..], &max_buffer_size.to_le_bytes()[..], &[1, 0]].concat();
+        call_bubblegum(
+            "create_tree",
+            &args,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                certificate_tree.to_account_info(),
+                ctx.accounts.noop_program.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &ctx.accounts.bubblegum_program,
+            certificate_tree,
+            &[],
+        )?;
+
+        emit!(CertificateTreeInitializedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: certificate_tree.token_mint,
+            merkle_tree: certificate_tree.merkle_tree,
+            max_depth,
+            max_buffer_size,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Mint a holder's share certificate into the token's certificate tree: a compressed
+    /// NFT owned by the holder, named after their share class and dated now, whose record
+    /// keeps the restriction legend. Send it with the holder's first issuance. Needs
+    /// `SHARE_CERTIFICATES`; permissionless, with the payer funding the record. A holder
+    /// has one certificate until `retire_share_certificate` burns it.
+    pub fn issue_share_certificate(ctx: Context<IssueShareCertificate>) -> Result<()> {
+        require!(
+            ctx.accounts.token_config.features.contains(FeatureFlags::SHARE_CERTIFICATES),
+            ErrorCode::ShareCertificatesDisabled
+        );
+        let entry = &ctx.accounts.allowlist_entry;
+        require!(
+            entry.approved_token_account == Some(ctx.accounts.token_account.key()),
+            ErrorCode::TokenAccountBindingRequired
+        );
+        require!(ctx.accounts.token_account.amount > 0, ErrorCode::InvalidAmount);
+
+        let certificate_tree = &mut ctx.accounts.certificate_tree;
+        let nonce = certificate_tree.next_nonce;
+        let certificate = &mut ctx.accounts.share_certificate;
+        certificate.token_mint = certificate_tree.token_mint;
+        certificate.holder = entry.wallet;
+        certificate.asset_id = Pubkey::find_program_address(
+            &[b"asset", certificate_tree.merkle_tree.as_ref(), &nonce.to_le_bytes()],
+            &BUBBLEGUM_ID,
+        ).0;
+        certificate.nonce = nonce;
+        certificate.class = ctx.accounts.share_class.as_ref()
+            .map_or_else(|| DEFAULT_CERTIFICATE_CLASS.to_string(), |class| class.name.clone());
+        certificate.symbol = ctx.accounts.token_config.symbol.clone();
+        certificate.uri = format!("{}/{}", certificate_tree.uri, entry.wallet);
+        certificate.legend = certificate_tree.legend.clone();
+        certificate.issued_at = Clock::get()?.unix_timestamp;
+        certificate.rent_payer = ctx.accounts.payer.key();
+        certificate.bump = ctx.bumps.share_certificate;
+
+        // The certificate tree stays the leaf's delegate, so it can burn it on exit
+        call_bubblegum(
+            "mint_v1",
+            &certificate.metadata().try_to_vec()?,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.holder.to_account_info(),
+                certificate_tree.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.payer.to_account_info(),
+                certificate_tree.to_account_info(),
+                ctx.accounts.noop_program.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &ctx.accounts.bubblegum_program,
+            certificate_tree,
+            &[],
+        )?;
+        certificate_tree.next_nonce = nonce.checked_add(1).ok_or(ErrorCode::Overflow)?;
+
+        emit!(ShareCertificateIssuedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: certificate.token_mint,
+            holder: certificate.holder,
+            asset_id: certificate.asset_id,
+            class: certificate.class.clone(),
+            issued_at: certificate.issued_at,
+        });
+
+        Ok(())
+    }
+
+    /// Burn the certificate of a holder whose bound token account is empty and close its
+    /// record to whoever funded it. Permissionless; the proof of the certificate's leaf
+    /// under `root` goes in the remaining accounts. The burn is signed as the leaf's
+    /// delegate, which Bubblegum resets when the holder moves the certificate, so a
+    /// certificate moved out of the holder's wallet cannot be retired.
+    pub fn retire_share_certificate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RetireShareCertificate<'info>>,
+        root: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.allowlist_entry.approved_token_account == Some(ctx.accounts.token_account.key()),
+            ErrorCode::TokenAccountBindingRequired
+        );
+        require!(ctx.accounts.token_account.amount == 0, ErrorCode::HolderNotExited);
+
+        let certificate = &ctx.accounts.share_certificate;
+        let index = u32::try_from(certificate.nonce).map_err(|_| ErrorCode::Overflow)?;
+        let args = [
+            &root[..],
+            &certificate.data_hash()?[..],
+            &certificate.creator_hash()[..],
+            &certificate.nonce.to_le_bytes()[..],
+            &index.to_le_bytes()[..],
+        ]
+        .concat();
+        call_bubblegum(
+            "burn",
+            &args,
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.holder.to_account_info(),
+                ctx.accounts.certificate_tree.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.noop_program.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            &ctx.accounts.bubblegum_program,
+            &ctx.accounts.certificate_tree,
+            ctx.remaining_accounts,
+        )?;
+
+        emit!(ShareCertificateRetiredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: certificate.token_mint,
+            holder: certificate.holder,
+            asset_id: certificate.asset_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Append an empty page to the cap table
+    pub fn create_holder_page(ctx: Context<CreateHolderPage>) -> Result<()> {
+        let cap_table = &mut ctx.accounts.cap_table;
+        let mut holder_page = ctx.accounts.holder_page.load_init()?;
+        holder_page.token_mint = cap_table.token_mint;
+        holder_page.page_index = cap_table.next_page_index;
+        holder_page.bump = ctx.bumps.holder_page;
+
+        cap_table.next_page_index = cap_table.next_page_index.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(HolderPageCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: holder_page.token_mint,
+            page_index: holder_page.page_index,
+        });
+
+        Ok(())
+    }
+
+    /// Record a wallet's current balance in the cap table, e.g. for holders that
+    /// predate it or after its bound token account changed. Permissionless.
+    pub fn sync_holder(ctx: Context<SyncHolder>) -> Result<()> {
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.allowlist_entry,
+            &ctx.accounts.token_account.key(),
+            Some(&ctx.accounts.holder_page),
+            ctx.accounts.token_account.amount,
+        )
+    }
+
+    /// Move the holder records of the allowlist entries passed as remaining accounts
+    /// from `source_page` to `destination_page`, so sparse pages can be emptied
+    pub fn compact_holder_pages(ctx: Context<CompactHolderPages>) -> Result<()> {
+        let mut source_page = ctx.accounts.source_page.load_mut()?;
+        let mut destination_page = ctx.accounts.destination_page.load_mut()?;
+        require!(
+            source_page.page_index != destination_page.page_index,
+            ErrorCode::InvalidHolderPage
+        );
+
+        let mint = ctx.accounts.token_config.mint;
+        for info in ctx.remaining_accounts.iter() {
+            require_keys_eq!(*info.owner, crate::ID, ErrorCode::InvalidHolderPage);
+            require!(info.is_writable, ErrorCode::InvalidHolderPage);
+            let mut data = info.try_borrow_mut_data()?;
+            let mut entry = AllowlistEntry::try_deserialize(&mut &data[..])?;
+            let expected = Pubkey::create_program_address(
+                &[b"allowlist", mint.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidHolderPage)?;
+            require_keys_eq!(expected, *info.key, ErrorCode::InvalidHolderPage);
+            require!(entry.holder_page == Some(source_page.page_index), ErrorCode::InvalidHolderPage);
+
+            let position = source_page.position(&entry.wallet)
+                .ok_or(ErrorCode::InvalidHolderPage)?;
+            let record = source_page.swap_remove(position);
+            destination_page.push(record)?;
+            entry.holder_page = Some(destination_page.page_index);
+            entry.try_serialize(&mut &mut data[..])?;
+        }
+
+        emit!(HolderPagesCompactedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint,
+            source_page: source_page.page_index,
+            destination_page: destination_page.page_index,
+            moved: ctx.remaining_accounts.len() as u32,
+            remaining: source_page.len,
+        });
+
+        Ok(())
+    }
+
+    /// Close an empty cap table page and reclaim its rent
+    pub fn close_holder_page(ctx: Context<CloseHolderPage>) -> Result<()> {
+        let holder_page = ctx.accounts.holder_page.load()?;
+        require!(holder_page.len == 0, ErrorCode::HolderPageNotEmpty);
+
+        emit!(HolderPageClosedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: holder_page.token_mint,
+            page_index: holder_page.page_index,
+        });
+
+        Ok(())
+    }
+
+    /// Set the issuer's right-of-first-refusal window. While non-zero, every
+    /// `gated_transfer` must settle a `SaleProposal` whose window has lapsed unexercised.
+    pub fn set_rofr_period(ctx: Context<UpdateTokenSettings>, period_seconds: i64) -> Result<()> {
+        require!(period_seconds >= 0, ErrorCode::InvalidRofrPeriod);
+        let token_config = &mut ctx.accounts.token_config;
+        let old_period = token_config.rofr_period_seconds;
+        token_config.rofr_period_seconds = period_seconds;
+
+        let clock = Clock::get()?;
+
+        emit!(RofrPeriodUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_period,
+            new_period: period_seconds,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Post a proposed secondary sale of `amount` shares to `buyer` at `price` (payment
+    /// mint base units per whole share), opening the issuer's ROFR window
+    pub fn propose_sale(
+        ctx: Context<ProposeSale>,
+        buyer: Pubkey,
+        amount: u64,
+        price: u64,
+    ) -> Result<()> {
+        let token_config = &ctx.accounts.token_config;
+        require!(token_config.rofr_period_seconds > 0, ErrorCode::RofrNotEnabled);
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        token_config.validate_lot(amount)?;
+        require!(ctx.accounts.seller_allowlist_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(
+            ctx.accounts.seller_token_account.amount >= amount,
+            ErrorCode::InsufficientBalance
+        );
+
+        let clock = Clock::get()?;
+        let expires_at = clock.unix_timestamp.checked_add(token_config.rofr_period_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let proposal = &mut ctx.accounts.sale_proposal;
+        proposal.token_mint = token_config.mint;
+        proposal.seller = ctx.accounts.seller.key();
+        proposal.buyer = buyer;
+        proposal.amount = amount;
+        proposal.price = price;
+        proposal.payment_mint = ctx.accounts.payment_mint.key();
+        proposal.posted_at = clock.unix_timestamp;
+        proposal.expires_at = expires_at;
+        proposal.status = SaleProposalStatus::Pending;
+        proposal.bump = ctx.bumps.sale_proposal;
+
+        emit!(SaleProposedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            seller: proposal.seller,
+            buyer,
+            amount,
+            price,
+            payment_mint: proposal.payment_mint,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a pending sale proposal
+    pub fn withdraw_sale_proposal(ctx: Context<WithdrawSaleProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.sale_proposal;
+        require!(proposal.status == SaleProposalStatus::Pending, ErrorCode::RofrExercised);
+
+        emit!(SaleProposalWithdrawnEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            seller: proposal.seller,
+            buyer: proposal.buyer,
+            amount: proposal.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Match a pending sale within the ROFR window. The original sale can no longer
+    /// settle; the seller completes the sale to the issuer with `settle_rofr`.
+    pub fn exercise_rofr(ctx: Context<ExerciseRofr>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.sale_proposal;
+        require!(proposal.status == SaleProposalStatus::Pending, ErrorCode::RofrExercised);
+        require!(clock.unix_timestamp < proposal.expires_at, ErrorCode::RofrPeriodEnded);
+        proposal.status = SaleProposalStatus::Exercised;
+
+        emit!(RofrExercisedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            seller: proposal.seller,
+            amount: proposal.amount,
+            price: proposal.price,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Complete an exercised ROFR: the seller's shares move to the issuer treasury and
+    /// the matched price is paid from the treasury's payment account
+    pub fn settle_rofr<'info>(ctx: Context<'_, '_, '_, 'info, SettleRofr<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        let proposal = &ctx.accounts.sale_proposal;
+        require!(proposal.status == SaleProposalStatus::Exercised, ErrorCode::RofrNotExercised);
+
+        // Both sides of the sale are screened as at any transfer
+        let seller_entry = &ctx.accounts.seller_allowlist_entry;
+        require!(seller_entry.is_approved, ErrorCode::SenderNotApproved);
+        screen_transfer_party(&ctx.accounts.token_config, &TransferParty {
+            wallet: ctx.accounts.seller.key(),
+            entry: Some(seller_entry),
+            country_rule: ctx.accounts.seller_country_rule.as_deref(),
+            sanctions_flag: ctx.accounts.seller_sanctions_flag.as_ref(),
+        })?;
+        screen_transfer_party(&ctx.accounts.token_config, &TransferParty {
+            wallet: ctx.accounts.treasury.key(),
+            entry: None,
+            country_rule: None,
+            sanctions_flag: ctx.accounts.treasury_sanctions_flag.as_ref(),
+        })?;
+        let amount = proposal.amount;
+        let payment = proposal.cost(ctx.accounts.mint.decimals)?;
+
+        let leaves = ctx.accounts.seller_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.seller_allowlist_entry,
+            ctx.accounts.seller_snapshot_balance.as_deref_mut(),
+            ctx.bumps.seller_snapshot_balance,
+            ctx.accounts.seller_token_account.amount,
+        )?;
+        let seller_balance = ctx.accounts.seller_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.seller_holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.seller.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.seller_allowlist_entry, &ctx.accounts.seller_token_account.key(), seller_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.seller_allowlist_entry,
+            &ctx.accounts.seller_token_account.key(),
+            ctx.accounts.seller_holder_page.as_ref(),
+            seller_balance,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.seller_token_account.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.treasury_token_account.to_account_info(),
+            authority: ctx.accounts.seller.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        transfer_checked_with_hook(CpiContext::new(cpi_program, cpi_accounts), amount, ctx.accounts.mint.decimals, ctx.remaining_accounts)?;
+
+        if payment > 0 {
+            let mint_key = ctx.accounts.mint.key();
+            let seeds: &[&[u8]] = &[b"treasury", mint_key.as_ref(), &[ctx.bumps.treasury]];
+            let signer_seeds = &[seeds];
+
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.treasury_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.seller_payment_account.to_account_info(),
+                authority: ctx.accounts.treasury.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token::transfer_checked(cpi_ctx, payment, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        if leaves {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(RofrSettledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.sale_proposal.token_mint,
+            seller: ctx.accounts.seller.key(),
+            amount,
+            payment,
+        });
+
+        Ok(())
+    }
+
+    /// Offer `new_shares` raw token units to holders of record at `snapshot`, pro-rata to
+    /// their snapshot balances, at `price` (payment mint base units per whole share)
+    /// until `deadline`. Rights not subscribed by the deadline lapse; if
+    /// `allow_oversubscription` is set they are reallocated to holders who asked for more.
+    pub fn open_rights_offering(
+        ctx: Context<OpenRightsOffering>,
+        new_shares: u64,
+        price: u64,
+        deadline: i64,
+        allow_oversubscription: bool,
+    ) -> Result<()> {
+        require!(new_shares > 0, ErrorCode::InvalidRightsOffering);
+        require!(ctx.accounts.snapshot.total_supply > 0, ErrorCode::InvalidRightsOffering);
+        let clock = Clock::get()?;
+        require!(deadline > clock.unix_timestamp, ErrorCode::InvalidRightsOffering);
+
+        let token_config = &mut ctx.accounts.token_config;
+        let offering = &mut ctx.accounts.rights_offering;
+        offering.token_mint = token_config.mint;
+        offering.offering_id = token_config.next_rights_offering_id;
+        offering.snapshot_id = ctx.accounts.snapshot.snapshot_id;
+        offering.snapshot_supply = ctx.accounts.snapshot.total_supply;
+        offering.new_shares = new_shares;
+        offering.price = price;
+        offering.payment_mint = ctx.accounts.payment_mint.key();
+        offering.deadline = deadline;
+        offering.allow_oversubscription = allow_oversubscription;
+        offering.total_subscribed = 0;
+        offering.total_oversubscription = 0;
+        offering.bump = ctx.bumps.rights_offering;
+
+        token_config.next_rights_offering_id = token_config.next_rights_offering_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(RightsOfferingOpenedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: offering.token_mint,
+            offering_id: offering.offering_id,
+            snapshot_id: offering.snapshot_id,
+            new_shares,
+            price,
+            payment_mint: offering.payment_mint,
+            deadline,
+            allow_oversubscription,
+        });
+
+        Ok(())
+    }
+
+    /// Subscribe `amount` of the holder's pro-rata rights and optionally request
+    /// `oversubscription` extra shares, escrowing the full price for both
+    pub fn subscribe_rights(
+        ctx: Context<SubscribeRights>,
+        amount: u64,
+        oversubscription: u64,
+    ) -> Result<()> {
+        require!(amount > 0 || oversubscription > 0, ErrorCode::InvalidAmount);
+        let clock = Clock::get()?;
+        let offering = &ctx.accounts.rights_offering;
+        require!(clock.unix_timestamp < offering.deadline, ErrorCode::RightsOfferingClosed);
+        require!(
+            oversubscription == 0 || offering.allow_oversubscription,
+            ErrorCode::OversubscriptionNotAllowed
+        );
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::WalletNotApproved);
+
+        let subscription = &mut ctx.accounts.subscription;
+        if subscription.holder == Pubkey::default() {
+            let record_balance = ctx.accounts.snapshot.balance_of(
+                holder_entry,
+                ctx.accounts.holder_token_account.amount,
+                ctx.accounts.holder_snapshot_balance.as_deref().map(|checkpoint| &**checkpoint),
+            )?;
+            subscription.rights_offering = offering.key();
+            subscription.holder = ctx.accounts.holder.key();
+            subscription.entitlement = offering.entitlement(
+                record_balance,
+                ctx.accounts.token_config.lot_size,
+            )?;
+            subscription.bump = ctx.bumps.subscription;
+        }
+
+        let subscribed = subscription.subscribed.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(subscribed <= subscription.entitlement, ErrorCode::RightsEntitlementExceeded);
+        let lot_size = ctx.accounts.token_config.lot_size.max(1);
+        require!(
+            amount.checked_rem(lot_size) == Some(0) && oversubscription.checked_rem(lot_size) == Some(0),
+            ErrorCode::InvalidLotMultiple
+        );
+        let requested = subscription.oversubscription.checked_add(oversubscription)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let payment = offering.cost(
+            amount.checked_add(oversubscription).ok_or(ErrorCode::Overflow)?,
+            ctx.accounts.mint.decimals,
+        )?;
+        if payment > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.holder_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.payment_vault.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            token::transfer_checked(CpiContext::new(cpi_program, cpi_accounts), payment, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        subscription.subscribed = subscribed;
+        subscription.oversubscription = requested;
+        subscription.paid = subscription.paid.checked_add(payment)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let offering = &mut ctx.accounts.rights_offering;
+        offering.total_subscribed = offering.total_subscribed.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        offering.total_oversubscription = offering.total_oversubscription.checked_add(oversubscription)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(RightsSubscribedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: offering.token_mint,
+            offering_id: offering.offering_id,
+            holder: subscription.holder,
+            amount,
+            oversubscription,
+            entitlement: subscription.entitlement,
+            payment,
+        });
+
+        Ok(())
+    }
+
+    /// After the deadline, mint a subscriber's allotment (their subscribed rights plus any
+    /// oversubscription allocation), refund payment for shares not allotted and move
+    /// the proceeds to the treasury
+    pub fn settle_rights_subscription(ctx: Context<SettleRightsSubscription>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let clock = Clock::get()?;
+        let offering = &ctx.accounts.rights_offering;
+        require!(clock.unix_timestamp >= offering.deadline, ErrorCode::RightsOfferingActive);
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let subscription = &ctx.accounts.subscription;
+        let extra = offering.oversubscription_allocation(
+            subscription.oversubscription,
+            ctx.accounts.token_config.lot_size,
+        )?;
+        let allotted = subscription.subscribed.checked_add(extra)
+            .ok_or(ErrorCode::Overflow)?;
+        let proceeds = offering.cost(allotted, ctx.accounts.mint.decimals)?
+            .min(subscription.paid);
+        let refund = subscription.paid - proceeds;
+
+        let id_bytes = offering.offering_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"rights_offering",
+            offering.token_mint.as_ref(),
+            &id_bytes,
+            &[offering.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        if proceeds > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.treasury_payment_account.to_account_info(),
+                authority: offering.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token::transfer_checked(cpi_ctx, proceeds, ctx.accounts.payment_mint.decimals)?;
+        }
+        if refund > 0 {
+            let cpi_accounts = TransferChecked {
+                from: ctx.accounts.payment_vault.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.holder_payment_account.to_account_info(),
+                authority: offering.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            token::transfer_checked(cpi_ctx, refund, ctx.accounts.payment_mint.decimals)?;
+        }
+
+        if allotted > 0 {
+            let is_new_holder = ctx.accounts.holder_token_account.amount == 0;
+            enforce_share_class(
+                &ctx.accounts.token_config,
+                ctx.accounts.share_class.as_ref(),
+                i64::from(is_new_holder),
+                false,
+            )?;
+            checkpoint_snapshot_balance(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.holder_allowlist_entry,
+                ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+                ctx.bumps.holder_snapshot_balance,
+                ctx.accounts.holder_token_account.amount,
+            )?;
+            let holder_balance = ctx.accounts.holder_token_account.amount.checked_add(allotted)
+                .ok_or(ErrorCode::Overflow)?;
+            sync_holder_index(
+                &mut ctx.accounts.token_config,
+                HolderIndexAccounts {
+                    holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                    leaving_number: None,
+                    last_number: None,
+                    joining_number: ctx.accounts.holder_number.as_deref(),
+                    payer: &ctx.accounts.authority.to_account_info(),
+                    system_program: &ctx.accounts.system_program.to_account_info(),
+                },
+                None,
+                Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            )?;
+            sync_cap_table(
+                &ctx.accounts.token_config,
+                &mut ctx.accounts.holder_allowlist_entry,
+                &ctx.accounts.holder_token_account.key(),
+                ctx.accounts.holder_page.as_ref(),
+                holder_balance,
+            )?;
+
+            mint_with_program_authority(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                ctx.accounts.holder_token_account.to_account_info(),
+                &ctx.accounts.mint_authority,
+                ctx.bumps.mint_authority,
+                allotted,
+            )?;
+
+            let token_config = &mut ctx.accounts.token_config;
+            record_minted_supply(token_config, allotted)?;
+            if is_new_holder {
+                token_config.holder_count = token_config.holder_count.checked_add(1)
+                    .ok_or(ErrorCode::Overflow)?;
+            }
+        }
+
+        emit!(RightsSettledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.rights_offering.token_mint,
+            offering_id: ctx.accounts.rights_offering.offering_id,
+            holder: ctx.accounts.subscription.holder,
+            allotted,
+            oversubscription_allotted: extra,
+            proceeds,
+            refund,
+        });
+
+        Ok(())
+    }
+
+    /// Start a pro-rata issuance of `issuance_amount` raw token units to the holders of
+    /// record at the current snapshot. Holders are minted their share by
+    /// `crank_allocation`; rounding dust goes to the treasury on `finalize_allocation`.
+    pub fn open_allocation(ctx: Context<OpenAllocation>, issuance_amount: u64) -> Result<()> {
+        require!(issuance_amount > 0, ErrorCode::InvalidAmount);
+        let token_config = &mut ctx.accounts.token_config;
+        require!(!token_config.allocation_active, ErrorCode::AllocationActive);
+        let snapshot = &ctx.accounts.snapshot;
+        require!(
+            snapshot.snapshot_id == token_config.current_snapshot_id && snapshot.total_supply > 0,
+            ErrorCode::AllocationSnapshotStale
+        );
+
+        token_config.last_allocation_id = token_config.last_allocation_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        token_config.allocation_active = true;
+
+        let allocation = &mut ctx.accounts.allocation;
+        allocation.token_mint = token_config.mint;
+        allocation.allocation_id = token_config.last_allocation_id;
+        allocation.snapshot_id = snapshot.snapshot_id;
+        allocation.snapshot_supply = snapshot.total_supply;
+        allocation.issuance_amount = issuance_amount;
+        allocation.allocated_amount = 0;
+        allocation.holders_processed = 0;
+        allocation.finalized = false;
+        allocation.bump = ctx.bumps.allocation;
+
+        emit!(AllocationOpenedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: allocation.token_mint,
+            allocation_id: allocation.allocation_id,
+            snapshot_id: allocation.snapshot_id,
+            snapshot_supply: allocation.snapshot_supply,
+            issuance_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Mint pro-rata allocations to a batch of holders, passed as groups of
+    /// `ALLOCATION_ACCOUNTS_PER_HOLDER` remaining accounts. Holders already processed are
+    /// skipped; revoked holders are marked processed and their share left to the dust.
+    pub fn crank_allocation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankAllocation<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let allocation = &ctx.accounts.allocation;
+        require!(!allocation.finalized, ErrorCode::AllocationFinalized);
+        require!(
+            ctx.accounts.token_config.current_snapshot_id == allocation.snapshot_id,
+            ErrorCode::AllocationSnapshotStale
+        );
+        require!(
+            ctx.remaining_accounts.len().checked_rem(ALLOCATION_ACCOUNTS_PER_HOLDER) == Some(0),
+            ErrorCode::InvalidAllocationAccounts
+        );
+
+        let mint_key = ctx.accounts.mint.key();
+        let allocation_id = allocation.allocation_id;
+        let snapshot_id = allocation.snapshot_id;
+        let lot_size = ctx.accounts.token_config.lot_size;
+        let mut batch_amount: u64 = 0;
+        let mut batch_holders: u64 = 0;
+
+        for accounts in ctx.remaining_accounts.chunks(ALLOCATION_ACCOUNTS_PER_HOLDER) {
+            require!(accounts[0].is_writable, ErrorCode::InvalidAllocationAccounts);
+            let mut entry: Account<AllowlistEntry> = Account::try_from(&accounts[0])?;
+            let expected_entry = Pubkey::create_program_address(
+                &[b"allowlist", mint_key.as_ref(), entry.wallet.as_ref(), &[entry.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidAllocationAccounts)?;
+            require_keys_eq!(expected_entry, entry.key(), ErrorCode::InvalidAllocationAccounts);
+            if entry.allocated_through >= allocation_id {
+                continue;
+            }
+
+            let token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accounts[1])?;
+            require_keys_eq!(token_account.mint, mint_key, ErrorCode::InvalidAllocationAccounts);
+            require_keys_eq!(token_account.owner, entry.wallet, ErrorCode::InvalidAllocationAccounts);
+
+            let record_balance = if entry.last_snapshot_id >= snapshot_id {
+                let checkpoint: Account<SnapshotBalance> = Account::try_from(&accounts[2])?;
+                require_keys_eq!(checkpoint.token_mint, mint_key, ErrorCode::InvalidSnapshotCheckpoint);
+                require_keys_eq!(checkpoint.wallet, entry.wallet, ErrorCode::InvalidSnapshotCheckpoint);
+                require!(checkpoint.covers(snapshot_id), ErrorCode::InvalidSnapshotCheckpoint);
+                checkpoint.balance
+            } else {
+                token_account.amount
+            };
+
+            let amount = ctx.accounts.allocation.share_of(record_balance, lot_size)?;
+            entry.allocated_through = allocation_id;
+            batch_holders += 1;
+
+            let mut minted = 0;
+            if amount > 0
+                && entry.is_approved
+                && entry.permits_token_account(&token_account.key())
+            {
+                create_snapshot_checkpoint(
+                    &ctx.accounts.token_config,
+                    &mut entry,
+                    &accounts[2],
+                    &ctx.accounts.authority.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                    token_account.amount,
+                )?;
+
+                let holder_page: Option<AccountLoader<HolderPage>> = if accounts[3].key() == crate::ID {
+                    None
+                } else {
+                    let page: AccountLoader<HolderPage> = AccountLoader::try_from(&accounts[3])?;
+                    require_keys_eq!(page.load()?.token_mint, mint_key, ErrorCode::InvalidHolderPage);
+                    Some(page)
+                };
+                let balance = token_account.amount.checked_add(amount)
+                    .ok_or(ErrorCode::Overflow)?;
+                sync_holder_index(
+                    &mut ctx.accounts.token_config,
+                    HolderIndexAccounts {
+                        holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                        leaving_number: None,
+                        last_number: None,
+                        joining_number: (accounts[4].key() != crate::ID).then_some(&accounts[4]),
+                        payer: &ctx.accounts.authority.to_account_info(),
+                        system_program: &ctx.accounts.system_program.to_account_info(),
+                    },
+                    None,
+                    Some((&mut entry, &token_account.key(), balance)),
+                )?;
+                sync_cap_table(
+                    &ctx.accounts.token_config,
+                    &mut entry,
+                    &token_account.key(),
+                    holder_page.as_ref(),
+                    balance,
+                )?;
+
+                let is_new_holder = token_account.amount == 0;
+                enforce_share_class(
+                    &ctx.accounts.token_config,
+                    ctx.accounts.share_class.as_ref(),
+                    i64::from(is_new_holder),
+                    false,
+                )?;
+
+                mint_with_program_authority(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.mint,
+                    token_account.to_account_info(),
+                    &ctx.accounts.mint_authority,
+                    ctx.bumps.mint_authority,
+                    amount,
+                )?;
+
+                let token_config = &mut ctx.accounts.token_config;
+                record_minted_supply(token_config, amount)?;
+                if is_new_holder {
+                    token_config.holder_count = token_config.holder_count.checked_add(1)
+                        .ok_or(ErrorCode::Overflow)?;
+                }
+                batch_amount = batch_amount.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+                minted = amount;
+            }
+
+            emit!(AllocationMintedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: ctx.accounts.token_config.next_event_sequence()?,
+                token_mint: mint_key,
+                allocation_id,
+                wallet: entry.wallet,
+                record_balance,
+                amount: minted,
+            });
+            entry.exit(&crate::ID)?;
+        }
+
+        let allocation = &mut ctx.accounts.allocation;
+        allocation.allocated_amount = allocation.allocated_amount.checked_add(batch_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        allocation.holders_processed = allocation.holders_processed.checked_add(batch_holders)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Close an allocation, minting whatever was not allocated to holders (rounding
+    /// dust and any unprocessed or revoked holders' shares) to the treasury
+    pub fn finalize_allocation(ctx: Context<FinalizeAllocation>) -> Result<()> {
+        let allocation = &ctx.accounts.allocation;
+        require!(!allocation.finalized, ErrorCode::AllocationFinalized);
+        let dust = allocation.issuance_amount.saturating_sub(allocation.allocated_amount);
+
+        if dust > 0 {
+            mint_with_program_authority(
+                &ctx.accounts.token_program,
+                &ctx.accounts.mint,
+                ctx.accounts.treasury_token_account.to_account_info(),
+                &ctx.accounts.mint_authority,
+                ctx.bumps.mint_authority,
+                dust,
+            )?;
+
+            let token_config = &mut ctx.accounts.token_config;
+            record_minted_supply(token_config, dust)?;
+        }
+        ctx.accounts.token_config.allocation_active = false;
+
+        let allocation = &mut ctx.accounts.allocation;
+        allocation.finalized = true;
+
+        emit!(AllocationFinalizedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: allocation.token_mint,
+            allocation_id: allocation.allocation_id,
+            allocated_amount: allocation.allocated_amount,
+            dust,
+            holders_processed: allocation.holders_processed,
+        });
+
+        Ok(())
+    }
+
+    /// Opt the holder in or out of reinvesting SPL-token dividends (DRIP)
+    pub fn set_drip_election(ctx: Context<SetDripElection>, enabled: bool) -> Result<()> {
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        allowlist_entry.drip_enabled = enabled;
+
+        emit!(DripElectionUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.token_config.mint,
+            wallet: allowlist_entry.wallet,
+            enabled,
+        });
+
+        Ok(())
+    }
+
+    /// Set the price (payout mint base units per whole share) at which DRIP holders'
+    /// dividends from a distribution are reinvested; 0 pays everyone in cash
+    pub fn set_drip_price(ctx: Context<SetDripPrice>, drip_price: u64) -> Result<()> {
+        let distribution = &mut ctx.accounts.distribution;
+        require!(
+            distribution.payout_mint != Pubkey::default(),
+            ErrorCode::InvalidDistribution
+        );
+        distribution.drip_price = drip_price;
+
+        emit!(DripPriceUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: distribution.token_mint,
+            distribution_id: distribution.distribution_id,
+            drip_price,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Set the fixed annual rate the share class accrues. The current period is closed
+    /// at the old rate first; the first non-zero rate starts the accrual clock.
+    pub fn set_share_class_accrual(ctx: Context<SetShareClassAccrual>, rate_bps: u16) -> Result<()> {
+        require!(rate_bps <= MAX_ACCRUAL_RATE_BPS, ErrorCode::InvalidAccrualRate);
+        let clock = Clock::get()?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let share_class = &mut ctx.accounts.share_class;
+        accrue_interest_index(token_config, share_class, clock.unix_timestamp)?;
+        if token_config.interest_accrued_at == 0 && rate_bps > 0 {
+            token_config.interest_accrued_at = clock.unix_timestamp;
+        }
+        share_class.accrual_rate_bps = rate_bps;
+
+        emit!(ShareClassAccrualUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            share_class: share_class.key(),
+            rate_bps,
+            interest_index: token_config.interest_index,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule planned issuance to the issuer treasury: `amount_per_period` raw units
+    /// become due every `period_seconds` from `start_time` until `end_time`
+    pub fn create_mint_schedule(
+        ctx: Context<CreateMintSchedule>,
+        amount_per_period: u64,
+        period_seconds: i64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        require!(
+            amount_per_period > 0 && period_seconds > 0 && end_time > start_time,
+            ErrorCode::InvalidMintSchedule
+        );
+        ctx.accounts.token_config.validate_lot(amount_per_period)?;
+
+        let mint_schedule = &mut ctx.accounts.mint_schedule;
+        mint_schedule.token_mint = ctx.accounts.token_config.mint;
+        mint_schedule.treasury_account = ctx.accounts.treasury_account.key();
+        mint_schedule.amount_per_period = amount_per_period;
+        mint_schedule.period_seconds = period_seconds;
+        mint_schedule.start_time = start_time;
+        mint_schedule.end_time = end_time;
+        mint_schedule.periods_emitted = 0;
+        mint_schedule.total_emitted = 0;
+        mint_schedule.bump = ctx.bumps.mint_schedule;
+
+        emit!(MintScheduleCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_schedule.token_mint,
+            treasury_account: mint_schedule.treasury_account,
+            amount_per_period,
+            period_seconds,
+            start_time,
+            end_time,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Stop future emissions and reclaim the schedule's rent
+    pub fn cancel_mint_schedule(ctx: Context<CancelMintSchedule>) -> Result<()> {
+        let mint_schedule = &ctx.accounts.mint_schedule;
+
+        emit!(MintScheduleCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_schedule.token_mint,
+            total_emitted: mint_schedule.total_emitted,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank minting every period that has come due since the last crank
+    /// into the schedule's treasury account, signing as the program's mint authority PDA
+    pub fn crank_emission(ctx: Context<CrankEmission>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let clock = Clock::get()?;
+
+        let periods_due = ctx.accounts.mint_schedule.periods_due(clock.unix_timestamp)?;
+        require!(periods_due > 0, ErrorCode::NoEmissionDue);
+        let amount = ctx.accounts.mint_schedule.amount_per_period
+            .checked_mul(periods_due)
+            .ok_or(ErrorCode::Overflow)?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.treasury_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+
+        let mint_schedule = &mut ctx.accounts.mint_schedule;
+        mint_schedule.periods_emitted = mint_schedule.periods_emitted.checked_add(periods_due)
+            .ok_or(ErrorCode::Overflow)?;
+        mint_schedule.total_emitted = mint_schedule.total_emitted.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(EmissionMintedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: mint_schedule.token_mint,
+            treasury_account: mint_schedule.treasury_account,
+            periods: periods_due,
+            amount,
+            total_emitted: mint_schedule.total_emitted,
+            new_supply: token_config.total_supply,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank closing the current interest accrual period
+    pub fn accrue_interest(ctx: Context<AccrueInterest>) -> Result<()> {
+        let clock = Clock::get()?;
+        accrue_interest_index(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.share_class,
+            clock.unix_timestamp,
+        )
+    }
+
+    /// Mint a holder's accrued interest, in whole lots, into their token account.
+    /// Any sub-lot remainder stays accrued for a later claim.
+    pub fn claim_interest(ctx: Context<ClaimInterest>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        let entry = &ctx.accounts.holder_allowlist_entry;
+        require!(entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        let clock = Clock::get()?;
+        accrue_interest_index(
+            &mut ctx.accounts.token_config,
+            &ctx.accounts.share_class,
+            clock.unix_timestamp,
+        )?;
+
+        // Settles interest up to now as part of the checkpoint
+        let balance = ctx.accounts.holder_token_account.amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            balance,
+        )?;
+        let accrued = ctx.accounts.holder_allowlist_entry.accrued_interest;
+        let amount = accrued - accrued % ctx.accounts.token_config.lot_size.max(1);
+        require!(amount > 0, ErrorCode::NoInterestAccrued);
+
+        let is_new_holder = balance == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            Some(&ctx.accounts.share_class),
+            i64::from(is_new_holder),
+            false,
+        )?;
+        let holder_balance = balance.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.holder_number.as_deref(),
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        mint_with_program_authority(
+            &ctx.accounts.token_program,
+            &ctx.accounts.mint,
+            ctx.accounts.holder_token_account.to_account_info(),
+            &ctx.accounts.mint_authority,
+            ctx.bumps.mint_authority,
+            amount,
+        )?;
+
+        ctx.accounts.holder_allowlist_entry.accrued_interest = accrued - amount;
+        let token_config = &mut ctx.accounts.token_config;
+        record_minted_supply(token_config, amount)?;
+        if is_new_holder {
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        emit!(InterestClaimedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            interest_index: token_config.interest_index,
+            remaining_accrued: accrued - amount,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share class's liquidation preference: a multiple of its issue price paid
+    /// ahead of junior classes, with seniority taken from `dividend_priority`
+    pub fn set_liquidation_preference(
+        ctx: Context<SetLiquidationPreference>,
+        multiple_bps: u32,
+        issue_price: u64,
+        participating: bool,
+    ) -> Result<()> {
+        require!(
+            multiple_bps <= MAX_LIQUIDATION_MULTIPLE_BPS,
+            ErrorCode::InvalidLiquidationPreference
+        );
+        require!(
+            (multiple_bps == 0) == (issue_price == 0),
+            ErrorCode::InvalidLiquidationPreference
+        );
+
+        let share_class = &mut ctx.accounts.share_class;
+        share_class.liquidation_multiple_bps = multiple_bps;
+        share_class.liquidation_issue_price = issue_price;
+        share_class.participating = participating;
+
+        emit!(LiquidationPreferenceUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: share_class.token_mint,
+            share_class: share_class.key(),
+            multiple_bps,
+            issue_price,
+            seniority: share_class.dividend_priority,
+            participating,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Run a wind-down waterfall over `total_proceeds` of the payout mint (e.g. USDC):
+    /// classes are paid their preferences in seniority order, pro-rata within a tier
+    /// when proceeds fall short, and the residual is shared pro-rata by share count
+    /// among classes without a preference and participating preferred. Each class's
+    /// allotment funds a new record-date distribution its holders claim as usual.
+    ///
+    /// Remaining accounts, per entry of `classes`: token config (mut), share class,
+    /// share mint, distribution PDA (mut, uninitialized) and its vault (mut,
+    /// uninitialized) at the class's next distribution id. Proceeds left undistributed
+    /// by rounding, or for lack of residual participants, stay in the funding account.
+    pub fn execute_waterfall<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteWaterfall<'info>>,
+        total_proceeds: u64,
+        claim_deadline: i64,
+        classes: Vec<WaterfallClassInput>,
+    ) -> Result<()> {
+        require!(total_proceeds > 0, ErrorCode::InvalidAmount);
+        require!(
+            !classes.is_empty() && classes.len() <= MAX_WATERFALL_CLASSES,
+            ErrorCode::InvalidWaterfall
+        );
+        require!(
+            ctx.remaining_accounts.len() == classes.len() * WATERFALL_ACCOUNTS_PER_CLASS,
+            ErrorCode::InvalidWaterfallAccounts
+        );
+        let clock = Clock::get()?;
+        require!(claim_deadline > clock.unix_timestamp, ErrorCode::InvalidDistribution);
+        let authority_key = ctx.accounts.authority.key();
+
+        let mut tranches = Vec::with_capacity(classes.len());
+        for (input, accounts) in classes.iter().zip(ctx.remaining_accounts.chunks(WATERFALL_ACCOUNTS_PER_CLASS)) {
+            require!(input.snapshot_supply > 0, ErrorCode::InvalidDistribution);
+            let token_config: Account<TokenConfig> = Account::try_from(&accounts[0])?;
+            let share_class: Account<ShareClass> = Account::try_from(&accounts[1])?;
+            let mint: InterfaceAccount<Mint> = InterfaceAccount::try_from(&accounts[2])?;
+            require_keys_eq!(token_config.authority, authority_key, ErrorCode::UnauthorizedAuthority);
+            require_keys_eq!(token_config.share_class, share_class.key(), ErrorCode::InvalidWaterfallAccounts);
+            require_keys_eq!(token_config.mint, mint.key(), ErrorCode::InvalidWaterfallAccounts);
+            require!(
+                tranches.iter().all(|t: &WaterfallTranche| t.token_mint != mint.key()),
+                ErrorCode::InvalidWaterfallAccounts
+            );
+            tranches.push(WaterfallTranche {
+                token_mint: mint.key(),
+                share_class: share_class.key(),
+                seniority: share_class.dividend_priority,
+                decimals: mint.decimals,
+                shares: input.snapshot_supply,
+                preference: share_class.liquidation_preference(input.snapshot_supply, mint.decimals)?,
+                shares_in_residual: share_class.shares_in_residual(),
+                preference_paid: 0,
+                residual_paid: 0,
+            });
+        }
+
+        let undistributed = allocate_waterfall(&mut tranches, total_proceeds)?;
+
+        for ((tranche, input), accounts) in tranches
+            .iter()
+            .zip(classes.iter())
+            .zip(ctx.remaining_accounts.chunks(WATERFALL_ACCOUNTS_PER_CLASS))
+        {
+            let amount = tranche.preference_paid.checked_add(tranche.residual_paid)
+                .ok_or(ErrorCode::Overflow)?;
+            let distribution_id = if amount > 0 {
+                Some(create_waterfall_distribution(
+                    ctx.accounts,
+                    accounts,
+                    input,
+                    amount,
+                    clock.unix_timestamp,
+                    claim_deadline,
+                )?)
+            } else {
+                None
+            };
+            let mut token_config: Account<TokenConfig> = Account::try_from(&accounts[0])?;
+            let sequence = token_config.next_event_sequence()?;
+            token_config.exit(&crate::ID)?;
+
+            emit!(WaterfallTranchePaidEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence,
+                token_mint: tranche.token_mint,
+                share_class: tranche.share_class,
+                seniority: tranche.seniority,
+                preference: tranche.preference,
+                preference_paid: tranche.preference_paid,
+                residual_paid: tranche.residual_paid,
+                distribution_id,
+            });
+        }
+
+        emit!(WaterfallExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            payout_mint: ctx.accounts.payout_mint.key(),
+            total_proceeds,
+            distributed: total_proceeds - undistributed,
+            undistributed,
+            authority: authority_key,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Hand the token's authority to an m-of-n council.
+    /// From then on every authority instruction (minting, pausing, allowlist changes)
+    /// runs only as a council operation approved by `threshold` members. The council
+    /// signer PDA pays for any accounts those instructions create, so keep it funded.
+    pub fn initialize_authority_council(
+        ctx: Context<InitializeAuthorityCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_council(&members, threshold)?;
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+
+        let council_signer = ctx.accounts.council_signer.key();
+
+        let council = &mut ctx.accounts.council;
+        council.token_mint = ctx.accounts.mint.key();
+        council.members = members;
+        council.threshold = threshold;
+        council.next_operation_id = 0;
+        council.bump = ctx.bumps.council;
+        council.signer_bump = ctx.bumps.council_signer;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let old_authority = token_config.authority;
+        token_config.authority = council_signer;
+        token_config.pending_authority = Pubkey::default();
+
+        let clock = Clock::get()?;
+
+        emit!(AuthorityCouncilUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: council.token_mint,
+            council: council.key(),
+            members: council.members.clone(),
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(AuthorityTransferredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_authority,
+            new_authority: council_signer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the council's members and threshold; only callable as a council operation
+    pub fn update_authority_council(
+        ctx: Context<UpdateAuthorityCouncil>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_council(&members, threshold)?;
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+
+        let council = &mut ctx.accounts.council;
+        council.members = members;
+        council.threshold = threshold;
+
+        let clock = Clock::get()?;
+
+        emit!(AuthorityCouncilUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: council.token_mint,
+            council: council.key(),
+            members: council.members.clone(),
+            threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Propose a gated-token instruction for the council to execute with its authority.
+    /// The proposer's approval is counted immediately.
+    pub fn propose_operation(
+        ctx: Context<ProposeOperation>,
+        data: Vec<u8>,
+        accounts: Vec<OperationAccount>,
+    ) -> Result<()> {
+        let council = &mut ctx.accounts.council;
+        let proposer = ctx.accounts.proposer.key();
+        require!(council.members.contains(&proposer), ErrorCode::NotCouncilMember);
+        require!(
+            data.len() >= 8 && data.len() <= MAX_OPERATION_DATA_LEN,
+            ErrorCode::InvalidOperation
+        );
+        require!(accounts.len() <= MAX_OPERATION_ACCOUNTS, ErrorCode::InvalidOperation);
+        // The operation cannot act on its own record
+        let operation_key = ctx.accounts.operation.key();
+        require!(
+            accounts.iter().all(|account| account.pubkey != operation_key),
+            ErrorCode::InvalidOperation
+        );
+
+        let clock = Clock::get()?;
+        let operation = &mut ctx.accounts.operation;
+        operation.council = council.key();
+        operation.operation_id = council.next_operation_id;
+        operation.proposer = proposer;
+        operation.data = data;
+        operation.accounts = accounts;
+        operation.approvals = vec![proposer];
+        operation.executed = false;
+        operation.created_at = clock.unix_timestamp;
+        operation.bump = ctx.bumps.operation;
+
+        council.next_operation_id = council.next_operation_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperationProposedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: council.token_mint,
+            operation_id: operation.operation_id,
+            proposer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Add a council member's approval to a pending operation
+    pub fn approve_operation(ctx: Context<ApproveOperation>) -> Result<()> {
+        let council = &ctx.accounts.council;
+        let member = ctx.accounts.member.key();
+        require!(council.members.contains(&member), ErrorCode::NotCouncilMember);
+
+        let operation = &mut ctx.accounts.operation;
+        require!(!operation.executed, ErrorCode::OperationExecuted);
+        require!(!operation.approvals.contains(&member), ErrorCode::OperationAlreadyApproved);
+        operation.approvals.push(member);
+
+        let clock = Clock::get()?;
+
+        emit!(OperationApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: council.token_mint,
+            operation_id: operation.operation_id,
+            member,
+            approvals: operation.current_approvals(council),
+            threshold: council.threshold,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Execute an operation once current members' approvals reach the threshold. Pass
+    /// the accounts it references, followed by this program's id, as remaining accounts.
+    pub fn execute_operation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteOperation<'info>>,
+    ) -> Result<()> {
+        let council = &ctx.accounts.council;
+        let executor = ctx.accounts.executor.key();
+        require!(council.members.contains(&executor), ErrorCode::NotCouncilMember);
+
+        let operation = &mut ctx.accounts.operation;
+        require!(!operation.executed, ErrorCode::OperationExecuted);
+        let approvals = operation.current_approvals(council);
+        require!(approvals >= council.threshold, ErrorCode::OperationThresholdNotMet);
+
+        // Record execution before the call so the operation cannot be replayed from within
+        operation.executed = true;
+        operation.exit(&crate::ID)?;
+
+        let instruction = Instruction {
+            program_id: crate::ID,
+            accounts: operation
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: operation.data.clone(),
+        };
+        let council_key = council.key();
+        let seeds: &[&[u8]] = &[b"council_signer", council_key.as_ref(), &[council.signer_bump]];
+        invoke_signed(&instruction, ctx.remaining_accounts, &[seeds])?;
+        // The call may have written the token config; pick that up before it is saved
+        ctx.accounts.token_config.reload()?;
+
+        let clock = Clock::get()?;
+
+        emit!(OperationExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: council.token_mint,
+            operation_id: operation.operation_id,
+            approvals,
+            executor,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Close an operation, reclaiming its rent to the proposer. Closing one that has not
+    /// executed cancels it.
+    pub fn close_operation(ctx: Context<CloseOperation>) -> Result<()> {
+        let operation = &ctx.accounts.operation;
+        let clock = Clock::get()?;
+
+        emit!(OperationClosedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: ctx.accounts.council.token_mint,
+            operation_id: operation.operation_id,
+            executed: operation.executed,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Set the timelock delay on sensitive operations (authority changes, council
+    /// membership, the delay itself). Once enabled, changing it is itself timelocked.
+    pub fn set_timelock(ctx: Context<SetTimelock>, delay_seconds: i64) -> Result<()> {
+        require!(
+            (0..=MAX_TIMELOCK_SECONDS).contains(&delay_seconds),
+            ErrorCode::InvalidTimelock
+        );
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+
+        let token_config = &mut ctx.accounts.token_config;
+        let old_delay = token_config.timelock_seconds;
+        token_config.timelock_seconds = delay_seconds;
+
+        let clock = Clock::get()?;
+
+        emit!(TimelockUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            old_delay_seconds: old_delay,
+            delay_seconds,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Queue a sensitive gated-token instruction behind the timelock. It can execute
+    /// once the delay has passed, signed for by the timelock PDA `[b"timelock", mint]`.
+    pub fn queue_operation(
+        ctx: Context<QueueOperation>,
+        data: Vec<u8>,
+        accounts: Vec<OperationAccount>,
+    ) -> Result<()> {
+        let token_config = &mut ctx.accounts.token_config;
+        require!(token_config.timelock_seconds > 0, ErrorCode::TimelockNotEnabled);
+        require!(
+            data.len() >= 8 && data.len() <= MAX_OPERATION_DATA_LEN,
+            ErrorCode::InvalidOperation
+        );
+        require!(accounts.len() <= MAX_OPERATION_ACCOUNTS, ErrorCode::InvalidOperation);
+        let operation_key = ctx.accounts.operation.key();
+        require!(
+            accounts.iter().all(|account| account.pubkey != operation_key),
+            ErrorCode::InvalidOperation
+        );
+
+        let clock = Clock::get()?;
+        let eta = clock.unix_timestamp.checked_add(token_config.timelock_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        let operation = &mut ctx.accounts.operation;
+        operation.token_mint = token_config.mint;
+        operation.operation_id = token_config.next_pending_operation_id;
+        operation.queued_by = ctx.accounts.authority.key();
+        operation.data = data;
+        operation.accounts = accounts;
+        operation.queued_at = clock.unix_timestamp;
+        operation.eta = eta;
+        operation.bump = ctx.bumps.operation;
+
+        token_config.next_pending_operation_id = token_config.next_pending_operation_id
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(OperationQueuedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: operation.token_mint,
+            operation_id: operation.operation_id,
+            queued_by: operation.queued_by,
+            eta,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Execute a queued operation after its delay, closing it to the account that queued
+    /// it. Signers the instruction needs besides the timelock PDA (normally the authority)
+    /// must sign this transaction. Pass the accounts it references, followed by this
+    /// program's id, as remaining accounts.
+    pub fn execute_queued_operation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteQueuedOperation<'info>>,
+    ) -> Result<()> {
+        let operation = &ctx.accounts.operation;
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp >= operation.eta, ErrorCode::TimelockNotElapsed);
+
+        let instruction = Instruction {
+            program_id: crate::ID,
+            accounts: operation
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: operation.data.clone(),
+        };
+        let seeds: &[&[u8]] = &[b"timelock", operation.token_mint.as_ref(), &[ctx.bumps.timelock]];
+        invoke_signed(&instruction, ctx.remaining_accounts, &[seeds])?;
+        // The call may have written the token config; pick that up before it is saved
+        ctx.accounts.token_config.reload()?;
+
+        emit!(QueuedOperationExecutedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: operation.token_mint,
+            operation_id: operation.operation_id,
+            executor: ctx.accounts.executor.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a queued operation before it executes
+    pub fn cancel_queued_operation(ctx: Context<CancelQueuedOperation>) -> Result<()> {
+        let operation = &ctx.accounts.operation;
+        let clock = Clock::get()?;
+
+        emit!(QueuedOperationCancelledEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: operation.token_mint,
+            operation_id: operation.operation_id,
+            cancelled_by: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Put a resolution to shareholders. Votes are weighted by record-date balances
+    /// at the current snapshot (times the class's votes per share, if a class is set).
+    /// The resolution passes if turnout reaches the type's quorum of eligible votes and
+    /// votes for exceed its approval threshold of votes for and against.
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        title: String,
+        description_hash: [u8; 32],
+        proposal_type: ProposalType,
+        voting_period_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            !title.is_empty() && title.len() <= MAX_PROPOSAL_TITLE_LEN,
+            ErrorCode::InvalidName
+        );
+        let rules = ctx.accounts.governance_config.rules_for(proposal_type);
+        require!(
+            (rules.min_voting_period_seconds..=MAX_VOTING_PERIOD_SECONDS).contains(&voting_period_seconds),
+            ErrorCode::InvalidProposal
+        );
+        let quorum_bps = rules.quorum_bps;
+        let approval_threshold_bps = rules.approval_threshold_bps;
+        let token_config = &mut ctx.accounts.token_config;
+        require!(token_config.current_snapshot_id > 0, ErrorCode::InvalidProposal);
+        let snapshot = &ctx.accounts.snapshot;
+        let eligible_votes = match voting_share_class(token_config, ctx.accounts.share_class.as_ref())? {
+            Some(share_class) => share_class.voting_power(snapshot.total_supply)?,
+            None => snapshot.total_supply,
+        };
+
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.token_mint = token_config.mint;
+        proposal.proposal_id = token_config.next_proposal_id;
+        proposal.proposer = ctx.accounts.authority.key();
+        proposal.title = title;
+        proposal.description_hash = description_hash;
+        proposal.snapshot_id = snapshot.snapshot_id;
+        proposal.eligible_votes = eligible_votes;
+        proposal.votes_for = 0;
+        proposal.votes_against = 0;
+        proposal.votes_abstain = 0;
+        proposal.voting_starts_at = clock.unix_timestamp;
+        proposal.voting_ends_at = clock.unix_timestamp.checked_add(voting_period_seconds)
+            .ok_or(ErrorCode::Overflow)?;
+        proposal.quorum_bps = quorum_bps;
+        proposal.approval_threshold_bps = approval_threshold_bps;
+        proposal.proposal_type = proposal_type;
+        proposal.status = ProposalStatus::Active;
+        proposal.bump = ctx.bumps.proposal;
+
+        token_config.next_proposal_id = token_config.next_proposal_id.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(ProposalCreatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            proposal_id: proposal.proposal_id,
+            title: proposal.title.clone(),
+            description_hash,
+            proposal_type,
+            snapshot_id: proposal.snapshot_id,
+            eligible_votes,
+            voting_ends_at: proposal.voting_ends_at,
+            quorum_bps,
+            approval_threshold_bps,
+            proposer: proposal.proposer,
+        });
+
+        Ok(())
+    }
+
+    /// Create the token's governance config with default rules: ordinary resolutions
+    /// need a 50% quorum and simple majority over at least 3 days; special resolutions
+    /// a 50% quorum and two-thirds over at least 7 days
+    pub fn initialize_governance_config(ctx: Context<InitializeGovernanceConfig>) -> Result<()> {
+        let governance_config = &mut ctx.accounts.governance_config;
+        governance_config.token_mint = ctx.accounts.token_config.mint;
+        governance_config.rules = [
+            ProposalRules {
+                quorum_bps: 5_000,
+                approval_threshold_bps: 5_000,
+                min_voting_period_seconds: 3 * 86_400,
+            },
+            ProposalRules {
+                quorum_bps: 5_000,
+                approval_threshold_bps: 6_666,
+                min_voting_period_seconds: 7 * 86_400,
+            },
+        ];
+        governance_config.bump = ctx.bumps.governance_config;
+
+        for (proposal_type, rules) in [ProposalType::Ordinary, ProposalType::Special]
+            .into_iter()
+            .zip(governance_config.rules)
+        {
+            emit!(ProposalRulesUpdatedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: ctx.accounts.token_config.next_event_sequence()?,
+                token_mint: governance_config.token_mint,
+                proposal_type,
+                quorum_bps: rules.quorum_bps,
+                approval_threshold_bps: rules.approval_threshold_bps,
+                min_voting_period_seconds: rules.min_voting_period_seconds,
+                authority: ctx.accounts.authority.key(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Change the voting rules for a proposal type; timelocked when a delay is set.
+    /// Proposals already open keep the rules they were created under.
+    pub fn set_proposal_rules(
+        ctx: Context<SetProposalRules>,
+        proposal_type: ProposalType,
+        rules: ProposalRules,
+    ) -> Result<()> {
+        rules.validate()?;
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+
+        let governance_config = &mut ctx.accounts.governance_config;
+        governance_config.rules[proposal_type as usize] = rules;
+
+        emit!(ProposalRulesUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: governance_config.token_mint,
+            proposal_type,
+            quorum_bps: rules.quorum_bps,
+            approval_threshold_bps: rules.approval_threshold_bps,
+            min_voting_period_seconds: rules.min_voting_period_seconds,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Cast a holder's vote on an active proposal, weighted by their record-date balance.
+    /// A delegate also casts the weight of each delegator passed in the remaining
+    /// accounts, `DELEGATED_VOTE_ACCOUNTS` per delegator: vote delegation, allowlist
+    /// entry, token account, snapshot checkpoint (or this program's id if none) and the
+    /// delegator's vote record PDA. Only delegations made before the proposal's snapshot
+    /// count, and a delegator who has already voted cannot be voted for.
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CastVote<'info>>,
+        choice: VoteChoice,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalFinalized);
+        require!(clock.unix_timestamp < proposal.voting_ends_at, ErrorCode::VotingClosed);
+        require!(
+            ctx.remaining_accounts.len().checked_rem(DELEGATED_VOTE_ACCOUNTS) == Some(0),
+            ErrorCode::InvalidDelegationAccounts
+        );
+
+        let voter = ctx.accounts.voter.key();
+        let voter_entry = &ctx.accounts.voter_allowlist_entry;
+        require!(voter_entry.is_approved, ErrorCode::WalletNotApproved);
+        let share_class = voting_share_class(&ctx.accounts.token_config, ctx.accounts.share_class.as_ref())?;
+        let snapshot = &ctx.accounts.snapshot;
+        let voting_power = |record_balance: u64| -> Result<u64> {
+            match share_class {
+                Some(share_class) => share_class.voting_power(record_balance),
+                None => Ok(record_balance),
+            }
+        };
+
+        let weight = match &ctx.accounts.voter_token_account {
+            Some(token_account) => voting_power(snapshot.balance_of(
+                voter_entry,
+                token_account.amount,
+                ctx.accounts.voter_snapshot_balance.as_deref().map(|checkpoint| &**checkpoint),
+            )?)?,
+            None => 0,
+        };
+
+        let proposal_key = proposal.key();
+        let mint_key = proposal.token_mint;
+        let mut delegated_weight: u64 = 0;
+        for accounts in ctx.remaining_accounts.chunks(DELEGATED_VOTE_ACCOUNTS) {
+            let delegation: Account<VoteDelegation> = Account::try_from(&accounts[0])?;
+            require_keys_eq!(delegation.token_mint, mint_key, ErrorCode::InvalidDelegationAccounts);
+            require_keys_eq!(delegation.delegate, voter, ErrorCode::NotDelegate);
+            require!(
+                delegation.delegated_at < snapshot.created_at,
+                ErrorCode::DelegationAfterSnapshot
+            );
+            let delegator = delegation.delegator;
+
+            let entry: Account<AllowlistEntry> = Account::try_from(&accounts[1])?;
+            let expected_entry = Pubkey::create_program_address(
+                &[b"allowlist", mint_key.as_ref(), delegator.as_ref(), &[entry.bump]],
+                &crate::ID,
+            )
+            .map_err(|_| ErrorCode::InvalidDelegationAccounts)?;
+            require_keys_eq!(expected_entry, accounts[1].key(), ErrorCode::InvalidDelegationAccounts);
+            require!(entry.is_approved, ErrorCode::WalletNotApproved);
+
+            let token_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(&accounts[2])?;
+            require_keys_eq!(token_account.mint, mint_key, ErrorCode::InvalidDelegationAccounts);
+            require_keys_eq!(token_account.owner, delegator, ErrorCode::InvalidDelegationAccounts);
+            let checkpoint: Option<Account<SnapshotBalance>> = if accounts[3].key() == crate::ID {
+                None
+            } else {
+                Some(Account::try_from(&accounts[3])?)
+            };
+            let delegator_weight = voting_power(snapshot.balance_of(
+                &entry,
+                token_account.amount,
+                checkpoint.as_deref(),
+            )?)?;
+
+            create_vote_record(
+                &accounts[4],
+                &ctx.accounts.voter.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                VoteRecord {
+                    proposal: proposal_key,
+                    voter: delegator,
+                    choice,
+                    weight: delegator_weight,
+                    cast_at: clock.unix_timestamp,
+                    bump: 0,
+                    cast_by: voter,
+                },
+            )?;
+            delegated_weight = delegated_weight.checked_add(delegator_weight)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+
+        let total_weight = weight.checked_add(delegated_weight).ok_or(ErrorCode::Overflow)?;
+        require!(total_weight > 0, ErrorCode::NoVotingPower);
+
+        let proposal = &mut ctx.accounts.proposal;
+        let tally = match choice {
+            VoteChoice::For => &mut proposal.votes_for,
+            VoteChoice::Against => &mut proposal.votes_against,
+            VoteChoice::Abstain => &mut proposal.votes_abstain,
+        };
+        *tally = tally.checked_add(total_weight).ok_or(ErrorCode::Overflow)?;
+
+        let vote_record = &mut ctx.accounts.vote_record;
+        vote_record.proposal = proposal_key;
+        vote_record.voter = voter;
+        vote_record.choice = choice;
+        vote_record.weight = weight;
+        vote_record.cast_at = clock.unix_timestamp;
+        vote_record.bump = ctx.bumps.vote_record;
+        vote_record.cast_by = voter;
+
+        emit!(VoteCastEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            proposal_id: proposal.proposal_id,
+            voter,
+            choice,
+            weight,
+            delegated_weight,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Delegate the holder's votes to another wallet, replacing any earlier delegate
+    pub fn delegate_votes(ctx: Context<DelegateVotes>, delegate: Pubkey) -> Result<()> {
+        let delegator = ctx.accounts.delegator.key();
+        require!(
+            delegate != Pubkey::default() && delegate != delegator,
+            ErrorCode::InvalidDelegate
+        );
+        require!(ctx.accounts.delegator_allowlist_entry.is_approved, ErrorCode::WalletNotApproved);
+
+        let clock = Clock::get()?;
+        let delegation = &mut ctx.accounts.delegation;
+        let previous_delegate = delegation.delegate;
+        delegation.token_mint = ctx.accounts.token_config.mint;
+        delegation.delegator = delegator;
+        delegation.delegate = delegate;
+        delegation.delegated_at = clock.unix_timestamp;
+        delegation.bump = ctx.bumps.delegation;
+
+        emit!(VotesDelegatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: delegation.token_mint,
+            delegator,
+            previous_delegate,
+            delegate,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a vote delegation; the holder votes for themselves again
+    pub fn revoke_delegation(ctx: Context<RevokeDelegation>) -> Result<()> {
+        let delegation = &ctx.accounts.delegation;
+        let clock = Clock::get()?;
+
+        emit!(VotesDelegatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: delegation.token_mint,
+            delegator: delegation.delegator,
+            previous_delegate: delegation.delegate,
+            delegate: Pubkey::default(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Tally a proposal once its voting period has ended (permissionless)
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(proposal.status == ProposalStatus::Active, ErrorCode::ProposalFinalized);
+        require!(clock.unix_timestamp >= proposal.voting_ends_at, ErrorCode::VotingActive);
+
+        let quorum_reached = proposal.quorum_reached();
+        let approved = proposal.approved();
+        proposal.status = if quorum_reached && approved {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        };
+
+        emit!(ProposalFinalizedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: proposal.token_mint,
+            proposal_id: proposal.proposal_id,
+            status: proposal.status,
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+            votes_abstain: proposal.votes_abstain,
+            quorum_reached,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Register the SPL Governance realm this token supplies voter weight to, so its
+    /// holders can govern through Realms with this program as the voter-weight plugin
+    pub fn configure_voter_weight_registrar(ctx: Context<ConfigureVoterWeightRegistrar>) -> Result<()> {
+        let realm = ctx.accounts.realm.key();
+        let governance_program_id = *ctx.accounts.realm.owner;
+        let registrar = &mut ctx.accounts.registrar;
+        registrar.token_mint = ctx.accounts.token_config.mint;
+        registrar.realm = realm;
+        registrar.governance_program_id = governance_program_id;
+        registrar.bump = ctx.bumps.registrar;
+
+        emit!(VoterWeightRegistrarUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: registrar.token_mint,
+            realm,
+            governance_program_id,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Refresh a holder's SPL Governance voter-weight record from their allowlisted
+    /// balance at the current snapshot (times the class's votes per share). The record
+    /// expires at the current slot, so Realms requires it refreshed in the same transaction.
+    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
+        let entry = &ctx.accounts.holder_allowlist_entry;
+        require!(entry.is_approved, ErrorCode::WalletNotApproved);
+        require!(
+            entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        let record_balance = ctx.accounts.snapshot.balance_of(
+            entry,
+            ctx.accounts.holder_token_account.amount,
+            ctx.accounts.holder_snapshot_balance.as_deref().map(|checkpoint| &**checkpoint),
+        )?;
+        let voter_weight = match voting_share_class(&ctx.accounts.token_config, ctx.accounts.share_class.as_ref())? {
+            Some(share_class) => share_class.voting_power(record_balance)?,
+            None => record_balance,
+        };
+
+        let clock = Clock::get()?;
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.realm = ctx.accounts.registrar.realm;
+        record.governing_token_mint = ctx.accounts.token_config.mint;
+        record.governing_token_owner = ctx.accounts.holder.key();
+        record.voter_weight = voter_weight;
+        record.voter_weight_expiry = Some(clock.slot);
+        record.weight_action = None;
+        record.weight_action_target = None;
+
+        emit!(VoterWeightUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: record.governing_token_mint,
+            realm: record.realm,
+            holder: record.governing_token_owner,
+            snapshot_id: ctx.accounts.snapshot.snapshot_id,
+            voter_weight,
+            slot: clock.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Refresh the realm's max voter-weight record from the current snapshot's supply
+    pub fn update_max_voter_weight_record(ctx: Context<UpdateMaxVoterWeightRecord>) -> Result<()> {
+        let supply = ctx.accounts.snapshot.total_supply;
+        let max_voter_weight = match voting_share_class(&ctx.accounts.token_config, ctx.accounts.share_class.as_ref())? {
+            Some(share_class) => share_class.voting_power(supply)?,
+            None => supply,
+        };
+
+        let clock = Clock::get()?;
+        let record = &mut ctx.accounts.max_voter_weight_record;
+        record.realm = ctx.accounts.registrar.realm;
+        record.governing_token_mint = ctx.accounts.token_config.mint;
+        record.max_voter_weight = max_voter_weight;
+        record.max_voter_weight_expiry = Some(clock.slot);
+
+        Ok(())
+    }
+
+    /// Anchor a signed corporate document (board consent, amended charter) to the token
+    /// by its SHA-256 hash and URI. Pass the shareholder proposal it implements, if any;
+    /// it must have passed.
+    pub fn record_resolution(
+        ctx: Context<RecordResolution>,
+        kind: ResolutionKind,
+        document_hash: [u8; 32],
+        uri: String,
+        effective_date: i64,
+    ) -> Result<()> {
+        require!(document_hash != [0; 32], ErrorCode::InvalidResolution);
+        require!(
+            !uri.is_empty() && uri.len() <= MAX_RESOLUTION_URI_LEN,
+            ErrorCode::InvalidResolution
+        );
+        let proposal = match &ctx.accounts.proposal {
+            Some(proposal) => {
+                require!(proposal.status == ProposalStatus::Passed, ErrorCode::ProposalNotPassed);
+                Some(proposal.key())
+            }
+            None => None,
+        };
+
+        let clock = Clock::get()?;
+        let resolution = &mut ctx.accounts.resolution;
+        resolution.token_mint = ctx.accounts.token_config.mint;
+        resolution.kind = kind;
+        resolution.document_hash = document_hash;
+        resolution.uri = uri;
+        resolution.effective_date = effective_date;
+        resolution.proposal = proposal;
+        resolution.recorded_by = ctx.accounts.authority.key();
+        resolution.recorded_at = clock.unix_timestamp;
+        resolution.bump = ctx.bumps.resolution;
+
+        emit!(ResolutionRecordedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: resolution.token_mint,
+            resolution: resolution.key(),
+            kind,
+            document_hash,
+            uri: resolution.uri.clone(),
+            effective_date,
+            proposal,
+            recorded_by: resolution.recorded_by,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the token's ERC-3643 claim topics registry: the claim topics every
+    /// identity-verified holder must carry, and the issuers trusted to attest each one.
+    /// Dropping an issuer invalidates its claims at once. Must be signed by the
+    /// compliance officer.
+    pub fn set_claim_topics(
+        ctx: Context<SetClaimTopics>,
+        required_topics: Vec<u64>,
+        trusted_issuers: Vec<TrustedIssuer>,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.claim_topics;
+        registry.token_mint = ctx.accounts.token_config.mint;
+        registry.required_topics = required_topics;
+        registry.trusted_issuers = trusted_issuers;
+        registry.bump = ctx.bumps.claim_topics;
+        registry.validate()?;
+
+        let clock = Clock::get()?;
+
+        emit!(ClaimTopicsUpdatedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: registry.token_mint,
+            required_topics: registry.required_topics.clone(),
+            trusted_issuers: registry.trusted_issuers.iter().map(|trusted| trusted.issuer).collect(),
+            updated_by: ctx.accounts.compliance_officer.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Add a claim on `topic` to a wallet's identity, creating the identity on first use.
+    /// The signer must be a trusted issuer for the topic; a claim it already holds on the
+    /// topic is replaced. `data_hash` commits to the off-chain evidence.
+    pub fn add_claim(
+        ctx: Context<AddClaim>,
+        topic: u64,
+        data_hash: [u8; 32],
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        let issuer = ctx.accounts.issuer.key();
+        require!(ctx.accounts.claim_topics.trusts(&issuer, topic), ErrorCode::UntrustedClaimIssuer);
+        let clock = Clock::get()?;
+        require!(expires_at.is_none_or(|expires_at| expires_at > clock.unix_timestamp), ErrorCode::InvalidClaim);
+
+        let identity = &mut ctx.accounts.identity;
+        if identity.wallet == Pubkey::default() {
+            identity.token_mint = ctx.accounts.token_config.mint;
+            identity.wallet = ctx.accounts.wallet.key();
+            identity.bump = ctx.bumps.identity;
+        }
+        let claim = IdentityClaim {
+            topic,
+            issuer,
+            data_hash,
+            issued_at: clock.unix_timestamp,
+            expires_at,
+        };
+        match identity.claims.iter_mut().find(|held| held.topic == topic && held.issuer == issuer) {
+            Some(held) => *held = claim,
+            None => {
+                require!(identity.claims.len() < MAX_IDENTITY_CLAIMS, ErrorCode::IdentityClaimsFull);
+                identity.claims.push(claim);
+            }
+        }
+
+        emit!(ClaimAddedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: identity.token_mint,
+            wallet: identity.wallet,
+            topic,
+            issuer,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Remove `issuer`'s claim on `topic` from a wallet's identity. Signed by that issuer
+    /// or by the compliance officer.
+    pub fn remove_claim(ctx: Context<RemoveClaim>, topic: u64, issuer: Pubkey) -> Result<()> {
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == issuer || signer == ctx.accounts.token_config.compliance_officer,
+            ErrorCode::UnauthorizedClaimRemoval
+        );
+
+        let identity = &mut ctx.accounts.identity;
+        let position = identity.claims.iter()
+            .position(|held| held.topic == topic && held.issuer == issuer)
+            .ok_or(ErrorCode::ClaimNotFound)?;
+        identity.claims.remove(position);
+
+        let clock = Clock::get()?;
+
+        emit!(ClaimRemovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: identity.token_mint,
+            wallet: identity.wallet,
+            topic,
+            issuer,
+            removed_by: signer,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// ERC-3643 `isVerified`: approve a wallet whose identity holds a valid claim on every
+    /// required topic. Anyone may call it and pay for the allowlist entry. A wallet the
+    /// authority revoked stays revoked; one revoked by `revoke_unverified` may verify
+    /// again. The approval event names the identity account as approver.
+    pub fn verify_identity(ctx: Context<VerifyIdentity>) -> Result<()> {
+        let clock = Clock::get()?;
+        let identity = &mut ctx.accounts.identity;
+        require!(
+            ctx.accounts.claim_topics.verifies(identity, clock.unix_timestamp),
+            ErrorCode::IdentityNotVerified
+        );
+
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        if allowlist_entry.wallet == Pubkey::default() {
+            allowlist_entry.wallet = identity.wallet;
+            allowlist_entry.lockup_until = None;
+            allowlist_entry.transfer_locked = false;
+            allowlist_entry.is_insider = false;
+            allowlist_entry.approved_token_account = None;
+            allowlist_entry.last_snapshot_id = 0;
+            allowlist_entry.holder_page = None;
+            allowlist_entry.allocated_through = 0;
+            allowlist_entry.drip_enabled = false;
+            allowlist_entry.interest_index = ctx.accounts.token_config.interest_index;
+            allowlist_entry.accrued_interest = 0;
+            allowlist_entry.version = AllowlistEntry::CURRENT_VERSION;
+            allowlist_entry.holder_indexed = false;
+            allowlist_entry.bump = ctx.bumps.allowlist_entry;
+        } else {
+            require!(!allowlist_entry.is_approved, ErrorCode::WalletAlreadyApproved);
+            require!(
+                identity.lapsed_at.is_some() && allowlist_entry.revoked_at == identity.lapsed_at,
+                ErrorCode::WalletRevokedByAuthority
+            );
+            allowlist_entry.revoked_at = None;
+        }
+        // Revocation released the country slot, so residence is assigned afresh
+        allowlist_entry.country_code = UNASSIGNED_COUNTRY;
+        allowlist_entry.is_approved = true;
+        allowlist_entry.approved_at = clock.unix_timestamp;
+        identity.approved_by_identity = true;
+        identity.lapsed_at = None;
+
+        let token_config = &mut ctx.accounts.token_config;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &allowlist_entry.wallet);
+
+        emit!(WalletApprovedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            wallet: identity.wallet,
+            approved_by: identity.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke an approval granted by `verify_identity` once the identity no longer
+    /// verifies, because a claim expired or was removed or its issuer is no longer
+    /// trusted. Permissionless, so anyone can crank lapsed holders off the allowlist.
+    pub fn revoke_unverified(ctx: Context<RevokeUnverified>) -> Result<()> {
+        let clock = Clock::get()?;
+        let identity = &mut ctx.accounts.identity;
+        let allowlist_entry = &mut ctx.accounts.allowlist_entry;
+        require!(
+            identity.approved_by_identity && allowlist_entry.is_approved,
+            ErrorCode::WalletNotApproved
+        );
+        require!(
+            !ctx.accounts.claim_topics.verifies(identity, clock.unix_timestamp),
+            ErrorCode::IdentityStillVerified
+        );
+
+        if allowlist_entry.country_code != UNASSIGNED_COUNTRY {
+            let country_rule = ctx.accounts.country_rule
+                .as_mut()
+                .ok_or(ErrorCode::CountryRuleRequired)?;
+            country_rule.investor_count = country_rule.investor_count.saturating_sub(1);
+        }
+
+        allowlist_entry.is_approved = false;
+        allowlist_entry.revoked_at = Some(clock.unix_timestamp);
+        identity.approved_by_identity = false;
+        identity.lapsed_at = Some(clock.unix_timestamp);
+
+        let token_config = &mut ctx.accounts.token_config;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &allowlist_entry.wallet);
+
+        emit!(WalletRevokedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            wallet: identity.wallet,
+            revoked_by: identity.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Route the token through a Wormhole Token Bridge deployment. Shares only leave
+    /// through `bridge_out` and only return through `redeem_bridged`, which signs for
+    /// inbound transfers addressed to this token's bridge config. Mints with a transfer
+    /// hook cannot be bridged, as the token bridge does not forward the hook's accounts.
+    pub fn configure_bridge(
+        ctx: Context<ConfigureBridge>,
+        token_bridge_program: Pubkey,
+        wormhole_program: Pubkey,
+        enabled: bool,
+    ) -> Result<()> {
+        require_timelock(&ctx.accounts.token_config, ctx.accounts.timelock.as_ref())?;
+        require!(!has_transfer_hook(&ctx.accounts.mint)?, ErrorCode::TransferHookUnsupported);
+        require!(
+            token_bridge_program != Pubkey::default() && wormhole_program != Pubkey::default(),
+            ErrorCode::InvalidBridgeAccounts
+        );
+
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        bridge_config.token_mint = ctx.accounts.mint.key();
+        bridge_config.token_bridge_program = token_bridge_program;
+        bridge_config.wormhole_program = wormhole_program;
+        bridge_config.enabled = enabled;
+        bridge_config.bump = ctx.bumps.bridge_config;
+
+        let clock = Clock::get()?;
+
+        emit!(BridgeConfiguredEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: bridge_config.token_mint,
+            token_bridge_program,
+            wormhole_program,
+            enabled,
+            authority: ctx.accounts.authority.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Lock an approved holder's shares in the token bridge's custody and post a Wormhole
+    /// transfer of them to `target_address` on `target_chain`. The holder faces the sender
+    /// side of `gated_transfer`'s controls, with `target_address` as the ROFR buyer. The
+    /// remaining accounts are every open `BlackoutWindow`, then the token bridge's
+    /// `transfer_native` accounts, in its order, with the holder's token account as
+    /// `from`. Bridged shares leave the cap table until redeemed.
+    pub fn bridge_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BridgeOut<'info>>,
+        amount: u64,
+        target_chain: u16,
+        target_address: [u8; 32],
+        nonce: u32,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        require!(ctx.accounts.bridge_config.enabled, ErrorCode::BridgeDisabled);
+        require!(
+            target_chain != 0 && target_chain != WORMHOLE_CHAIN_SOLANA && target_address != [0; 32],
+            ErrorCode::InvalidBridgeTarget
+        );
+        ctx.accounts.token_config.validate_lot(amount)?;
+        bridge_amount(amount, ctx.accounts.mint.decimals)?;
+        let clock = Clock::get()?;
+
+        let holder_entry = &ctx.accounts.holder_allowlist_entry;
+        require!(holder_entry.is_approved, ErrorCode::SenderNotApproved);
+        require!(!holder_entry.transfer_locked, ErrorCode::SenderTransferLocked);
+        if let Some(lockup_until) = holder_entry.lockup_until {
+            require!(clock.unix_timestamp >= lockup_until, ErrorCode::SenderLockedUp);
+        }
+        require!(
+            holder_entry.permits_token_account(&ctx.accounts.holder_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+
+        // Bridging out is a sale to the target address as far as the transfer controls go
+        let (blackout_windows, bridge_accounts) =
+            split_blackout_windows(&ctx.accounts.token_config, ctx.remaining_accounts)?;
+        require_no_fee_memo_or_journal(&ctx.accounts.token_config)?;
+        if let (Some(velocity_counter), Some(bump)) =
+            (ctx.accounts.velocity_counter.as_mut(), ctx.bumps.velocity_counter)
+        {
+            velocity_counter.bump = bump;
+        }
+        enforce_transfer_policy(
+            &ctx.accounts.token_config,
+            &TransferParty {
+                wallet: ctx.accounts.holder.key(),
+                entry: Some(&ctx.accounts.holder_allowlist_entry),
+                country_rule: ctx.accounts.holder_country_rule.as_deref(),
+                sanctions_flag: ctx.accounts.holder_sanctions_flag.as_ref(),
+            },
+            None,
+            &Pubkey::new_from_array(target_address),
+            None,
+            TransferPolicyAccounts {
+                sale_proposal: ctx.accounts.sale_proposal.as_deref(),
+                destination_registry: None,
+                velocity_counter: ctx.accounts.velocity_counter.as_deref_mut(),
+            },
+            blackout_windows,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        require!(
+            bridge_accounts.get(TRANSFER_NATIVE_FROM_INDEX).map(|account| account.key())
+                == Some(ctx.accounts.holder_token_account.key()),
+            ErrorCode::InvalidBridgeAccounts
+        );
+
+        let leaves = ctx.accounts.holder_token_account.amount == amount;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            ctx.accounts.holder_snapshot_balance.as_deref_mut(),
+            ctx.bumps.holder_snapshot_balance,
+            ctx.accounts.holder_token_account.amount,
+        )?;
+        let holder_balance = ctx.accounts.holder_token_account.amount.checked_sub(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: ctx.accounts.holder_number.as_deref_mut(),
+                last_number: ctx.accounts.last_holder_number.as_deref_mut(),
+                joining_number: None,
+                payer: &ctx.accounts.holder.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            Some((&mut ctx.accounts.holder_allowlist_entry, &ctx.accounts.holder_token_account.key(), holder_balance)),
+            None,
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.holder_allowlist_entry,
+            &ctx.accounts.holder_token_account.key(),
+            ctx.accounts.holder_holder_page.as_ref(),
+            holder_balance,
+        )?;
+
+        // The token bridge moves the shares into custody as its own delegate
+        let token_bridge = ctx.accounts.token_bridge_program.key();
+        let (authority_signer, _) = Pubkey::find_program_address(&[b"authority_signer"], &token_bridge);
+        let delegate = bridge_accounts.iter()
+            .find(|account| account.key() == authority_signer)
+            .ok_or(ErrorCode::InvalidBridgeAccounts)?;
+        let cpi_accounts = token::Approve {
+            to: ctx.accounts.holder_token_account.to_account_info(),
+            delegate: delegate.clone(),
+            authority: ctx.accounts.holder.to_account_info(),
+        };
+        token::approve(CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_accounts), amount)?;
+
+        let mut data = vec![TOKEN_BRIDGE_TRANSFER_NATIVE];
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data.extend_from_slice(&amount.to_le_bytes());
+        // No relayer fee: the holder, as payer, submits the transfer
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&target_address);
+        data.extend_from_slice(&target_chain.to_le_bytes());
+        call_token_bridge(&ctx.accounts.token_bridge_program, bridge_accounts, data, None)?;
+
+        // The cleared proposal is spent by this sale
+        if ctx.accounts.token_config.rofr_period_seconds > 0 {
+            if let Some(proposal) = ctx.accounts.sale_proposal.as_ref() {
+                proposal.close(ctx.accounts.holder.to_account_info())?;
+            }
+        }
+
+        if leaves {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_sub(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        bridge_config.total_bridged_out = bridge_config.total_bridged_out.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit!(SharesBridgedOutEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: bridge_config.token_mint,
+            holder: ctx.accounts.holder.key(),
+            amount,
+            target_chain,
+            target_address,
+            nonce,
+            total_bridged_out: bridge_config.total_bridged_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Complete an inbound Wormhole transfer of the token, permissionlessly. The transfer
+    /// must carry a payload naming the recipient wallet and be addressed to this token's
+    /// bridge config, so no one else can redeem it; the shares are released only into
+    /// that wallet once it passes the allowlist and sanctions checks. Until then they
+    /// stay claimable on the bridge. The remaining accounts are the token bridge's
+    /// `complete_native_with_payload` accounts, in its order, with the bridge vault as
+    /// `to` and the bridge config as redeemer.
+    pub fn redeem_bridged<'info>(ctx: Context<'_, '_, 'info, 'info, RedeemBridged<'info>>) -> Result<()> {
+        require!(!ctx.accounts.token_config.paused, ErrorCode::TokenPaused);
+        require!(ctx.accounts.token_config.features.contains(FeatureFlags::TRANSFERS_ENABLED), ErrorCode::TransfersDisabled);
+        require!(ctx.accounts.bridge_config.enabled, ErrorCode::BridgeDisabled);
+        let clock = Clock::get()?;
+
+        let vaa = &ctx.accounts.vaa;
+        require_keys_eq!(*vaa.owner, ctx.accounts.bridge_config.wormhole_program, ErrorCode::InvalidBridgeTransfer);
+        let message = PostedVaa::parse(&vaa.try_borrow_data()?)?;
+        let transfer = BridgedTransfer::parse(&message.payload)?;
+        let mint_key = ctx.accounts.mint.key();
+        require!(
+            transfer.token_chain == WORMHOLE_CHAIN_SOLANA
+                && transfer.token_address == mint_key.to_bytes()
+                && transfer.to_chain == WORMHOLE_CHAIN_SOLANA
+                && transfer.to == ctx.accounts.bridge_config.key().to_bytes(),
+            ErrorCode::InvalidBridgeTransfer
+        );
+        require!(
+            transfer.recipient == ctx.accounts.recipient.key().to_bytes(),
+            ErrorCode::BridgeRecipientMismatch
+        );
+        require!(
+            ctx.remaining_accounts.get(COMPLETE_NATIVE_VAA_INDEX).map(|account| account.key()) == Some(vaa.key()),
+            ErrorCode::InvalidBridgeAccounts
+        );
+        let amount = denormalize_bridge_amount(transfer.amount, ctx.accounts.mint.decimals)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let recipient_entry = &ctx.accounts.recipient_allowlist_entry;
+        require!(recipient_entry.is_approved, ErrorCode::RecipientNotApproved);
+        require!(
+            recipient_entry.permits_token_account(&ctx.accounts.recipient_token_account.key()),
+            ErrorCode::TokenAccountNotApproved
+        );
+        require!(
+            !is_sanctioned(
+                &ctx.accounts.token_config.sanctions_oracle,
+                ctx.accounts.recipient_sanctions_flag.as_ref(),
+                &ctx.accounts.recipient.key(),
+            )?,
+            ErrorCode::WalletSanctioned
+        );
+
+        let joins = ctx.accounts.recipient_token_account.amount == 0;
+        enforce_share_class(
+            &ctx.accounts.token_config,
+            ctx.accounts.share_class.as_ref(),
+            i64::from(joins),
+            true,
+        )?;
+        checkpoint_snapshot_balance(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            ctx.accounts.recipient_snapshot_balance.as_deref_mut(),
+            ctx.bumps.recipient_snapshot_balance,
+            ctx.accounts.recipient_token_account.amount,
+        )?;
+        let recipient_balance = ctx.accounts.recipient_token_account.amount.checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        sync_holder_index(
+            &mut ctx.accounts.token_config,
+            HolderIndexAccounts {
+                holder_index: ctx.accounts.holder_index.as_deref_mut(),
+                leaving_number: None,
+                last_number: None,
+                joining_number: ctx.accounts.recipient_holder_number.as_deref(),
+                payer: &ctx.accounts.payer.to_account_info(),
+                system_program: &ctx.accounts.system_program.to_account_info(),
+            },
+            None,
+            Some((&mut ctx.accounts.recipient_allowlist_entry, &ctx.accounts.recipient_token_account.key(), recipient_balance)),
+        )?;
+        sync_cap_table(
+            &ctx.accounts.token_config,
+            &mut ctx.accounts.recipient_allowlist_entry,
+            &ctx.accounts.recipient_token_account.key(),
+            ctx.accounts.recipient_holder_page.as_ref(),
+            recipient_balance,
+        )?;
+
+        // The token bridge releases custody into the vault, signed for by the bridge config
+        let staged = ctx.accounts.bridge_vault.amount;
+        let seeds: &[&[u8]] = &[b"bridge_config", mint_key.as_ref(), &[ctx.accounts.bridge_config.bump]];
+        call_token_bridge(
+            &ctx.accounts.token_bridge_program,
+            ctx.remaining_accounts,
+            vec![TOKEN_BRIDGE_COMPLETE_NATIVE_WITH_PAYLOAD],
+            Some((ctx.accounts.bridge_config.key(), seeds)),
+        )?;
+        ctx.accounts.bridge_vault.reload()?;
+        require!(
+            ctx.accounts.bridge_vault.amount.checked_sub(staged) == Some(amount),
+            ErrorCode::InvalidBridgeAccounts
+        );
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.bridge_vault.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.recipient_token_account.to_account_info(),
+            authority: ctx.accounts.bridge_config.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, &[seeds]),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        if joins {
+            let token_config = &mut ctx.accounts.token_config;
+            token_config.holder_count = token_config.holder_count.checked_add(1)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        // Shares bridged out by calling the token bridge directly return through here too
+        let bridge_config = &mut ctx.accounts.bridge_config;
+        bridge_config.total_bridged_out = bridge_config.total_bridged_out.saturating_sub(amount);
+
+        emit!(SharesBridgedInEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: ctx.accounts.token_config.next_event_sequence()?,
+            token_mint: mint_key,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            emitter_chain: message.emitter_chain,
+            vaa_sequence: message.sequence,
+            total_bridged_out: bridge_config.total_bridged_out,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+/// Maximum length of a share class name
+pub const MAX_SHARE_CLASS_NAME_LEN: usize = 32;
+
+/// Country code of an allowlist entry whose residence has not been recorded
+pub const UNASSIGNED_COUNTRY: [u8; 2] = [0; 2];
+
+/// Number of tokens listed per registry page
+pub const REGISTRY_PAGE_CAPACITY: usize = 64;
+
+/// Transfers a `TransferJournal` retains before overwriting the oldest
+pub const TRANSFER_JOURNAL_CAPACITY: usize = 64;
+
+/// Holder records stored per cap table page, sized to stay under the 10 KiB limit on
+/// accounts created by CPI
+pub const HOLDER_PAGE_CAPACITY: usize = 128;
+
+/// Remaining accounts passed per holder to `crank_allocation`: allowlist entry, token
+/// account, current-snapshot checkpoint PDA, holder page and the next holder number
+/// account for a wallet joining the holder index (the program id for none)
+pub const ALLOCATION_ACCOUNTS_PER_HOLDER: usize = 5;
+
+/// Remaining accounts passed per leg to `batch_transfer`: source token account,
+/// destination token account, sender allowlist entry and recipient allowlist entry
+pub const BATCH_TRANSFER_ACCOUNTS_PER_LEG: usize = 4;
+
+/// Remaining accounts passed per recipient to `batch_mint`: token account and allowlist entry
+pub const BATCH_MINT_ACCOUNTS_PER_RECIPIENT: usize = 2;
+
+/// How long an allowlist entry must have been revoked before `reclaim_stale_accounts`
+/// may close it
+pub const STALE_ENTRY_RETENTION_SECONDS: i64 = 365 * 86_400;
+
+/// Zero bytes appended when decoding an account written under an older layout; covers
+/// every field added since
+pub const MIGRATION_PADDING: usize = 256;
+
+/// Allowlist slots per bitmap page, and the depth of the page's assignment tree
+pub const BITMAP_PAGE_SLOTS: u32 = 8_192;
+pub const BITMAP_PAGE_DEPTH: usize = 13;
+
+/// spl-account-compression and the noop program it logs tree changes through
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Metaplex Bubblegum, which mints share certificates as compressed NFTs
+pub const BUBBLEGUM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+/// allowlist-transfer-hook, which Token-2022 mints created with a transfer hook run on
+/// every transfer
+pub const ALLOWLIST_TRANSFER_HOOK_ID: Pubkey = pubkey!("97WbTLYAUdT1TrgJRn2hbtbBUtUvkXz8cVTA6NUu7wvG");
+
+/// Transfer hook interface discriminator of `initialize_extra_account_meta_list`
+const TRANSFER_HOOK_INITIALIZE_EXTRA_ACCOUNT_METAS: [u8; 8] = [43, 34, 13, 49, 167, 88, 235, 235];
+
+/// Longest restriction legend a share certificate carries
+pub const MAX_CERTIFICATE_LEGEND_LEN: usize = 200;
+
+/// Longest certificate metadata base URI: Bubblegum's 200 less `/<holder>`
+pub const MAX_CERTIFICATE_URI_LEN: usize = 155;
+
+/// Class named on certificates of tokens without a `ShareClass`
+pub const DEFAULT_CERTIFICATE_CLASS: &str = "Common";
+
+/// Widest oracle confidence interval priced actions accept, in basis points of the price
+pub const MAX_ORACLE_CONFIDENCE_BPS: u64 = 200;
+
+/// Length of a velocity-limit epoch
+pub const VELOCITY_EPOCH_SECONDS: i64 = 86_400;
+
+/// Maximum number of blackout windows that may exist for a token at once
+pub const MAX_OPEN_BLACKOUT_WINDOWS: usize = 8;
+
+/// Layout version stamped on every emitted event. Bump when any event's fields change
+/// so indexers can pick the matching decoder during rolling upgrades.
+/// Version 2 added `sequence`, drawn from the token's `TokenConfig::event_sequence`:
+/// consecutive per mint across all event types, so a skipped value means a missed event.
+/// Only events not scoped to one token (issuers, multi-class waterfalls) go without.
+pub const EVENT_SCHEMA_VERSION: u8 = 2;
+
+/// Fixed-point scale of the interest accrual index
+pub const INTEREST_INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// Seconds in the 365-day year interest rates are quoted over
+pub const SECONDS_PER_YEAR: u128 = 31_536_000;
+
+/// Highest annual accrual rate a share class may carry (100%)
+pub const MAX_ACCRUAL_RATE_BPS: u16 = 10_000;
+
+/// Highest liquidation preference multiple a share class may carry (10x)
+pub const MAX_LIQUIDATION_MULTIPLE_BPS: u32 = 100_000;
+
+/// Maximum number of members on an authority council
+pub const MAX_COUNCIL_MEMBERS: usize = 10;
+
+/// Maximum accounts a council or queued operation's instruction may reference, room for
+/// a `force_transfer` of a hooked mint with the hook's accounts
+pub const MAX_OPERATION_ACCOUNTS: usize = 40;
+
+/// Longest timelock delay that can be configured (30 days)
+pub const MAX_TIMELOCK_SECONDS: i64 = 30 * 86_400;
+
+/// Maximum length of a shareholder proposal title
+pub const MAX_PROPOSAL_TITLE_LEN: usize = 64;
+
+/// Number of `ProposalType` variants, each with its own rules in `GovernanceConfig`
+pub const PROPOSAL_TYPE_COUNT: usize = 2;
+
+/// Maximum length of a resolution document URI
+pub const MAX_RESOLUTION_URI_LEN: usize = 200;
+
+/// Domain prefix of the message signed for a transfer permit
+pub const PERMIT_DOMAIN: &[u8] = b"chainequity:permit_transfer";
+
+/// Longest metadata URI accepted by Metaplex Token Metadata
+pub const MAX_METADATA_URI_LEN: usize = 200;
+
+/// Longest issuer legal name and the most tokens one issuer may register
+pub const MAX_ISSUER_NAME_LEN: usize = 50;
+pub const MAX_ISSUER_TOKENS: usize = 16;
+
+/// Shortest and longest voting periods a proposal may run for
+pub const MIN_VOTING_PERIOD_SECONDS: i64 = 3_600;
+pub const MAX_VOTING_PERIOD_SECONDS: i64 = 90 * 86_400;
+
+/// Remaining accounts passed per delegator to `cast_vote`: vote delegation, allowlist
+/// entry, token account, snapshot checkpoint, vote record
+pub const DELEGATED_VOTE_ACCOUNTS: usize = 5;
+
+/// Maximum instruction data a council operation may carry
+pub const MAX_OPERATION_DATA_LEN: usize = 512;
+
+/// Share classes (mints) a single liquidation waterfall can cover
+pub const MAX_WATERFALL_CLASSES: usize = 8;
+
+/// Remaining accounts passed per class to `execute_waterfall`:
+/// token config, share class, share mint, distribution, distribution vault
+pub const WATERFALL_ACCOUNTS_PER_CLASS: usize = 5;
+
+/// Upper bound on the transfer fee (10%)
+pub const MAX_TRANSFER_FEE_BPS: u16 = 1_000;
+
+/// Maximum number of program destinations in a destination registry
+pub const MAX_APPROVED_DESTINATIONS: usize = 16;
+
+/// Maximum number of keys that may co-sign a circuit breaker reset
+pub const MAX_BREAKER_SIGNERS: usize = 5;
+
+/// Claim topics an ERC-3643 registry may require, and topics one trusted issuer may attest
+pub const MAX_CLAIM_TOPICS: usize = 8;
+
+/// Maximum number of issuers in a token's trusted issuers registry
+pub const MAX_TRUSTED_ISSUERS: usize = 8;
+
+/// Claims one identity can hold
+pub const MAX_IDENTITY_CLAIMS: usize = 16;
+
+/// Wormhole chain id of Solana
+pub const WORMHOLE_CHAIN_SOLANA: u16 = 1;
+
+/// Position of `from` among the token bridge's `transfer_native` accounts
+pub const TRANSFER_NATIVE_FROM_INDEX: usize = 2;
+
+/// Position of the posted VAA among the token bridge's `complete_native_with_payload` accounts
+pub const COMPLETE_NATIVE_VAA_INDEX: usize = 2;
+
+/// Token bridge instruction tags of `transfer_native` and `complete_native_with_payload`
+const TOKEN_BRIDGE_TRANSFER_NATIVE: u8 = 5;
+const TOKEN_BRIDGE_COMPLETE_NATIVE_WITH_PAYLOAD: u8 = 9;
+
+/// Decimals Wormhole truncates bridged amounts to
+const WORMHOLE_DECIMALS: u8 = 8;
+
+/// Fold a wallet into (or out of) the order-independent allowlist checksum.
+/// Approving and revoking the same wallet cancel out.
+fn fold_allowlist_checksum(checksum: &mut [u8; 32], wallet: &Pubkey) {
+    let digest = solana_sha256_hasher::hashv(&[b"allowlist", wallet.as_ref()]);
+    for (byte, d) in checksum.iter_mut().zip(digest.to_bytes().iter()) {
+        *byte ^= d;
+    }
+}
+
+/// Record a settled corporate action for one holder and announce it
+fn write_action_receipt(token_config: &mut TokenConfig, receipt: &mut Account<ActionReceipt>, terms: ActionReceipt) -> Result<()> {
+    emit!(ActionReceiptIssuedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        action: terms.action,
+        kind: terms.kind,
+        holder: terms.holder,
+        amount_in: terms.amount_in,
+        amount_out: terms.amount_out,
+        settled_at: terms.settled_at,
+    });
+    receipt.set_inner(terms);
+    Ok(())
+}
+
+/// One side of a transfer, as the token's transfer controls see it
+struct TransferParty<'a, 'info> {
+    wallet: Pubkey,
+    /// `None` for a wallet a compliance permit lets trade without an allowlist entry
+    entry: Option<&'a AllowlistEntry>,
+    country_rule: Option<&'a Account<'info, CountryRule>>,
+    sanctions_flag: Option<&'a UncheckedAccount<'info>>,
+}
+
+impl TransferParty<'_, '_> {
+    fn is_insider(&self) -> bool {
+        self.entry.is_some_and(|entry| entry.is_insider)
+    }
+}
+
+/// Accounts for the transfer controls beyond the two parties, see `enforce_transfer_policy`
+struct TransferPolicyAccounts<'a, 'info> {
+    sale_proposal: Option<&'a Account<'info, SaleProposal>>,
+    destination_registry: Option<&'a Account<'info, DestinationRegistry>>,
+    velocity_counter: Option<&'a mut Account<'info, VelocityCounter>>,
+}
+
+/// The checks every path that moves shares out of a wallet shares with `gated_transfer`:
+/// both parties' country rules and sanctions flags, blackout windows, the issuer's right
+/// of first refusal, registered destination programs and the rolling daily limit.
+/// `recipient` is `None` when the shares leave the chain; `buyer` is the counterparty a
+/// sale proposal must name and `destination` the receiving account's owner, if on chain.
+/// `blackout_windows` must hold every open window of the token.
+#[allow(clippy::too_many_arguments)]
+fn enforce_transfer_policy<'info>(
+    token_config: &TokenConfig,
+    sender: &TransferParty<'_, 'info>,
+    recipient: Option<&TransferParty<'_, 'info>>,
+    buyer: &Pubkey,
+    destination: Option<&AccountInfo<'info>>,
+    accounts: TransferPolicyAccounts<'_, 'info>,
+    blackout_windows: &[AccountInfo],
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    for party in std::iter::once(sender).chain(recipient) {
+        screen_transfer_party(token_config, party)?;
+    }
+
+    // Reject trades inside any active blackout window
+    enforce_blackout_windows(token_config, blackout_windows, sender, recipient, now)?;
+
+    // Secondary sales must first clear the issuer's right of first refusal
+    if token_config.rofr_period_seconds > 0 {
+        let proposal = accounts.sale_proposal.ok_or(ErrorCode::SaleProposalRequired)?;
+        require!(
+            proposal.buyer == *buyer && proposal.amount == amount,
+            ErrorCode::SaleProposalMismatch
+        );
+        require!(proposal.status == SaleProposalStatus::Pending, ErrorCode::RofrExercised);
+        require!(now >= proposal.expires_at, ErrorCode::RofrPeriodActive);
+    }
+
+    // Program-controlled destinations (pools, vaults, unknown PDAs) must be registered,
+    // except the token's own authority, which may be a multisig vault PDA
+    if let Some(destination) = destination {
+        if destination.key() != token_config.authority && is_program_controlled(destination) {
+            let approved = accounts.destination_registry
+                .is_some_and(|registry| registry.is_approved(destination));
+            require!(approved, ErrorCode::DestinationProgramNotApproved);
+        }
+    }
+
+    // Enforce the rolling 24h send limit
+    let daily_limit = token_config.daily_transfer_limit;
+    if daily_limit > 0 {
+        let velocity_counter = accounts.velocity_counter.ok_or(ErrorCode::VelocityCounterRequired)?;
+        velocity_counter.record(sender.wallet, amount, daily_limit, now)?;
+    }
+    Ok(())
+}
+
+/// A counterparty must reside in a currently allowed jurisdiction, and a sanctions hit
+/// fails the transfer regardless of allowlist status
+fn screen_transfer_party(token_config: &TokenConfig, party: &TransferParty) -> Result<()> {
+    if let Some(entry) = party.entry {
+        if let Some(rule) = party.country_rule {
+            require!(
+                rule.token_mint == token_config.mint && rule.country_code == entry.country_code,
+                ErrorCode::CountryRuleMismatch
+            );
+        }
+        enforce_country_rule(entry, party.country_rule)?;
+    }
+    require!(
+        !is_sanctioned(&token_config.sanctions_oracle, party.sanctions_flag, &party.wallet)?,
+        ErrorCode::WalletSanctioned
+    );
+    Ok(())
+}
+
+/// Refuse a transfer path that settles through a program vault while the token runs any
+/// control checked only as shares move directly between holders: velocity limits,
+/// blackout windows and ROFR, besides fees, memos and the journal
+fn require_no_transfer_controls(token_config: &TokenConfig) -> Result<()> {
+    require_no_fee_memo_or_journal(token_config)?;
+    require!(
+        token_config.daily_transfer_limit == 0
+            && token_config.open_blackout_windows == 0
+            && token_config.rofr_period_seconds == 0,
+        ErrorCode::TransferPathUnsupported
+    );
+    Ok(())
+}
+
+/// Refuse a transfer path that neither collects transfer fees, carries a travel-rule
+/// memo nor appends to the transfer journal while the token has any of them on
+fn require_no_fee_memo_or_journal(token_config: &TokenConfig) -> Result<()> {
+    require!(
+        token_config.transfer_fee_bps == 0
+            && !token_config.features.contains(FeatureFlags::MEMO_REQUIRED)
+            && !token_config.journal_enabled,
+        ErrorCode::TransferPathUnsupported
+    );
+    Ok(())
+}
+
+/// Check a transfer against every open blackout window of the token.
+/// All open windows must be supplied so a caller cannot skip one.
+fn enforce_blackout_windows<'info>(
+    token_config: &TokenConfig,
+    windows: &[AccountInfo],
+    sender: &TransferParty<'_, 'info>,
+    recipient: Option<&TransferParty<'_, 'info>>,
+    now: i64,
+) -> Result<()> {
+    let mut seen: Vec<u64> = Vec::with_capacity(windows.len());
+    for info in windows.iter() {
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::InvalidBlackoutWindow);
+        let window = BlackoutWindow::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require_keys_eq!(window.token_mint, token_config.mint, ErrorCode::InvalidBlackoutWindow);
+        require!(!seen.contains(&window.window_id), ErrorCode::InvalidBlackoutWindow);
+        seen.push(window.window_id);
+
+        let applies = std::iter::once(sender).chain(recipient)
+            .any(|party| window.applies_to(&party.wallet, party.is_insider()));
+        if window.is_active(now) && applies {
+            emit!(TransferBlockedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                // Logged only by the failing transaction, so nothing is consumed
+                sequence: token_config.event_sequence,
+                token_mint: token_config.mint,
+                from: sender.wallet,
+                to: recipient.map_or(Pubkey::default(), |party| party.wallet),
+                window_id: window.window_id,
+                timestamp: now,
+            });
+            return err!(ErrorCode::TradingBlackout);
+        }
+    }
+    require!(
+        seen.len() == usize::from(token_config.open_blackout_windows),
+        ErrorCode::BlackoutWindowsMissing
+    );
+    Ok(())
+}
+
+/// Split remaining accounts into the token's open blackout windows, which come first,
+/// and whatever the instruction takes after them
+fn split_blackout_windows<'a, 'info>(
+    token_config: &TokenConfig,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let open_windows = usize::from(token_config.open_blackout_windows);
+    require!(accounts.len() >= open_windows, ErrorCode::BlackoutWindowsMissing);
+    Ok(accounts.split_at(open_windows))
+}
+
+/// Append a newly created token to the current registry page
+fn append_to_token_registry(
+    token_config: &mut TokenConfig,
+    token_registry: &mut Account<TokenRegistry>,
+    registry_page: &mut Account<RegistryPage>,
+    page_bump: u8,
+    entry: RegistryEntry,
+) -> Result<()> {
+    let page_index = token_registry.current_page_index();
+    if registry_page.entries.is_empty() {
+        registry_page.page_index = page_index;
+        registry_page.bump = page_bump;
+    }
+    require!(registry_page.page_index == page_index, ErrorCode::InvalidRegistryPage);
+    require!(registry_page.entries.len() < REGISTRY_PAGE_CAPACITY, ErrorCode::InvalidRegistryPage);
+
+    emit!(TokenRegisteredEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        mint: entry.mint,
+        authority: entry.authority,
+        index: token_registry.total_tokens,
+        page_index,
+    });
+
+    registry_page.entries.push(entry);
+    token_registry.total_tokens = token_registry.total_tokens.checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+/// Whether `code` looks like an ISO 3166-1 alpha-2 code (two uppercase ASCII letters)
+fn is_valid_country_code(code: &[u8; 2]) -> bool {
+    code.iter().all(u8::is_ascii_uppercase)
+}
+
+/// Check a wallet's recorded country against its rule. Wallets without a recorded
+/// country are not subject to country rules.
+fn enforce_country_rule(entry: &AllowlistEntry, rule: Option<&Account<CountryRule>>) -> Result<()> {
+    if entry.country_code == UNASSIGNED_COUNTRY {
+        return Ok(());
+    }
+    let rule = rule.ok_or(ErrorCode::CountryRuleRequired)?;
+    require!(rule.is_allowed, ErrorCode::CountryNotAllowed);
+    Ok(())
+}
+
+/// Create the share mint. With `transfer_hook` set the mint must live under Token-2022
+/// and is created with the allowlist transfer hook, so the allowlist holds for raw
+/// token-program transfers too.
+#[allow(clippy::too_many_arguments)]
+fn create_share_mint<'info>(
+    payer: &Signer<'info>,
+    mint: &Signer<'info>,
+    mint_authority: &Pubkey,
+    freeze_authority: &Pubkey,
+    decimals: u8,
+    transfer_hook: bool,
+    token_program: &Interface<'info, TokenInterface>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    use token::spl_token_2022::extension::{transfer_hook as hook_extension, ExtensionType};
+    use token::spl_token_2022::{instruction as token_instruction, state::Mint as MintState};
+
+    let token_2022 = token_program.key() == token::spl_token_2022::ID;
+    require!(token_2022 || !transfer_hook, ErrorCode::TransferHookRequiresToken2022);
+    let mut extensions = Vec::new();
+    if token_2022 {
+        extensions.push(ExtensionType::PermanentDelegate);
+    }
+    if transfer_hook {
+        extensions.push(ExtensionType::TransferHook);
+    }
+    let space = ExtensionType::try_calculate_account_len::<MintState>(&extensions)?;
+    let cpi_accounts = system_program::CreateAccount {
+        from: payer.to_account_info(),
+        to: mint.to_account_info(),
+    };
+    system_program::create_account(
+        CpiContext::new(system_program.to_account_info(), cpi_accounts),
+        Rent::get()?.minimum_balance(space),
+        space as u64,
+        &token_program.key(),
+    )?;
+
+    // Extensions are initialized before the mint itself. The mint-authority PDA is the
+    // permanent delegate `force_transfer` signs as, and the hook program is fixed for
+    // good: no authority may repoint it.
+    if token_2022 {
+        let instruction = token_instruction::initialize_permanent_delegate(
+            &token_program.key(),
+            &mint.key(),
+            mint_authority,
+        )?;
+        invoke(&instruction, &[mint.to_account_info()])?;
+    }
+    if transfer_hook {
+        let instruction = hook_extension::instruction::initialize(
+            &token_program.key(),
+            &mint.key(),
+            None,
+            Some(ALLOWLIST_TRANSFER_HOOK_ID),
+        )?;
+        invoke(&instruction, &[mint.to_account_info()])?;
+    }
+    let instruction = token_instruction::initialize_mint2(
+        &token_program.key(),
+        &mint.key(),
+        mint_authority,
+        Some(freeze_authority),
+        decimals,
+    )?;
+    invoke(&instruction, &[mint.to_account_info()])?;
+    Ok(())
+}
+
+/// `transfer_checked` that forwards the accounts a mint's transfer hook needs. They are
+/// looked up by address in `hook_accounts`, which should hold the hook program, its
+/// validation account and every extra account it lists; mints without a hook ignore
+/// them.
+fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    token::spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        hook_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Whether `mint` was created with a transfer hook
+fn has_transfer_hook(mint: &InterfaceAccount<Mint>) -> Result<bool> {
+    use token::spl_token_2022::extension::{transfer_hook as hook_extension, StateWithExtensions};
+    use token::spl_token_2022::state::Mint as MintState;
+
+    let info = mint.to_account_info();
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(hook_extension::get_program_id(&state).is_some())
+}
+
+/// Whether `mint` names `delegate` its permanent delegate
+fn is_permanent_delegate(mint: &InterfaceAccount<Mint>, delegate: &Pubkey) -> Result<bool> {
+    use token::spl_token_2022::extension::{permanent_delegate, StateWithExtensions};
+    use token::spl_token_2022::state::Mint as MintState;
+
+    let info = mint.to_account_info();
+    let data = info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(permanent_delegate::get_permanent_delegate(&state) == Some(*delegate))
+}
+
+/// Approve the PDA owning a program vault of a hooked mint, so the transfer hook lets
+/// shares in and out of the vault. `entry` must be written back before the vault moves
+/// shares, as the hook reads it from the account.
+fn approve_vault_owner<'info>(
+    token_config: &mut TokenConfig,
+    mint: &InterfaceAccount<'info, Mint>,
+    entry: Option<&mut Account<'info, AllowlistEntry>>,
+    bump: Option<u8>,
+    vault_owner: Pubkey,
+    approved_by: Pubkey,
+) -> Result<()> {
+    let Some(entry) = entry else {
+        require!(!has_transfer_hook(mint)?, ErrorCode::VaultAllowlistEntryRequired);
+        return Ok(());
+    };
+    let clock = Clock::get()?;
+    entry.wallet = vault_owner;
+    entry.is_approved = true;
+    entry.approved_at = clock.unix_timestamp;
+    entry.lockup_until = None;
+    entry.transfer_locked = false;
+    entry.is_insider = false;
+    entry.approved_token_account = None;
+    entry.country_code = UNASSIGNED_COUNTRY;
+    entry.last_snapshot_id = 0;
+    entry.holder_page = None;
+    entry.allocated_through = 0;
+    entry.drip_enabled = false;
+    entry.interest_index = token_config.interest_index;
+    entry.accrued_interest = 0;
+    entry.version = AllowlistEntry::CURRENT_VERSION;
+    entry.holder_indexed = false;
+    entry.bump = bump.ok_or(ErrorCode::VaultAllowlistEntryRequired)?;
+    entry.exit(&crate::ID)?;
+    fold_allowlist_checksum(&mut token_config.allowlist_checksum, &vault_owner);
+
+    emit!(WalletApprovedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        token_mint: token_config.mint,
+        wallet: vault_owner,
+        approved_by,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Take a closing vault's owner back off the allowlist. Its context closes `entry`.
+fn retire_vault_owner(
+    token_config: &mut TokenConfig,
+    mint: &InterfaceAccount<Mint>,
+    entry: Option<&Account<AllowlistEntry>>,
+) -> Result<()> {
+    match entry {
+        Some(entry) => fold_allowlist_checksum(&mut token_config.allowlist_checksum, &entry.wallet),
+        None => require!(!has_transfer_hook(mint)?, ErrorCode::VaultAllowlistEntryRequired),
+    }
+    Ok(())
+}
+
+/// Close the `[vault_seed, owner]` vault of a finished program account and the owner's
+/// allowlist entry, sending their rent to `rent_destination` and returning how many
+/// accounts were closed. A vault still open must be empty; either may already be gone.
+#[allow(clippy::too_many_arguments)]
+fn reclaim_vault_of<'info>(
+    token_config: &mut TokenConfig,
+    owner: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    vault_seed: &[u8],
+    vault: &'info AccountInfo<'info>,
+    entry: &'info AccountInfo<'info>,
+    token_program: Option<&Interface<'info, TokenInterface>>,
+    rent_destination: &AccountInfo<'info>,
+) -> Result<u32> {
+    let mut closed = 0;
+    let (expected_vault, _) = Pubkey::find_program_address(&[vault_seed, owner.key.as_ref()], &crate::ID);
+    require_keys_eq!(expected_vault, vault.key(), ErrorCode::AccountNotReclaimable);
+    if vault.lamports() > 0 {
+        let token_program = token_program.ok_or(ErrorCode::AccountNotReclaimable)?;
+        let vault_account: InterfaceAccount<TokenAccount> = InterfaceAccount::try_from(vault)?;
+        require!(
+            vault_account.owner == owner.key() && vault_account.amount == 0,
+            ErrorCode::AccountNotReclaimable
+        );
+        let cpi_accounts = token::CloseAccount {
+            account: vault.clone(),
+            destination: rent_destination.clone(),
+            authority: owner.clone(),
+        };
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        ))?;
+        closed += 1;
+    }
+
+    let (expected_entry, _) = Pubkey::find_program_address(
+        &[b"allowlist", token_config.mint.as_ref(), owner.key.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(expected_entry, entry.key(), ErrorCode::AccountNotReclaimable);
+    if entry.lamports() > 0 {
+        let entry: Account<AllowlistEntry> = Account::try_from(entry)?;
+        fold_allowlist_checksum(&mut token_config.allowlist_checksum, &entry.wallet);
+        entry.close(rent_destination.clone())?;
+        closed += 1;
+    }
+    Ok(closed)
+}
+
+/// Empty a program-owned vault into `destination` and close it to `rent_destination`,
+/// signing as the PDA `vault_authority` that owns it. `hook_accounts` are forwarded to
+/// the mint's transfer hook.
+#[allow(clippy::too_many_arguments)]
+fn drain_program_vault<'info>(
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    rent_destination: AccountInfo<'info>,
+    vault_authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    let cpi_program = token_program.to_account_info();
+
+    let cpi_accounts = TransferChecked {
+        from: vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to: destination,
+        authority: vault_authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program.clone(), cpi_accounts, signer_seeds);
+    transfer_checked_with_hook(cpi_ctx, vault.amount, mint.decimals, hook_accounts)?;
+
+    let cpi_accounts = token::CloseAccount {
+        account: vault.to_account_info(),
+        destination: rent_destination,
+        authority: vault_authority,
+    };
+    token::close_account(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds))
+}
+
+/// Prefix of a Pyth-style price feed account (e.g. mock-oracle's `PriceFeed`): the
+/// Anchor discriminator followed by these fields
+#[derive(AnchorDeserialize)]
+struct PriceFeedHeader {
+    _authority: Pubkey,
+    price: i64,
+}
+
+/// Full Pyth-style price feed account (e.g. mock-oracle's `PriceFeed`) after its
+/// discriminator
+#[derive(AnchorDeserialize)]
+struct PriceFeedAccount {
+    _authority: Pubkey,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+}
+
+/// Anchor discriminator of the Pyth receiver's `PriceUpdateV2`:
+/// sha256("account:PriceUpdateV2")[..8]
+const PYTH_PRICE_UPDATE_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+#[derive(AnchorDeserialize)]
+enum PythVerificationLevel {
+    Partial { _num_signatures: u8 },
+    Full,
+}
+
+/// Prefix of a Pyth `PriceUpdateV2` account after its discriminator
+#[derive(AnchorDeserialize)]
+struct PythPriceUpdate {
+    _write_authority: Pubkey,
+    verification_level: PythVerificationLevel,
+    _feed_id: [u8; 32],
+    price: i64,
+    conf: u64,
+    exponent: i32,
+    publish_time: i64,
+}
+
+/// Current price of the token's registered price feed, read as a Pyth `PriceUpdateV2`
+/// or a Pyth-style `PriceFeed` by its discriminator. Rejects feeds that are not the
+/// registered one, non-positive prices, Pyth updates without full guardian
+/// verification, prices older than the token's maximum age and prices whose
+/// confidence interval is wider than `MAX_ORACLE_CONFIDENCE_BPS` of the price.
+fn read_oracle_price(token_config: &TokenConfig, feed: &UncheckedAccount, now: i64) -> Result<OraclePrice> {
+    require!(token_config.price_oracle != Pubkey::default(), ErrorCode::PriceOracleNotSet);
+    require_keys_eq!(feed.key(), token_config.price_feed, ErrorCode::InvalidPriceFeed);
+    require_keys_eq!(*feed.owner, token_config.price_oracle, ErrorCode::InvalidPriceFeed);
+
+    let data = feed.try_borrow_data()?;
+    require!(data.len() >= 8, ErrorCode::InvalidPriceFeed);
+    let oracle_price = if data[..8] == PYTH_PRICE_UPDATE_DISCRIMINATOR {
+        let update = PythPriceUpdate::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::InvalidPriceFeed)?;
+        require!(
+            matches!(update.verification_level, PythVerificationLevel::Full),
+            ErrorCode::InvalidPriceFeed
+        );
+        OraclePrice {
+            price: update.price,
+            confidence: update.conf,
+            exponent: update.exponent,
+            publish_time: update.publish_time,
+        }
+    } else {
+        let discriminator = solana_sha256_hasher::hashv(&[b"account:PriceFeed"]);
+        require!(data[..8] == discriminator.to_bytes()[..8], ErrorCode::InvalidPriceFeed);
+        let feed = PriceFeedAccount::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::InvalidPriceFeed)?;
+        OraclePrice {
+            price: feed.price,
+            confidence: feed.conf,
+            exponent: feed.expo,
+            publish_time: feed.publish_time,
+        }
+    };
+
+    require!(oracle_price.price > 0, ErrorCode::InvalidOraclePrice);
+    require!(
+        now.saturating_sub(oracle_price.publish_time) <= token_config.max_price_age_seconds,
+        ErrorCode::StaleOraclePrice
+    );
+    require!(
+        u128::from(oracle_price.confidence) * 10_000
+            <= oracle_price.price as u128 * u128::from(MAX_ORACLE_CONFIDENCE_BPS),
+        ErrorCode::OraclePriceUncertain
+    );
+    Ok(oracle_price)
+}
+
+/// Value of `amount` raw token units at `price` payment base units per whole share,
+/// rounded down
+fn shares_value(amount: u64, price: u64, decimals: u8) -> Result<u64> {
+    let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+    let value = u128::from(amount)
+        .checked_mul(u128::from(price))
+        .ok_or(ErrorCode::Overflow)?
+        / unit;
+    Ok(u64::try_from(value).map_err(|_| ErrorCode::Overflow)?)
+}
+
+/// Anchor discriminator of the oracle's `SanctionsFlag`: sha256("account:SanctionsFlag")[..8],
+/// spelled out so every screened transfer does not hash it again
+const SANCTIONS_FLAG_DISCRIMINATOR: [u8; 8] = [236, 152, 117, 94, 236, 159, 10, 62];
+
+/// Prefix of a sanctions-oracle flag account: the oracle's Anchor `SanctionsFlag`
+/// discriminator followed by these fields.
+#[derive(AnchorDeserialize)]
+struct SanctionsFlagHeader {
+    wallet: Pubkey,
+    is_sanctioned: bool,
+}
+
+/// Whether the registered sanctions oracle has flagged `wallet`. The flag must be the
+/// oracle's `[b"sanctions_flag", wallet]` PDA; a wallet the oracle never screened has no
+/// flag account yet and is treated as clear.
+fn is_sanctioned(oracle: &Pubkey, flag: Option<&UncheckedAccount>, wallet: &Pubkey) -> Result<bool> {
+    if *oracle == Pubkey::default() {
+        return Ok(false);
+    }
+    let flag = flag.ok_or(ErrorCode::SanctionsFlagRequired)?;
+    let (expected, _) = Pubkey::find_program_address(&[b"sanctions_flag", wallet.as_ref()], oracle);
+    require_keys_eq!(flag.key(), expected, ErrorCode::InvalidSanctionsFlag);
+
+    if flag.data_is_empty() {
+        return Ok(false);
+    }
+    require_keys_eq!(*flag.owner, *oracle, ErrorCode::InvalidSanctionsFlag);
+
+    let data = flag.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && data[..8] == SANCTIONS_FLAG_DISCRIMINATOR,
+        ErrorCode::InvalidSanctionsFlag
+    );
+    let header = SanctionsFlagHeader::deserialize(&mut &data[8..])
+        .map_err(|_| ErrorCode::InvalidSanctionsFlag)?;
+    require_keys_eq!(header.wallet, *wallet, ErrorCode::InvalidSanctionsFlag);
+    Ok(header.is_sanctioned)
+}
+
+/// Wormhole core bridge `PostedVAA` account: the "vaa" magic, then these fields in Borsh
+#[derive(AnchorDeserialize)]
+struct PostedVaa {
+    _version: u8,
+    _consistency_level: u8,
+    _vaa_time: u32,
+    _vaa_signature_account: Pubkey,
+    _submission_time: u32,
+    _nonce: u32,
+    sequence: u64,
+    emitter_chain: u16,
+    _emitter_address: [u8; 32],
+    payload: Vec<u8>,
+}
+
+impl PostedVaa {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let mut body = data.strip_prefix(b"vaa").ok_or(ErrorCode::InvalidBridgeTransfer)?;
+        Self::deserialize(&mut body).map_err(|_| error!(ErrorCode::InvalidBridgeTransfer))
+    }
+}
+
+/// Token bridge transfer with payload (payload id 3) whose own payload starts with the
+/// recipient wallet. Integers are big-endian on the wire.
+struct BridgedTransfer {
+    /// Amount normalized to at most 8 decimals
+    amount: u64,
+    token_address: [u8; 32],
+    token_chain: u16,
+    to: [u8; 32],
+    to_chain: u16,
+    recipient: [u8; 32],
+}
+
+impl BridgedTransfer {
+    fn parse(payload: &[u8]) -> Result<Self> {
+        // id, u256 amount, token address, token chain, to, to chain, sender, payload
+        require!(payload.len() >= 133 + 32 && payload[0] == 3, ErrorCode::InvalidBridgeTransfer);
+        require!(payload[1..25].iter().all(|byte| *byte == 0), ErrorCode::InvalidBridgeTransfer);
+        let address = |at: usize| -> [u8; 32] { payload[at..at + 32].try_into().unwrap() };
+        Ok(Self {
+            amount: u64::from_be_bytes(payload[25..33].try_into().unwrap()),
+            token_address: address(33),
+            token_chain: u16::from_be_bytes([payload[65], payload[66]]),
+            to: address(67),
+            to_chain: u16::from_be_bytes([payload[99], payload[100]]),
+            recipient: address(133),
+        })
+    }
+}
+
+/// Reject amounts the token bridge would truncate to 8 decimals, stranding the dust
+fn bridge_amount(amount: u64, decimals: u8) -> Result<()> {
+    let unit = 10u64.pow(u32::from(decimals.saturating_sub(WORMHOLE_DECIMALS)));
+    require!(amount.checked_rem(unit) == Some(0), ErrorCode::AmountNotBridgeable);
+    Ok(())
+}
+
+/// Raw amount of a transfer the token bridge normalized to at most 8 decimals
+fn denormalize_bridge_amount(normalized: u64, decimals: u8) -> Result<u64> {
+    let unit = 10u64.pow(u32::from(decimals.saturating_sub(WORMHOLE_DECIMALS)));
+    Ok(normalized.checked_mul(unit).ok_or(ErrorCode::Overflow)?)
+}
+
+/// Call the token bridge over `accounts`, in order, with `data`. A PDA `signer` is marked
+/// as signing and its seeds sign the call.
+fn call_token_bridge<'info>(
+    program: &UncheckedAccount<'info>,
+    accounts: &[AccountInfo<'info>],
+    data: Vec<u8>,
+    signer: Option<(Pubkey, &[&[u8]])>,
+) -> Result<()> {
+    let signer_key = signer.map(|(key, _)| key);
+    let instruction = Instruction {
+        program_id: program.key(),
+        accounts: accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || Some(account.key()) == signer_key,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut infos = accounts.to_vec();
+    infos.push(program.to_account_info());
+    match signer {
+        Some((_, seeds)) => invoke_signed(&instruction, &infos, &[seeds])?,
+        None => invoke(&instruction, &infos)?,
+    }
+    Ok(())
+}
+
+/// Leaf committed to by a distribution's snapshot root for one holder's record-date balance
+fn snapshot_leaf(holder: &Pubkey, balance: u64) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[b"snapshot", holder.as_ref(), &balance.to_le_bytes()]).to_bytes()
+}
+
+/// Verify a Merkle proof for a holder's snapshot balance
+fn verify_snapshot_proof(root: &[u8; 32], holder: &Pubkey, balance: u64, proof: &[[u8; 32]]) -> bool {
+    verify_merkle_proof(root, snapshot_leaf(holder, balance), proof)
+}
+
+/// Leaf committed to by an airdrop campaign's root for one wallet's grant
+fn airdrop_leaf(wallet: &Pubkey, amount: u64) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[b"airdrop", wallet.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+/// Fold `proof` up from `leaf` and compare against `root`. Sibling pairs are hashed in
+/// sorted order, so proofs carry no left/right flags.
+fn verify_merkle_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+/// Parent of two Merkle nodes, hashed in sorted order
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    solana_sha256_hasher::hashv(&[first, second]).to_bytes()
+}
+
+/// Leaf of the compressed allowlist tree for a wallet's approval state
+fn compressed_entry_leaf(wallet: &Pubkey, approved: bool) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[b"allowlist_entry", wallet.as_ref(), &[u8::from(approved)]]).to_bytes()
+}
+
+/// spl-account-compression instruction `name`, prefixed with its Anchor discriminator
+fn account_compression_instruction(name: &str, args: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+    let discriminator = solana_sha256_hasher::hashv(&[b"global:", name.as_bytes()]);
+    let mut data = discriminator.to_bytes()[..8].to_vec();
+    data.extend_from_slice(args);
+    Instruction {
+        program_id: SPL_ACCOUNT_COMPRESSION_ID,
+        accounts,
+        data,
+    }
+}
+
+/// Run a tree-modifying compression instruction (`init_empty_merkle_tree`, `append`,
+/// `replace_leaf`) signed by the token's `AllowlistTree`, with `proof` nodes appended
+fn modify_allowlist_tree<'info>(
+    name: &str,
+    args: &[u8],
+    merkle_tree: &AccountInfo<'info>,
+    allowlist_tree: &Account<'info, AllowlistTree>,
+    noop_program: &AccountInfo<'info>,
+    proof: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut metas = vec![
+        AccountMeta::new(merkle_tree.key(), false),
+        AccountMeta::new_readonly(allowlist_tree.key(), true),
+        AccountMeta::new_readonly(noop_program.key(), false),
+    ];
+    metas.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+    let instruction = account_compression_instruction(name, args, metas);
+
+    let mut infos = vec![merkle_tree.clone(), allowlist_tree.to_account_info(), noop_program.clone()];
+    infos.extend_from_slice(proof);
+    let seeds: &[&[u8]] = &[b"allowlist_tree", allowlist_tree.token_mint.as_ref(), &[allowlist_tree.bump]];
+    invoke_signed(&instruction, &infos, &[seeds])?;
+    Ok(())
+}
+
+/// Have the compression program check that `leaf` sits at `index` of `merkle_tree` under
+/// `root`, or any root still in its change log, given the uncached `proof` path
+fn verify_compressed_leaf<'info>(
+    merkle_tree: &AccountInfo<'info>,
+    root: [u8; 32],
+    leaf: [u8; 32],
+    index: u32,
+    proof: &[AccountInfo<'info>],
+) -> Result<()> {
+    let mut metas = vec![AccountMeta::new_readonly(merkle_tree.key(), false)];
+    metas.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+    let args = [&root[..], &leaf[..], &index.to_le_bytes()[..]].concat();
+    let instruction = account_compression_instruction("verify_leaf", &args, metas);
+
+    let mut infos = vec![merkle_tree.clone()];
+    infos.extend_from_slice(proof);
+    invoke(&instruction, &infos)?;
+    Ok(())
+}
+
+/// Run Bubblegum instruction `name` over `accounts`, in order, with `proof` nodes
+/// appended. The token's `CertificateTree` signs wherever it appears.
+fn call_bubblegum<'info>(
+    name: &str,
+    args: &[u8],
+    accounts: &[AccountInfo<'info>],
+    bubblegum_program: &UncheckedAccount<'info>,
+    certificate_tree: &Account<'info, CertificateTree>,
+    proof: &[AccountInfo<'info>],
+) -> Result<()> {
+    let discriminator = solana_sha256_hasher::hashv(&[b"global:", name.as_bytes()]);
+    let mut data = discriminator.to_bytes()[..8].to_vec();
+    data.extend_from_slice(args);
+    let mut infos = [accounts, proof].concat();
+    let instruction = Instruction {
+        program_id: BUBBLEGUM_ID,
+        accounts: infos
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.is_signer || account.key() == certificate_tree.key(),
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    infos.push(bubblegum_program.to_account_info());
+    let seeds: &[&[u8]] = &[b"certificate_tree", certificate_tree.token_mint.as_ref(), &[certificate_tree.bump]];
+    invoke_signed(&instruction, &infos, &[seeds])?;
+    Ok(())
+}
+
+/// Bubblegum's `MetadataArgs`, as a share certificate fills it in
+#[derive(AnchorSerialize)]
+struct CertificateMetadata {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    /// `TokenStandard`, where 0 is `NonFungible`
+    token_standard: Option<u8>,
+    collection: Option<(bool, Pubkey)>,
+    uses: Option<(u8, u64, u64)>,
+    /// `TokenProgramVersion`, where 0 is `Original`
+    token_program_version: u8,
+    creators: Vec<(Pubkey, bool, u8)>,
+}
+
+/// Leaf committed to by a bitmap page's assignment tree for one wallet's slot
+fn allowlist_slot_leaf(wallet: &Pubkey, slot: u32) -> [u8; 32] {
+    solana_sha256_hasher::hashv(&[b"allowlist_slot", wallet.as_ref(), &slot.to_le_bytes()]).to_bytes()
+}
+
+/// Verify a holder's snapshot proof, book their payout against the distribution, write the
+/// claim receipt and announce it. Returns the amount owed to the holder.
+fn settle_dividend_claim(
+    token_config: &mut TokenConfig,
+    distribution: &mut Account<Distribution>,
+    dividend_claim: &mut Account<DividendClaim>,
+    holder: Pubkey,
+    snapshot_balance: u64,
+    proof: &[[u8; 32]],
+    claim_bump: u8,
+) -> Result<u64> {
+    require!(
+        verify_snapshot_proof(&distribution.snapshot_root, &holder, snapshot_balance, proof),
+        ErrorCode::InvalidSnapshotProof
+    );
+    record_dividend_claim(token_config, distribution, dividend_claim, holder, snapshot_balance, claim_bump)
+}
+
+/// Book a holder's payout for a verified record-date balance against the distribution,
+/// write the claim receipt and announce it. Returns the amount owed to the holder.
+fn record_dividend_claim(
+    token_config: &mut TokenConfig,
+    distribution: &mut Account<Distribution>,
+    dividend_claim: &mut Account<DividendClaim>,
+    holder: Pubkey,
+    snapshot_balance: u64,
+    claim_bump: u8,
+) -> Result<u64> {
+    let payout = distribution.entitlement(snapshot_balance)?;
+    require!(payout > 0, ErrorCode::InvalidAmount);
+    distribution.claimed_amount = distribution.claimed_amount.checked_add(payout)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        distribution.claimed_amount <= distribution.total_amount,
+        ErrorCode::InvalidDistribution
+    );
+
+    let clock = Clock::get()?;
+    dividend_claim.distribution = distribution.key();
+    dividend_claim.holder = holder;
+    dividend_claim.snapshot_balance = snapshot_balance;
+    dividend_claim.amount = payout;
+    dividend_claim.claimed_at = clock.unix_timestamp;
+    dividend_claim.bump = claim_bump;
+
+    emit!(DividendClaimedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        token_mint: distribution.token_mint,
+        distribution_id: distribution.distribution_id,
+        holder,
+        snapshot_balance,
+        amount: payout,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(payout)
+}
+
+/// Checkpoint a wallet's balance before its first change after the latest snapshot.
+/// The checkpoint covers every snapshot since the wallet's previous one, as the
+/// balance cannot have moved in between. Interest earned on that balance is settled first.
+fn checkpoint_snapshot_balance(
+    token_config: &TokenConfig,
+    entry: &mut AllowlistEntry,
+    checkpoint: Option<&mut Account<SnapshotBalance>>,
+    checkpoint_bump: Option<u8>,
+    balance: u64,
+) -> Result<()> {
+    settle_interest(token_config, entry, balance)?;
+    let snapshot_id = token_config.current_snapshot_id;
+    if entry.last_snapshot_id >= snapshot_id {
+        return Ok(());
+    }
+    let checkpoint = checkpoint.ok_or(ErrorCode::SnapshotCheckpointRequired)?;
+    let bump = checkpoint_bump.ok_or(ErrorCode::SnapshotCheckpointRequired)?;
+    checkpoint.set_inner(SnapshotBalance {
+        token_mint: token_config.mint,
+        snapshot_id,
+        wallet: entry.wallet,
+        balance,
+        covers_from: entry.last_snapshot_id + 1,
+        bump,
+    });
+    entry.last_snapshot_id = snapshot_id;
+    Ok(())
+}
+
+/// Rewrite a `Versioned` account at the current layout, reallocating (and topping up rent
+/// from `payer`) if it grew. Returns the version it was migrated from.
+fn migrate_account<'info, T>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<u8>
+where
+    T: AccountSerialize + AccountDeserialize + Versioned,
+{
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::AccountNotMigrated);
+    // Fields the stored layout predates decode from the zero padding
+    let mut padded = account.try_borrow_data()?.to_vec();
+    padded.resize(padded.len() + MIGRATION_PADDING, 0);
+    let mut state = T::try_deserialize(&mut &padded[..])?;
+    let from_version = state.version();
+    require!(from_version < T::CURRENT_VERSION, ErrorCode::AccountAlreadyMigrated);
+    state.set_version(T::CURRENT_VERSION);
+
+    let mut data = Vec::new();
+    state.try_serialize(&mut data)?;
+    if data.len() > account.data_len() {
+        let shortfall = Rent::get()?.minimum_balance(data.len()).saturating_sub(account.lamports());
+        if shortfall > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), shortfall)?;
+        }
+        account.resize(data.len())?;
+    }
+    account.try_borrow_mut_data()?[..data.len()].copy_from_slice(&data);
+    Ok(from_version)
+}
+
+/// Create the recipient's associated token account for the mint unless it already exists
+fn create_recipient_ata(accounts: &CreateRecipientAta) -> Result<()> {
+    let cpi_accounts = associated_token::Create {
+        payer: accounts.authority.to_account_info(),
+        associated_token: accounts.recipient_token_account.to_account_info(),
+        authority: accounts.recipient.to_account_info(),
+        mint: accounts.mint.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        token_program: accounts.token_program.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(accounts.associated_token_program.to_account_info(), cpi_accounts);
+    associated_token::create_idempotent(cpi_ctx)
+}
+
+/// Greatest common divisor, used to keep the cumulative split factor reduced
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Apply the token's share-class rules, if a class is configured, to a mint or transfer
+/// that changes the holder count by `holder_delta`
+fn enforce_share_class(
+    token_config: &TokenConfig,
+    share_class: Option<&Account<ShareClass>>,
+    holder_delta: i64,
+    is_transfer: bool,
+) -> Result<()> {
+    if token_config.share_class == Pubkey::default() {
+        return Ok(());
+    }
+    let share_class = share_class.ok_or(ErrorCode::ShareClassRequired)?;
+    if is_transfer {
+        require!(share_class.transferable, ErrorCode::ShareClassNotTransferable);
+    }
+    if holder_delta > 0 && share_class.max_holders > 0 {
+        let holders = token_config.holder_count.checked_add(holder_delta)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            holders <= i64::from(share_class.max_holders),
+            ErrorCode::ShareClassHolderCapReached
+        );
+    }
+    Ok(())
+}
+
+/// Record a tender offer's outcome and refund the authority's escrowed funds beyond
+/// what the accepted shares cost
+fn finalize_tender_offer(ctx: Context<CloseTenderOffer>) -> Result<()> {
+    let tender_offer = &ctx.accounts.tender_offer;
+    let required = tender_offer.cost(tender_offer.accepted_amount, ctx.accounts.mint.decimals)?;
+    let refund = ctx.accounts.payment_vault.amount.saturating_sub(required);
+
+    if refund > 0 {
+        let id_bytes = tender_offer.offer_id.to_le_bytes();
+        let seeds: &[&[u8]] = &[
+            b"tender_offer",
+            tender_offer.token_mint.as_ref(),
+            &id_bytes,
+            &[tender_offer.bump],
+        ];
+        let signer_seeds = &[seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.payment_vault.to_account_info(),
+            mint: ctx.accounts.payment_mint.to_account_info(),
+            to: ctx.accounts.refund_token_account.to_account_info(),
+            authority: tender_offer.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+        token::transfer_checked(cpi_ctx, refund, ctx.accounts.payment_mint.decimals)?;
+    }
+
+    emit!(TenderOfferClosedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: ctx.accounts.token_config.next_event_sequence()?,
+        token_mint: tender_offer.token_mint,
+        offer_id: tender_offer.offer_id,
+        status: tender_offer.status,
+        total_tendered: tender_offer.total_tendered,
+        accepted_amount: tender_offer.accepted_amount,
+        refund,
+        authority: ctx.accounts.authority.key(),
+    });
+
+    Ok(())
+}
+
+/// Mirror a wallet's post-change `balance` into the cap table, if one is enabled.
+/// Wallets join the supplied page on their first balance and leave it at zero.
+fn sync_cap_table(
+    token_config: &TokenConfig,
+    entry: &mut AllowlistEntry,
+    token_account: &Pubkey,
+    page: Option<&AccountLoader<HolderPage>>,
+    balance: u64,
+) -> Result<()> {
+    // Paths that don't pass holder index accounts cannot move a wallet across zero
+    if token_config.holder_index != Pubkey::default() {
+        require!(entry.holder_indexed == (balance > 0), ErrorCode::HolderIndexRequired);
+    }
+    if token_config.cap_table == Pubkey::default()
+        || (entry.holder_page.is_none() && balance == 0)
+    {
+        return Ok(());
+    }
+    require!(
+        entry.approved_token_account == Some(*token_account),
+        ErrorCode::TokenAccountBindingRequired
+    );
+    let mut page = page.ok_or(ErrorCode::HolderPageRequired)?.load_mut()?;
+
+    match entry.holder_page {
+        Some(page_index) => {
+            require!(page.page_index == page_index, ErrorCode::InvalidHolderPage);
+            let position = page.position(&entry.wallet)
+                .ok_or(ErrorCode::InvalidHolderPage)?;
+            if balance == 0 {
+                page.swap_remove(position);
+                entry.holder_page = None;
+            } else {
+                page.holders[position].balance = balance;
+            }
+        }
+        None => {
+            page.push(HolderRecord { wallet: entry.wallet, balance })?;
+            entry.holder_page = Some(page.page_index);
+        }
+    }
+    Ok(())
+}
+
+/// Holder index accounts a balance change passes, see `sync_holder_index`
+struct HolderIndexAccounts<'a, 'info> {
+    holder_index: Option<&'a mut Account<'info, HolderIndex>>,
+    /// Number held by the wallet whose balance may reach zero
+    leaving_number: Option<&'a mut Account<'info, HolderNumber>>,
+    /// Highest number, moved into the gap a leaving wallet opens
+    last_number: Option<&'a mut Account<'info, HolderNumber>>,
+    /// Uninitialized account for the number a wallet starting to hold takes
+    joining_number: Option<&'a AccountInfo<'info>>,
+    payer: &'a AccountInfo<'info>,
+    system_program: &'a AccountInfo<'info>,
+}
+
+/// Keep the holder index dense as wallets cross zero. `leaving` and `joining` are a
+/// wallet's entry, token account and post-change balance. A wallet that stops holding
+/// hands its number to the highest-numbered holder; one that starts takes the next
+/// number. When both happen in one change the joiner takes the leaver's number.
+fn sync_holder_index(
+    token_config: &mut TokenConfig,
+    accounts: HolderIndexAccounts,
+    leaving: Option<(&mut AllowlistEntry, &Pubkey, u64)>,
+    joining: Option<(&mut AllowlistEntry, &Pubkey, u64)>,
+) -> Result<()> {
+    if token_config.holder_index == Pubkey::default() {
+        return Ok(());
+    }
+    let leaving = leaving.filter(|(entry, _, balance)| entry.holder_indexed && *balance == 0);
+    let joining = joining.filter(|(entry, _, balance)| !entry.holder_indexed && *balance > 0);
+    if leaving.is_none() && joining.is_none() {
+        return Ok(());
+    }
+    for (entry, token_account, _) in leaving.iter().chain(joining.iter()) {
+        require!(
+            entry.approved_token_account == Some(**token_account),
+            ErrorCode::TokenAccountBindingRequired
+        );
+    }
+    let holder_index = accounts.holder_index.ok_or(ErrorCode::HolderIndexRequired)?;
+
+    match (leaving, joining) {
+        (Some((leaver, _, _)), Some((joiner, _, _))) => {
+            let number = accounts.leaving_number.ok_or(ErrorCode::HolderIndexRequired)?;
+            require_keys_eq!(number.wallet, leaver.wallet, ErrorCode::InvalidHolderNumber);
+            number.wallet = joiner.wallet;
+            leaver.holder_indexed = false;
+            joiner.holder_indexed = true;
+
+            emit!(HolderNumberAssignedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: token_config.next_event_sequence()?,
+                token_mint: holder_index.token_mint,
+                number: number.number,
+                wallet: joiner.wallet,
+                holders: holder_index.len,
+            });
+        }
+        (Some((leaver, _, _)), None) => release_holder_number(
+            token_config,
+            holder_index,
+            leaver,
+            accounts.leaving_number,
+            accounts.last_number,
+            accounts.payer,
+        )?,
+        (None, Some((joiner, _, _))) => assign_holder_number(
+            token_config,
+            holder_index,
+            joiner,
+            accounts.joining_number,
+            accounts.payer,
+            accounts.system_program,
+        )?,
+        (None, None) => {}
+    }
+    Ok(())
+}
+
+/// Give `entry`'s wallet the next holder number, creating its account in `number_account`
+fn assign_holder_number<'info>(
+    token_config: &mut TokenConfig,
+    holder_index: &mut Account<'info, HolderIndex>,
+    entry: &mut AllowlistEntry,
+    number_account: Option<&AccountInfo<'info>>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let number_account = number_account.ok_or(ErrorCode::HolderIndexRequired)?;
+    let number = holder_index.len;
+    let number_bytes = number.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"holder_number", holder_index.token_mint.as_ref(), &number_bytes],
+        &crate::ID,
+    );
+    require_keys_eq!(expected, number_account.key(), ErrorCode::InvalidHolderNumber);
+
+    let space = 8 + 32 + 8 + 32 + 1;
+    let lamports = Rent::get()?.minimum_balance(space);
+    let seeds: &[&[u8]] = &[
+        b"holder_number",
+        holder_index.token_mint.as_ref(),
+        &number_bytes,
+        &[bump],
+    ];
+    let cpi_accounts = system_program::CreateAccount {
+        from: payer.clone(),
+        to: number_account.clone(),
+    };
+    let signer_seeds = &[seeds];
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+    system_program::create_account(cpi_ctx, lamports, space as u64, &crate::ID)?;
+
+    let record = HolderNumber {
+        token_mint: holder_index.token_mint,
+        number,
+        wallet: entry.wallet,
+        bump,
+    };
+    record.try_serialize(&mut &mut number_account.try_borrow_mut_data()?[..])?;
+    holder_index.len = number.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    entry.holder_indexed = true;
+
+    emit!(HolderNumberAssignedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        token_mint: holder_index.token_mint,
+        number,
+        wallet: entry.wallet,
+        holders: holder_index.len,
+    });
+    Ok(())
+}
+
+/// Take `entry`'s wallet out of the holder index. Its number goes to the holder of the
+/// last number, passed as `last` unless the leaving wallet holds it, and the last account
+/// is closed to `rent_recipient`.
+fn release_holder_number<'info>(
+    token_config: &mut TokenConfig,
+    holder_index: &mut Account<'info, HolderIndex>,
+    entry: &mut AllowlistEntry,
+    number: Option<&mut Account<'info, HolderNumber>>,
+    last: Option<&mut Account<'info, HolderNumber>>,
+    rent_recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    let number = number.ok_or(ErrorCode::HolderIndexRequired)?;
+    require_keys_eq!(number.wallet, entry.wallet, ErrorCode::InvalidHolderNumber);
+    let last_number = holder_index.len.checked_sub(1).ok_or(ErrorCode::Overflow)?;
+
+    let vacated = if number.number == last_number {
+        number
+    } else {
+        let last = last.ok_or(ErrorCode::HolderIndexRequired)?;
+        require!(last.number == last_number, ErrorCode::InvalidHolderNumber);
+        number.wallet = last.wallet;
+        emit!(HolderNumberAssignedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: holder_index.token_mint,
+            number: number.number,
+            wallet: number.wallet,
+            holders: last_number,
+        });
+        last
+    };
+    vacated.close(rent_recipient.clone())?;
+    holder_index.len = last_number;
+    entry.holder_indexed = false;
+
+    emit!(HolderNumberReleasedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        token_mint: holder_index.token_mint,
+        number: last_number,
+        wallet: entry.wallet,
+        holders: last_number,
+    });
+    Ok(())
+}
+
+/// Create the current-snapshot checkpoint for a wallet passed outside the typed accounts
+/// (batched cranks), recording its pre-change `balance`. No-op if one already exists.
+fn create_snapshot_checkpoint<'info>(
+    token_config: &TokenConfig,
+    entry: &mut AllowlistEntry,
+    checkpoint: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    balance: u64,
+) -> Result<()> {
+    settle_interest(token_config, entry, balance)?;
+    let snapshot_id = token_config.current_snapshot_id;
+    if entry.last_snapshot_id >= snapshot_id {
+        return Ok(());
+    }
+
+    let id_bytes = snapshot_id.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"snapshot_balance", token_config.mint.as_ref(), &id_bytes, entry.wallet.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(expected, checkpoint.key(), ErrorCode::InvalidSnapshotCheckpoint);
+
+    let space = 8 + 32 + 8 + 32 + 8 + 8 + 1;
+    let lamports = Rent::get()?.minimum_balance(space);
+    let seeds: &[&[u8]] = &[
+        b"snapshot_balance",
+        token_config.mint.as_ref(),
+        &id_bytes,
+        entry.wallet.as_ref(),
+        &[bump],
+    ];
+    let cpi_accounts = system_program::CreateAccount {
+        from: payer.clone(),
+        to: checkpoint.clone(),
+    };
+    let signer_seeds = &[seeds];
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+    system_program::create_account(cpi_ctx, lamports, space as u64, &crate::ID)?;
+
+    let record = SnapshotBalance {
+        token_mint: token_config.mint,
+        snapshot_id,
+        wallet: entry.wallet,
+        balance,
+        covers_from: entry.last_snapshot_id + 1,
+        bump,
+    };
+    record.try_serialize(&mut &mut checkpoint.try_borrow_mut_data()?[..])?;
+    entry.last_snapshot_id = snapshot_id;
+    Ok(())
+}
+
+/// Close the interest accrual period ending at `now`, advancing the token's interest
+/// index by the class rate for the elapsed time
+fn accrue_interest_index(token_config: &mut TokenConfig, share_class: &ShareClass, now: i64) -> Result<()> {
+    let period_start = token_config.interest_accrued_at;
+    if period_start == 0 || now <= period_start {
+        token_config.interest_accrued_at = token_config.interest_accrued_at.max(now);
+        return Ok(());
+    }
+    let elapsed = u128::try_from(now - period_start).map_err(|_| ErrorCode::Overflow)?;
+    let index_delta = u128::from(share_class.accrual_rate_bps)
+        .checked_mul(elapsed)
+        .and_then(|v| v.checked_mul(INTEREST_INDEX_SCALE))
+        .ok_or(ErrorCode::Overflow)?
+        / (10_000 * SECONDS_PER_YEAR);
+    token_config.interest_index = token_config.interest_index.checked_add(index_delta)
+        .ok_or(ErrorCode::Overflow)?;
+    token_config.interest_accrued_at = now;
+
+    if share_class.accrual_rate_bps > 0 {
+        emit!(InterestAccruedEvent {
+            schema_version: EVENT_SCHEMA_VERSION,
+            sequence: token_config.next_event_sequence()?,
+            token_mint: token_config.mint,
+            period_start,
+            period_end: now,
+            rate_bps: share_class.accrual_rate_bps,
+            index_delta,
+            interest_index: token_config.interest_index,
+        });
+    }
+    Ok(())
+}
+
+/// Move interest earned on `balance` since the holder's last settlement into their
+/// unclaimed total. Runs before every balance change, alongside the snapshot checkpoint.
+fn settle_interest(token_config: &TokenConfig, entry: &mut AllowlistEntry, balance: u64) -> Result<()> {
+    let index_delta = token_config.interest_index.saturating_sub(entry.interest_index);
+    if index_delta > 0 {
+        let earned = u128::from(balance)
+            .checked_mul(index_delta)
+            .ok_or(ErrorCode::Overflow)?
+            / INTEREST_INDEX_SCALE;
+        let earned = u64::try_from(earned).map_err(|_| ErrorCode::Overflow)?;
+        entry.accrued_interest = entry.accrued_interest.checked_add(earned)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+    entry.interest_index = token_config.interest_index;
+    Ok(())
+}
+
+/// Split liquidation proceeds across tranches: preferences by ascending seniority,
+/// pro-rata within a tier that cannot be paid in full, then the residual pro-rata by
+/// whole-share equivalents. Returns the proceeds left unallocated.
+fn allocate_waterfall(tranches: &mut [WaterfallTranche], total_proceeds: u64) -> Result<u64> {
+    let mut remaining = total_proceeds;
+
+    let mut tiers: Vec<u8> = tranches.iter().map(|t| t.seniority).collect();
+    tiers.sort_unstable();
+    tiers.dedup();
+    for tier in tiers {
+        let owed: u128 = tranches
+            .iter()
+            .filter(|t| t.seniority == tier)
+            .map(|t| u128::from(t.preference))
+            .sum();
+        if owed == 0 {
+            continue;
+        }
+        let available = u128::from(remaining);
+        for tranche in tranches.iter_mut().filter(|t| t.seniority == tier) {
+            let paid = if available >= owed {
+                u128::from(tranche.preference)
+            } else {
+                u128::from(tranche.preference) * available / owed
+            };
+            tranche.preference_paid = u64::try_from(paid).map_err(|_| ErrorCode::Overflow)?;
+            remaining -= tranche.preference_paid;
+        }
+    }
+
+    // Different mints may use different decimals; weigh the residual by whole shares
+    let max_decimals = tranches.iter().map(|t| t.decimals).max().unwrap_or(0);
+    let weight = |t: &WaterfallTranche| -> Result<u128> {
+        let scale = 10u128
+            .checked_pow(u32::from(max_decimals - t.decimals))
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(u128::from(t.shares).checked_mul(scale).ok_or(ErrorCode::Overflow)?)
+    };
+    let mut total_weight: u128 = 0;
+    for tranche in tranches.iter().filter(|t| t.shares_in_residual) {
+        total_weight = total_weight.checked_add(weight(tranche)?).ok_or(ErrorCode::Overflow)?;
+    }
+    if total_weight > 0 && remaining > 0 {
+        let residual = u128::from(remaining);
+        for tranche in tranches.iter_mut().filter(|t| t.shares_in_residual) {
+            let paid = residual
+                .checked_mul(weight(tranche)?)
+                .ok_or(ErrorCode::Overflow)?
+                / total_weight;
+            tranche.residual_paid = u64::try_from(paid).map_err(|_| ErrorCode::Overflow)?;
+            remaining -= tranche.residual_paid;
+        }
+    }
+
+    Ok(remaining)
+}
+
+/// Create and fund the record-date distribution carrying one class's waterfall
+/// allotment, at the class's next distribution id. Returns the distribution id.
+fn create_waterfall_distribution<'info>(
+    ctx_accounts: &ExecuteWaterfall<'info>,
+    accounts: &'info [AccountInfo<'info>],
+    input: &WaterfallClassInput,
+    amount: u64,
+    now: i64,
+    claim_deadline: i64,
+) -> Result<u64> {
+    let mut token_config: Account<TokenConfig> = Account::try_from(&accounts[0])?;
+    let mint_key = token_config.mint;
+    let distribution_id = token_config.next_distribution_id;
+    let id_bytes = distribution_id.to_le_bytes();
+
+    let (distribution_key, distribution_bump) = Pubkey::find_program_address(
+        &[b"distribution", mint_key.as_ref(), &id_bytes],
+        &crate::ID,
+    );
+    require_keys_eq!(distribution_key, accounts[3].key(), ErrorCode::InvalidWaterfallAccounts);
+    let (vault_key, vault_bump) = Pubkey::find_program_address(
+        &[b"distribution_vault", distribution_key.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(vault_key, accounts[4].key(), ErrorCode::InvalidWaterfallAccounts);
+
+    let rent = Rent::get()?;
+    let payer = ctx_accounts.authority.to_account_info();
+    let system_program = ctx_accounts.system_program.to_account_info();
+    let token_program = ctx_accounts.token_program.to_account_info();
+
+    let space = 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 8 + 8;
+    let distribution_seeds: &[&[u8]] = &[b"distribution", mint_key.as_ref(), &id_bytes, &[distribution_bump]];
+    let signer_seeds = &[distribution_seeds];
+    let cpi_accounts = system_program::CreateAccount {
+        from: payer.clone(),
+        to: accounts[3].clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+    system_program::create_account(cpi_ctx, rent.minimum_balance(space), space as u64, &crate::ID)?;
+
+    let vault_seeds: &[&[u8]] = &[b"distribution_vault", distribution_key.as_ref(), &[vault_bump]];
+    let signer_seeds = &[vault_seeds];
+    let cpi_accounts = system_program::CreateAccount {
+        from: payer,
+        to: accounts[4].clone(),
+    };
+    let cpi_ctx = CpiContext::new_with_signer(system_program, cpi_accounts, signer_seeds);
+    // Token-2022 vaults grow with the payout mint's extensions, so ask its program
+    let vault_space = token::get_account_data_size(
+        CpiContext::new(
+            token_program.clone(),
+            token::GetAccountDataSize { mint: ctx_accounts.payout_mint.to_account_info() },
+        ),
+        &[],
+    )?;
+    system_program::create_account(
+        cpi_ctx,
+        rent.minimum_balance(vault_space as usize),
+        vault_space,
+        token_program.key,
+    )?;
+    let cpi_accounts = token::InitializeAccount3 {
+        account: accounts[4].clone(),
+        mint: ctx_accounts.payout_mint.to_account_info(),
+        authority: accounts[3].clone(),
+    };
+    token::initialize_account3(CpiContext::new(token_program.clone(), cpi_accounts))?;
+
+    let distribution = Distribution {
+        token_mint: mint_key,
+        distribution_id,
+        record_date: now,
+        snapshot_root: input.snapshot_root,
+        snapshot_supply: input.snapshot_supply,
+        total_amount: amount,
+        claimed_amount: 0,
+        created_at: now,
+        bump: distribution_bump,
+        payout_mint: ctx_accounts.payout_mint.key(),
+        claim_deadline,
+        drip_price: 0,
+        snapshot_id: 0,
+    };
+    distribution.try_serialize(&mut &mut accounts[3].try_borrow_mut_data()?[..])?;
+
+    let cpi_accounts = TransferChecked {
+        from: ctx_accounts.funding_token_account.to_account_info(),
+        mint: ctx_accounts.payout_mint.to_account_info(),
+        to: accounts[4].clone(),
+        authority: ctx_accounts.authority.to_account_info(),
+    };
+    token::transfer_checked(CpiContext::new(token_program, cpi_accounts), amount, ctx_accounts.payout_mint.decimals)?;
+
+    token_config.next_distribution_id = distribution_id.checked_add(1)
+        .ok_or(ErrorCode::Overflow)?;
+    let sequence = token_config.next_event_sequence()?;
+    token_config.exit(&crate::ID)?;
+
+    emit!(DistributionCreatedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence,
+        token_mint: mint_key,
+        distribution_id,
+        record_date: now,
+        snapshot_supply: input.snapshot_supply,
+        total_amount: amount,
+        payout_mint: ctx_accounts.payout_mint.key(),
+        claim_deadline,
+        authority: ctx_accounts.authority.key(),
+    });
+
+    Ok(distribution_id)
+}
+
+/// Sensitive instructions must run through the timelock queue while a delay is set
+fn require_timelock(token_config: &TokenConfig, timelock: Option<&Signer>) -> Result<()> {
+    if token_config.timelock_seconds > 0 {
+        require!(timelock.is_some(), ErrorCode::TimelockRequired);
+    }
+    Ok(())
+}
+
+/// The share class whose votes per share weigh votes, if the token has one. Non-voting
+/// classes cannot hold shareholder votes.
+fn voting_share_class<'a>(
+    token_config: &TokenConfig,
+    share_class: Option<&'a Account<'_, ShareClass>>,
+) -> Result<Option<&'a ShareClass>> {
+    if token_config.share_class == Pubkey::default() {
+        return Ok(None);
+    }
+    let share_class = share_class.ok_or(ErrorCode::ShareClassRequired)?;
+    require!(share_class.votes_per_share > 0, ErrorCode::NoVotingPower);
+    Ok(Some(&**share_class))
+}
+
+/// Create a delegator's vote record while their delegate votes for them, marking the
+/// delegator's weight as used on the proposal
+fn create_vote_record<'info>(
+    vote_record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mut record: VoteRecord,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"vote", record.proposal.as_ref(), record.voter.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(expected, vote_record.key(), ErrorCode::InvalidDelegationAccounts);
+    require!(vote_record.data_is_empty(), ErrorCode::AlreadyVoted);
+
+    // Allocate and assign rather than `create_account`, which fails if anyone has sent the
+    // address lamports and would leave the delegator's vote uncastable
+    let space = VoteRecord::SPACE;
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(vote_record.lamports());
+    if shortfall > 0 {
+        let cpi_accounts = system_program::Transfer {
+            from: payer.clone(),
+            to: vote_record.clone(),
+        };
+        system_program::transfer(CpiContext::new(system_program.clone(), cpi_accounts), shortfall)?;
+    }
+    let seeds: &[&[u8]] = &[b"vote", record.proposal.as_ref(), record.voter.as_ref(), &[bump]];
+    let signer_seeds = &[seeds];
+    let cpi_accounts = system_program::Allocate { account_to_allocate: vote_record.clone() };
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+    system_program::allocate(cpi_ctx, space as u64)?;
+    let cpi_accounts = system_program::Assign { account_to_assign: vote_record.clone() };
+    let cpi_ctx = CpiContext::new_with_signer(system_program.clone(), cpi_accounts, signer_seeds);
+    system_program::assign(cpi_ctx, &crate::ID)?;
+
+    record.bump = bump;
+    record.try_serialize(&mut &mut vote_record.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Bytes the compliance officer signs to authorize a single `permit_transfer`
+fn permit_message(
+    mint: &Pubkey,
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(PERMIT_DOMAIN.len() + 32 * 4 + 8 * 3);
+    message.extend_from_slice(PERMIT_DOMAIN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(mint.as_ref());
+    message.extend_from_slice(sender.as_ref());
+    message.extend_from_slice(recipient.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+/// Require that the instruction just before this one is an ed25519 program check of
+/// `signer`'s signature over `message`, with all of its data held inline
+fn verify_ed25519_signature(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)?;
+    require!(current_index > 0, ErrorCode::InvalidPermitSignature);
+    let ix = load_instruction_at_checked(usize::from(current_index - 1), instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ErrorCode::InvalidPermitSignature);
+
+    // Layout: count (u8), padding (u8), then one 14-byte offsets record per signature
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidPermitSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix = read_u16(4);
+    let public_key_offset = usize::from(read_u16(6));
+    let public_key_ix = read_u16(8);
+    let message_offset = usize::from(read_u16(10));
+    let message_size = usize::from(read_u16(12));
+    let message_ix = read_u16(14);
+    require!(
+        signature_ix == u16::MAX && public_key_ix == u16::MAX && message_ix == u16::MAX,
+        ErrorCode::InvalidPermitSignature
+    );
+
+    let public_key = data.get(public_key_offset..public_key_offset + 32)
+        .ok_or(ErrorCode::InvalidPermitSignature)?;
+    let signed_message = data.get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidPermitSignature)?;
+    require!(
+        public_key == signer.as_ref() && signed_message == message,
+        ErrorCode::InvalidPermitSignature
+    );
+    Ok(())
+}
+
+/// Reject a call reached through CPI from a program the token does not trust, when the
+/// token restricts CPI callers. The instructions sysvar only shows top-level instructions,
+/// so the program checked is the one that started the call chain; chains started by this
+/// program (council and timelock execution) count as direct calls.
+fn enforce_cpi_caller(
+    token_config: &TokenConfig,
+    instructions: Option<&UncheckedAccount>,
+    trusted_caller: Option<&Account<TrustedCaller>>,
+) -> Result<()> {
+    if !token_config.restrict_cpi_callers {
+        return Ok(());
+    }
+    let instructions = instructions.ok_or(ErrorCode::InstructionsSysvarRequired)?;
+    let current_index = load_current_index_checked(instructions)?;
+    let caller = load_instruction_at_checked(usize::from(current_index), instructions)?.program_id;
+    if caller == crate::ID {
+        return Ok(());
+    }
+    match trusted_caller {
+        Some(trusted) if trusted.program_id == caller => Ok(()),
+        _ => err!(ErrorCode::UntrustedCpiCaller),
+    }
+}
+
+/// Mint through the `[b"mint_authority", mint]` PDA, which holds the SPL mint authority
+/// of every gated token so issuance can only happen through program checks
+fn mint_with_program_authority<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    mint_authority: &UncheckedAccount<'info>,
+    mint_authority_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let mint_key = mint.key();
+    let seeds: &[&[u8]] = &[b"mint_authority", mint_key.as_ref(), &[mint_authority_bump]];
+    let signer_seeds = &[seeds];
+    let cpi_accounts = token::MintTo {
+        mint: mint.to_account_info(),
+        to,
+        authority: mint_authority.to_account_info(),
+    };
+    let cpi_program = token_program.to_account_info();
+    token::mint_to(CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds), amount)
+}
+
+/// Add `raw_amount` of newly minted tokens to the recorded supply, rejecting any mint
+/// that would carry it past `max_supply` and announcing when the cap is reached
+fn record_minted_supply(token_config: &mut TokenConfig, raw_amount: u64) -> Result<()> {
+    require!(
+        !token_config.features.contains(FeatureFlags::MINTING_FINALIZED),
+        ErrorCode::MintingFinalized
+    );
+    let minted_shares = token_config.split_adjusted_exact(raw_amount)?;
+    token_config.total_supply = token_config.total_supply.checked_add(minted_shares)
+        .ok_or(ErrorCode::Overflow)?;
+    if let Some(max_supply) = token_config.max_supply {
+        require!(token_config.total_supply <= max_supply, ErrorCode::MaxSupplyExceeded);
+        if minted_shares > 0 && token_config.total_supply == max_supply {
+            emit!(SupplyCapReachedEvent {
+                schema_version: EVENT_SCHEMA_VERSION,
+                sequence: token_config.next_event_sequence()?,
+                token_mint: token_config.mint,
+                max_supply,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Council membership must be distinct keys with a reachable, non-zero threshold
+fn validate_council(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty() && members.len() <= MAX_COUNCIL_MEMBERS,
+        ErrorCode::InvalidCouncil
+    );
+    require!(
+        threshold > 0 && usize::from(threshold) <= members.len(),
+        ErrorCode::InvalidCouncil
+    );
+    for (i, member) in members.iter().enumerate() {
+        require!(!members[..i].contains(member), ErrorCode::InvalidCouncil);
+    }
+    Ok(())
+}
+
+/// Reinvest a DRIP holder's dividend: shares are minted at the distribution's DRIP
+/// price and the cash they cost moves to the issuer treasury. Returns the cash left.
+fn reinvest_dividend(
+    accounts: &mut ClaimTokenDividend,
+    snapshot_bump: Option<u8>,
+    mint_authority_bump: Option<u8>,
+    payout: u64,
+) -> Result<u64> {
+    let mint = accounts.mint.as_ref().ok_or(ErrorCode::DripAccountsRequired)?;
+    let share_token_account = accounts.share_token_account
+        .as_ref()
+        .ok_or(ErrorCode::DripAccountsRequired)?;
+    let mint_authority = accounts.mint_authority.as_ref().ok_or(ErrorCode::DripAccountsRequired)?;
+    let mint_authority_bump = mint_authority_bump.ok_or(ErrorCode::DripAccountsRequired)?;
+    let treasury = accounts.treasury.as_ref().ok_or(ErrorCode::DripAccountsRequired)?;
+    let treasury_payment_account = accounts.treasury_payment_account
+        .as_ref()
+        .ok_or(ErrorCode::DripAccountsRequired)?;
+    require_keys_eq!(
+        treasury_payment_account.owner,
+        treasury.key(),
+        ErrorCode::DripAccountsRequired
+    );
+    require!(!accounts.token_config.paused, ErrorCode::TokenPaused);
+    require!(
+        accounts.holder_allowlist_entry.permits_token_account(&share_token_account.key()),
+        ErrorCode::TokenAccountNotApproved
+    );
+
+    let distribution = &accounts.distribution;
+    let shares = distribution.drip_shares(payout, mint.decimals, accounts.token_config.lot_size)?;
+    if shares == 0 {
+        return Ok(payout);
+    }
+    let cost = distribution.drip_cost(shares, mint.decimals)?;
+
+    let id_bytes = distribution.distribution_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"distribution",
+        distribution.token_mint.as_ref(),
+        &id_bytes,
+        &[distribution.bump],
+    ];
+    let signer_seeds = &[seeds];
+    let cpi_accounts = TransferChecked {
+        from: accounts.distribution_vault.to_account_info(),
+        mint: accounts.payout_mint.to_account_info(),
+        to: treasury_payment_account.to_account_info(),
+        authority: distribution.to_account_info(),
+    };
+    let cpi_program = accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    token::transfer_checked(cpi_ctx, cost, accounts.payout_mint.decimals)?;
+
+    let is_new_holder = share_token_account.amount == 0;
+    enforce_share_class(
+        &accounts.token_config,
+        accounts.share_class.as_ref(),
+        i64::from(is_new_holder),
+        false,
+    )?;
+    checkpoint_snapshot_balance(
+        &accounts.token_config,
+        &mut accounts.holder_allowlist_entry,
+        accounts.holder_snapshot_balance.as_deref_mut(),
+        snapshot_bump,
+        share_token_account.amount,
+    )?;
+    let share_token_account_balance = share_token_account.amount.checked_add(shares)
+        .ok_or(ErrorCode::Overflow)?;
+    sync_holder_index(
+        &mut accounts.token_config,
+        HolderIndexAccounts {
+            holder_index: accounts.holder_index.as_deref_mut(),
+            leaving_number: None,
+            last_number: None,
+            joining_number: accounts.holder_number.as_deref(),
+            payer: &accounts.holder.to_account_info(),
+            system_program: &accounts.system_program.to_account_info(),
+        },
+        None,
+        Some((&mut accounts.holder_allowlist_entry, &share_token_account.key(), share_token_account_balance)),
+    )?;
+    sync_cap_table(
+        &accounts.token_config,
+        &mut accounts.holder_allowlist_entry,
+        &share_token_account.key(),
+        accounts.holder_page.as_ref(),
+        share_token_account_balance,
+    )?;
+
+    mint_with_program_authority(
+        &accounts.token_program,
+        mint,
+        share_token_account.to_account_info(),
+        mint_authority,
+        mint_authority_bump,
+        shares,
+    )?;
+
+    let token_config = &mut accounts.token_config;
+    record_minted_supply(token_config, shares)?;
+    if is_new_holder {
+        token_config.holder_count = token_config.holder_count.checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+    }
+
+    emit!(DividendReinvestedEvent {
+        schema_version: EVENT_SCHEMA_VERSION,
+        sequence: token_config.next_event_sequence()?,
+        token_mint: accounts.distribution.token_mint,
+        distribution_id: accounts.distribution.distribution_id,
+        holder: accounts.holder.key(),
+        payout,
+        shares,
+        cost,
+        drip_price: accounts.distribution.drip_price,
+    });
+
+    payout.checked_sub(cost).ok_or(ErrorCode::Overflow.into())
+}
+
+/// Fee owed on a transfer of `amount` at `fee_bps` basis points, rounded down
+fn calculate_transfer_fee(amount: u64, fee_bps: u16) -> Result<u64> {
+    let fee = u128::from(amount)
+        .checked_mul(u128::from(fee_bps))
+        .ok_or(ErrorCode::Overflow)?
+        / 10_000;
+    Ok(u64::try_from(fee).map_err(|_| ErrorCode::Overflow)?)
+}
+
+/// Whether a token account owner is controlled by a program rather than a wallet keypair:
+/// an executable program, an account owned by a non-system program, or an off-curve PDA.
+fn is_program_controlled(owner: &AccountInfo) -> bool {
+    owner.executable || *owner.owner != system_program::ID || !owner.key.is_on_curve()
+}
+
+// Account structures
+#[account]
+pub struct TokenConfig {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+    pub total_supply: u64,
+    pub bump: u8,
+    pub paused: bool,
+    pub breaker_tripped: bool,
+    pub holder_count: i64,
+    pub allowlist_checksum: [u8; 32],
+    pub transfer_fee_bps: u16,
+    pub next_blackout_id: u64,
+    pub open_blackout_windows: u8,
+    pub daily_transfer_limit: u64,
+    pub compliance_officer: Pubkey,
+    pub min_transfer_amount: u64,
+    pub lot_size: u64,
+    pub sanctions_oracle: Pubkey,
+    pub next_distribution_id: u64,
+    pub current_snapshot_id: u64,
+    /// Cumulative in-place split factor (numerator / denominator) applied to raw balances
+    pub split_numerator: u64,
+    pub split_denominator: u64,
+    pub share_class: Pubkey,
+    pub next_vesting_id: u64,
+    pub next_option_grant_id: u64,
+    pub next_tender_offer_id: u64,
+    pub next_note_id: u64,
+    pub next_warrant_id: u64,
+    /// Cap table registry kept in step with balances; default when not enabled
+    pub cap_table: Pubkey,
+    /// Issuer's right-of-first-refusal window on secondary sales; 0 disables ROFR
+    pub rofr_period_seconds: i64,
+    pub next_rights_offering_id: u64,
+    /// Id of the most recent pro-rata allocation; ids start at 1
+    pub last_allocation_id: u64,
+    pub allocation_active: bool,
+    /// Cumulative interest per raw token unit, scaled by `INTEREST_INDEX_SCALE`
+    pub interest_index: u128,
+    /// End of the last accrual period; 0 until the class starts accruing
+    pub interest_accrued_at: i64,
+    /// Authority proposed by `propose_authority`, awaiting acceptance; default when none
+    pub pending_authority: Pubkey,
+    /// Delay sensitive operations wait in the queue before executing; 0 disables the timelock
+    pub timelock_seconds: i64,
+    pub next_pending_operation_id: u64,
+    pub next_proposal_id: u64,
+    /// Emergency key that may pause (but not unpause) the token; default when unset
+    pub guardian: Pubkey,
+    /// Hard cap on `total_supply`, fixed at initialization and only ever lowered
+    pub max_supply: Option<u64>,
+    /// Off-chain metadata JSON
+    pub uri: String,
+    /// `Issuer` this token is registered under; default when standalone
+    pub issuer: Pubkey,
+    pub next_escrow_id: u64,
+    pub next_swap_offer_id: u64,
+    pub next_stream_id: u64,
+    /// Superseded by `FeatureFlags::MEMO_REQUIRED` in version 6; only read when migrating
+    pub require_memo: bool,
+    pub next_airdrop_id: u64,
+    /// Layout version this account was written under, see `Versioned`
+    pub version: u8,
+    /// `HolderIndex` numbering this token's holders; default when disabled
+    pub holder_index: Pubkey,
+    /// Every `gated_transfer` is appended to the token's `TransferJournal`
+    pub journal_enabled: bool,
+    /// Sequence number the token's next event carries
+    pub event_sequence: u64,
+    /// Instructions that issue, force-move or freeze shares, reached through CPI, must
+    /// come from a `TrustedCaller`
+    pub restrict_cpi_callers: bool,
+    /// Rule bundle the issuer has switched on, see `set_features`
+    pub features: FeatureFlags,
+    /// Program owning `price_feed`; default when the token has no price oracle
+    pub price_oracle: Pubkey,
+    /// Feed NAV redemptions and market-priced option exercises read, see `set_price_oracle`
+    pub price_feed: Pubkey,
+    pub max_price_age_seconds: i64,
+}
+
+impl TokenConfig {
+    /// Bytes taken by everything but the symbol, name and URI, discriminator included
+    pub const FIXED_SPACE: usize = 8 + 32 + 32 + 1 + 8 + 1 + 1 + 1 + 8 + 32 + 2 + 8 + 1 + 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 8 + 1 + 16 + 8 + 32 + 8 + 8 + 8 + 32 + 9 + 32 + 8 + 8 + 8 + 1 + 8 + 1 + 32 + 1 + 8 + 1 + 4 + 32 + 32 + 8;
+
+    /// Exact account size holding these strings, each stored with a 4-byte length prefix
+    pub fn space(symbol: &str, name: &str, uri: &str) -> usize {
+        Self::FIXED_SPACE + 4 + symbol.len() + 4 + name.len() + 4 + uri.len()
+    }
+
+    /// Take the sequence number for the event about to be emitted
+    pub fn next_event_sequence(&mut self) -> Result<u64> {
+        let sequence = self.event_sequence;
+        self.event_sequence = sequence.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(sequence)
+    }
+
+    /// Whether holders may live on the compact allowlist, which keeps no per-wallet state
+    /// for snapshots, the cap table, the holder index or sanctions screening to hang off
+    pub fn supports_compact_allowlist(&self) -> bool {
+        self.current_snapshot_id == 0
+            && self.cap_table == Pubkey::default()
+            && self.holder_index == Pubkey::default()
+            && self.sanctions_oracle == Pubkey::default()
+    }
+
+    /// Base units in one whole share at this token's decimals
+    pub fn whole_share_unit(&self) -> Result<u64> {
+        10u64.checked_pow(u32::from(self.decimals)).ok_or(ErrorCode::Overflow.into())
+    }
+
+    /// Reject amounts below the configured minimum or not a whole number of lots
+    pub fn validate_lot(&self, amount: u64) -> Result<()> {
+        require!(amount >= self.min_transfer_amount, ErrorCode::BelowMinimumAmount);
+        require!(amount.checked_rem(self.lot_size.max(1)) == Some(0), ErrorCode::InvalidLotMultiple);
+        Ok(())
+    }
+
+    /// A raw on-chain token amount expressed in post-split shares, rounded down
+    pub fn split_adjusted(&self, raw_amount: u64) -> Result<u64> {
+        let adjusted = u128::from(raw_amount)
+            .checked_mul(u128::from(self.split_numerator))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.split_denominator.max(1));
+        Ok(u64::try_from(adjusted).map_err(|_| ErrorCode::Overflow)?)
+    }
+
+    /// Like `split_adjusted`, but rejects raw amounts that do not map to whole shares,
+    /// so the recorded supply never drifts from the mint through rounding
+    pub fn split_adjusted_exact(&self, raw_amount: u64) -> Result<u64> {
+        let scaled = u128::from(raw_amount)
+            .checked_mul(u128::from(self.split_numerator))
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            scaled.checked_rem(u128::from(self.split_denominator.max(1))) == Some(0),
+            ErrorCode::AmountNotSplitAligned
+        );
+        self.split_adjusted(raw_amount)
+    }
+}
+
+#[account]
+pub struct AllowlistEntry {
+    pub wallet: Pubkey,
+    pub is_approved: bool,
+    pub approved_at: i64,
+    pub revoked_at: Option<i64>,
+    pub bump: u8,
+    pub lockup_until: Option<i64>,
+    pub transfer_locked: bool,
+    pub is_insider: bool,
+    pub approved_token_account: Option<Pubkey>,
+    pub country_code: [u8; 2],
+    pub last_snapshot_id: u64,
+    /// Cap table page holding this wallet's balance record
+    pub holder_page: Option<u32>,
+    /// Latest pro-rata allocation this wallet has been processed for
+    pub allocated_through: u64,
+    /// Holder elected to reinvest SPL-token dividends in new shares
+    pub drip_enabled: bool,
+    /// `TokenConfig::interest_index` the holder's interest was last settled at
+    pub interest_index: u128,
+    /// Interest settled but not yet claimed, in raw token units
+    pub accrued_interest: u64,
+    /// Layout version this account was written under, see `Versioned`
+    pub version: u8,
+    /// Wallet holds a number in the token's holder index
+    pub holder_indexed: bool,
+}
+
+impl AllowlistEntry {
+    pub const SPACE: usize = 8 + 32 + 1 + 8 + 9 + 1 + 9 + 1 + 1 + 33 + 2 + 8 + 5 + 8 + 1 + 16 + 8 + 1 + 1;
+
+    /// Whether `token_account` may be used under this approval
+    pub fn permits_token_account(&self, token_account: &Pubkey) -> bool {
+        self.approved_token_account.is_none_or(|approved| approved == *token_account)
+    }
+}
+
+/// Long-lived accounts whose layout grows over time. Fields are only ever appended, so an
+/// account written under an older layout decodes with the newer fields zeroed, which
+/// `migrate_*` instructions then fill in before writing it back at `CURRENT_VERSION`.
+/// Accounts written before versioning read as version 0.
+pub trait Versioned {
+    const CURRENT_VERSION: u8;
+
+    fn version(&self) -> u8;
+    fn set_version(&mut self, version: u8);
+
+    /// Guard for logic that relies on fields added by the latest layout
+    fn require_current_version(&self) -> Result<()> {
+        require!(self.version() == Self::CURRENT_VERSION, ErrorCode::AccountNotMigrated);
+        Ok(())
+    }
+}
+
+impl Versioned for TokenConfig {
+    const CURRENT_VERSION: u8 = 7;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
+
+impl Versioned for AllowlistEntry {
+    const CURRENT_VERSION: u8 = 2;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+
+    fn set_version(&mut self, version: u8) {
+        self.version = version;
+    }
+}
+
+/// Bitfield of the rule bundles an issuer has switched on for a token
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FeatureFlags {
+    pub bits: u32,
+}
+
+impl FeatureFlags {
+    /// Shares may move between wallets. Clearing it stops every path that moves them,
+    /// vault refunds included; only `force_transfer` is governed by its own flag.
+    pub const TRANSFERS_ENABLED: u32 = 1 << 0;
+    /// Issuance is closed for good: nothing may mint, and the bit cannot be cleared
+    pub const MINTING_FINALIZED: u32 = 1 << 1;
+    /// Every `gated_transfer` must carry a travel-rule payload hash
+    pub const MEMO_REQUIRED: u32 = 1 << 2;
+    /// Holders may be issued compressed NFT share certificates
+    pub const SHARE_CERTIFICATES: u32 = 1 << 3;
+    /// The authority may move shares out of a holder's account with `force_transfer`
+    pub const FORCED_TRANSFERS: u32 = 1 << 4;
+    /// Every bit with a meaning
+    pub const ALL: u32 = Self::TRANSFERS_ENABLED
+        | Self::MINTING_FINALIZED
+        | Self::MEMO_REQUIRED
+        | Self::SHARE_CERTIFICATES
+        | Self::FORCED_TRANSFERS;
+    /// Flags a new token starts with
+    pub const DEFAULT: Self = Self { bits: Self::TRANSFERS_ENABLED };
+
+    pub fn contains(self, flag: u32) -> bool {
+        self.bits & flag == flag
+    }
+
+    pub fn set(&mut self, flag: u32, enabled: bool) {
+        if enabled {
+            self.bits |= flag;
+        } else {
+            self.bits &= !flag;
+        }
+    }
+}
+
+#[account]
+pub struct SplitConfig {
+    pub original_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub split_ratio: u64,
+    pub executed_at: i64,
+    pub executed_by: Pubkey,
+    pub bump: u8,
+}
+
+#[account]
+pub struct CircuitBreaker {
+    pub token_mint: Pubkey,
+    pub supply_tolerance: u64,
+    pub reset_signers: Vec<Pubkey>,
+    pub reset_threshold: u8,
+    pub last_trip_reason: Option<BreakerTripReason>,
+    pub tripped_at: Option<i64>,
+    pub trip_count: u32,
+    pub bump: u8,
+}
+
+#[account]
+pub struct DestinationRegistry {
+    pub token_mint: Pubkey,
+    pub approved_destinations: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl DestinationRegistry {
+    /// A destination is approved if the owner address itself or its owning program is listed
+    pub fn is_approved(&self, owner: &AccountInfo) -> bool {
+        self.approved_destinations.contains(owner.key)
+            || self.approved_destinations.contains(owner.owner)
+    }
+}
+
+/// Deployment-wide settings at `[b"program_config"]`, managed by the protocol admin
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub parameters: ProtocolParameters,
+    /// Halts token creation, issuance and transfers for every token
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl ProgramConfig {
+    pub const SPACE: usize = 8 + 32 + ProtocolParameters::SPACE + 1 + 1;
+}
+
+/// Cross-issuer parameters the protocol admin sets on `ProgramConfig`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProtocolParameters {
+    /// Receives the token creation fee
+    pub fee_recipient: Pubkey,
+    /// Lamports an issuer pays for each `initialize_token`
+    pub token_creation_fee: u64,
+    /// `min_transfer_amount` new tokens start with
+    pub default_min_transfer_amount: u64,
+    /// `timelock_seconds` new tokens start with
+    pub default_timelock_seconds: i64,
+}
+
+impl ProtocolParameters {
+    pub const SPACE: usize = 32 + 8 + 8 + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            (0..=MAX_TIMELOCK_SECONDS).contains(&self.default_timelock_seconds),
+            ErrorCode::InvalidTimelock
+        );
+        Ok(())
+    }
+}
+
+/// Program-wide index of every token created, stored across fixed-capacity pages
+#[account]
+pub struct TokenRegistry {
+    pub total_tokens: u64,
+    pub bump: u8,
+}
+
+impl TokenRegistry {
+    /// Page that the next registered token is appended to
+    pub fn current_page_index(&self) -> u32 {
+        (self.total_tokens / REGISTRY_PAGE_CAPACITY as u64) as u32
+    }
+}
+
+#[account]
+pub struct RegistryPage {
+    pub page_index: u32,
+    pub entries: Vec<RegistryEntry>,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RegistryEntry {
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub created_at: i64,
+}
+
+#[account]
+pub struct CountryRule {
+    pub token_mint: Pubkey,
+    pub country_code: [u8; 2],
+    pub is_allowed: bool,
+    pub max_investors: u32,
+    pub investor_count: u32,
+    pub bump: u8,
+}
+
+impl CountryRule {
+    /// Whether another investor may be assigned to this country
+    pub fn has_capacity(&self) -> bool {
+        self.max_investors == 0 || self.investor_count < self.max_investors
+    }
+}
+
+#[account]
+pub struct Distribution {
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub record_date: i64,
+    pub snapshot_root: [u8; 32],
+    pub snapshot_supply: u64,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub created_at: i64,
+    pub bump: u8,
+    /// Mint paid out, or `Pubkey::default()` for a lamport distribution
+    pub payout_mint: Pubkey,
+    pub claim_deadline: i64,
+    /// Payout mint base units per whole share for DRIP reinvestment; 0 disables DRIP
+    pub drip_price: u64,
+    /// On-chain snapshot entitlements are read from, for scheduled distributions; 0 when
+    /// they are fixed by `snapshot_root`
+    pub snapshot_id: u64,
+}
+
+impl Distribution {
+    /// Pro-rata share of the distribution for a record-date balance, rounded down
+    pub fn entitlement(&self, snapshot_balance: u64) -> Result<u64> {
+        let share = u128::from(self.total_amount)
+            .checked_mul(u128::from(snapshot_balance))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.snapshot_supply);
+        Ok(u64::try_from(share).map_err(|_| ErrorCode::Overflow)?)
+    }
+
+    /// Raw token units a `payout` buys at the DRIP price, rounded down to whole lots
+    pub fn drip_shares(&self, payout: u64, decimals: u8, lot_size: u64) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let shares = u128::from(payout)
+            .checked_mul(unit)
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.drip_price);
+        let shares = u64::try_from(shares).map_err(|_| ErrorCode::Overflow)?;
+        Ok(shares - shares % lot_size.max(1))
+    }
+
+    /// Payout consumed by reinvesting in `shares` raw token units, rounded up
+    pub fn drip_cost(&self, shares: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(shares)
+            .checked_mul(u128::from(self.drip_price))
+            .ok_or(ErrorCode::Overflow)?
+            .div_ceil(unit);
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct DividendClaim {
+    pub distribution: Pubkey,
+    pub holder: Pubkey,
+    pub snapshot_balance: u64,
+    pub amount: u64,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct Snapshot {
+    pub token_mint: Pubkey,
+    pub snapshot_id: u64,
+    /// Mint supply in raw token units, the unit holder balances are read in, so pro-rata
+    /// shares stay exact across splits (unlike the split-adjusted `TokenConfig::total_supply`)
+    pub total_supply: u64,
+    pub holder_count: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Snapshot {
+    /// A holder's balance as of this snapshot: from a checkpoint covering it, or the live
+    /// balance if the holder has not moved since the snapshot was taken
+    pub fn balance_of(
+        &self,
+        entry: &AllowlistEntry,
+        live_balance: u64,
+        checkpoint: Option<&SnapshotBalance>,
+    ) -> Result<u64> {
+        if let Some(checkpoint) = checkpoint {
+            require_keys_eq!(checkpoint.token_mint, self.token_mint, ErrorCode::InvalidSnapshotCheckpoint);
+            require_keys_eq!(checkpoint.wallet, entry.wallet, ErrorCode::InvalidSnapshotCheckpoint);
+            require!(checkpoint.covers(self.snapshot_id), ErrorCode::InvalidSnapshotCheckpoint);
+            return Ok(checkpoint.balance);
+        }
+        require!(entry.last_snapshot_id < self.snapshot_id, ErrorCode::SnapshotCheckpointRequired);
+        Ok(live_balance)
+    }
+}
+
+#[account]
+pub struct SnapshotBalance {
+    pub token_mint: Pubkey,
+    pub snapshot_id: u64,
+    pub wallet: Pubkey,
+    pub balance: u64,
+    /// Earliest snapshot this balance also applies to
+    pub covers_from: u64,
+    pub bump: u8,
+}
+
+impl SnapshotBalance {
+    /// Whether this checkpoint holds the wallet's balance as of `snapshot_id`
+    pub fn covers(&self, snapshot_id: u64) -> bool {
+        (self.covers_from..=self.snapshot_id).contains(&snapshot_id)
+    }
+}
+
+#[account]
+pub struct ShareClass {
+    pub token_mint: Pubkey,
+    pub name: String,
+    pub kind: ShareClassKind,
+    /// Votes carried by each share (0 = non-voting)
+    pub votes_per_share: u16,
+    /// Dividend and liquidation seniority; 0 is paid first
+    pub dividend_priority: u8,
+    pub transferable: bool,
+    /// Maximum number of holders of the class (0 = unlimited)
+    pub max_holders: u32,
+    pub bump: u8,
+    /// Fixed annual interest rate the class accrues, in basis points (0 = no accrual)
+    pub accrual_rate_bps: u16,
+    /// Liquidation preference as a multiple of the issue price, in basis points (0 = none)
+    pub liquidation_multiple_bps: u32,
+    /// Original issue price per whole share, in liquidation proceeds base units
+    pub liquidation_issue_price: u64,
+    /// Whether the class also shares in the residual after preferences are paid
+    pub participating: bool,
+}
+
+impl ShareClass {
+    /// Voting power of `shares` of this class
+    pub fn voting_power(&self, shares: u64) -> Result<u64> {
+        Ok(shares.checked_mul(u64::from(self.votes_per_share)).ok_or(ErrorCode::Overflow)?)
+    }
+
+    /// Liquidation preference owed on `shares` raw token units, rounded down
+    pub fn liquidation_preference(&self, shares: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let preference = u128::from(shares)
+            .checked_mul(u128::from(self.liquidation_issue_price))
+            .and_then(|v| v.checked_mul(u128::from(self.liquidation_multiple_bps)))
+            .ok_or(ErrorCode::Overflow)?
+            / unit
+            / 10_000;
+        Ok(u64::try_from(preference).map_err(|_| ErrorCode::Overflow)?)
+    }
+
+    /// Classes without a preference, and participating preferred, share the residual
+    pub fn shares_in_residual(&self) -> bool {
+        self.liquidation_multiple_bps == 0 || self.participating
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShareClassKind {
+    Common,
+    Preferred,
+}
+
+/// m-of-n council holding a token's authority through its signer PDA
+/// `[b"council_signer", council]`
+#[account]
+pub struct AuthorityCouncil {
+    pub token_mint: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub next_operation_id: u64,
+    pub bump: u8,
+    pub signer_bump: u8,
+}
+
+/// A gated-token instruction awaiting council approval
+#[account]
+pub struct CouncilOperation {
+    pub council: Pubkey,
+    pub operation_id: u64,
+    pub proposer: Pubkey,
+    pub data: Vec<u8>,
+    pub accounts: Vec<OperationAccount>,
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CouncilOperation {
+    /// Approvals from keys that are still council members
+    pub fn current_approvals(&self, council: &AuthorityCouncil) -> u8 {
+        let count = self
+            .approvals
+            .iter()
+            .filter(|approver| council.members.contains(approver))
+            .count();
+        u8::try_from(count).unwrap_or(u8::MAX)
+    }
+}
+
+/// A sensitive gated-token instruction queued behind the token's timelock
+#[account]
+pub struct PendingOperation {
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub queued_by: Pubkey,
+    pub data: Vec<u8>,
+    pub accounts: Vec<OperationAccount>,
+    pub queued_at: i64,
+    /// Earliest time the operation may execute
+    pub eta: i64,
+    pub bump: u8,
+}
+
+/// Account reference of a council or queued operation's instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct OperationAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[account]
+pub struct Proposal {
+    pub token_mint: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub title: String,
+    /// Hash of the full resolution text, published off-chain
+    pub description_hash: [u8; 32],
+    /// Snapshot whose record-date balances weigh the votes
+    pub snapshot_id: u64,
+    pub eligible_votes: u64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub votes_abstain: u64,
+    pub voting_starts_at: i64,
+    pub voting_ends_at: i64,
+    /// Share of eligible votes that must be cast, abstentions included
+    pub quorum_bps: u16,
+    /// Share of votes for and against that must be for
+    pub approval_threshold_bps: u16,
+    pub status: ProposalStatus,
+    pub bump: u8,
+    pub proposal_type: ProposalType,
+}
+
+impl Proposal {
+    pub fn quorum_reached(&self) -> bool {
+        let cast = u128::from(self.votes_for)
+            + u128::from(self.votes_against)
+            + u128::from(self.votes_abstain);
+        cast * 10_000 >= u128::from(self.eligible_votes) * u128::from(self.quorum_bps)
+    }
+
+    /// Votes for strictly exceed the approval threshold of decisive votes
+    pub fn approved(&self) -> bool {
+        let decisive = u128::from(self.votes_for) + u128::from(self.votes_against);
+        decisive > 0
+            && u128::from(self.votes_for) * 10_000 > decisive * u128::from(self.approval_threshold_bps)
+    }
+}
+
+/// Signed corporate document anchored by its hash at `[b"resolution", mint, hash]`
+#[account]
+pub struct Resolution {
+    pub token_mint: Pubkey,
+    pub kind: ResolutionKind,
+    /// SHA-256 of the signed document
+    pub document_hash: [u8; 32],
+    pub uri: String,
+    pub effective_date: i64,
+    /// Shareholder proposal the resolution implements, if any
+    pub proposal: Option<Pubkey>,
+    pub recorded_by: Pubkey,
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResolutionKind {
+    BoardConsent,
+    ShareholderResolution,
+    CharterAmendment,
+    Other,
+}
+
+/// ERC-3643 claim topics registry and trusted issuers registry of one token, at
+/// `[b"claim_topics", mint]`
+#[account]
+pub struct ClaimTopicsRegistry {
+    pub token_mint: Pubkey,
+    /// Topics an identity must hold a valid claim on to verify
+    pub required_topics: Vec<u64>,
+    pub trusted_issuers: Vec<TrustedIssuer>,
+    pub bump: u8,
+}
+
+impl ClaimTopicsRegistry {
+    pub const SPACE: usize = 8 + 32 + (4 + 8 * MAX_CLAIM_TOPICS)
+        + (4 + TrustedIssuer::SPACE * MAX_TRUSTED_ISSUERS) + 1;
+
+    /// Whether `issuer` may attest claims on `topic`
+    pub fn trusts(&self, issuer: &Pubkey, topic: u64) -> bool {
+        self.trusted_issuers.iter().any(|trusted| trusted.issuer == *issuer && trusted.topics.contains(&topic))
+    }
+
+    /// Whether `identity` holds an unexpired claim from a trusted issuer on every required
+    /// topic at `now`. A registry requiring nothing verifies no one.
+    pub fn verifies(&self, identity: &Identity, now: i64) -> bool {
+        !self.required_topics.is_empty() && self.required_topics.iter().all(|topic| {
+            identity.claims.iter().any(|claim| {
+                claim.topic == *topic && claim.is_valid(now) && self.trusts(&claim.issuer, *topic)
+            })
+        })
+    }
+
+    fn validate(&self) -> Result<()> {
+        require!(
+            self.required_topics.len() <= MAX_CLAIM_TOPICS
+                && self.trusted_issuers.len() <= MAX_TRUSTED_ISSUERS
+                && !has_duplicates(&self.required_topics),
+            ErrorCode::InvalidClaimTopics
+        );
+        let mut issuers = BTreeSet::new();
+        for trusted in &self.trusted_issuers {
+            require!(
+                issuers.insert(trusted.issuer)
+                    && !trusted.topics.is_empty()
+                    && trusted.topics.len() <= MAX_CLAIM_TOPICS
+                    && !has_duplicates(&trusted.topics),
+                ErrorCode::InvalidClaimTopics
+            );
+        }
+        Ok(())
+    }
+}
+
+fn has_duplicates(topics: &[u64]) -> bool {
+    topics.iter().collect::<BTreeSet<_>>().len() != topics.len()
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TrustedIssuer {
+    pub issuer: Pubkey,
+    /// Claim topics this issuer may attest
+    pub topics: Vec<u64>,
+}
+
+impl TrustedIssuer {
+    pub const SPACE: usize = 32 + 4 + 8 * MAX_CLAIM_TOPICS;
+}
+
+/// A wallet's ERC-734/735 identity for one token, at `[b"identity", mint, wallet]`
+#[account]
+pub struct Identity {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub claims: Vec<IdentityClaim>,
+    /// The wallet's current approval was granted by `verify_identity`
+    pub approved_by_identity: bool,
+    /// When `revoke_unverified` last withdrew that approval
+    pub lapsed_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl Identity {
+    pub const SPACE: usize = 8 + 32 + 32 + (4 + IdentityClaim::SPACE * MAX_IDENTITY_CLAIMS) + 1 + 9 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct IdentityClaim {
+    pub topic: u64,
+    pub issuer: Pubkey,
+    /// Hash of the off-chain evidence behind the claim
+    pub data_hash: [u8; 32],
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+impl IdentityClaim {
+    pub const SPACE: usize = 8 + 32 + 32 + 8 + 9;
+
+    pub fn is_valid(&self, now: i64) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+}
+
+/// Wormhole Token Bridge route of one token, at `[b"bridge_config", mint]`. It redeems
+/// inbound transfers and owns the `[b"bridge_vault", mint]` account they land in.
+#[account]
+pub struct BridgeConfig {
+    pub token_mint: Pubkey,
+    pub token_bridge_program: Pubkey,
+    pub wormhole_program: Pubkey,
+    pub enabled: bool,
+    /// Shares sent out through `bridge_out` and not yet redeemed back
+    pub total_bridged_out: u64,
+    pub bump: u8,
+}
+
+/// Kind of resolution, selecting the voting rules it is held to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProposalType {
+    /// Ordinary business, decided by simple majority
+    Ordinary,
+    /// Charter amendments, mergers and the like, requiring a supermajority
+    Special,
+}
+
+/// Voting rules for one proposal type
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProposalRules {
+    /// Share of eligible votes that must be cast, abstentions included
+    pub quorum_bps: u16,
+    /// Share of votes for and against that must be for (5_000 simple, 6_666 two-thirds)
+    pub approval_threshold_bps: u16,
+    pub min_voting_period_seconds: i64,
+}
+
+impl ProposalRules {
+    pub fn validate(&self) -> Result<()> {
+        require!(self.quorum_bps <= 10_000, ErrorCode::InvalidProposalRules);
+        require!(
+            (5_000..10_000).contains(&self.approval_threshold_bps),
+            ErrorCode::InvalidProposalRules
+        );
+        require!(
+            (MIN_VOTING_PERIOD_SECONDS..=MAX_VOTING_PERIOD_SECONDS).contains(&self.min_voting_period_seconds),
+            ErrorCode::InvalidProposalRules
+        );
+        Ok(())
+    }
+}
+
+/// Per-type voting rules shareholder proposals are created under
+#[account]
+pub struct GovernanceConfig {
+    pub token_mint: Pubkey,
+    /// Indexed by `ProposalType`
+    pub rules: [ProposalRules; PROPOSAL_TYPE_COUNT],
+    pub bump: u8,
+}
+
+impl GovernanceConfig {
+    pub fn rules_for(&self, proposal_type: ProposalType) -> ProposalRules {
+        self.rules[proposal_type as usize]
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ProposalStatus {
+    Active,
+    Passed,
+    Rejected,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub choice: VoteChoice,
+    pub weight: u64,
+    pub cast_at: i64,
+    pub bump: u8,
+    /// Wallet that cast the vote: the voter, or their delegate
+    pub cast_by: Pubkey,
+}
+
+impl VoteRecord {
+    pub const SPACE: usize = 8 + 32 + 32 + 1 + 8 + 8 + 1 + 32;
+}
+
+/// Realm this token supplies SPL Governance voter weight to
+#[account]
+pub struct VoterWeightRegistrar {
+    pub token_mint: Pubkey,
+    pub realm: Pubkey,
+    pub governance_program_id: Pubkey,
+    pub bump: u8,
+}
+
+/// SPL Governance voter-weight record (spl-governance-addin-api layout; the Anchor
+/// discriminator is the one Realms expects)
+#[account]
+pub struct VoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    /// Slot the weight is valid for; Realms rejects it in any later slot
+    pub voter_weight_expiry: Option<u64>,
+    pub weight_action: Option<VoterWeightAction>,
+    pub weight_action_target: Option<Pubkey>,
+    pub reserved: [u8; 8],
+}
+
+/// SPL Governance max voter-weight record (spl-governance-addin-api layout)
+#[account]
+pub struct MaxVoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub max_voter_weight: u64,
+    pub max_voter_weight_expiry: Option<u64>,
+    pub reserved: [u8; 8],
+}
+
+/// Governance action a voter weight is scoped to, as defined by spl-governance-addin-api
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VoterWeightAction {
+    CastVote,
+    CommentProposal,
+    CreateGovernance,
+    CreateProposal,
+    SignOffProposal,
+}
+
+/// A holder's standing delegation of their votes to another wallet
+#[account]
+pub struct VoteDelegation {
+    pub token_mint: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub delegated_at: i64,
+    pub bump: u8,
+}
+
+/// Per-class input to `execute_waterfall`: the record-date holder snapshot its
+/// distribution is claimed against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct WaterfallClassInput {
+    pub snapshot_root: [u8; 32],
+    pub snapshot_supply: u64,
+}
+
+/// Working state for one class while a waterfall is computed
+struct WaterfallTranche {
+    token_mint: Pubkey,
+    share_class: Pubkey,
+    seniority: u8,
+    decimals: u8,
+    shares: u64,
+    preference: u64,
+    shares_in_residual: bool,
+    preference_paid: u64,
+    residual_paid: u64,
+}
+
+#[account]
+pub struct VestingSchedule {
+    pub token_mint: Pubkey,
+    pub vesting_id: u64,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub released_amount: u64,
+    pub terms: VestingTerms,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub bump: u8,
+}
+
+impl VestingSchedule {
+    /// Amount vested at `now`. A revoked schedule is frozen at what had vested when it
+    /// was revoked.
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if self.revoked {
+            return Ok(self.total_amount);
+        }
+        self.terms.vested_amount(self.total_amount, now)
+    }
+}
+
+#[account]
+pub struct OptionGrant {
+    pub token_mint: Pubkey,
+    pub grant_id: u64,
+    pub employee: Pubkey,
+    pub quantity: u64,
+    pub exercised: u64,
+    /// Payment mint base units per whole share
+    pub strike_price: u64,
+    pub payment_mint: Pubkey,
+    pub vesting: VestingTerms,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl OptionGrant {
+    /// Options vested at `now` and not yet exercised
+    pub fn exercisable(&self, now: i64) -> Result<u64> {
+        Ok(self.vesting.vested_amount(self.quantity, now)?
+            .saturating_sub(self.exercised))
+    }
+
+    /// Strike owed for exercising `amount` raw token units, rounded up
+    pub fn exercise_cost(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(amount)
+            .checked_mul(u128::from(self.strike_price))
+            .ok_or(ErrorCode::Overflow)?
+            .div_ceil(unit);
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct RedemptionConfig {
+    pub token_mint: Pubkey,
+    pub payment_mint: Pubkey,
+    /// Payment mint base units per whole share; 0 when the window is priced at NAV
+    pub price: u64,
+    pub is_open: bool,
+    pub closes_at: i64,
+    pub total_redeemed: u64,
+    pub bump: u8,
+}
+
+impl RedemptionConfig {
+    /// Whether holders may redeem at `now`
+    pub fn is_window_open(&self, now: i64) -> bool {
+        self.is_open && now < self.closes_at
+    }
+
+    /// Payment owed for redeeming `amount` raw token units, rounded down
+    pub fn payout(&self, amount: u64, decimals: u8) -> Result<u64> {
+        shares_value(amount, self.price, decimals)
+    }
+}
+
+/// Oracle reading a priced action settled at: `price * 10^exponent` in the feed's quote
+/// currency, give or take `confidence` at the same scale
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub confidence: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Price per whole share in base units of a payment mint with `payment_decimals`,
+    /// taking one payment token to be one unit of the feed's quote currency. Rounded down.
+    pub fn per_share(&self, payment_decimals: u8) -> Result<u64> {
+        let price = u128::try_from(self.price).map_err(|_| ErrorCode::InvalidOraclePrice)?;
+        let scale = self.exponent.checked_add(i32::from(payment_decimals)).ok_or(ErrorCode::Overflow)?;
+        let factor = 10u128.checked_pow(scale.unsigned_abs()).ok_or(ErrorCode::Overflow)?;
+        let value = if scale >= 0 {
+            price.checked_mul(factor).ok_or(ErrorCode::Overflow)?
+        } else {
+            price / factor
+        };
+        Ok(u64::try_from(value).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TenderOfferStatus {
+    Open,
+    Settled,
+    Withdrawn,
+}
+
+#[account]
+pub struct TenderOffer {
+    pub token_mint: Pubkey,
+    pub offer_id: u64,
+    pub payment_mint: Pubkey,
+    /// Payment mint base units per whole share
+    pub price: u64,
+    pub cap: u64,
+    pub min_acceptance: u64,
+    pub deadline: i64,
+    pub total_tendered: u64,
+    pub accepted_amount: u64,
+    pub status: TenderOfferStatus,
+    pub bump: u8,
+}
+
+impl TenderOffer {
+    /// Payment owed for `amount` raw token units, rounded down
+    pub fn cost(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(amount)
+            .checked_mul(u128::from(self.price))
+            .ok_or(ErrorCode::Overflow)?
+            / unit;
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+
+    /// Portion of a holder's `tendered` shares accepted, pro-rata when oversubscribed
+    pub fn accepted_share(&self, tendered: u64) -> Result<u64> {
+        if self.status != TenderOfferStatus::Settled || self.total_tendered == 0 {
+            return Ok(0);
+        }
+        let accepted = u128::from(tendered)
+            .checked_mul(u128::from(self.accepted_amount))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.total_tendered);
+        Ok(u64::try_from(accepted).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct TenderPosition {
+    pub tender_offer: Pubkey,
+    pub holder: Pubkey,
+    pub tendered: u64,
+    pub bump: u8,
+}
+
+/// Event that converts a note into equity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConversionTrigger {
+    /// Priced round at `price_per_share` (pricing base units per whole share)
+    QualifiedFinancing { price_per_share: u64 },
+    /// Note reached maturity without a financing; converts at the valuation cap
+    Maturity,
+}
+
+#[account]
+pub struct ConvertibleNote {
+    pub token_mint: Pubkey,
+    pub note_id: u64,
+    pub noteholder: Pubkey,
+    /// Pricing currency base units
+    pub principal: u64,
+    pub discount_bps: u16,
+    /// Pre-money valuation cap in pricing currency base units; 0 for uncapped
+    pub valuation_cap: u64,
+    pub maturity: i64,
+    pub issued_at: i64,
+    pub bump: u8,
+}
+
+impl ConvertibleNote {
+    /// Price per whole share the note converts at, given the current raw `supply`
+    pub fn conversion_price(&self, trigger: ConversionTrigger, supply: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cap_price = if self.valuation_cap > 0 && supply > 0 {
+            let price = u128::from(self.valuation_cap)
+                .checked_mul(unit)
+                .ok_or(ErrorCode::Overflow)?
+                / u128::from(supply);
+            Some(u64::try_from(price).map_err(|_| ErrorCode::Overflow)?)
+        } else {
+            None
+        };
+
+        let price = match trigger {
+            ConversionTrigger::QualifiedFinancing { price_per_share } => {
+                let discounted = u128::from(price_per_share)
+                    .checked_mul(u128::from(10_000 - self.discount_bps))
+                    .ok_or(ErrorCode::Overflow)?
+                    / 10_000;
+                let discounted = u64::try_from(discounted).map_err(|_| ErrorCode::Overflow)?;
+                cap_price.map_or(discounted, |cap| cap.min(discounted))
+            }
+            ConversionTrigger::Maturity => cap_price.ok_or(ErrorCode::NoteNotConvertible)?,
+        };
+        require!(price > 0, ErrorCode::NoteNotConvertible);
+        Ok(price)
+    }
+
+    /// Raw token units the principal buys at `price` per whole share, rounded down
+    pub fn shares_at(&self, price: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let shares = u128::from(self.principal)
+            .checked_mul(unit)
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(price);
+        Ok(u64::try_from(shares).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct Warrant {
+    pub token_mint: Pubkey,
+    pub warrant_id: u64,
+    pub holder: Pubkey,
+    pub quantity: u64,
+    pub exercised: u64,
+    /// Payment mint base units per whole share
+    pub strike_price: u64,
+    pub payment_mint: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Warrant {
+    /// Quantity not yet exercised
+    pub fn remaining(&self) -> u64 {
+        self.quantity.saturating_sub(self.exercised)
+    }
+
+    /// Strike owed for exercising `amount` raw token units, rounded up
+    pub fn exercise_cost(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(amount)
+            .checked_mul(u128::from(self.strike_price))
+            .ok_or(ErrorCode::Overflow)?
+            .div_ceil(unit);
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+/// Per-token index of cap table pages, enumerable without getProgramAccounts scans
+#[account]
+pub struct CapTable {
+    pub token_mint: Pubkey,
+    /// Pages `0..next_page_index` have been created; closed pages leave gaps
+    pub next_page_index: u32,
+    pub bump: u8,
+}
+
+/// Ring buffer of a token's most recent `gated_transfer`s at `[b"transfer_journal", mint]`.
+/// `digest` chains every entry ever appended, so an indexer holding an earlier digest
+/// can tell whether the history it replays since then is complete and unaltered.
+#[account(zero_copy)]
+pub struct TransferJournal {
+    pub token_mint: Pubkey,
+    pub digest: [u8; 32],
+    /// Entries appended since creation; the next one lands at `total % capacity`
+    pub total: u64,
+    pub entries: [JournalEntry; TRANSFER_JOURNAL_CAPACITY],
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl TransferJournal {
+    pub const SPACE: usize = 8 + std::mem::size_of::<TransferJournal>();
+
+    pub fn append(&mut self, entry: JournalEntry) -> Result<()> {
+        let slot = (self.total % TRANSFER_JOURNAL_CAPACITY as u64) as usize;
+        self.digest = solana_sha256_hasher::hashv(&[
+            &self.digest,
+            entry.from.as_ref(),
+            entry.to.as_ref(),
+            &entry.amount.to_le_bytes(),
+            &entry.slot.to_le_bytes(),
+        ])
+        .to_bytes();
+        self.entries[slot] = entry;
+        self.total = self.total.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
+
+#[zero_copy]
+#[derive(PartialEq, Eq, Debug)]
+pub struct JournalEntry {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+}
+
+/// Program allowed to invoke a token's restricted instructions through CPI, at
+/// `[b"trusted_caller", mint, program_id]`
+#[account]
+pub struct TrustedCaller {
+    pub token_mint: Pubkey,
+    pub program_id: Pubkey,
+    pub bump: u8,
+}
+
+/// Dense numbering of a token's holders at `[b"holder_index", mint]`: numbers `0..len`
+/// each map to one wallet with a positive balance, so cranks can walk every holder by
+/// number without off-chain enumeration
+#[account]
+pub struct HolderIndex {
+    pub token_mint: Pubkey,
+    pub len: u64,
+    pub bump: u8,
+}
+
+/// Holder number `number` at `[b"holder_number", mint, number]`
+#[account]
+pub struct HolderNumber {
+    pub token_mint: Pubkey,
+    pub number: u64,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+/// Zero-copy so balance changes touch one record in place instead of decoding the
+/// whole page; `holders[..len]` are live
+#[account(zero_copy)]
+pub struct HolderPage {
+    pub token_mint: Pubkey,
+    pub page_index: u32,
+    pub len: u32,
+    pub holders: [HolderRecord; HOLDER_PAGE_CAPACITY],
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl HolderPage {
+    pub const SPACE: usize = 8 + std::mem::size_of::<HolderPage>();
+
+    pub fn holders(&self) -> &[HolderRecord] {
+        &self.holders[..self.len as usize]
+    }
+
+    pub fn position(&self, wallet: &Pubkey) -> Option<usize> {
+        self.holders().iter().position(|record| record.wallet == *wallet)
+    }
+
+    pub fn push(&mut self, record: HolderRecord) -> Result<()> {
+        let len = self.len as usize;
+        require!(len < HOLDER_PAGE_CAPACITY, ErrorCode::HolderPageFull);
+        self.holders[len] = record;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the record at `index`, moving the last live record into its slot
+    pub fn swap_remove(&mut self, index: usize) -> HolderRecord {
+        let last = self.len as usize - 1;
+        let record = self.holders[index];
+        self.holders[index] = self.holders[last];
+        self.holders[last] = bytemuck::Zeroable::zeroed();
+        self.len -= 1;
+        record
+    }
+}
+
+#[zero_copy]
+#[derive(PartialEq, Eq, Debug)]
+pub struct HolderRecord {
+    pub wallet: Pubkey,
+    pub balance: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SaleProposalStatus {
+    /// Inside or past the ROFR window without the issuer matching
+    Pending,
+    /// Issuer matched; the seller owes the shares to the treasury
+    Exercised,
+}
+
+/// Proposed secondary sale awaiting the issuer's right of first refusal
+#[account]
+pub struct SaleProposal {
+    pub token_mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    /// Payment mint base units per whole share
+    pub price: u64,
+    pub payment_mint: Pubkey,
+    pub posted_at: i64,
+    pub expires_at: i64,
+    pub status: SaleProposalStatus,
+    pub bump: u8,
+}
+
+impl SaleProposal {
+    /// Payment owed for the proposed shares at the proposed price, rounded down
+    pub fn cost(&self, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(self.amount)
+            .checked_mul(u128::from(self.price))
+            .ok_or(ErrorCode::Overflow)?
+            / unit;
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct RightsOffering {
+    pub token_mint: Pubkey,
+    pub offering_id: u64,
+    /// Record-date snapshot entitlements are computed from
+    pub snapshot_id: u64,
+    pub snapshot_supply: u64,
+    pub new_shares: u64,
+    /// Payment mint base units per whole share
+    pub price: u64,
+    pub payment_mint: Pubkey,
+    pub deadline: i64,
+    pub allow_oversubscription: bool,
+    pub total_subscribed: u64,
+    pub total_oversubscription: u64,
+    pub bump: u8,
+}
+
+impl RightsOffering {
+    /// Pro-rata rights for a record-date balance, rounded down to whole lots
+    pub fn entitlement(&self, record_balance: u64, lot_size: u64) -> Result<u64> {
+        let rights = u128::from(record_balance)
+            .checked_mul(u128::from(self.new_shares))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.snapshot_supply);
+        let rights = u64::try_from(rights).map_err(|_| ErrorCode::Overflow)?;
+        Ok(rights - rights % lot_size.max(1))
+    }
+
+    /// Extra shares allotted for an oversubscription request: the lapsed rights shared
+    /// pro-rata to requests, never more than requested, rounded down to whole lots
+    pub fn oversubscription_allocation(&self, requested: u64, lot_size: u64) -> Result<u64> {
+        if requested == 0 || !self.allow_oversubscription {
+            return Ok(0);
+        }
+        let available = self.new_shares.saturating_sub(self.total_subscribed);
+        let allocation = if self.total_oversubscription <= available {
+            requested
+        } else {
+            let share = u128::from(requested)
+                .checked_mul(u128::from(available))
+                .ok_or(ErrorCode::Overflow)?
+                / u128::from(self.total_oversubscription);
+            u64::try_from(share).map_err(|_| ErrorCode::Overflow)?
+        };
+        Ok(allocation - allocation % lot_size.max(1))
+    }
+
+    /// Price of `amount` raw token units, rounded up
+    pub fn cost(&self, amount: u64, decimals: u8) -> Result<u64> {
+        let unit = 10u128.checked_pow(u32::from(decimals)).ok_or(ErrorCode::Overflow)?;
+        let cost = u128::from(amount)
+            .checked_mul(u128::from(self.price))
+            .ok_or(ErrorCode::Overflow)?
+            .div_ceil(unit);
+        Ok(u64::try_from(cost).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+#[account]
+pub struct RightsSubscription {
+    pub rights_offering: Pubkey,
+    pub holder: Pubkey,
+    pub entitlement: u64,
+    pub subscribed: u64,
+    pub oversubscription: u64,
+    /// Payment escrowed for subscribed and oversubscribed shares
+    pub paid: u64,
+    pub bump: u8,
+}
+
+/// Pro-rata issuance to the holders of record at a snapshot
+#[account]
+pub struct Allocation {
+    pub token_mint: Pubkey,
+    pub allocation_id: u64,
+    pub snapshot_id: u64,
+    pub snapshot_supply: u64,
+    pub issuance_amount: u64,
+    pub allocated_amount: u64,
+    pub holders_processed: u64,
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+impl Allocation {
+    /// A holder's share of the issuance for a record-date balance, rounded down to
+    /// whole lots
+    pub fn share_of(&self, record_balance: u64, lot_size: u64) -> Result<u64> {
+        let share = u128::from(record_balance)
+            .checked_mul(u128::from(self.issuance_amount))
+            .ok_or(ErrorCode::Overflow)?
+            / u128::from(self.snapshot_supply);
+        let share = u64::try_from(share).map_err(|_| ErrorCode::Overflow)?;
+        Ok(share - share % lot_size.max(1))
+    }
+}
+
+/// Cliff-then-linear release schedule shared by token vesting and option grants
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct VestingTerms {
+    pub start_ts: i64,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+}
+
+impl VestingTerms {
+    /// Duration must be positive and the cliff within it
+    pub fn validate(&self) -> Result<()> {
+        require!(self.duration_seconds > 0, ErrorCode::InvalidVestingSchedule);
+        require!(
+            (0..=self.duration_seconds).contains(&self.cliff_seconds),
+            ErrorCode::InvalidVestingSchedule
+        );
+        require!(
+            self.start_ts.checked_add(self.duration_seconds).is_some(),
+            ErrorCode::InvalidVestingSchedule
+        );
+        Ok(())
+    }
+
+    /// Portion of `total` vested at `now`: nothing before the cliff, then linear until
+    /// fully vested
+    pub fn vested_amount(&self, total: u64, now: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.start_ts);
+        if elapsed < self.cliff_seconds {
+            return Ok(0);
+        }
+        if elapsed >= self.duration_seconds {
+            return Ok(total);
+        }
+        let vested = u128::from(total)
+            .checked_mul(u128::try_from(elapsed).map_err(|_| ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?
+            / u128::try_from(self.duration_seconds).map_err(|_| ErrorCode::Overflow)?;
+        Ok(u64::try_from(vested).map_err(|_| ErrorCode::Overflow)?)
+    }
+}
+
+/// Hash of a signed off-chain compliance digest for one reporting period
+#[account]
+pub struct ComplianceDigest {
+    pub token_mint: Pubkey,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub digest_hash: [u8; 32],
+    pub alert_count: u32,
+    pub signed_by: Pubkey,
+    pub posted_at: i64,
+    pub bump: u8,
+}
+
+/// Next permit nonce a wallet must present to `permit_transfer`
+#[account]
+pub struct PermitNonce {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
+}
+
+/// Planned issuance to the issuer treasury, minted period by period by `crank_emission`
+#[account]
+pub struct MintSchedule {
+    pub token_mint: Pubkey,
+    pub treasury_account: Pubkey,
+    pub amount_per_period: u64,
+    pub period_seconds: i64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub periods_emitted: u64,
+    pub total_emitted: u64,
+    pub bump: u8,
+}
+
+impl MintSchedule {
+    /// Whole periods elapsed by `now` (capped at `end_time`) that have not yet been minted
+    pub fn periods_due(&self, now: i64) -> Result<u64> {
+        let until = now.min(self.end_time);
+        if until <= self.start_time {
+            return Ok(0);
+        }
+        let elapsed = until.checked_sub(self.start_time).ok_or(ErrorCode::Overflow)?;
+        let periods = u64::try_from(elapsed / self.period_seconds).map_err(|_| ErrorCode::Overflow)?;
+        Ok(periods.saturating_sub(self.periods_emitted))
+    }
+}
+
+/// Legal entity behind several gated tokens, at `[b"issuer", authority]`. `mints`
+/// lists every registered token so indexers can enumerate them from this one PDA.
+#[account]
+pub struct Issuer {
+    pub authority: Pubkey,
+    /// Compliance officer pushed to every registered token
+    pub compliance_officer: Pubkey,
+    pub name: String,
+    pub mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+/// What must hold before an escrow can be released
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReleaseCondition {
+    /// Releases once the clock reaches `release_at`
+    Timestamp { release_at: i64 },
+    /// Releases when `signer` (e.g. an escrow agent or the counterparty) co-signs
+    CounterSignature { signer: Pubkey },
+    /// Releases once the oracle-owned `price_feed` reports at least `min_price`
+    OraclePrice { oracle_program: Pubkey, price_feed: Pubkey, min_price: i64 },
+}
+
+impl ReleaseCondition {
+    /// Borsh size of the largest variant
+    pub const MAX_SIZE: usize = 1 + 32 + 32 + 8;
+
+    /// Whether the condition holds, given whichever optional release accounts were passed
+    pub fn is_met(
+        &self,
+        now: i64,
+        counter_signer: Option<&Signer>,
+        price_feed: Option<&UncheckedAccount>,
+    ) -> Result<bool> {
+        match *self {
+            ReleaseCondition::Timestamp { release_at } => Ok(now >= release_at),
+            ReleaseCondition::CounterSignature { signer } => {
+                Ok(counter_signer.is_some_and(|counter_signer| counter_signer.key() == signer))
+            }
+            ReleaseCondition::OraclePrice { oracle_program, price_feed: feed_key, min_price } => {
+                let feed = price_feed.ok_or(ErrorCode::InvalidPriceFeed)?;
+                require_keys_eq!(feed.key(), feed_key, ErrorCode::InvalidPriceFeed);
+                require_keys_eq!(*feed.owner, oracle_program, ErrorCode::InvalidPriceFeed);
+                let data = feed.try_borrow_data()?;
+                let discriminator = solana_sha256_hasher::hashv(&[b"account:PriceFeed"]);
+                require!(
+                    data.len() >= 8 && data[..8] == discriminator.to_bytes()[..8],
+                    ErrorCode::InvalidPriceFeed
+                );
+                let header = PriceFeedHeader::deserialize(&mut &data[8..])
+                    .map_err(|_| ErrorCode::InvalidPriceFeed)?;
+                Ok(header.price >= min_price)
+            }
+        }
+    }
+}
+
+/// Shares locked at `[b"escrow", mint, escrow_id]` for `recipient`, held in the
+/// `[b"escrow_vault", escrow]` token account until the condition is met
+#[account]
+pub struct Escrow {
+    pub token_mint: Pubkey,
+    pub escrow_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub condition: ReleaseCondition,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Standing offer at `[b"swap_offer", offered_mint, offer_id]` to exchange the shares
+/// held in its `[b"swap_vault", offer]` vault for `requested_amount` of another token
+#[account]
+pub struct SwapOffer {
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    /// Only this wallet may accept; anyone approved on both mints when `None`
+    pub taker: Option<Pubkey>,
+    pub offered_mint: Pubkey,
+    pub offered_amount: u64,
+    pub requested_mint: Pubkey,
+    pub requested_amount: u64,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Wrapper for a gated token: `wrapped_mint` is an unrestricted SPL mint whose supply is
+/// backed one-for-one by shares in the `[b"wrapper_vault", mint]` account
+#[account]
+pub struct WrapperConfig {
+    pub token_mint: Pubkey,
+    pub wrapped_mint: Pubkey,
+    /// Shares currently wrapped; must equal both the vault balance and wrapped supply
+    pub total_wrapped: u64,
+    pub bump: u8,
+}
+
+/// Shares streaming from `sender` to `recipient` out of the `[b"stream_vault", stream]`
+/// account, accruing at `rate_per_second` from `start_time` up to `deposited`
+#[account]
+pub struct PaymentStream {
+    pub token_mint: Pubkey,
+    pub stream_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+    /// Set while paused; accrual stops at this time
+    pub paused_at: Option<i64>,
+    /// Total seconds spent paused since `start_time`
+    pub paused_seconds: i64,
+    pub bump: u8,
+}
+
+impl PaymentStream {
+    /// Total amount streamed by `now`, both withdrawn and not
+    pub fn streamed_amount(&self, now: i64) -> Result<u64> {
+        let until = self.paused_at.unwrap_or(now);
+        let elapsed = until.saturating_sub(self.start_time)
+            .saturating_sub(self.paused_seconds)
+            .max(0) as u128;
+        let streamed = elapsed.checked_mul(self.rate_per_second as u128)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(streamed.min(self.deposited as u128) as u64)
+    }
+}
+
+/// Claim-based grant at `[b"airdrop", mint, campaign_id]`, paid out of the
+/// `[b"airdrop_vault", campaign]` account to wallets proving a leaf of `merkle_root`
+#[account]
+pub struct AirdropCampaign {
+    pub token_mint: Pubkey,
+    pub campaign_id: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub expires_at: i64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Receipt of one wallet's airdrop claim; its existence blocks a second claim
+#[account]
+pub struct AirdropClaim {
+    pub campaign: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+/// Recurring distribution plan at `[b"distribution_schedule", mint]`, paid from the
+/// `[b"schedule_vault", schedule]` account one epoch per `interval_seconds`
+#[account]
+pub struct DistributionSchedule {
+    pub token_mint: Pubkey,
+    pub payout_mint: Pubkey,
+    pub amount_per_period: u64,
+    pub interval_seconds: i64,
+    /// How long holders have to claim each epoch before it can be swept
+    pub claim_window_seconds: i64,
+    pub next_run_at: i64,
+    pub epochs_run: u64,
+    pub active: bool,
+    pub bump: u8,
+}
+
+/// Compact allowlist storage for `BITMAP_PAGE_SLOTS` wallets in one account, for large,
+/// mostly static investor bases where a PDA per wallet costs too much rent. Wallets are
+/// assigned slots in order; each (wallet, slot) pair is appended to `assignment_root`
+/// and approval is one bit per slot, so approving or revoking never touches the tree.
+/// Global slot `n` lives on page `n / BITMAP_PAGE_SLOTS`.
+#[account(zero_copy)]
+pub struct AllowlistBitmapPage {
+    pub token_mint: Pubkey,
+    /// Root of the sorted-pair Merkle tree over `allowlist_slot_leaf` of every assigned
+    /// slot, padded with zero leaves to `BITMAP_PAGE_DEPTH`
+    pub assignment_root: [u8; 32],
+    /// Left siblings on the path of the next slot, for appending without the leaves
+    pub frontier: [[u8; 32]; BITMAP_PAGE_DEPTH],
+    pub approved: [u8; BITMAP_PAGE_SLOTS as usize / 8],
+    pub page_index: u32,
+    /// Slots assigned so far; the next wallet gets local slot `assigned`
+    pub assigned: u32,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl AllowlistBitmapPage {
+    pub const SPACE: usize = 8 + std::mem::size_of::<AllowlistBitmapPage>();
+
+    /// Global number of the page's first slot
+    pub fn first_slot(&self) -> u32 {
+        self.page_index * BITMAP_PAGE_SLOTS
+    }
+
+    /// Assign the next slot to `wallet` and return its global number
+    pub fn assign(&mut self, wallet: &Pubkey) -> Result<u32> {
+        require!(self.assigned < BITMAP_PAGE_SLOTS, ErrorCode::BitmapPageFull);
+        let slot = self.first_slot() + self.assigned;
+        self.assigned += 1;
+
+        let mut node = allowlist_slot_leaf(wallet, slot);
+        let mut size = self.assigned;
+        for level in 0..BITMAP_PAGE_DEPTH {
+            if size & 1 == 1 {
+                self.frontier[level] = node;
+                break;
+            }
+            node = hash_pair(&self.frontier[level], &node);
+            size >>= 1;
+        }
+
+        let mut root = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.assigned;
+        for level in 0..BITMAP_PAGE_DEPTH {
+            root = if size & 1 == 1 {
+                hash_pair(&self.frontier[level], &root)
+            } else {
+                hash_pair(&root, &zero)
+            };
+            zero = hash_pair(&zero, &zero);
+            size >>= 1;
+        }
+        self.assignment_root = root;
+        Ok(slot)
+    }
+
+    pub fn set_approved(&mut self, slot: u32, approved: bool) -> Result<()> {
+        let local = self.local_slot(slot)?;
+        require!(local < self.assigned, ErrorCode::InvalidAllowlistSlot);
+        let mask = 1u8 << (local % 8);
+        let byte = &mut self.approved[(local / 8) as usize];
+        if approved {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+        Ok(())
+    }
+
+    /// Whether `wallet` holds `slot` on this page, per `proof`, and the slot is approved
+    pub fn is_approved(&self, wallet: &Pubkey, slot: u32, proof: &[[u8; 32]]) -> Result<bool> {
+        let local = self.local_slot(slot)?;
+        require!(
+            proof.len() == BITMAP_PAGE_DEPTH
+                && verify_merkle_proof(&self.assignment_root, allowlist_slot_leaf(wallet, slot), proof),
+            ErrorCode::InvalidAllowlistSlot
+        );
+        Ok(self.approved[(local / 8) as usize] & (1u8 << (local % 8)) != 0)
+    }
+
+    fn local_slot(&self, slot: u32) -> Result<u32> {
+        require!(slot / BITMAP_PAGE_SLOTS == self.page_index, ErrorCode::InvalidAllowlistSlot);
+        Ok(slot % BITMAP_PAGE_SLOTS)
+    }
+}
+
+/// Compressed allowlist of a mint at `[b"allowlist_tree", mint]`: approvals are leaves
+/// of an spl-account-compression concurrent Merkle tree this PDA has authority over.
+/// The tree account is allocated by the client with the canopy it wants kept on-chain;
+/// proofs then only carry the nodes below the canopy.
+#[account]
+pub struct AllowlistTree {
+    pub token_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    /// Index the next appended wallet's leaf will occupy
+    pub next_leaf_index: u32,
+    pub bump: u8,
+}
+
+/// Share certificate tree of a mint at `[b"certificate_tree", mint]`: the private
+/// creator and leaf delegate of a Bubblegum tree holding one compressed NFT per holder
+#[account]
+pub struct CertificateTree {
+    pub token_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    /// Restriction legend copied onto each certificate
+    pub legend: String,
+    /// Base URI of certificate metadata
+    pub uri: String,
+    /// Bubblegum nonce, and leaf index, of the next certificate
+    pub next_nonce: u64,
+    pub bump: u8,
+}
+
+/// A holder's share certificate at `[b"share_certificate", mint, holder]`, holding what
+/// its compressed NFT was minted with
+#[account]
+pub struct ShareCertificate {
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    /// Bubblegum asset ID of the compressed NFT
+    pub asset_id: Pubkey,
+    pub nonce: u64,
+    pub class: String,
+    pub symbol: String,
+    pub uri: String,
+    pub legend: String,
+    pub issued_at: i64,
+    /// Who funded the record, and gets its rent back on retirement
+    pub rent_payer: Pubkey,
+    pub bump: u8,
+}
+
+impl ShareCertificate {
+    pub const SPACE: usize = 8 + 32 + 32 + 32 + 8
+        + (4 + MAX_SHARE_CLASS_NAME_LEN)
+        + (4 + 10)
+        + (4 + MAX_CERTIFICATE_URI_LEN + 45)
+        + (4 + MAX_CERTIFICATE_LEGEND_LEN)
+        + 8 + 32 + 1;
+
+    /// Immutable, royalty-free and without creators: certificates are recognized by the
+    /// tree they sit in
+    fn metadata(&self) -> CertificateMetadata {
+        CertificateMetadata {
+            name: self.class.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: 0,
+            primary_sale_happened: true,
+            is_mutable: false,
+            edition_nonce: None,
+            token_standard: Some(0),
+            collection: None,
+            uses: None,
+            token_program_version: 0,
+            creators: Vec::new(),
+        }
+    }
+
+    /// Bubblegum's hash of the certificate's metadata, as committed to by its leaf
+    fn data_hash(&self) -> Result<[u8; 32]> {
+        let metadata = self.metadata();
+        let args_hash = solana_keccak_hasher::hashv(&[&metadata.try_to_vec()?]);
+        Ok(solana_keccak_hasher::hashv(&[
+            args_hash.as_ref(),
+            &metadata.seller_fee_basis_points.to_le_bytes(),
+        ]).to_bytes())
+    }
+
+    /// Bubblegum's hash of the (empty) creator list
+    fn creator_hash(&self) -> [u8; 32] {
+        solana_keccak_hasher::hashv(&[]).to_bytes()
+    }
+}
+
+/// Per-wallet amount sent in the current velocity epoch
+#[account]
+pub struct VelocityCounter {
+    pub wallet: Pubkey,
+    pub epoch: i64,
+    pub amount_sent: u64,
+    pub bump: u8,
+}
+
+impl VelocityCounter {
+    /// Add `amount` to the wallet's running total, resetting when a new epoch starts
+    pub fn record(&mut self, wallet: Pubkey, amount: u64, limit: u64, now: i64) -> Result<()> {
+        let epoch = now.div_euclid(VELOCITY_EPOCH_SECONDS);
+        if self.wallet != wallet || self.epoch != epoch {
+            self.wallet = wallet;
+            self.epoch = epoch;
+            self.amount_sent = 0;
+        }
+
+        let amount_sent = self.amount_sent.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        require!(amount_sent <= limit, ErrorCode::DailyTransferLimitExceeded);
+        self.amount_sent = amount_sent;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct BlackoutWindow {
+    pub token_mint: Pubkey,
+    pub window_id: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub scope: BlackoutScope,
+    pub created_by: Pubkey,
+    pub bump: u8,
+}
+
+impl BlackoutWindow {
+    pub fn is_active(&self, now: i64) -> bool {
+        self.start_ts <= now && now < self.end_ts
+    }
+
+    /// Whether the window blocks a transfer that `wallet` is a party to
+    pub fn applies_to(&self, wallet: &Pubkey, is_insider: bool) -> bool {
+        match self.scope {
+            BlackoutScope::AllHolders => true,
+            BlackoutScope::Wallet(blocked) => blocked == *wallet,
+            BlackoutScope::Insiders => is_insider,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlackoutScope {
+    AllHolders,
+    Wallet(Pubkey),
+    /// Earnings blackout: only wallets designated as insiders are blocked
+    Insiders,
+}
+
+/// Non-transferable proof that a corporate action settled for a holder.
+/// `amount_out / amount_in` is expressed by `rate_numerator / rate_denominator`.
+#[account]
+pub struct ActionReceipt {
+    pub action: Pubkey,
+    pub kind: ActionKind,
+    pub holder: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    pub settled_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ActionKind {
+    SplitMigration,
+    Tender,
+    Exchange,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BreakerTripReason {
+    SupplyDrift,
+    NegativeHolderCount,
+    RegistryChecksumMismatch,
+}
 
 // Context structures
 #[derive(Accounts)]
-#[instruction(symbol: String, name: String)]
-pub struct InitializeToken<'info> {
+#[instruction(symbol: String, name: String, decimals: u8, max_supply: Option<u64>, uri: String)]
+pub struct InitializeToken<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: PDA holding the SPL freeze authority
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+    
+    /// Fresh keypair for the share mint, created in the handler
+    #[account(mut)]
+    pub mint: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = TokenConfig::space(&symbol, &name, &uri),
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_registry"],
+        bump = token_registry.bump
+    )]
+    pub token_registry: Account<'info, TokenRegistry>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    /// CHECK: Receives the token creation fee; only needed while one is charged
+    #[account(mut, address = program_config.parameters.fee_recipient @ ErrorCode::FeeRecipientRequired)]
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 4 + (4 + (32 + 32 + 8) * REGISTRY_PAGE_CAPACITY) + 1,
+        seeds = [b"registry_page".as_ref(), &token_registry.current_page_index().to_le_bytes()],
+        bump
+    )]
+    pub registry_page: Box<Account<'info, RegistryPage>>,
+    
+    /// CHECK: Metaplex metadata PDA for the mint, created by the Token Metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+    
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    
+    /// CHECK: allowlist-transfer-hook; given, the mint is created under Token-2022 with
+    /// the hook enforcing the allowlist on every transfer
+    #[account(address = ALLOWLIST_TRANSFER_HOOK_ID)]
+    pub transfer_hook_program: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: The hook's validation account for the mint, created by the hook program
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump,
+        seeds::program = ALLOWLIST_TRANSFER_HOOK_ID
+    )]
+    pub extra_account_meta_list: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveWallet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet to be approved
+    pub wallet: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", token_config.mint.as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeWallet<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet to be revoked
+    pub wallet: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), wallet.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"country_rule", token_config.mint.as_ref(), allowlist_entry.country_code.as_ref()],
+        bump = country_rule.bump
+    )]
+    pub country_rule: Option<Account<'info, CountryRule>>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct CreateAllowlistBitmapPage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistBitmapPage::SPACE,
+        seeds = [b"allowlist_bitmap", token_config.mint.as_ref(), &page_index.to_le_bytes()],
+        bump
+    )]
+    pub page: AccountLoader<'info, AllowlistBitmapPage>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowlistBitmapPage<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist_bitmap", token_config.mint.as_ref(), &page.load()?.page_index.to_le_bytes()],
+        bump = page.load()?.bump
+    )]
+    pub page: AccountLoader<'info, AllowlistBitmapPage>,
+}
+
+#[derive(Accounts)]
+pub struct CompactMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == recipient.key()
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist_bitmap", mint.key().as_ref(), &recipient_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_page.load()?.bump
+    )]
+    pub recipient_page: AccountLoader<'info, AllowlistBitmapPage>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CompactTransfer<'info> {
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == authority.key()
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Page holding the sender's slot; may be the same account as `recipient_page`
+    #[account(
+        seeds = [b"allowlist_bitmap", mint.key().as_ref(), &sender_page.load()?.page_index.to_le_bytes()],
+        bump = sender_page.load()?.bump
+    )]
+    pub sender_page: AccountLoader<'info, AllowlistBitmapPage>,
+    
+    #[account(
+        seeds = [b"allowlist_bitmap", mint.key().as_ref(), &recipient_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_page.load()?.bump
+    )]
+    pub recipient_page: AccountLoader<'info, AllowlistBitmapPage>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitAllowlistTree<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 4 + 4 + 1,
+        seeds = [b"allowlist_tree", token_config.mint.as_ref()],
+        bump
+    )]
+    pub allowlist_tree: Account<'info, AllowlistTree>,
+    
+    /// CHECK: Uninitialized tree account, written by the compression program
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Noop program the compression program logs through
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowlistTree<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist_tree", token_config.mint.as_ref()],
+        bump = allowlist_tree.bump
+    )]
+    pub allowlist_tree: Account<'info, AllowlistTree>,
+    
+    /// CHECK: The token's allowlist tree, written by the compression program
+    #[account(mut, address = allowlist_tree.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Noop program the compression program logs through
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitCertificateTree<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + (4 + MAX_CERTIFICATE_LEGEND_LEN) + (4 + MAX_CERTIFICATE_URI_LEN) + 8 + 1,
+        seeds = [b"certificate_tree", token_config.mint.as_ref()],
+        bump
+    )]
+    pub certificate_tree: Account<'info, CertificateTree>,
+    
+    /// CHECK: Bubblegum's config of the tree, created by Bubblegum
+    #[account(mut, seeds = [merkle_tree.key().as_ref()], bump, seeds::program = bubblegum_program.key())]
+    pub tree_config: UncheckedAccount<'info>,
+    
+    /// CHECK: Uninitialized tree account, written by the compression program
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: Metaplex Bubblegum
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Noop program the compression program logs through
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IssueShareCertificate<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// CHECK: The certificate's owner, the entry's wallet
+    #[account(address = allowlist_entry.wallet)]
+    pub holder: UncheckedAccount<'info>,
+    
+    #[account(
+        constraint = token_account.mint == token_config.mint,
+        constraint = token_account.owner == allowlist_entry.wallet
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"certificate_tree", token_config.mint.as_ref()],
+        bump = certificate_tree.bump
+    )]
+    pub certificate_tree: Account<'info, CertificateTree>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = ShareCertificate::SPACE,
+        seeds = [b"share_certificate", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump
+    )]
+    pub share_certificate: Account<'info, ShareCertificate>,
+    
+    /// CHECK: Bubblegum's config of the tree, written by Bubblegum
+    #[account(mut, seeds = [merkle_tree.key().as_ref()], bump, seeds::program = bubblegum_program.key())]
+    pub tree_config: UncheckedAccount<'info>,
+    
+    /// CHECK: The token's certificate tree, written by the compression program
+    #[account(mut, address = certificate_tree.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: Metaplex Bubblegum
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Noop program the compression program logs through
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RetireShareCertificate<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// CHECK: The certificate's owner, the entry's wallet
+    #[account(address = allowlist_entry.wallet)]
+    pub holder: UncheckedAccount<'info>,
+    
+    #[account(
+        constraint = token_account.mint == token_config.mint,
+        constraint = token_account.owner == allowlist_entry.wallet
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"certificate_tree", token_config.mint.as_ref()],
+        bump = certificate_tree.bump
+    )]
+    pub certificate_tree: Account<'info, CertificateTree>,
+    
+    #[account(
+        mut,
+        close = rent_payer,
+        seeds = [b"share_certificate", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = share_certificate.bump
+    )]
+    pub share_certificate: Account<'info, ShareCertificate>,
+    
+    /// CHECK: Receives the record's rent
+    #[account(mut, address = share_certificate.rent_payer)]
+    pub rent_payer: UncheckedAccount<'info>,
+    
+    /// CHECK: Bubblegum's config of the tree
+    #[account(seeds = [merkle_tree.key().as_ref()], bump, seeds::program = bubblegum_program.key())]
+    pub tree_config: UncheckedAccount<'info>,
+    
+    /// CHECK: The token's certificate tree, written by the compression program
+    #[account(mut, address = certificate_tree.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: Metaplex Bubblegum
+    #[account(address = BUBBLEGUM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Noop program the compression program logs through
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CompressedMint<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == recipient.key()
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist_tree", mint.key().as_ref()],
+        bump = allowlist_tree.bump
+    )]
+    pub allowlist_tree: Account<'info, AllowlistTree>,
+    
+    /// CHECK: The token's allowlist tree, read by the compression program
+    #[account(address = allowlist_tree.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CompressedTransfer<'info> {
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == authority.key()
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist_tree", mint.key().as_ref()],
+        bump = allowlist_tree.bump
+    )]
+    pub allowlist_tree: Account<'info, AllowlistTree>,
+    
+    /// CHECK: The token's allowlist tree, read by the compression program
+    #[account(address = allowlist_tree.merkle_tree)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MintTokens<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == recipient.key()
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"country_rule", token_config.mint.as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Leading accounts of the `*_with_ata` variants; the wrapped instruction's own accounts
+/// follow as remaining accounts
+#[derive(Accounts)]
+pub struct CreateRecipientAta<'info> {
+    /// Minting authority or sender; pays the new account's rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Recipient's associated token account, created if missing and validated by
+    /// the associated token program
+    #[account(mut)]
+    pub recipient_token_account: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GatedTransfer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == authority.key()
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), authority.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"destination_registry", mint.key().as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Option<Account<'info, DestinationRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub fee_treasury: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"velocity", mint.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub velocity_counter: Option<Account<'info, VelocityCounter>>,
+    
+    #[account(
+        seeds = [b"country_rule", token_config.mint.as_ref(), sender_allowlist_entry.country_code.as_ref()],
+        bump = sender_country_rule.bump
+    )]
+    pub sender_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", token_config.mint.as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Sender's flag account in the registered sanctions oracle, validated in the handler
+    pub sender_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"sale_proposal", mint.key().as_ref(), authority.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Option<Box<Account<'info, SaleProposal>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &sender_holder_number.number.to_le_bytes()],
+        bump = sender_holder_number.bump
+    )]
+    pub sender_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"transfer_journal", mint.key().as_ref()],
+        bump = transfer_journal.load()?.bump
+    )]
+    pub transfer_journal: Option<AccountLoader<'info, TransferJournal>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PermitTransfer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == authority.key()
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [b"permit_nonce", mint.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub permit_nonce: Account<'info, PermitNonce>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), authority.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    /// CHECK: Sender's flag account in the registered sanctions oracle, validated in the handler
+    pub sender_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// Rule for the sender's recorded country, matched against its entry in the handler
+    pub sender_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// Rule for the recipient's recorded country, matched against its entry in the handler
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"destination_registry", mint.key().as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"velocity", mint.key().as_ref(), authority.key().as_ref()],
+        bump
+    )]
+    pub velocity_counter: Option<Box<Account<'info, VelocityCounter>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"sale_proposal", mint.key().as_ref(), authority.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Option<Box<Account<'info, SaleProposal>>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), authority.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &sender_holder_number.number.to_le_bytes()],
+        bump = sender_holder_number.bump
+    )]
+    pub sender_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read for the preceding ed25519 signature check
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveDelegate<'info> {
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Delegate wallet, which must hold an approved allowlist entry
+    pub delegate: AccountInfo<'info>,
+    
+    #[account(mut, seeds = [b"token_config", mint.key().as_ref()], bump = token_config.bump)]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == mint.key(),
+        constraint = owner_token_account.owner == owner.key()
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), owner.key().as_ref()],
+        bump = owner_allowlist_entry.bump
+    )]
+    pub owner_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), delegate.key().as_ref()],
+        bump = delegate_allowlist_entry.bump
+    )]
+    pub delegate_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    pub owner: Signer<'info>,
+    
+    #[account(mut, constraint = owner_token_account.owner == owner.key())]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", owner_token_account.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct DelegatedTransfer<'info> {
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+    
+    /// CHECK: Owner of the debited token account
+    pub owner: AccountInfo<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == owner.key(),
+        constraint = Option::<Pubkey>::from(from_token_account.delegate) == Some(delegate.key()) @ ErrorCode::InsufficientAllowance
+    )]
+    pub from_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), owner.key().as_ref()],
+        bump = owner_allowlist_entry.bump
+    )]
+    pub owner_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), delegate.key().as_ref()],
+        bump = delegate_allowlist_entry.bump
+    )]
+    pub delegate_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Owner's flag account in the registered sanctions oracle, validated in the handler
+    pub owner_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Delegate's flag account in the registered sanctions oracle, validated in the handler
+    pub delegate_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), owner_allowlist_entry.country_code.as_ref()],
+        bump = owner_country_rule.bump
+    )]
+    pub owner_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"destination_registry", mint.key().as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    /// The owner's counter: delegated sends count against the owner's daily limit
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"velocity", mint.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub velocity_counter: Option<Box<Account<'info, VelocityCounter>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"sale_proposal", mint.key().as_ref(), owner.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Option<Box<Account<'info, SaleProposal>>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), owner.key().as_ref()],
+        bump
+    )]
+    pub owner_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &owner_holder_page.load()?.page_index.to_le_bytes()],
+        bump = owner_holder_page.load()?.bump
+    )]
+    pub owner_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &owner_holder_number.number.to_le_bytes()],
+        bump = owner_holder_number.bump
+    )]
+    pub owner_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSwapOffer<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", offered_mint.key().as_ref()],
+        bump = offered_token_config.bump
+    )]
+    pub offered_token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        seeds = [b"token_config", requested_token_config.mint.as_ref()],
+        bump = requested_token_config.bump,
+        constraint = requested_token_config.mint != offered_mint.key() @ ErrorCode::InvalidSwapOffer
+    )]
+    pub requested_token_config: Box<Account<'info, TokenConfig>>,
+    
+    pub offered_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = maker_offered_token_account.mint == offered_mint.key(),
+        constraint = maker_offered_token_account.owner == maker.key()
+    )]
+    pub maker_offered_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", offered_mint.key().as_ref(), maker.key().as_ref()],
+        bump = maker_offered_allowlist_entry.bump
+    )]
+    pub maker_offered_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"allowlist", requested_token_config.mint.as_ref(), maker.key().as_ref()],
+        bump = maker_requested_allowlist_entry.bump
+    )]
+    pub maker_requested_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + 8 + 32 + 33 + 32 + 8 + 32 + 8 + 9 + 8 + 1,
+        seeds = [b"swap_offer", offered_mint.key().as_ref(), &offered_token_config.next_swap_offer_id.to_le_bytes()],
+        bump
+    )]
+    pub offer: Box<Account<'info, SwapOffer>>,
+    
+    #[account(
+        init,
+        payer = maker,
+        token::mint = offered_mint,
+        token::authority = offer,
+        seeds = [b"swap_vault", offer.key().as_ref()],
+        bump
+    )]
+    pub offer_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = maker,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", offered_mint.key().as_ref(), offer.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", offered_token_config.mint.as_ref(), &offered_token_config.current_snapshot_id.to_le_bytes(), maker.key().as_ref()],
+        bump
+    )]
+    pub maker_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", offered_token_config.mint.as_ref(), &maker_holder_page.load()?.page_index.to_le_bytes()],
+        bump = maker_holder_page.load()?.bump
+    )]
+    pub maker_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", offered_token_config.mint.as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", offered_token_config.mint.as_ref(), &maker_holder_number.number.to_le_bytes()],
+        bump = maker_holder_number.bump
+    )]
+    pub maker_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", offered_token_config.mint.as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptSwapOffer<'info> {
+    #[account(mut)]
+    pub taker: Signer<'info>,
+    
+    /// CHECK: Offer maker, receiving token B and the closed accounts' rent
+    #[account(mut, address = offer.maker)]
+    pub maker: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"swap_offer", offered_mint.key().as_ref(), &offer.offer_id.to_le_bytes()],
+        bump = offer.bump,
+        close = maker
+    )]
+    pub offer: Box<Account<'info, SwapOffer>>,
+    
+    #[account(mut, seeds = [b"swap_vault", offer.key().as_ref()], bump)]
+    pub offer_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry of the vault's owner, closed with the vault; required when the
+    /// mint has a transfer hook
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"allowlist", offer.offered_mint.as_ref(), offer.key().as_ref()],
+        bump = vault_allowlist_entry.bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(address = offer.offered_mint)]
+    pub offered_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(address = offer.requested_mint)]
+    pub requested_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", offered_mint.key().as_ref()],
+        bump = offered_token_config.bump
+    )]
+    pub offered_token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", requested_mint.key().as_ref()],
+        bump = requested_token_config.bump
+    )]
+    pub requested_token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        mut,
+        constraint = taker_offered_token_account.mint == offered_mint.key(),
+        constraint = taker_offered_token_account.owner == taker.key()
+    )]
+    pub taker_offered_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = taker_requested_token_account.mint == requested_mint.key(),
+        constraint = taker_requested_token_account.owner == taker.key()
+    )]
+    pub taker_requested_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = maker_requested_token_account.mint == requested_mint.key(),
+        constraint = maker_requested_token_account.owner == maker.key()
+    )]
+    pub maker_requested_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        seeds = [b"allowlist", offered_mint.key().as_ref(), maker.key().as_ref()],
+        bump = maker_offered_allowlist_entry.bump
+    )]
+    pub maker_offered_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", offered_mint.key().as_ref(), taker.key().as_ref()],
+        bump = taker_offered_allowlist_entry.bump
+    )]
+    pub taker_offered_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", requested_mint.key().as_ref(), maker.key().as_ref()],
+        bump = maker_requested_allowlist_entry.bump
+    )]
+    pub maker_requested_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", requested_mint.key().as_ref(), taker.key().as_ref()],
+        bump = taker_requested_allowlist_entry.bump
+    )]
+    pub taker_requested_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Maker's flag in the offered token's sanctions oracle, validated in the handler
+    pub maker_offered_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Taker's flag in the offered token's sanctions oracle, validated in the handler
+    pub taker_offered_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Maker's flag in the requested token's sanctions oracle, validated in the handler
+    pub maker_requested_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Taker's flag in the requested token's sanctions oracle, validated in the handler
+    pub taker_requested_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"country_rule", offered_mint.key().as_ref(), maker_offered_allowlist_entry.country_code.as_ref()],
+        bump = maker_offered_country_rule.bump
+    )]
+    pub maker_offered_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", offered_mint.key().as_ref(), taker_offered_allowlist_entry.country_code.as_ref()],
+        bump = taker_offered_country_rule.bump
+    )]
+    pub taker_offered_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", requested_mint.key().as_ref(), maker_requested_allowlist_entry.country_code.as_ref()],
+        bump = maker_requested_country_rule.bump
+    )]
+    pub maker_requested_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", requested_mint.key().as_ref(), taker_requested_allowlist_entry.country_code.as_ref()],
+        bump = taker_requested_country_rule.bump
+    )]
+    pub taker_requested_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"destination_registry", offered_mint.key().as_ref()],
+        bump = offered_destination_registry.bump
+    )]
+    pub offered_destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    #[account(
+        seeds = [b"destination_registry", requested_mint.key().as_ref()],
+        bump = requested_destination_registry.bump
+    )]
+    pub requested_destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    #[account(
+        seeds = [b"share_class", offered_mint.key().as_ref()],
+        bump = offered_share_class.bump
+    )]
+    pub offered_share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        seeds = [b"share_class", requested_mint.key().as_ref()],
+        bump = requested_share_class.bump
+    )]
+    pub requested_share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", offered_token_config.mint.as_ref(), &offered_token_config.current_snapshot_id.to_le_bytes(), taker.key().as_ref()],
+        bump
+    )]
+    pub taker_offered_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", offered_token_config.mint.as_ref(), &taker_offered_holder_page.load()?.page_index.to_le_bytes()],
+        bump = taker_offered_holder_page.load()?.bump
+    )]
+    pub taker_offered_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", requested_token_config.mint.as_ref(), &requested_token_config.current_snapshot_id.to_le_bytes(), taker.key().as_ref()],
+        bump
+    )]
+    pub taker_requested_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", requested_token_config.mint.as_ref(), &taker_requested_holder_page.load()?.page_index.to_le_bytes()],
+        bump = taker_requested_holder_page.load()?.bump
+    )]
+    pub taker_requested_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", requested_token_config.mint.as_ref(), &requested_token_config.current_snapshot_id.to_le_bytes(), maker.key().as_ref()],
+        bump
+    )]
+    pub maker_requested_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", requested_token_config.mint.as_ref(), &maker_requested_holder_page.load()?.page_index.to_le_bytes()],
+        bump = maker_requested_holder_page.load()?.bump
+    )]
+    pub maker_requested_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", offered_token_config.mint.as_ref()],
+        bump = offered_holder_index.bump
+    )]
+    pub offered_holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// taker starts holding
+    #[account(mut)]
+    pub taker_offered_holder_number: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", requested_token_config.mint.as_ref()],
+        bump = requested_holder_index.bump
+    )]
+    pub requested_holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", requested_token_config.mint.as_ref(), &taker_requested_holder_number.number.to_le_bytes()],
+        bump = taker_requested_holder_number.bump
+    )]
+    pub taker_requested_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", requested_token_config.mint.as_ref(), &requested_last_holder_number.number.to_le_bytes()],
+        bump = requested_last_holder_number.bump
+    )]
+    pub requested_last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// maker starts holding
+    #[account(mut)]
+    pub maker_requested_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSwapOffer<'info> {
+    #[account(mut, address = offer.maker @ ErrorCode::UnauthorizedSwapAction)]
+    pub maker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(address = offer.offered_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"swap_offer", mint.key().as_ref(), &offer.offer_id.to_le_bytes()],
+        bump = offer.bump,
+        close = maker
+    )]
+    pub offer: Box<Account<'info, SwapOffer>>,
+    
+    #[account(mut, seeds = [b"swap_vault", offer.key().as_ref()], bump)]
+    pub offer_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry of the vault's owner, closed with the vault; required when the
+    /// mint has a transfer hook
+    #[account(
+        mut,
+        close = maker,
+        seeds = [b"allowlist", offer.offered_mint.as_ref(), offer.key().as_ref()],
+        bump = vault_allowlist_entry.bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        mut,
+        constraint = maker_token_account.mint == mint.key(),
+        constraint = maker_token_account.owner == maker.key()
+    )]
+    pub maker_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), maker.key().as_ref()],
+        bump = maker_allowlist_entry.bump
+    )]
+    pub maker_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", token_config.mint.as_ref(), &token_config.current_snapshot_id.to_le_bytes(), maker.key().as_ref()],
+        bump
+    )]
+    pub maker_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &maker_holder_page.load()?.page_index.to_le_bytes()],
+        bump = maker_holder_page.load()?.bump
+    )]
+    pub maker_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", token_config.mint.as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// maker starts holding
+    #[account(mut)]
+    pub maker_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeWrapper<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [b"wrapper", mint.key().as_ref()],
+        bump
+    )]
+    pub wrapper: Box<Account<'info, WrapperConfig>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = mint.decimals,
+        mint::authority = wrapper,
+        seeds = [b"wrapped_mint", mint.key().as_ref()],
+        bump
+    )]
+    pub wrapped_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = wrapper,
+        seeds = [b"wrapper_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub wrapper_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), wrapper.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WrapShares<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"wrapper", mint.key().as_ref()],
+        bump = wrapper.bump
+    )]
+    pub wrapper: Box<Account<'info, WrapperConfig>>,
+    
+    #[account(mut, address = wrapper.wrapped_mint)]
+    pub wrapped_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut, seeds = [b"wrapper_vault", mint.key().as_ref()], bump)]
+    pub wrapper_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = holder_wrapped_account.mint == wrapped_mint.key(),
+        constraint = holder_wrapped_account.owner == holder.key()
+    )]
+    pub holder_wrapped_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_holder_page.load()?.bump
+    )]
+    pub holder_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnwrapShares<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"wrapper", mint.key().as_ref()],
+        bump = wrapper.bump
+    )]
+    pub wrapper: Box<Account<'info, WrapperConfig>>,
+    
+    #[account(mut, address = wrapper.wrapped_mint)]
+    pub wrapped_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(mut, seeds = [b"wrapper_vault", mint.key().as_ref()], bump)]
+    pub wrapper_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = holder_wrapped_account.mint == wrapped_mint.key(),
+        constraint = holder_wrapped_account.owner == holder.key()
+    )]
+    pub holder_wrapped_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Holder's flag account in the registered sanctions oracle, validated in the handler
+    pub holder_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_holder_page.load()?.bump
+    )]
+    pub holder_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileWrapper<'info> {
+    #[account(
+        seeds = [b"wrapper", wrapper.token_mint.as_ref()],
+        bump = wrapper.bump
+    )]
+    pub wrapper: Account<'info, WrapperConfig>,
+    
+    #[account(address = wrapper.wrapped_mint)]
+    pub wrapped_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(seeds = [b"wrapper_vault", wrapper.token_mint.as_ref()], bump)]
+    pub wrapper_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", wrapper.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CreateStream<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// CHECK: Wallet the shares stream to
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key()
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), sender.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), sender_allowlist_entry.country_code.as_ref()],
+        bump = sender_country_rule.bump
+    )]
+    pub sender_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Sender's flag account in the registered sanctions oracle, validated in the handler
+    pub sender_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"destination_registry", mint.key().as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 32 + 8 + 8 + 8 + 8 + 9 + 8 + 1,
+        seeds = [b"stream", mint.key().as_ref(), &token_config.next_stream_id.to_le_bytes()],
+        bump
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        token::mint = mint,
+        token::authority = stream,
+        seeds = [b"stream_vault", stream.key().as_ref()],
+        bump
+    )]
+    pub stream_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = sender,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), stream.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), sender.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &sender_holder_number.number.to_le_bytes()],
+        bump = sender_holder_number.bump
+    )]
+    pub sender_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawStreamed<'info> {
+    #[account(mut, address = stream.recipient)]
+    pub recipient: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(address = stream.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream", mint.key().as_ref(), &stream.stream_id.to_le_bytes()],
+        bump = stream.bump
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+    
+    #[account(mut, seeds = [b"stream_vault", stream.key().as_ref()], bump)]
+    pub stream_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == recipient.key()
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStreamPaused<'info> {
+    #[account(address = stream.sender @ ErrorCode::UnauthorizedStreamAction)]
+    pub sender: Signer<'info>,
+    
+    #[account(mut)]
+    pub stream: Account<'info, PaymentStream>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", stream.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelStream<'info> {
+    #[account(mut, address = stream.sender @ ErrorCode::UnauthorizedStreamAction)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(address = stream.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"stream", mint.key().as_ref(), &stream.stream_id.to_le_bytes()],
+        bump = stream.bump,
+        close = sender
+    )]
+    pub stream: Box<Account<'info, PaymentStream>>,
+    
+    #[account(mut, seeds = [b"stream_vault", stream.key().as_ref()], bump)]
+    pub stream_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry of the vault's owner, closed with the vault; required when the
+    /// mint has a transfer hook
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"allowlist", stream.token_mint.as_ref(), stream.key().as_ref()],
+        bump = vault_allowlist_entry.bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key()
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), sender.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == stream.recipient
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), stream.recipient.as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), sender.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), stream.recipient.as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// sender starts holding
+    #[account(mut)]
+    pub sender_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateAirdropCampaign<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"airdrop", mint.key().as_ref(), &token_config.next_airdrop_id.to_le_bytes()],
+        bump
+    )]
+    pub campaign: Box<Account<'info, AirdropCampaign>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = campaign,
+        seeds = [b"airdrop_vault", campaign.key().as_ref()],
+        bump
+    )]
+    pub campaign_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), campaign.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(address = campaign.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"airdrop", mint.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump
+    )]
+    pub campaign: Box<Account<'info, AirdropCampaign>>,
+    
+    #[account(mut, seeds = [b"airdrop_vault", campaign.key().as_ref()], bump)]
+    pub campaign_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        init,
+        payer = wallet,
+        space = 8 + 32 + 32 + 8 + 8 + 1,
+        seeds = [b"airdrop_claim", campaign.key().as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub airdrop_claim: Box<Account<'info, AirdropClaim>>,
+    
+    #[account(
+        mut,
+        constraint = wallet_token_account.mint == mint.key(),
+        constraint = wallet_token_account.owner == wallet.key()
+    )]
+    pub wallet_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), wallet.key().as_ref()],
+        bump = wallet_allowlist_entry.bump
+    )]
+    pub wallet_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Wallet's flag account in the registered sanctions oracle, validated in the handler
+    pub wallet_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = wallet,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), wallet.key().as_ref()],
+        bump
+    )]
+    pub wallet_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &wallet_holder_page.load()?.page_index.to_le_bytes()],
+        bump = wallet_holder_page.load()?.bump
+    )]
+    pub wallet_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// wallet starts holding
+    #[account(mut)]
+    pub wallet_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepAirdrop<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(address = campaign.token_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"airdrop", mint.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, AirdropCampaign>,
+    
+    #[account(mut, seeds = [b"airdrop_vault", campaign.key().as_ref()], bump)]
+    pub campaign_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == mint.key(),
+        constraint = treasury_token_account.owner == treasury.key()
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BatchMint<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct BatchTransfer<'info> {
+    /// Owner or delegate of every leg's source account
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateEscrow<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    
+    /// CHECK: Wallet the shares are released to
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key()
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), sender.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), sender_allowlist_entry.country_code.as_ref()],
+        bump = sender_country_rule.bump
+    )]
+    pub sender_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Sender's flag account in the registered sanctions oracle, validated in the handler
+    pub sender_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"destination_registry", mint.key().as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Option<Box<Account<'info, DestinationRegistry>>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 32 + 8 + ReleaseCondition::MAX_SIZE + 8 + 1,
+        seeds = [b"escrow", mint.key().as_ref(), &token_config.next_escrow_id.to_le_bytes()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(
+        init,
+        payer = sender,
+        token::mint = mint,
+        token::authority = escrow,
+        seeds = [b"escrow_vault", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = sender,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), escrow.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), sender.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &sender_holder_number.number.to_le_bytes()],
+        bump = sender_holder_number.bump
+    )]
+    pub sender_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    /// Cranker, paying for any snapshot checkpoint the recipient needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Escrow sender, receiving the closed accounts' rent
+    #[account(mut, address = escrow.sender)]
+    pub sender: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(address = escrow.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", mint.key().as_ref(), &escrow.escrow_id.to_le_bytes()],
+        bump = escrow.bump,
+        close = sender
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry of the vault's owner, closed with the vault; required when the
+    /// mint has a transfer hook
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"allowlist", escrow.token_mint.as_ref(), escrow.key().as_ref()],
+        bump = vault_allowlist_entry.bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == escrow.recipient
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), escrow.recipient.as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    /// Counter-signer named by a `CounterSignature` condition
+    pub counter_signer: Option<Signer<'info>>,
+    
+    /// CHECK: Price feed named by an `OraclePrice` condition, validated in the handler
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), escrow.recipient.as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    #[account(mut, address = escrow.sender @ ErrorCode::UnauthorizedEscrowAction)]
+    pub sender: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(address = escrow.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow", mint.key().as_ref(), &escrow.escrow_id.to_le_bytes()],
+        bump = escrow.bump,
+        close = sender
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+    
+    #[account(mut, seeds = [b"escrow_vault", escrow.key().as_ref()], bump)]
+    pub escrow_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Allowlist entry of the vault's owner, closed with the vault; required when the
+    /// mint has a transfer hook
+    #[account(
+        mut,
+        close = sender,
+        seeds = [b"allowlist", escrow.token_mint.as_ref(), escrow.key().as_ref()],
+        bump = vault_allowlist_entry.bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        mut,
+        constraint = sender_token_account.mint == mint.key(),
+        constraint = sender_token_account.owner == sender.key()
+    )]
+    pub sender_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), sender.key().as_ref()],
+        bump = sender_allowlist_entry.bump
+    )]
+    pub sender_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Price feed named by an `OraclePrice` condition, validated in the handler
+    pub price_feed: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), sender.key().as_ref()],
+        bump
+    )]
+    pub sender_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &sender_holder_page.load()?.page_index.to_le_bytes()],
+        bump = sender_holder_page.load()?.bump
+    )]
+    pub sender_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// sender starts holding
+    #[account(mut)]
+    pub sender_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(split_ratio: u64, new_symbol: String, new_name: String)]
+pub struct ExecuteStockSplit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", old_token_config.mint.as_ref()],
+        bump = old_token_config.bump,
+        constraint = old_token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub old_token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", new_mint.key().as_ref()], bump)]
+    pub new_mint_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: PDA holding the SPL freeze authority
+    #[account(seeds = [b"freeze_authority", new_mint.key().as_ref()], bump)]
+    pub new_mint_freeze_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = old_token_config.decimals,
+        mint::authority = new_mint_authority,
+        mint::freeze_authority = new_mint_freeze_authority,
+    )]
+    pub new_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = TokenConfig::space(&new_symbol, &new_name, &old_token_config.uri),
+        seeds = [b"token_config", new_mint.key().as_ref()],
+        bump
+    )]
+    pub new_token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 8 + 32 + 1,
+        seeds = [b"split_config", old_token_config.mint.as_ref(), new_mint.key().as_ref()],
+        bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_registry"],
+        bump = token_registry.bump
+    )]
+    pub token_registry: Account<'info, TokenRegistry>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 4 + (4 + (32 + 32 + 8) * REGISTRY_PAGE_CAPACITY) + 1,
+        seeds = [b"registry_page".as_ref(), &token_registry.current_page_index().to_le_bytes()],
+        bump
+    )]
+    pub registry_page: Box<Account<'info, RegistryPage>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateHolderSplit<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Holder wallet
+    pub holder: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"split_config", split_config.original_mint.as_ref(), split_config.new_mint.as_ref()],
+        bump = split_config.bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+    
+    #[account(mut)]
+    pub new_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", new_mint.key().as_ref()], bump)]
+    pub new_mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", new_mint.key().as_ref()],
+        bump = new_token_config.bump
+    )]
+    pub new_token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        mut,
+        constraint = holder_new_token_account.mint == new_mint.key(),
+        constraint = holder_new_token_account.owner == holder.key()
+    )]
+    pub holder_new_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"receipt", split_config.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, ActionReceipt>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_symbol: String, new_name: String, new_uri: String)]
+pub struct UpdateTokenMetadata<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority,
+        realloc = TokenConfig::space(&new_symbol, &new_name, &new_uri),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+
+    /// CHECK: Metaplex metadata PDA; validated by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), token_config.mint.as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct PauseToken<'info> {
+    /// Authority or guardian
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key()
+            || (token_config.guardian != Pubkey::default() && token_config.guardian == authority.key())
+            @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCircuitBreaker<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + (4 + 32 * MAX_BREAKER_SIGNERS) + 1 + 2 + 9 + 4 + 1,
+        seeds = [b"circuit_breaker", token_config.mint.as_ref()],
+        bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckInvariants<'info> {
+    pub caller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"circuit_breaker", mint.key().as_ref()],
+        bump = circuit_breaker.bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+}
+
+#[derive(Accounts)]
+pub struct ResetCircuitBreaker<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"circuit_breaker", mint.key().as_ref()],
+        bump = circuit_breaker.bump
+    )]
+    pub circuit_breaker: Account<'info, CircuitBreaker>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDestinationRegistry<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + (4 + 32 * MAX_APPROVED_DESTINATIONS) + 1,
+        seeds = [b"destination_registry", token_config.mint.as_ref()],
+        bump
+    )]
+    pub destination_registry: Account<'info, DestinationRegistry>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateDestinationRegistry<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"destination_registry", token_config.mint.as_ref()],
+        bump = destination_registry.bump
+    )]
+    pub destination_registry: Account<'info, DestinationRegistry>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeeTreasury<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = treasury,
+        seeds = [b"fee_treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub fee_treasury: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), treasury.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokenSettings<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// Timelock PDA, signing when the proposal executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// Outgoing authority
+    pub authority: Signer<'info>,
+    
+    pub pending_authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority,
+        constraint = token_config.pending_authority != Pubkey::default() @ ErrorCode::NoPendingAuthority,
+        constraint = token_config.pending_authority == pending_authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet receiving withdrawn fees
+    pub destination: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"fee_treasury", mint.key().as_ref()],
+        bump
+    )]
+    pub fee_treasury: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == mint.key(),
+        constraint = destination_token_account.owner == destination.key()
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), destination.key().as_ref()],
+        bump = destination_allowlist_entry.bump
+    )]
+    pub destination_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"country_rule", token_config.mint.as_ref(), destination_allowlist_entry.country_code.as_ref()],
+        bump = destination_country_rule.bump
+    )]
+    pub destination_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Destination's flag account in the registered sanctions oracle, validated in the handler
+    pub destination_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), destination.key().as_ref()],
+        bump
+    )]
+    pub destination_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &destination_holder_page.load()?.page_index.to_le_bytes()],
+        bump = destination_holder_page.load()?.bump
+    )]
+    pub destination_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// destination starts holding
+    #[account(mut)]
+    pub destination_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OnboardInvestor<'info> {
+    #[account(mut)]
+    pub sponsor: Signer<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Investor wallet being onboarded
+    pub investor: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = sponsor,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), investor.key().as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        associated_token::mint = mint,
+        associated_token::authority = investor,
+    )]
+    pub investor_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = sponsor,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), investor.key().as_ref()],
+        bump
+    )]
+    pub snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// investor starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAllowlistEntry<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTransferJournal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = TransferJournal::SPACE,
+        seeds = [b"transfer_journal", token_config.mint.as_ref()],
+        bump
+    )]
+    pub transfer_journal: AccountLoader<'info, TransferJournal>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey)]
+pub struct AddTrustedCaller<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 1,
+        seeds = [b"trusted_caller", token_config.mint.as_ref(), program_id.as_ref()],
+        bump
+    )]
+    pub trusted_caller: Account<'info, TrustedCaller>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveTrustedCaller<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"trusted_caller", token_config.mint.as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Account<'info, TrustedCaller>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBlackoutWindow<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 33 + 32 + 1,
+        seeds = [b"blackout", token_config.mint.as_ref(), &token_config.next_blackout_id.to_le_bytes()],
+        bump
+    )]
+    pub blackout_window: Account<'info, BlackoutWindow>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelBlackoutWindow<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"blackout", token_config.mint.as_ref(), &blackout_window.window_id.to_le_bytes()],
+        bump = blackout_window.bump
+    )]
+    pub blackout_window: Account<'info, BlackoutWindow>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimStaleAccounts<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// Closes the vaults of finished vesting schedules and airdrop campaigns that are
+    /// still open
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTokenConfig<'info> {
+    /// Anyone; pays any rent the larger layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Written under an older layout, so decoded and rewritten by the handler
+    #[account(mut, seeds = [b"token_config", mint.key().as_ref()], bump)]
+    pub token_config: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResizeTokenConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority,
+        realloc = TokenConfig::space(&token_config.symbol, &token_config.name, &token_config.uri),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAllowlistEntry<'info> {
+    /// Anyone; pays any rent the larger layout needs
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Wallet the entry belongs to
+    pub wallet: AccountInfo<'info>,
+    
+    /// CHECK: Written under an older layout, so decoded and rewritten by the handler
+    #[account(mut, seeds = [b"allowlist", mint.key().as_ref(), wallet.key().as_ref()], bump)]
+    pub allowlist_entry: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+#[instruction(period_start: i64)]
+pub struct PostDigest<'info> {
+    #[account(mut)]
+    pub compliance_officer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.compliance_officer == compliance_officer.key() @ ErrorCode::UnauthorizedComplianceOfficer
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = compliance_officer,
+        space = 8 + 32 + 8 + 8 + 32 + 4 + 32 + 8 + 1,
+        seeds = [b"digest", token_config.mint.as_ref(), &period_start.to_le_bytes()],
+        bump
+    )]
+    pub compliance_digest: Account<'info, ComplianceDigest>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    /// Upgrade authority of this deployment
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::SPACE,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::UnauthorizedProgramAdmin)]
+    pub program: Program<'info, crate::program::GatedToken>,
+    
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::UnauthorizedProgramAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    pub admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == admin.key() @ ErrorCode::UnauthorizedProgramAdmin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct TransferProgramAdmin<'info> {
+    pub admin: Signer<'info>,
+    
+    pub new_admin: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == admin.key() @ ErrorCode::UnauthorizedProgramAdmin
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTokenRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 8 + 1,
+        seeds = [b"token_registry"],
+        bump
+    )]
+    pub token_registry: Account<'info, TokenRegistry>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateIssuer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + (4 + MAX_ISSUER_NAME_LEN) + (4 + 32 * MAX_ISSUER_TOKENS) + 1,
+        seeds = [b"issuer", authority.key().as_ref()],
+        bump
+    )]
+    pub issuer: Account<'info, Issuer>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterIssuerToken<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"issuer", authority.key().as_ref()],
+        bump = issuer.bump
+    )]
+    pub issuer: Account<'info, Issuer>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority,
+        constraint = token_config.issuer == Pubkey::default() @ ErrorCode::TokenAlreadyRegistered
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateIssuer<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"issuer", authority.key().as_ref()],
+        bump = issuer.bump
+    )]
+    pub issuer: Account<'info, Issuer>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenAccountFrozen<'info> {
+    pub compliance_officer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.compliance_officer == compliance_officer.key() @ ErrorCode::UnauthorizedComplianceOfficer
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        address = token_config.mint,
+        constraint = Option::<Pubkey>::from(mint.freeze_authority) == Some(freeze_authority.key()) @ ErrorCode::FreezingDisabled
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL freeze authority
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+    
+    #[account(mut, token::mint = mint)]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ForceTransfer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet the shares are taken from
+    pub holder: AccountInfo<'info>,
+    
+    /// CHECK: Recipient wallet
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority, the mint's permanent delegate
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = from_token_account.mint == mint.key(),
+        constraint = from_token_account.owner == holder.key()
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = to_token_account.mint == mint.key(),
+        constraint = to_token_account.owner == recipient.key()
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", token_config.mint.as_ref(), recipient_allowlist_entry.country_code.as_ref()],
+        bump = recipient_country_rule.bump
+    )]
+    pub recipient_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
+        bump
+    )]
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the holder's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
+    
+    /// Timelock PDA, signing when the transfer executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DisableFreezing<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        address = token_config.mint,
+        constraint = Option::<Pubkey>::from(mint.freeze_authority) == Some(freeze_authority.key()) @ ErrorCode::FreezingDisabled
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL freeze authority
+    #[account(seeds = [b"freeze_authority", mint.key().as_ref()], bump)]
+    pub freeze_authority: UncheckedAccount<'info>,
+    
+    /// Timelock PDA, signing when the renouncement executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTokenConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority,
+        close = authority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut, address = token_config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", token_config.mint.as_ref()], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    /// Treasury holding any shares left over after a buyout or redemption
+    #[account(mut, token::mint = mint)]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Issuer the token is registered under, if any
+    #[account(mut, address = token_config.issuer @ ErrorCode::InvalidIssuerAccounts)]
+    pub issuer: Option<Account<'info, Issuer>>,
+    
+    /// Timelock PDA, signing when the closure executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(country_code: [u8; 2])]
+pub struct SetCountryRule<'info> {
+    #[account(mut)]
+    pub compliance_officer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.compliance_officer == compliance_officer.key() @ ErrorCode::UnauthorizedComplianceOfficer
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = compliance_officer,
+        space = 8 + 32 + 2 + 1 + 4 + 4 + 1,
+        seeds = [b"country_rule", token_config.mint.as_ref(), country_code.as_ref()],
+        bump
+    )]
+    pub country_rule: Account<'info, CountryRule>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(country_code: [u8; 2])]
+pub struct AssignWalletCountry<'info> {
+    pub compliance_officer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.compliance_officer == compliance_officer.key() @ ErrorCode::UnauthorizedComplianceOfficer
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"country_rule", token_config.mint.as_ref(), country_code.as_ref()],
+        bump = country_rule.bump
+    )]
+    pub country_rule: Account<'info, CountryRule>,
+    
+    #[account(
+        mut,
+        seeds = [b"country_rule", token_config.mint.as_ref(), allowlist_entry.country_code.as_ref()],
+        bump = previous_country_rule.bump
+    )]
+    pub previous_country_rule: Option<Account<'info, CountryRule>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDistribution<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 8 + 8,
+        seeds = [b"distribution", token_config.mint.as_ref(), &token_config.next_distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDividend<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution", distribution.token_mint.as_ref(), &distribution.distribution_id.to_le_bytes()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1,
+        seeds = [b"dividend_claim", distribution.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub dividend_claim: Account<'info, DividendClaim>,
+    
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", distribution.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateTokenDistribution<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 8 + 8,
+        seeds = [b"distribution", token_config.mint.as_ref(), &token_config.next_distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payout_mint,
+        token::authority = distribution,
+        seeds = [b"distribution_vault", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = funding_token_account.mint == payout_mint.key(),
+        constraint = funding_token_account.owner == authority.key()
+    )]
+    pub funding_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateDistributionSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1,
+        seeds = [b"distribution_schedule", token_config.mint.as_ref()],
+        bump
+    )]
+    pub schedule: Account<'info, DistributionSchedule>,
+    
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payout_mint,
+        token::authority = schedule,
+        seeds = [b"schedule_vault", schedule.key().as_ref()],
+        bump
+    )]
+    pub schedule_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetDistributionScheduleActive<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_schedule", token_config.mint.as_ref()],
+        bump = schedule.bump
+    )]
+    pub schedule: Account<'info, DistributionSchedule>,
+}
+
+#[derive(Accounts)]
+pub struct CrankDistribution<'info> {
+    /// Anyone; pays for the new snapshot, distribution and vault
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", schedule.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_schedule", schedule.token_mint.as_ref()],
+        bump = schedule.bump
+    )]
+    pub schedule: Box<Account<'info, DistributionSchedule>>,
+    
+    #[account(mut, seeds = [b"schedule_vault", schedule.key().as_ref()], bump)]
+    pub schedule_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(address = schedule.payout_mint)]
+    pub payout_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(address = schedule.token_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"snapshot", schedule.token_mint.as_ref(), &(token_config.current_snapshot_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub snapshot: Box<Account<'info, Snapshot>>,
+    
+    #[account(
+        init,
+        payer = cranker,
+        space = 8 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 8 + 8,
+        seeds = [b"distribution", schedule.token_mint.as_ref(), &token_config.next_distribution_id.to_le_bytes()],
+        bump
+    )]
+    pub distribution: Box<Account<'info, Distribution>>,
+    
+    #[account(
+        init,
+        payer = cranker,
+        token::mint = payout_mint,
+        token::authority = distribution,
+        seeds = [b"distribution_vault", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimScheduledDividend<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution", distribution.token_mint.as_ref(), &distribution.distribution_id.to_le_bytes()],
+        bump = distribution.bump,
+        constraint = distribution.snapshot_id > 0 @ ErrorCode::InvalidDistribution
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        seeds = [b"snapshot", distribution.token_mint.as_ref(), &distribution.snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(address = distribution.payout_mint @ ErrorCode::InvalidDistribution)]
+    pub payout_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_vault", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_payout_account.mint == distribution.payout_mint @ ErrorCode::InvalidDistribution,
+        constraint = holder_payout_account.owner == holder.key()
+    )]
+    pub holder_payout_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        constraint = share_token_account.mint == distribution.token_mint,
+        constraint = share_token_account.owner == holder.key()
+    )]
+    pub share_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist", distribution.token_mint.as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// Checkpoint holding the holder's record-date balance, if they moved since
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1,
+        seeds = [b"dividend_claim", distribution.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub dividend_claim: Account<'info, DividendClaim>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", distribution.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimTokenDividend<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution", distribution.token_mint.as_ref(), &distribution.distribution_id.to_le_bytes()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(address = distribution.payout_mint @ ErrorCode::InvalidDistribution)]
+    pub payout_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_vault", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == distribution.payout_mint @ ErrorCode::InvalidDistribution,
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", distribution.token_mint.as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", distribution.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        init,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 1,
+        seeds = [b"dividend_claim", distribution.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub dividend_claim: Account<'info, DividendClaim>,
+    
+    // Accounts below are only used when the holder has elected DRIP
+    #[account(mut, address = distribution.token_mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = share_token_account.mint == distribution.token_mint,
+        constraint = share_token_account.owner == holder.key()
+    )]
+    pub share_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", distribution.token_mint.as_ref()], bump)]
+    pub mint_authority: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", distribution.token_mint.as_ref()], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = treasury_payment_account.mint == distribution.payout_mint @ ErrorCode::InvalidDistribution
+    )]
+    pub treasury_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        seeds = [b"share_class", distribution.token_mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", distribution.token_mint.as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", distribution.token_mint.as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", distribution.token_mint.as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SweepDistribution<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"distribution", token_config.mint.as_ref(), &distribution.distribution_id.to_le_bytes()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+    
+    #[account(address = distribution.payout_mint @ ErrorCode::InvalidDistribution)]
+    pub payout_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution_vault", distribution.key().as_ref()],
+        bump
+    )]
+    pub distribution_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination_token_account.mint == distribution.payout_mint @ ErrorCode::InvalidDistribution
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSnapshot<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(address = token_config.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"snapshot", token_config.mint.as_ref(), &(token_config.current_snapshot_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteSplit<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+#[derive(Accounts)]
+pub struct SetShareClass<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + (4 + 32) + 1 + 2 + 1 + 1 + 4 + 1 + 2 + 4 + 8 + 1,
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVestingSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet the tokens vest to
+    pub beneficiary: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1,
+        seeds = [b"vesting", mint.key().as_ref(), &token_config.next_vesting_id.to_le_bytes()],
+        bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = vesting_schedule,
+        seeds = [b"vesting_vault", vesting_schedule.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), vesting_schedule.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    /// CHECK: Instructions sysvar, read to find the calling program when CPI callers
+    /// are restricted
+    #[account(address = solana_sdk_ids::sysvar::instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"trusted_caller", mint.key().as_ref(), trusted_caller.program_id.as_ref()],
+        bump = trusted_caller.bump
+    )]
+    pub trusted_caller: Option<Box<Account<'info, TrustedCaller>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", mint.key().as_ref(), &vesting_schedule.vesting_id.to_le_bytes()],
+        bump = vesting_schedule.bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting_vault", vesting_schedule.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = beneficiary_token_account.mint == mint.key(),
+        constraint = beneficiary_token_account.owner == vesting_schedule.beneficiary
+    )]
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), vesting_schedule.beneficiary.as_ref()],
+        bump = beneficiary_allowlist_entry.bump
+    )]
+    pub beneficiary_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), beneficiary_allowlist_entry.country_code.as_ref()],
+        bump = beneficiary_country_rule.bump
+    )]
+    pub beneficiary_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Beneficiary's flag account in the registered sanctions oracle, validated in the handler
+    pub beneficiary_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), vesting_schedule.beneficiary.as_ref()],
+        bump
+    )]
+    pub beneficiary_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &beneficiary_holder_page.load()?.page_index.to_le_bytes()],
+        bump = beneficiary_holder_page.load()?.bump
+    )]
+    pub beneficiary_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// beneficiary starts holding
+    #[account(mut)]
+    pub beneficiary_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeVesting<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", mint.key().as_ref(), &vesting_schedule.vesting_id.to_le_bytes()],
+        bump = vesting_schedule.bump
+    )]
+    pub vesting_schedule: Account<'info, VestingSchedule>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting_vault", vesting_schedule.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == mint.key(),
+        constraint = treasury_token_account.owner == treasury.key()
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOptionGrant<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Employee receiving the grant
+    pub employee: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 32 + 24 + 8 + 1,
+        seeds = [b"option_grant", token_config.mint.as_ref(), &token_config.next_option_grant_id.to_le_bytes()],
+        bump
+    )]
+    pub option_grant: Account<'info, OptionGrant>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExerciseOptions<'info> {
+    #[account(mut)]
+    pub employee: Signer<'info>,
+    
+    /// Mint authority co-signs the issuance
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"option_grant", mint.key().as_ref(), &option_grant.grant_id.to_le_bytes()],
+        bump = option_grant.bump,
+        constraint = option_grant.employee == employee.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub option_grant: Account<'info, OptionGrant>,
+    
+    #[account(address = option_grant.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = employee_token_account.mint == mint.key(),
+        constraint = employee_token_account.owner == employee.key()
+    )]
+    pub employee_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), employee.key().as_ref()],
+        bump = employee_allowlist_entry.bump
+    )]
+    pub employee_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        constraint = employee_payment_account.mint == option_grant.payment_mint,
+        constraint = employee_payment_account.owner == employee.key()
+    )]
+    pub employee_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_payment_account.mint == option_grant.payment_mint,
+        constraint = treasury_payment_account.owner == treasury.key()
+    )]
+    pub treasury_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = employee,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), employee.key().as_ref()],
+        bump
+    )]
+    pub employee_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &employee_holder_page.load()?.page_index.to_le_bytes()],
+        bump = employee_holder_page.load()?.bump
+    )]
+    pub employee_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// employee starts holding
+    #[account(mut)]
+    pub employee_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExerciseOptionsAtMarket<'info> {
+    #[account(mut)]
+    pub employee: Signer<'info>,
+    
+    /// Mint authority co-signs the issuance
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"option_grant", mint.key().as_ref(), &option_grant.grant_id.to_le_bytes()],
+        bump = option_grant.bump,
+        constraint = option_grant.employee == employee.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub option_grant: Account<'info, OptionGrant>,
+    
+    /// Mint the strike is denominated in, for its decimals
+    #[account(address = option_grant.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    /// CHECK: The token's registered price feed, validated in the handler
+    pub price_feed: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = employee_token_account.mint == mint.key(),
+        constraint = employee_token_account.owner == employee.key()
+    )]
+    pub employee_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), employee.key().as_ref()],
+        bump = employee_allowlist_entry.bump
+    )]
+    pub employee_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = employee,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), employee.key().as_ref()],
+        bump
+    )]
+    pub employee_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &employee_holder_page.load()?.page_index.to_le_bytes()],
+        bump = employee_holder_page.load()?.bump
+    )]
+    pub employee_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// employee starts holding
+    #[account(mut)]
+    pub employee_holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOptionGrant<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"option_grant", token_config.mint.as_ref(), &option_grant.grant_id.to_le_bytes()],
+        bump = option_grant.bump
+    )]
+    pub option_grant: Account<'info, OptionGrant>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRedemption<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 1 + 8 + 8 + 1,
+        seeds = [b"redemption", mint.key().as_ref()],
+        bump
+    )]
+    pub redemption_config: Account<'info, RedemptionConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = redemption_config,
+        seeds = [b"redemption_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub redemption_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRedemptionVault<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        seeds = [b"redemption", token_config.mint.as_ref()],
+        bump = redemption_config.bump
+    )]
+    pub redemption_config: Account<'info, RedemptionConfig>,
+    
+    #[account(address = redemption_config.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption_vault", token_config.mint.as_ref()],
+        bump
+    )]
+    pub redemption_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut, constraint = funding_token_account.owner == authority.key())]
+    pub funding_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRedemptionWindow<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption", token_config.mint.as_ref()],
+        bump = redemption_config.bump
+    )]
+    pub redemption_config: Account<'info, RedemptionConfig>,
+}
+
+#[derive(Accounts)]
+pub struct BurnTokens<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption", mint.key().as_ref()],
+        bump = redemption_config.bump
+    )]
+    pub redemption_config: Account<'info, RedemptionConfig>,
+    
+    #[account(address = redemption_config.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub redemption_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == redemption_config.payment_mint,
+        constraint = holder_payment_account.owner == holder.key()
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceOracle<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// Timelock PDA, signing when the change executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemAtNav<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: The token's registered price feed, validated in the handler
+    pub price_feed: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption", mint.key().as_ref()],
+        bump = redemption_config.bump
+    )]
+    pub redemption_config: Account<'info, RedemptionConfig>,
+    
+    #[account(address = redemption_config.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"redemption_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub redemption_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == redemption_config.payment_mint,
+        constraint = holder_payment_account.owner == holder.key()
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenTenderOffer<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1,
+        seeds = [b"tender_offer", mint.key().as_ref(), &token_config.next_tender_offer_id.to_le_bytes()],
+        bump
+    )]
+    pub tender_offer: Account<'info, TenderOffer>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = mint,
+        token::authority = tender_offer,
+        seeds = [b"tender_escrow", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Allowlist entry letting the transfer hook move shares through the vault, required
+    /// when the mint has one
+    #[account(
+        init,
+        payer = authority,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", mint.key().as_ref(), tender_offer.key().as_ref()],
+        bump
+    )]
+    pub vault_allowlist_entry: Option<Box<Account<'info, AllowlistEntry>>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = tender_offer,
+        seeds = [b"tender_payment_vault", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut, constraint = funding_token_account.owner == authority.key())]
+    pub funding_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TenderShares<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_offer", mint.key().as_ref(), &tender_offer.offer_id.to_le_bytes()],
+        bump = tender_offer.bump
+    )]
+    pub tender_offer: Account<'info, TenderOffer>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_escrow", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [b"tender_position", tender_offer.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub tender_position: Account<'info, TenderPosition>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTenderOffer<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_offer", mint.key().as_ref(), &tender_offer.offer_id.to_le_bytes()],
+        bump = tender_offer.bump
+    )]
+    pub tender_offer: Account<'info, TenderOffer>,
+    
+    #[account(address = tender_offer.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_payment_vault", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut, constraint = refund_token_account.owner == authority.key())]
+    pub refund_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SettleTender<'info> {
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    
+    /// CHECK: Holder whose position is settled; receives the position's rent
+    #[account(mut)]
+    pub holder: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"tender_offer", mint.key().as_ref(), &tender_offer.offer_id.to_le_bytes()],
+        bump = tender_offer.bump
+    )]
+    pub tender_offer: Account<'info, TenderOffer>,
+    
+    #[account(address = tender_offer.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        close = holder,
+        seeds = [b"tender_position", tender_offer.key().as_ref(), holder.key().as_ref()],
+        bump = tender_position.bump
+    )]
+    pub tender_position: Account<'info, TenderPosition>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_escrow", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"tender_payment_vault", tender_offer.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == tender_offer.payment_mint,
+        constraint = holder_payment_account.owner == holder.key()
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == mint.key(),
+        constraint = treasury_token_account.owner == treasury.key()
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IssueConvertibleNote<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Investor holding the note
+    pub noteholder: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 2 + 8 + 8 + 8 + 1,
+        seeds = [b"convertible_note", token_config.mint.as_ref(), &token_config.next_note_id.to_le_bytes()],
+        bump
+    )]
+    pub convertible_note: Account<'info, ConvertibleNote>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertNote<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"convertible_note", mint.key().as_ref(), &convertible_note.note_id.to_le_bytes()],
+        bump = convertible_note.bump
+    )]
+    pub convertible_note: Account<'info, ConvertibleNote>,
+    
+    #[account(
+        mut,
+        constraint = noteholder_token_account.mint == mint.key(),
+        constraint = noteholder_token_account.owner == convertible_note.noteholder
+    )]
+    pub noteholder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), convertible_note.noteholder.as_ref()],
+        bump = noteholder_allowlist_entry.bump
+    )]
+    pub noteholder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), convertible_note.noteholder.as_ref()],
+        bump
+    )]
+    pub noteholder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &noteholder_page.load()?.page_index.to_le_bytes()],
+        bump = noteholder_page.load()?.bump
+    )]
+    pub noteholder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// noteholder starts holding
+    #[account(mut)]
+    pub noteholder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IssueWarrant<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Warrant holder
+    pub holder: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 8 + 32 + 8 + 1,
+        seeds = [b"warrant", token_config.mint.as_ref(), &token_config.next_warrant_id.to_le_bytes()],
+        bump
+    )]
+    pub warrant: Account<'info, Warrant>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExerciseWarrant<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    /// Mint authority co-signs the issuance
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"warrant", mint.key().as_ref(), &warrant.warrant_id.to_le_bytes()],
+        bump = warrant.bump,
+        constraint = warrant.holder == holder.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub warrant: Account<'info, Warrant>,
+    
+    #[account(address = warrant.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == warrant.payment_mint,
+        constraint = holder_payment_account.owner == holder.key()
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_payment_account.mint == warrant.payment_mint,
+        constraint = treasury_payment_account.owner == treasury.key()
+    )]
+    pub treasury_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseWarrant<'info> {
+    /// CHECK: Receives the warrant's rent; must be the token authority
+    #[account(mut, address = token_config.authority @ ErrorCode::UnauthorizedAuthority)]
+    pub authority: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"warrant", token_config.mint.as_ref(), &warrant.warrant_id.to_le_bytes()],
+        bump = warrant.bump
+    )]
+    pub warrant: Account<'info, Warrant>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCapTable<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 4 + 1,
+        seeds = [b"cap_table", token_config.mint.as_ref()],
+        bump
+    )]
+    pub cap_table: Account<'info, CapTable>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeHolderIndex<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 1,
+        seeds = [b"holder_index", token_config.mint.as_ref()],
+        bump
+    )]
+    pub holder_index: Account<'info, HolderIndex>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct IndexHolder<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", token_config.mint.as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Account<'info, HolderIndex>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        constraint = token_account.mint == token_config.mint,
+        constraint = token_account.owner == allowlist_entry.wallet
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler
+    #[account(mut)]
+    pub holder_number: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateHolderPage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"cap_table", token_config.mint.as_ref()],
+        bump = cap_table.bump
+    )]
+    pub cap_table: Account<'info, CapTable>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = HolderPage::SPACE,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &cap_table.next_page_index.to_le_bytes()],
+        bump
+    )]
+    pub holder_page: AccountLoader<'info, HolderPage>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SyncHolder<'info> {
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        constraint = token_account.mint == token_config.mint,
+        constraint = token_account.owner == allowlist_entry.wallet
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), allowlist_entry.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: AccountLoader<'info, HolderPage>,
+}
+
+#[derive(Accounts)]
+pub struct CompactHolderPages<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &source_page.load()?.page_index.to_le_bytes()],
+        bump = source_page.load()?.bump
+    )]
+    pub source_page: AccountLoader<'info, HolderPage>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &destination_page.load()?.page_index.to_le_bytes()],
+        bump = destination_page.load()?.bump
+    )]
+    pub destination_page: AccountLoader<'info, HolderPage>,
+}
+
+#[derive(Accounts)]
+pub struct CloseHolderPage<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"holder_page", token_config.mint.as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: AccountLoader<'info, HolderPage>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeSale<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        constraint = seller_token_account.mint == mint.key(),
+        constraint = seller_token_account.owner == seller.key()
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), seller.key().as_ref()],
+        bump = seller_allowlist_entry.bump
+    )]
+    pub seller_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + 32 + 32 + 32 + 8 + 8 + 32 + 8 + 8 + 1 + 1,
+        seeds = [b"sale_proposal", mint.key().as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub sale_proposal: Account<'info, SaleProposal>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSaleProposal<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"sale_proposal", sale_proposal.token_mint.as_ref(), seller.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Account<'info, SaleProposal>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", sale_proposal.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExerciseRofr<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"sale_proposal", token_config.mint.as_ref(), sale_proposal.seller.as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Account<'info, SaleProposal>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRofr<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"sale_proposal", mint.key().as_ref(), seller.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Account<'info, SaleProposal>,
+    
+    #[account(address = sale_proposal.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        constraint = seller_token_account.mint == mint.key(),
+        constraint = seller_token_account.owner == seller.key()
+    )]
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), seller.key().as_ref()],
+        bump = seller_allowlist_entry.bump
+    )]
+    pub seller_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), seller_allowlist_entry.country_code.as_ref()],
+        bump = seller_country_rule.bump
+    )]
+    pub seller_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Seller's flag account in the registered sanctions oracle, validated in the handler
+    pub seller_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = seller_payment_account.mint == sale_proposal.payment_mint,
+        constraint = seller_payment_account.owner == seller.key()
+    )]
+    pub seller_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    /// CHECK: Treasury's flag account in the registered sanctions oracle, validated in the handler
+    pub treasury_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == mint.key(),
+        constraint = treasury_token_account.owner == treasury.key()
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = treasury_payment_account.mint == sale_proposal.payment_mint,
+        constraint = treasury_payment_account.owner == treasury.key()
+    )]
+    pub treasury_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), seller.key().as_ref()],
+        bump
+    )]
+    pub seller_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &seller_holder_page.load()?.page_index.to_le_bytes()],
+        bump = seller_holder_page.load()?.bump
+    )]
+    pub seller_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &seller_holder_number.number.to_le_bytes()],
+        bump = seller_holder_number.bump
+    )]
+    pub seller_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenRightsOffering<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        seeds = [b"snapshot", mint.key().as_ref(), &snapshot.snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 8 + 1 + 8 + 8 + 1,
+        seeds = [b"rights_offering", mint.key().as_ref(), &token_config.next_rights_offering_id.to_le_bytes()],
+        bump
+    )]
+    pub rights_offering: Account<'info, RightsOffering>,
+    
+    #[account(
+        init,
+        payer = authority,
+        token::mint = payment_mint,
+        token::authority = rights_offering,
+        seeds = [b"rights_payment_vault", rights_offering.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeRights<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        seeds = [b"rights_offering", mint.key().as_ref(), &rights_offering.offering_id.to_le_bytes()],
+        bump = rights_offering.bump
+    )]
+    pub rights_offering: Account<'info, RightsOffering>,
+    
+    #[account(address = rights_offering.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        seeds = [b"snapshot", mint.key().as_ref(), &rights_offering.snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"rights_subscription", rights_offering.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub subscription: Account<'info, RightsSubscription>,
+    
+    #[account(
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// Checkpoint holding the holder's record-date balance, if they moved since
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == rights_offering.payment_mint,
+        constraint = holder_payment_account.owner == holder.key()
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"rights_payment_vault", rights_offering.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettleRightsSubscription<'info> {
+    /// Mint authority signs the issuance
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Subscriber; receives the subscription's rent
+    #[account(mut, address = subscription.holder)]
+    pub holder: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"rights_offering", mint.key().as_ref(), &rights_offering.offering_id.to_le_bytes()],
+        bump = rights_offering.bump
+    )]
+    pub rights_offering: Account<'info, RightsOffering>,
+    
+    #[account(address = rights_offering.payment_mint)]
+    pub payment_mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        close = holder,
+        seeds = [b"rights_subscription", rights_offering.key().as_ref(), subscription.holder.as_ref()],
+        bump = subscription.bump
+    )]
+    pub subscription: Account<'info, RightsSubscription>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == subscription.holder
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), subscription.holder.as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"rights_payment_vault", rights_offering.key().as_ref()],
+        bump
+    )]
+    pub payment_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = holder_payment_account.mint == rights_offering.payment_mint,
+        constraint = holder_payment_account.owner == subscription.holder
+    )]
+    pub holder_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_payment_account.mint == rights_offering.payment_mint,
+        constraint = treasury_payment_account.owner == treasury.key()
+    )]
+    pub treasury_payment_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), subscription.holder.as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenAllocation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"snapshot", token_config.mint.as_ref(), &snapshot.snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1,
+        seeds = [b"allocation", token_config.mint.as_ref(), &(token_config.last_allocation_id + 1).to_le_bytes()],
+        bump
+    )]
+    pub allocation: Account<'info, Allocation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankAllocation<'info> {
+    /// Mint authority signs the issuance and funds new checkpoints
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"allocation", mint.key().as_ref(), &allocation.allocation_id.to_le_bytes()],
+        bump = allocation.bump
+    )]
+    pub allocation: Account<'info, Allocation>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAllocation<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"allocation", mint.key().as_ref(), &allocation.allocation_id.to_le_bytes()],
+        bump = allocation.bump
+    )]
+    pub allocation: Account<'info, Allocation>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", mint.key().as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = treasury_token_account.mint == mint.key(),
+        constraint = treasury_token_account.owner == treasury.key()
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetDripElection<'info> {
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), holder.key().as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+}
+
+#[derive(Accounts)]
+pub struct SetDripPrice<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"distribution", token_config.mint.as_ref(), &distribution.distribution_id.to_le_bytes()],
+        bump = distribution.bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+}
+
+#[derive(Accounts)]
+pub struct SetShareClassAccrual<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMintSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// CHECK: PDA that owns issuer treasury token accounts
+    #[account(seeds = [b"treasury", token_config.mint.as_ref()], bump)]
+    pub treasury: UncheckedAccount<'info>,
+    
+    #[account(
+        constraint = treasury_account.mint == token_config.mint,
+        constraint = treasury_account.owner == treasury.key()
+    )]
+    pub treasury_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1,
+        seeds = [b"mint_schedule", token_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_schedule: Account<'info, MintSchedule>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMintSchedule<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint_schedule.token_mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"mint_schedule", mint_schedule.token_mint.as_ref()],
+        bump = mint_schedule.bump
+    )]
+    pub mint_schedule: Account<'info, MintSchedule>,
+}
+
+#[derive(Accounts)]
+pub struct CrankEmission<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(
+        mut,
+        seeds = [b"mint_schedule", mint.key().as_ref()],
+        bump = mint_schedule.bump
+    )]
+    pub mint_schedule: Account<'info, MintSchedule>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(mut, address = mint_schedule.treasury_account)]
+    pub treasury_account: InterfaceAccount<'info, TokenAccount>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct AccrueInterest<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimInterest<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    /// Mint authority co-signs the issuance
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: PDA holding the SPL mint authority
+    #[account(seeds = [b"mint_authority", mint.key().as_ref()], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_page.load()?.bump
+    )]
+    pub holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// holder starts holding
+    #[account(mut)]
+    pub holder_number: Option<UncheckedAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetLiquidationPreference<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Account<'info, ShareClass>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteWaterfall<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub payout_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        mut,
+        constraint = funding_token_account.mint == payout_mint.key(),
+        constraint = funding_token_account.owner == authority.key()
+    )]
+    pub funding_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAuthorityCouncil<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + (4 + 32 * MAX_COUNCIL_MEMBERS) + 1 + 8 + 1 + 1,
+        seeds = [b"authority_council", mint.key().as_ref()],
+        bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    /// CHECK: Data-less PDA that signs as the token authority for executed operations
+    #[account(seeds = [b"council_signer", council.key().as_ref()], bump)]
+    pub council_signer: UncheckedAccount<'info>,
+    
+    /// Timelock PDA, signing when the handover executes from the queue
+    #[account(seeds = [b"timelock", mint.key().as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAuthorityCouncil<'info> {
+    #[account(
+        seeds = [b"council_signer", council.key().as_ref()],
+        bump = council.signer_bump
+    )]
+    pub council_signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"authority_council", council.token_mint.as_ref()],
+        bump = council.bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", council.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// Timelock PDA, signing when the change executes from the queue
+    #[account(seeds = [b"timelock", council.token_mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(data: Vec<u8>, accounts: Vec<OperationAccount>)]
+pub struct ProposeOperation<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"authority_council", council.token_mint.as_ref()],
+        bump = council.bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + 32 + 8 + 32 + (4 + data.len()) + (4 + 34 * accounts.len())
+            + (4 + 32 * MAX_COUNCIL_MEMBERS) + 1 + 8 + 1,
+        seeds = [b"council_operation", council.key().as_ref(), &council.next_operation_id.to_le_bytes()],
+        bump
+    )]
+    pub operation: Account<'info, CouncilOperation>,
+    
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", council.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveOperation<'info> {
+    pub member: Signer<'info>,
+    
+    #[account(
+        seeds = [b"authority_council", council.token_mint.as_ref()],
+        bump = council.bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    #[account(
+        mut,
+        seeds = [b"council_operation", council.key().as_ref(), &operation.operation_id.to_le_bytes()],
+        bump = operation.bump
+    )]
+    pub operation: Account<'info, CouncilOperation>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", council.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteOperation<'info> {
+    pub executor: Signer<'info>,
+    
+    #[account(
+        seeds = [b"authority_council", council.token_mint.as_ref()],
+        bump = council.bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    #[account(
+        mut,
+        seeds = [b"council_operation", council.key().as_ref(), &operation.operation_id.to_le_bytes()],
+        bump = operation.bump
+    )]
+    pub operation: Account<'info, CouncilOperation>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", council.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOperation<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    
+    #[account(
+        seeds = [b"authority_council", council.token_mint.as_ref()],
+        bump = council.bump
+    )]
+    pub council: Account<'info, AuthorityCouncil>,
+    
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"council_operation", council.key().as_ref(), &operation.operation_id.to_le_bytes()],
+        bump = operation.bump,
+        constraint = operation.proposer == proposer.key() @ ErrorCode::InvalidOperation
+    )]
+    pub operation: Account<'info, CouncilOperation>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", council.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetTimelock<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// Timelock PDA, signing when the change executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(data: Vec<u8>, accounts: Vec<OperationAccount>)]
+pub struct QueueOperation<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + (4 + data.len()) + (4 + 34 * accounts.len()) + 8 + 8 + 1,
+        seeds = [b"pending_operation", token_config.mint.as_ref(), &token_config.next_pending_operation_id.to_le_bytes()],
+        bump
+    )]
+    pub operation: Account<'info, PendingOperation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteQueuedOperation<'info> {
+    pub executor: Signer<'info>,
+    
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [b"pending_operation", operation.token_mint.as_ref(), &operation.operation_id.to_le_bytes()],
+        bump = operation.bump
+    )]
+    pub operation: Account<'info, PendingOperation>,
+    
+    /// CHECK: Receives the operation's rent
+    #[account(mut, address = operation.queued_by)]
+    pub queued_by: UncheckedAccount<'info>,
+    
+    /// CHECK: Data-less PDA that signs for the executed instruction
+    #[account(seeds = [b"timelock", operation.token_mint.as_ref()], bump)]
+    pub timelock: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", operation.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct CancelQueuedOperation<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        close = queued_by,
+        seeds = [b"pending_operation", token_config.mint.as_ref(), &operation.operation_id.to_le_bytes()],
+        bump = operation.bump
+    )]
+    pub operation: Account<'info, PendingOperation>,
+    
+    /// CHECK: Receives the operation's rent
+    #[account(mut, address = operation.queued_by)]
+    pub queued_by: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"snapshot", token_config.mint.as_ref(), &token_config.current_snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        seeds = [b"governance_config", token_config.mint.as_ref()],
+        bump = governance_config.bump
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+    
+    #[account(
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 32 + (4 + MAX_PROPOSAL_TITLE_LEN) + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 2 + 2 + 1 + 1 + 1,
+        seeds = [b"proposal", token_config.mint.as_ref(), &token_config.next_proposal_id.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGovernanceConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + (2 + 2 + 8) * PROPOSAL_TYPE_COUNT + 1,
+        seeds = [b"governance_config", token_config.mint.as_ref()],
+        bump
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProposalRules<'info> {
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"governance_config", token_config.mint.as_ref()],
+        bump = governance_config.bump
+    )]
+    pub governance_config: Account<'info, GovernanceConfig>,
+    
+    /// Timelock PDA, signing when the change executes from the queue
+    #[account(seeds = [b"timelock", token_config.mint.as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: ResolutionKind, document_hash: [u8; 32])]
+pub struct RecordResolution<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"proposal", token_config.mint.as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Option<Account<'info, Proposal>>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 1 + 32 + (4 + MAX_RESOLUTION_URI_LEN) + 8 + 33 + 32 + 8 + 1,
+        seeds = [b"resolution", token_config.mint.as_ref(), document_hash.as_ref()],
+        bump
+    )]
+    pub resolution: Account<'info, Resolution>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", proposal.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.token_mint.as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        seeds = [b"snapshot", proposal.token_mint.as_ref(), &proposal.snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        seeds = [b"share_class", proposal.token_mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        seeds = [b"allowlist", proposal.token_mint.as_ref(), voter.key().as_ref()],
+        bump = voter_allowlist_entry.bump
+    )]
+    pub voter_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    /// Voter's own shares; omitted by a proxy voting only delegated weight
+    #[account(
+        constraint = voter_token_account.mint == proposal.token_mint,
+        constraint = voter_token_account.owner == voter.key()
+    )]
+    pub voter_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Checkpoint holding the voter's record-date balance, if they moved since
+    pub voter_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init,
+        payer = voter,
+        space = VoteRecord::SPACE,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateVotes<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"allowlist", token_config.mint.as_ref(), delegator.key().as_ref()],
+        bump = delegator_allowlist_entry.bump
+    )]
+    pub delegator_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"vote_delegation", token_config.mint.as_ref(), delegator.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, VoteDelegation>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegation<'info> {
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+    
+    #[account(
+        mut,
+        close = delegator,
+        seeds = [b"vote_delegation", delegation.token_mint.as_ref(), delegator.key().as_ref()],
+        bump = delegation.bump
+    )]
+    pub delegation: Account<'info, VoteDelegation>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", delegation.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureVoterWeightRegistrar<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    /// CHECK: Realm account; the governance program instance is taken from its owner
+    #[account(constraint = realm.owner != &System::id() @ ErrorCode::InvalidRealm)]
+    pub realm: UncheckedAccount<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 32 + 32 + 1,
+        seeds = [b"registrar", token_config.mint.as_ref()],
+        bump
+    )]
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecord<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"registrar", token_config.mint.as_ref()],
+        bump = registrar.bump
+    )]
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+    
+    #[account(
+        seeds = [b"snapshot", token_config.mint.as_ref(), &token_config.current_snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        seeds = [b"allowlist", token_config.mint.as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        constraint = holder_token_account.mint == token_config.mint,
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Checkpoint holding the holder's record-date balance, if they moved since
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 32 + 32 + 8 + 9 + 2 + 33 + 8,
+        seeds = [b"voter-weight-record", registrar.realm.as_ref(), token_config.mint.as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxVoterWeightRecord<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"registrar", token_config.mint.as_ref()],
+        bump = registrar.bump
+    )]
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+    
+    #[account(
+        seeds = [b"snapshot", token_config.mint.as_ref(), &token_config.current_snapshot_id.to_le_bytes()],
+        bump = snapshot.bump
+    )]
+    pub snapshot: Account<'info, Snapshot>,
+    
+    #[account(
+        seeds = [b"share_class", token_config.mint.as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 32 + 8 + 9 + 8,
+        seeds = [b"max-voter-weight-record", registrar.realm.as_ref(), token_config.mint.as_ref()],
+        bump
+    )]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeProposal<'info> {
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.token_mint.as_ref(), &proposal.proposal_id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    #[account(
+        mut,
+        seeds = [b"token_config", proposal.token_mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimTopics<'info> {
+    #[account(mut)]
+    pub compliance_officer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.compliance_officer == compliance_officer.key() @ ErrorCode::UnauthorizedComplianceOfficer
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = compliance_officer,
+        space = ClaimTopicsRegistry::SPACE,
+        seeds = [b"claim_topics", token_config.mint.as_ref()],
+        bump
+    )]
+    pub claim_topics: Box<Account<'info, ClaimTopicsRegistry>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddClaim<'info> {
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+    
+    /// CHECK: Wallet the identity belongs to
+    pub wallet: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"claim_topics", token_config.mint.as_ref()],
+        bump = claim_topics.bump
+    )]
+    pub claim_topics: Box<Account<'info, ClaimTopicsRegistry>>,
+    
+    #[account(
+        init_if_needed,
+        payer = issuer,
+        space = Identity::SPACE,
+        seeds = [b"identity", token_config.mint.as_ref(), wallet.key().as_ref()],
+        bump
+    )]
+    pub identity: Box<Account<'info, Identity>>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveClaim<'info> {
+    pub signer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"identity", token_config.mint.as_ref(), identity.wallet.as_ref()],
+        bump = identity.bump
+    )]
+    pub identity: Box<Account<'info, Identity>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyIdentity<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"claim_topics", token_config.mint.as_ref()],
+        bump = claim_topics.bump
+    )]
+    pub claim_topics: Box<Account<'info, ClaimTopicsRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"identity", token_config.mint.as_ref(), identity.wallet.as_ref()],
+        bump = identity.bump
+    )]
+    pub identity: Box<Account<'info, Identity>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AllowlistEntry::SPACE,
+        seeds = [b"allowlist", token_config.mint.as_ref(), identity.wallet.as_ref()],
+        bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeUnverified<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_config", token_config.mint.as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Account<'info, TokenConfig>,
+    
+    #[account(
+        seeds = [b"claim_topics", token_config.mint.as_ref()],
+        bump = claim_topics.bump
+    )]
+    pub claim_topics: Box<Account<'info, ClaimTopicsRegistry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"identity", token_config.mint.as_ref(), identity.wallet.as_ref()],
+        bump = identity.bump
+    )]
+    pub identity: Box<Account<'info, Identity>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", token_config.mint.as_ref(), identity.wallet.as_ref()],
+        bump = allowlist_entry.bump
+    )]
+    pub allowlist_entry: Account<'info, AllowlistEntry>,
+    
+    #[account(
+        mut,
+        seeds = [b"country_rule", token_config.mint.as_ref(), allowlist_entry.country_code.as_ref()],
+        bump = country_rule.bump
+    )]
+    pub country_rule: Option<Account<'info, CountryRule>>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureBridge<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump,
+        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 32 + 32 + 1 + 8 + 1,
+        seeds = [b"bridge_config", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_config: Box<Account<'info, BridgeConfig>>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = mint,
+        token::authority = bridge_config,
+        seeds = [b"bridge_vault", mint.key().as_ref()],
+        bump
+    )]
+    pub bridge_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Timelock PDA, signing when the change executes from the queue
+    #[account(seeds = [b"timelock", mint.key().as_ref()], bump)]
+    pub timelock: Option<Signer<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BridgeOut<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
+    )]
+    pub token_config: Box<Account<'info, TokenConfig>>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_config", mint.key().as_ref()],
+        bump = bridge_config.bump
+    )]
+    pub bridge_config: Box<Account<'info, BridgeConfig>>,
+    
+    /// CHECK: The configured Wormhole Token Bridge program
+    #[account(address = bridge_config.token_bridge_program @ ErrorCode::InvalidBridgeAccounts)]
+    pub token_bridge_program: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = holder_token_account.mint == mint.key(),
+        constraint = holder_token_account.owner == holder.key()
+    )]
+    pub holder_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), holder.key().as_ref()],
+        bump = holder_allowlist_entry.bump
+    )]
+    pub holder_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    #[account(
+        seeds = [b"country_rule", mint.key().as_ref(), holder_allowlist_entry.country_code.as_ref()],
+        bump = holder_country_rule.bump
+    )]
+    pub holder_country_rule: Option<Box<Account<'info, CountryRule>>>,
+    
+    /// CHECK: Holder's flag account in the registered sanctions oracle, validated in the handler
+    pub holder_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"velocity", mint.key().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub velocity_counter: Option<Box<Account<'info, VelocityCounter>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"sale_proposal", mint.key().as_ref(), holder.key().as_ref()],
+        bump = sale_proposal.bump
+    )]
+    pub sale_proposal: Option<Box<Account<'info, SaleProposal>>>,
+    
+    #[account(
+        init_if_needed,
+        payer = holder,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), holder.key().as_ref()],
+        bump
+    )]
+    pub holder_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &holder_holder_page.load()?.page_index.to_le_bytes()],
+        bump = holder_holder_page.load()?.bump
+    )]
+    pub holder_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &holder_number.number.to_le_bytes()],
+        bump = holder_number.bump
+    )]
+    pub holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    /// Highest holder number, moved into the gap when the leaving wallet's number is not it
+    #[account(
+        mut,
+        seeds = [b"holder_number", mint.key().as_ref(), &last_holder_number.number.to_le_bytes()],
+        bump = last_holder_number.bump
+    )]
+    pub last_holder_number: Option<Box<Account<'info, HolderNumber>>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RedeemBridged<'info> {
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
+    
+    /// CHECK: Wallet named in the transfer's payload
+    pub recipient: AccountInfo<'info>,
     
     #[account(
-        init,
-        payer = authority,
-        mint::decimals = 9,
-        mint::authority = authority,
+        mut,
+        seeds = [b"token_config", mint.key().as_ref()],
+        bump = token_config.bump
     )]
-    pub mint: Account<'info, Mint>,
+    pub token_config: Box<Account<'info, TokenConfig>>,
     
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 40 + 100 + 1 + 8 + 1,
-        seeds = [b"token_config", mint.key().as_ref()],
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.paused @ ErrorCode::ProgramPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+    
+    #[account(
+        mut,
+        seeds = [b"bridge_config", mint.key().as_ref()],
+        bump = bridge_config.bump
+    )]
+    pub bridge_config: Box<Account<'info, BridgeConfig>>,
+    
+    #[account(mut, seeds = [b"bridge_vault", mint.key().as_ref()], bump)]
+    pub bridge_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// CHECK: The configured Wormhole Token Bridge program
+    #[account(address = bridge_config.token_bridge_program @ ErrorCode::InvalidBridgeAccounts)]
+    pub token_bridge_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Posted VAA of the transfer, owned by the configured core bridge and parsed in the handler
+    pub vaa: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = recipient_token_account.mint == mint.key(),
+        constraint = recipient_token_account.owner == recipient.key()
+    )]
+    pub recipient_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"allowlist", mint.key().as_ref(), recipient.key().as_ref()],
+        bump = recipient_allowlist_entry.bump
+    )]
+    pub recipient_allowlist_entry: Box<Account<'info, AllowlistEntry>>,
+    
+    /// CHECK: Recipient's flag account in the registered sanctions oracle, validated in the handler
+    pub recipient_sanctions_flag: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"share_class", mint.key().as_ref()],
+        bump = share_class.bump
+    )]
+    pub share_class: Option<Account<'info, ShareClass>>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + 32 + 8 + 32 + 8 + 8 + 1,
+        seeds = [b"snapshot_balance", mint.key().as_ref(), &token_config.current_snapshot_id.to_le_bytes(), recipient.key().as_ref()],
         bump
     )]
-    pub token_config: Account<'info, TokenConfig>,
+    pub recipient_snapshot_balance: Option<Box<Account<'info, SnapshotBalance>>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_page", mint.key().as_ref(), &recipient_holder_page.load()?.page_index.to_le_bytes()],
+        bump = recipient_holder_page.load()?.bump
+    )]
+    pub recipient_holder_page: Option<AccountLoader<'info, HolderPage>>,
+    
+    #[account(
+        mut,
+        seeds = [b"holder_index", mint.key().as_ref()],
+        bump = holder_index.bump
+    )]
+    pub holder_index: Option<Box<Account<'info, HolderIndex>>>,
+    
+    /// CHECK: Next holder number account, created and validated in the handler if the
+    /// recipient starts holding
+    #[account(mut)]
+    pub recipient_holder_number: Option<UncheckedAccount<'info>>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-#[derive(Accounts)]
-pub struct ApproveWallet<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Wallet to be approved
-    pub wallet: AccountInfo<'info>,
-    
-    #[account(
-        seeds = [b"token_config", token_config.mint.as_ref()],
-        bump = token_config.bump,
-        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
-    )]
-    pub token_config: Account<'info, TokenConfig>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 1 + 8 + 9 + 1,
-        seeds = [b"allowlist", token_config.mint.as_ref(), wallet.key().as_ref()],
-        bump
-    )]
-    pub allowlist_entry: Account<'info, AllowlistEntry>,
-    
-    pub system_program: Program<'info, System>,
+// Events
+#[event]
+pub struct TokenInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u8,
+}
+
+#[event]
+pub struct WalletApprovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WalletRevokedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensMintedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub new_supply: u64,
+}
+
+#[event]
+pub struct MintScheduleCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub treasury_account: Pubkey,
+    pub amount_per_period: u64,
+    pub period_seconds: i64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct MintScheduleCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub total_emitted: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct EmissionMintedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub treasury_account: Pubkey,
+    pub periods: u64,
+    pub amount: u64,
+    pub total_emitted: u64,
+    pub new_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramConfigUpdatedEvent {
+    pub schema_version: u8,
+    pub admin: Pubkey,
+    pub parameters: ProtocolParameters,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramPausedEvent {
+    pub schema_version: u8,
+    pub paused: bool,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProgramAdminTransferredEvent {
+    pub schema_version: u8,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IssuerCreatedEvent {
+    pub schema_version: u8,
+    pub issuer: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IssuerTokenRegisteredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub issuer: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_count: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IssuerComplianceOfficerUpdatedEvent {
+    pub schema_version: u8,
+    pub issuer: Pubkey,
+    pub old_officer: Pubkey,
+    pub new_officer: Pubkey,
+    pub tokens_updated: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct StaleAccountsReclaimedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub accounts_closed: u32,
+    pub rent_reclaimed: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub accounts_closed: u32,
+    pub rent_reclaimed: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FreezeAuthorityChangedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_freeze_authority: Option<Pubkey>,
+    pub new_freeze_authority: Option<Pubkey>,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenAccountFreezeChangedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,
+    pub frozen: bool,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxSupplyUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_max_supply: Option<u64>,
+    pub new_max_supply: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplyCapReachedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub max_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensBurnedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub new_supply: u64,
+}
+
+#[event]
+pub struct AllowlistBitmapPageCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub page_index: u32,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AllowlistSlotsAssignedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    /// Global slot of `wallets[0]`; the rest follow consecutively
+    pub first_slot: u32,
+    pub wallets: Vec<Pubkey>,
+    pub approved: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AllowlistSlotsApprovalUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub slots: Vec<u32>,
+    pub approved: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AllowlistTreeInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct CompressedEntryUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub leaf_index: u32,
+    pub approved: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TokensTransferredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    /// Hash of the originator/beneficiary payload held off-chain by the VASPs
+    pub travel_rule_hash: Option<[u8; 32]>,
+}
+
+#[event]
+pub struct ForcedTransferEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    /// Hash of the order or request behind the transfer, held off-chain
+    pub reason_hash: [u8; 32],
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PermitTransferEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub signed_by: Pubkey,
+}
+
+#[event]
+pub struct DelegateApprovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub owner: Pubkey,
+    /// Default when the approval was revoked
+    pub delegate: Pubkey,
+    pub token_account: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DelegatedTransferEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub remaining_allowance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub escrow_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub condition: ReleaseCondition,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowReleasedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub escrow_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub released_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub escrow_id: u64,
+    pub sender: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapOfferCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub taker: Option<Pubkey>,
+    pub offered_mint: Pubkey,
+    pub offered_amount: u64,
+    pub requested_mint: Pubkey,
+    pub requested_amount: u64,
+    pub expires_at: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapExecutedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub offered_mint: Pubkey,
+    pub offered_amount: u64,
+    pub requested_mint: Pubkey,
+    pub requested_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SwapOfferCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub offer_id: u64,
+    pub maker: Pubkey,
+    pub offered_mint: Pubkey,
+    pub offered_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WrapperInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wrapped_mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct SharesWrappedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub total_wrapped: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharesUnwrappedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub total_wrapped: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WrapperReconciledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub total_wrapped: u64,
+    pub wrapped_supply: u64,
+    pub escrowed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub stream_id: u64,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub deposited: u64,
+    pub rate_per_second: u64,
+    pub start_time: i64,
+}
+
+#[event]
+pub struct StreamWithdrawnEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub stream_id: u64,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamPausedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub stream_id: u64,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreamCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub stream_id: u64,
+    pub paid_to_recipient: u64,
+    pub refunded_to_sender: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferJournalUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub enabled: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CpiCallersRestrictedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub restricted: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TrustedCallerUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub program_id: Pubkey,
+    pub trusted: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeaturesUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub previous_features: u32,
+    pub features: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RequireMemoUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub enabled: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AirdropCampaignCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub campaign_id: u64,
+    pub merkle_root: [u8; 32],
+    pub total_amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AirdropClaimedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub campaign_id: u64,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub claimed_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AirdropSweptEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub campaign_id: u64,
+    pub amount: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchMintedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub total_amount: u64,
+    pub new_supply: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct BatchTransferEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub legs: u32,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StockSplitExecutedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub old_mint: Pubkey,
+    pub new_mint: Pubkey,
+    pub split_ratio: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HolderMigratedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub wallet: Pubkey,
+    pub old_balance: u64,
+    pub new_balance: u64,
+    pub split_ratio: u64,
+}
+
+#[event]
+pub struct SymbolChangedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub mint: Pubkey,
+    pub old_symbol: String,
+    pub new_symbol: String,
+    pub old_name: String,
+    pub new_name: String,
+    pub old_uri: String,
+    pub new_uri: String,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenPausedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub paused_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenUnpausedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub unpaused_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub reason: BreakerTripReason,
+    pub recorded_supply: u64,
+    pub observed_supply: u64,
+    pub holder_count: i64,
+    pub detected_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerResetEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub reconciled: bool,
+    pub total_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DestinationApprovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub destination: Pubkey,
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DestinationRemovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub destination: Pubkey,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferFeeCollectedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub from: Pubkey,
+    pub fee: u64,
+    pub fee_bps: u16,
+}
+
+#[event]
+pub struct TransferFeeUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferFeesWithdrawnEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct InvestorOnboardedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub investor: Pubkey,
+    pub token_account: Pubkey,
+    pub sponsor: Pubkey,
+    pub lockup_until: Option<i64>,
+    pub initial_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferLockUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub transfer_locked: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ActionReceiptIssuedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub action: Pubkey,
+    pub kind: ActionKind,
+    pub holder: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub settled_at: i64,
+}
+
+#[event]
+pub struct BlackoutWindowCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub window_id: u64,
+    pub start_ts: i64,
+    pub end_ts: i64,
+    pub scope: BlackoutScope,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct BlackoutWindowCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub window_id: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferBlockedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub window_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InsiderStatusUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub is_insider: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DailyTransferLimitUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_limit: u64,
+    pub new_limit: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComplianceOfficerUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_officer: Pubkey,
+    pub new_officer: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuardianUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_guardian: Pubkey,
+    pub new_guardian: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComplianceDigestPostedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub digest_hash: [u8; 32],
+    pub alert_count: u32,
+    pub signed_by: Pubkey,
+}
+
+#[event]
+pub struct LotRulesUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub min_transfer_amount: u64,
+    pub lot_size: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenRegisteredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub index: u64,
+    pub page_index: u32,
+}
+
+#[event]
+pub struct TokenAccountBoundEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub token_account: Option<Pubkey>,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CountryRuleUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub country_code: [u8; 2],
+    pub is_allowed: bool,
+    pub max_investors: u32,
+    pub investor_count: u32,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WalletCountryAssignedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub previous_country: [u8; 2],
+    pub country_code: [u8; 2],
+    pub assigned_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SanctionsOracleUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_oracle: Pubkey,
+    pub new_oracle: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributionScheduleUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub payout_mint: Pubkey,
+    pub amount_per_period: u64,
+    pub interval_seconds: i64,
+    pub next_run_at: i64,
+    pub active: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct DistributionCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub record_date: i64,
+    pub snapshot_supply: u64,
+    pub total_amount: u64,
+    pub payout_mint: Pubkey,
+    pub claim_deadline: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct DividendClaimedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub holder: Pubkey,
+    pub snapshot_balance: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributionSweptEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub amount: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SnapshotCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub snapshot_id: u64,
+    pub total_supply: u64,
+    pub holder_count: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitExecutedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub numerator: u64,
+    pub denominator: u64,
+    pub cumulative_numerator: u64,
+    pub cumulative_denominator: u64,
+    pub old_total_supply: u64,
+    pub new_total_supply: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ShareClassUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub share_class: Pubkey,
+    pub kind: ShareClassKind,
+    pub votes_per_share: u16,
+    pub dividend_priority: u8,
+    pub transferable: bool,
+    pub max_holders: u32,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingScheduleCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub vesting_id: u64,
+    pub beneficiary: Pubkey,
+    pub total_amount: u64,
+    pub terms: VestingTerms,
+    pub revocable: bool,
+}
+
+#[event]
+pub struct VestedTokensReleasedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub vesting_id: u64,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub released_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevokedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub vesting_id: u64,
+    pub beneficiary: Pubkey,
+    pub vested_amount: u64,
+    pub returned_amount: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionGrantCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub grant_id: u64,
+    pub employee: Pubkey,
+    pub quantity: u64,
+    pub strike_price: u64,
+    pub payment_mint: Pubkey,
+    pub vesting: VestingTerms,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct OptionsExercisedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub grant_id: u64,
+    pub employee: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+    pub exercised: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OptionGrantClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub grant_id: u64,
+    pub employee: Pubkey,
+    pub lapsed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RedemptionVaultFundedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub amount: u64,
+    pub vault_balance: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RedemptionWindowUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub is_open: bool,
+    pub price: u64,
+    pub closes_at: i64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharesRedeemedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub payout: u64,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TenderOfferOpenedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offer_id: u64,
+    pub payment_mint: Pubkey,
+    pub price: u64,
+    pub cap: u64,
+    pub min_acceptance: u64,
+    pub deadline: i64,
+    pub funding: u64,
+}
+
+#[event]
+pub struct SharesTenderedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offer_id: u64,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub total_tendered: u64,
+}
+
+#[event]
+pub struct TenderOfferClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offer_id: u64,
+    pub status: TenderOfferStatus,
+    pub total_tendered: u64,
+    pub accepted_amount: u64,
+    pub refund: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TenderSettledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offer_id: u64,
+    pub holder: Pubkey,
+    pub accepted: u64,
+    pub returned: u64,
+    pub payout: u64,
+}
+
+#[event]
+pub struct ConvertibleNoteIssuedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub note_id: u64,
+    pub noteholder: Pubkey,
+    pub principal: u64,
+    pub discount_bps: u16,
+    pub valuation_cap: u64,
+    pub maturity: i64,
+}
+
+#[event]
+pub struct NoteConvertedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub note_id: u64,
+    pub noteholder: Pubkey,
+    pub trigger: ConversionTrigger,
+    pub principal: u64,
+    pub conversion_price: u64,
+    pub shares_minted: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WarrantIssuedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub warrant_id: u64,
+    pub holder: Pubkey,
+    pub quantity: u64,
+    pub strike_price: u64,
+    pub payment_mint: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct WarrantExercisedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub warrant_id: u64,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub cost: u64,
+    pub exercised: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WarrantClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub warrant_id: u64,
+    pub holder: Pubkey,
+    pub lapsed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CapTableInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub cap_table: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct HolderIndexInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder_index: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct HolderNumberAssignedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub number: u64,
+    pub wallet: Pubkey,
+    /// Index length after the change
+    pub holders: u64,
+}
+
+#[event]
+pub struct HolderNumberReleasedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    /// Number whose account was closed; always the old last number
+    pub number: u64,
+    pub wallet: Pubkey,
+    pub holders: u64,
+}
+
+#[event]
+pub struct HolderPageCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub page_index: u32,
+}
+
+#[event]
+pub struct HolderPagesCompactedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub source_page: u32,
+    pub destination_page: u32,
+    pub moved: u32,
+    pub remaining: u32,
+}
+
+#[event]
+pub struct HolderPageClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub page_index: u32,
+}
+
+#[event]
+pub struct RofrPeriodUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_period: i64,
+    pub new_period: i64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SaleProposedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub price: u64,
+    pub payment_mint: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct SaleProposalWithdrawnEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RofrExercisedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub price: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RofrSettledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub payment: u64,
+}
+
+#[event]
+pub struct RightsOfferingOpenedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offering_id: u64,
+    pub snapshot_id: u64,
+    pub new_shares: u64,
+    pub price: u64,
+    pub payment_mint: Pubkey,
+    pub deadline: i64,
+    pub allow_oversubscription: bool,
+}
+
+#[event]
+pub struct RightsSubscribedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offering_id: u64,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub oversubscription: u64,
+    pub entitlement: u64,
+    pub payment: u64,
+}
+
+#[event]
+pub struct RightsSettledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub offering_id: u64,
+    pub holder: Pubkey,
+    pub allotted: u64,
+    pub oversubscription_allotted: u64,
+    pub proceeds: u64,
+    pub refund: u64,
+}
+
+#[event]
+pub struct AllocationOpenedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub allocation_id: u64,
+    pub snapshot_id: u64,
+    pub snapshot_supply: u64,
+    pub issuance_amount: u64,
+}
+
+#[event]
+pub struct AllocationMintedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub allocation_id: u64,
+    pub wallet: Pubkey,
+    pub record_balance: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AllocationFinalizedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub allocation_id: u64,
+    pub allocated_amount: u64,
+    pub dust: u64,
+    pub holders_processed: u64,
 }
 
-#[derive(Accounts)]
-pub struct RevokeWallet<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Wallet to be revoked
-    pub wallet: AccountInfo<'info>,
-    
-    #[account(
-        seeds = [b"token_config", token_config.mint.as_ref()],
-        bump = token_config.bump,
-        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
-    )]
-    pub token_config: Account<'info, TokenConfig>,
-    
-    #[account(
-        mut,
-        seeds = [b"allowlist", token_config.mint.as_ref(), wallet.key().as_ref()],
-        bump = allowlist_entry.bump
-    )]
-    pub allowlist_entry: Account<'info, AllowlistEntry>,
+#[event]
+pub struct DripElectionUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub enabled: bool,
 }
 
-#[derive(Accounts)]
-pub struct MintTokens<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Recipient wallet
-    pub recipient: AccountInfo<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"token_config", mint.key().as_ref()],
-        bump = token_config.bump,
-        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
-    )]
-    pub token_config: Account<'info, TokenConfig>,
-    
-    #[account(mut)]
-    pub mint: Account<'info, Mint>,
-    
-    #[account(
-        mut,
-        constraint = recipient_token_account.mint == mint.key(),
-        constraint = recipient_token_account.owner == recipient.key()
-    )]
-    pub recipient_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
-        bump = recipient_allowlist_entry.bump
-    )]
-    pub recipient_allowlist_entry: Account<'info, AllowlistEntry>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct DripPriceUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub drip_price: u64,
+    pub authority: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct GatedTransfer<'info> {
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Recipient wallet
-    pub recipient: AccountInfo<'info>,
-    
-    #[account(
-        seeds = [b"token_config", mint.key().as_ref()],
-        bump = token_config.bump
-    )]
-    pub token_config: Account<'info, TokenConfig>,
-    
-    pub mint: Account<'info, Mint>,
-    
-    #[account(
-        mut,
-        constraint = from_token_account.mint == mint.key(),
-        constraint = from_token_account.owner == authority.key()
-    )]
-    pub from_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        mut,
-        constraint = to_token_account.mint == mint.key(),
-        constraint = to_token_account.owner == recipient.key()
-    )]
-    pub to_token_account: Account<'info, TokenAccount>,
-    
-    #[account(
-        seeds = [b"allowlist", token_config.mint.as_ref(), authority.key().as_ref()],
-        bump = sender_allowlist_entry.bump
-    )]
-    pub sender_allowlist_entry: Account<'info, AllowlistEntry>,
-    
-    #[account(
-        seeds = [b"allowlist", token_config.mint.as_ref(), recipient.key().as_ref()],
-        bump = recipient_allowlist_entry.bump
-    )]
-    pub recipient_allowlist_entry: Account<'info, AllowlistEntry>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct DividendReinvestedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub distribution_id: u64,
+    pub holder: Pubkey,
+    pub payout: u64,
+    pub shares: u64,
+    pub cost: u64,
+    pub drip_price: u64,
 }
 
-#[derive(Accounts)]
-#[instruction(split_ratio: u64, new_symbol: String, new_name: String)]
-pub struct ExecuteStockSplit<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    #[account(
-        seeds = [b"token_config", old_token_config.mint.as_ref()],
-        bump = old_token_config.bump,
-        constraint = old_token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
-    )]
-    pub old_token_config: Account<'info, TokenConfig>,
-    
-    #[account(
-        init,
-        payer = authority,
-        mint::decimals = old_token_config.decimals,
-        mint::authority = authority,
-    )]
-    pub new_mint: Account<'info, Mint>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 40 + 100 + 1 + 8 + 1,
-        seeds = [b"token_config", new_mint.key().as_ref()],
-        bump
-    )]
-    pub new_token_config: Account<'info, TokenConfig>,
-    
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 8 + 8 + 32 + 1,
-        seeds = [b"split_config", old_token_config.mint.as_ref(), new_mint.key().as_ref()],
-        bump
-    )]
-    pub split_config: Account<'info, SplitConfig>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+#[event]
+pub struct ShareClassAccrualUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub share_class: Pubkey,
+    pub rate_bps: u16,
+    pub interest_index: u128,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InterestAccruedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub period_start: i64,
+    pub period_end: i64,
+    pub rate_bps: u16,
+    pub index_delta: u128,
+    pub interest_index: u128,
+}
+
+#[event]
+pub struct InterestClaimedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub interest_index: u128,
+    pub remaining_accrued: u64,
+}
+
+#[event]
+pub struct LiquidationPreferenceUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub share_class: Pubkey,
+    pub multiple_bps: u32,
+    pub issue_price: u64,
+    pub seniority: u8,
+    pub participating: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WaterfallTranchePaidEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub share_class: Pubkey,
+    pub seniority: u8,
+    pub preference: u64,
+    pub preference_paid: u64,
+    pub residual_paid: u64,
+    /// Distribution funded with the class's allotment; `None` when nothing was owed
+    pub distribution_id: Option<u64>,
+}
+
+#[event]
+pub struct WaterfallExecutedEvent {
+    pub schema_version: u8,
+    pub payout_mint: Pubkey,
+    pub total_proceeds: u64,
+    pub distributed: u64,
+    pub undistributed: u64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityProposedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityProposalCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuthorityCouncilUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub council: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationProposedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationApprovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationExecutedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub approvals: u8,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationClosedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub executed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TimelockUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub old_delay_seconds: i64,
+    pub delay_seconds: i64,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OperationQueuedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub queued_by: Pubkey,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QueuedOperationExecutedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QueuedOperationCancelledEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub operation_id: u64,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
 }
 
-#[derive(Accounts)]
-pub struct MigrateHolderSplit<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    /// CHECK: Holder wallet
-    pub holder: AccountInfo<'info>,
-    
-    #[account(
-        seeds = [b"split_config", split_config.original_mint.as_ref(), split_config.new_mint.as_ref()],
-        bump = split_config.bump
-    )]
-    pub split_config: Account<'info, SplitConfig>,
-    
-    #[account(mut)]
-    pub new_mint: Account<'info, Mint>,
-    
-    #[account(
-        mut,
-        seeds = [b"token_config", new_mint.key().as_ref()],
-        bump = new_token_config.bump
-    )]
-    pub new_token_config: Account<'info, TokenConfig>,
-    
-    #[account(
-        mut,
-        constraint = holder_new_token_account.mint == new_mint.key(),
-        constraint = holder_new_token_account.owner == holder.key()
-    )]
-    pub holder_new_token_account: Account<'info, TokenAccount>,
-    
-    pub token_program: Program<'info, Token>,
+#[event]
+pub struct ProposalCreatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub proposal_id: u64,
+    pub title: String,
+    pub description_hash: [u8; 32],
+    pub proposal_type: ProposalType,
+    pub snapshot_id: u64,
+    pub eligible_votes: u64,
+    pub voting_ends_at: i64,
+    pub quorum_bps: u16,
+    pub approval_threshold_bps: u16,
+    pub proposer: Pubkey,
 }
 
-#[derive(Accounts)]
-pub struct UpdateTokenMetadata<'info> {
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    #[account(
-        mut,
-        seeds = [b"token_config", token_config.mint.as_ref()],
-        bump = token_config.bump,
-        constraint = token_config.authority == authority.key() @ ErrorCode::UnauthorizedAuthority
-    )]
-    pub token_config: Account<'info, TokenConfig>,
+#[event]
+pub struct ProposalRulesUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub proposal_type: ProposalType,
+    pub quorum_bps: u16,
+    pub approval_threshold_bps: u16,
+    pub min_voting_period_seconds: i64,
+    pub authority: Pubkey,
 }
 
-// Events
 #[event]
-pub struct TokenInitializedEvent {
+pub struct ResolutionRecordedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub resolution: Pubkey,
+    pub kind: ResolutionKind,
+    pub document_hash: [u8; 32],
+    pub uri: String,
+    pub effective_date: i64,
+    pub proposal: Option<Pubkey>,
+    pub recorded_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteCastEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub choice: VoteChoice,
+    /// Voter's own weight
+    pub weight: u64,
+    /// Weight cast on behalf of delegators
+    pub delegated_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VotesDelegatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub delegator: Pubkey,
+    /// Earlier delegate replaced, or default for a first delegation
+    pub previous_delegate: Pubkey,
+    /// New delegate, or default when the delegation is revoked
+    pub delegate: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalFinalizedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub votes_abstain: u64,
+    pub quorum_reached: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoterWeightRegistrarUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub realm: Pubkey,
+    pub governance_program_id: Pubkey,
     pub authority: Pubkey,
-    pub mint: Pubkey,
-    pub symbol: String,
-    pub name: String,
-    pub decimals: u8,
 }
 
 #[event]
-pub struct WalletApprovedEvent {
+pub struct VoterWeightUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub realm: Pubkey,
+    pub holder: Pubkey,
+    pub snapshot_id: u64,
+    pub voter_weight: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ClaimTopicsUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub required_topics: Vec<u64>,
+    pub trusted_issuers: Vec<Pubkey>,
+    pub updated_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimAddedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub token_mint: Pubkey,
     pub wallet: Pubkey,
-    pub approved_by: Pubkey,
+    pub topic: u64,
+    pub issuer: Pubkey,
+    pub expires_at: Option<i64>,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct WalletRevokedEvent {
+pub struct ClaimRemovedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub token_mint: Pubkey,
     pub wallet: Pubkey,
-    pub revoked_by: Pubkey,
+    pub topic: u64,
+    pub issuer: Pubkey,
+    pub removed_by: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct TokensMintedEvent {
+pub struct BridgeConfiguredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub token_mint: Pubkey,
-    pub recipient: Pubkey,
+    pub token_bridge_program: Pubkey,
+    pub wormhole_program: Pubkey,
+    pub enabled: bool,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharesBridgedOutEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
     pub amount: u64,
-    pub new_supply: u64,
+    pub target_chain: u16,
+    pub target_address: [u8; 32],
+    pub nonce: u32,
+    pub total_bridged_out: u64,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct TokensTransferredEvent {
+pub struct SharesBridgedInEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
     pub token_mint: Pubkey,
-    pub from: Pubkey,
-    pub to: Pubkey,
+    pub recipient: Pubkey,
     pub amount: u64,
+    /// Chain and sequence of the Wormhole message that carried the transfer
+    pub emitter_chain: u16,
+    pub vaa_sequence: u64,
+    pub total_bridged_out: u64,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct StockSplitExecutedEvent {
-    pub old_mint: Pubkey,
-    pub new_mint: Pubkey,
-    pub split_ratio: u64,
+pub struct PriceOracleUpdatedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub oracle_program: Pubkey,
+    pub price_feed: Pubkey,
+    pub max_price_age_seconds: i64,
     pub authority: Pubkey,
     pub timestamp: i64,
 }
 
 #[event]
-pub struct HolderMigratedEvent {
-    pub wallet: Pubkey,
-    pub old_balance: u64,
-    pub new_balance: u64,
-    pub split_ratio: u64,
+pub struct SharesRedeemedAtNavEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub amount: u64,
+    pub payout: u64,
+    /// Payment mint base units per whole share the oracle price came to
+    pub nav: u64,
+    pub oracle_price: OraclePrice,
+    pub timestamp: i64,
 }
 
 #[event]
-pub struct SymbolChangedEvent {
-    pub mint: Pubkey,
-    pub old_symbol: String,
-    pub new_symbol: String,
-    pub old_name: String,
-    pub new_name: String,
+pub struct OptionsExercisedAtMarketEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub grant_id: u64,
+    pub employee: Pubkey,
+    /// Options surrendered
+    pub amount: u64,
+    pub shares_issued: u64,
+    pub strike_price: u64,
+    /// Payment mint base units per whole share the oracle price came to
+    pub market_price: u64,
+    pub oracle_price: OraclePrice,
+    pub exercised: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CertificateTreeInitializedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
     pub authority: Pubkey,
+}
+
+#[event]
+pub struct ShareCertificateIssuedEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub asset_id: Pubkey,
+    pub class: String,
+    pub issued_at: i64,
+}
+
+#[event]
+pub struct ShareCertificateRetiredEvent {
+    pub schema_version: u8,
+    pub sequence: u64,
+    pub token_mint: Pubkey,
+    pub holder: Pubkey,
+    pub asset_id: Pubkey,
     pub timestamp: i64,
 }
 
@@ -636,5 +25166,568 @@ pub enum ErrorCode {
     
     #[msg("Invalid split ratio: must be greater than 0")]
     InvalidSplitRatio,
+    
+    #[msg("Token is paused")]
+    TokenPaused,
+    
+    #[msg("Circuit breaker is tripped: a threshold-signed reset is required")]
+    CircuitBreakerTripped,
+    
+    #[msg("Circuit breaker is not tripped")]
+    CircuitBreakerNotTripped,
+    
+    #[msg("Invalid circuit breaker configuration")]
+    InvalidBreakerConfig,
+    
+    #[msg("Not enough reset signers approved the circuit breaker reset")]
+    InsufficientResetSignatures,
+    
+    #[msg("Only a circuit breaker signer may submit a registry checksum")]
+    UnauthorizedMonitor,
+    
+    #[msg("Destination is a program-controlled account not on the destination registry")]
+    DestinationProgramNotApproved,
+    
+    #[msg("Invalid destination")]
+    InvalidDestination,
+    
+    #[msg("Destination is already approved")]
+    DestinationAlreadyApproved,
+    
+    #[msg("Destination registry is full")]
+    DestinationRegistryFull,
+    
+    #[msg("Destination not found in registry")]
+    DestinationNotFound,
+    
+    #[msg("Invalid transfer fee: must be at most 1000 basis points")]
+    InvalidTransferFee,
+    
+    #[msg("Fee treasury account is required when a transfer fee applies")]
+    FeeTreasuryRequired,
+    
+    #[msg("Sender tokens are locked up")]
+    SenderLockedUp,
+    
+    #[msg("Invalid lockup: must end in the future")]
+    InvalidLockup,
+    
+    #[msg("Sender wallet is non-transferable")]
+    SenderTransferLocked,
+    
+    #[msg("Invalid blackout window")]
+    InvalidBlackoutWindow,
+    
+    #[msg("Too many open blackout windows")]
+    TooManyBlackoutWindows,
+    
+    #[msg("All open blackout windows must be supplied")]
+    BlackoutWindowsMissing,
+    
+    #[msg("Trading is blocked by an active blackout window")]
+    TradingBlackout,
+    
+    #[msg("Velocity counter account is required when a daily limit applies")]
+    VelocityCounterRequired,
+    
+    #[msg("Daily transfer limit exceeded")]
+    DailyTransferLimitExceeded,
+    
+    #[msg("Unauthorized: only the compliance officer can perform this action")]
+    UnauthorizedComplianceOfficer,
+    
+    #[msg("Invalid digest period")]
+    InvalidDigestPeriod,
+    
+    #[msg("Amount is below the minimum transfer amount")]
+    BelowMinimumAmount,
+    
+    #[msg("Amount must be a multiple of the lot size")]
+    InvalidLotMultiple,
+    
+    #[msg("Invalid lot size: must be greater than 0")]
+    InvalidLotSize,
+    
+    #[msg("Registry page does not match the registry's current page")]
+    InvalidRegistryPage,
+    
+    #[msg("Token account is not the one approved for this wallet")]
+    TokenAccountNotApproved,
+    
+    #[msg("Country code must be two uppercase ISO 3166-1 alpha-2 letters")]
+    InvalidCountryCode,
+    
+    #[msg("Wallet's country is not allowed to hold this token")]
+    CountryNotAllowed,
+    
+    #[msg("Country has reached its maximum investor count")]
+    CountryInvestorCapReached,
+    
+    #[msg("Country rule account required for a wallet with a recorded country")]
+    CountryRuleRequired,
+    
+    #[msg("Wallet is flagged by the sanctions oracle")]
+    WalletSanctioned,
+    
+    #[msg("Sanctions flag account required while a sanctions oracle is registered")]
+    SanctionsFlagRequired,
+    
+    #[msg("Sanctions flag account does not belong to the registered oracle or wallet")]
+    InvalidSanctionsFlag,
+    
+    #[msg("Invalid distribution parameters")]
+    InvalidDistribution,
+    
+    #[msg("Snapshot balance proof does not match the distribution's snapshot root")]
+    InvalidSnapshotProof,
+    
+    #[msg("Claim deadline for this distribution has passed")]
+    ClaimPeriodEnded,
+    
+    #[msg("Distribution is still open for claims")]
+    ClaimPeriodActive,
+    
+    #[msg("Snapshot balance checkpoint account required")]
+    SnapshotCheckpointRequired,
+    
+    #[msg("Snapshot balance checkpoint does not cover this snapshot or wallet")]
+    InvalidSnapshotCheckpoint,
+    
+    #[msg("Amount does not convert to a whole number of post-split shares")]
+    AmountNotSplitAligned,
+    
+    #[msg("Share class account required for a token with a configured share class")]
+    ShareClassRequired,
+    
+    #[msg("Shares of this class are not transferable")]
+    ShareClassNotTransferable,
+    
+    #[msg("Share class has reached its maximum number of holders")]
+    ShareClassHolderCapReached,
+    
+    #[msg("Invalid vesting schedule: duration must be positive and the cliff within it")]
+    InvalidVestingSchedule,
+    
+    #[msg("No vested tokens to release")]
+    NothingVested,
+    
+    #[msg("Vesting schedule is not revocable or was already revoked")]
+    VestingNotRevocable,
+    
+    #[msg("Invalid option grant parameters")]
+    InvalidOptionGrant,
+    
+    #[msg("Option grant has expired")]
+    OptionGrantExpired,
+    
+    #[msg("Amount exceeds the vested, unexercised options")]
+    OptionsNotExercisable,
+    
+    #[msg("Option grant is still exercisable")]
+    OptionGrantActive,
+    
+    #[msg("Redemption price must be greater than 0")]
+    InvalidRedemptionPrice,
+    
+    #[msg("Redemption window must close in the future")]
+    InvalidRedemptionWindow,
+    
+    #[msg("Redemption window is closed")]
+    RedemptionWindowClosed,
+    
+    #[msg("Redemption vault has insufficient funds")]
+    InsufficientRedemptionFunds,
+    
+    #[msg("Invalid tender offer: price and cap must be positive, minimum within cap, deadline in the future")]
+    InvalidTenderOffer,
+    
+    #[msg("Tender offer is not open")]
+    TenderOfferClosed,
+    
+    #[msg("Tender offer has not closed yet")]
+    TenderOfferActive,
+    
+    #[msg("Invalid convertible note: principal must be positive, discount below 100%, maturity in the future")]
+    InvalidConvertibleNote,
+    
+    #[msg("Note cannot convert: not yet mature, no valuation cap, or principal too small for a lot")]
+    NoteNotConvertible,
+    
+    #[msg("Warrant expiry must be in the future")]
+    InvalidWarrant,
+    
+    #[msg("Warrant has expired")]
+    WarrantExpired,
+    
+    #[msg("Amount exceeds the warrant's unexercised quantity")]
+    WarrantQuantityExceeded,
+    
+    #[msg("Warrant is still exercisable")]
+    WarrantActive,
+    
+    #[msg("Cap table is enabled: the wallet's holder page is required")]
+    HolderPageRequired,
+    
+    #[msg("Holder page does not match the wallet's cap table record")]
+    InvalidHolderPage,
+    
+    #[msg("Holder page is full")]
+    HolderPageFull,
+    
+    #[msg("Holder page still has records")]
+    HolderPageNotEmpty,
+    
+    #[msg("Cap table or holder index is enabled: the wallet must transact through its bound token account")]
+    TokenAccountBindingRequired,
+    
+    #[msg("ROFR period cannot be negative")]
+    InvalidRofrPeriod,
+    
+    #[msg("ROFR is not enabled for this token")]
+    RofrNotEnabled,
+    
+    #[msg("Balance is below the proposed sale amount")]
+    InsufficientBalance,
+    
+    #[msg("ROFR is enabled: a cleared sale proposal is required")]
+    SaleProposalRequired,
+    
+    #[msg("Transfer does not match the sale proposal's buyer and amount")]
+    SaleProposalMismatch,
+    
+    #[msg("The issuer exercised its right of first refusal on this sale")]
+    RofrExercised,
+    
+    #[msg("The issuer's ROFR window is still open")]
+    RofrPeriodActive,
+    
+    #[msg("The issuer's ROFR window has ended")]
+    RofrPeriodEnded,
+    
+    #[msg("The issuer has not exercised its ROFR on this sale")]
+    RofrNotExercised,
+    
+    #[msg("Invalid rights offering: shares, snapshot supply and deadline must be set")]
+    InvalidRightsOffering,
+    
+    #[msg("Rights offering has closed")]
+    RightsOfferingClosed,
+    
+    #[msg("Rights offering is still open")]
+    RightsOfferingActive,
+    
+    #[msg("Subscription exceeds the holder's pro-rata entitlement")]
+    RightsEntitlementExceeded,
+    
+    #[msg("This rights offering does not accept oversubscription")]
+    OversubscriptionNotAllowed,
+    
+    #[msg("Another allocation is still active")]
+    AllocationActive,
+    
+    #[msg("Allocation must use the current snapshot, and no snapshot may be taken until it is finalized")]
+    AllocationSnapshotStale,
+    
+    #[msg("Allocation has been finalized")]
+    AllocationFinalized,
+    
+    #[msg("Allocation crank accounts are malformed")]
+    InvalidAllocationAccounts,
+    
+    #[msg("Holder elected DRIP: mint, share account, mint authority and treasury accounts are required")]
+    DripAccountsRequired,
+    
+    #[msg("Accrual rate exceeds the maximum")]
+    InvalidAccrualRate,
+    
+    #[msg("No whole lot of accrued interest to claim")]
+    NoInterestAccrued,
+    
+    #[msg("Invalid liquidation preference terms")]
+    InvalidLiquidationPreference,
+    
+    #[msg("A waterfall must cover between one and the maximum number of share classes")]
+    InvalidWaterfall,
+    
+    #[msg("Waterfall accounts do not match the classes supplied")]
+    InvalidWaterfallAccounts,
+    
+    #[msg("Proposed authority must be a new, non-default key")]
+    InvalidAuthorityProposal,
+    
+    #[msg("No authority handover is pending")]
+    NoPendingAuthority,
+    
+    #[msg("Council needs distinct members and a threshold between one and the member count")]
+    InvalidCouncil,
+    
+    #[msg("Signer is not a member of the authority council")]
+    NotCouncilMember,
+    
+    #[msg("Invalid council operation")]
+    InvalidOperation,
+    
+    #[msg("Member has already approved this operation")]
+    OperationAlreadyApproved,
+    
+    #[msg("Operation does not have enough council approvals")]
+    OperationThresholdNotMet,
+    
+    #[msg("Operation has already been executed")]
+    OperationExecuted,
+    
+    #[msg("Timelock delay exceeds the maximum")]
+    InvalidTimelock,
+    
+    #[msg("Timelock is not enabled for this token")]
+    TimelockNotEnabled,
+    
+    #[msg("Sensitive operation must be queued behind the timelock")]
+    TimelockRequired,
+    
+    #[msg("Timelock delay has not elapsed")]
+    TimelockNotElapsed,
+    
+    #[msg("Invalid proposal parameters")]
+    InvalidProposal,
+    
+    #[msg("Voting on this proposal has closed")]
+    VotingClosed,
+    
+    #[msg("Voting on this proposal is still open")]
+    VotingActive,
+    
+    #[msg("Proposal has already been finalized")]
+    ProposalFinalized,
+    
+    #[msg("Voter has no voting power at the proposal's record date")]
+    NoVotingPower,
+    
+    #[msg("Delegate must be another, non-default wallet")]
+    InvalidDelegate,
+    
+    #[msg("Delegated vote accounts are malformed")]
+    InvalidDelegationAccounts,
+    
+    #[msg("Voter is not the delegate of this delegation")]
+    NotDelegate,
+    
+    #[msg("Delegation was made after the proposal's snapshot")]
+    DelegationAfterSnapshot,
+    
+    #[msg("Wallet has already voted on this proposal")]
+    AlreadyVoted,
+    
+    #[msg("Realm account is not owned by a governance program")]
+    InvalidRealm,
+    
+    #[msg("Invalid proposal rules")]
+    InvalidProposalRules,
+    
+    #[msg("Resolution needs a document hash and a URI within the length limit")]
+    InvalidResolution,
+    
+    #[msg("Proposal has not passed")]
+    ProposalNotPassed,
+    #[msg("Invalid permit: sender and recipient must differ")]
+    InvalidPermit,
+    #[msg("Permit has expired")]
+    PermitExpired,
+    #[msg("Permit nonce does not match the wallet's next nonce")]
+    InvalidPermitNonce,
+    #[msg("Permit must be signed by the compliance officer in a preceding ed25519 instruction")]
+    InvalidPermitSignature,
+    #[msg("Mint would exceed the token's maximum supply")]
+    MaxSupplyExceeded,
+    #[msg("Maximum supply can only be lowered, and not below the issued supply")]
+    InvalidMaxSupply,
+    #[msg("Invalid mint schedule: amount and period must be positive and end after start")]
+    InvalidMintSchedule,
+    #[msg("No emission period has come due since the last crank")]
+    NoEmissionDue,
+    #[msg("Metadata URI exceeds the maximum length")]
+    InvalidMetadataUri,
+    #[msg("Metadata account and Token Metadata program must be passed together")]
+    MetadataAccountsRequired,
+    #[msg("Freezing has been permanently disabled for this token")]
+    FreezingDisabled,
+    #[msg("Issuer already has the maximum number of registered tokens")]
+    IssuerTokenLimitReached,
+    #[msg("Token is already registered under an issuer")]
+    TokenAlreadyRegistered,
+    #[msg("Remaining accounts must be every token config registered to the issuer, in order")]
+    InvalidIssuerAccounts,
+    #[msg("Token still has supply outside the issuer treasury")]
+    SupplyOutstanding,
+    #[msg("Treasury and treasury token account must be passed together and match")]
+    InvalidTreasuryAccount,
+    #[msg("Account is not an allowlist entry of this token")]
+    InvalidAllowlistAccount,
+    #[msg("Delegate is not approved on the allowlist")]
+    DelegateNotApproved,
+    #[msg("Delegate is not approved for this amount on the token account")]
+    InsufficientAllowance,
+    #[msg("Invalid escrow condition: release time must be in the future")]
+    InvalidEscrowCondition,
+    #[msg("Escrow release condition has not been met")]
+    EscrowConditionNotMet,
+    #[msg("Escrow release condition is already met; it can no longer be cancelled")]
+    EscrowConditionMet,
+    #[msg("Only the escrow sender can cancel it")]
+    UnauthorizedEscrowAction,
+    #[msg("Price feed is missing or not the one named by the escrow condition")]
+    InvalidPriceFeed,
+    #[msg("Invalid swap offer: tokens must differ and any expiry must be in the future")]
+    InvalidSwapOffer,
+    #[msg("Swap offer has expired")]
+    SwapOfferExpired,
+    #[msg("Swap offer is reserved for a different taker")]
+    SwapOfferRestricted,
+    #[msg("Only the offer maker can cancel it")]
+    UnauthorizedSwapAction,
+    #[msg("Wrapped supply does not match the shares held in the wrapper vault")]
+    WrapperOutOfBalance,
+    #[msg("Invalid stream: rate must be greater than zero")]
+    InvalidStream,
+    #[msg("Nothing has streamed since the last withdrawal")]
+    NothingStreamed,
+    #[msg("Stream is already in the requested pause state")]
+    InvalidStreamState,
+    #[msg("Only the stream sender can pause or cancel it")]
+    UnauthorizedStreamAction,
+    #[msg("This token requires a travel-rule payload hash on every transfer")]
+    TravelRuleHashRequired,
+    #[msg("Invalid batch accounts: expected one writable account group per amount")]
+    InvalidBatchAccounts,
+    #[msg("Token has transfer controls batch_transfer cannot enforce; use gated_transfer")]
+    BatchTransferUnsupported,
+    #[msg("Sanctions-screened tokens must mint through mint_tokens")]
+    BatchMintUnsupported,
+    #[msg("Invalid airdrop campaign: expiry must be in the future")]
+    InvalidAirdropCampaign,
+    #[msg("Airdrop campaign has expired")]
+    AirdropExpired,
+    #[msg("Airdrop campaign is still open for claims")]
+    AirdropActive,
+    #[msg("Merkle proof does not match the airdrop campaign's root")]
+    InvalidAirdropProof,
+    #[msg("Invalid distribution schedule: interval and claim window must be positive")]
+    InvalidDistributionSchedule,
+    #[msg("Distribution schedule is stopped")]
+    DistributionScheduleInactive,
+    #[msg("The next scheduled distribution is not due yet")]
+    DistributionNotDue,
+    #[msg("Schedule vault holds less than one period's distribution")]
+    DistributionScheduleUnderfunded,
+    #[msg("Account is not a reclaimable compliance account or is still in use")]
+    AccountNotReclaimable,
+    #[msg("Account uses an older layout; run its migrate instruction first")]
+    AccountNotMigrated,
+    #[msg("Account is already at the current layout version")]
+    AccountAlreadyMigrated,
+    #[msg("Allowlist bitmap page has no free slots")]
+    BitmapPageFull,
+    #[msg("Wallet does not hold this allowlist slot")]
+    InvalidAllowlistSlot,
+    #[msg("Token has per-wallet controls the compact allowlist cannot enforce")]
+    CompactAllowlistUnsupported,
+    #[msg("Holder index is enabled: the wallet's holder number accounts are required")]
+    HolderIndexRequired,
+    #[msg("Holder number account does not match the holder index")]
+    InvalidHolderNumber,
+    #[msg("Wallet already holds a number in the holder index")]
+    HolderAlreadyIndexed,
+    #[msg("Transfer journal is enabled: the journal account is required")]
+    TransferJournalRequired,
+    #[msg("CPI callers are restricted: the instructions sysvar is required")]
+    InstructionsSysvarRequired,
+    #[msg("Calling program is not a trusted caller for this token")]
+    UntrustedCpiCaller,
+    #[msg("Transfers are disabled for this token")]
+    TransfersDisabled,
+    #[msg("Minting has been permanently finalized for this token")]
+    MintingFinalized,
+    #[msg("Feature flags contain bits with no meaning")]
+    UnknownFeatureFlags,
+    #[msg("Program is paused for the whole deployment")]
+    ProgramPaused,
+    #[msg("Signer is not the protocol admin")]
+    UnauthorizedProgramAdmin,
+    #[msg("Token creation fee must be paid to the configured fee recipient")]
+    FeeRecipientRequired,
+    #[msg("Claim topics are duplicated, empty for an issuer or over the registry limits")]
+    InvalidClaimTopics,
+    #[msg("Signer is not a trusted issuer for this claim topic")]
+    UntrustedClaimIssuer,
+    #[msg("Claim has already expired")]
+    InvalidClaim,
+    #[msg("Identity holds the maximum number of claims")]
+    IdentityClaimsFull,
+    #[msg("Identity holds no such claim")]
+    ClaimNotFound,
+    #[msg("Only the claim's issuer or the compliance officer may remove it")]
+    UnauthorizedClaimRemoval,
+    #[msg("Identity lacks a valid claim on a required topic")]
+    IdentityNotVerified,
+    #[msg("Identity still holds a valid claim on every required topic")]
+    IdentityStillVerified,
+    #[msg("Wallet is already approved")]
+    WalletAlreadyApproved,
+    #[msg("Wallet was revoked by the token authority")]
+    WalletRevokedByAuthority,
+    #[msg("Bridging is disabled for this token")]
+    BridgeDisabled,
+    #[msg("Bridge target must be a non-zero address on another chain")]
+    InvalidBridgeTarget,
+    #[msg("Token bridge accounts do not match the token's bridge route")]
+    InvalidBridgeAccounts,
+    #[msg("VAA is not a transfer of this token to its bridge route")]
+    InvalidBridgeTransfer,
+    #[msg("Recipient does not match the wallet named in the transfer")]
+    BridgeRecipientMismatch,
+    #[msg("Amount has precision the bridge would truncate")]
+    AmountNotBridgeable,
+    #[msg("No price oracle is registered for this token")]
+    PriceOracleNotSet,
+    #[msg("Price oracle needs a feed and a positive maximum age, or neither when unset")]
+    InvalidPriceOracle,
+    #[msg("Oracle price must be positive")]
+    InvalidOraclePrice,
+    #[msg("Oracle price is older than the token allows")]
+    StaleOraclePrice,
+    #[msg("Oracle confidence interval is too wide to price against")]
+    OraclePriceUncertain,
+    #[msg("Redemption window is priced at NAV; use redeem_at_nav")]
+    RedemptionPricedAtNav,
+    #[msg("Redemption window has a fixed price; use redeem")]
+    RedemptionNotPricedAtNav,
+    #[msg("Market price does not exceed the strike")]
+    OptionsOutOfTheMoney,
+    #[msg("Priced amount is below the minimum the caller accepts")]
+    SlippageExceeded,
+    #[msg("Share certificates are not enabled for this token")]
+    ShareCertificatesDisabled,
+    #[msg("Certificate legend must be 1-200 characters")]
+    InvalidCertificateLegend,
+    #[msg("Holder still has shares in their bound token account")]
+    HolderNotExited,
+    #[msg("Country rule belongs to a different token or country")]
+    CountryRuleMismatch,
+    #[msg("This transfer path does not support a transfer control the token has configured")]
+    TransferPathUnsupported,
+    #[msg("Transfer hook program and validation account must be passed together")]
+    TransferHookAccountsRequired,
+    #[msg("Transfer hook mints must be created under Token-2022")]
+    TransferHookRequiresToken2022,
+    #[msg("This path cannot move shares of a mint with a transfer hook")]
+    TransferHookUnsupported,
+    #[msg("Vaults of a mint with a transfer hook need an allowlist entry for their owner")]
+    VaultAllowlistEntryRequired,
+    #[msg("Forced transfers are disabled for this token")]
+    ForcedTransfersDisabled,
+    #[msg("Forced transfers need a Token-2022 mint with the program as permanent delegate")]
+    ForcedTransferUnsupported,
+    #[msg("A forced transfer must move shares between two different accounts")]
+    InvalidForcedTransfer,
 }
 
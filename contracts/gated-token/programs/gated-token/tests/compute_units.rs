@@ -0,0 +1,302 @@
+//! Compute-unit regression suite for the instructions on the issuance and transfer hot
+//! paths. Each instruction runs once in a representative scenario against the SBF build
+//! and fails if it uses more than its budget in `BUDGETS`, so a rule that makes transfers
+//! heavier has to raise the budget deliberately.
+//!
+//! Needs `target/deploy/gated_token.so`, so it is ignored by plain `cargo test` and run
+//! by the `gated-token` CI workflow after `anchor build`:
+//!
+//!     anchor build
+//!     cargo test -p gated-token --test compute_units -- --ignored --nocapture
+
+use anchor_lang::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::sysvar;
+use solana_sdk::transaction::Transaction;
+use solana_sdk_ids::bpf_loader_upgradeable;
+
+/// Most compute units each benchmarked instruction may consume
+const BUDGETS: &[(&str, u64)] = &[
+    ("initialize_token_registry", 15_000),
+    ("initialize_token", 80_000),
+    ("approve_wallet", 30_000),
+    ("mint_tokens", 45_000),
+    ("gated_transfer", 60_000),
+];
+
+struct Bench {
+    context: ProgramTestContext,
+    usage: Vec<(&'static str, u64)>,
+}
+
+impl Bench {
+    async fn start() -> Self {
+        if std::env::var_os("SBF_OUT_DIR").is_none() {
+            std::env::set_var("SBF_OUT_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy"));
+        }
+        // Deployed under the upgradeable loader so `initialize_program_config` can check
+        // the upgrade authority, which is handed to the payer below
+        let mut program_test = ProgramTest::default();
+        program_test.add_upgradeable_program_to_genesis("gated_token", &gated_token::ID);
+        let mut context = program_test.start_with_context().await;
+        let program_data = program_data_address();
+        let mut account = context.banks_client.get_account(program_data).await.unwrap().unwrap();
+        // ProgramData metadata: 4-byte tag, 8-byte slot, then an optional authority
+        account.data[12] = 1;
+        account.data[13..45].copy_from_slice(context.payer.pubkey().as_ref());
+        context.set_account(&program_data, &account.into());
+        Self {
+            context,
+            usage: Vec::new(),
+        }
+    }
+
+    fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Send `instruction` alone in a transaction paid by the payer. Gated-token
+    /// instructions are recorded under `name`; setup instructions pass `None`.
+    async fn send(&mut self, name: Option<&'static str>, instruction: Instruction, signers: &[&Keypair]) {
+        let mut keys: Vec<&Keypair> = vec![&self.context.payer];
+        keys.extend_from_slice(signers);
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(&[instruction], Some(&self.payer()), &keys, blockhash);
+        let outcome = self.context.banks_client
+            .process_transaction_with_metadata(transaction)
+            .await
+            .unwrap();
+        if let Err(error) = outcome.result {
+            panic!("{} failed: {error}", name.unwrap_or("setup"));
+        }
+        if let Some(name) = name {
+            self.usage.push((name, outcome.metadata.unwrap().compute_units_consumed));
+        }
+    }
+}
+
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &gated_token::ID).0
+}
+
+fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[gated_token::ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/gated_token.so from `anchor build`"]
+async fn hot_paths_stay_within_compute_budgets() {
+    let mut bench = Bench::start().await;
+    let authority = bench.payer();
+    let mint = Keypair::new();
+    let mint_key = mint.pubkey();
+    let token_config = pda(&[b"token_config", mint_key.as_ref()]);
+    let token_registry = pda(&[b"token_registry"]);
+    let mint_authority = pda(&[b"mint_authority", mint_key.as_ref()]);
+    let program_config = pda(&[b"program_config"]);
+
+    bench.send(
+        None,
+        Instruction {
+            program_id: gated_token::ID,
+            accounts: gated_token::accounts::InitializeProgramConfig {
+                admin: authority,
+                program_config,
+                program: gated_token::ID,
+                program_data: program_data_address(),
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: gated_token::instruction::InitializeProgramConfig {
+                parameters: gated_token::ProtocolParameters {
+                    fee_recipient: authority,
+                    token_creation_fee: 0,
+                    default_min_transfer_amount: 0,
+                    default_timelock_seconds: 0,
+                },
+            }
+            .data(),
+        },
+        &[],
+    ).await;
+
+    bench.send(
+        Some("initialize_token_registry"),
+        Instruction {
+            program_id: gated_token::ID,
+            accounts: gated_token::accounts::InitializeTokenRegistry {
+                payer: authority,
+                token_registry,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: gated_token::instruction::InitializeTokenRegistry {}.data(),
+        },
+        &[],
+    ).await;
+
+    bench.send(
+        Some("initialize_token"),
+        Instruction {
+            program_id: gated_token::ID,
+            accounts: gated_token::accounts::InitializeToken {
+                authority,
+                mint_authority,
+                freeze_authority: pda(&[b"freeze_authority", mint_key.as_ref()]),
+                mint: mint_key,
+                token_config,
+                token_registry,
+                program_config,
+                fee_recipient: None,
+                registry_page: pda(&[b"registry_page", &0u32.to_le_bytes()]),
+                metadata: None,
+                token_metadata_program: None,
+                transfer_hook_program: None,
+                extra_account_meta_list: None,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            }
+            .to_account_metas(None),
+            data: gated_token::instruction::InitializeToken {
+                symbol: "BNCH".to_string(),
+                name: "Benchmark Shares".to_string(),
+                decimals: 0,
+                max_supply: None,
+                uri: String::new(),
+            }
+            .data(),
+        },
+        &[&mint],
+    ).await;
+
+    let sender = Keypair::new();
+    let recipient = Keypair::new();
+    let entry = |wallet: &Pubkey| pda(&[b"allowlist", mint_key.as_ref(), wallet.as_ref()]);
+    for (index, wallet) in [sender.pubkey(), recipient.pubkey()].iter().enumerate() {
+        bench.send(
+            (index == 0).then_some("approve_wallet"),
+            Instruction {
+                program_id: gated_token::ID,
+                accounts: gated_token::accounts::ApproveWallet {
+                    authority,
+                    wallet: *wallet,
+                    token_config,
+                    allowlist_entry: entry(wallet),
+                    system_program: system_program::ID,
+                }
+                .to_account_metas(None),
+                data: gated_token::instruction::ApproveWallet {}.data(),
+            },
+            &[],
+        ).await;
+        bench.send(
+            None,
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &authority,
+                wallet,
+                &mint_key,
+                &spl_token::ID,
+            ),
+            &[],
+        ).await;
+    }
+    let sender_account = get_associated_token_address(&sender.pubkey(), &mint_key);
+    let recipient_account = get_associated_token_address(&recipient.pubkey(), &mint_key);
+
+    bench.send(
+        Some("mint_tokens"),
+        Instruction {
+            program_id: gated_token::ID,
+            accounts: gated_token::accounts::MintTokens {
+                authority,
+                recipient: sender.pubkey(),
+                token_config,
+                program_config,
+                mint: mint_key,
+                mint_authority,
+                recipient_token_account: sender_account,
+                recipient_allowlist_entry: entry(&sender.pubkey()),
+                recipient_country_rule: None,
+                recipient_sanctions_flag: None,
+                share_class: None,
+                recipient_snapshot_balance: None,
+                recipient_holder_page: None,
+                holder_index: None,
+                recipient_holder_number: None,
+                instructions: None,
+                trusted_caller: None,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: gated_token::instruction::MintTokens { amount: 1_000 }.data(),
+        },
+        &[],
+    ).await;
+
+    bench.send(
+        Some("gated_transfer"),
+        Instruction {
+            program_id: gated_token::ID,
+            accounts: gated_token::accounts::GatedTransfer {
+                authority: sender.pubkey(),
+                recipient: recipient.pubkey(),
+                token_config,
+                program_config,
+                mint: mint_key,
+                from_token_account: sender_account,
+                to_token_account: recipient_account,
+                sender_allowlist_entry: entry(&sender.pubkey()),
+                recipient_allowlist_entry: entry(&recipient.pubkey()),
+                destination_registry: None,
+                fee_treasury: None,
+                velocity_counter: None,
+                sender_country_rule: None,
+                recipient_country_rule: None,
+                sender_sanctions_flag: None,
+                recipient_sanctions_flag: None,
+                sender_snapshot_balance: None,
+                share_class: None,
+                recipient_snapshot_balance: None,
+                sender_holder_page: None,
+                recipient_holder_page: None,
+                sale_proposal: None,
+                holder_index: None,
+                sender_holder_number: None,
+                last_holder_number: None,
+                recipient_holder_number: None,
+                transfer_journal: None,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            }
+            .to_account_metas(None),
+            data: gated_token::instruction::GatedTransfer {
+                amount: 400,
+                travel_rule_hash: None,
+            }
+            .data(),
+        },
+        &[&sender],
+    ).await;
+
+    println!("{:<28} {:>8} {:>8}", "instruction", "units", "budget");
+    let mut over_budget = Vec::new();
+    for (name, units) in &bench.usage {
+        let budget = BUDGETS.iter()
+            .find(|(budgeted, _)| budgeted == name)
+            .map(|(_, budget)| *budget)
+            .unwrap_or_else(|| panic!("{name} has no compute budget"));
+        println!("{name:<28} {units:>8} {budget:>8}");
+        if *units > budget {
+            over_budget.push(format!("{name} used {units} CU, budget {budget}"));
+        }
+    }
+    assert!(over_budget.is_empty(), "compute budget regressions: {over_budget:?}");
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+
+declare_id!("CPvx2KLLNKTkTW33ENSXo8RpCWwCrVm1HAKxwL8urTBk");
+
+/// Test-only KYC/attestation provider. A provider signs attestations about
+/// subject wallets (KYC level, jurisdiction, sanctions flag) with an expiry.
+#[program]
+pub mod mock_attestation {
+    use super::*;
+
+    /// Issue (or refresh) an attestation for a subject wallet
+    pub fn issue_attestation(
+        ctx: Context<IssueAttestation>,
+        kyc_level: u8,
+        country_code: [u8; 2],
+        is_sanctioned: bool,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(expires_at > clock.unix_timestamp, ErrorCode::InvalidExpiry);
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.provider = ctx.accounts.provider.key();
+        attestation.subject = ctx.accounts.subject.key();
+        attestation.kyc_level = kyc_level;
+        attestation.country_code = country_code;
+        attestation.is_sanctioned = is_sanctioned;
+        attestation.is_revoked = false;
+        attestation.issued_at = clock.unix_timestamp;
+        attestation.expires_at = expires_at;
+        attestation.bump = ctx.bumps.attestation;
+
+        emit!(AttestationIssuedEvent {
+            provider: attestation.provider,
+            subject: attestation.subject,
+            kyc_level,
+            is_sanctioned,
+            expires_at,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke an attestation
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.is_revoked = true;
+
+        emit!(AttestationRevokedEvent {
+            provider: attestation.provider,
+            subject: attestation.subject,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct Attestation {
+    pub provider: Pubkey,
+    pub subject: Pubkey,
+    pub kyc_level: u8,
+    pub country_code: [u8; 2],
+    pub is_sanctioned: bool,
+    pub is_revoked: bool,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl Attestation {
+    /// Whether the attestation is currently usable for gating
+    pub fn is_valid(&self, now: i64) -> bool {
+        !self.is_revoked && now < self.expires_at
+    }
+}
+
+// Context structures
+#[derive(Accounts)]
+pub struct IssueAttestation<'info> {
+    #[account(mut)]
+    pub provider: Signer<'info>,
+
+    /// CHECK: Wallet the attestation is about
+    pub subject: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + 32 + 32 + 1 + 2 + 1 + 1 + 8 + 8 + 1,
+        seeds = [b"attestation", provider.key().as_ref(), subject.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    pub provider: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"attestation", provider.key().as_ref(), attestation.subject.as_ref()],
+        bump = attestation.bump,
+        has_one = provider @ ErrorCode::UnauthorizedProvider
+    )]
+    pub attestation: Account<'info, Attestation>,
+}
+
+// Events
+#[event]
+pub struct AttestationIssuedEvent {
+    pub provider: Pubkey,
+    pub subject: Pubkey,
+    pub kyc_level: u8,
+    pub is_sanctioned: bool,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AttestationRevokedEvent {
+    pub provider: Pubkey,
+    pub subject: Pubkey,
+}
+
+// Error codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+
+    #[msg("Unauthorized: only the issuing provider can revoke")]
+    UnauthorizedProvider,
+}
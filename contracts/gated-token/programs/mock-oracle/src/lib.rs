@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+declare_id!("GrxsbAvCJBR2QnfB1adPTgtbdEgLbMRgtQxYSep4eBbX");
+
+/// Test-only price oracle. Feeds use a Pyth-style (price, confidence, exponent)
+/// representation so consumers can be exercised on localnet without external feeds.
+#[program]
+pub mod mock_oracle {
+    use super::*;
+
+    /// Create a price feed controlled by the signer
+    pub fn initialize_price_feed(
+        ctx: Context<InitializePriceFeed>,
+        price: i64,
+        conf: u64,
+        expo: i32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.authority = ctx.accounts.authority.key();
+        price_feed.price = price;
+        price_feed.conf = conf;
+        price_feed.expo = expo;
+        price_feed.publish_time = clock.unix_timestamp;
+
+        emit!(PriceUpdatedEvent {
+            price_feed: price_feed.key(),
+            price,
+            conf,
+            expo,
+            publish_time: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Publish a new price
+    pub fn set_price(ctx: Context<SetPrice>, price: i64, conf: u64) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let price_feed = &mut ctx.accounts.price_feed;
+        price_feed.price = price;
+        price_feed.conf = conf;
+        price_feed.publish_time = clock.unix_timestamp;
+
+        emit!(PriceUpdatedEvent {
+            price_feed: price_feed.key(),
+            price,
+            conf,
+            expo: price_feed.expo,
+            publish_time: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Backdate the publish time so staleness checks can be tested
+    pub fn set_publish_time(ctx: Context<SetPrice>, publish_time: i64) -> Result<()> {
+        ctx.accounts.price_feed.publish_time = publish_time;
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// Context structures
+#[derive(Accounts)]
+pub struct InitializePriceFeed<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 4 + 8,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPrice<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = authority @ ErrorCode::UnauthorizedAuthority
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+}
+
+// Events
+#[event]
+pub struct PriceUpdatedEvent {
+    pub price_feed: Pubkey,
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// Error codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized: only the feed authority can publish prices")]
+    UnauthorizedAuthority,
+}
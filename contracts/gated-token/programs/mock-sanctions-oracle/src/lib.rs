@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+declare_id!("Ead4D7MMEKw1Fr3knf62AW9wjitiSREWS9FeWE9tZS4E");
+
+/// Test-only sanctions screening oracle. A screener maintains one flag
+/// account per wallet at `[b"sanctions_flag", wallet]`, which gated-token
+/// reads during mints and transfers.
+#[program]
+pub mod mock_sanctions_oracle {
+    use super::*;
+
+    /// Create or update the sanctions flag for a wallet. The first screener
+    /// to flag a wallet owns its flag account.
+    pub fn set_sanctions_flag(ctx: Context<SetSanctionsFlag>, is_sanctioned: bool) -> Result<()> {
+        let clock = Clock::get()?;
+        let flag = &mut ctx.accounts.sanctions_flag;
+
+        if flag.screener == Pubkey::default() {
+            flag.wallet = ctx.accounts.wallet.key();
+            flag.screener = ctx.accounts.screener.key();
+            flag.bump = ctx.bumps.sanctions_flag;
+        }
+        require_keys_eq!(flag.screener, ctx.accounts.screener.key(), ErrorCode::UnauthorizedScreener);
+
+        flag.is_sanctioned = is_sanctioned;
+        flag.updated_at = clock.unix_timestamp;
+
+        emit!(SanctionsFlagUpdatedEvent {
+            wallet: flag.wallet,
+            screener: flag.screener,
+            is_sanctioned,
+        });
+
+        Ok(())
+    }
+}
+
+// Account structures
+#[account]
+pub struct SanctionsFlag {
+    pub wallet: Pubkey,
+    pub is_sanctioned: bool,
+    pub screener: Pubkey,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+// Context structures
+#[derive(Accounts)]
+pub struct SetSanctionsFlag<'info> {
+    #[account(mut)]
+    pub screener: Signer<'info>,
+
+    /// CHECK: Wallet being screened
+    pub wallet: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = screener,
+        space = 8 + 32 + 1 + 32 + 8 + 1,
+        seeds = [b"sanctions_flag", wallet.key().as_ref()],
+        bump
+    )]
+    pub sanctions_flag: Account<'info, SanctionsFlag>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Events
+#[event]
+pub struct SanctionsFlagUpdatedEvent {
+    pub wallet: Pubkey,
+    pub screener: Pubkey,
+    pub is_sanctioned: bool,
+}
+
+// Error codes
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Unauthorized: only the screener that created this flag can update it")]
+    UnauthorizedScreener,
+}
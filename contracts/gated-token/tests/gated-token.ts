@@ -2,8 +2,62 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { GatedToken } from "../target/types/gated_token";
 import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, createMint, createAccount, getAccount } from "@solana/spl-token";
+import {
+    TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    createMint,
+    createAccount,
+    getAccount,
+    getMint,
+    getAssociatedTokenAddressSync,
+    mintTo,
+} from "@solana/spl-token";
+import {
+    ConcurrentMerkleTreeAccount,
+    MerkleTree,
+    SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    SPL_NOOP_PROGRAM_ID,
+    createAllocTreeIx,
+} from "@solana/spl-account-compression";
 import { assert } from "chai";
+import { createHash } from "crypto";
+import { createPriceFeed, mockOracleProgram } from "./helpers/mocks";
+
+/** Leaf committed to by a distribution snapshot root: sha256("snapshot" || holder || balance_le) */
+function snapshotLeaf(holder: PublicKey, balance: anchor.BN): Buffer {
+    return createHash("sha256")
+        .update(Buffer.from("snapshot"))
+        .update(holder.toBuffer())
+        .update(balance.toArrayLike(Buffer, "le", 8))
+        .digest();
+}
+
+/** Leaf committed to by an airdrop campaign root: sha256("airdrop" || wallet || amount_le) */
+function airdropLeaf(wallet: PublicKey, amount: anchor.BN): Buffer {
+    return createHash("sha256")
+        .update(Buffer.from("airdrop"))
+        .update(wallet.toBuffer())
+        .update(amount.toArrayLike(Buffer, "le", 8))
+        .digest();
+}
+
+/** Parent of two snapshot tree nodes, hashed in sorted order */
+function snapshotParent(a: Buffer, b: Buffer): Buffer {
+    const [first, second] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+    return createHash("sha256").update(first).update(second).digest();
+}
+
+/** Leaf committed to by a bitmap page's assignment tree: sha256("allowlist_slot" || wallet || slot_le) */
+function allowlistSlotLeaf(wallet: PublicKey, slot: number): Buffer {
+    const slotBytes = Buffer.alloc(4);
+    slotBytes.writeUInt32LE(slot);
+    return createHash("sha256")
+        .update(Buffer.from("allowlist_slot"))
+        .update(wallet.toBuffer())
+        .update(slotBytes)
+        .digest();
+}
 
 describe("gated-token", () => {
     // Configure the client to use the local cluster
@@ -16,6 +70,10 @@ describe("gated-token", () => {
     let authority: Keypair;
     let mint: Keypair;
     let tokenConfig: PublicKey;
+    let mintAuthority: PublicKey;
+    let freezeAuthority: PublicKey;
+    let tokenRegistry: PublicKey;
+    let programConfig: PublicKey;
     let aliceKeypair: Keypair;
     let bobKeypair: Keypair;
     let charlieKeypair: Keypair;
@@ -40,6 +98,16 @@ describe("gated-token", () => {
             program.programId
         );
 
+        [mintAuthority] = await PublicKey.findProgramAddress(
+            [Buffer.from("mint_authority"), mint.publicKey.toBuffer()],
+            program.programId
+        );
+
+        [freezeAuthority] = await PublicKey.findProgramAddress(
+            [Buffer.from("freeze_authority"), mint.publicKey.toBuffer()],
+            program.programId
+        );
+
         [aliceAllowlist] = await PublicKey.findProgramAddress(
             [Buffer.from("allowlist"), mint.publicKey.toBuffer(), aliceKeypair.publicKey.toBuffer()],
             program.programId
@@ -55,6 +123,41 @@ describe("gated-token", () => {
             program.programId
         );
 
+        // The program-wide token registry is created once per deployment
+        [tokenRegistry] = await PublicKey.findProgramAddress(
+            [Buffer.from("token_registry")],
+            program.programId
+        );
+        if (!(await provider.connection.getAccountInfo(tokenRegistry))) {
+            await program.methods
+                .initializeTokenRegistry()
+                .accounts({
+                    payer: authority.publicKey,
+                    tokenRegistry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        }
+
+        // So is the program config, by the upgrade authority that deployed the program
+        [programConfig] = PublicKey.findProgramAddressSync([Buffer.from("program_config")], program.programId);
+        if (!(await provider.connection.getAccountInfo(programConfig))) {
+            const [programData] = PublicKey.findProgramAddressSync(
+                [program.programId.toBuffer()],
+                new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+            );
+            await program.methods
+                .initializeProgramConfig(defaultParameters())
+                .accounts({
+                    admin: authority.publicKey,
+                    programConfig,
+                    program: program.programId,
+                    programData,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        }
+
         // Airdrop SOL to test wallets
         const airdropAmount = 2 * anchor.web3.LAMPORTS_PER_SOL;
         await provider.connection.confirmTransaction(
@@ -68,17 +171,86 @@ describe("gated-token", () => {
         );
     });
 
+    /** Protocol parameters the suite runs under: no creation fee and no token defaults */
+    const defaultParameters = () => ({
+        feeRecipient: authority.publicKey,
+        tokenCreationFee: new anchor.BN(0),
+        defaultMinTransferAmount: new anchor.BN(0),
+        defaultTimelockSeconds: new anchor.BN(0),
+    });
+
+    /** Create a classic SPL gated token with the given decimals, returning its config PDA */
+    const initShareToken = async (
+        shareMint: Keypair,
+        decimals: number,
+        symbol = "SHRZ",
+        name = "Whole Share Common",
+        uri = ""
+    ): Promise<PublicKey> => {
+        const pda = (seed: string) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from(seed), shareMint.publicKey.toBuffer()],
+                program.programId
+            )[0];
+        const registry = await program.account.tokenRegistry.fetch(tokenRegistry);
+        const pageIndex = Buffer.alloc(4);
+        pageIndex.writeUInt32LE(Math.floor(registry.totalTokens.toNumber() / 64));
+        const [registryPage] = PublicKey.findProgramAddressSync(
+            [Buffer.from("registry_page"), pageIndex],
+            program.programId
+        );
+        await program.methods
+            .initializeToken(symbol, name, decimals, null, uri)
+            .accounts({
+                authority: authority.publicKey,
+                mint: shareMint.publicKey,
+                mintAuthority: pda("mint_authority"),
+                freezeAuthority: pda("freeze_authority"),
+                tokenConfig: pda("token_config"),
+                tokenRegistry,
+                registryPage,
+                metadata: null,
+                tokenMetadataProgram: null,
+                transferHookProgram: null,
+                extraAccountMetaList: null,
+                feeRecipient: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            })
+            .signers([shareMint])
+            .rpc();
+        return pda("token_config");
+    };
+
     it("Test 1: Initialize token with metadata", async () => {
         const symbol = "ACME";
         const name = "ACME Security Token";
         const decimals = 9;
 
+        const registry = await program.account.tokenRegistry.fetch(tokenRegistry);
+        const pageIndex = Buffer.alloc(4);
+        pageIndex.writeUInt32LE(Math.floor(registry.totalTokens.toNumber() / 64));
+        const [registryPage] = await PublicKey.findProgramAddress(
+            [Buffer.from("registry_page"), pageIndex],
+            program.programId
+        );
+
         await program.methods
-            .initializeToken(symbol, name, decimals)
+            .initializeToken(symbol, name, decimals, null, "")
             .accounts({
                 authority: authority.publicKey,
                 mint: mint.publicKey,
+                mintAuthority,
+                freezeAuthority,
                 tokenConfig,
+                tokenRegistry,
+                registryPage,
+                metadata: null,
+                tokenMetadataProgram: null,
+                transferHookProgram: null,
+                extraAccountMetaList: null,
+                feeRecipient: null,
                 tokenProgram: TOKEN_PROGRAM_ID,
                 systemProgram: SystemProgram.programId,
                 rent: anchor.web3.SYSVAR_RENT_PUBKEY,
@@ -91,6 +263,9 @@ describe("gated-token", () => {
         assert.equal(configAccount.name, name);
         assert.equal(configAccount.decimals, decimals);
         assert.equal(configAccount.totalSupply.toNumber(), 0);
+
+        const page = await program.account.registryPage.fetch(registryPage);
+        assert.isTrue(page.entries.some((entry) => entry.mint.equals(mint.publicKey)));
         console.log("✓ Token initialized successfully");
     });
 
@@ -128,9 +303,16 @@ describe("gated-token", () => {
                 recipient: aliceKeypair.publicKey,
                 tokenConfig,
                 mint: mint.publicKey,
+                mintAuthority,
                 recipientTokenAccount: aliceTokenAccount,
                 recipientAllowlistEntry: aliceAllowlist,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
                 tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
             })
             .rpc();
 
@@ -163,7 +345,7 @@ describe("gated-token", () => {
         // Transfer from Alice to Bob
         const transferAmount = new anchor.BN(3_000 * Math.pow(10, 9));
         await program.methods
-            .gatedTransfer(transferAmount)
+            .gatedTransfer(transferAmount, null)
             .accounts({
                 authority: aliceKeypair.publicKey,
                 recipient: bobKeypair.publicKey,
@@ -173,7 +355,21 @@ describe("gated-token", () => {
                 toTokenAccount: bobTokenAccount,
                 senderAllowlistEntry: aliceAllowlist,
                 recipientAllowlistEntry: bobAllowlist,
+                destinationRegistry: null,
+                feeTreasury: null,
+                velocityCounter: null,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                senderSnapshotBalance: null,
+                recipientSnapshotBalance: null,
+                senderHolderPage: null,
+                recipientHolderPage: null,
+                saleProposal: null,
                 tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
             })
             .signers([aliceKeypair])
             .rpc();
@@ -196,7 +392,7 @@ describe("gated-token", () => {
 
         try {
             await program.methods
-                .gatedTransfer(transferAmount)
+                .gatedTransfer(transferAmount, null)
                 .accounts({
                     authority: aliceKeypair.publicKey,
                     recipient: charlieKeypair.publicKey,
@@ -206,7 +402,21 @@ describe("gated-token", () => {
                     toTokenAccount: charlieTokenAccount,
                     senderAllowlistEntry: aliceAllowlist,
                     recipientAllowlistEntry: charlieAllowlist,
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
                     tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
                 })
                 .signers([aliceKeypair])
                 .rpc();
@@ -223,7 +433,7 @@ describe("gated-token", () => {
 
         try {
             await program.methods
-                .gatedTransfer(transferAmount)
+                .gatedTransfer(transferAmount, null)
                 .accounts({
                     authority: charlieKeypair.publicKey,
                     recipient: bobKeypair.publicKey,
@@ -233,7 +443,21 @@ describe("gated-token", () => {
                     toTokenAccount: bobTokenAccount,
                     senderAllowlistEntry: charlieAllowlist,
                     recipientAllowlistEntry: bobAllowlist,
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
                     tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
                 })
                 .signers([charlieKeypair])
                 .rpc();
@@ -262,7 +486,7 @@ describe("gated-token", () => {
         // Verify Alice can transfer to Charlie
         const transferAmount1 = new anchor.BN(2_000 * Math.pow(10, 9));
         await program.methods
-            .gatedTransfer(transferAmount1)
+            .gatedTransfer(transferAmount1, null)
             .accounts({
                 authority: aliceKeypair.publicKey,
                 recipient: charlieKeypair.publicKey,
@@ -272,7 +496,21 @@ describe("gated-token", () => {
                 toTokenAccount: charlieTokenAccount,
                 senderAllowlistEntry: aliceAllowlist,
                 recipientAllowlistEntry: charlieAllowlist,
+                destinationRegistry: null,
+                feeTreasury: null,
+                velocityCounter: null,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                senderSnapshotBalance: null,
+                recipientSnapshotBalance: null,
+                senderHolderPage: null,
+                recipientHolderPage: null,
+                saleProposal: null,
                 tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
             })
             .signers([aliceKeypair])
             .rpc();
@@ -287,6 +525,7 @@ describe("gated-token", () => {
                 wallet: charlieKeypair.publicKey,
                 tokenConfig,
                 allowlistEntry: charlieAllowlist,
+                countryRule: null,
             })
             .rpc();
 
@@ -297,7 +536,7 @@ describe("gated-token", () => {
         // Try to transfer to revoked Charlie
         try {
             await program.methods
-                .gatedTransfer(transferAmount1)
+                .gatedTransfer(transferAmount1, null)
                 .accounts({
                     authority: aliceKeypair.publicKey,
                     recipient: charlieKeypair.publicKey,
@@ -307,7 +546,21 @@ describe("gated-token", () => {
                     toTokenAccount: charlieTokenAccount,
                     senderAllowlistEntry: aliceAllowlist,
                     recipientAllowlistEntry: charlieAllowlist,
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
                     tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
                 })
                 .signers([aliceKeypair])
                 .rpc();
@@ -362,5 +615,3024 @@ describe("gated-token", () => {
         console.log(`Charlie: ${charlieBalance.amount} (${(Number(charlieBalance.amount) / totalSupply * 100).toFixed(2)}%)`);
         console.log(`Total Supply: ${totalSupply}`);
     });
-});
 
+    it("Test 9: Supply drift trips circuit breaker → reset", async () => {
+        const [circuitBreaker] = await PublicKey.findProgramAddress(
+            [Buffer.from("circuit_breaker"), mint.publicKey.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .initializeCircuitBreaker(new anchor.BN(0), [authority.publicKey], 1)
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                circuitBreaker,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        // Mint outside the program so the recorded supply drifts from the mint
+        await mintTo(provider.connection, authority, mint.publicKey, aliceTokenAccount, authority, 1);
+
+        await program.methods
+            .checkInvariants(null)
+            .accounts({
+                caller: authority.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                circuitBreaker,
+            })
+            .rpc();
+
+        let configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.isTrue(configAccount.paused);
+        assert.isTrue(configAccount.breakerTripped);
+        console.log("✓ Circuit breaker tripped on supply drift");
+
+        try {
+            await program.methods
+                .gatedTransfer(new anchor.BN(1), null)
+                .accounts({
+                    authority: aliceKeypair.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig,
+                    mint: mint.publicKey,
+                    fromTokenAccount: aliceTokenAccount,
+                    toTokenAccount: bobTokenAccount,
+                    senderAllowlistEntry: aliceAllowlist,
+                    recipientAllowlistEntry: bobAllowlist,
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([aliceKeypair])
+                .rpc();
+
+            assert.fail("Transfer should have failed - token paused");
+        } catch (error) {
+            console.log("✓ Transfer blocked while breaker is tripped");
+        }
+
+        await program.methods
+            .resetCircuitBreaker(true)
+            .accounts({
+                tokenConfig,
+                mint: mint.publicKey,
+                circuitBreaker,
+            })
+            .remainingAccounts([{ pubkey: authority.publicKey, isSigner: true, isWritable: false }])
+            .rpc();
+
+        configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.isFalse(configAccount.paused);
+        assert.isFalse(configAccount.breakerTripped);
+        console.log("✓ Circuit breaker reset with reconciled supply");
+    });
+
+    it("Test 10: Onboard investor in one transaction with lockup", async () => {
+        const dave = Keypair.generate();
+        const [daveAllowlist] = await PublicKey.findProgramAddress(
+            [Buffer.from("allowlist"), mint.publicKey.toBuffer(), dave.publicKey.toBuffer()],
+            program.programId
+        );
+        const daveTokenAccount = getAssociatedTokenAddressSync(mint.publicKey, dave.publicKey);
+        const lockupUntil = new anchor.BN(Math.floor(Date.now() / 1000) + 86_400);
+        const initialAmount = new anchor.BN(500 * Math.pow(10, 9));
+
+        await program.methods
+            .onboardInvestor(lockupUntil, initialAmount)
+            .accounts({
+                sponsor: authority.publicKey,
+                authority: authority.publicKey,
+                investor: dave.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                mintAuthority,
+                allowlistEntry: daveAllowlist,
+                investorTokenAccount: daveTokenAccount,
+                shareClass: null,
+                snapshotBalance: null,
+                holderPage: null,
+                instructions: null,
+                trustedCaller: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const daveEntry = await program.account.allowlistEntry.fetch(daveAllowlist);
+        assert.isTrue(daveEntry.isApproved);
+        assert.equal(daveEntry.lockupUntil.toString(), lockupUntil.toString());
+        const daveBalance = await getAccount(provider.connection, daveTokenAccount);
+        assert.equal(daveBalance.amount.toString(), initialAmount.toString());
+        console.log("✓ Dave approved, ATA created, and initial allocation minted");
+
+        try {
+            await program.methods
+                .gatedTransfer(new anchor.BN(1), null)
+                .accounts({
+                    authority: dave.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig,
+                    mint: mint.publicKey,
+                    fromTokenAccount: daveTokenAccount,
+                    toTokenAccount: bobTokenAccount,
+                    senderAllowlistEntry: daveAllowlist,
+                    recipientAllowlistEntry: bobAllowlist,
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([dave])
+                .rpc();
+
+            assert.fail("Transfer should have failed - sender locked up");
+        } catch (error) {
+            console.log("✓ Locked-up investor cannot transfer");
+        }
+    });
+
+    it("Test 11: SOL dividend paid pro-rata against a snapshot root, once per holder", async () => {
+        const aliceBalance = new anchor.BN(3_000);
+        const bobBalance = new anchor.BN(1_000);
+        const aliceLeaf = snapshotLeaf(aliceKeypair.publicKey, aliceBalance);
+        const bobLeaf = snapshotLeaf(bobKeypair.publicKey, bobBalance);
+        const snapshotRoot = snapshotParent(aliceLeaf, bobLeaf);
+
+        const configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        const [distribution] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("distribution"),
+                mint.publicKey.toBuffer(),
+                configAccount.nextDistributionId.toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+        );
+        const totalAmount = new anchor.BN(1_000_000);
+
+        await program.methods
+            .createDistribution(
+                new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+                Array.from(snapshotRoot),
+                aliceBalance.add(bobBalance),
+                totalAmount
+            )
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                distribution,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [aliceClaim] = PublicKey.findProgramAddressSync(
+            [Buffer.from("dividend_claim"), distribution.toBuffer(), aliceKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        const claimDividend = () =>
+            program.methods
+                .claimDividend(aliceBalance, [Array.from(bobLeaf)])
+                .accounts({
+                    holder: aliceKeypair.publicKey,
+                    distribution,
+                    dividendClaim: aliceClaim,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([aliceKeypair])
+                .rpc();
+
+        await claimDividend();
+
+        const claim = await program.account.dividendClaim.fetch(aliceClaim);
+        assert.equal(claim.amount.toNumber(), 750_000);
+        console.log("✓ Alice claimed 75% of the distribution");
+
+        try {
+            await claimDividend();
+            assert.fail("Second claim should have failed");
+        } catch (error) {
+            console.log("✓ Double claim rejected");
+        }
+    });
+
+    it("Test 12: USDC dividend escrowed in a vault and claimed into an allowlisted account", async () => {
+        const usdcMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+        const fundingAccount = await createAccount(provider.connection, authority, usdcMint, authority.publicKey);
+        await mintTo(provider.connection, authority, usdcMint, fundingAccount, authority, 5_000_000);
+        const aliceUsdcAccount = await createAccount(provider.connection, aliceKeypair, usdcMint, aliceKeypair.publicKey);
+
+        // Single-holder snapshot: the root is Alice's leaf and her proof is empty
+        const aliceBalance = new anchor.BN(1_000);
+        const snapshotRoot = snapshotLeaf(aliceKeypair.publicKey, aliceBalance);
+
+        const configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        const [distribution] = PublicKey.findProgramAddressSync(
+            [
+                Buffer.from("distribution"),
+                mint.publicKey.toBuffer(),
+                configAccount.nextDistributionId.toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+        );
+        const [distributionVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("distribution_vault"), distribution.toBuffer()],
+            program.programId
+        );
+
+        await program.methods
+            .createTokenDistribution(
+                new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+                Array.from(snapshotRoot),
+                aliceBalance,
+                new anchor.BN(5_000_000),
+                new anchor.BN(Math.floor(Date.now() / 1000) + 86_400)
+            )
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                distribution,
+                payoutMint: usdcMint,
+                distributionVault,
+                fundingTokenAccount: fundingAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [aliceClaim] = PublicKey.findProgramAddressSync(
+            [Buffer.from("dividend_claim"), distribution.toBuffer(), aliceKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .claimTokenDividend(aliceBalance, [])
+            .accounts({
+                holder: aliceKeypair.publicKey,
+                distribution,
+                payoutMint: usdcMint,
+                distributionVault,
+                holderTokenAccount: aliceUsdcAccount,
+                holderAllowlistEntry: aliceAllowlist,
+                tokenConfig,
+                dividendClaim: aliceClaim,
+                mint: null,
+                shareTokenAccount: null,
+                mintAuthority: null,
+                treasury: null,
+                treasuryPaymentAccount: null,
+                shareClass: null,
+                holderSnapshotBalance: null,
+                holderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        const aliceUsdc = await getAccount(provider.connection, aliceUsdcAccount);
+        assert.equal(aliceUsdc.amount.toString(), "5000000");
+        console.log("✓ Alice claimed her USDC dividend");
+
+        try {
+            await program.methods
+                .sweepDistribution()
+                .accounts({
+                    authority: authority.publicKey,
+                    tokenConfig,
+                    distribution,
+                    payoutMint: usdcMint,
+                    distributionVault,
+                    destinationTokenAccount: fundingAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+
+            assert.fail("Sweep should have failed - claim period still open");
+        } catch (error) {
+            console.log("✓ Sweep rejected before the claim deadline");
+        }
+    });
+
+    it("Test 13: Snapshot checkpoints a holder's balance on their first post-snapshot transfer", async () => {
+        let configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        const snapshotId = configAccount.currentSnapshotId.addn(1);
+        const snapshotIdBytes = snapshotId.toArrayLike(Buffer, "le", 8);
+        const [snapshot] = PublicKey.findProgramAddressSync(
+            [Buffer.from("snapshot"), mint.publicKey.toBuffer(), snapshotIdBytes],
+            program.programId
+        );
+
+        await program.methods
+            .createSnapshot()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                snapshot,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const snapshotAccount = await program.account.snapshot.fetch(snapshot);
+        assert.equal(snapshotAccount.totalSupply.toString(), configAccount.totalSupply.toString());
+
+        const [aliceCheckpoint] = PublicKey.findProgramAddressSync(
+            [Buffer.from("snapshot_balance"), mint.publicKey.toBuffer(), snapshotIdBytes, aliceKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        const [bobCheckpoint] = PublicKey.findProgramAddressSync(
+            [Buffer.from("snapshot_balance"), mint.publicKey.toBuffer(), snapshotIdBytes, bobKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        const aliceBefore = await getAccount(provider.connection, aliceTokenAccount);
+
+        await program.methods
+            .gatedTransfer(new anchor.BN(1_000), null)
+            .accounts({
+                authority: aliceKeypair.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                fromTokenAccount: aliceTokenAccount,
+                toTokenAccount: bobTokenAccount,
+                senderAllowlistEntry: aliceAllowlist,
+                recipientAllowlistEntry: bobAllowlist,
+                destinationRegistry: null,
+                feeTreasury: null,
+                velocityCounter: null,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                senderSnapshotBalance: aliceCheckpoint,
+                recipientSnapshotBalance: bobCheckpoint,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        const checkpoint = await program.account.snapshotBalance.fetch(aliceCheckpoint);
+        assert.equal(checkpoint.balance.toString(), aliceBefore.amount.toString());
+        assert.equal(checkpoint.snapshotId.toString(), snapshotId.toString());
+        const aliceEntry = await program.account.allowlistEntry.fetch(aliceAllowlist);
+        assert.equal(aliceEntry.lastSnapshotId.toString(), snapshotId.toString());
+        console.log("✓ Alice's record-date balance checkpointed before her transfer");
+    });
+
+    it("Test 14: In-place split and reverse split update the cumulative factor", async () => {
+        const before = await program.account.tokenConfig.fetch(tokenConfig);
+        const executeSplit = (numerator: number, denominator: number) =>
+            program.methods
+                .executeSplit(new anchor.BN(numerator), new anchor.BN(denominator))
+                .accounts({
+                    authority: authority.publicKey,
+                    tokenConfig,
+                    mint: mint.publicKey,
+                })
+                .rpc();
+
+        await executeSplit(2, 1);
+
+        let configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(configAccount.splitNumerator.toNumber(), 2);
+        assert.equal(configAccount.splitDenominator.toNumber(), 1);
+        assert.equal(configAccount.totalSupply.toString(), before.totalSupply.muln(2).toString());
+        console.log("✓ 2-for-1 split doubled the recorded supply");
+
+        await executeSplit(1, 2);
+
+        configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(configAccount.splitNumerator.toNumber(), 1);
+        assert.equal(configAccount.splitDenominator.toNumber(), 1);
+        assert.equal(configAccount.totalSupply.toString(), before.totalSupply.toString());
+        console.log("✓ 1-for-2 reverse split restored the original factor");
+    });
+
+    it("Test 15: Authority handover needs the proposed key to accept", async () => {
+        await program.methods
+            .proposeAuthority(Keypair.generate().publicKey)
+            .accounts({ authority: authority.publicKey, tokenConfig, timelock: null })
+            .rpc();
+        await program.methods
+            .cancelAuthorityProposal()
+            .accounts({ authority: authority.publicKey, tokenConfig })
+            .rpc();
+        let configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.ok(configAccount.pendingAuthority.equals(PublicKey.default));
+        console.log("✓ Mistyped proposal cancelled before it took effect");
+
+        const successor = Keypair.generate();
+        const handover = (from: Keypair, to: Keypair) =>
+            program.methods
+                .proposeAuthority(to.publicKey)
+                .accounts({ authority: from.publicKey, tokenConfig, timelock: null })
+                .signers([from])
+                .rpc()
+                .then(() =>
+                    program.methods
+                        .acceptAuthority()
+                        .accounts({
+                            authority: from.publicKey,
+                            pendingAuthority: to.publicKey,
+                            tokenConfig,
+                        })
+                        .signers([from, to])
+                        .rpc()
+                );
+
+        await handover(authority, successor);
+        configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.ok(configAccount.authority.equals(successor.publicKey));
+        assert.ok(configAccount.pendingAuthority.equals(PublicKey.default));
+        console.log("✓ Successor accepted and now holds the authority");
+
+        // Hand control back so the suite's admin remains in charge
+        await handover(successor, authority);
+        configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.ok(configAccount.authority.equals(authority.publicKey));
+    });
+
+    it("Test 16: Holder burns shares and total supply drops", async () => {
+        const before = await program.account.tokenConfig.fetch(tokenConfig);
+        const burnAmount = new anchor.BN(1_000 * Math.pow(10, 9));
+        const bobBefore = await getAccount(provider.connection, bobTokenAccount);
+
+        await program.methods
+            .burnTokens(burnAmount)
+            .accounts({
+                holder: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                holderTokenAccount: bobTokenAccount,
+                holderAllowlistEntry: bobAllowlist,
+                holderSnapshotBalance: null,
+                holderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([bobKeypair])
+            .rpc();
+
+        const bobAfter = await getAccount(provider.connection, bobTokenAccount);
+        assert.equal((bobBefore.amount - bobAfter.amount).toString(), burnAmount.toString());
+        const configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(
+            before.totalSupply.sub(configAccount.totalSupply).toString(),
+            burnAmount.toString()
+        );
+        console.log(`✓ Bob burned ${burnAmount.toString()} tokens`);
+    });
+
+    it("Test 17: Token-2022 mint is initialized and minted to an approved wallet", async () => {
+        const mint2022 = Keypair.generate();
+        const [tokenConfig2022] = PublicKey.findProgramAddressSync(
+            [Buffer.from("token_config"), mint2022.publicKey.toBuffer()],
+            program.programId
+        );
+        const [mintAuthority2022] = PublicKey.findProgramAddressSync(
+            [Buffer.from("mint_authority"), mint2022.publicKey.toBuffer()],
+            program.programId
+        );
+        const [freezeAuthority2022] = PublicKey.findProgramAddressSync(
+            [Buffer.from("freeze_authority"), mint2022.publicKey.toBuffer()],
+            program.programId
+        );
+        const [aliceAllowlist2022] = PublicKey.findProgramAddressSync(
+            [Buffer.from("allowlist"), mint2022.publicKey.toBuffer(), aliceKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        const registry = await program.account.tokenRegistry.fetch(tokenRegistry);
+        const pageIndex = Buffer.alloc(4);
+        pageIndex.writeUInt32LE(Math.floor(registry.totalTokens.toNumber() / 64));
+        const [registryPage] = PublicKey.findProgramAddressSync(
+            [Buffer.from("registry_page"), pageIndex],
+            program.programId
+        );
+
+        await program.methods
+            .initializeToken("EXTN", "Extensions Security Token", 0, null, "")
+            .accounts({
+                authority: authority.publicKey,
+                mint: mint2022.publicKey,
+                mintAuthority: mintAuthority2022,
+                freezeAuthority: freezeAuthority2022,
+                tokenConfig: tokenConfig2022,
+                tokenRegistry,
+                registryPage,
+                metadata: null,
+                tokenMetadataProgram: null,
+                transferHookProgram: null,
+                extraAccountMetaList: null,
+                feeRecipient: null,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+            })
+            .signers([mint2022])
+            .rpc();
+
+        await program.methods
+            .approveWallet()
+            .accounts({
+                authority: authority.publicKey,
+                wallet: aliceKeypair.publicKey,
+                tokenConfig: tokenConfig2022,
+                allowlistEntry: aliceAllowlist2022,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const aliceAccount2022 = await createAccount(
+            provider.connection,
+            aliceKeypair,
+            mint2022.publicKey,
+            aliceKeypair.publicKey,
+            undefined,
+            undefined,
+            TOKEN_2022_PROGRAM_ID
+        );
+        await program.methods
+            .mintTokens(new anchor.BN(250))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: aliceKeypair.publicKey,
+                tokenConfig: tokenConfig2022,
+                mint: mint2022.publicKey,
+                mintAuthority: mintAuthority2022,
+                recipientTokenAccount: aliceAccount2022,
+                recipientAllowlistEntry: aliceAllowlist2022,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_2022_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const aliceBalance = await getAccount(
+            provider.connection,
+            aliceAccount2022,
+            undefined,
+            TOKEN_2022_PROGRAM_ID
+        );
+        assert.equal(aliceBalance.amount.toString(), "250");
+        console.log("✓ Minted through the Token-2022 program");
+    });
+
+    it("Test 18: Metadata update grows the config account to fit a longer URI", async () => {
+        const before = await provider.connection.getAccountInfo(tokenConfig);
+        const oldConfig = await program.account.tokenConfig.fetch(tokenConfig);
+        const newUri = "https://example.com/metadata/" + "a".repeat(120) + ".json";
+
+        await program.methods
+            .updateTokenMetadata("ACMX", "ACME Holdings", newUri)
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                metadata: null,
+                tokenMetadataProgram: null,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(configAccount.symbol, "ACMX");
+        assert.equal(configAccount.uri, newUri);
+        const after = await provider.connection.getAccountInfo(tokenConfig);
+        assert.equal(
+            after.data.length - before.data.length,
+            "ACMX".length - oldConfig.symbol.length +
+                ("ACME Holdings".length - oldConfig.name.length) +
+                (newUri.length - oldConfig.uri.length)
+        );
+        console.log(`✓ Config account resized to ${after.data.length} bytes`);
+    });
+
+    it("Test 19: Compliance officer freezes and thaws a holder's token account", async () => {
+        const setFrozen = (frozen: boolean) =>
+            (frozen ? program.methods.freezeTokenAccount() : program.methods.thawTokenAccount())
+                .accounts({
+                    complianceOfficer: authority.publicKey,
+                    tokenConfig,
+                    mint: mint.publicKey,
+                    freezeAuthority,
+                    tokenAccount: aliceTokenAccount,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .rpc();
+
+        await setFrozen(true);
+        let aliceAccount = await getAccount(provider.connection, aliceTokenAccount);
+        assert.ok(aliceAccount.isFrozen);
+        console.log("✓ Alice's account frozen under a compliance hold");
+
+        await setFrozen(false);
+        aliceAccount = await getAccount(provider.connection, aliceTokenAccount);
+        assert.ok(!aliceAccount.isFrozen);
+        console.log("✓ Hold lifted and account thawed");
+    });
+
+    it("Test 20: Share token created with 0 decimals trades in whole shares", async () => {
+        const shareMint = Keypair.generate();
+        const shareConfig = await initShareToken(shareMint, 0);
+        const mintInfo = await getMint(provider.connection, shareMint.publicKey);
+        assert.equal(mintInfo.decimals, 0);
+
+        await program.methods
+            .setWholeSharesOnly(true)
+            .accounts({ authority: authority.publicKey, tokenConfig: shareConfig })
+            .rpc();
+        const configAccount = await program.account.tokenConfig.fetch(shareConfig);
+        assert.equal(configAccount.decimals, 0);
+        assert.equal(configAccount.lotSize.toNumber(), 1);
+        console.log("✓ 0-decimal mint created; one share is one base unit");
+
+        try {
+            await initShareToken(Keypair.generate(), 10);
+            assert.fail("Should have failed - more than 9 decimals");
+        } catch (error) {
+            assert.include(error.toString(), "InvalidDecimals");
+            console.log("✓ Decimals above 9 rejected");
+        }
+    });
+
+    it("Test 21: Issuer lists its tokens and shares one compliance officer", async () => {
+        const [issuer] = PublicKey.findProgramAddressSync(
+            [Buffer.from("issuer"), authority.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .createIssuer("ACME Holdings Inc.")
+            .accounts({
+                authority: authority.publicKey,
+                issuer,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await program.methods
+            .registerIssuerToken()
+            .accounts({ authority: authority.publicKey, issuer, tokenConfig })
+            .rpc();
+
+        let issuerAccount = await program.account.issuer.fetch(issuer);
+        assert.equal(issuerAccount.mints.length, 1);
+        assert.ok(issuerAccount.mints[0].equals(mint.publicKey));
+        console.log("✓ Token enumerable from the issuer account");
+
+        const officer = Keypair.generate().publicKey;
+        const setOfficer = (complianceOfficer: PublicKey) =>
+            program.methods
+                .setIssuerComplianceOfficer(complianceOfficer)
+                .accounts({ authority: authority.publicKey, issuer })
+                .remainingAccounts([{ pubkey: tokenConfig, isWritable: true, isSigner: false }])
+                .rpc();
+        await setOfficer(officer);
+        const configAccount = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.ok(configAccount.complianceOfficer.equals(officer));
+        console.log("✓ Issuer compliance officer propagated to its tokens");
+
+        // Restore the suite's admin as compliance officer
+        await setOfficer(authority.publicKey);
+        issuerAccount = await program.account.issuer.fetch(issuer);
+        assert.ok(issuerAccount.complianceOfficer.equals(authority.publicKey));
+    });
+
+    it("Test 22: Retired token with no supply closes and returns its rent", async () => {
+        const retiredMint = Keypair.generate();
+        const retiredConfig = await initShareToken(retiredMint, 0);
+        const balanceBefore = await provider.connection.getBalance(authority.publicKey);
+
+        await program.methods
+            .closeTokenConfig()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: retiredConfig,
+                mint: retiredMint.publicKey,
+                treasury: null,
+                treasuryTokenAccount: null,
+                issuer: null,
+                timelock: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        assert.isNull(await provider.connection.getAccountInfo(retiredConfig));
+        const balanceAfter = await provider.connection.getBalance(authority.publicKey);
+        assert.isAbove(balanceAfter, balanceBefore);
+        console.log("✓ Token config closed and rent reclaimed");
+    });
+
+    it("Test 23: Broker moves a client's shares within its approved allowance", async () => {
+        const allowance = new anchor.BN(5_000);
+        await program.methods
+            .approveDelegate(allowance)
+            .accounts({
+                owner: aliceKeypair.publicKey,
+                delegate: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                ownerTokenAccount: aliceTokenAccount,
+                ownerAllowlistEntry: aliceAllowlist,
+                delegateAllowlistEntry: bobAllowlist,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        const aliceBefore = await getAccount(provider.connection, aliceTokenAccount);
+        const amount = new anchor.BN(2_000);
+        await program.methods
+            .delegatedTransfer(amount)
+            .accounts({
+                delegate: bobKeypair.publicKey,
+                owner: aliceKeypair.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                fromTokenAccount: aliceTokenAccount,
+                toTokenAccount: bobTokenAccount,
+                ownerAllowlistEntry: aliceAllowlist,
+                recipientAllowlistEntry: bobAllowlist,
+                delegateAllowlistEntry: bobAllowlist,
+                ownerSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                delegateSanctionsFlag: null,
+                ownerCountryRule: null,
+                recipientCountryRule: null,
+                destinationRegistry: null,
+                velocityCounter: null,
+                saleProposal: null,
+                shareClass: null,
+                ownerSnapshotBalance: null,
+                recipientSnapshotBalance: null,
+                ownerHolderPage: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([bobKeypair])
+            .rpc();
+
+        const aliceAfter = await getAccount(provider.connection, aliceTokenAccount);
+        assert.equal((aliceBefore.amount - aliceAfter.amount).toString(), amount.toString());
+        assert.equal(aliceAfter.delegatedAmount.toString(), allowance.sub(amount).toString());
+        console.log("✓ Delegate transferred within its allowance");
+
+        await program.methods
+            .revokeDelegate()
+            .accounts({
+                owner: aliceKeypair.publicKey,
+                ownerTokenAccount: aliceTokenAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+        const aliceRevoked = await getAccount(provider.connection, aliceTokenAccount);
+        assert.isNull(aliceRevoked.delegate);
+        console.log("✓ Allowance revoked");
+    });
+
+    it("Test 24: Escrowed shares release only with the agent's counter-signature", async () => {
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [escrow] = PublicKey.findProgramAddressSync(
+            [Buffer.from("escrow"), mint.publicKey.toBuffer(), config.nextEscrowId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [escrowVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("escrow_vault"), escrow.toBuffer()],
+            program.programId
+        );
+        const amount = new anchor.BN(1_000);
+        await program.methods
+            .createEscrow(amount, { counterSignature: { signer: authority.publicKey } })
+            .accounts({
+                sender: aliceKeypair.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                senderTokenAccount: aliceTokenAccount,
+                senderAllowlistEntry: aliceAllowlist,
+                senderCountryRule: null,
+                senderSanctionsFlag: null,
+                destinationRegistry: null,
+                escrow,
+                escrowVault,
+                vaultAllowlistEntry: null,
+                senderSnapshotBalance: null,
+                senderHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+        const vault = await getAccount(provider.connection, escrowVault);
+        assert.equal(vault.amount.toString(), amount.toString());
+        console.log("✓ Shares locked in escrow vault");
+
+        const releaseAccounts = {
+            payer: authority.publicKey,
+            sender: aliceKeypair.publicKey,
+            tokenConfig,
+            mint: mint.publicKey,
+            escrow,
+            escrowVault,
+            vaultAllowlistEntry: null,
+            recipientTokenAccount: bobTokenAccount,
+            recipientAllowlistEntry: bobAllowlist,
+            recipientCountryRule: null,
+            recipientSanctionsFlag: null,
+            shareClass: null,
+            priceFeed: null,
+            recipientSnapshotBalance: null,
+            recipientHolderPage: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        };
+        try {
+            await program.methods
+                .releaseEscrow()
+                .accounts({ ...releaseAccounts, counterSigner: null })
+                .rpc();
+            assert.fail("Should have failed - counter-signature missing");
+        } catch (error) {
+            assert.include(error.toString(), "EscrowConditionNotMet");
+            console.log("✓ Release without counter-signature rejected");
+        }
+
+        const bobBefore = await getAccount(provider.connection, bobTokenAccount);
+        await program.methods
+            .releaseEscrow()
+            .accounts({ ...releaseAccounts, counterSigner: authority.publicKey })
+            .rpc();
+        const bobAfter = await getAccount(provider.connection, bobTokenAccount);
+        assert.equal((bobAfter.amount - bobBefore.amount).toString(), amount.toString());
+        assert.isNull(await provider.connection.getAccountInfo(escrow));
+        console.log("✓ Counter-signed escrow released to recipient");
+    });
+
+    it("Test 25: Swap offer settles shares of two gated tokens atomically", async () => {
+        const shareMint = Keypair.generate();
+        const shareConfig = await initShareToken(shareMint, 0);
+        const sharePda = (seed: string, wallet?: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [Buffer.from(seed), shareMint.publicKey.toBuffer(), ...(wallet ? [wallet.toBuffer()] : [])],
+                program.programId
+            )[0];
+        for (const wallet of [aliceKeypair.publicKey, bobKeypair.publicKey]) {
+            await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet,
+                    tokenConfig: shareConfig,
+                    allowlistEntry: sharePda("allowlist", wallet),
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        }
+        const aliceShares = await createAccount(provider.connection, aliceKeypair, shareMint.publicKey, aliceKeypair.publicKey);
+        const bobShares = await createAccount(provider.connection, bobKeypair, shareMint.publicKey, bobKeypair.publicKey);
+        const requestedAmount = new anchor.BN(10);
+        await program.methods
+            .mintTokens(requestedAmount)
+            .accounts({
+                authority: authority.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig: shareConfig,
+                mint: shareMint.publicKey,
+                mintAuthority: sharePda("mint_authority"),
+                recipientTokenAccount: bobShares,
+                recipientAllowlistEntry: sharePda("allowlist", bobKeypair.publicKey),
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [offer] = PublicKey.findProgramAddressSync(
+            [Buffer.from("swap_offer"), mint.publicKey.toBuffer(), config.nextSwapOfferId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [offerVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("swap_vault"), offer.toBuffer()],
+            program.programId
+        );
+        const offeredAmount = new anchor.BN(500);
+        await program.methods
+            .createSwapOffer(offeredAmount, requestedAmount, bobKeypair.publicKey, null)
+            .accounts({
+                maker: aliceKeypair.publicKey,
+                offeredTokenConfig: tokenConfig,
+                requestedTokenConfig: shareConfig,
+                offeredMint: mint.publicKey,
+                makerOfferedTokenAccount: aliceTokenAccount,
+                makerOfferedAllowlistEntry: aliceAllowlist,
+                makerRequestedAllowlistEntry: sharePda("allowlist", aliceKeypair.publicKey),
+                offer,
+                offerVault,
+                vaultAllowlistEntry: null,
+                makerSnapshotBalance: null,
+                makerHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+        console.log("✓ Alice's offered shares locked in the offer vault");
+
+        const bobBefore = await getAccount(provider.connection, bobTokenAccount);
+        await program.methods
+            .acceptSwapOffer()
+            .accounts({
+                taker: bobKeypair.publicKey,
+                maker: aliceKeypair.publicKey,
+                offer,
+                offerVault,
+                vaultAllowlistEntry: null,
+                offeredMint: mint.publicKey,
+                requestedMint: shareMint.publicKey,
+                offeredTokenConfig: tokenConfig,
+                requestedTokenConfig: shareConfig,
+                takerOfferedTokenAccount: bobTokenAccount,
+                takerRequestedTokenAccount: bobShares,
+                makerRequestedTokenAccount: aliceShares,
+                makerOfferedAllowlistEntry: aliceAllowlist,
+                takerOfferedAllowlistEntry: bobAllowlist,
+                makerRequestedAllowlistEntry: sharePda("allowlist", aliceKeypair.publicKey),
+                takerRequestedAllowlistEntry: sharePda("allowlist", bobKeypair.publicKey),
+                makerOfferedSanctionsFlag: null,
+                takerOfferedSanctionsFlag: null,
+                makerRequestedSanctionsFlag: null,
+                takerRequestedSanctionsFlag: null,
+                makerOfferedCountryRule: null,
+                takerOfferedCountryRule: null,
+                makerRequestedCountryRule: null,
+                takerRequestedCountryRule: null,
+                offeredDestinationRegistry: null,
+                requestedDestinationRegistry: null,
+                offeredShareClass: null,
+                requestedShareClass: null,
+                takerOfferedSnapshotBalance: null,
+                takerOfferedHolderPage: null,
+                takerRequestedSnapshotBalance: null,
+                takerRequestedHolderPage: null,
+                makerRequestedSnapshotBalance: null,
+                makerRequestedHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([bobKeypair])
+            .rpc();
+
+        const bobAfter = await getAccount(provider.connection, bobTokenAccount);
+        const aliceSharesAfter = await getAccount(provider.connection, aliceShares);
+        assert.equal((bobAfter.amount - bobBefore.amount).toString(), offeredAmount.toString());
+        assert.equal(aliceSharesAfter.amount.toString(), requestedAmount.toString());
+        assert.isNull(await provider.connection.getAccountInfo(offer));
+        console.log("✓ Both legs settled in one transaction");
+    });
+
+    it("Test 26: Wrapped shares stay backed one-for-one and unwrap through the allowlist", async () => {
+        const pda = (seed: string) =>
+            PublicKey.findProgramAddressSync([Buffer.from(seed), mint.publicKey.toBuffer()], program.programId)[0];
+        const [wrapper, wrappedMint, wrapperVault] = [pda("wrapper"), pda("wrapped_mint"), pda("wrapper_vault")];
+        await program.methods
+            .initializeWrapper()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                wrapper,
+                wrappedMint,
+                wrapperVault,
+                vaultAllowlistEntry: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const aliceWrapped = await createAccount(provider.connection, aliceKeypair, wrappedMint, aliceKeypair.publicKey);
+        const amount = new anchor.BN(700);
+        const holderAccounts = {
+            holder: aliceKeypair.publicKey,
+            tokenConfig,
+            mint: mint.publicKey,
+            wrapper,
+            wrappedMint,
+            wrapperVault,
+            holderTokenAccount: aliceTokenAccount,
+            holderWrappedAccount: aliceWrapped,
+            holderAllowlistEntry: aliceAllowlist,
+            holderSnapshotBalance: null,
+            holderHolderPage: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        };
+        await program.methods
+            .wrapShares(amount)
+            .accounts(holderAccounts)
+            .signers([aliceKeypair])
+            .rpc();
+        const wrapped = await getAccount(provider.connection, aliceWrapped);
+        assert.equal(wrapped.amount.toString(), amount.toString());
+        await program.methods
+            .reconcileWrapper()
+            .accounts({ wrapper, wrappedMint, wrapperVault })
+            .rpc();
+        console.log("✓ Wrapped supply reconciles against vaulted shares");
+
+        await program.methods
+            .unwrapShares(amount)
+            .accounts({ ...holderAccounts, holderSanctionsFlag: null, shareClass: null })
+            .signers([aliceKeypair])
+            .rpc();
+        const wrapperAccount = await program.account.wrapperConfig.fetch(wrapper);
+        assert.equal(wrapperAccount.totalWrapped.toNumber(), 0);
+        const vault = await getAccount(provider.connection, wrapperVault);
+        assert.equal(vault.amount.toString(), "0");
+        console.log("✓ Approved holder unwrapped back into shares");
+    });
+
+    it("Test 27: Advisor withdraws streamed shares and the sender cancels the rest", async () => {
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [stream] = PublicKey.findProgramAddressSync(
+            [Buffer.from("stream"), mint.publicKey.toBuffer(), config.nextStreamId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [streamVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("stream_vault"), stream.toBuffer()],
+            program.programId
+        );
+        // Started 100 seconds ago at 1 unit per second, so ~100 units are already due
+        const deposited = new anchor.BN(1_000);
+        const startTime = new anchor.BN(Math.floor(Date.now() / 1000) - 100);
+        await program.methods
+            .createStream(deposited, new anchor.BN(1), startTime)
+            .accounts({
+                sender: aliceKeypair.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                senderTokenAccount: aliceTokenAccount,
+                senderAllowlistEntry: aliceAllowlist,
+                recipientAllowlistEntry: bobAllowlist,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                destinationRegistry: null,
+                stream,
+                streamVault,
+                vaultAllowlistEntry: null,
+                senderSnapshotBalance: null,
+                senderHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        await program.methods
+            .withdrawStreamed()
+            .accounts({
+                recipient: bobKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                stream,
+                streamVault,
+                recipientTokenAccount: bobTokenAccount,
+                recipientAllowlistEntry: bobAllowlist,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([bobKeypair])
+            .rpc();
+        const streamAccount = await program.account.paymentStream.fetch(stream);
+        assert.isTrue(streamAccount.withdrawn.gten(100));
+        assert.isTrue(streamAccount.withdrawn.lt(deposited));
+        console.log(`✓ Recipient withdrew ${streamAccount.withdrawn.toString()} streamed units`);
+
+        await program.methods
+            .setStreamPaused(true)
+            .accounts({ sender: aliceKeypair.publicKey, stream })
+            .signers([aliceKeypair])
+            .rpc();
+        await program.methods
+            .cancelStream()
+            .accounts({
+                sender: aliceKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                stream,
+                streamVault,
+                vaultAllowlistEntry: null,
+                senderTokenAccount: aliceTokenAccount,
+                senderAllowlistEntry: aliceAllowlist,
+                recipientTokenAccount: bobTokenAccount,
+                recipientAllowlistEntry: bobAllowlist,
+                senderSnapshotBalance: null,
+                senderHolderPage: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+        assert.isNull(await provider.connection.getAccountInfo(stream));
+        console.log("✓ Paused stream cancelled and remainder refunded");
+    });
+
+    it("Test 28: Memo-required token rejects transfers without a travel-rule hash", async () => {
+        await program.methods
+            .setRequireMemo(true)
+            .accounts({ authority: authority.publicKey, tokenConfig })
+            .rpc();
+        const transferAccounts = {
+            authority: aliceKeypair.publicKey,
+            recipient: bobKeypair.publicKey,
+            tokenConfig,
+            mint: mint.publicKey,
+            fromTokenAccount: aliceTokenAccount,
+            toTokenAccount: bobTokenAccount,
+            senderAllowlistEntry: aliceAllowlist,
+            recipientAllowlistEntry: bobAllowlist,
+            destinationRegistry: null,
+            feeTreasury: null,
+            velocityCounter: null,
+            senderCountryRule: null,
+            recipientCountryRule: null,
+            senderSanctionsFlag: null,
+            recipientSanctionsFlag: null,
+            shareClass: null,
+            senderSnapshotBalance: null,
+            recipientSnapshotBalance: null,
+            senderHolderPage: null,
+            recipientHolderPage: null,
+            saleProposal: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        };
+
+        try {
+            await program.methods
+                .gatedTransfer(new anchor.BN(100), null)
+                .accounts(transferAccounts)
+                .signers([aliceKeypair])
+                .rpc();
+            assert.fail("Should have failed - travel-rule hash missing");
+        } catch (error) {
+            assert.include(error.toString(), "TravelRuleHashRequired");
+            console.log("✓ Transfer without travel-rule hash rejected");
+        }
+
+        const travelRuleHash = Array.from(
+            createHash("sha256").update("originator=Alice;beneficiary=Bob").digest()
+        );
+        await program.methods
+            .gatedTransfer(new anchor.BN(100), travelRuleHash)
+            .accounts(transferAccounts)
+            .signers([aliceKeypair])
+            .rpc();
+        console.log("✓ Transfer carrying travel-rule hash accepted");
+
+        await program.methods
+            .setRequireMemo(false)
+            .accounts({ authority: authority.publicKey, tokenConfig })
+            .rpc();
+    });
+
+    it("Test 29: Batch transfer settles every leg or none", async () => {
+        const leg = (from: PublicKey, to: PublicKey, senderEntry: PublicKey, recipientEntry: PublicKey) =>
+            [from, to, senderEntry, recipientEntry].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }));
+        const batchAccounts = {
+            authority: aliceKeypair.publicKey,
+            tokenConfig,
+            mint: mint.publicKey,
+            shareClass: null,
+            tokenProgram: TOKEN_PROGRAM_ID,
+        };
+
+        const bobBefore = await getAccount(provider.connection, bobTokenAccount);
+        await program.methods
+            .batchTransfer([new anchor.BN(100), new anchor.BN(200)])
+            .accounts(batchAccounts)
+            .remainingAccounts([
+                ...leg(aliceTokenAccount, bobTokenAccount, aliceAllowlist, bobAllowlist),
+                ...leg(aliceTokenAccount, bobTokenAccount, aliceAllowlist, bobAllowlist),
+            ])
+            .signers([aliceKeypair])
+            .rpc();
+        const bobAfter = await getAccount(provider.connection, bobTokenAccount);
+        assert.equal((bobAfter.amount - bobBefore.amount).toString(), "300");
+        console.log("✓ Two legs settled in one instruction");
+
+        try {
+            await program.methods
+                .batchTransfer([new anchor.BN(100), new anchor.BN(100)])
+                .accounts(batchAccounts)
+                .remainingAccounts([
+                    ...leg(aliceTokenAccount, bobTokenAccount, aliceAllowlist, bobAllowlist),
+                    ...leg(aliceTokenAccount, charlieTokenAccount, aliceAllowlist, charlieAllowlist),
+                ])
+                .signers([aliceKeypair])
+                .rpc();
+            assert.fail("Should have failed - unapproved recipient in second leg");
+        } catch (error) {
+            assert.include(error.toString(), "RecipientNotApproved");
+        }
+        const bobUnchanged = await getAccount(provider.connection, bobTokenAccount);
+        assert.equal(bobUnchanged.amount.toString(), bobAfter.amount.toString());
+        console.log("✓ Failing leg rolled back the whole batch");
+    });
+
+    it("Test 30: Batch mint closes a round across several investors", async () => {
+        const before = await program.account.tokenConfig.fetch(tokenConfig);
+        const amounts = [new anchor.BN(1_000), new anchor.BN(2_000)];
+        await program.methods
+            .batchMint(amounts)
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                mintAuthority,
+                shareClass: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts(
+                [aliceTokenAccount, aliceAllowlist, bobTokenAccount, bobAllowlist].map((pubkey) => ({
+                    pubkey,
+                    isSigner: false,
+                    isWritable: true,
+                }))
+            )
+            .rpc();
+
+        const after = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(after.totalSupply.sub(before.totalSupply).toNumber(), 3_000);
+        console.log("✓ Supply recorded once for the whole batch");
+    });
+
+    it("Test 31: Allowlisted wallets claim an airdrop grant once with a Merkle proof", async () => {
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [campaign] = PublicKey.findProgramAddressSync(
+            [Buffer.from("airdrop"), mint.publicKey.toBuffer(), config.nextAirdropId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [campaignVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("airdrop_vault"), campaign.toBuffer()],
+            program.programId
+        );
+        const aliceGrant = new anchor.BN(400);
+        const bobGrant = new anchor.BN(600);
+        const aliceLeaf = airdropLeaf(aliceKeypair.publicKey, aliceGrant);
+        const bobLeaf = airdropLeaf(bobKeypair.publicKey, bobGrant);
+        const root = snapshotParent(aliceLeaf, bobLeaf);
+
+        await program.methods
+            .createAirdropCampaign(Array.from(root), aliceGrant.add(bobGrant), new anchor.BN(Math.floor(Date.now() / 1000) + 3_600))
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                mintAuthority,
+                campaign,
+                campaignVault,
+                vaultAllowlistEntry: null,
+                instructions: null,
+                trustedCaller: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const [airdropClaim] = PublicKey.findProgramAddressSync(
+            [Buffer.from("airdrop_claim"), campaign.toBuffer(), bobKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        const claim = () =>
+            program.methods
+                .claimAirdrop(bobGrant, [Array.from(aliceLeaf)])
+                .accounts({
+                    wallet: bobKeypair.publicKey,
+                    tokenConfig,
+                    mint: mint.publicKey,
+                    campaign,
+                    campaignVault,
+                    airdropClaim,
+                    walletTokenAccount: bobTokenAccount,
+                    walletAllowlistEntry: bobAllowlist,
+                    walletSanctionsFlag: null,
+                    shareClass: null,
+                    walletSnapshotBalance: null,
+                    walletHolderPage: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([bobKeypair])
+                .rpc();
+
+        const bobBefore = await getAccount(provider.connection, bobTokenAccount);
+        await claim();
+        const bobAfter = await getAccount(provider.connection, bobTokenAccount);
+        assert.equal((bobAfter.amount - bobBefore.amount).toString(), bobGrant.toString());
+        console.log("✓ Bob claimed his airdrop grant");
+
+        try {
+            await claim();
+            assert.fail("Should have failed - grant already claimed");
+        } catch (error) {
+            console.log("✓ Second claim rejected");
+        }
+    });
+
+    it("Test 32: Anyone cranks a due quarterly distribution and holders claim from the snapshot", async () => {
+        const usdcMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+        const aliceUsdcAccount = await createAccount(provider.connection, aliceKeypair, usdcMint, aliceKeypair.publicKey);
+        const [schedule] = PublicKey.findProgramAddressSync(
+            [Buffer.from("distribution_schedule"), mint.publicKey.toBuffer()],
+            program.programId
+        );
+        const [scheduleVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("schedule_vault"), schedule.toBuffer()],
+            program.programId
+        );
+        const quarter = new anchor.BN(90 * 86_400);
+
+        await program.methods
+            .createDistributionSchedule(
+                new anchor.BN(1_000_000),
+                quarter,
+                new anchor.BN(30 * 86_400),
+                new anchor.BN(Math.floor(Date.now() / 1000) - 60)
+            )
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                schedule,
+                payoutMint: usdcMint,
+                scheduleVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await mintTo(provider.connection, authority, usdcMint, scheduleVault, authority, 1_000_000);
+
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [snapshot] = PublicKey.findProgramAddressSync(
+            [Buffer.from("snapshot"), mint.publicKey.toBuffer(), config.currentSnapshotId.addn(1).toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [distribution] = PublicKey.findProgramAddressSync(
+            [Buffer.from("distribution"), mint.publicKey.toBuffer(), config.nextDistributionId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const [distributionVault] = PublicKey.findProgramAddressSync(
+            [Buffer.from("distribution_vault"), distribution.toBuffer()],
+            program.programId
+        );
+
+        // Any wallet may run the crank; Charlie pays for the new epoch's accounts
+        const crank = () =>
+            program.methods
+                .crankDistribution()
+                .accounts({
+                    cranker: charlieKeypair.publicKey,
+                    tokenConfig,
+                    schedule,
+                    scheduleVault,
+                    payoutMint: usdcMint,
+                    mint: mint.publicKey,
+                    snapshot,
+                    distribution,
+                    distributionVault,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([charlieKeypair])
+                .rpc();
+        await crank();
+
+        const epoch = await program.account.distribution.fetch(distribution);
+        assert.equal(epoch.snapshotId.toString(), config.currentSnapshotId.addn(1).toString());
+        const scheduleAccount = await program.account.distributionSchedule.fetch(schedule);
+        assert.equal(scheduleAccount.epochsRun.toNumber(), 1);
+        console.log("✓ Crank opened the quarter's distribution");
+
+        try {
+            await crank();
+            assert.fail("Should have failed - next quarter not due");
+        } catch (error) {
+            console.log("✓ Early crank rejected");
+        }
+
+        const [aliceClaim] = PublicKey.findProgramAddressSync(
+            [Buffer.from("dividend_claim"), distribution.toBuffer(), aliceKeypair.publicKey.toBuffer()],
+            program.programId
+        );
+        await program.methods
+            .claimScheduledDividend()
+            .accounts({
+                holder: aliceKeypair.publicKey,
+                distribution,
+                snapshot,
+                payoutMint: usdcMint,
+                distributionVault,
+                holderPayoutAccount: aliceUsdcAccount,
+                shareTokenAccount: aliceTokenAccount,
+                holderAllowlistEntry: aliceAllowlist,
+                holderSnapshotBalance: null,
+                dividendClaim: aliceClaim,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        const aliceShares = await getAccount(provider.connection, aliceTokenAccount);
+        const expected = (BigInt(1_000_000) * aliceShares.amount) / BigInt(epoch.snapshotSupply.toString());
+        const aliceUsdc = await getAccount(provider.connection, aliceUsdcAccount);
+        assert.equal(aliceUsdc.amount.toString(), expected.toString());
+        console.log("✓ Alice claimed her pro-rata share of the epoch");
+    });
+
+    it("Test 33: Mints and transfers create a missing recipient token account", async () => {
+        const daveKeypair = Keypair.generate();
+        const erinKeypair = Keypair.generate();
+        const approve = async (wallet: PublicKey) => {
+            const [entry] = PublicKey.findProgramAddressSync(
+                [Buffer.from("allowlist"), mint.publicKey.toBuffer(), wallet.toBuffer()],
+                program.programId
+            );
+            await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet,
+                    tokenConfig,
+                    allowlistEntry: entry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            return entry;
+        };
+        const daveAllowlist = await approve(daveKeypair.publicKey);
+        const erinAllowlist = await approve(erinKeypair.publicKey);
+        const daveTokenAccount = getAssociatedTokenAddressSync(mint.publicKey, daveKeypair.publicKey);
+        const erinTokenAccount = getAssociatedTokenAddressSync(mint.publicKey, erinKeypair.publicKey);
+
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const checkpoint = (wallet: PublicKey) =>
+            PublicKey.findProgramAddressSync(
+                [
+                    Buffer.from("snapshot_balance"),
+                    mint.publicKey.toBuffer(),
+                    config.currentSnapshotId.toArrayLike(Buffer, "le", 8),
+                    wallet.toBuffer(),
+                ],
+                program.programId
+            )[0];
+
+        // The wrapped instruction's accounts ride along as remaining accounts
+        const mintIx = await program.methods
+            .mintTokens(new anchor.BN(500))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: daveKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                mintAuthority,
+                recipientTokenAccount: daveTokenAccount,
+                recipientAllowlistEntry: daveAllowlist,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: checkpoint(daveKeypair.publicKey),
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .instruction();
+        await program.methods
+            .mintTokensWithAta(new anchor.BN(500))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: daveKeypair.publicKey,
+                mint: mint.publicKey,
+                recipientTokenAccount: daveTokenAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts(mintIx.keys)
+            .rpc();
+
+        const daveBalance = await getAccount(provider.connection, daveTokenAccount);
+        assert.equal(daveBalance.amount.toString(), "500");
+        console.log("✓ Mint created Dave's token account");
+
+        const transferIx = await program.methods
+            .gatedTransfer(new anchor.BN(100), null)
+            .accounts({
+                authority: aliceKeypair.publicKey,
+                recipient: erinKeypair.publicKey,
+                tokenConfig,
+                mint: mint.publicKey,
+                fromTokenAccount: aliceTokenAccount,
+                toTokenAccount: erinTokenAccount,
+                senderAllowlistEntry: aliceAllowlist,
+                recipientAllowlistEntry: erinAllowlist,
+                destinationRegistry: null,
+                feeTreasury: null,
+                velocityCounter: null,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                senderSnapshotBalance: checkpoint(aliceKeypair.publicKey),
+                recipientSnapshotBalance: checkpoint(erinKeypair.publicKey),
+                senderHolderPage: null,
+                recipientHolderPage: null,
+                saleProposal: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .instruction();
+        await program.methods
+            .gatedTransferWithAta(new anchor.BN(100), null)
+            .accounts({
+                authority: aliceKeypair.publicKey,
+                recipient: erinKeypair.publicKey,
+                mint: mint.publicKey,
+                recipientTokenAccount: erinTokenAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .remainingAccounts(transferIx.keys)
+            .signers([aliceKeypair])
+            .rpc();
+
+        const erinBalance = await getAccount(provider.connection, erinTokenAccount);
+        assert.equal(erinBalance.amount.toString(), "100");
+        console.log("✓ Sender paid for Erin's new token account");
+    });
+
+    it("Test 34: Sweep closes ended blackout windows but keeps recent revocations", async () => {
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const [blackoutWindow] = PublicKey.findProgramAddressSync(
+            [Buffer.from("blackout"), mint.publicKey.toBuffer(), config.nextBlackoutId.toArrayLike(Buffer, "le", 8)],
+            program.programId
+        );
+        const now = Math.floor(Date.now() / 1000);
+        await program.methods
+            .createBlackoutWindow(new anchor.BN(now - 60), new anchor.BN(now + 2), { wallet: [charlieKeypair.publicKey] })
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig,
+                blackoutWindow,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await new Promise((resolve) => setTimeout(resolve, 3_000));
+
+        const sweep = (accounts: PublicKey[]) =>
+            program.methods
+                .reclaimStaleAccounts()
+                .accounts({ authority: authority.publicKey, tokenConfig, tokenProgram: null })
+                .remainingAccounts(accounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+                .rpc();
+
+        // Charlie was revoked minutes ago, well inside the retention period
+        try {
+            await sweep([charlieAllowlist, charlieTokenAccount]);
+            assert.fail("Should have failed - revocation is not stale yet");
+        } catch (error) {
+            console.log("✓ Recently revoked entry kept");
+        }
+
+        await sweep([blackoutWindow]);
+        assert.isNull(await provider.connection.getAccountInfo(blackoutWindow));
+        const after = await program.account.tokenConfig.fetch(tokenConfig);
+        assert.equal(after.openBlackoutWindows, config.openBlackoutWindows);
+        console.log("✓ Ended blackout window closed and its rent reclaimed");
+    });
+
+    it("Test 35: Accounts carry a layout version and current ones refuse re-migration", async () => {
+        const config = await program.account.tokenConfig.fetch(tokenConfig);
+        const entry = await program.account.allowlistEntry.fetch(aliceAllowlist);
+        assert.equal(config.version, 7);
+        assert.equal(entry.version, 2);
+
+        try {
+            await program.methods
+                .migrateTokenConfig()
+                .accounts({
+                    payer: authority.publicKey,
+                    mint: mint.publicKey,
+                    tokenConfig,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            assert.fail("Should have failed - token config already current");
+        } catch (error) {
+            assert.include(error.toString(), "AccountAlreadyMigrated");
+            console.log("✓ Current token config left untouched");
+        }
+
+        try {
+            await program.methods
+                .migrateAllowlistEntry()
+                .accounts({
+                    payer: authority.publicKey,
+                    mint: mint.publicKey,
+                    wallet: aliceKeypair.publicKey,
+                    allowlistEntry: aliceAllowlist,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            assert.fail("Should have failed - allowlist entry already current");
+        } catch (error) {
+            assert.include(error.toString(), "AccountAlreadyMigrated");
+            console.log("✓ Current allowlist entry left untouched");
+        }
+    });
+
+    it("Test 36: Compact allowlist holders mint and trade by proving their bitmap slots", async () => {
+        const compactMint = Keypair.generate();
+        const compactConfig = await initShareToken(compactMint, 0);
+        const [compactMintAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("mint_authority"), compactMint.publicKey.toBuffer()],
+            program.programId
+        );
+        const pageIndex = Buffer.alloc(4);
+        const [page] = PublicKey.findProgramAddressSync(
+            [Buffer.from("allowlist_bitmap"), compactMint.publicKey.toBuffer(), pageIndex],
+            program.programId
+        );
+
+        await program.methods
+            .createAllowlistBitmapPage(0)
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: compactConfig,
+                page,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await program.methods
+            .assignAllowlistSlots([aliceKeypair.publicKey, bobKeypair.publicKey], true)
+            .accounts({ authority: authority.publicKey, tokenConfig: compactConfig, page })
+            .rpc();
+
+        // Two leaves padded with empty subtrees to the page's depth of 13
+        const aliceLeaf = allowlistSlotLeaf(aliceKeypair.publicKey, 0);
+        const bobLeaf = allowlistSlotLeaf(bobKeypair.publicKey, 1);
+        const emptySubtrees = [Buffer.alloc(32)];
+        for (let level = 1; level < 13; level++) {
+            emptySubtrees.push(snapshotParent(emptySubtrees[level - 1], emptySubtrees[level - 1]));
+        }
+        const proofFor = (sibling: Buffer) => [sibling, ...emptySubtrees.slice(1)].map((node) => Array.from(node));
+        const root = emptySubtrees.slice(1).reduce((node, empty) => snapshotParent(node, empty), snapshotParent(aliceLeaf, bobLeaf));
+        const pageAccount = await program.account.allowlistBitmapPage.fetch(page);
+        assert.deepEqual(Buffer.from(pageAccount.assignmentRoot), root);
+
+        const aliceCompactAccount = await createAccount(provider.connection, aliceKeypair, compactMint.publicKey, aliceKeypair.publicKey);
+        const bobCompactAccount = await createAccount(provider.connection, bobKeypair, compactMint.publicKey, bobKeypair.publicKey);
+        await program.methods
+            .compactMint(new anchor.BN(1_000), 0, proofFor(bobLeaf))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: aliceKeypair.publicKey,
+                tokenConfig: compactConfig,
+                mint: compactMint.publicKey,
+                mintAuthority: compactMintAuthority,
+                recipientTokenAccount: aliceCompactAccount,
+                recipientPage: page,
+                shareClass: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+
+        const transfer = (amount: number) =>
+            program.methods
+                .compactTransfer(new anchor.BN(amount), 0, proofFor(bobLeaf), 1, proofFor(aliceLeaf))
+                .accounts({
+                    authority: aliceKeypair.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig: compactConfig,
+                    mint: compactMint.publicKey,
+                    fromTokenAccount: aliceCompactAccount,
+                    toTokenAccount: bobCompactAccount,
+                    senderPage: page,
+                    recipientPage: page,
+                    shareClass: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .signers([aliceKeypair])
+                .rpc();
+        await transfer(400);
+        const bobBalance = await getAccount(provider.connection, bobCompactAccount);
+        assert.equal(bobBalance.amount.toString(), "400");
+        console.log("✓ Compact holders traded without per-wallet allowlist accounts");
+
+        await program.methods
+            .setAllowlistSlotsApproved([1], false)
+            .accounts({ authority: authority.publicKey, tokenConfig: compactConfig, page })
+            .rpc();
+        try {
+            await transfer(100);
+            assert.fail("Should have failed - Bob's slot was revoked");
+        } catch (error) {
+            assert.include(error.toString(), "RecipientNotApproved");
+            console.log("✓ Revoked slot cannot receive");
+        }
+    });
+
+    it("Test 37: Compressed allowlist holders mint and trade by proving their tree leaves", async () => {
+        const treeMint = Keypair.generate();
+        const treeConfig = await initShareToken(treeMint, 0);
+        const [treeMintAuthority] = PublicKey.findProgramAddressSync(
+            [Buffer.from("mint_authority"), treeMint.publicKey.toBuffer()],
+            program.programId
+        );
+        const [allowlistTree] = PublicKey.findProgramAddressSync(
+            [Buffer.from("allowlist_tree"), treeMint.publicKey.toBuffer()],
+            program.programId
+        );
+        const merkleTree = Keypair.generate();
+        const depthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+        const allocTree = await createAllocTreeIx(
+            provider.connection,
+            merkleTree.publicKey,
+            authority.publicKey,
+            depthSizePair,
+            0
+        );
+        await provider.sendAndConfirm(new anchor.web3.Transaction().add(allocTree), [merkleTree]);
+
+        const treeAccounts = {
+            authority: authority.publicKey,
+            tokenConfig: treeConfig,
+            allowlistTree,
+            merkleTree: merkleTree.publicKey,
+            compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            noopProgram: SPL_NOOP_PROGRAM_ID,
+        };
+        await program.methods
+            .initAllowlistTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize)
+            .accounts({ ...treeAccounts, systemProgram: SystemProgram.programId })
+            .rpc();
+        for (const wallet of [aliceKeypair.publicKey, bobKeypair.publicKey]) {
+            await program.methods.appendCompressedEntry(wallet).accounts(treeAccounts).rpc();
+        }
+
+        const entryLeaf = (wallet: PublicKey, approved: boolean) =>
+            createHash("sha256")
+                .update(Buffer.concat([Buffer.from("allowlist_entry"), wallet.toBuffer(), Buffer.from([approved ? 1 : 0])]))
+                .digest();
+        const leaves = [entryLeaf(aliceKeypair.publicKey, true), entryLeaf(bobKeypair.publicKey, true)];
+        const proofFor = (index: number) => {
+            const proof = MerkleTree.sparseMerkleTreeFromLeaves(leaves, depthSizePair.maxDepth).getProof(index);
+            return {
+                root: Array.from(proof.root),
+                accounts: proof.proof.map((node) => ({ pubkey: new PublicKey(node), isSigner: false, isWritable: false })),
+            };
+        };
+        const treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(provider.connection, merkleTree.publicKey);
+        assert.deepEqual(treeAccount.getCurrentRoot(), Buffer.from(proofFor(0).root));
+
+        const aliceTreeAccount = await createAccount(provider.connection, aliceKeypair, treeMint.publicKey, aliceKeypair.publicKey);
+        const bobTreeAccount = await createAccount(provider.connection, bobKeypair, treeMint.publicKey, bobKeypair.publicKey);
+        const aliceProof = proofFor(0);
+        await program.methods
+            .compressedMint(new anchor.BN(1_000), aliceProof.root, 0)
+            .accounts({
+                authority: authority.publicKey,
+                recipient: aliceKeypair.publicKey,
+                tokenConfig: treeConfig,
+                mint: treeMint.publicKey,
+                mintAuthority: treeMintAuthority,
+                recipientTokenAccount: aliceTreeAccount,
+                allowlistTree,
+                merkleTree: merkleTree.publicKey,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                shareClass: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .remainingAccounts(aliceProof.accounts)
+            .rpc();
+
+        const transfer = (amount: number) => {
+            const senderProof = proofFor(0);
+            const recipientProof = proofFor(1);
+            return program.methods
+                .compressedTransfer(new anchor.BN(amount), senderProof.root, 0, recipientProof.root, 1, senderProof.accounts.length)
+                .accounts({
+                    authority: aliceKeypair.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig: treeConfig,
+                    mint: treeMint.publicKey,
+                    fromTokenAccount: aliceTreeAccount,
+                    toTokenAccount: bobTreeAccount,
+                    allowlistTree,
+                    merkleTree: merkleTree.publicKey,
+                    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                    shareClass: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                })
+                .remainingAccounts([...senderProof.accounts, ...recipientProof.accounts])
+                .signers([aliceKeypair])
+                .rpc();
+        };
+        await transfer(400);
+        const bobBalance = await getAccount(provider.connection, bobTreeAccount);
+        assert.equal(bobBalance.amount.toString(), "400");
+        console.log("✓ Compressed holders traded against the concurrent Merkle tree");
+
+        const bobProof = proofFor(1);
+        await program.methods
+            .setCompressedEntryApproved(bobKeypair.publicKey, bobProof.root, 1, false)
+            .accounts(treeAccounts)
+            .remainingAccounts(bobProof.accounts)
+            .rpc();
+        leaves[1] = entryLeaf(bobKeypair.publicKey, false);
+        try {
+            await transfer(100);
+            assert.fail("Should have failed - Bob's leaf was revoked");
+        } catch (error) {
+            assert.notInclude(error.toString(), "Should have failed");
+            console.log("✓ Revoked leaf cannot receive");
+        }
+    });
+
+    it("Test 38: Holder index keeps sequential numbers as balances cross zero", async () => {
+        const indexMint = Keypair.generate();
+        const indexConfig = await initShareToken(indexMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const holderIndex = pda(Buffer.from("holder_index"), indexMint.publicKey.toBuffer());
+        const holderNumber = (n: number) => {
+            const number = Buffer.alloc(8);
+            number.writeBigUInt64LE(BigInt(n));
+            return pda(Buffer.from("holder_number"), indexMint.publicKey.toBuffer(), number);
+        };
+
+        await program.methods
+            .initializeHolderIndex()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: indexConfig,
+                holderIndex,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const holders = [aliceKeypair, bobKeypair];
+        const entries: PublicKey[] = [];
+        const tokenAccounts: PublicKey[] = [];
+        for (const holder of holders) {
+            const entry = pda(Buffer.from("allowlist"), indexMint.publicKey.toBuffer(), holder.publicKey.toBuffer());
+            await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet: holder.publicKey,
+                    tokenConfig: indexConfig,
+                    allowlistEntry: entry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            const tokenAccount = await createAccount(provider.connection, holder, indexMint.publicKey, holder.publicKey);
+            await program.methods
+                .bindTokenAccount(tokenAccount)
+                .accounts({ authority: authority.publicKey, tokenConfig: indexConfig, allowlistEntry: entry })
+                .rpc();
+            entries.push(entry);
+            tokenAccounts.push(tokenAccount);
+        }
+
+        for (const [i, amount] of [100, 50].entries()) {
+            await program.methods
+                .mintTokens(new anchor.BN(amount))
+                .accounts({
+                    authority: authority.publicKey,
+                    recipient: holders[i].publicKey,
+                    tokenConfig: indexConfig,
+                    mint: indexMint.publicKey,
+                    mintAuthority: pda(Buffer.from("mint_authority"), indexMint.publicKey.toBuffer()),
+                    recipientTokenAccount: tokenAccounts[i],
+                    recipientAllowlistEntry: entries[i],
+                    recipientCountryRule: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    recipientSnapshotBalance: null,
+                    recipientHolderPage: null,
+                    holderIndex,
+                    recipientHolderNumber: holderNumber(i),
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        }
+        let index = await program.account.holderIndex.fetch(holderIndex);
+        assert.equal(index.len.toNumber(), 2);
+        const first = await program.account.holderNumber.fetch(holderNumber(0));
+        assert.isTrue(first.wallet.equals(aliceKeypair.publicKey));
+        console.log("✓ New holders took numbers 0 and 1");
+
+        // Alice sells out: Bob, holding the last number, moves into number 0
+        await program.methods
+            .gatedTransfer(new anchor.BN(100), null)
+            .accounts({
+                authority: aliceKeypair.publicKey,
+                recipient: bobKeypair.publicKey,
+                tokenConfig: indexConfig,
+                mint: indexMint.publicKey,
+                fromTokenAccount: tokenAccounts[0],
+                toTokenAccount: tokenAccounts[1],
+                senderAllowlistEntry: entries[0],
+                recipientAllowlistEntry: entries[1],
+                destinationRegistry: null,
+                feeTreasury: null,
+                velocityCounter: null,
+                senderCountryRule: null,
+                recipientCountryRule: null,
+                senderSanctionsFlag: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                senderSnapshotBalance: null,
+                recipientSnapshotBalance: null,
+                senderHolderPage: null,
+                recipientHolderPage: null,
+                saleProposal: null,
+                holderIndex,
+                senderHolderNumber: holderNumber(0),
+                lastHolderNumber: holderNumber(1),
+                recipientHolderNumber: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([aliceKeypair])
+            .rpc();
+
+        index = await program.account.holderIndex.fetch(holderIndex);
+        assert.equal(index.len.toNumber(), 1);
+        const moved = await program.account.holderNumber.fetch(holderNumber(0));
+        assert.isTrue(moved.wallet.equals(bobKeypair.publicKey));
+        assert.isNull(await provider.connection.getAccountInfo(holderNumber(1)));
+        const aliceEntry = await program.account.allowlistEntry.fetch(entries[0]);
+        assert.isFalse(aliceEntry.holderIndexed);
+        console.log("✓ Departing holder's number went to the last holder");
+    });
+
+    it("Test 39: Transfer journal records recent transfers in a hash-chained ring buffer", async () => {
+        const journalMint = Keypair.generate();
+        const journalConfig = await initShareToken(journalMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const transferJournal = pda(Buffer.from("transfer_journal"), journalMint.publicKey.toBuffer());
+
+        const holders = [aliceKeypair, bobKeypair];
+        const entries: PublicKey[] = [];
+        const tokenAccounts: PublicKey[] = [];
+        for (const holder of holders) {
+            const entry = pda(Buffer.from("allowlist"), journalMint.publicKey.toBuffer(), holder.publicKey.toBuffer());
+            await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet: holder.publicKey,
+                    tokenConfig: journalConfig,
+                    allowlistEntry: entry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            entries.push(entry);
+            tokenAccounts.push(await createAccount(provider.connection, holder, journalMint.publicKey, holder.publicKey));
+        }
+        await program.methods
+            .mintTokens(new anchor.BN(100))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: aliceKeypair.publicKey,
+                tokenConfig: journalConfig,
+                mint: journalMint.publicKey,
+                mintAuthority: pda(Buffer.from("mint_authority"), journalMint.publicKey.toBuffer()),
+                recipientTokenAccount: tokenAccounts[0],
+                recipientAllowlistEntry: entries[0],
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                holderIndex: null,
+                recipientHolderNumber: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        await program.methods
+            .initializeTransferJournal()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: journalConfig,
+                transferJournal,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        await program.methods
+            .setTransferJournalEnabled(true)
+            .accounts({ authority: authority.publicKey, tokenConfig: journalConfig })
+            .rpc();
+
+        const transfer = (amount: number, journal: PublicKey | null) =>
+            program.methods
+                .gatedTransfer(new anchor.BN(amount), null)
+                .accounts({
+                    authority: aliceKeypair.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig: journalConfig,
+                    mint: journalMint.publicKey,
+                    fromTokenAccount: tokenAccounts[0],
+                    toTokenAccount: tokenAccounts[1],
+                    senderAllowlistEntry: entries[0],
+                    recipientAllowlistEntry: entries[1],
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
+                    holderIndex: null,
+                    senderHolderNumber: null,
+                    lastHolderNumber: null,
+                    recipientHolderNumber: null,
+                    transferJournal: journal,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([aliceKeypair])
+                .rpc();
+
+        try {
+            await transfer(10, null);
+            assert.fail("Should have failed - journal omitted");
+        } catch (error) {
+            assert.include(error.toString(), "TransferJournalRequired");
+            console.log("✓ Journaled token refuses transfers that skip the journal");
+        }
+
+        await transfer(10, transferJournal);
+        await transfer(20, transferJournal);
+        const journal = await program.account.transferJournal.fetch(transferJournal);
+        assert.equal(journal.total.toNumber(), 2);
+        assert.isTrue(journal.entries[0].from.equals(aliceKeypair.publicKey));
+        assert.isTrue(journal.entries[1].to.equals(bobKeypair.publicKey));
+        assert.equal(journal.entries[1].amount.toNumber(), 20);
+
+        let digest = Buffer.alloc(32);
+        for (const entry of journal.entries.slice(0, 2)) {
+            digest = createHash("sha256")
+                .update(
+                    Buffer.concat([
+                        digest,
+                        entry.from.toBuffer(),
+                        entry.to.toBuffer(),
+                        entry.amount.toArrayLike(Buffer, "le", 8),
+                        entry.slot.toArrayLike(Buffer, "le", 8),
+                    ])
+                )
+                .digest();
+        }
+        assert.deepEqual(Buffer.from(journal.digest), digest);
+        console.log("✓ Journal digest chains every recorded transfer");
+    });
+
+    it("Test 40: Token events carry a gap-free per-mint sequence number", async () => {
+        const seqMint = Keypair.generate();
+        const seqConfig = await initShareToken(seqMint, 0);
+        const before = await program.account.tokenConfig.fetch(seqConfig);
+
+        const approve = async (wallet: PublicKey) => {
+            const signature = await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet,
+                    tokenConfig: seqConfig,
+                    allowlistEntry: PublicKey.findProgramAddressSync(
+                        [Buffer.from("allowlist"), seqMint.publicKey.toBuffer(), wallet.toBuffer()],
+                        program.programId
+                    )[0],
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc({ commitment: "confirmed" });
+            const tx = await provider.connection.getTransaction(signature, {
+                commitment: "confirmed",
+                maxSupportedTransactionVersion: 0,
+            });
+            const parser = new anchor.EventParser(program.programId, program.coder);
+            return [...parser.parseLogs(tx.meta.logMessages)];
+        };
+
+        const events = [...(await approve(aliceKeypair.publicKey)), ...(await approve(bobKeypair.publicKey))];
+        const start = before.eventSequence.toNumber();
+        assert.deepEqual(
+            events.map((event) => event.data.sequence.toNumber()),
+            events.map((_, i) => start + i)
+        );
+        assert.isTrue(events.every((event) => event.data.schemaVersion === 2));
+
+        const after = await program.account.tokenConfig.fetch(seqConfig);
+        assert.equal(after.eventSequence.toNumber(), start + events.length);
+        console.log("✓ Consecutive events on a mint are numbered without gaps");
+    });
+
+    it("Test 41: Config account fits max-length strings exactly and tracks their size", async () => {
+        const sizedMint = Keypair.generate();
+        const symbol = "MAXSYMBOLZ";
+        const name = "N".repeat(50);
+        const uri = "https://example.com/" + "u".repeat(180);
+        const sizedConfig = await initShareToken(sizedMint, 0, symbol, name, uri);
+
+        const config = await program.account.tokenConfig.fetch(sizedConfig);
+        assert.equal(config.symbol, symbol);
+        assert.equal(config.name, name);
+        assert.equal(config.uri, uri);
+        const full = await provider.connection.getAccountInfo(sizedConfig);
+        console.log(`✓ Max-length strings round-trip through a ${full.data.length} byte config`);
+
+        const update = (newSymbol: string, newName: string, newUri: string) =>
+            program.methods
+                .updateTokenMetadata(newSymbol, newName, newUri)
+                .accounts({
+                    authority: authority.publicKey,
+                    tokenConfig: sizedConfig,
+                    metadata: null,
+                    tokenMetadataProgram: null,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+
+        await update("MIN", "Nm", "");
+        const shrunk = await provider.connection.getAccountInfo(sizedConfig);
+        assert.equal(full.data.length - shrunk.data.length, 7 + 48 + uri.length);
+        assert.isBelow(shrunk.lamports, full.lamports);
+
+        await update(symbol, name, uri);
+        const restored = await program.account.tokenConfig.fetch(sizedConfig);
+        assert.equal(restored.symbol, symbol);
+        assert.equal(restored.name, name);
+        assert.equal(restored.uri, uri);
+        assert.equal((await provider.connection.getAccountInfo(sizedConfig)).data.length, full.data.length);
+        console.log("✓ Metadata updates shrink and regrow the account to fit");
+
+        await program.methods
+            .resizeTokenConfig()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: sizedConfig,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        assert.equal((await provider.connection.getAccountInfo(sizedConfig)).data.length, full.data.length);
+        console.log("✓ Resizing an exactly sized config leaves it unchanged");
+    });
+
+    it("Test 42: Restricted tokens check who started a CPI into sensitive instructions", async () => {
+        const guardedMint = Keypair.generate();
+        const guardedConfig = await initShareToken(guardedMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const entry = pda(Buffer.from("allowlist"), guardedMint.publicKey.toBuffer(), aliceKeypair.publicKey.toBuffer());
+        await program.methods
+            .approveWallet()
+            .accounts({
+                authority: authority.publicKey,
+                wallet: aliceKeypair.publicKey,
+                tokenConfig: guardedConfig,
+                allowlistEntry: entry,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const tokenAccount = await createAccount(provider.connection, aliceKeypair, guardedMint.publicKey, aliceKeypair.publicKey);
+
+        await program.methods
+            .setRestrictCpiCallers(true)
+            .accounts({ authority: authority.publicKey, tokenConfig: guardedConfig })
+            .rpc();
+
+        const trustedProgram = Keypair.generate().publicKey;
+        const trustedCaller = pda(
+            Buffer.from("trusted_caller"),
+            guardedMint.publicKey.toBuffer(),
+            trustedProgram.toBuffer()
+        );
+        await program.methods
+            .addTrustedCaller(trustedProgram)
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: guardedConfig,
+                trustedCaller,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const trusted = await program.account.trustedCaller.fetch(trustedCaller);
+        assert.isTrue(trusted.programId.equals(trustedProgram));
+
+        const mint = (instructions: PublicKey | null) =>
+            program.methods
+                .mintTokens(new anchor.BN(10))
+                .accounts({
+                    authority: authority.publicKey,
+                    recipient: aliceKeypair.publicKey,
+                    tokenConfig: guardedConfig,
+                    mint: guardedMint.publicKey,
+                    mintAuthority: pda(Buffer.from("mint_authority"), guardedMint.publicKey.toBuffer()),
+                    recipientTokenAccount: tokenAccount,
+                    recipientAllowlistEntry: entry,
+                    recipientCountryRule: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    recipientSnapshotBalance: null,
+                    recipientHolderPage: null,
+                    holderIndex: null,
+                    recipientHolderNumber: null,
+                    instructions,
+                    trustedCaller: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+
+        try {
+            await mint(null);
+            assert.fail("Should have failed - instructions sysvar omitted");
+        } catch (error) {
+            assert.include(error.toString(), "InstructionsSysvarRequired");
+            console.log("✓ Restricted token needs the instructions sysvar to identify the caller");
+        }
+
+        // A top-level call names this program as the caller, so it needs no trusted caller
+        await mint(anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY);
+        const balance = await getAccount(provider.connection, tokenAccount);
+        assert.equal(balance.amount.toString(), "10");
+        console.log("✓ Direct calls pass the CPI caller check");
+
+        await program.methods
+            .removeTrustedCaller()
+            .accounts({ authority: authority.publicKey, tokenConfig: guardedConfig, trustedCaller })
+            .rpc();
+        assert.isNull(await provider.connection.getAccountInfo(trustedCaller));
+        console.log("✓ Trusted caller removed");
+    });
+
+    it("Test 43: Feature flags switch transfers off and finalize minting for good", async () => {
+        const flaggedMint = Keypair.generate();
+        const flaggedConfig = await initShareToken(flaggedMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const TRANSFERS_ENABLED = 1 << 0;
+        const MINTING_FINALIZED = 1 << 1;
+
+        let config = await program.account.tokenConfig.fetch(flaggedConfig);
+        assert.equal(config.features.bits, TRANSFERS_ENABLED);
+
+        const holders = [aliceKeypair, bobKeypair];
+        const entries: PublicKey[] = [];
+        const tokenAccounts: PublicKey[] = [];
+        for (const holder of holders) {
+            const entry = pda(Buffer.from("allowlist"), flaggedMint.publicKey.toBuffer(), holder.publicKey.toBuffer());
+            await program.methods
+                .approveWallet()
+                .accounts({
+                    authority: authority.publicKey,
+                    wallet: holder.publicKey,
+                    tokenConfig: flaggedConfig,
+                    allowlistEntry: entry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+            entries.push(entry);
+            tokenAccounts.push(await createAccount(provider.connection, holder, flaggedMint.publicKey, holder.publicKey));
+        }
+
+        const mint = () =>
+            program.methods
+                .mintTokens(new anchor.BN(100))
+                .accounts({
+                    authority: authority.publicKey,
+                    recipient: aliceKeypair.publicKey,
+                    tokenConfig: flaggedConfig,
+                    mint: flaggedMint.publicKey,
+                    mintAuthority: pda(Buffer.from("mint_authority"), flaggedMint.publicKey.toBuffer()),
+                    recipientTokenAccount: tokenAccounts[0],
+                    recipientAllowlistEntry: entries[0],
+                    recipientCountryRule: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    recipientSnapshotBalance: null,
+                    recipientHolderPage: null,
+                    holderIndex: null,
+                    recipientHolderNumber: null,
+                    instructions: null,
+                    trustedCaller: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        const transfer = () =>
+            program.methods
+                .gatedTransfer(new anchor.BN(10), null)
+                .accounts({
+                    authority: aliceKeypair.publicKey,
+                    recipient: bobKeypair.publicKey,
+                    tokenConfig: flaggedConfig,
+                    mint: flaggedMint.publicKey,
+                    fromTokenAccount: tokenAccounts[0],
+                    toTokenAccount: tokenAccounts[1],
+                    senderAllowlistEntry: entries[0],
+                    recipientAllowlistEntry: entries[1],
+                    destinationRegistry: null,
+                    feeTreasury: null,
+                    velocityCounter: null,
+                    senderCountryRule: null,
+                    recipientCountryRule: null,
+                    senderSanctionsFlag: null,
+                    recipientSanctionsFlag: null,
+                    shareClass: null,
+                    senderSnapshotBalance: null,
+                    recipientSnapshotBalance: null,
+                    senderHolderPage: null,
+                    recipientHolderPage: null,
+                    saleProposal: null,
+                    holderIndex: null,
+                    senderHolderNumber: null,
+                    lastHolderNumber: null,
+                    recipientHolderNumber: null,
+                    transferJournal: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([aliceKeypair])
+                .rpc();
+        const setFeatures = (bits: number) =>
+            program.methods
+                .setFeatures({ bits })
+                .accounts({ authority: authority.publicKey, tokenConfig: flaggedConfig })
+                .rpc();
+
+        await mint();
+        await setFeatures(0);
+        try {
+            await transfer();
+            assert.fail("Should have failed - transfers disabled");
+        } catch (error) {
+            assert.include(error.toString(), "TransfersDisabled");
+            console.log("✓ Transfers refused while the transfers flag is off");
+        }
+
+        await setFeatures(TRANSFERS_ENABLED | MINTING_FINALIZED);
+        await transfer();
+        try {
+            await mint();
+            assert.fail("Should have failed - minting finalized");
+        } catch (error) {
+            assert.include(error.toString(), "MintingFinalized");
+            console.log("✓ Finalized token refuses to mint");
+        }
+
+        try {
+            await setFeatures(TRANSFERS_ENABLED);
+            assert.fail("Should have failed - finalization cleared");
+        } catch (error) {
+            assert.include(error.toString(), "MintingFinalized");
+            console.log("✓ Minting finalization cannot be undone");
+        }
+
+        try {
+            await setFeatures(TRANSFERS_ENABLED | MINTING_FINALIZED | (1 << 8));
+            assert.fail("Should have failed - unknown bit");
+        } catch (error) {
+            assert.include(error.toString(), "UnknownFeatureFlags");
+            console.log("✓ Unknown feature bits rejected");
+        }
+
+        config = await program.account.tokenConfig.fetch(flaggedConfig);
+        assert.equal(config.features.bits, TRANSFERS_ENABLED | MINTING_FINALIZED);
+    });
+
+    it("Test 44: Program config charges creation fees and pauses the whole deployment", async () => {
+        const feeRecipient = Keypair.generate().publicKey;
+        const fee = 5_000_000;
+        await program.methods
+            .updateProgramConfig({ ...defaultParameters(), feeRecipient, tokenCreationFee: new anchor.BN(fee) })
+            .accounts({ admin: authority.publicKey, programConfig })
+            .rpc();
+
+        const pda = (shareMint: Keypair, seed: string) =>
+            PublicKey.findProgramAddressSync([Buffer.from(seed), shareMint.publicKey.toBuffer()], program.programId)[0];
+        const initialize = async (shareMint: Keypair, recipient: PublicKey | null) => {
+            const registry = await program.account.tokenRegistry.fetch(tokenRegistry);
+            const pageIndex = Buffer.alloc(4);
+            pageIndex.writeUInt32LE(Math.floor(registry.totalTokens.toNumber() / 64));
+            const [registryPage] = PublicKey.findProgramAddressSync(
+                [Buffer.from("registry_page"), pageIndex],
+                program.programId
+            );
+            await program.methods
+                .initializeToken("FEEX", "Fee Paying Shares", 0, null, "")
+                .accounts({
+                    authority: authority.publicKey,
+                    mint: shareMint.publicKey,
+                    mintAuthority: pda(shareMint, "mint_authority"),
+                    freezeAuthority: pda(shareMint, "freeze_authority"),
+                    tokenConfig: pda(shareMint, "token_config"),
+                    tokenRegistry,
+                    programConfig,
+                    feeRecipient: recipient,
+                    registryPage,
+                    metadata: null,
+                    tokenMetadataProgram: null,
+                    transferHookProgram: null,
+                    extraAccountMetaList: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                    rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+                })
+                .signers([shareMint])
+                .rpc();
+        };
+
+        try {
+            await initialize(Keypair.generate(), null);
+            assert.fail("Should have failed - fee recipient omitted");
+        } catch (error) {
+            assert.include(error.toString(), "FeeRecipientRequired");
+            console.log("✓ Token creation needs the fee recipient while a fee is charged");
+        }
+        await initialize(Keypair.generate(), feeRecipient);
+        assert.equal(await provider.connection.getBalance(feeRecipient), fee);
+        console.log("✓ Creation fee paid to the protocol fee recipient");
+
+        await program.methods
+            .setProgramPaused(true)
+            .accounts({ admin: authority.publicKey, programConfig })
+            .rpc();
+        try {
+            await initialize(Keypair.generate(), feeRecipient);
+            assert.fail("Should have failed - program paused");
+        } catch (error) {
+            assert.include(error.toString(), "ProgramPaused");
+            console.log("✓ Paused deployment refuses new tokens");
+        }
+
+        const outsider = Keypair.generate();
+        try {
+            await program.methods
+                .setProgramPaused(false)
+                .accounts({ admin: outsider.publicKey, programConfig })
+                .signers([outsider])
+                .rpc();
+            assert.fail("Should have failed - not the admin");
+        } catch (error) {
+            assert.include(error.toString(), "UnauthorizedProgramAdmin");
+            console.log("✓ Only the protocol admin may unpause");
+        }
+
+        await program.methods
+            .setProgramPaused(false)
+            .accounts({ admin: authority.publicKey, programConfig })
+            .rpc();
+        await program.methods
+            .updateProgramConfig(defaultParameters())
+            .accounts({ admin: authority.publicKey, programConfig })
+            .rpc();
+        const config = await program.account.programConfig.fetch(programConfig);
+        assert.isFalse(config.paused);
+        assert.equal(config.parameters.tokenCreationFee.toNumber(), 0);
+    });
+
+    it("Test 45: ERC-3643 claims from trusted issuers approve identities and lapse with them", async () => {
+        const identityMint = Keypair.generate();
+        const identityConfig = await initShareToken(identityMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const KYC = new anchor.BN(1);
+        const ACCREDITED = new anchor.BN(7);
+        const claimTopics = pda(Buffer.from("claim_topics"), identityMint.publicKey.toBuffer());
+        const identity = pda(Buffer.from("identity"), identityMint.publicKey.toBuffer(), charlieKeypair.publicKey.toBuffer());
+        const entry = pda(Buffer.from("allowlist"), identityMint.publicKey.toBuffer(), charlieKeypair.publicKey.toBuffer());
+
+        await program.methods
+            .setClaimTopics([KYC, ACCREDITED], [{ issuer: aliceKeypair.publicKey, topics: [KYC, ACCREDITED] }])
+            .accounts({
+                complianceOfficer: authority.publicKey,
+                tokenConfig: identityConfig,
+                claimTopics,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const addClaim = (issuer: Keypair, topic: anchor.BN) =>
+            program.methods
+                .addClaim(topic, Array(32).fill(topic.toNumber()), null)
+                .accounts({
+                    issuer: issuer.publicKey,
+                    wallet: charlieKeypair.publicKey,
+                    tokenConfig: identityConfig,
+                    claimTopics,
+                    identity,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([issuer])
+                .rpc();
+        const verify = () =>
+            program.methods
+                .verifyIdentity()
+                .accounts({
+                    payer: charlieKeypair.publicKey,
+                    tokenConfig: identityConfig,
+                    claimTopics,
+                    identity,
+                    allowlistEntry: entry,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([charlieKeypair])
+                .rpc();
+        const revokeUnverified = () =>
+            program.methods
+                .revokeUnverified()
+                .accounts({
+                    tokenConfig: identityConfig,
+                    claimTopics,
+                    identity,
+                    allowlistEntry: entry,
+                    countryRule: null,
+                })
+                .rpc();
+
+        try {
+            await addClaim(bobKeypair, KYC);
+            assert.fail("Should have failed - untrusted issuer");
+        } catch (error) {
+            assert.include(error.toString(), "UntrustedClaimIssuer");
+            console.log("✓ Only trusted issuers add claims");
+        }
+
+        await addClaim(aliceKeypair, KYC);
+        try {
+            await verify();
+            assert.fail("Should have failed - accreditation claim missing");
+        } catch (error) {
+            assert.include(error.toString(), "IdentityNotVerified");
+            console.log("✓ Identity missing a required topic does not verify");
+        }
+
+        await addClaim(aliceKeypair, ACCREDITED);
+        await verify();
+        let allowlist = await program.account.allowlistEntry.fetch(entry);
+        assert.isTrue(allowlist.isApproved);
+        console.log("✓ Verified identity approves its wallet without the authority");
+
+        try {
+            await revokeUnverified();
+            assert.fail("Should have failed - still verified");
+        } catch (error) {
+            assert.include(error.toString(), "IdentityStillVerified");
+        }
+
+        await program.methods
+            .removeClaim(ACCREDITED, aliceKeypair.publicKey)
+            .accounts({ signer: authority.publicKey, tokenConfig: identityConfig, identity })
+            .rpc();
+        await revokeUnverified();
+        allowlist = await program.account.allowlistEntry.fetch(entry);
+        assert.isFalse(allowlist.isApproved);
+        console.log("✓ Anyone can revoke a wallet whose claims lapsed");
+
+        await addClaim(aliceKeypair, ACCREDITED);
+        await verify();
+        allowlist = await program.account.allowlistEntry.fetch(entry);
+        assert.isTrue(allowlist.isApproved);
+        const held = await program.account.identity.fetch(identity);
+        assert.equal(held.claims.length, 2);
+        console.log("✓ Renewed claims re-approve a lapsed wallet");
+    });
+
+    it("Test 46: Bridged shares only leave approved wallets for another chain", async () => {
+        const bridgedMint = Keypair.generate();
+        const bridgedConfig = await initShareToken(bridgedMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const bridgeConfig = pda(Buffer.from("bridge_config"), bridgedMint.publicKey.toBuffer());
+        const bridgeVault = pda(Buffer.from("bridge_vault"), bridgedMint.publicKey.toBuffer());
+        const charlieEntry = pda(Buffer.from("allowlist"), bridgedMint.publicKey.toBuffer(), charlieKeypair.publicKey.toBuffer());
+        const tokenBridge = Keypair.generate().publicKey;
+        const wormhole = Keypair.generate().publicKey;
+
+        const configure = (enabled: boolean) =>
+            program.methods
+                .configureBridge(tokenBridge, wormhole, enabled)
+                .accounts({
+                    authority: authority.publicKey,
+                    tokenConfig: bridgedConfig,
+                    mint: bridgedMint.publicKey,
+                    bridgeConfig,
+                    bridgeVault,
+                    timelock: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .rpc();
+        await configure(true);
+        const route = await program.account.bridgeConfig.fetch(bridgeConfig);
+        assert.isTrue(route.tokenBridgeProgram.equals(tokenBridge));
+        assert.isTrue(route.enabled);
+
+        await program.methods
+            .approveWallet()
+            .accounts({
+                authority: authority.publicKey,
+                wallet: charlieKeypair.publicKey,
+                tokenConfig: bridgedConfig,
+                allowlistEntry: charlieEntry,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const charlieAccount = await createAccount(
+            provider.connection,
+            charlieKeypair,
+            bridgedMint.publicKey,
+            charlieKeypair.publicKey
+        );
+        const bridgeOut = (targetChain: number) =>
+            program.methods
+                .bridgeOut(new anchor.BN(1), targetChain, Array(32).fill(7), 0)
+                .accounts({
+                    holder: charlieKeypair.publicKey,
+                    tokenConfig: bridgedConfig,
+                    mint: bridgedMint.publicKey,
+                    bridgeConfig,
+                    tokenBridgeProgram: tokenBridge,
+                    holderTokenAccount: charlieAccount,
+                    holderAllowlistEntry: charlieEntry,
+                    holderCountryRule: null,
+                    holderSanctionsFlag: null,
+                    velocityCounter: null,
+                    saleProposal: null,
+                    holderSnapshotBalance: null,
+                    holderHolderPage: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([charlieKeypair])
+                .rpc();
+
+        try {
+            await bridgeOut(1);
+            assert.fail("Should have failed - Solana is not another chain");
+        } catch (error) {
+            assert.include(error.toString(), "InvalidBridgeTarget");
+            console.log("✓ Bridging back to Solana is rejected");
+        }
+
+        await configure(false);
+        try {
+            await bridgeOut(2);
+            assert.fail("Should have failed - bridge disabled");
+        } catch (error) {
+            assert.include(error.toString(), "BridgeDisabled");
+            console.log("✓ Disabled bridge route blocks outbound transfers");
+        }
+    });
+
+    it("Test 47: NAV redemptions pay the oracle price and record the reading", async () => {
+        const navMint = Keypair.generate();
+        const navConfig = await initShareToken(navMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const redemptionConfig = pda(Buffer.from("redemption"), navMint.publicKey.toBuffer());
+        const redemptionVault = pda(Buffer.from("redemption_vault"), navMint.publicKey.toBuffer());
+        const charlieEntry = pda(Buffer.from("allowlist"), navMint.publicKey.toBuffer(), charlieKeypair.publicKey.toBuffer());
+        const usdcMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+        const fundingAccount = await createAccount(provider.connection, authority, usdcMint, authority.publicKey);
+        await mintTo(provider.connection, authority, usdcMint, fundingAccount, authority, 100_000_000);
+        const charlieUsdc = await createAccount(provider.connection, charlieKeypair, usdcMint, charlieKeypair.publicKey);
+        // $12.50 per share
+        const priceFeed = await createPriceFeed(authority, 12_500_000, -6, 10_000);
+
+        await program.methods
+            .initializeRedemption()
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: navConfig,
+                mint: navMint.publicKey,
+                paymentMint: usdcMint,
+                redemptionConfig,
+                redemptionVault,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const openNavWindow = () =>
+            program.methods
+                .openNavRedemptionWindow(new anchor.BN(Math.floor(Date.now() / 1000) + 3_600))
+                .accounts({ authority: authority.publicKey, tokenConfig: navConfig, redemptionConfig })
+                .rpc();
+        try {
+            await openNavWindow();
+            assert.fail("Should have failed - no price oracle");
+        } catch (error) {
+            assert.include(error.toString(), "PriceOracleNotSet");
+        }
+
+        await program.methods
+            .setPriceOracle(mockOracleProgram().programId, priceFeed, new anchor.BN(3_600))
+            .accounts({ authority: authority.publicKey, tokenConfig: navConfig, timelock: null })
+            .rpc();
+        await program.methods
+            .fundRedemptionVault(new anchor.BN(100_000_000))
+            .accounts({
+                authority: authority.publicKey,
+                tokenConfig: navConfig,
+                redemptionConfig,
+                paymentMint: usdcMint,
+                redemptionVault,
+                fundingTokenAccount: fundingAccount,
+                tokenProgram: TOKEN_PROGRAM_ID,
+            })
+            .rpc();
+        await openNavWindow();
+
+        await program.methods
+            .approveWallet()
+            .accounts({
+                authority: authority.publicKey,
+                wallet: charlieKeypair.publicKey,
+                tokenConfig: navConfig,
+                allowlistEntry: charlieEntry,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const charlieAccount = await createAccount(
+            provider.connection,
+            charlieKeypair,
+            navMint.publicKey,
+            charlieKeypair.publicKey
+        );
+        await program.methods
+            .mintTokens(new anchor.BN(10))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: charlieKeypair.publicKey,
+                tokenConfig: navConfig,
+                mint: navMint.publicKey,
+                mintAuthority: pda(Buffer.from("mint_authority"), navMint.publicKey.toBuffer()),
+                recipientTokenAccount: charlieAccount,
+                recipientAllowlistEntry: charlieEntry,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const redeemAtNav = (minPayout: number) =>
+            program.methods
+                .redeemAtNav(new anchor.BN(4), new anchor.BN(minPayout))
+                .accounts({
+                    holder: charlieKeypair.publicKey,
+                    tokenConfig: navConfig,
+                    mint: navMint.publicKey,
+                    priceFeed,
+                    holderTokenAccount: charlieAccount,
+                    holderAllowlistEntry: charlieEntry,
+                    redemptionConfig,
+                    paymentMint: usdcMint,
+                    redemptionVault,
+                    holderPaymentAccount: charlieUsdc,
+                    holderSnapshotBalance: null,
+                    holderPage: null,
+                    tokenProgram: TOKEN_PROGRAM_ID,
+                    systemProgram: SystemProgram.programId,
+                })
+                .signers([charlieKeypair])
+                .rpc();
+        try {
+            await redeemAtNav(50_000_001);
+            assert.fail("Should have failed - payout below the minimum");
+        } catch (error) {
+            assert.include(error.toString(), "SlippageExceeded");
+            console.log("✓ Redemption below the holder's minimum payout rejected");
+        }
+
+        await redeemAtNav(50_000_000);
+        const paid = await getAccount(provider.connection, charlieUsdc);
+        assert.equal(paid.amount.toString(), "50000000");
+        const shares = await getAccount(provider.connection, charlieAccount);
+        assert.equal(shares.amount.toString(), "6");
+        console.log("✓ Four shares redeemed at a $12.50 NAV");
+    });
+
+    it("Test 48: Holders get a compressed share certificate that is burned when they exit", async () => {
+        const certMint = Keypair.generate();
+        const certConfig = await initShareToken(certMint, 0);
+        const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+        const bubblegum = new PublicKey("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+        const certificateTree = pda(Buffer.from("certificate_tree"), certMint.publicKey.toBuffer());
+        const shareCertificate = pda(
+            Buffer.from("share_certificate"),
+            certMint.publicKey.toBuffer(),
+            charlieKeypair.publicKey.toBuffer()
+        );
+        const charlieEntry = pda(Buffer.from("allowlist"), certMint.publicKey.toBuffer(), charlieKeypair.publicKey.toBuffer());
+        const merkleTree = Keypair.generate();
+        const depthSizePair = { maxDepth: 3, maxBufferSize: 8 };
+        const allocTree = await createAllocTreeIx(
+            provider.connection,
+            merkleTree.publicKey,
+            authority.publicKey,
+            depthSizePair,
+            0
+        );
+        await provider.sendAndConfirm(new anchor.web3.Transaction().add(allocTree), [merkleTree]);
+        const [treeConfig] = PublicKey.findProgramAddressSync([merkleTree.publicKey.toBuffer()], bubblegum);
+        const bubblegumAccounts = {
+            treeConfig,
+            merkleTree: merkleTree.publicKey,
+            bubblegumProgram: bubblegum,
+            compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            noopProgram: SPL_NOOP_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+        };
+
+        const legend = "These shares have not been registered under the Securities Act of 1933.";
+        await program.methods
+            .initCertificateTree(depthSizePair.maxDepth, depthSizePair.maxBufferSize, legend, "https://ir.example.com/certificates")
+            .accounts({ authority: authority.publicKey, tokenConfig: certConfig, certificateTree, ...bubblegumAccounts })
+            .rpc();
+
+        await program.methods
+            .approveWallet()
+            .accounts({
+                authority: authority.publicKey,
+                wallet: charlieKeypair.publicKey,
+                tokenConfig: certConfig,
+                allowlistEntry: charlieEntry,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+        const charlieAccount = await createAccount(
+            provider.connection,
+            charlieKeypair,
+            certMint.publicKey,
+            charlieKeypair.publicKey
+        );
+        await program.methods
+            .bindTokenAccount(charlieAccount)
+            .accounts({ authority: authority.publicKey, tokenConfig: certConfig, allowlistEntry: charlieEntry })
+            .rpc();
+        await program.methods
+            .mintTokens(new anchor.BN(5))
+            .accounts({
+                authority: authority.publicKey,
+                recipient: charlieKeypair.publicKey,
+                tokenConfig: certConfig,
+                mint: certMint.publicKey,
+                mintAuthority: pda(Buffer.from("mint_authority"), certMint.publicKey.toBuffer()),
+                recipientTokenAccount: charlieAccount,
+                recipientAllowlistEntry: charlieEntry,
+                recipientCountryRule: null,
+                recipientSanctionsFlag: null,
+                shareClass: null,
+                recipientSnapshotBalance: null,
+                recipientHolderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+
+        const holderAccounts = {
+            allowlistEntry: charlieEntry,
+            holder: charlieKeypair.publicKey,
+            tokenAccount: charlieAccount,
+            certificateTree,
+            shareCertificate,
+            ...bubblegumAccounts,
+        };
+        const issue = () =>
+            program.methods
+                .issueShareCertificate()
+                .accounts({ payer: authority.publicKey, tokenConfig: certConfig, shareClass: null, ...holderAccounts })
+                .rpc();
+        try {
+            await issue();
+            assert.fail("Should have failed - certificates not enabled");
+        } catch (error) {
+            assert.include(error.toString(), "ShareCertificatesDisabled");
+            console.log("✓ Certificates wait for the feature flag");
+        }
+
+        const TRANSFERS_ENABLED = 1 << 0;
+        const SHARE_CERTIFICATES = 1 << 3;
+        await program.methods
+            .setFeatures({ bits: TRANSFERS_ENABLED | SHARE_CERTIFICATES })
+            .accounts({ authority: authority.publicKey, tokenConfig: certConfig })
+            .rpc();
+        await issue();
+        const certificate = await program.account.shareCertificate.fetch(shareCertificate);
+        const nonce = Buffer.alloc(8);
+        const [assetId] = PublicKey.findProgramAddressSync(
+            [Buffer.from("asset"), merkleTree.publicKey.toBuffer(), nonce],
+            bubblegum
+        );
+        assert.isTrue(certificate.assetId.equals(assetId));
+        assert.equal(certificate.class, "Common");
+        assert.equal(certificate.legend, legend);
+        assert.equal(certificate.uri, `https://ir.example.com/certificates/${charlieKeypair.publicKey.toBase58()}`);
+        console.log("✓ Certificate minted to the holder at issuance");
+
+        const retire = async () => {
+            const tree = await ConcurrentMerkleTreeAccount.fromAccountAddress(provider.connection, merkleTree.publicKey);
+            // Siblings of leaf 0 while it is the only leaf are empty subtrees
+            const proof = MerkleTree.sparseMerkleTreeFromLeaves([Buffer.alloc(32)], depthSizePair.maxDepth).getProof(0);
+            return program.methods
+                .retireShareCertificate(Array.from(tree.getCurrentRoot()))
+                .accounts({ tokenConfig: certConfig, rentPayer: authority.publicKey, ...holderAccounts })
+                .remainingAccounts(proof.proof.map((node) => ({ pubkey: new PublicKey(node), isSigner: false, isWritable: false })))
+                .rpc();
+        };
+        try {
+            await retire();
+            assert.fail("Should have failed - holder still has shares");
+        } catch (error) {
+            assert.include(error.toString(), "HolderNotExited");
+            console.log("✓ Certificate stays while the holder has shares");
+        }
+
+        await program.methods
+            .burnTokens(new anchor.BN(5))
+            .accounts({
+                holder: charlieKeypair.publicKey,
+                tokenConfig: certConfig,
+                mint: certMint.publicKey,
+                holderTokenAccount: charlieAccount,
+                holderAllowlistEntry: charlieEntry,
+                holderSnapshotBalance: null,
+                holderPage: null,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: SystemProgram.programId,
+            })
+            .signers([charlieKeypair])
+            .rpc();
+        await retire();
+        assert.isNull(await program.account.shareCertificate.fetchNullable(shareCertificate));
+        console.log("✓ Certificate burned once the holder exits");
+    });
+});
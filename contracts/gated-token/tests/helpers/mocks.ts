@@ -0,0 +1,161 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { MockOracle } from "../../target/types/mock_oracle";
+import { MockAttestation } from "../../target/types/mock_attestation";
+import { MockSanctionsOracle } from "../../target/types/mock_sanctions_oracle";
+import { PublicKey, Keypair, SystemProgram } from "@solana/web3.js";
+
+/**
+ * Harness helpers for the in-workspace mock programs.
+ * Lets NAV pricing and attestation gating be exercised on localnet
+ * without any external oracle or KYC provider.
+ */
+
+export function mockOracleProgram(): Program<MockOracle> {
+    return anchor.workspace.MockOracle as Program<MockOracle>;
+}
+
+export function mockAttestationProgram(): Program<MockAttestation> {
+    return anchor.workspace.MockAttestation as Program<MockAttestation>;
+}
+
+export function mockSanctionsOracleProgram(): Program<MockSanctionsOracle> {
+    return anchor.workspace.MockSanctionsOracle as Program<MockSanctionsOracle>;
+}
+
+/**
+ * Create a price feed. `price` is scaled by 10^expo (e.g. 1_250 with expo -2 = 12.50)
+ */
+export async function createPriceFeed(
+    authority: Keypair,
+    price: number,
+    expo: number = -6,
+    conf: number = 0
+): Promise<PublicKey> {
+    const program = mockOracleProgram();
+    const priceFeed = Keypair.generate();
+
+    await program.methods
+        .initializePriceFeed(new anchor.BN(price), new anchor.BN(conf), expo)
+        .accounts({
+            authority: authority.publicKey,
+            priceFeed: priceFeed.publicKey,
+            systemProgram: SystemProgram.programId,
+        })
+        .signers([authority, priceFeed])
+        .rpc();
+
+    return priceFeed.publicKey;
+}
+
+export async function setPrice(
+    authority: Keypair,
+    priceFeed: PublicKey,
+    price: number,
+    conf: number = 0
+): Promise<void> {
+    await mockOracleProgram().methods
+        .setPrice(new anchor.BN(price), new anchor.BN(conf))
+        .accounts({ authority: authority.publicKey, priceFeed })
+        .signers([authority])
+        .rpc();
+}
+
+export async function makePriceStale(
+    authority: Keypair,
+    priceFeed: PublicKey,
+    publishTime: number
+): Promise<void> {
+    await mockOracleProgram().methods
+        .setPublishTime(new anchor.BN(publishTime))
+        .accounts({ authority: authority.publicKey, priceFeed })
+        .signers([authority])
+        .rpc();
+}
+
+export function attestationAddress(provider: PublicKey, subject: PublicKey): PublicKey {
+    const [attestation] = PublicKey.findProgramAddressSync(
+        [Buffer.from("attestation"), provider.toBuffer(), subject.toBuffer()],
+        mockAttestationProgram().programId
+    );
+    return attestation;
+}
+
+/**
+ * Issue an attestation for `subject`. Defaults to a clean, KYC level 1, US investor valid for one day.
+ */
+export async function issueAttestation(
+    provider: Keypair,
+    subject: PublicKey,
+    options: {
+        kycLevel?: number;
+        countryCode?: string;
+        isSanctioned?: boolean;
+        expiresAt?: number;
+    } = {}
+): Promise<PublicKey> {
+    const attestation = attestationAddress(provider.publicKey, subject);
+    const expiresAt = options.expiresAt ?? Math.floor(Date.now() / 1000) + 86_400;
+    const countryCode = Array.from(Buffer.from(options.countryCode ?? "US", "ascii"));
+
+    await mockAttestationProgram().methods
+        .issueAttestation(
+            options.kycLevel ?? 1,
+            countryCode,
+            options.isSanctioned ?? false,
+            new anchor.BN(expiresAt)
+        )
+        .accounts({
+            provider: provider.publicKey,
+            subject,
+            attestation,
+            systemProgram: SystemProgram.programId,
+        })
+        .signers([provider])
+        .rpc();
+
+    return attestation;
+}
+
+export async function revokeAttestation(provider: Keypair, subject: PublicKey): Promise<void> {
+    await mockAttestationProgram().methods
+        .revokeAttestation()
+        .accounts({
+            provider: provider.publicKey,
+            attestation: attestationAddress(provider.publicKey, subject),
+        })
+        .signers([provider])
+        .rpc();
+}
+
+export function sanctionsFlagAddress(wallet: PublicKey): PublicKey {
+    const [flag] = PublicKey.findProgramAddressSync(
+        [Buffer.from("sanctions_flag"), wallet.toBuffer()],
+        mockSanctionsOracleProgram().programId
+    );
+    return flag;
+}
+
+/**
+ * Flag (or clear) `wallet` in the mock sanctions oracle
+ */
+export async function setSanctionsFlag(
+    screener: Keypair,
+    wallet: PublicKey,
+    isSanctioned: boolean
+): Promise<PublicKey> {
+    const sanctionsFlag = sanctionsFlagAddress(wallet);
+
+    await mockSanctionsOracleProgram().methods
+        .setSanctionsFlag(isSanctioned)
+        .accounts({
+            screener: screener.publicKey,
+            wallet,
+            sanctionsFlag,
+            systemProgram: SystemProgram.programId,
+        })
+        .signers([screener])
+        .rpc();
+
+    return sanctionsFlag;
+}
@@ -0,0 +1,59 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Keypair } from "@solana/web3.js";
+import { assert } from "chai";
+import {
+    mockOracleProgram,
+    mockAttestationProgram,
+    mockSanctionsOracleProgram,
+    createPriceFeed,
+    setPrice,
+    issueAttestation,
+    revokeAttestation,
+    setSanctionsFlag,
+} from "./helpers/mocks";
+
+describe("mock programs", () => {
+    const provider = anchor.AnchorProvider.env();
+    anchor.setProvider(provider);
+
+    const authority = provider.wallet.payer;
+
+    it("Mock oracle publishes and updates prices", async () => {
+        const priceFeed = await createPriceFeed(authority, 12_500_000, -6);
+
+        await setPrice(authority, priceFeed, 13_000_000, 5_000);
+
+        const feed = await mockOracleProgram().account.priceFeed.fetch(priceFeed);
+        assert.equal(feed.price.toNumber(), 13_000_000);
+        assert.equal(feed.conf.toNumber(), 5_000);
+        assert.equal(feed.expo, -6);
+        console.log("✓ Mock price feed updated");
+    });
+
+    it("Mock attestation provider issues and revokes attestations", async () => {
+        const investor = Keypair.generate();
+
+        const attestation = await issueAttestation(authority, investor.publicKey, { countryCode: "DE" });
+        let record = await mockAttestationProgram().account.attestation.fetch(attestation);
+        assert.isFalse(record.isRevoked);
+        assert.equal(Buffer.from(record.countryCode).toString("ascii"), "DE");
+
+        await revokeAttestation(authority, investor.publicKey);
+        record = await mockAttestationProgram().account.attestation.fetch(attestation);
+        assert.isTrue(record.isRevoked);
+        console.log("✓ Mock attestation issued and revoked");
+    });
+
+    it("Mock sanctions oracle flags and clears wallets", async () => {
+        const investor = Keypair.generate();
+
+        const sanctionsFlag = await setSanctionsFlag(authority, investor.publicKey, true);
+        let record = await mockSanctionsOracleProgram().account.sanctionsFlag.fetch(sanctionsFlag);
+        assert.isTrue(record.isSanctioned);
+
+        await setSanctionsFlag(authority, investor.publicKey, false);
+        record = await mockSanctionsOracleProgram().account.sanctionsFlag.fetch(sanctionsFlag);
+        assert.isFalse(record.isSanctioned);
+        console.log("✓ Mock sanctions flag set and cleared");
+    });
+});